@@ -1,5 +1,10 @@
 import { SiteHeader } from "@/components/layout/site-header";
 import { QueryProvider } from "@/components/providers/query-provider";
+import { AutoProcessListener } from "@/components/providers/auto-process-listener";
+import { IntegrityScanListener } from "@/components/providers/integrity-scan-listener";
+import { LaunchImportListener } from "@/components/providers/launch-import-listener";
+import { ScheduledExportListener } from "@/components/providers/scheduled-export-listener";
+import { WatchedFolderListener } from "@/components/providers/watched-folder-listener";
 import { GeminiModelCatalogBootstrap } from "@/components/providers/gemini-model-catalog-bootstrap";
 import { ThemeProvider } from "@/components/theme/theme-provider";
 import { Toaster } from "@/components/ui/sonner";
@@ -38,6 +43,11 @@ export default function RootLayout({
         >
           <QueryProvider>
             <GeminiModelCatalogBootstrap />
+            <AutoProcessListener />
+            <IntegrityScanListener />
+            <LaunchImportListener />
+            <ScheduledExportListener />
+            <WatchedFolderListener />
             <SiteHeader />
             {children}
             <Toaster />
@@ -14,7 +14,7 @@ import type { FileRecord } from "@/lib/files";
 import { Loader2, MoreHorizontal } from "lucide-react";
 import { toast } from "sonner";
 import { useFileMutations } from "@/lib/hooks/use-files";
-import { saveFile } from "@/lib/filesystem";
+import { saveXmlExport } from "@/lib/xml";
 import { convertToInvoiceData, generateTallyXml } from "@/lib/xml/xml-generator";
 import { save } from "@tauri-apps/plugin-dialog";
 import { XmlSelectionDialog } from "../xml-selection-dialog";
@@ -97,8 +97,19 @@ export function FileActionMenu({
     }
     try {
       const fileIds = deleteTargets.map((file) => file.id);
-      await deleteFiles.mutateAsync(fileIds);
-      toast.success(`Deleted ${deleteTargets.length} files.`);
+      const results = await deleteFiles.mutateAsync(fileIds);
+      const deleted = results.filter(
+        (result) => result.outcome === "trashed" || result.outcome === "shredded",
+      ).length;
+      const failed = results.filter((result) => result.outcome === "failed");
+      if (deleted > 0) {
+        toast.success(`Deleted ${deleted} files.`);
+      }
+      if (failed.length > 0) {
+        toast.error(`Failed to delete ${failed.length} files.`, {
+          description: failed.map((result) => `${result.fileName}: ${result.error}`).join("\n"),
+        });
+      }
       onDeleteComplete?.();
       onProcessComplete?.(); // refresh list
     } catch (error) {
@@ -141,6 +152,16 @@ export function FileActionMenu({
       if (result.failedFiles > 0) {
         toast.error(`Failed to process ${result.failedFiles} files.`);
       }
+      if (result.conflictFiles > 0) {
+        toast.info(
+          `${result.conflictFiles} reprocessed files differ from their saved details and need review.`,
+        );
+      }
+      if (result.waitingFiles > 0) {
+        toast.info(
+          `You're offline. ${result.waitingFiles} files will be processed when the connection returns.`,
+        );
+      }
       onProcessComplete?.();
     } catch (error) {
       console.error("Processing error:", error);
@@ -209,7 +230,7 @@ export function FileActionMenu({
       });
 
       if (savePath) {
-        await saveFile(savePath, xmlContent);
+        await saveXmlExport(xmlId, savePath, xmlContent);
         toast.success(`Successfully exported to XML: ${savePath}`);
       }
     } catch (error) {
@@ -17,6 +17,8 @@ export const STATUS_OPTIONS = [
   { label: "Processing", value: FILE_STATUS.PROCESSING },
   { label: "Processed", value: FILE_STATUS.PROCESSED },
   { label: "Failed", value: FILE_STATUS.FAILED },
+  { label: "Waiting for network", value: FILE_STATUS.WAITING_FOR_NETWORK },
+  { label: "Needs review", value: FILE_STATUS.NEEDS_REVIEW },
 ] as const;
 
 type StatusBadgeVariant = NonNullable<React.ComponentProps<typeof Badge>["variant"]>;
@@ -27,6 +29,8 @@ const STATUS_VARIANT_MAP: Record<string, StatusBadgeVariant> = {
   [FILE_STATUS.PROCESSED]: "secondary",
   Completed: "secondary",
   [FILE_STATUS.FAILED]: "destructive",
+  [FILE_STATUS.WAITING_FOR_NETWORK]: "outline",
+  [FILE_STATUS.NEEDS_REVIEW]: "outline",
   Cancelled: "destructive",
 };
 
@@ -0,0 +1,72 @@
+"use client";
+
+import { useQueryClient } from "@tanstack/react-query";
+import { useEffect } from "react";
+
+import { FILE_STATUS } from "@/lib/constants";
+import { isTauriRuntime } from "@/lib/database";
+import { FileQueries } from "@/lib/files/queries";
+import { createLogger } from "@/lib/logger";
+
+/** Emitted by the backend scheduler and when files waiting for the network can resume. */
+const AUTO_PROCESS_EVENT = "scheduler://auto-process";
+const AUTO_PROCESS_BATCH_SIZE = 200;
+
+const autoProcessLogger = createLogger("AutoProcess");
+
+export function AutoProcessListener() {
+  const queryClient = useQueryClient();
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let running = false;
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    const processPending = async () => {
+      if (running) {
+        return;
+      }
+      running = true;
+      try {
+        const { files } = await FileQueries.listFiles({
+          statusFilter: FILE_STATUS.UNPROCESSED,
+          limit: AUTO_PROCESS_BATCH_SIZE,
+          offset: 0,
+        });
+        if (files.length > 0) {
+          const { processFiles } = await import("@/lib/files/file-processing");
+          await processFiles(files);
+          queryClient.invalidateQueries({ queryKey: ["files"] });
+        }
+      } catch (error) {
+        autoProcessLogger.warn("Automatic processing failed", { error });
+      } finally {
+        running = false;
+      }
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) => listen(AUTO_PROCESS_EVENT, () => void processPending()))
+      .then((stop) => {
+        if (cancelled) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        autoProcessLogger.warn("Failed to subscribe to auto-process events", { error });
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [queryClient]);
+
+  return null;
+}
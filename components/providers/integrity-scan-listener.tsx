@@ -0,0 +1,109 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect } from "react";
+import { toast } from "sonner";
+
+import { isTauriRuntime } from "@/lib/database";
+import { createLogger } from "@/lib/logger";
+
+/** Emitted by the backend once the startup integrity scan has finished. */
+const INTEGRITY_SCAN_EVENT = "integrity://scan-completed";
+/** A stored report older than this belongs to an earlier session or a manual scan. */
+const STARTUP_REPORT_MAX_AGE_MS = 5 * 60 * 1000;
+
+interface IntegrityIssue {
+  kind: "missing_file" | "stuck_processing" | "dangling_export_membership" | "stale_temp_file";
+  subject: string;
+  detail: string;
+  suggestion: string;
+  repaired: boolean;
+}
+
+interface IntegrityReport {
+  scannedAt: string;
+  issues: IntegrityIssue[];
+}
+
+const integrityLogger = createLogger("IntegrityScan");
+
+const showReport = (report: IntegrityReport) => {
+  if (report.issues.length === 0) {
+    return;
+  }
+  const repaired = report.issues.filter((issue) => issue.repaired).length;
+  const open = report.issues.length - repaired;
+  integrityLogger.info("Startup integrity scan found issues", { issues: report.issues });
+
+  const parts = [];
+  if (repaired > 0) {
+    parts.push(`${repaired} fixed automatically`);
+  }
+  if (open > 0) {
+    parts.push(`${open} need attention`);
+  }
+  const description = report.issues
+    .filter((issue) => !issue.repaired)
+    .slice(0, 3)
+    .map((issue) => `${issue.detail} ${issue.suggestion}`)
+    .join("\n");
+
+  const title = `Integrity check: ${parts.join(", ")}.`;
+  if (open > 0) {
+    toast.warning(title, { description, duration: 15000 });
+  } else {
+    toast.info(title);
+  }
+};
+
+export function IntegrityScanListener() {
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    let shown = false;
+
+    const show = (report: IntegrityReport | null) => {
+      if (report && !shown && !cancelled) {
+        shown = true;
+        showReport(report);
+      }
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) =>
+        listen<IntegrityReport>(INTEGRITY_SCAN_EVENT, (event) => show(event.payload)),
+      )
+      .then((stop) => {
+        if (cancelled) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        integrityLogger.warn("Failed to subscribe to integrity scan events", { error });
+      });
+
+    // The scan may have finished before this listener was attached.
+    invoke<IntegrityReport | null>("get_last_integrity_report")
+      .then((report) => {
+        if (report && Date.now() - Date.parse(report.scannedAt) < STARTUP_REPORT_MAX_AGE_MS) {
+          show(report);
+        }
+      })
+      .catch((error) => {
+        integrityLogger.warn("Failed to load the last integrity report", { error });
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  return null;
+}
@@ -0,0 +1,70 @@
+"use client";
+
+import { useQueryClient } from "@tanstack/react-query";
+import { useEffect } from "react";
+import { toast } from "sonner";
+
+import { isTauriRuntime } from "@/lib/database";
+import { createLogger } from "@/lib/logger";
+
+/** Emitted after documents opened with Invox (file association, invox:// link, second launch) are imported. */
+const LAUNCH_IMPORT_EVENT = "launch://files-imported";
+
+interface LaunchImportResult {
+  imported: string[];
+  duplicates: string[];
+  failed: string[];
+}
+
+const launchImportLogger = createLogger("LaunchImport");
+
+export function LaunchImportListener() {
+  const queryClient = useQueryClient();
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    const handle = ({ imported, duplicates, failed }: LaunchImportResult) => {
+      queryClient.invalidateQueries({ queryKey: ["files"] });
+      if (imported.length > 0) {
+        toast.success(`Imported ${imported.length} file${imported.length === 1 ? "" : "s"}.`);
+      }
+      if (duplicates.length > 0) {
+        toast.info(
+          `${duplicates.length} file${duplicates.length === 1 ? " is" : "s are"} already in the library.`,
+        );
+      }
+      if (failed.length > 0) {
+        launchImportLogger.warn("Failed to import opened files", { failed });
+        toast.error(`Could not import ${failed.length} file${failed.length === 1 ? "" : "s"}.`);
+      }
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) =>
+        listen<LaunchImportResult>(LAUNCH_IMPORT_EVENT, (event) => handle(event.payload)),
+      )
+      .then((stop) => {
+        if (cancelled) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        launchImportLogger.warn("Failed to subscribe to launch import events", { error });
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [queryClient]);
+
+  return null;
+}
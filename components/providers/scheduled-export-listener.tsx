@@ -0,0 +1,86 @@
+"use client";
+
+import { useQueryClient } from "@tanstack/react-query";
+import { useEffect } from "react";
+import { toast } from "sonner";
+
+import { isTauriRuntime } from "@/lib/database";
+import { createLogger } from "@/lib/logger";
+import { generateXmlFile, saveXmlExport } from "@/lib/xml";
+import { convertToInvoiceData, generateTallyXml } from "@/lib/xml/xml-generator";
+
+/** Emitted by the backend scheduler when a scheduled export includes Tally XML. */
+const SCHEDULED_XML_EVENT = "scheduler://xml-export";
+
+interface ScheduledXmlExport {
+  xmlId: number;
+  path: string;
+}
+
+const scheduledExportLogger = createLogger("ScheduledExport");
+
+const writeXml = async ({ xmlId, path }: ScheduledXmlExport) => {
+  const result = await generateXmlFile(xmlId);
+  const invoices = result.content
+    .split("\n")
+    .filter((line) => line.trim().length > 0)
+    .flatMap((line) => {
+      try {
+        return [convertToInvoiceData(JSON.parse(line))];
+      } catch (error) {
+        scheduledExportLogger.warn("Skipping unreadable invoice in scheduled export", { error });
+        return [];
+      }
+    });
+  if (invoices.length === 0) {
+    throw new Error("No valid invoice data found to export.");
+  }
+  await saveXmlExport(xmlId, path, generateTallyXml(invoices));
+};
+
+export function ScheduledExportListener() {
+  const queryClient = useQueryClient();
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    const handle = (request: ScheduledXmlExport) => {
+      writeXml(request)
+        .then(() => {
+          queryClient.invalidateQueries({ queryKey: ["xml-files"] });
+          toast.success(`Scheduled XML export saved to ${request.path}`);
+        })
+        .catch((error) => {
+          scheduledExportLogger.warn("Scheduled XML export failed", { error, ...request });
+          toast.error("Scheduled XML export failed.");
+        });
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) =>
+        listen<ScheduledXmlExport>(SCHEDULED_XML_EVENT, (event) => handle(event.payload)),
+      )
+      .then((stop) => {
+        if (cancelled) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        scheduledExportLogger.warn("Failed to subscribe to scheduled export events", { error });
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [queryClient]);
+
+  return null;
+}
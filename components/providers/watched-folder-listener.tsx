@@ -0,0 +1,54 @@
+"use client";
+
+import { useQueryClient } from "@tanstack/react-query";
+import { useEffect } from "react";
+import { toast } from "sonner";
+
+import { isTauriRuntime } from "@/lib/database";
+import { WATCHED_FILE_IMPORTED_EVENT, type WatchedFileImported } from "@/lib/files/watched-folders";
+import { createLogger } from "@/lib/logger";
+
+const watchedFolderLogger = createLogger("WatchedFolders");
+
+export function WatchedFolderListener() {
+  const queryClient = useQueryClient();
+
+  useEffect(() => {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+
+    const handle = ({ path, duplicate }: WatchedFileImported) => {
+      queryClient.invalidateQueries({ queryKey: ["files"] });
+      if (!duplicate) {
+        const name = path.split(/[\\/]/).pop() ?? path;
+        toast.success(`Imported ${name} from a watched folder.`);
+      }
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) =>
+        listen<WatchedFileImported>(WATCHED_FILE_IMPORTED_EVENT, (event) => handle(event.payload)),
+      )
+      .then((stop) => {
+        if (cancelled) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        watchedFolderLogger.warn("Failed to subscribe to watched folder events", { error });
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [queryClient]);
+
+  return null;
+}
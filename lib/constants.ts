@@ -10,6 +10,8 @@ export const FILE_STATUS = {
   PROCESSING: "Processing",
   PROCESSED: "Processed",
   FAILED: "Failed",
+  WAITING_FOR_NETWORK: "WaitingForNetwork",
+  NEEDS_REVIEW: "NeedsReview",
 } as const;
 
 export type FileStatus = (typeof FILE_STATUS)[keyof typeof FILE_STATUS];
@@ -0,0 +1,138 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { isTauriRuntime } from "@/lib/database";
+
+export type CloudProvider = "google_drive" | "dropbox" | "one_drive" | "s3";
+
+export interface CloudDriveLink {
+  id: number;
+  provider: CloudProvider;
+  folder: string;
+  folderName: string | null;
+  /** S3 links only. */
+  region: string | null;
+  endpoint: string | null;
+  createdAt: string;
+  lastSyncedAt: string | null;
+}
+
+export interface CloudFile {
+  remoteId: string;
+  name: string;
+  sizeBytes: number;
+  modifiedAt: string | null;
+  imported: boolean;
+}
+
+export interface CloudFilePage {
+  files: CloudFile[];
+  /** Pass back to `listCloudDriveFiles` for the next page; `null` on the last page. */
+  nextCursor: string | null;
+}
+
+export interface CloudSyncSummary {
+  linkId: number;
+  listed: number;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  failures: { name: string; error: string }[];
+}
+
+export interface LinkCloudDriveOptions {
+  provider: CloudProvider;
+  clientId: string;
+  clientSecret?: string;
+  /** Drive folder id for Google Drive, a path such as `/Invoices` for Dropbox and OneDrive. */
+  folder: string;
+  folderName?: string;
+}
+
+export interface LinkS3Options {
+  accessKeyId: string;
+  secretAccessKey: string;
+  /** Defaults to `us-east-1`. */
+  region?: string;
+  /** `bucket` or `bucket/prefix`. */
+  folder: string;
+  /** Host of an S3-compatible store such as MinIO or R2; AWS when omitted. */
+  endpoint?: string;
+  folderName?: string;
+}
+
+const requireRuntime = () => {
+  if (!isTauriRuntime()) {
+    throw new Error("Cloud drive import requires the Invox desktop runtime.");
+  }
+};
+
+/**
+ * Link a cloud-drive folder. Opens the provider's consent page and resolves once the
+ * user has authorized read access; the refresh token is kept in the OS keyring.
+ */
+export async function linkCloudDrive(options: LinkCloudDriveOptions): Promise<CloudDriveLink> {
+  requireRuntime();
+  return invoke<CloudDriveLink>("link_cloud_drive", {
+    options: {
+      ...options,
+      clientSecret: options.clientSecret ?? null,
+      folderName: options.folderName ?? null,
+    },
+  });
+}
+
+/**
+ * Link an S3 bucket prefix with an access key. The key is verified by listing the prefix
+ * and the secret is kept in the OS keyring.
+ */
+export async function linkS3Bucket(options: LinkS3Options): Promise<CloudDriveLink> {
+  requireRuntime();
+  return invoke<CloudDriveLink>("link_s3_bucket", {
+    options: {
+      ...options,
+      region: options.region ?? null,
+      endpoint: options.endpoint ?? null,
+      folderName: options.folderName ?? null,
+    },
+  });
+}
+
+export async function listCloudDriveLinks(): Promise<CloudDriveLink[]> {
+  if (!isTauriRuntime()) {
+    return [];
+  }
+  return invoke<CloudDriveLink[]>("list_cloud_drive_links");
+}
+
+export async function unlinkCloudDrive(linkId: number): Promise<void> {
+  requireRuntime();
+  await invoke("unlink_cloud_drive", { linkId });
+}
+
+export async function listCloudDriveFiles(
+  linkId: number,
+  cursor: string | null = null,
+): Promise<CloudFilePage> {
+  requireRuntime();
+  return invoke<CloudFilePage>("list_cloud_drive_files", { linkId, cursor });
+}
+
+/**
+ * Import only the chosen files, even ones pulled before. Duplicates are still detected.
+ */
+export async function importCloudDriveFiles(
+  linkId: number,
+  remoteIds: string[],
+): Promise<CloudSyncSummary> {
+  requireRuntime();
+  return invoke<CloudSyncSummary>("import_cloud_drive_files", { linkId, remoteIds });
+}
+
+/**
+ * Import new and changed documents from a linked folder through the dedup pipeline.
+ * Schedule the `cloud_drive_sync` job to do this automatically.
+ */
+export async function syncCloudDrive(linkId: number): Promise<CloudSyncSummary> {
+  requireRuntime();
+  return invoke<CloudSyncSummary>("sync_cloud_drive", { linkId });
+}
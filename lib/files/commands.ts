@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { isTauriRuntime } from "../database";
 import type { FileStatus } from "../constants";
+import type { DocType, FileDeleteResult, PageRange, SplitPart } from "./types";
 
 /**
  * File Commands - Write operations
@@ -29,6 +30,62 @@ export const FileCommands = {
     await invoke("update_files_status", { fileIds, status });
   },
 
+  /**
+   * Move files held for review to Processed; resolves to how many were waiting
+   */
+  async approveFiles(fileIds: string[]): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("approve_files", { fileIds });
+  },
+
+  /**
+   * Tag files, creating new tags as needed; resolves to how many tags were newly added
+   */
+  async addTags(fileIds: string[], tags: string[]): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("add_tags", { fileIds, tags });
+  },
+
+  /**
+   * Take tags off files; resolves to how many were removed
+   */
+  async removeTags(fileIds: string[], tags: string[]): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("remove_tags", { fileIds, tags });
+  },
+
+  /**
+   * Set the confidence (0 to 1) an extraction must exceed to skip review; null turns
+   * auto-approval off
+   */
+  async setAutoApproveThreshold(threshold: number | null): Promise<void> {
+    if (!isTauriRuntime()) {
+      throw new Error("Settings require the Tauri desktop runtime.");
+    }
+
+    await invoke("set_auto_approve_threshold", { threshold });
+  },
+
+  /**
+   * Correct the document type of files; resolves to how many were updated
+   */
+  async setDocumentType(fileIds: string[], docType: DocType): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("set_document_type", { fileIds, docType });
+  },
+
   /**
    * Update parsed details for a file
    */
@@ -41,14 +98,103 @@ export const FileCommands = {
   },
 
   /**
-   * Delete files by their IDs
+   * Save an extraction result. Files that already have details keep them; a differing
+   * result is held for review and reported as a conflict.
    */
-  async deleteFiles(fileIds: string[]): Promise<void> {
+  async saveExtractionResult(
+    fileId: string,
+    parsedDetails: string,
+  ): Promise<{ status: "applied" | "unchanged" | "conflict"; differences: unknown[] }> {
     if (!isTauriRuntime()) {
       throw new Error("File operations require the Tauri desktop runtime.");
     }
 
-    await invoke("delete_files", { fileIds });
+    return invoke("save_extraction_result", { fileId, parsedDetails });
+  },
+
+  /**
+   * Rename files from a template such as "{seller}-{invoice_number}-{date}"
+   */
+  async renameByTemplate(
+    fileIds: string[],
+    template: string,
+  ): Promise<Array<{ id: string; oldName: string; newName: string }>> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke("rename_files_by_template", { fileIds, template });
+  },
+
+  /**
+   * Delete multiple files. They go to the trash unless secure delete is on; resolves to
+   * what happened to each one
+   */
+  async deleteFiles(
+    fileIds: string[],
+    options?: { pin?: string; reason?: string },
+  ): Promise<FileDeleteResult[]> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<FileDeleteResult[]>("delete_files", {
+      fileIds,
+      pin: options?.pin ?? null,
+      reason: options?.reason ?? null,
+    });
+  },
+
+  /**
+   * Put trashed files back in the library; resolves to how many were restored
+   */
+  async restoreFiles(fileIds: string[], options?: { pin?: string }): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("restore_files", { fileIds, pin: options?.pin ?? null });
+  },
+
+  /**
+   * Delete files trashed more than `olderThanDays` ago for good; 0 empties the trash
+   */
+  async purgeTrash(olderThanDays: number, pin?: string): Promise<number> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<number>("purge_trash", { olderThanDays, pin: pin ?? null });
+  },
+
+  /**
+   * Record the tokens an extraction request used, for the usage dashboard
+   */
+  async recordUsage(record: {
+    fileId: string;
+    model: string;
+    profile?: string;
+    promptTokens: number;
+    outputTokens: number;
+  }): Promise<void> {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    await invoke("record_processing_usage", {
+      record: { ...record, profile: record.profile ?? null },
+    });
+  },
+
+  /**
+   * Ask the backend to show a desktop notification for a finished processing batch
+   */
+  async notifyBatchFinished(processed: number, failed: number): Promise<void> {
+    if (!isTauriRuntime()) {
+      return;
+    }
+
+    await invoke("notify_batch_finished", { processed, failed });
   },
 
   /**
@@ -72,4 +218,28 @@ export const FileCommands = {
 
     await invoke("copy_file_to_path", { sourcePath, targetPath, overwrite });
   },
+
+  /**
+   * Open the folder a file was imported from, or its web link for cloud imports; resolves
+   * to the recorded source
+   */
+  async revealOriginalSource(fileId: string): Promise<string> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<string>("reveal_original_source", { fileId });
+  },
+
+  /**
+   * Split a multi-invoice PDF or TIFF scan into one file per page range; the scan itself
+   * is kept but hidden from default listings
+   */
+  async splitDocument(fileId: string, pageRanges: PageRange[]): Promise<SplitPart[]> {
+    if (!isTauriRuntime()) {
+      throw new Error("File operations require the Tauri desktop runtime.");
+    }
+
+    return invoke<SplitPart[]>("split_document", { fileId, pageRanges });
+  },
 };
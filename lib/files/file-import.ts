@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 
 import { isTauriRuntime } from "../database";
+import { createLogger } from "../logger";
 
 export interface ImportedFileResult {
   id: string;
@@ -8,6 +9,111 @@ export interface ImportedFileResult {
   name: string;
 }
 
+export type ImportEntryStatus = "imported" | "duplicate" | "skipped" | "failed";
+
+export interface ImportEntryResult {
+  /** The file's path, or its path inside the archive it came from. */
+  entry: string;
+  status: ImportEntryStatus;
+  fileId: string | null;
+  reason: string | null;
+}
+
+export interface ArchiveImportSummary {
+  archive: string;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  failed: number;
+  cancelled: boolean;
+  entries: ImportEntryResult[];
+}
+
+export interface BatchImportSummary {
+  importId: string;
+  batchId: number;
+  cancelled: boolean;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  failed: number;
+  entries: ImportEntryResult[];
+}
+
+/** Emitted by the backend as a batch import starts, after every file and when it ends. */
+export const IMPORT_PROGRESS_EVENT = "import://progress";
+
+export type ImportPhase = "running" | "completed" | "cancelled";
+
+export interface ImportProgress {
+  importId: string;
+  batchId: number;
+  phase: ImportPhase;
+  totalFiles: number;
+  completedFiles: number;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  failed: number;
+  currentFile: string | null;
+  bytesProcessed: number;
+  totalBytes: number;
+}
+
+/** Clean-up applied to photographed invoices (PNG, JPEG, WebP, TIFF) before they are stored. */
+export interface PreprocessOptions {
+  /** Bake the camera's EXIF orientation into the pixels. */
+  autoRotate?: boolean;
+  /** Straighten pages photographed at a slight angle. */
+  deskew?: boolean;
+  normalizeContrast?: boolean;
+  grayscale?: boolean;
+}
+
+export interface ImportOptions {
+  /** Reported with every progress event; generated when omitted. */
+  importId?: string;
+  /** Import batch to record the files in; the backend starts one when omitted. */
+  batchId?: number;
+  /** Aborting stops the import after the file it is on. */
+  signal?: AbortSignal;
+  onProgress?: (progress: ImportProgress) => void;
+  /** Not applied to documents inside archives. */
+  preprocess?: PreprocessOptions;
+}
+
+export interface DirectoryFilters {
+  /** Extensions to import, without the dot; every supported type when empty. */
+  extensions?: string[];
+  maxSizeBytes?: number;
+  /** Date (YYYY-MM-DD) or RFC 3339 timestamp; older files are left out. */
+  modifiedAfter?: string;
+  /** Walk subfolders too; defaults to true. */
+  recursive?: boolean;
+}
+
+export interface DirectoryEntry {
+  path: string;
+  fileName: string;
+  sizeBytes: number;
+  modifiedAt: string | null;
+  included: boolean;
+  /** Which filter left the file out. */
+  reason: string | null;
+}
+
+export interface DirectoryPreview {
+  directory: string;
+  includedFiles: number;
+  includedBytes: number;
+  excludedFiles: number;
+  entries: DirectoryEntry[];
+}
+
+const importLogger = createLogger("FileImport");
+
+const isArchive = (file: File) => file.name.toLowerCase().endsWith(".zip");
+
 const parseImportResponse = (response: string) => {
   if (response.startsWith("OK:")) {
     return { duplicate: false, id: response.slice(3) };
@@ -18,19 +124,161 @@ const parseImportResponse = (response: string) => {
   throw new Error(`Unexpected import response: ${response}`);
 };
 
-async function importSingleFile(file: File): Promise<ImportedFileResult> {
+async function importSingleFile(
+  file: File,
+  batchId: number,
+  preprocess?: PreprocessOptions,
+): Promise<ImportedFileResult> {
   const fileBuffer = await file.arrayBuffer();
   const byteArray = Array.from(new Uint8Array(fileBuffer));
 
   const response = await invoke<string>("import_data", {
     fileName: file.name,
     bytes: byteArray,
+    batchId,
+    preprocess: preprocess ?? null,
   });
   const details = parseImportResponse(response);
   return { id: details.id, duplicate: details.duplicate, name: file.name };
 }
 
-export async function importFiles(files: File[]): Promise<ImportedFileResult[]> {
+/**
+ * Stop a running import after the file it is on. Resolves to false when nothing runs
+ * under the id.
+ */
+export async function cancelImport(importId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_import", { importId });
+}
+
+/**
+ * Run a backend import under a fresh id, forwarding its progress events and turning an
+ * abort of `signal` into `cancel_import`.
+ */
+async function trackImport<T>(
+  options: ImportOptions,
+  run: (importId: string) => Promise<T>,
+): Promise<T> {
+  const importId = options.importId ?? crypto.randomUUID();
+  const { listen } = await import("@tauri-apps/api/event");
+  const unlisten = options.onProgress
+    ? await listen<ImportProgress>(IMPORT_PROGRESS_EVENT, (event) => {
+        if (event.payload.importId === importId) {
+          options.onProgress?.(event.payload);
+        }
+      })
+    : undefined;
+  const onAbort = () => {
+    cancelImport(importId).catch((error) => {
+      importLogger.warn("Failed to cancel import", { importId, error });
+    });
+  };
+  options.signal?.addEventListener("abort", onAbort);
+
+  try {
+    return await run(importId);
+  } finally {
+    options.signal?.removeEventListener("abort", onAbort);
+    unlisten?.();
+  }
+}
+
+/**
+ * Unpack a `.zip` in the backend and import every supported document inside it.
+ */
+export async function importArchive(
+  file: File,
+  options: ImportOptions = {},
+): Promise<ArchiveImportSummary> {
+  const fileBuffer = await file.arrayBuffer();
+  return trackImport(options, (importId) =>
+    invoke<ArchiveImportSummary>("import_archive_data", {
+      fileName: file.name,
+      bytes: Array.from(new Uint8Array(fileBuffer)),
+      importId,
+      batchId: options.batchId ?? null,
+    }),
+  );
+}
+
+/**
+ * Import documents and `.zip` archives from disk paths (for example from a file dialog)
+ * as one batch, with progress events and cancellation.
+ */
+export async function importPaths(
+  paths: string[],
+  options: ImportOptions = {},
+): Promise<BatchImportSummary> {
+  if (!isTauriRuntime()) {
+    throw new Error("File ingestion requires the Tauri desktop runtime.");
+  }
+  return trackImport(options, (importId) =>
+    invoke<BatchImportSummary>("import_paths", {
+      paths,
+      importId,
+      preprocess: options.preprocess ?? null,
+    }),
+  );
+}
+
+/**
+ * List a dropped folder against `filters`, so the user can confirm what will be imported.
+ */
+export async function previewDirectoryImport(
+  directory: string,
+  filters: DirectoryFilters = {},
+): Promise<DirectoryPreview> {
+  if (!isTauriRuntime()) {
+    throw new Error("File ingestion requires the Tauri desktop runtime.");
+  }
+  return invoke<DirectoryPreview>("preview_directory_import", { directory, filters });
+}
+
+/**
+ * Import the files of a dropped folder that pass `filters`, as one batch labelled with the
+ * folder. Use the same filters as the confirmed preview.
+ */
+export async function importDirectory(
+  directory: string,
+  filters: DirectoryFilters = {},
+  options: ImportOptions = {},
+): Promise<BatchImportSummary> {
+  if (!isTauriRuntime()) {
+    throw new Error("File ingestion requires the Tauri desktop runtime.");
+  }
+  return trackImport(options, (importId) =>
+    invoke<BatchImportSummary>("import_directory", {
+      directory,
+      filters,
+      importId,
+      preprocess: options.preprocess ?? null,
+    }),
+  );
+}
+
+/**
+ * Import whatever is on the OS clipboard: copied files, or a copied image or screenshot,
+ * which is stored as a PNG.
+ */
+export async function importFromClipboard(
+  options: Pick<ImportOptions, "batchId" | "preprocess"> = {},
+): Promise<ImportEntryResult[]> {
+  if (!isTauriRuntime()) {
+    throw new Error("File ingestion requires the Tauri desktop runtime.");
+  }
+  return invoke<ImportEntryResult[]>("import_from_clipboard", {
+    batchId: options.batchId ?? null,
+    preprocess: options.preprocess ?? null,
+  });
+}
+
+/**
+ * Import files picked or dropped in the webview as one import batch. Aborting `signal`
+ * stops before the next file, or after the current entry of an archive.
+ */
+export async function importFiles(
+  files: File[],
+  options: ImportOptions = {},
+): Promise<ImportedFileResult[]> {
   if (files.length === 0) {
     return [];
   }
@@ -39,11 +287,58 @@ export async function importFiles(files: File[]): Promise<ImportedFileResult[]>
   }
 
   const results: ImportedFileResult[] = [];
+  const batchId = options.batchId ?? (await invoke<number>("begin_import_batch", { label: null }));
 
-  for (const file of files) {
-    const imported = await importSingleFile(file);
-    results.push(imported);
+  try {
+    for (const file of files) {
+      if (options.signal?.aborted) {
+        break;
+      }
+      await importIntoBatch(file, batchId, options, results);
+    }
+  } finally {
+    if (options.batchId === undefined) {
+      await invoke("finish_import_batch", {
+        batchId,
+        cancelled: options.signal?.aborted ?? false,
+      });
+    }
   }
 
   return results;
 }
+
+async function importIntoBatch(
+  file: File,
+  batchId: number,
+  options: ImportOptions,
+  results: ImportedFileResult[],
+) {
+  if (!isArchive(file)) {
+    results.push(await importSingleFile(file, batchId, options.preprocess));
+    return;
+  }
+
+  const summary = await importArchive(file, {
+    batchId,
+    signal: options.signal,
+    onProgress: options.onProgress,
+  });
+  for (const entry of summary.entries) {
+    if (entry.fileId && (entry.status === "imported" || entry.status === "duplicate")) {
+      results.push({
+        id: entry.fileId,
+        duplicate: entry.status === "duplicate",
+        name: entry.entry.split("/").pop() ?? entry.entry,
+      });
+    }
+  }
+  if (summary.skipped > 0 || summary.failed > 0) {
+    importLogger.warn("Some archive entries were not imported", {
+      archive: summary.archive,
+      entries: summary.entries.filter(
+        (entry) => entry.status === "skipped" || entry.status === "failed",
+      ),
+    });
+  }
+}
@@ -1,6 +1,13 @@
+import { invoke } from "@tauri-apps/api/core";
+
 import { readFileBinary } from "../filesystem";
 
-import { MIME_BY_EXTENSION } from "@/lib/invoice/constants";
+import {
+  INVOICE_JSON_SCHEMA,
+  MIME_BY_EXTENSION,
+  SYSTEM_INSTRUCTION,
+  USER_PROMPT,
+} from "@/lib/invoice/constants";
 import type { InvoiceFileInput } from "@/lib/invoice/helpers";
 
 import { FILE_STATUS } from "../constants";
@@ -37,15 +44,71 @@ const createFileLabel = (fileName: string, id: string) => {
 export interface FileProcessingResult {
   processedFiles: number;
   failedFiles: number;
+  /** Files parked until the provider is reachable again; the backend re-queues them. */
+  waitingFiles: number;
+  /** Reprocessed files whose new result differs from their saved details and awaits review. */
+  conflictFiles: number;
 }
 
 export interface FileProcessingOptions {
   onStatusUpdate?: (message: string) => void;
   onProgress?: (processed: number, total: number) => void;
+  /** Label usage is recorded under, e.g. a client or workflow name. */
+  profile?: string;
+}
+
+export interface ProcessFolderOptions {
+  /** Label usage is recorded under, e.g. a client or workflow name. */
+  profile?: string;
+  recursive?: boolean;
+  /** Existing sheet to append the results to. */
+  sheetId?: number;
+  /** Name of a new sheet, used when `sheetId` is not set. */
+  sheetName?: string;
+  /** Where to write the Tally XML for the sheet. */
+  xmlOutputPath?: string;
+}
+
+export interface FolderPipelineSummary {
+  folder: string;
+  startedAt: string;
+  finishedAt: string;
+  imported: number;
+  duplicates: number;
+  unsupported: number;
+  processed: number;
+  waitingForNetwork: number;
+  failures: { fileName: string; error: string }[];
+  conflicts: number;
+  sheetId: number | null;
+  xmlOutputPath: string | null;
+  promptTokens: number;
+  outputTokens: number;
+  cost: number;
 }
 
 const fileProcessingLogger = createLogger("FileProcessing");
 
+const NETWORK_ERROR_PATTERNS = [
+  "failed to fetch",
+  "fetch failed",
+  "networkerror",
+  "network error",
+  "network request failed",
+  "enotfound",
+  "econnrefused",
+  "econnreset",
+  "etimedout",
+  "err_internet_disconnected",
+];
+
+const isOffline = () => typeof navigator !== "undefined" && navigator.onLine === false;
+
+const isNetworkError = (error: Error) => {
+  const message = error.message.toLowerCase();
+  return NETWORK_ERROR_PATTERNS.some((pattern) => message.includes(pattern));
+};
+
 const withTimeout = async <T>(promise: Promise<T>, timeoutMs: number): Promise<T> => {
   let timeoutId: ReturnType<typeof setTimeout> | undefined;
   const timeoutPromise = new Promise<T>((_, reject) => {
@@ -128,6 +191,7 @@ export async function processFiles(
 
     const results: import("@/lib/invoice/types").BatchExtractionResult[] = [];
     const errors: import("@/lib/invoice/types").ProcessingError[] = [];
+    const waitingFileIds: string[] = [];
 
     for (let index = 0; index < invoiceInputs.length; index += 1) {
       const file = invoiceInputs[index];
@@ -135,14 +199,23 @@ export async function processFiles(
       const fileName = file.displayName ?? `file-${index + 1}`;
       let lastError: Error | null = null;
       let processed = false;
+      let waitingForNetwork = isOffline();
 
-      for (const model of modelsToTry) {
+      for (const model of waitingForNetwork ? [] : modelsToTry) {
         try {
           const result = await withTimeout(
             processInvoices({ files: [file], model, apiKey }),
             PER_FILE_TIMEOUT_MS,
           );
           if (result.length > 0) {
+            await FileCommands.recordUsage({
+              fileId,
+              model,
+              profile: options?.profile,
+              ...result[0].usage,
+            }).catch((error) => {
+              fileProcessingLogger.warn("Recording token usage failed", { error });
+            });
             results.push({
               fileId,
               fileName,
@@ -153,6 +226,10 @@ export async function processFiles(
           }
         } catch (error) {
           lastError = error instanceof Error ? error : new Error(String(error));
+          if (isNetworkError(lastError) || isOffline()) {
+            waitingForNetwork = true;
+            break;
+          }
           const is429 =
             error instanceof Error &&
             (error.message.includes("429") ||
@@ -164,7 +241,9 @@ export async function processFiles(
         }
       }
 
-      if (!processed) {
+      if (!processed && waitingForNetwork) {
+        waitingFileIds.push(fileId);
+      } else if (!processed) {
         errors.push({
           fileId,
           fileName,
@@ -173,7 +252,7 @@ export async function processFiles(
         });
       }
 
-      const processedCount = results.length + errors.length;
+      const processedCount = results.length + errors.length + waitingFileIds.length;
       const remaining = invoiceInputs.length - processedCount;
       emit?.(`Processing files: ${processedCount} processed, ${remaining} remaining`);
       options?.onProgress?.(processedCount, invoiceInputs.length);
@@ -183,18 +262,23 @@ export async function processFiles(
       data: {
         successCount: results.length,
         errorCount: errors.length,
+        waitingCount: waitingFileIds.length,
       },
     });
 
     // Update database for successful files
     emit?.("Saving extracted data...");
 
+    let conflictFiles = 0;
     for (const result of results) {
       const payload = result.result;
 
       // Update file status and parsed details in database
       await FileCommands.updateStatus(result.fileId, FILE_STATUS.PROCESSED);
-      await FileCommands.updateParsedDetails(result.fileId, JSON.stringify(payload));
+      const outcome = await FileCommands.saveExtractionResult(result.fileId, JSON.stringify(payload));
+      if (outcome.status === "conflict") {
+        conflictFiles += 1;
+      }
     }
 
     // Update database for failed files
@@ -214,8 +298,17 @@ export async function processFiles(
       });
     }
 
+    // Keep their previous parsed details; only the status changes until the network returns
+    if (waitingFileIds.length > 0) {
+      await FileCommands.updateMultipleStatus(waitingFileIds, FILE_STATUS.WAITING_FOR_NETWORK);
+    }
+
     // Provide feedback based on results
-    if (errors.length === 0) {
+    if (waitingFileIds.length > 0) {
+      emit?.(
+        `Offline: ${waitingFileIds.length} file(s) will be processed automatically when the connection returns.`,
+      );
+    } else if (errors.length === 0) {
       emit?.("Processing completed successfully.");
     } else if (results.length === 0) {
       emit?.("Processing failed: all files failed to process.");
@@ -223,9 +316,15 @@ export async function processFiles(
       emit?.(`Processing completed with ${errors.length} failed file(s).`);
     }
 
+    await FileCommands.notifyBatchFinished(results.length, errors.length).catch((error) => {
+      fileProcessingLogger.warn("Batch notification failed", { error });
+    });
+
     return {
       processedFiles: results.length,
       failedFiles: errors.length,
+      waitingFiles: waitingFileIds.length,
+      conflictFiles,
     };
   } catch (error) {
     // Mark all files as failed if processing crashes
@@ -236,3 +335,34 @@ export async function processFiles(
     throw error;
   }
 }
+
+/**
+ * Import, extract and export a whole folder as one backend job. Progress is emitted as
+ * `pipeline://progress`; the XML, when requested, is written by the scheduled export listener.
+ */
+export async function processFolder(
+  path: string,
+  options: ProcessFolderOptions = {},
+): Promise<FolderPipelineSummary> {
+  if (!isTauriRuntime()) {
+    throw new Error("Processing files requires the Invox desktop runtime.");
+  }
+
+  const apiKey = await getGeminiApiKey();
+  if (!apiKey) {
+    throw new Error("Set your Gemini API key in Account preferences before processing files.");
+  }
+  const { profile, ...rest } = options;
+
+  return invoke<FolderPipelineSummary>("process_folder", {
+    path,
+    profile: profile ?? null,
+    options: {
+      ...rest,
+      apiKey,
+      model: await getGeminiDefaultModel(),
+      prompts: { systemInstruction: SYSTEM_INSTRUCTION, userPrompt: USER_PROMPT },
+      responseSchema: INVOICE_JSON_SCHEMA,
+    },
+  });
+}
@@ -0,0 +1,62 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { isTauriRuntime } from "@/lib/database";
+
+import type { ImportEntryStatus } from "./file-import";
+
+export type ImportSource =
+  | "upload"
+  | "paths"
+  | "watched_folder"
+  | "cloud_drive"
+  | "folder_pipeline"
+  | "launch"
+  | "legacy"
+  | "clipboard";
+
+export interface ImportBatch {
+  id: number;
+  source: ImportSource;
+  /** Folder, archive or drive the batch was imported from, when there is one. */
+  label: string | null;
+  startedAt: string;
+  finishedAt: string | null;
+  cancelled: boolean;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  failed: number;
+}
+
+export interface ImportBatchItem {
+  entry: string;
+  status: ImportEntryStatus;
+  /** The new file, or the existing one a duplicate matched. */
+  fileId: string | null;
+  reason: string | null;
+  recordedAt: string;
+}
+
+export interface ImportBatchReport {
+  batch: ImportBatch;
+  items: ImportBatchItem[];
+}
+
+/**
+ * Import batches started in the range, newest first. `from` and `to` are `YYYY-MM-DD`
+ * dates or RFC 3339 timestamps; a `to` date includes that whole day.
+ */
+export async function listImportBatches(from?: string, to?: string): Promise<ImportBatch[]> {
+  if (!isTauriRuntime()) {
+    return [];
+  }
+  return invoke<ImportBatch[]>("list_import_batches", { from: from ?? null, to: to ?? null });
+}
+
+/** A batch with what every file in it turned into. */
+export async function getBatchReport(batchId: number): Promise<ImportBatchReport> {
+  if (!isTauriRuntime()) {
+    throw new Error("Import batches require the Invox desktop runtime.");
+  }
+  return invoke<ImportBatchReport>("get_batch_report", { batchId });
+}
@@ -1,6 +1,15 @@
 import { invoke } from "@tauri-apps/api/core";
 import { isTauriRuntime } from "../database";
-import type { FileListQuery, FileRecord, PaginatedFilesResult } from "./types";
+import type {
+  FieldProvenance,
+  DeletedFile,
+  FileSearchHit,
+  Tag,
+  FileListQuery,
+  FileRecord,
+  FileVersion,
+  PaginatedFilesResult,
+} from "./types";
 
 /**
  * File Queries - Read-only operations
@@ -18,6 +27,61 @@ export const FileQueries = {
     return invoke<PaginatedFilesResult>("list_files_paginated", { query });
   },
 
+  /**
+   * Full-text search over file names and parsed fields, best match first
+   */
+  async search(query: string, limit?: number): Promise<FileSearchHit[]> {
+    if (!isTauriRuntime()) {
+      return [];
+    }
+
+    return invoke<FileSearchHit[]>("search_files", { query, limit: limit ?? null });
+  },
+
+  /**
+   * Files held back by the auto-approve threshold, least confident first
+   */
+  async listNeedingReview(limit: number, offset: number): Promise<PaginatedFilesResult> {
+    if (!isTauriRuntime()) {
+      throw new Error("File listing requires the Tauri desktop runtime.");
+    }
+
+    return invoke<PaginatedFilesResult>("list_files_needing_review", { limit, offset });
+  },
+
+  /**
+   * Confidence an extraction must exceed to skip review, or null when auto-approval is off
+   */
+  async getAutoApproveThreshold(): Promise<number | null> {
+    if (!isTauriRuntime()) {
+      return null;
+    }
+
+    return invoke<number | null>("get_auto_approve_threshold");
+  },
+
+  /**
+   * Tags in use, by name, with how many files carry each
+   */
+  async listTags(): Promise<Tag[]> {
+    if (!isTauriRuntime()) {
+      return [];
+    }
+
+    return invoke<Tag[]>("list_tags");
+  },
+
+  /**
+   * Files in the trash, most recently deleted first
+   */
+  async listDeleted(limit?: number): Promise<DeletedFile[]> {
+    if (!isTauriRuntime()) {
+      return [];
+    }
+
+    return invoke<DeletedFile[]>("list_recently_deleted", { limit: limit ?? null });
+  },
+
   /**
    * Get a single file by ID
    */
@@ -33,4 +97,26 @@ export const FileQueries = {
 
     return result.files.find((f: FileRecord) => f.id === id) ?? null;
   },
+
+  /**
+   * Page and bounding box of each extracted value, for highlighting in the review view
+   */
+  async getFieldProvenance(fileId: string): Promise<FieldProvenance[]> {
+    if (!isTauriRuntime()) {
+      return [];
+    }
+
+    return invoke<FieldProvenance[]>("get_field_provenance", { fileId });
+  },
+
+  /**
+   * Every imported copy of the file's invoice (same seller and invoice number), oldest first
+   */
+  async listVersions(fileId: string): Promise<FileVersion[]> {
+    if (!isTauriRuntime()) {
+      return [];
+    }
+
+    return invoke<FileVersion[]>("list_file_versions", { fileId });
+  },
 };
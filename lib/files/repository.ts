@@ -1,5 +1,5 @@
 import type { FileStatus } from "../constants";
-import type { FileListQuery, FileRecord, PaginatedFilesResult } from "./types";
+import type { FileDeleteResult, FileListQuery, FileRecord, PaginatedFilesResult } from "./types";
 
 /**
  * File Repository Interface
@@ -14,5 +14,8 @@ export interface FileRepository {
   // Commands (write)
   updateStatus(fileId: string, status: FileStatus): Promise<void>;
   updateParsedDetails(fileId: string, data: string): Promise<void>;
-  deleteFiles(fileIds: string[]): Promise<void>;
+  deleteFiles(
+    fileIds: string[],
+    options?: { pin?: string; reason?: string },
+  ): Promise<FileDeleteResult[]>;
 }
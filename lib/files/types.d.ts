@@ -11,8 +11,78 @@ export interface FileRecord {
   createdAt: string;
   processedAt: string | null;
   updatedAt: string | null;
+  taxIdsValid: boolean | null;
+  invoiceDate: string | null;
+  invoiceDateRaw: string | null;
+  sellerId: number | null;
+  buyerId: number | null;
+  arithmeticValid: boolean | null;
+  dueDate: string | null;
+  paymentTerms: string | null;
+  contentHash: string;
+  hashAlgo: HashAlgorithm;
+  /** Id of the older version of the same invoice this file replaces. */
+  supersedes: string | null;
+  /** Id of the multi-invoice scan this file was split from. */
+  parentFileId: string | null;
+  /** Pages of the parent this file holds, e.g. "3-5". */
+  pageRange: string | null;
+  /** Original path or remote URL the document was imported from. */
+  source: string | null;
+  /** Tesseract code of the language the document is written in, e.g. "hin". */
+  documentLanguage: string | null;
+  /** Null until the file's text or parsed details say what it is. */
+  docType: DocType | null;
+  /** Between 0 and 1, from the last extraction; null until extracted. */
+  extractionConfidence: number | null;
+  /** Names of the file's tags, in alphabetical order. */
+  tags: string[];
 }
 
+export interface Tag {
+  id: number;
+  name: string;
+  fileCount: number;
+}
+
+export interface DeletedFile {
+  fileId: string;
+  fileName: string;
+  sizeBytes: number;
+  deletedAt: string;
+  /** Role that deleted the file. */
+  deletedBy: string | null;
+  reason: string | null;
+  /** When the retention setting purges it; null when auto-purge is off. */
+  purgeAfter: string | null;
+}
+
+export type DeleteOutcome = "trashed" | "shredded" | "not_found" | "failed";
+
+/** What happened to one file passed to `delete_files`. */
+export interface FileDeleteResult {
+  fileId: string;
+  /** null when the file was not found. */
+  fileName: string | null;
+  outcome: DeleteOutcome;
+  /** Why the file was kept, or what went wrong overwriting a shredded document. */
+  error: string | null;
+}
+
+export type DocType = "invoice" | "receipt" | "credit_note" | "purchase_order" | "other";
+
+export interface FileVersion {
+  id: string;
+  fileName: string;
+  contentHash: string;
+  createdAt: string;
+  supersedes: string | null;
+  latest: boolean;
+}
+
+/** Content hash algorithm recorded per file; new imports use the configured one. */
+export type HashAlgorithm = "blake3" | "sha256";
+
 export interface PaginatedFilesResult {
   files: FileRecord[];
   totalCount: number;
@@ -21,8 +91,53 @@ export interface PaginatedFilesResult {
 export interface FileListQuery {
   statusFilter?: string;
   searchQuery?: string;
+  sellerId?: number;
+  buyerId?: number;
+  /** Only documents written in this language (Tesseract code). */
+  language?: string;
+  docType?: DocType;
+  /** Only files due on or after / on or before these dates (YYYY-MM-DD). */
+  dueFrom?: string;
+  dueTo?: string;
+  /** Only files carrying every one of these tags. */
+  tags?: string[];
   limit: number;
   offset: number;
   sortBy?: string;
   sortOrder?: "ASC" | "DESC";
+  /** Listings show only the newest version of each invoice unless this is set. */
+  includeSuperseded?: boolean;
+  /** Scans split into separate files are hidden unless this is set. */
+  includeSplitSources?: boolean;
+}
+
+/** Inclusive, 1-based page range. */
+export interface PageRange {
+  start: number;
+  end: number;
+}
+
+export interface SplitPart {
+  fileId: string;
+  fileName: string;
+  start: number;
+  end: number;
+  /** The pages matched an already imported file, which was left as it was. */
+  duplicate: boolean;
+}
+
+/** Location of an extracted value; box coordinates are fractions (0-1) of the page size. */
+export interface FileSearchHit {
+  fileId: string;
+  fileName: string;
+  /** Best-matching text, with matched terms wrapped in `<mark>` and `</mark>`. */
+  snippet: string;
+  /** BM25 relevance; higher is better. */
+  score: number;
+}
+
+export interface FieldProvenance {
+  field: string;
+  page: number;
+  boundingBox: { xMin: number; yMin: number; xMax: number; yMax: number };
 }
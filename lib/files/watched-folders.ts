@@ -0,0 +1,56 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { isTauriRuntime } from "@/lib/database";
+
+/** Emitted by the backend for every document imported from a watched folder. */
+export const WATCHED_FILE_IMPORTED_EVENT = "watcher://file-imported";
+
+export interface WatchedFolder {
+  id: number;
+  path: string;
+  recursive: boolean;
+  enabled: boolean;
+  createdAt: string;
+  lastScannedAt: string | null;
+}
+
+export interface WatchedFileImported {
+  folderId: number | null;
+  path: string;
+  fileId: string;
+  duplicate: boolean;
+}
+
+const requireRuntime = () => {
+  if (!isTauriRuntime()) {
+    throw new Error("Watched folders require the Invox desktop runtime.");
+  }
+};
+
+export async function listWatchedFolders(): Promise<WatchedFolder[]> {
+  if (!isTauriRuntime()) {
+    return [];
+  }
+  return invoke<WatchedFolder[]>("list_watched_folders");
+}
+
+/**
+ * Watch a folder; documents added to it from now on are imported automatically.
+ */
+export async function addWatchedFolder(path: string, recursive = false): Promise<WatchedFolder> {
+  requireRuntime();
+  return invoke<WatchedFolder>("add_watched_folder", { path, recursive });
+}
+
+export async function removeWatchedFolder(folderId: number): Promise<void> {
+  requireRuntime();
+  await invoke("remove_watched_folder", { folderId });
+}
+
+export async function setWatchedFolderEnabled(
+  folderId: number,
+  enabled: boolean,
+): Promise<WatchedFolder> {
+  requireRuntime();
+  return invoke<WatchedFolder>("set_watched_folder_enabled", { folderId, enabled });
+}
@@ -21,7 +21,7 @@ export function useFileMutations() {
   };
 
   const importFilesMutation = useMutation({
-    mutationFn: importFiles,
+    mutationFn: (files: File[]) => importFiles(files),
     onSuccess: invalidateFiles,
   });
 
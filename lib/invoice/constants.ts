@@ -8,6 +8,7 @@ export const ALLOWED_EXTENSIONS = [
   ".tiff",
   ".bmp",
   ".heic",
+  ".zip",
 ] as const;
 
 export const INVOICE_JSON_SCHEMA = {
@@ -66,6 +67,32 @@ export const INVOICE_JSON_SCHEMA = {
       description:
         "Supplier's invoice date (Tally REFERENCEDATE). Prefer ISO format YYYY-MM-DD; if not parseable, return raw string.",
     },
+    "due date": {
+      type: ["string", "null"],
+      description:
+        "Payment due date if printed. Prefer ISO format YYYY-MM-DD; if not parseable, return raw string.",
+    },
+    "payment terms": {
+      type: ["string", "null"],
+      description: 'Payment terms exactly as printed, e.g. "Net 30" or "Due on receipt".',
+    },
+    irn: {
+      type: ["string", "null"],
+      description: "64-character Invoice Reference Number (IRN) of an Indian e-invoice, if printed.",
+    },
+    "signed qr code": {
+      type: ["string", "null"],
+      description:
+        "Text of the signed e-invoice QR code (a JWT issued by the IRP) if it can be read from the document; otherwise null.",
+    },
+    "upi id": {
+      type: ["string", "null"],
+      description: 'Seller UPI ID / VPA for payment, e.g. "vendor@okbank", if printed.',
+    },
+    iban: {
+      type: ["string", "null"],
+      description: "Seller IBAN for bank transfer, if printed. Remove spaces.",
+    },
 
     // Classification
     "voucher type": {
@@ -78,6 +105,22 @@ export const INVOICE_JSON_SCHEMA = {
       description: "Place of supply / state name as printed (e.g. Maharashtra).",
     },
 
+    currency: {
+      type: ["string", "null"],
+      description:
+        'ISO 4217 code of the invoice currency (e.g. "INR", "USD"), from the printed symbol or code.',
+    },
+    "document type": {
+      type: ["string", "null"],
+      description:
+        'Kind of document by its printed title: "invoice", "receipt", "credit note", "purchase order" or "other".',
+    },
+    "document language": {
+      type: ["string", "null"],
+      description:
+        'English name of the language the invoice is mainly written in (e.g. "English", "Hindi", "German").',
+    },
+
     // Totals (optional but useful for sanity checks)
     subtotal: {
       anyOf: [{ type: "number" }, { type: "null" }],
@@ -147,6 +190,83 @@ export const INVOICE_JSON_SCHEMA = {
             anyOf: [{ type: "number" }, { type: "null" }],
             description: "SGST rate in percent for this line (e.g. 9 for 9% SGST).",
           },
+          igst: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "IGST amount for this line, if separately available.",
+          },
+          igst_rate: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "IGST rate in percent for this line (e.g. 18 for 18% IGST).",
+          },
+        },
+      },
+    },
+
+    // Tax summary
+    "tax breakup": {
+      type: "array",
+      description:
+        "Rows of the printed GST summary table (tax analysis by rate or HSN), one per row. Empty when the invoice prints no such table.",
+      items: {
+        type: "object",
+        additionalProperties: false,
+        required: ["rate"],
+        properties: {
+          rate: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "Total GST rate in percent for the row (e.g. 18 for 9% CGST + 9% SGST).",
+          },
+          "taxable value": {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "Taxable value taxed at this rate.",
+          },
+          cgst: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "CGST amount at this rate.",
+          },
+          sgst: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "SGST/UTGST amount at this rate.",
+          },
+          igst: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "IGST amount at this rate.",
+          },
+          cess: {
+            anyOf: [{ type: "number" }, { type: "null" }],
+            description: "Compensation cess at this rate, if printed.",
+          },
+        },
+      },
+    },
+
+    // Provenance
+    "field locations": {
+      type: "array",
+      description:
+        "Where each extracted value is printed on the document, so reviewers can find it on the page.",
+      items: {
+        type: "object",
+        additionalProperties: false,
+        required: ["field", "page", "box"],
+        properties: {
+          field: {
+            type: "string",
+            description:
+              'Top-level key the value was extracted for, or "items[N].<key>" for line item values (N is zero-based).',
+          },
+          page: {
+            type: "integer",
+            description: "1-based page number the value was read from.",
+          },
+          box: {
+            type: "array",
+            items: { type: "integer" },
+            minItems: 4,
+            maxItems: 4,
+            description:
+              "Bounding box of the printed value as [ymin, xmin, ymax, xmax], normalized to 0-1000 of the page size.",
+          },
         },
       },
     },
@@ -161,10 +281,11 @@ export const SYSTEM_INSTRUCTION = [
   "2) Extract all fields defined in the schema when they are clearly present. If a field is missing or cannot be confidently determined, use null (or an empty array for items).",
   "3) Do NOT invent, guess, or normalize values beyond what is printed. Never hallucinate GSTINs, addresses, dates, or totals.",
   "4) Strip currency symbols and thousand separators from numeric amounts; return numeric fields as plain numbers when present.",
-  "5) For GST-related fields (cgst, sgst, gst_rate, tax total):",
+  "5) For GST-related fields (cgst, sgst, igst, their rates, tax breakup, tax total):",
   "   - Use only values that are explicitly present or clearly implied on the invoice.",
   "   - If CGST/SGST or GST rate are not printed or cannot be reliably inferred, set them to null.",
   "   - Do NOT compute or back-calculate missing taxes or totals.",
+  '   - Copy "tax breakup" rows from the printed GST summary table only; leave it empty when no such table is printed.',
   "6) For totals (subtotal, tax total, grand total): if printed, read them exactly; if not printed, set them to null. Do NOT recompute them from line items.",
   "7) Prefer ISO date format (YYYY-MM-DD) if you can reliably parse the date; otherwise return the date exactly as printed.",
   "8) Preserve original spelling and case for all text fields (e.g., seller name, buyer name, addresses, voucher type).",
@@ -172,6 +293,8 @@ export const SYSTEM_INSTRUCTION = [
   "10) For voucher number, reference number, and reference date, only fill them if they are explicitly present or clearly labeled on the document; otherwise use null.",
   "11) The items array must always be present (at least an empty array). Each item must follow the item schema exactly.",
   "12) Return ONLY a single valid JSON object as the response, with no extra text before or after.",
+  '13) For every non-null value you extract, add an entry to "field locations" with the page and the bounding box of the printed text. Omit entries for values you could not locate.',
+  '14) Report the invoice currency as an ISO 4217 code in "currency" only when a currency symbol, code or name is printed; otherwise use null.',
 ].join("\n");
 
 export const USER_PROMPT = `
@@ -181,17 +304,23 @@ Top-level keys include:
 - "seller name", "seller address", "seller gstin",
 - "buyer name", "buyer address", "buyer gstin",
 - "invoce number", "voucher number", "reference number",
-- "date", "reference date",
-- "voucher type", "place of supply",
+- "date", "reference date", "due date", "payment terms",
+- "irn", "signed qr code", "upi id", "iban",
+- "voucher type", "place of supply", "currency", "document type", "document language",
 - "subtotal", "tax total", "grand total",
-- and "items".
+- "items", "tax breakup",
+- and "field locations".
 
 Each element of "items" must be an object with keys:
 - "description", "name", "HSN/SAC",
 - "quantity", "unit",
 - "rate", "amount",
-- "cgst", "sgst",
-- "gst_rate".
+- "cgst", "sgst", "igst",
+- "cgst_rate", "sgst_rate", "igst_rate".
+
+Each element of "tax breakup" must be an object with keys "rate", "taxable value", "cgst", "sgst", "igst" and "cess".
+
+Each element of "field locations" must give the "field" key, the 1-based "page" and the "box" as [ymin, xmin, ymax, xmax] normalized to 0-1000.
 
 Follow all rules in SYSTEM_INSTRUCTION: do not invent values, use null when fields are missing, and return ONLY the JSON object with no additional text.
 `.trim();
@@ -0,0 +1,64 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface FieldCorrection {
+  id: number;
+  fileId: string;
+  fileName: string | null;
+  /** Path into the parsed details, e.g. `grand total` or `items[2].rate`. */
+  field: string;
+  originalValue: unknown;
+  correctedValue: unknown;
+  /** What produced the original value, e.g. `template:Acme` or `gemini:gemini-2.5-flash`. */
+  extractedBy: string | null;
+  editor: string | null;
+  role: string;
+  createdAt: string;
+}
+
+export interface CorrectionStat {
+  extractedBy: string | null;
+  /** Item fields are counted together, as `items[].rate`. */
+  field: string;
+  corrections: number;
+  files: number;
+}
+
+/** Fractions (0-1) of the page, measured from the top left. */
+export interface RegionRect {
+  xMin: number;
+  yMin: number;
+  xMax: number;
+  yMax: number;
+}
+
+export interface RegionText {
+  fileId: string;
+  page: number;
+  rect: RegionRect;
+  text: string;
+  /** `text_layer` for PDF text read directly, `ocr` when the crop went to the model. */
+  source: "text_layer" | "ocr";
+  cost: number;
+}
+
+export const CorrectionCommands = {
+  /** Resolves to null when the field already had this value. */
+  correct: (fileId: string, field: string, value: unknown, editor?: string) =>
+    invoke<FieldCorrection | null>("correct_parsed_field", {
+      fileId,
+      field,
+      value,
+      editor: editor ?? null,
+    }),
+  list: (fileId?: string, limit?: number) =>
+    invoke<FieldCorrection[]>("list_field_corrections", {
+      fileId: fileId ?? null,
+      limit: limit ?? null,
+    }),
+  /** `period` takes the report forms: `2024`, `2024-07`, `2024-Q2` or a date range. */
+  stats: (period?: string) =>
+    invoke<CorrectionStat[]>("get_correction_stats", { period: period ?? null }),
+  /** Reads the text inside `rect` on a 1-based `page`, for snipping a missed field. */
+  extractRegion: (fileId: string, page: number, rect: RegionRect) =>
+    invoke<RegionText>("extract_region", { fileId, page, rect }),
+};
@@ -0,0 +1,86 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type AnchorPosition = "right" | "below";
+
+export interface TemplateRule {
+  /** Key in the parsed details, e.g. `invoce number` or `grand total`. */
+  field: string;
+  /** Label the value is printed next to, matched case-insensitively. */
+  anchor?: string | null;
+  /** `right`: rest of the anchor's line; `below`: the next non-empty line. */
+  position?: AnchorPosition;
+  /** Regex on the anchored text, or the whole document; the first group is the value. */
+  pattern?: string | null;
+  numeric?: boolean;
+}
+
+export interface TemplateInput {
+  name: string;
+  sellerId: number | null;
+  /** Identifies the vendor's documents, typically their GSTIN or letterhead name. */
+  matchPattern: string;
+  rules: TemplateRule[];
+  /** Per-line regex with named groups: description, hsn, quantity, unit, rate, amount, ... */
+  itemPattern: string | null;
+  enabled?: boolean;
+}
+
+export interface ExtractionTemplate extends Required<Omit<TemplateInput, "rules">> {
+  id: number;
+  sellerName: string | null;
+  rules: TemplateRule[];
+  useCount: number;
+  lastUsedAt: string | null;
+  createdAt: string;
+  updatedAt: string;
+  /** Letterhead logo learned when the template was applied by hand. */
+  logoHash: string | null;
+}
+
+export interface TemplateMatch {
+  sellerId: number | null;
+  sellerName: string | null;
+  detectedBy: "gstin" | "logo" | "name" | null;
+  /** Null when the file goes to the generic model extraction. */
+  templateId: number | null;
+  templateName: string | null;
+  reason: string;
+  candidates: { templateId: number; name: string; sellerId: number | null; patternMatched: boolean }[];
+}
+
+export interface TemplateRun {
+  matched: boolean;
+  fields: { field: string; value: string | number | null }[];
+  itemCount: number;
+  complete: boolean;
+  parsedDetails: Record<string, unknown>;
+}
+
+export interface TemplateApplyResult {
+  templateId: number;
+  templateName: string;
+  fileId: string;
+  status: "applied" | "unchanged" | "conflict";
+  missingFields: string[];
+  itemCount: number;
+}
+
+export const ExtractionTemplateCommands = {
+  list: () => invoke<ExtractionTemplate[]>("list_extraction_templates"),
+  create: (template: TemplateInput) =>
+    invoke<ExtractionTemplate>("create_extraction_template", { template }),
+  update: (id: number, template: TemplateInput) =>
+    invoke<ExtractionTemplate>("update_extraction_template", { id, template }),
+  remove: (id: number) => invoke<void>("delete_extraction_template", { id }),
+  /** Which template parsing the file would use, and why. */
+  match: (fileId: string) => invoke<TemplateMatch>("get_template_match", { fileId }),
+  /** Dry run against a stored PDF; nothing is saved. */
+  test: (template: TemplateInput, fileId: string) =>
+    invoke<TemplateRun>("test_extraction_template", { template, fileId }),
+  /** Uses `templateId`, or whichever template matches each file; unmatched files are skipped. */
+  apply: (fileIds: string[], templateId?: number) =>
+    invoke<TemplateApplyResult[]>("apply_extraction_template", {
+      fileIds,
+      templateId: templateId ?? null,
+    }),
+};
@@ -0,0 +1,80 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface InvoiceRecord {
+  id: number;
+  fileId: string;
+  fileName: string;
+  sellerId: number | null;
+  sellerName: string | null;
+  buyerId: number | null;
+  buyerName: string | null;
+  invoiceNumber: string | null;
+  /** ISO date, when the extracted date could be normalized. */
+  invoiceDate: string | null;
+  dueDate: string | null;
+  voucherType: string | null;
+  placeOfSupply: string | null;
+  currency: string | null;
+  subtotal: number;
+  cgstTotal: number;
+  sgstTotal: number;
+  igstTotal: number;
+  cessTotal: number;
+  taxTotal: number;
+  grandTotal: number;
+  updatedAt: string;
+}
+
+export interface InvoiceLineItemRecord {
+  position: number;
+  description: string | null;
+  name: string | null;
+  hsnSac: string | null;
+  quantity: number | null;
+  unit: string | null;
+  rate: number | null;
+  amount: number | null;
+  cgst: number | null;
+  sgst: number | null;
+  cgstRate: number | null;
+  sgstRate: number | null;
+  igst: number | null;
+  igstRate: number | null;
+}
+
+/** Taxable value and GST components at one rate. */
+export interface TaxLine {
+  /** Total GST rate in percent, e.g. 18 for 9% CGST and 9% SGST. */
+  rate: number;
+  taxableValue: number;
+  cgst: number;
+  sgst: number;
+  igst: number;
+  cess: number;
+  /** Printed GST summary, line items grouped by rate, or the invoice totals. */
+  source: "printed" | "items" | "totals";
+}
+
+export interface InvoiceDetail extends InvoiceRecord {
+  lineItems: InvoiceLineItemRecord[];
+  taxLines: TaxLine[];
+}
+
+export interface InvoiceQuery {
+  /** `2024`, `2024-07`, `2024-Q2` or `2024-04-01..2024-06-30`. */
+  period?: string;
+  sellerId?: number;
+  buyerId?: number;
+  limit?: number;
+  offset?: number;
+}
+
+export const InvoiceCommands = {
+  /** Saves an extraction result and resolves to the structured invoice built from it. */
+  save: (fileId: string, parsedDetails: Record<string, unknown>) =>
+    invoke<InvoiceDetail>("save_parsed_invoice", { fileId, parsedDetails }),
+  get: (fileId: string) => invoke<InvoiceDetail | null>("get_invoice", { fileId }),
+  list: (query?: InvoiceQuery) => invoke<InvoiceRecord[]>("list_invoices", { query: query ?? null }),
+  /** Builds rows for parsed files imported before invoices were stored. */
+  sync: () => invoke<number>("sync_invoices"),
+};
@@ -0,0 +1,44 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type TableColumn =
+  | "serial"
+  | "description"
+  | "hsn_sac"
+  | "quantity"
+  | "unit"
+  | "rate"
+  | "amount"
+  | "other";
+
+export interface TableLineItem {
+  page: number;
+  description: string | null;
+  hsnSac: string | null;
+  quantity: number | null;
+  unit: string | null;
+  rate: number | null;
+  amount: number | null;
+}
+
+export interface DetectedTable {
+  page: number;
+  /** Columns came from the table's ruling lines rather than its header positions. */
+  ruled: boolean;
+  headers: string[];
+  columns: TableColumn[];
+  rows: number;
+}
+
+export interface TableExtraction {
+  fileId: string;
+  tables: DetectedTable[];
+  items: TableLineItem[];
+}
+
+/**
+ * Reads line items from the item table in a PDF's text layer. With `apply`, they replace
+ * the items in the file's parsed details.
+ */
+export function extractLineItems(fileId: string, apply = false) {
+  return invoke<TableExtraction>("extract_line_items", { fileId, apply });
+}
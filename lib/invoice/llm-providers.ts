@@ -0,0 +1,87 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { INVOICE_JSON_SCHEMA } from "./constants";
+import { loadActivePrompts } from "./prompts";
+
+export type LlmProvider = "gemini" | "openai" | "anthropic" | "ollama";
+
+export interface LlmProviderInfo {
+  provider: LlmProvider;
+  label: string;
+  model: string;
+  hasKey: boolean;
+  /** Runs on this machine through Ollama or a llama.cpp server; needs no key. */
+  local: boolean;
+  endpoint: string | null;
+  active: boolean;
+}
+
+export interface LocalModelHealth {
+  endpoint: string;
+  reachable: boolean;
+  models: string[];
+  latencyMs: number;
+  error: string | null;
+}
+
+export interface LlmParseResult {
+  fileId: string;
+  provider: LlmProvider;
+  model: string;
+  status: "applied" | "unchanged" | "conflict";
+  attempts: number;
+  promptTokens: number;
+  outputTokens: number;
+  cost: number;
+}
+
+export interface LlmRequestLog {
+  id: number;
+  fileId: string | null;
+  provider: LlmProvider;
+  model: string;
+  attempt: number;
+  request: Record<string, unknown> | null;
+  response: string | null;
+  error: string | null;
+  promptTokens: number;
+  outputTokens: number;
+  startedAt: string;
+  finishedAt: string | null;
+}
+
+/**
+ * Extracts a stored file in the backend with the given provider, or the configured one.
+ * Rate limits and server errors are retried there; every attempt is logged.
+ */
+export async function parseFileWithLlm(
+  fileId: string,
+  provider?: LlmProvider,
+): Promise<LlmParseResult> {
+  // Seeds the stored prompts from the bundled ones on first use.
+  await loadActivePrompts();
+  return invoke<LlmParseResult>("parse_file_with_llm", {
+    fileId,
+    provider: provider ?? null,
+    responseSchema: INVOICE_JSON_SCHEMA,
+  });
+}
+
+export const LlmProviderCommands = {
+  list: () => invoke<LlmProviderInfo[]>("list_llm_providers"),
+  setActive: (provider: LlmProvider, model?: string) =>
+    invoke<void>("set_llm_provider", { provider, model: model ?? null }),
+  setApiKey: (provider: LlmProvider, apiKey: string) =>
+    invoke<void>("set_llm_api_key", { provider, apiKey }),
+  clearApiKey: (provider: LlmProvider) => invoke<void>("clear_llm_api_key", { provider }),
+  listRequests: (fileId: string) => invoke<LlmRequestLog[]>("list_llm_requests", { fileId }),
+  checkLocal: (endpoint?: string) =>
+    invoke<LocalModelHealth>("check_local_llm", { endpoint: endpoint ?? null }),
+  /** Only localhost endpoints are accepted. Resolves to the saved endpoint. */
+  setLocalEndpoint: (endpoint: string | null) =>
+    invoke<string>("set_local_llm_endpoint", { endpoint }),
+  temperature: () => invoke<number>("get_llm_temperature"),
+  /** Between 0 and 2; null goes back to 0. Resolves to the saved temperature. */
+  setTemperature: (temperature: number | null) =>
+    invoke<number>("set_llm_temperature", { temperature }),
+};
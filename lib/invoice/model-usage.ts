@@ -1,4 +1,5 @@
-import { isTauriRuntime, getDatabase } from "@/lib/database";
+import { invoke } from "@tauri-apps/api/core";
+import { isTauriRuntime } from "@/lib/database";
 import { createLogger } from "@/lib/logger";
 import type { GeminiModelRateLimit } from "@/lib/invoice/model-catalog";
 
@@ -58,29 +59,37 @@ const withModelLock = async (model: string, task: () => Promise<void>) => {
   return next;
 };
 
+type StoredUsage = {
+  model: string;
+  day: string;
+  minuteWindowStart: number;
+  requestsMinute: number;
+  requestsDay: number;
+};
+
 const loadUsageRow = async (model: string): Promise<UsageRow | null> => {
-  const db = await getDatabase();
-  const rows = await db.select<UsageRow[]>(
-    "SELECT model, day, minute_window_start, requests_minute, requests_day FROM gemini_model_usage WHERE model = ?",
-    [model],
-  );
-  return rows[0] ?? null;
+  const stored = await invoke<StoredUsage | null>("get_gemini_model_usage", { model });
+  if (!stored) {
+    return null;
+  }
+  return {
+    model: stored.model,
+    day: stored.day,
+    minute_window_start: stored.minuteWindowStart,
+    requests_minute: stored.requestsMinute,
+    requests_day: stored.requestsDay,
+  };
 };
 
 const upsertUsageRow = async (row: UsageRow) => {
-  const db = await getDatabase();
-  await db.execute(
-    `INSERT INTO gemini_model_usage
-      (model, day, minute_window_start, requests_minute, requests_day)
-     VALUES (?, ?, ?, ?, ?)
-     ON CONFLICT(model) DO UPDATE SET
-       day = excluded.day,
-       minute_window_start = excluded.minute_window_start,
-       requests_minute = excluded.requests_minute,
-       requests_day = excluded.requests_day,
-       updated_at = CURRENT_TIMESTAMP`,
-    [row.model, row.day, row.minute_window_start, row.requests_minute, row.requests_day],
-  );
+  const usage: StoredUsage = {
+    model: row.model,
+    day: row.day,
+    minuteWindowStart: row.minute_window_start,
+    requestsMinute: row.requests_minute,
+    requestsDay: row.requests_day,
+  };
+  await invoke("save_gemini_model_usage", { usage });
 };
 
 export async function syncGeminiModelUsage(models: string[]): Promise<void> {
@@ -88,20 +97,8 @@ export async function syncGeminiModelUsage(models: string[]): Promise<void> {
     return;
   }
 
-  const db = await getDatabase();
-  const now = Date.now();
-  const today = toPacificDayKey(new Date());
-
   try {
-    for (const model of models) {
-      await db.execute(
-        `INSERT INTO gemini_model_usage
-          (model, day, minute_window_start, requests_minute, requests_day)
-         VALUES (?, ?, ?, 0, 0)
-         ON CONFLICT(model) DO NOTHING`,
-        [model, today, now],
-      );
-    }
+    await invoke("sync_gemini_model_usage", { models });
   } catch (error) {
     usageLogger.warn("Failed to sync Gemini model usage records", { error });
   }
@@ -0,0 +1,41 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** `document` is reading the file, its text layer or OCR; `model` is the provider or template. */
+export type ParseStage = "document" | "model" | "save";
+
+export interface LatencyStats {
+  p50Ms: number;
+  p95Ms: number;
+  maxMs: number;
+}
+
+export interface ProviderMetrics {
+  /** null for the totals across every provider; `template` for extraction templates. */
+  provider: string | null;
+  parses: number;
+  succeeded: number;
+  conflicts: number;
+  failed: number;
+  waitingForNetwork: number;
+  cancelled: number;
+  /** From 0 to 1; conflicts count as successes and cancelled parses are left out. */
+  successRate: number;
+  averageAttempts: number;
+  total: LatencyStats;
+  document: LatencyStats;
+  model: LatencyStats;
+  save: LatencyStats;
+  /** The stage with the highest median time. */
+  bottleneck: ParseStage | null;
+}
+
+export interface ParsingMetrics {
+  overall: ProviderMetrics;
+  byProvider: ProviderMetrics[];
+}
+
+export const ParseMetricsCommands = {
+  /** `period` takes the report forms: `2024`, `2024-07`, `2024-Q2` or a date range. */
+  get: (period?: string) =>
+    invoke<ParsingMetrics>("get_parsing_metrics", { period: period ?? null }),
+};
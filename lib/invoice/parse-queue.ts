@@ -0,0 +1,150 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { INVOICE_JSON_SCHEMA } from "./constants";
+import type { LlmProvider } from "./llm-providers";
+import { loadActivePrompts } from "./prompts";
+
+export const PARSE_QUEUE_PROGRESS_EVENT = "parse-queue://progress";
+export const PARSE_FILES_PROGRESS_EVENT = "parse-files://progress";
+
+export type ParseJobStatus = "queued" | "running" | "succeeded" | "failed" | "cancelled";
+
+export interface ParseJob {
+  id: number;
+  fileId: string;
+  fileName: string | null;
+  /** Provider the job was queued for; the configured one when null. */
+  provider: LlmProvider | null;
+  status: ParseJobStatus;
+  attempts: number;
+  /** When a failed job is tried again; only set while it waits. */
+  nextRetryAt: string | null;
+  lastError: string | null;
+  enqueuedAt: string;
+  startedAt: string | null;
+  finishedAt: string | null;
+}
+
+export interface ParseQueueStatus {
+  queued: number;
+  /** Queued jobs waiting out their retry backoff. */
+  retrying: number;
+  running: number;
+  succeeded: number;
+  failed: number;
+  cancelled: number;
+  workers: number;
+  maxAttempts: number;
+  paused: boolean;
+}
+
+export interface ParseQueueProgress {
+  jobId: number;
+  fileId: string;
+  status: ParseJobStatus;
+  attempt: number;
+  error: string | null;
+  nextRetryAt: string | null;
+  queued: number;
+  running: number;
+}
+
+export type BatchParseFileStatus =
+  | "queued"
+  | "running"
+  | "applied"
+  | "unchanged"
+  | "conflict"
+  | "failed"
+  | "cancelled"
+  | "skipped";
+
+export interface BatchParseFile {
+  fileId: string;
+  fileName: string | null;
+  status: BatchParseFileStatus;
+  /** `template:<name>` or `<provider>:<model>`. */
+  extractedBy: string | null;
+  error: string | null;
+  durationMs: number;
+}
+
+export interface BatchParseProgress extends BatchParseFile {
+  batchId: string;
+  completed: number;
+  total: number;
+}
+
+export interface BatchParseSummary {
+  batchId: string;
+  total: number;
+  applied: number;
+  unchanged: number;
+  conflicts: number;
+  failed: number;
+  cancelled: number;
+  skipped: number;
+  /** In the order the files were given. */
+  files: BatchParseFile[];
+}
+
+/**
+ * Queue files for extraction by the backend worker pool. Resolves to the number of jobs
+ * added; files already queued or running are skipped.
+ */
+export async function enqueueParseJobs(fileIds: string[], provider?: LlmProvider) {
+  // Seeds the stored prompts from the bundled ones on first use.
+  await loadActivePrompts();
+  return invoke<number>("enqueue_parse_jobs", {
+    fileIds,
+    provider: provider ?? null,
+    responseSchema: INVOICE_JSON_SCHEMA,
+  });
+}
+
+export async function enqueueUnprocessedFiles(provider?: LlmProvider) {
+  await loadActivePrompts();
+  return invoke<number>("enqueue_unprocessed_files", {
+    provider: provider ?? null,
+    responseSchema: INVOICE_JSON_SCHEMA,
+  });
+}
+
+/**
+ * Extract files right away, `concurrency` at a time (the worker count by default).
+ * Resolves once every file is done; listen with `onParseFilesProgress` for a live view.
+ */
+export async function parseFiles(fileIds: string[], provider?: LlmProvider, concurrency?: number) {
+  await loadActivePrompts();
+  return invoke<BatchParseSummary>("parse_files", {
+    fileIds,
+    provider: provider ?? null,
+    responseSchema: INVOICE_JSON_SCHEMA,
+    concurrency: concurrency ?? null,
+  });
+}
+
+export const ParseQueueCommands = {
+  status: () => invoke<ParseQueueStatus>("get_parse_queue_status"),
+  list: (status?: ParseJobStatus, limit?: number) =>
+    invoke<ParseJob[]>("list_parse_jobs", { status: status ?? null, limit: limit ?? null }),
+  /** Cancels queued jobs for `fileIds`, or every queued job. */
+  cancel: (fileIds?: string[]) => invoke<number>("cancel_parse_jobs", { fileIds: fileIds ?? null }),
+  /** Stops one job; a running one is aborted and its file goes back to Unprocessed. */
+  cancelJob: (jobId: number) => invoke<void>("cancel_parse", { jobId }),
+  setWorkers: (count: number) => invoke<void>("set_parse_workers", { count }),
+  setRetryLimit: (maxAttempts: number) => invoke<void>("set_parse_retry_limit", { maxAttempts }),
+  /** Retries Failed files now, skipping any remaining backoff. */
+  retryFailed: (fileIds?: string[], provider?: LlmProvider) =>
+    invoke<number>("retry_failed_files", { fileIds: fileIds ?? null, provider: provider ?? null }),
+};
+
+export async function onParseQueueProgress(handler: (progress: ParseQueueProgress) => void) {
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<ParseQueueProgress>(PARSE_QUEUE_PROGRESS_EVENT, (event) => handler(event.payload));
+}
+
+export async function onParseFilesProgress(handler: (progress: BatchParseProgress) => void) {
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<BatchParseProgress>(PARSE_FILES_PROGRESS_EVENT, (event) => handler(event.payload));
+}
@@ -0,0 +1,41 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** A seller or buyer from the master tables parsing fills in. */
+export interface PartyRecord {
+  id: number;
+  /** Name the party is shown, filtered and reported under. */
+  name: string;
+  taxId: string | null;
+  address: string | null;
+  /** Spellings that resolve to this party, e.g. "ACME PRIVATE LIMITED" for "Acme Pvt Ltd". */
+  aliases: string[];
+  fileCount: number;
+  createdAt: string;
+}
+
+export const SellerCommands = {
+  list: () => invoke<PartyRecord[]>("list_sellers"),
+  /** Folds `ids` into `canonicalId`; their names become aliases of it. */
+  merge: (ids: number[], canonicalId: number) =>
+    invoke<void>("merge_sellers", { ids, canonicalId }),
+  addAlias: (sellerId: number, alias: string) =>
+    invoke<void>("add_seller_alias", { sellerId, alias }),
+  removeAlias: (alias: string) => invoke<void>("remove_seller_alias", { alias }),
+  /** The old name stays an alias. */
+  rename: (sellerId: number, name: string) => invoke<void>("rename_seller", { sellerId, name }),
+  /** Links parsed files that have no seller yet; resolves to how many were linked. */
+  sync: () => invoke<number>("sync_sellers"),
+};
+
+/** Buyers are the entities invoices are billed to, e.g. each company whose books are kept here. */
+export const BuyerCommands = {
+  list: () => invoke<PartyRecord[]>("list_buyers"),
+  /** Folds `ids` into `canonicalId`; their names become aliases of it. */
+  merge: (ids: number[], canonicalId: number) => invoke<void>("merge_buyers", { ids, canonicalId }),
+  addAlias: (buyerId: number, alias: string) => invoke<void>("add_buyer_alias", { buyerId, alias }),
+  removeAlias: (alias: string) => invoke<void>("remove_buyer_alias", { alias }),
+  /** The old name stays an alias. */
+  rename: (buyerId: number, name: string) => invoke<void>("rename_buyer", { buyerId, name }),
+  /** Links parsed files that have no buyer yet; resolves to how many were linked. */
+  sync: () => invoke<number>("sync_buyers"),
+};
@@ -1,9 +1,9 @@
 import type { GenerateContentResponse } from "@google/genai";
 import { GoogleGenAI } from "@google/genai";
 
-import { USER_PROMPT } from "./constants";
 import { getGeminiDefaultModel, getGeminiModelRateLimits } from "./model-catalog";
 import { claimGeminiModelRequest } from "./model-usage";
+import { type ActivePrompts, loadActivePrompts } from "./prompts";
 import {
   ExtractionPayload,
   GENERATION_CONFIG,
@@ -17,15 +17,22 @@ import {
 
 export type { ExtractionPayload, InvoiceExtractionResult, InvoiceFileInput } from "./helpers";
 
+export interface GeminiTokenUsage {
+  promptTokens: number;
+  outputTokens: number;
+}
+
 interface GeminiGeneratedResult {
   file: string;
   result: ExtractionPayload;
+  usage: GeminiTokenUsage;
 }
 
 export class GeminiInvoiceClient {
   constructor(
     private readonly ai: GoogleGenAI,
     private readonly model: string,
+    private readonly prompts: ActivePrompts,
   ) {}
 
   async generate(file: NormalizedFile): Promise<GeminiGeneratedResult> {
@@ -40,15 +47,22 @@ export class GeminiInvoiceClient {
         {
           role: "user",
           parts: [
-            { text: USER_PROMPT },
+            { text: this.prompts.userPrompt },
             { inlineData: { data: inlineData.data, mimeType: inlineData.mimeType } },
           ],
         },
       ],
-      config: GENERATION_CONFIG,
+      config: { ...GENERATION_CONFIG, systemInstruction: this.prompts.systemInstruction },
     });
 
-    return { file: file.label, result: parseResponse(response) };
+    return {
+      file: file.label,
+      result: parseResponse(response),
+      usage: {
+        promptTokens: response.usageMetadata?.promptTokenCount ?? 0,
+        outputTokens: response.usageMetadata?.candidatesTokenCount ?? 0,
+      },
+    };
   }
 }
 
@@ -93,6 +107,7 @@ export async function processInvoices({
   const client = new GeminiInvoiceClient(
     new GoogleGenAI({ apiKey: ensureApiKey(apiKey) }),
     resolvedModel,
+    await loadActivePrompts(),
   );
   return Promise.all(normalized.map((file) => client.generate(file)));
 }
@@ -0,0 +1,66 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { isTauriRuntime } from "@/lib/database";
+import { INVOICE_JSON_SCHEMA, SYSTEM_INSTRUCTION, USER_PROMPT } from "./constants";
+
+export type PromptKind = "system_instruction" | "user_prompt";
+
+export interface ActivePrompts {
+  systemInstruction: string;
+  userPrompt: string;
+}
+
+export interface PromptTemplate {
+  id: number;
+  kind: PromptKind;
+  version: number;
+  content: string;
+  notes: string | null;
+  isActive: boolean;
+  createdAt: string;
+}
+
+export interface PromptTestResult {
+  templateId: number;
+  version: number;
+  output: Record<string, unknown>;
+  current: Record<string, unknown> | null;
+  changedFields: string[];
+  promptTokens: number;
+  outputTokens: number;
+}
+
+const BUILT_IN_PROMPTS: ActivePrompts = {
+  systemInstruction: SYSTEM_INSTRUCTION,
+  userPrompt: USER_PROMPT,
+};
+
+/**
+ * Prompts to extract with: the active stored versions, seeded from the bundled
+ * prompts on first use. Outside the desktop runtime the bundled prompts are used as is.
+ */
+export async function loadActivePrompts(): Promise<ActivePrompts> {
+  if (!isTauriRuntime()) {
+    return BUILT_IN_PROMPTS;
+  }
+  return invoke<ActivePrompts>("get_active_prompts", { defaults: BUILT_IN_PROMPTS });
+}
+
+/**
+ * Runs a stored prompt version against a file without saving the result, using the same
+ * response schema as real extractions.
+ */
+export async function testPromptTemplate(
+  id: number,
+  fileId: string,
+  model: string,
+  apiKey: string,
+): Promise<PromptTestResult> {
+  return invoke<PromptTestResult>("test_prompt_template", {
+    id,
+    fileId,
+    apiKey,
+    model,
+    responseSchema: INVOICE_JSON_SCHEMA,
+  });
+}
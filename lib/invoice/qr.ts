@@ -0,0 +1,40 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type QrPayloadKind = "EInvoice" | "Upi" | "Epc";
+
+export interface ScannedQr {
+  /** Null for codes that are not an e-invoice, UPI or EPC payload. */
+  kind: QrPayloadKind | null;
+  content: string;
+  /** 1-based page, for PDFs and multi-page TIFFs. */
+  page: number | null;
+  irn: string | null;
+  sellerGstin: string | null;
+  buyerGstin: string | null;
+  invoiceNumber: string | null;
+  /** ISO date. */
+  invoiceDate: string | null;
+  grandTotal: number | null;
+  itemCount: number | null;
+  upiId: string | null;
+  iban: string | null;
+}
+
+export interface QrMismatch {
+  /** Parsed-details key, e.g. `grand total`. */
+  field: string;
+  qrValue: string;
+  extractedValue: string;
+  message: string;
+}
+
+export interface FileQrCodes {
+  fileId: string;
+  codes: ScannedQr[];
+  mismatches: QrMismatch[];
+}
+
+export const QrCommands = {
+  /** Decodes the file's QR codes again and re-checks them against the extracted fields. */
+  scan: (fileId: string) => invoke<FileQrCodes>("scan_file_qr_codes", { fileId }),
+};
@@ -0,0 +1,32 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import type { LlmProvider } from "./llm-providers";
+
+export interface SemanticHit {
+  fileId: string;
+  fileName: string;
+  sellerName: string | null;
+  invoiceNumber: string | null;
+  /** Cosine similarity to the query, up to 1. */
+  score: number;
+}
+
+export interface SemanticIndexStatus {
+  provider: LlmProvider;
+  model: string;
+  /** Parsed files with an up-to-date embedding. */
+  indexed: number;
+  total: number;
+}
+
+export const SemanticSearchCommands = {
+  /** Invoices closest in meaning to `query`; files parsed since the last search are embedded first. */
+  search: (query: string, limit?: number) =>
+    invoke<SemanticHit[]>("semantic_search", { query, limit: limit ?? null }),
+  /** Embeds every parsed file not indexed yet. Resolves to how many were embedded. */
+  buildIndex: () => invoke<number>("build_semantic_index"),
+  status: () => invoke<SemanticIndexStatus>("get_semantic_index_status"),
+  /** `null` follows the extraction provider. */
+  setProvider: (provider: LlmProvider | null) =>
+    invoke<void>("set_semantic_search_provider", { provider }),
+};
@@ -10,6 +10,24 @@ export type InvoiceItem = {
   sgst: number | null;
   cgst_rate: number | null;
   sgst_rate: number | null;
+  igst?: number | null;
+  igst_rate?: number | null;
+};
+
+/** One row of the printed GST summary table. */
+export type InvoiceTaxBreakup = {
+  rate: number | null;
+  "taxable value"?: number | null;
+  cgst?: number | null;
+  sgst?: number | null;
+  igst?: number | null;
+  cess?: number | null;
+};
+
+export type FieldLocation = {
+  field: string;
+  page: number;
+  box: [number, number, number, number];
 };
 
 export type InvoiceExtractionResult = {
@@ -27,10 +45,19 @@ export type InvoiceExtractionResult = {
   "reference number"?: string | null;
   date: string | null;
   "reference date"?: string | null;
+  "due date"?: string | null;
+  "payment terms"?: string | null;
+
+  // E-invoice & payment details
+  irn?: string | null;
+  "signed qr code"?: string | null;
+  "upi id"?: string | null;
+  iban?: string | null;
 
   // Classification
   "voucher type"?: string | null;
   "place of supply"?: string | null;
+  currency?: string | null;
 
   // Totals
   subtotal?: number | null;
@@ -39,6 +66,14 @@ export type InvoiceExtractionResult = {
 
   // Line items
   items?: InvoiceItem[];
+  "tax breakup"?: InvoiceTaxBreakup[];
+
+  // Provenance
+  "field locations"?: FieldLocation[];
+
+  /** Shape version, stamped by the backend when details are stored. Older payloads are
+   * upgraded to the current version when they are read. */
+  schema_version?: number;
 };
 
 export type ExtractionPayload = InvoiceExtractionResult | { _raw: string };
@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface UsageTotals {
+  requests: number;
+  promptTokens: number;
+  outputTokens: number;
+  /** Estimated USD at the prices in effect when each request was recorded. */
+  cost: number;
+}
+
+export interface UsageBreakdown extends UsageTotals {
+  name: string;
+}
+
+export interface MonthlyCost extends UsageTotals {
+  /** `YYYY-MM`. */
+  month: string;
+  byModel: UsageBreakdown[];
+}
+
+export interface ParseCostSummary {
+  start: string;
+  end: string;
+  totals: UsageTotals;
+  /** Months with at least one request, oldest first. */
+  months: MonthlyCost[];
+}
+
+/** USD per million tokens for models whose name starts with `model`. */
+export interface ModelPrice {
+  model: string;
+  inputPerMillion: number;
+  outputPerMillion: number;
+  /** Entered in settings rather than one of the bundled list prices. */
+  custom?: boolean;
+}
+
+export const UsageCommands = {
+  /** `period` takes the report forms: `2024`, `2024-07`, `2024-Q2` or a date range. */
+  costSummary: (period: string) =>
+    invoke<ParseCostSummary>("get_parse_cost_summary", { period }),
+  pricing: () => invoke<ModelPrice[]>("get_model_pricing"),
+  /** Replaces the prices entered in settings; an empty list goes back to the bundled prices. */
+  setPricing: (prices: ModelPrice[]) => invoke<ModelPrice[]>("set_model_pricing", { prices }),
+};
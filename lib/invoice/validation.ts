@@ -0,0 +1,54 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface ArithmeticIssue {
+  /** `line_amount`, `line_cgst`, `line_sgst`, `subtotal`, `tax_total` or `grand_total`. */
+  check: string;
+  field: string;
+  computed: number;
+  stated: number;
+  difference: number;
+  message: string;
+}
+
+export interface FileValidation {
+  fileId: string;
+  fileName: string;
+  /** Null when the file has no parsed details to check. */
+  valid: boolean | null;
+  errors: ArithmeticIssue[];
+}
+
+export const ValidationCommands = {
+  /** Re-checks line sums, tax math and totals; `tolerance` is in invoice currency. */
+  validate: (fileId: string, tolerance?: number) =>
+    invoke<FileValidation>("validate_file", { fileId, tolerance: tolerance ?? null }),
+  listWithErrors: () => invoke<FileValidation[]>("list_files_with_validation_errors"),
+};
+
+export type TaxIdKind = "Gstin" | "EuVat" | "Ein" | "Unknown";
+
+export interface TaxIdCheck {
+  /** Parsed-details field the identifier came from, e.g. `seller gstin`. */
+  field: string;
+  value: string;
+  normalized: string;
+  kind: TaxIdKind;
+  valid: boolean;
+  /** False when only the format was checked, as for EINs and VAT numbers without a check digit. */
+  checkDigitVerified: boolean;
+  message: string | null;
+}
+
+export interface FileTaxIdValidation {
+  fileId: string;
+  fileName: string;
+  /** Null when the file has no tax identifiers to check. */
+  valid: boolean | null;
+  checks: TaxIdCheck[];
+}
+
+export const TaxIdCommands = {
+  validateFile: (fileId: string) => invoke<TaxIdCheck[]>("validate_file_tax_ids", { fileId }),
+  /** Checks GSTIN, VAT and EIN numbers across files, e.g. before filing a return. */
+  validate: (fileIds: string[]) => invoke<FileTaxIdValidation[]>("validate_tax_ids", { fileIds }),
+};
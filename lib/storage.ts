@@ -8,6 +8,26 @@ export interface StorageStats {
   fileCount: number;
 }
 
+export interface BackupResult {
+  path: string;
+  sizeBytes: number;
+  createdAt: string;
+}
+
+export interface DatabaseEncryptionStatus {
+  encrypted: boolean;
+  /** Whether the OS keyring holds a passphrase for the database. */
+  passphraseStored: boolean;
+}
+
+export interface RestoreReport {
+  backupPath: string;
+  /** The database as it was before the restore. */
+  safetyCopyPath: string;
+  /** Rows per table; 0 where the table did not exist. */
+  tables: { table: string; before: number; after: number }[];
+}
+
 const ensureTauri = () => {
   if (!isTauriRuntime()) {
     throw new Error("Storage details are only available inside the desktop shell.");
@@ -19,7 +39,35 @@ export async function getStorageStats() {
   return invoke<StorageStats>("get_storage_stats");
 }
 
-export async function clearStoredFiles() {
+export async function clearStoredFiles(pin?: string) {
+  ensureTauri();
+  return invoke<StorageStats>("clear_processed_files", { pin: pin ?? null });
+}
+
+/** Copies the database to `targetPath`; imports and parsing can carry on meanwhile. */
+export async function backupDatabase(targetPath: string) {
+  ensureTauri();
+  return invoke<BackupResult>("backup_database", { targetPath });
+}
+
+/** Replaces the database with a backup, after saving a copy of the current one. */
+export async function restoreDatabase(backupPath: string, pin?: string) {
+  ensureTauri();
+  return invoke<RestoreReport>("restore_database", { backupPath, pin: pin ?? null });
+}
+
+export async function getDatabaseEncryption() {
+  ensureTauri();
+  return invoke<DatabaseEncryptionStatus>("get_database_encryption");
+}
+
+/** Encrypts the database, or changes its passphrase; the passphrase is kept in the OS keyring. */
+export async function encryptDatabase(passphrase: string, pin?: string) {
+  ensureTauri();
+  return invoke<DatabaseEncryptionStatus>("encrypt_database", { passphrase, pin: pin ?? null });
+}
+
+export async function decryptDatabase(pin?: string) {
   ensureTauri();
-  return invoke<StorageStats>("clear_processed_files");
+  return invoke<DatabaseEncryptionStatus>("decrypt_database", { pin: pin ?? null });
 }
@@ -0,0 +1,102 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import { isTauriRuntime } from "../database";
+import type { DocType } from "../files/types";
+import type { ExportRecord } from "./index";
+
+export type ExportProfileFormat = "xlsx" | "csv" | "tally_xml";
+
+export interface ColumnMapping {
+  /** Invoice sheet header, e.g. "Invoice Number". */
+  source: string;
+  header?: string | null;
+}
+
+export interface ExportLocaleOptions {
+  delimiter: "comma" | "semicolon" | "tab";
+  decimalSeparator: "dot" | "comma";
+  dateFormat: string;
+  encoding: "utf8" | "utf8_bom";
+}
+
+export interface ExportProfile {
+  id: number;
+  name: string;
+  format: ExportProfileFormat;
+  columns: ColumnMapping[];
+  localeOptions: ExportLocaleOptions;
+  destination: string | null;
+  namingTemplate: string;
+  createdAt: string;
+  updatedAt: string;
+  lastRunAt: string | null;
+}
+
+export interface ExportProfileInput {
+  name: string;
+  format: ExportProfileFormat;
+  columns?: ColumnMapping[];
+  localeOptions?: Partial<ExportLocaleOptions>;
+  destination?: string | null;
+  /** File name without extension; supports {profile}, {period} and {date}. */
+  namingTemplate: string;
+}
+
+export interface ExportFilter {
+  period?: string;
+  status?: string;
+  sellerId?: number;
+  buyerId?: number;
+  /** Document language as a Tesseract code, e.g. "deu". */
+  language?: string;
+  /** E.g. only "invoice", to keep credit notes out of an invoice sheet. */
+  docType?: DocType;
+}
+
+export interface ExportRunResult {
+  profileId: number;
+  path: string;
+  fileCount: number;
+  export: ExportRecord | null;
+  /** Set for Tally XML runs; the XML is saved by the scheduled export listener. */
+  xmlId: number | null;
+}
+
+const requireRuntime = () => {
+  if (!isTauriRuntime()) {
+    throw new Error("Export profiles require the Invox desktop runtime.");
+  }
+};
+
+export async function listExportProfiles(): Promise<ExportProfile[]> {
+  if (!isTauriRuntime()) {
+    return [];
+  }
+  return invoke<ExportProfile[]>("list_export_profiles");
+}
+
+export async function createExportProfile(profile: ExportProfileInput): Promise<ExportProfile> {
+  requireRuntime();
+  return invoke<ExportProfile>("create_export_profile", { profile });
+}
+
+export async function updateExportProfile(
+  profileId: number,
+  profile: ExportProfileInput,
+): Promise<ExportProfile> {
+  requireRuntime();
+  return invoke<ExportProfile>("update_export_profile", { profileId, profile });
+}
+
+export async function deleteExportProfile(profileId: number): Promise<void> {
+  requireRuntime();
+  await invoke("delete_export_profile", { profileId });
+}
+
+/**
+ * Run a saved profile against the processed invoices matching `filter`.
+ */
+export async function runExport(profileId: number, filter?: ExportFilter): Promise<ExportRunResult> {
+  requireRuntime();
+  return invoke<ExportRunResult>("run_export", { profileId, filter: filter ?? null });
+}
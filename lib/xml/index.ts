@@ -25,6 +25,17 @@ export async function createXmlForFiles(fileIds: string[], xmlName: string): Pro
   return invoke<number>("create_xml_for_files", { fileIds, xmlName });
 }
 
+/**
+ * Create a new XML export with every processed file billed to a buyer
+ */
+export async function createXmlForBuyer(buyerId: number, xmlName: string): Promise<number> {
+  if (!isTauriRuntime()) {
+    throw new Error("XML creation is only available inside the desktop shell.");
+  }
+
+  return invoke<number>("create_xml_for_buyer", { buyerId, xmlName });
+}
+
 /**
  * List existing XML files
  */
@@ -45,6 +56,26 @@ export async function appendXmlFile(xmlId: number, fileIds: string[]): Promise<v
   await invoke("append_xml_file", { xmlId, fileIds });
 }
 
+/**
+ * Rename an XML export sheet
+ */
+export async function renameXmlFile(xmlId: number, xmlName: string): Promise<void> {
+  if (!isTauriRuntime()) {
+    throw new Error("XML updates are only available inside the desktop shell.");
+  }
+  await invoke("rename_xml_file", { xmlId, xmlName });
+}
+
+/**
+ * Delete an XML export sheet; its files are kept
+ */
+export async function deleteXmlFile(xmlId: number): Promise<void> {
+  if (!isTauriRuntime()) {
+    throw new Error("XML updates are only available inside the desktop shell.");
+  }
+  await invoke("delete_xml_file", { xmlId });
+}
+
 /**
  * Generate and download XML file
  */
@@ -54,3 +85,86 @@ export async function generateXmlFile(xmlId: number): Promise<XmlDownloadRespons
   }
   return invoke<XmlDownloadResponse>("generate_xml_file", { xmlId });
 }
+
+export interface ExportRecord {
+  id: string;
+  kind: string;
+  format: string;
+  path: string;
+  sha256: string;
+  sizeBytes: number;
+  rowCount: number;
+  files: { fileId: string; invoiceNumber: string | null }[];
+  createdAt: string;
+}
+
+export interface ExportVerification {
+  exportId: string;
+  path: string;
+  status: "intact" | "modified" | "missing";
+  expectedSha256: string;
+  actualSha256: string | null;
+  checkedAt: string;
+}
+
+/**
+ * Save generated Tally XML for a sheet and record its checksum manifest
+ */
+export async function saveXmlExport(
+  xmlId: number,
+  path: string,
+  content: string,
+): Promise<ExportRecord> {
+  if (!isTauriRuntime()) {
+    throw new Error("Saving XML is only available inside the desktop shell.");
+  }
+  return invoke<ExportRecord>("save_xml_export", { xmlId, path, content });
+}
+
+/**
+ * List recorded exports, most recent first
+ */
+export async function listExports(limit?: number): Promise<ExportRecord[]> {
+  if (!isTauriRuntime()) {
+    return [];
+  }
+  return invoke<ExportRecord[]>("list_exports", { limit: limit ?? null });
+}
+
+/**
+ * Check an export against the SHA-256 recorded when it was written
+ */
+export async function verifyExport(exportId: string): Promise<ExportVerification> {
+  if (!isTauriRuntime()) {
+    throw new Error("Export verification is only available inside the desktop shell.");
+  }
+  return invoke<ExportVerification>("verify_export", { exportId });
+}
+
+export interface ExportBundleResult {
+  path: string;
+  invoices: number;
+  documentsCopied: number;
+  skippedFileIds: string[];
+  export: ExportRecord;
+}
+
+/**
+ * Export an audit bundle: the XML or XLSX plus the source documents and a manifest.
+ * For XML bundles pass the generated Tally XML, with vouchers in `fileIds` order.
+ */
+export async function exportBundle(options: {
+  fileIds: string[];
+  format: "xml" | "xlsx";
+  outputPath: string;
+  xmlContent?: string;
+  zip: boolean;
+}): Promise<ExportBundleResult> {
+  if (!isTauriRuntime()) {
+    throw new Error("Export bundles are only available inside the desktop shell.");
+  }
+  return invoke<ExportBundleResult>("export_bundle", {
+    ...options,
+    xmlContent: options.xmlContent ?? null,
+  });
+}
@@ -0,0 +1,14 @@
+use crate::db::get_connection;
+use crate::services::audit_log::{AuditChainReport, AuditEntry, AuditLog};
+
+#[tauri::command]
+pub fn list_audit_log(limit: Option<i64>, offset: Option<i64>) -> Result<Vec<AuditEntry>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AuditLog::list(&conn, limit.unwrap_or(100), offset.unwrap_or(0))
+}
+
+#[tauri::command]
+pub fn verify_audit_chain() -> Result<AuditChainReport, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AuditLog::verify(&conn)
+}
@@ -0,0 +1,45 @@
+use crate::db::get_connection;
+use crate::services::{
+    app_lock::AppLock,
+    audit_log::AuditLog,
+    database_backup::{BackupResult, DatabaseBackup, RestoreReport},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use std::path::Path;
+
+/// Copies the database to `target_path` while the app keeps running.
+#[tauri::command(async)]
+pub fn backup_database(target_path: String) -> Result<BackupResult, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let result = DatabaseBackup::create(&conn, Path::new(&target_path))?;
+    AuditLog::record(
+        &conn,
+        "database.backed_up",
+        None,
+        Some(serde_json::json!({ "path": result.path, "sizeBytes": result.size_bytes })),
+    )?;
+    Ok(result)
+}
+
+/// Replaces the database with a backup, after saving a copy of the current one.
+#[tauri::command(async)]
+pub fn restore_database(backup_path: String, pin: Option<String>) -> Result<RestoreReport, String> {
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.restore_database")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    let report = DatabaseBackup::restore(&mut conn, Path::new(&backup_path))?;
+
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AuditLog::record(
+        &conn,
+        "database.restored",
+        None,
+        Some(serde_json::json!({
+            "backupPath": report.backup_path,
+            "safetyCopyPath": report.safety_copy_path,
+        })),
+    )?;
+    Ok(report)
+}
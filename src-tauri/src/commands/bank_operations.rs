@@ -0,0 +1,182 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    bank_reconciliation::{BankMatcher, StatementParser},
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankImportResult {
+    pub statement_id: i64,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub suggested: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankLineRow {
+    pub id: i64,
+    pub statement_id: i64,
+    pub posted_on: String,
+    pub amount: f64,
+    pub description: Option<String>,
+    pub reference: Option<String>,
+    pub match_status: String,
+    pub suggested_file_id: Option<String>,
+    pub suggested_file_name: Option<String>,
+    pub match_score: Option<f64>,
+}
+
+/// Imports a CSV or OFX bank statement and suggests invoice matches for its lines.
+#[tauri::command]
+pub fn import_bank_statement(path: String, locale: Option<String>) -> Result<BankImportResult, String> {
+    let content = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+    let file_name = Path::new(&path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("statement")
+        .to_string();
+    let lines = StatementParser::parse(&file_name, &content, locale.as_deref())?;
+
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let tx = conn.transaction().map_err(|error| error.to_string())?;
+
+    tx.execute(
+        "INSERT INTO bank_statements (file_name, source_path) VALUES (?1, ?2)",
+        params![file_name, path],
+    )
+    .map_err(|error| error.to_string())?;
+    let statement_id = tx.last_insert_rowid();
+    Telemetry::record_feature(&tx, "bank_statement_import");
+
+    let mut imported = 0;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR IGNORE INTO bank_statement_lines
+                   (statement_id, line_key, posted_on, amount, description, reference)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|error| error.to_string())?;
+        for line in &lines {
+            imported += stmt
+                .execute(params![
+                    statement_id,
+                    line.line_key(),
+                    line.posted_on,
+                    line.amount,
+                    line.description,
+                    line.reference
+                ])
+                .map_err(|error| error.to_string())?;
+        }
+    }
+
+    tx.commit().map_err(|error| error.to_string())?;
+    let suggested = BankMatcher::suggest(&conn)?;
+
+    Ok(BankImportResult {
+        statement_id,
+        imported,
+        duplicates: lines.len() - imported,
+        suggested,
+    })
+}
+
+/// Statement lines that are not confirmed yet, with the best suggested invoice if any.
+#[tauri::command]
+pub fn list_unmatched() -> Result<Vec<BankLineRow>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.id, l.statement_id, l.posted_on, l.amount, l.description, l.reference,
+                    l.match_status, l.matched_file_id, f.file_name, l.match_score
+             FROM bank_statement_lines l
+             LEFT JOIN files f ON f.id = l.matched_file_id
+             WHERE l.match_status <> 'Confirmed'
+             ORDER BY l.posted_on DESC, l.id DESC",
+        )
+        .map_err(|error| error.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(BankLineRow {
+                id: row.get(0)?,
+                statement_id: row.get(1)?,
+                posted_on: row.get(2)?,
+                amount: row.get(3)?,
+                description: row.get(4)?,
+                reference: row.get(5)?,
+                match_status: row.get(6)?,
+                suggested_file_id: row.get(7)?,
+                suggested_file_name: row.get(8)?,
+                match_score: row.get(9)?,
+            })
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    Ok(rows)
+}
+
+/// Confirms that a statement line pays an invoice and records the payment.
+#[tauri::command]
+pub fn confirm_match(line_id: i64, file_id: String) -> Result<(), String> {
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+
+    let (posted_on, amount, reference, description): (String, f64, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT posted_on, amount, reference, description FROM bank_statement_lines WHERE id = ?1",
+            params![line_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Statement line not found: {}", line_id))?;
+
+    conn.query_row("SELECT 1 FROM files WHERE id = ?1", params![file_id], |_row| {
+        Ok(())
+    })
+    .optional()
+    .map_err(|error| error.to_string())?
+    .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    let tx = conn.transaction().map_err(|error| error.to_string())?;
+    tx.execute(
+        "UPDATE bank_statement_lines
+         SET matched_file_id = ?1, match_status = 'Confirmed', confirmed_at = CURRENT_TIMESTAMP
+         WHERE id = ?2",
+        params![file_id, line_id],
+    )
+    .map_err(|error| error.to_string())?;
+    tx.execute(
+        "INSERT INTO payments (file_id, paid_on, reference, amount) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(file_id) DO UPDATE SET
+           paid_on = excluded.paid_on,
+           reference = excluded.reference,
+           amount = excluded.amount",
+        params![file_id, posted_on, reference.or(description), amount.abs()],
+    )
+    .map_err(|error| error.to_string())?;
+    AuditLog::record(
+        &tx,
+        "bank.match_confirmed",
+        Some(&file_id),
+        Some(serde_json::json!({ "lineId": line_id, "postedOn": posted_on, "amount": amount })),
+    )?;
+    tx.commit().map_err(|error| error.to_string())?;
+
+    // The invoice is now paid; other lines may have been pointing at it.
+    BankMatcher::suggest(&conn)?;
+    Ok(())
+}
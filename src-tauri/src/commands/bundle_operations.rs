@@ -0,0 +1,85 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    export_bundle::{BundleFormat, ExportBundle},
+    export_manifest::{ExportManifests, ExportRecord},
+    notifier::{JobEvent, Notifier},
+    telemetry::Telemetry,
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundleResult {
+    pub path: String,
+    pub invoices: usize,
+    pub documents_copied: usize,
+    pub skipped_file_ids: Vec<String>,
+    pub export: ExportRecord,
+}
+
+/// Exports `file_ids` as an audit bundle: `invoices.xml` or `invoices.xlsx`, the source
+/// documents under `documents/` named by invoice number, and `manifest.json`. XML bundles
+/// take the Tally XML generated by the frontend, with vouchers in `file_ids` order.
+#[tauri::command]
+pub fn export_bundle(
+    app: AppHandle,
+    file_ids: Vec<String>,
+    format: String,
+    output_path: String,
+    xml_content: Option<String>,
+    zip: bool,
+) -> Result<ExportBundleResult, String> {
+    let format = BundleFormat::parse(&format)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let bundle = ExportBundle::load(&conn, &file_ids, format)?;
+    let manifest = bundle.write(Path::new(&output_path), xml_content.as_deref(), zip)?;
+    // Folder bundles are checksummed by their XML or XLSX; zip bundles as a whole.
+    let output = if zip {
+        PathBuf::from(&output_path)
+    } else {
+        Path::new(&output_path).join(format.export_name())
+    };
+    let export = ExportManifests::record(
+        &conn,
+        "export_bundle",
+        &output,
+        &bundle.file_ids(),
+        manifest.entries.len(),
+    )?;
+
+    let documents_copied = manifest
+        .entries
+        .iter()
+        .filter(|entry| entry.document.is_some())
+        .count();
+    Telemetry::record_export(&conn, "export_bundle");
+    AuditLog::record(
+        &conn,
+        "export.bundle_created",
+        Some(&output_path),
+        Some(serde_json::json!({
+            "format": manifest.export,
+            "invoices": bundle.file_count(),
+            "documents": documents_copied,
+            "exportId": export.id,
+        })),
+    )?;
+    Notifier::notify(
+        &app,
+        JobEvent::ExportCompleted {
+            label: "Export bundle".to_string(),
+            path: output_path.clone(),
+        },
+    );
+
+    Ok(ExportBundleResult {
+        path: output_path,
+        invoices: bundle.file_count(),
+        documents_copied,
+        skipped_file_ids: manifest.skipped_file_ids,
+        export,
+    })
+}
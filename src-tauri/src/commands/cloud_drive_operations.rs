@@ -0,0 +1,103 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    cloud_connector::CloudFilePage,
+    cloud_drive::{CloudDrive, CloudDriveLink, CloudSyncSummary, LinkCloudDriveOptions, LinkS3Options},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+
+/// Opens the provider's consent page and waits for the user to authorize read access.
+#[tauri::command(async)]
+pub fn link_cloud_drive(options: LinkCloudDriveOptions) -> Result<CloudDriveLink, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    let link = CloudDrive::link(&conn, &options)?;
+    AuditLog::record(
+        &conn,
+        "cloud_drive.linked",
+        Some(&link.id.to_string()),
+        Some(serde_json::json!({ "provider": link.provider.as_str(), "folder": link.folder })),
+    )?;
+    Ok(link)
+}
+
+/// Links an S3 (or S3-compatible) bucket prefix with an access key. The key is checked by
+/// listing the prefix before it is saved.
+#[tauri::command(async)]
+pub fn link_s3_bucket(options: LinkS3Options) -> Result<CloudDriveLink, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    let link = CloudDrive::link_s3(&conn, &options)?;
+    AuditLog::record(
+        &conn,
+        "cloud_drive.linked",
+        Some(&link.id.to_string()),
+        Some(serde_json::json!({ "provider": link.provider.as_str(), "folder": link.folder })),
+    )?;
+    Ok(link)
+}
+
+#[tauri::command]
+pub fn list_cloud_drive_links() -> Result<Vec<CloudDriveLink>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    CloudDrive::list(&conn)
+}
+
+#[tauri::command]
+pub fn unlink_cloud_drive(link_id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    CloudDrive::unlink(&conn, link_id)?;
+    AuditLog::record(&conn, "cloud_drive.unlinked", Some(&link_id.to_string()), None)
+}
+
+/// One page of the linked folder; pass `next_cursor` back for the next one.
+#[tauri::command(async)]
+pub fn list_cloud_drive_files(link_id: i64, cursor: Option<String>) -> Result<CloudFilePage, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    CloudDrive::list_files(&conn, link_id, cursor.as_deref())
+}
+
+/// Imports only the chosen files from the linked folder.
+#[tauri::command(async)]
+pub fn import_cloud_drive_files(link_id: i64, remote_ids: Vec<String>) -> Result<CloudSyncSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let summary = CloudDrive::import_files(&conn, link_id, &remote_ids)?;
+    AuditLog::record(
+        &conn,
+        "cloud_drive.imported",
+        Some(&link_id.to_string()),
+        Some(serde_json::json!({
+            "requested": remote_ids.len(),
+            "imported": summary.imported,
+            "duplicates": summary.duplicates,
+            "failed": summary.failures.len(),
+        })),
+    )?;
+    Ok(summary)
+}
+
+/// Imports new and changed documents from the linked folder now. The `cloud_drive_sync`
+/// schedule does the same on a timer.
+#[tauri::command(async)]
+pub fn sync_cloud_drive(link_id: i64) -> Result<CloudSyncSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let summary = CloudDrive::sync(&conn, link_id)?;
+    AuditLog::record(
+        &conn,
+        "cloud_drive.synced",
+        Some(&link_id.to_string()),
+        Some(serde_json::json!({
+            "imported": summary.imported,
+            "duplicates": summary.duplicates,
+            "failed": summary.failures.len(),
+        })),
+    )?;
+    Ok(summary)
+}
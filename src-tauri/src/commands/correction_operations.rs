@@ -0,0 +1,39 @@
+use crate::db::get_connection;
+use crate::services::{
+    field_corrections::{CorrectionStat, FieldCorrection, FieldCorrections},
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use serde_json::Value;
+
+/// Changes one extracted value, e.g. `grand total` or `items[2].rate`, and records the
+/// original and corrected values. `editor` is the name shown next to the correction.
+#[tauri::command]
+pub fn correct_parsed_field(
+    file_id: String,
+    field: String,
+    value: Value,
+    editor: Option<String>,
+) -> Result<Option<FieldCorrection>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let correction = FieldCorrections::correct(&conn, &file_id, &field, value, editor.as_deref())?;
+    Telemetry::record_feature(&conn, "field_correction");
+    Ok(correction)
+}
+
+#[tauri::command]
+pub fn list_field_corrections(
+    file_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<FieldCorrection>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    FieldCorrections::list(&conn, file_id.as_deref(), limit)
+}
+
+/// How often each field was corrected per template or model, within `period` when given.
+#[tauri::command]
+pub fn get_correction_stats(period: Option<String>) -> Result<Vec<CorrectionStat>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    FieldCorrections::stats(&conn, period.as_deref())
+}
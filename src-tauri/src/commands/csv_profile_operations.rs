@@ -0,0 +1,27 @@
+use crate::db::get_connection;
+use crate::services::csv_export::{CsvOptions, CsvProfiles};
+use crate::services::roles::Roles;
+use crate::services::workspace_lock::WorkspaceLock;
+use std::collections::BTreeMap;
+
+/// Every saved CSV profile by name; `default` is always present.
+#[tauri::command]
+pub fn list_csv_profiles() -> Result<BTreeMap<String, CsvOptions>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    CsvProfiles::list(&conn)
+}
+
+#[tauri::command]
+pub fn save_csv_profile(name: String, options: CsvOptions) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    CsvProfiles::save(&conn, &name, options)
+}
+
+#[tauri::command]
+pub fn delete_csv_profile(name: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.delete_csv_profiles")?;
+    CsvProfiles::delete(&conn, &name)
+}
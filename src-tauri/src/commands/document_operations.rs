@@ -0,0 +1,119 @@
+use crate::commands::file_operations::persist_buffer;
+use crate::db::get_connection;
+use crate::services::audit_log::AuditLog;
+use crate::services::document_split::{DocumentSplitter, SplitFormat};
+use crate::services::multi_invoice::{MultiInvoiceDetector, PageRange, SplitProposal};
+use crate::services::telemetry::Telemetry;
+use crate::services::workspace_lock::WorkspaceLock;
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPart {
+    pub file_id: String,
+    pub file_name: String,
+    pub start: u32,
+    pub end: u32,
+    /// The pages matched a file that was already imported; it is left as it was.
+    pub duplicate: bool,
+}
+
+/// Classifies each page of a stored PDF or TIFF and proposes page ranges, one per
+/// invoice, for splitting a copier batch scan into separate documents.
+#[tauri::command]
+pub fn detect_invoice_splits(file_id: String) -> Result<SplitProposal, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let (stored_path, mime_type): (String, Option<String>) = conn
+        .query_row(
+            "SELECT stored_path, mime_type FROM files WHERE id = ?1",
+            params![file_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    let path = Path::new(&stored_path);
+    let splittable = mime_type.as_deref() == Some("application/pdf")
+        || mime_type.as_deref() == Some("image/tiff")
+        || SplitFormat::from_path(path).is_some();
+    if !splittable {
+        return Err("Invoice split detection is only available for PDF and TIFF files.".to_string());
+    }
+
+    Telemetry::record_feature(&conn, "invoice_split_detection");
+    MultiInvoiceDetector::detect(path)
+}
+
+/// Imports each page range of a stored PDF or TIFF as its own file, linked back to the
+/// source through `parent_file_id`. The source keeps its blob and record but drops out of
+/// default file listings, so its invoices are not counted twice.
+#[tauri::command(async)]
+pub fn split_document(file_id: String, page_ranges: Vec<PageRange>) -> Result<Vec<SplitPart>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let (stored_path, file_name, origin): (String, String, Option<String>) = conn
+        .query_row(
+            "SELECT stored_path, file_name, source FROM files WHERE id = ?1",
+            params![file_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    let documents = DocumentSplitter::split(Path::new(&stored_path), &page_ranges)?;
+    let source = Path::new(&file_name);
+    let stem = source.file_stem().and_then(|stem| stem.to_str()).unwrap_or("document");
+    let extension = Path::new(&stored_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+
+    let mut parts = Vec::with_capacity(documents.len());
+    for (range, bytes) in page_ranges.iter().zip(documents) {
+        let pages = if range.start == range.end {
+            format!("page {}", range.start)
+        } else {
+            format!("pages {}-{}", range.start, range.end)
+        };
+        let part_name = format!("{} ({}).{}", stem, pages, extension);
+        let response = persist_buffer(&part_name, &bytes, origin.as_deref())?;
+        let (duplicate, part_id) = match response.split_once(':') {
+            Some(("DUPLICATE", id)) => (true, id.to_string()),
+            Some((_, id)) => (false, id.to_string()),
+            None => return Err(format!("Unexpected import result: {}", response)),
+        };
+        if !duplicate {
+            conn.execute(
+                "UPDATE files SET parent_file_id = ?1, page_range = ?2 WHERE id = ?3",
+                params![file_id, format!("{}-{}", range.start, range.end), part_id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        parts.push(SplitPart {
+            file_id: part_id,
+            file_name: part_name,
+            start: range.start,
+            end: range.end,
+            duplicate,
+        });
+    }
+
+    AuditLog::record(
+        &conn,
+        "file.split",
+        Some(&file_id),
+        Some(json!({
+            "parts": parts
+                .iter()
+                .map(|part| json!({ "fileId": part.file_id, "start": part.start, "end": part.end }))
+                .collect::<Vec<_>>(),
+        })),
+    )?;
+    Telemetry::record_feature(&conn, "document_split");
+    Ok(parts)
+}
@@ -0,0 +1,135 @@
+use crate::commands::file_operations::FileStatus;
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    double_payment::DOUBLE_PAYMENT_REVIEW_SOURCE,
+    parsed_invoice::{ParsedInvoice, GRAND_TOTAL, INVOICE_NUMBER, SELLER_NAME},
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoublePaymentFile {
+    pub id: String,
+    pub file_name: String,
+    pub status: FileStatus,
+    pub invoice_date: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoublePaymentGroup {
+    pub invoice_key: String,
+    pub seller_name: Option<String>,
+    pub invoice_number: Option<String>,
+    pub amount: Option<f64>,
+    pub canonical_file_id: Option<String>,
+    pub files: Vec<DoublePaymentFile>,
+}
+
+#[tauri::command]
+pub fn list_potential_double_payments(include_resolved: Option<bool>) -> Result<Vec<DoublePaymentGroup>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.duplicate_invoice_key, f.id, f.file_name, f.status, f.invoice_date, f.created_at,
+                    f.parsed_details, r.canonical_file_id
+             FROM files f
+             LEFT JOIN double_payment_resolutions r ON r.invoice_key = f.duplicate_invoice_key
+             WHERE f.duplicate_invoice_key IN (
+               SELECT duplicate_invoice_key FROM files
+               WHERE duplicate_invoice_key IS NOT NULL
+               GROUP BY duplicate_invoice_key HAVING COUNT(*) > 1
+             )
+             AND (?1 OR r.invoice_key IS NULL)
+             ORDER BY f.duplicate_invoice_key, f.created_at ASC",
+        )
+        .map_err(|error| error.to_string())?;
+
+    let rows_iter = stmt
+        .query_map(params![include_resolved.unwrap_or(false)], |row| {
+            let status_str: String = row.get(3)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                DoublePaymentFile {
+                    id: row.get(1)?,
+                    file_name: row.get(2)?,
+                    status: status_str.parse::<FileStatus>().unwrap_or(FileStatus::Unprocessed),
+                    invoice_date: row.get(4)?,
+                    created_at: row.get(5)?,
+                },
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?;
+
+    let mut groups: Vec<DoublePaymentGroup> = Vec::new();
+    for row in rows_iter {
+        let (invoice_key, file, parsed_details, canonical_file_id) =
+            row.map_err(|error| error.to_string())?;
+
+        match groups.last_mut() {
+            Some(group) if group.invoice_key == invoice_key => group.files.push(file),
+            _ => {
+                let invoice = parsed_details.as_deref().and_then(ParsedInvoice::from_json);
+                groups.push(DoublePaymentGroup {
+                    invoice_key,
+                    seller_name: invoice.as_ref().and_then(|invoice| invoice.text(SELLER_NAME)),
+                    invoice_number: invoice.as_ref().and_then(|invoice| invoice.text(INVOICE_NUMBER)),
+                    amount: invoice.as_ref().and_then(|invoice| invoice.number(GRAND_TOTAL)),
+                    canonical_file_id,
+                    files: vec![file],
+                });
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+#[tauri::command]
+pub fn mark_canonical_invoice(file_id: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+
+    let invoice_key: String = conn
+        .query_row(
+            "SELECT duplicate_invoice_key FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?
+        .ok_or_else(|| "File has no extracted invoice number to compare.".to_string())?;
+
+    conn.execute(
+        "INSERT INTO double_payment_resolutions (invoice_key, canonical_file_id)
+         VALUES (?1, ?2)
+         ON CONFLICT(invoice_key) DO UPDATE SET
+           canonical_file_id = excluded.canonical_file_id,
+           resolved_at = CURRENT_TIMESTAMP",
+        params![invoice_key, file_id],
+    )
+    .map_err(|error| error.to_string())?;
+
+    conn.execute(
+        "UPDATE review_flags SET resolved_at = CURRENT_TIMESTAMP
+         WHERE source = ?1 AND resolved_at IS NULL
+         AND file_id IN (SELECT id FROM files WHERE duplicate_invoice_key = ?2)",
+        params![DOUBLE_PAYMENT_REVIEW_SOURCE, invoice_key],
+    )
+    .map_err(|error| error.to_string())?;
+
+    AuditLog::record(
+        &conn,
+        "duplicate.canonical_marked",
+        Some(&file_id),
+        Some(serde_json::json!({ "invoiceKey": invoice_key })),
+    )
+}
@@ -0,0 +1,73 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    exchange_rates::{ExchangeRate, ExchangeRates},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn list_exchange_rates() -> Result<Vec<ExchangeRate>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExchangeRates::list(&conn)
+}
+
+/// `rate` is the amount of the base currency one unit of `currency` is worth.
+#[tauri::command]
+pub fn set_exchange_rate(currency: String, rate: f64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ExchangeRates::set(&conn, &currency, rate)?;
+    AuditLog::record(
+        &conn,
+        "settings.exchange_rate",
+        Some(&currency),
+        Some(serde_json::json!({ "rate": rate })),
+    )
+}
+
+#[tauri::command]
+pub fn delete_exchange_rate(currency: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ExchangeRates::delete(&conn, &currency)?;
+    AuditLog::record(&conn, "settings.exchange_rate_deleted", Some(&currency), None)
+}
+
+#[tauri::command]
+pub fn get_base_currency() -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExchangeRates::base_currency(&conn)
+}
+
+/// Changing the base currency clears the stored rates, which were relative to the old one.
+#[tauri::command]
+pub fn set_base_currency(currency: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ExchangeRates::set_base_currency(&conn, &currency)?;
+    AuditLog::record(&conn, "settings.base_currency", Some(&currency), None)
+}
+
+#[tauri::command]
+pub fn get_converted_sheet_totals() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExchangeRates::converted_totals(&conn)
+}
+
+#[tauri::command]
+pub fn set_converted_sheet_totals(enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ExchangeRates::set_converted_totals(&conn, enabled)?;
+    AuditLog::record(
+        &conn,
+        "settings.converted_sheet_totals",
+        None,
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+}
@@ -0,0 +1,31 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    export_manifest::{ExportManifests, ExportRecord, ExportVerification},
+};
+
+#[tauri::command]
+pub fn list_exports(limit: Option<i64>) -> Result<Vec<ExportRecord>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExportManifests::list(&conn, limit.unwrap_or(50))
+}
+
+#[tauri::command]
+pub fn get_export_manifest(export_id: String) -> Result<ExportRecord, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExportManifests::get(&conn, &export_id)
+}
+
+/// Checks an export against the SHA-256 recorded when it was written.
+#[tauri::command]
+pub fn verify_export(export_id: String) -> Result<ExportVerification, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let verification = ExportManifests::verify(&conn, &export_id)?;
+    AuditLog::record(
+        &conn,
+        "export.verified",
+        Some(&export_id),
+        Some(serde_json::json!({ "path": verification.path, "status": verification.status })),
+    )?;
+    Ok(verification)
+}
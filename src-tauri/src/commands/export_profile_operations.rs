@@ -0,0 +1,81 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    export_profiles::{ExportFilter, ExportProfile, ExportProfileInput, ExportProfiles, ExportRunResult},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn list_export_profiles() -> Result<Vec<ExportProfile>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExportProfiles::list(&conn)
+}
+
+#[tauri::command]
+pub fn get_export_profile(profile_id: i64) -> Result<ExportProfile, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExportProfiles::get(&conn, profile_id)
+}
+
+#[tauri::command]
+pub fn create_export_profile(profile: ExportProfileInput) -> Result<ExportProfile, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let profile = ExportProfiles::create(&conn, &profile)?;
+    AuditLog::record(
+        &conn,
+        "export_profile.created",
+        Some(&profile.id.to_string()),
+        Some(serde_json::json!({ "name": profile.name, "format": profile.format.as_str() })),
+    )?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn update_export_profile(profile_id: i64, profile: ExportProfileInput) -> Result<ExportProfile, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let profile = ExportProfiles::update(&conn, profile_id, &profile)?;
+    AuditLog::record(
+        &conn,
+        "export_profile.updated",
+        Some(&profile_id.to_string()),
+        Some(serde_json::json!({ "name": profile.name, "format": profile.format.as_str() })),
+    )?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_export_profile(profile_id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.delete_export_profiles")?;
+    ExportProfiles::delete(&conn, profile_id)?;
+    AuditLog::record(
+        &conn,
+        "export_profile.deleted",
+        Some(&profile_id.to_string()),
+        None,
+    )
+}
+
+/// Runs a saved profile against the invoices matching `filter`. XLSX and CSV runs only read
+/// invoices, so they also work while the workspace is locked.
+#[tauri::command(async)]
+pub fn run_export(
+    app: AppHandle,
+    profile_id: i64,
+    filter: Option<ExportFilter>,
+) -> Result<ExportRunResult, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let result = ExportProfiles::run(&app, &conn, profile_id, &filter.unwrap_or_default())?;
+    AuditLog::record(
+        &conn,
+        "export_profile.run",
+        Some(&profile_id.to_string()),
+        Some(serde_json::json!({ "path": result.path, "files": result.file_count })),
+    )?;
+    Ok(result)
+}
@@ -1,9 +1,9 @@
 use crate::db::get_connection;
 use chrono::Utc;
-use rusqlite::{params, Row};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{Read};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::path::Path;
 use uuid::Uuid;
 
@@ -13,6 +13,10 @@ pub enum FileStatus {
     Processing,
     Processed,
     Failed,
+    /// Extraction could not reach the provider; resumed automatically once it can.
+    WaitingForNetwork,
+    /// Extracted, but below the auto-approve confidence threshold.
+    NeedsReview,
 }
 
 impl FileStatus {
@@ -22,6 +26,8 @@ impl FileStatus {
             FileStatus::Processing => "Processing",
             FileStatus::Processed => "Processed",
             FileStatus::Failed => "Failed",
+            FileStatus::WaitingForNetwork => "WaitingForNetwork",
+            FileStatus::NeedsReview => "NeedsReview",
         }
     }
 }
@@ -35,6 +41,8 @@ impl std::str::FromStr for FileStatus {
             "Processing" => Ok(FileStatus::Processing),
             "Processed" => Ok(FileStatus::Processed),
             "Failed" => Ok(FileStatus::Failed),
+            "WaitingForNetwork" => Ok(FileStatus::WaitingForNetwork),
+            "NeedsReview" => Ok(FileStatus::NeedsReview),
             _ => Err(format!("Invalid file status: {}", s)),
         }
     }
@@ -45,6 +53,7 @@ pub struct FileRow {
     pub id: String,
     pub file_name: String,
     pub hash: String,
+    pub hash_algo: HashAlgorithm,
 }
 
 #[derive(Serialize)]
@@ -60,6 +69,31 @@ pub struct FileRecord {
     pub created_at: String,
     pub processed_at: Option<String>,
     pub updated_at: Option<String>,
+    pub tax_ids_valid: Option<bool>,
+    pub invoice_date: Option<String>,
+    pub invoice_date_raw: Option<String>,
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    pub arithmetic_valid: Option<bool>,
+    pub due_date: Option<String>,
+    pub payment_terms: Option<String>,
+    pub content_hash: String,
+    pub hash_algo: HashAlgorithm,
+    /// The older version of the same invoice this file replaces.
+    pub supersedes: Option<String>,
+    /// The multi-invoice scan this file was split from, and its pages there ("3-5").
+    pub parent_file_id: Option<String>,
+    pub page_range: Option<String>,
+    /// Original path or remote URL the document was imported from.
+    pub source: Option<String>,
+    /// Tesseract code of the language the document is written in, e.g. `hin`.
+    pub document_language: Option<String>,
+    /// `invoice`, `receipt`, `credit_note`, `purchase_order` or `other`; `None` until known.
+    pub doc_type: Option<String>,
+    /// Between 0 and 1, from the last extraction; `None` until extracted.
+    pub extraction_confidence: Option<f64>,
+    /// Names of the file's tags, in alphabetical order.
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -74,34 +108,107 @@ pub struct PaginatedFilesResult {
 pub struct FileListQuery {
     pub status_filter: Option<String>,
     pub search_query: Option<String>,
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    pub language: Option<String>,
+    pub doc_type: Option<DocType>,
+    /// Only files due on or after / on or before these dates (YYYY-MM-DD).
+    pub due_from: Option<String>,
+    pub due_to: Option<String>,
+    /// Only files carrying every one of these tags.
+    pub tags: Option<Vec<String>>,
     pub limit: i64,
     pub offset: i64,
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
+    /// Also list versions replaced by a newer copy of the same invoice.
+    pub include_superseded: Option<bool>,
+    /// Also list scans that were split into separate files.
+    pub include_split_sources: Option<bool>,
 }
 
+use crate::commands::validation_operations::apply_parsed_details_checks;
 use crate::services::{
-    file_hasher::FileHasher,
+    app_lock::AppLock,
+    archive_import::{ArchiveImport, ArchiveImportSummary},
+    audit_log::AuditLog,
+    batch_import::{
+        import_result, skipped, BatchImport, BatchImportSummary, ImportEntryResult, ImportPhase, ImportTracker,
+    },
+    clipboard_import::{ClipboardContent, ClipboardImport},
+    directory_import::{DirectoryFilters, DirectoryImport, DirectoryPreview},
+    document_classifier::{DocType, DocumentClassifier},
+    duplicate_policy::DuplicatePolicy,
+    gemini::mime_for,
+    file_hasher::{FileHasher, HashAlgorithm},
     file_metadata::FileMetadata,
+    file_name_template::{FileNameTemplate, TemplateContext},
     file_storage::FileStorage,
+    file_versions::{FileVersion, FileVersions},
+    image_preprocess::{ImagePreprocessor, PreprocessOptions},
+    import_batches::{ImportBatches, ImportSource},
+    parsed_history::{DetailsSource, ParsedHistory},
+    parsed_schema::ParsedSchema,
+    roles::Roles,
+    secure_delete::SecureDelete,
+    telemetry::{Telemetry, FILES_IMPORTED},
+    trash::{FileDeleteResult, Trash},
+    workspace_lock::WorkspaceLock,
 };
+use crate::services::parsed_invoice::{FieldProvenance, ParsedInvoice};
+use std::collections::HashSet;
 use std::path::PathBuf;
+use tauri::AppHandle;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedFile {
+    pub id: String,
+    pub old_name: String,
+    pub new_name: String,
+}
 
 fn file_row_from_row(row: &Row) -> rusqlite::Result<FileRow> {
     Ok(FileRow {
         id: row.get(0)?,
         file_name: row.get(1)?,
         hash: row.get(2)?,
+        hash_algo: HashAlgorithm::parse(&row.get::<_, String>(3)?).unwrap_or(HashAlgorithm::Blake3),
     })
 }
 
-fn persist_buffer(file_name: &str, buffer: &[u8]) -> Result<String, String> {
+/// `source` is where the document came from (its original path or remote URL), kept so
+/// `reveal_original_source` can go back to it.
+pub(crate) fn persist_buffer(file_name: &str, buffer: &[u8], source: Option<&str>) -> Result<String, String> {
     // 1. Calculate Hash
-    let hash_hex = FileHasher::calculate_hash(buffer);
-
-    // 2. Check for Duplicates
-    if let Some(existing_id) = FileMetadata::check_duplicate(&hash_hex)? {
-        return Ok(format!("DUPLICATE:{}", existing_id));
+    let (algorithm, policy) = {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        WorkspaceLock::require_unlocked(&conn)?;
+        (FileHasher::configured(&conn)?, DuplicatePolicy::configured(&conn)?)
+    };
+    let hash_hex = FileHasher::calculate_hash(algorithm, buffer);
+
+    // 2. Check for Duplicates under the configured policy
+    match policy {
+        DuplicatePolicy::ContentHash => {
+            // Against files hashed with either algorithm
+            for candidate in HashAlgorithm::ALL {
+                let hash = if candidate == algorithm {
+                    hash_hex.clone()
+                } else {
+                    FileHasher::calculate_hash(candidate, buffer)
+                };
+                if let Some(existing_id) = FileMetadata::check_duplicate(&hash, candidate)? {
+                    return Ok(format!("DUPLICATE:{}", existing_id));
+                }
+            }
+        }
+        DuplicatePolicy::NameAndSize => {
+            if let Some(existing_id) = FileMetadata::find_by_name_and_size(file_name, buffer.len() as i64)? {
+                return Ok(format!("DUPLICATE:{}", existing_id));
+            }
+        }
+        DuplicatePolicy::None => {}
     }
 
     // 3. Generate ID
@@ -110,37 +217,248 @@ fn persist_buffer(file_name: &str, buffer: &[u8]) -> Result<String, String> {
     // 4. Write to Disk
     let stored_path = FileStorage::save_file(&id, file_name, buffer)?;
 
-    // 5. Write to Database
-    FileMetadata::save_metadata(
+    // 5. Write to Database, dropping the stored copy if it cannot be recorded
+    if let Err(error) = FileMetadata::save_metadata(
         &id,
         &hash_hex,
+        algorithm,
         file_name,
         stored_path.to_string_lossy().as_ref(),
         buffer.len() as i64,
-    )?;
+        source,
+    ) {
+        let _ = fs::remove_file(&stored_path);
+        return Err(error);
+    }
+
+    // 6. Guess the document type; parsing the file settles it
+    if let Some(doc_type) = DocumentClassifier::classify_import(file_name, buffer) {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        DocumentClassifier::set(&conn, &id, doc_type)?;
+    }
+    Telemetry::track(FILES_IMPORTED);
 
     Ok(format!("OK:{}", id))
 }
 
-#[tauri::command]
-pub fn import_file(path: String) -> Result<String, String> {
-    let mut file = fs::File::open(&path).map_err(|error| error.to_string())?;
-    let mut buf = Vec::new();
-    file.read_to_end(&mut buf)
-        .map_err(|error| error.to_string())?;
+/// `persist_buffer` after the requested clean-up of photographed invoices, so the hash and
+/// duplicate checks see the stored bytes.
+pub(crate) fn persist_document(
+    file_name: &str,
+    buffer: &[u8],
+    source: Option<&str>,
+    preprocess: Option<&PreprocessOptions>,
+) -> Result<String, String> {
+    match preprocess
+        .map(|options| ImagePreprocessor::apply(file_name, buffer, options))
+        .transpose()?
+        .flatten()
+    {
+        Some(processed) => persist_buffer(file_name, &processed, source),
+        None => persist_buffer(file_name, buffer, source),
+    }
+}
 
+/// Imports one document into the import batch `batch_id`, or into a batch of its own.
+/// Images are cleaned up first when `preprocess` asks for it.
+#[tauri::command]
+pub fn import_file(
+    path: String,
+    batch_id: Option<i64>,
+    preprocess: Option<PreprocessOptions>,
+) -> Result<String, String> {
     let original_path = Path::new(&path);
     let file_name = original_path
         .file_name()
         .and_then(|s| s.to_str())
         .unwrap_or("file");
 
-    persist_buffer(file_name, &buf)
+    let outcome = fs::File::open(&path)
+        .and_then(|mut file| {
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).map(|_| buf)
+        })
+        .map_err(|error| error.to_string())
+        .and_then(|buf| persist_document(file_name, &buf, Some(&path), preprocess.as_ref()));
+    ImportBatches::record_outcome(batch_id, ImportSource::Paths, &path, &outcome)?;
+    outcome
+}
+
+#[tauri::command]
+pub fn import_data(
+    file_name: String,
+    bytes: Vec<u8>,
+    batch_id: Option<i64>,
+    preprocess: Option<PreprocessOptions>,
+) -> Result<String, String> {
+    let outcome = persist_document(&file_name, &bytes, None, preprocess.as_ref());
+    ImportBatches::record_outcome(batch_id, ImportSource::Upload, &file_name, &outcome)?;
+    outcome
+}
+
+/// Imports what is on the OS clipboard: copied files, or a copied image or screenshot
+/// stored as a PNG. Everything is recorded in `batch_id`, or in a batch of its own.
+#[tauri::command(async)]
+pub fn import_from_clipboard(
+    batch_id: Option<i64>,
+    preprocess: Option<PreprocessOptions>,
+) -> Result<Vec<ImportEntryResult>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let content = ClipboardImport::read()?;
+    let (batch_id, owns_batch) = match batch_id {
+        Some(batch_id) => (batch_id, false),
+        None => (ImportBatches::begin(&conn, ImportSource::Clipboard, None)?, true),
+    };
+
+    let entries = match content {
+        ClipboardContent::Image { file_name, png } => {
+            vec![import_result(
+                file_name.clone(),
+                persist_document(&file_name, &png, None, preprocess.as_ref()),
+            )]
+        }
+        ClipboardContent::Files(paths) => paths
+            .iter()
+            .map(|path| {
+                let entry = path.to_string_lossy().to_string();
+                let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("file");
+                if mime_for(path).is_none() {
+                    return skipped(&entry, "Not a supported document type.");
+                }
+                let outcome = fs::read(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|buffer| persist_document(file_name, &buffer, Some(&entry), preprocess.as_ref()));
+                import_result(entry, outcome)
+            })
+            .collect(),
+    };
+
+    for entry in &entries {
+        ImportBatches::record(&conn, batch_id, entry)?;
+    }
+    if owns_batch {
+        ImportBatches::finish(&conn, batch_id, false)?;
+    }
+    Ok(entries)
+}
+
+/// Imports documents and `.zip` archives by path. Progress is emitted as `import://progress`
+/// under `import_id`, which `cancel_import` takes to stop the batch between files.
+#[tauri::command(async)]
+pub fn import_paths(
+    app: AppHandle,
+    paths: Vec<String>,
+    import_id: Option<String>,
+    preprocess: Option<PreprocessOptions>,
+) -> Result<BatchImportSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    BatchImport::import_paths(&app, &conn, import_id, &paths, None, preprocess.as_ref())
+}
+
+/// Lists a dropped folder against `filters`, marking which files an import would take.
+#[tauri::command(async)]
+pub fn preview_directory_import(
+    directory: String,
+    filters: Option<DirectoryFilters>,
+) -> Result<DirectoryPreview, String> {
+    DirectoryImport::preview(Path::new(&directory), &filters.unwrap_or_default())
 }
 
+/// Imports the files of a dropped folder that pass `filters`, as one batch labelled with
+/// the folder. The folder is listed again, so files added since the preview are included
+/// when they match.
+#[tauri::command(async)]
+pub fn import_directory(
+    app: AppHandle,
+    directory: String,
+    filters: Option<DirectoryFilters>,
+    import_id: Option<String>,
+    preprocess: Option<PreprocessOptions>,
+) -> Result<BatchImportSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let preview = DirectoryImport::preview(Path::new(&directory), &filters.unwrap_or_default())?;
+    let paths: Vec<String> = preview
+        .entries
+        .into_iter()
+        .filter(|entry| entry.included)
+        .map(|entry| entry.path)
+        .collect();
+    BatchImport::import_paths(&app, &conn, import_id, &paths, Some(&directory), preprocess.as_ref())
+}
+
+/// Imports every supported document inside a `.zip` archive.
+#[tauri::command(async)]
+pub fn import_archive(
+    app: AppHandle,
+    path: String,
+    import_id: Option<String>,
+) -> Result<ArchiveImportSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let file = fs::File::open(&path).map_err(|error| error.to_string())?;
+    let archive_name = Path::new(&path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("archive.zip");
+    let reader = io::BufReader::new(file);
+    import_archive_into(&app, &conn, archive_name, Some(&path), reader, import_id, None)
+}
+
+/// Same as `import_archive`, for archives picked or dropped in the webview.
+#[tauri::command(async)]
+pub fn import_archive_data(
+    app: AppHandle,
+    file_name: String,
+    bytes: Vec<u8>,
+    import_id: Option<String>,
+    batch_id: Option<i64>,
+) -> Result<ArchiveImportSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let reader = io::Cursor::new(bytes);
+    import_archive_into(&app, &conn, &file_name, None, reader, import_id, batch_id)
+}
+
+/// Runs an archive import under a tracker, in `batch_id` or in a batch of its own labelled
+/// with the archive. `source` is the archive's path on disk, when it has one.
+fn import_archive_into<Rd: Read + Seek>(
+    app: &AppHandle,
+    conn: &Connection,
+    archive_name: &str,
+    source: Option<&str>,
+    mut reader: Rd,
+    import_id: Option<String>,
+    batch_id: Option<i64>,
+) -> Result<ArchiveImportSummary, String> {
+    let (batch_id, owns_batch) = match batch_id {
+        Some(batch_id) => (batch_id, false),
+        None => {
+            let kind = if source.is_some() {
+                ImportSource::Paths
+            } else {
+                ImportSource::Upload
+            };
+            (ImportBatches::begin(conn, kind, Some(source.unwrap_or(archive_name)))?, true)
+        }
+    };
+    let size = reader.seek(SeekFrom::End(0)).map_err(|error| error.to_string())?;
+    reader.rewind().map_err(|error| error.to_string())?;
+    let mut tracker = ImportTracker::start(app, import_id, batch_id, 1, size)?;
+    let summary = ArchiveImport::import(archive_name, source, reader, &mut tracker);
+    let cancelled = tracker.finish().phase == ImportPhase::Cancelled;
+    if owns_batch {
+        ImportBatches::finish(conn, batch_id, cancelled)?;
+    }
+    summary
+}
+
+/// Stops a running import after the file it is on. False when nothing runs under the id.
 #[tauri::command]
-pub fn import_data(file_name: String, bytes: Vec<u8>) -> Result<String, String> {
-    persist_buffer(&file_name, &bytes)
+pub fn cancel_import(import_id: String) -> Result<bool, String> {
+    BatchImport::cancel(&import_id)
 }
 
 #[tauri::command]
@@ -148,7 +466,7 @@ pub fn list_files() -> Result<Vec<FileRow>, String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
 
     let mut stmt = conn
-        .prepare("SELECT id, file_name, hash_sha256 FROM files ORDER BY created_at DESC LIMIT 50")
+        .prepare("SELECT id, file_name, content_hash, hash_algo FROM files ORDER BY created_at DESC LIMIT 50")
         .map_err(|error| error.to_string())?;
 
     let rows_iter = stmt
@@ -179,6 +497,51 @@ pub fn list_files_paginated(query: FileListQuery) -> Result<PaginatedFilesResult
         where_clauses.push("file_name LIKE ?");
         params.push(Box::new(format!("%{}%", search)));
     }
+
+    if let Some(seller_id) = query.seller_id {
+        where_clauses.push("seller_id = ?");
+        params.push(Box::new(seller_id));
+    }
+
+    if let Some(buyer_id) = query.buyer_id {
+        where_clauses.push("buyer_id = ?");
+        params.push(Box::new(buyer_id));
+    }
+
+    if let Some(language) = &query.language {
+        where_clauses.push("document_language = ?");
+        params.push(Box::new(language.clone()));
+    }
+
+    if let Some(doc_type) = query.doc_type {
+        where_clauses.push("doc_type = ?");
+        params.push(Box::new(doc_type.as_str()));
+    }
+
+    if let Some(due_from) = &query.due_from {
+        where_clauses.push("due_date >= ?");
+        params.push(Box::new(due_from.clone()));
+    }
+
+    if let Some(due_to) = &query.due_to {
+        where_clauses.push("due_date <= ?");
+        params.push(Box::new(due_to.clone()));
+    }
+
+    for tag in query.tags.iter().flatten() {
+        where_clauses.push(
+            "id IN (SELECT file_tags.file_id FROM file_tags JOIN tags ON tags.id = file_tags.tag_id WHERE tags.name = ?)",
+        );
+        params.push(Box::new(tag.trim().to_string()));
+    }
+
+    if !query.include_superseded.unwrap_or(false) {
+        where_clauses.push("id NOT IN (SELECT supersedes FROM files WHERE supersedes IS NOT NULL)");
+    }
+
+    if !query.include_split_sources.unwrap_or(false) {
+        where_clauses.push("id NOT IN (SELECT parent_file_id FROM files WHERE parent_file_id IS NOT NULL)");
+    }
     
     let where_clause = if where_clauses.is_empty() {
         String::new()
@@ -201,29 +564,26 @@ pub fn list_files_paginated(query: FileListQuery) -> Result<PaginatedFilesResult
     
     // Build main query
     let main_query = format!(
-        "SELECT id, file_name, stored_path, size_bytes, mime_type, status, parsed_details, created_at, processed_at, updated_at FROM files {} {} LIMIT ? OFFSET ?",
+        "SELECT id, file_name, stored_path, size_bytes, mime_type, status, parsed_details, created_at, processed_at, updated_at, tax_ids_valid, invoice_date, invoice_date_raw, seller_id, buyer_id, arithmetic_valid, due_date, payment_terms, content_hash, hash_algo, supersedes, parent_file_id, page_range, source, document_language, doc_type, extraction_confidence, (SELECT group_concat(tags.name, char(31)) FROM file_tags JOIN tags ON tags.id = file_tags.tag_id WHERE file_tags.file_id = files.id) FROM files {} {} LIMIT ? OFFSET ?",
         where_clause, order_clause
     );
     
     let mut stmt = conn.prepare(&main_query).map_err(|error| error.to_string())?;
     
-    // Rebuild params for main query
-    let mut main_params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-    if let Some(status) = &query.status_filter {
-        main_params.push(Box::new(status.clone()));
-    }
-    if let Some(search) = &query.search_query {
-        main_params.push(Box::new(format!("%{}%", search)));
-    }
-    main_params.push(Box::new(query.limit));
-    main_params.push(Box::new(query.offset));
+    params.push(Box::new(query.limit));
+    params.push(Box::new(query.offset));
     
     let files_iter = stmt.query_map(
-        rusqlite::params_from_iter(main_params.iter().map(|p| p.as_ref())),
+        rusqlite::params_from_iter(params.iter().map(|p| p.as_ref())),
         |row| {
             let status_str: String = row.get(5)?;
             let status = status_str.parse::<FileStatus>()
                 .unwrap_or(FileStatus::Unprocessed);
+            let mut tags: Vec<String> = row
+                .get::<_, Option<String>>(27)?
+                .map(|names| names.split('\u{1f}').map(str::to_string).collect())
+                .unwrap_or_default();
+            tags.sort_by_key(|name| name.to_lowercase());
             
             Ok(FileRecord {
                 id: row.get(0)?,
@@ -236,6 +596,25 @@ pub fn list_files_paginated(query: FileListQuery) -> Result<PaginatedFilesResult
                 created_at: row.get(7)?,
                 processed_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                tax_ids_valid: row.get(10)?,
+                invoice_date: row.get(11)?,
+                invoice_date_raw: row.get(12)?,
+                seller_id: row.get(13)?,
+                buyer_id: row.get(14)?,
+                arithmetic_valid: row.get(15)?,
+                due_date: row.get(16)?,
+                payment_terms: row.get(17)?,
+                content_hash: row.get(18)?,
+                hash_algo: HashAlgorithm::parse(&row.get::<_, String>(19)?)
+                    .unwrap_or(HashAlgorithm::Blake3),
+                supersedes: row.get(20)?,
+                parent_file_id: row.get(21)?,
+                page_range: row.get(22)?,
+                source: row.get(23)?,
+                document_language: row.get(24)?,
+                doc_type: row.get(25)?,
+                extraction_confidence: row.get(26)?,
+                tags,
             })
         }
     ).map_err(|error| error.to_string())?;
@@ -244,6 +623,11 @@ pub fn list_files_paginated(query: FileListQuery) -> Result<PaginatedFilesResult
     for file in files_iter {
         files.push(file.map_err(|error| error.to_string())?);
     }
+    drop(stmt);
+    // Details written before the current schema version are upgraded as they are listed.
+    for file in &mut files {
+        file.parsed_details = ParsedSchema::load(&conn, &file.id, file.parsed_details.take())?;
+    }
     
     Ok(PaginatedFilesResult {
         files,
@@ -251,6 +635,28 @@ pub fn list_files_paginated(query: FileListQuery) -> Result<PaginatedFilesResult
     })
 }
 
+/// Files held back by the auto-approve threshold, least confident first.
+#[tauri::command]
+pub fn list_files_needing_review(limit: i64, offset: i64) -> Result<PaginatedFilesResult, String> {
+    list_files_paginated(FileListQuery {
+        status_filter: Some(FileStatus::NeedsReview.as_str().to_string()),
+        search_query: None,
+        seller_id: None,
+        buyer_id: None,
+        language: None,
+        doc_type: None,
+        due_from: None,
+        due_to: None,
+        tags: None,
+        limit,
+        offset,
+        sort_by: Some("extraction_confidence".to_string()),
+        sort_order: Some("ASC".to_string()),
+        include_superseded: None,
+        include_split_sources: None,
+    })
+}
+
 #[tauri::command]
 pub fn open_file_paths(paths: Vec<String>) -> Result<(), String> {
     for raw in paths {
@@ -267,6 +673,38 @@ pub fn open_file_paths(paths: Vec<String>) -> Result<(), String> {
     Ok(())
 }
 
+/// Opens the folder a file was imported from, or its web link for cloud imports. Returns
+/// the recorded source.
+#[tauri::command]
+pub fn reveal_original_source(file_id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let source: Option<String> = conn
+        .query_row(
+            "SELECT source FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+    let source = source.ok_or_else(|| "No original source was recorded for this file.".to_string())?;
+
+    if ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| source.starts_with(scheme))
+    {
+        open::that(&source).map_err(|error| format!("Failed to open {}: {}", source, error))?;
+        return Ok(source);
+    }
+
+    let folder = Path::new(&source)
+        .parent()
+        .filter(|folder| folder.is_dir())
+        .ok_or_else(|| format!("The original location is not available on this computer: {}", source))?;
+    open::that(folder).map_err(|error| format!("Failed to open {}: {}", folder.display(), error))?;
+    Ok(source)
+}
+
 #[tauri::command]
 pub fn copy_file_to_path(source_path: String, target_path: String, overwrite: Option<bool>) -> Result<(), String> {
     if source_path.trim().is_empty() {
@@ -299,6 +737,7 @@ pub fn copy_file_to_path(source_path: String, target_path: String, overwrite: Op
 #[tauri::command]
 pub fn update_file_status(file_id: String, status: FileStatus) -> Result<(), String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
     
     let processed_at = if status == FileStatus::Processed {
         Some(Utc::now().to_rfc3339())
@@ -318,13 +757,57 @@ pub fn update_file_status(file_id: String, status: FileStatus) -> Result<(), Str
 #[tauri::command]
 pub fn update_file_parsed_details(file_id: String, parsed_details: String) -> Result<(), String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
-    
+    WorkspaceLock::require_unlocked(&conn)?;
+    store_parsed_details(&conn, &file_id, &parsed_details, DetailsSource::ManualEdit)
+}
+
+/// Page and bounding box of each extracted value, for highlighting in the review view.
+#[tauri::command]
+pub fn get_field_provenance(file_id: String) -> Result<Vec<FieldProvenance>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let parsed_details: Option<String> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    Ok(parsed_details
+        .as_deref()
+        .and_then(ParsedInvoice::from_json)
+        .map(|invoice| invoice.provenance())
+        .unwrap_or_default())
+}
+
+/// Every imported copy of the file's invoice (same seller and invoice number), oldest first.
+#[tauri::command]
+pub fn list_file_versions(file_id: String) -> Result<Vec<FileVersion>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    FileVersions::history(&conn, &file_id)
+}
+
+/// Writes parsed details at the current schema version, keeps the new version in history
+/// and re-runs the checks that derive from them.
+pub(crate) fn store_parsed_details(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: &str,
+    source: DetailsSource,
+) -> Result<(), String> {
+    let parsed_details = &ParsedSchema::upgrade(parsed_details);
+    ParsedHistory::record(conn, file_id, parsed_details, source)?;
     conn.execute(
         "UPDATE files SET parsed_details = ?1 WHERE id = ?2",
         params![parsed_details, file_id],
     )
     .map_err(|error| error.to_string())?;
-    
+
+    apply_parsed_details_checks(conn, file_id, Some(parsed_details))?;
+    AuditLog::record(conn, "file.parsed_details_updated", Some(file_id), None)?;
+
     Ok(())
 }
 
@@ -335,6 +818,7 @@ pub fn update_files_status(file_ids: Vec<String>, status: FileStatus) -> Result<
     }
     
     let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
     
     let processed_at = if status == FileStatus::Processed {
         Some(Utc::now().to_rfc3339())
@@ -364,39 +848,134 @@ pub fn update_files_status(file_ids: Vec<String>, status: FileStatus) -> Result<
     Ok(())
 }
 
+/// Corrects the document type of several files, e.g. a credit note read as an invoice.
+/// Parsing the file again classifies it afresh.
+#[tauri::command]
+pub fn set_document_type(file_ids: Vec<String>, doc_type: DocType) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let mut updated = 0;
+    for file_id in &file_ids {
+        if DocumentClassifier::set(&conn, file_id, doc_type)? {
+            updated += 1;
+        }
+    }
+    if updated > 0 {
+        AuditLog::record(
+            &conn,
+            "file.doc_type_changed",
+            None,
+            Some(serde_json::json!({ "fileIds": file_ids, "docType": doc_type.as_str() })),
+        )?;
+    }
+    Ok(updated)
+}
+
+/// Renames files in bulk from a template like `{seller}-{invoice_number}-{date}`.
+/// Only the display name changes; stored copies keep their id-based paths.
+/// Files whose template resolves to nothing are left untouched.
 #[tauri::command]
-pub fn delete_files(file_ids: Vec<String>) -> Result<(), String> {
+pub fn rename_files_by_template(file_ids: Vec<String>, template: String) -> Result<Vec<RenamedFile>, String> {
+    let template = FileNameTemplate::parse(&template)?;
     if file_ids.is_empty() {
-        return Ok(());
+        return Ok(Vec::new());
     }
-    
-    let conn = get_connection().map_err(|error| error.to_string())?;
-    
-    // Get file paths first
-    let placeholders = file_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
-    let query = format!("SELECT id, stored_path FROM files WHERE id IN ({})", placeholders);
-    
-    let mut stmt = conn.prepare(&query).map_err(|error| error.to_string())?;
-    let params: Vec<&dyn rusqlite::ToSql> = file_ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-    
-    let files_iter = stmt.query_map(params.as_slice(), |row| {
-        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-    }).map_err(|error| error.to_string())?;
-    
-    let mut files_to_delete = Vec::new();
-    for file in files_iter {
-        files_to_delete.push(file.map_err(|error| error.to_string())?);
+
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let tx = conn.transaction().map_err(|error| error.to_string())?;
+    let mut used_names = HashSet::new();
+    let mut renamed = Vec::new();
+
+    for file_id in &file_ids {
+        let row: Option<(String, Option<String>, Option<String>)> = tx
+            .query_row(
+                "SELECT file_name, parsed_details, invoice_date FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        let Some((old_name, parsed_details, invoice_date)) = row else {
+            continue;
+        };
+
+        let invoice = parsed_details.as_deref().and_then(ParsedInvoice::from_json);
+        let context = TemplateContext {
+            original_name: &old_name,
+            invoice_date: invoice_date.as_deref(),
+            invoice: invoice.as_ref(),
+        };
+        let Some(candidate) = template.render(&context) else {
+            continue;
+        };
+        let new_name = unique_name(&candidate, &mut used_names);
+        if new_name == old_name {
+            continue;
+        }
+
+        tx.execute(
+            "UPDATE files SET file_name = ?1 WHERE id = ?2",
+            params![new_name, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        AuditLog::record(
+            &tx,
+            "file.renamed",
+            Some(file_id),
+            Some(serde_json::json!({ "from": old_name, "to": new_name })),
+        )?;
+
+        renamed.push(RenamedFile {
+            id: file_id.clone(),
+            old_name,
+            new_name,
+        });
     }
-    
-    // Delete files from disk
-    for (id, path) in &files_to_delete {
-        let file_path = Path::new(path);
-        if file_path.exists() {
-            fs::remove_file(file_path).map_err(|error| error.to_string())?;
+
+    tx.commit().map_err(|error| error.to_string())?;
+    Telemetry::record_feature(&conn, "rename_by_template");
+    Ok(renamed)
+}
+
+/// Appends ` (2)`, ` (3)`, ... before the extension when a batch produces the same name twice.
+fn unique_name(candidate: &str, used_names: &mut HashSet<String>) -> String {
+    if used_names.insert(candidate.to_lowercase()) {
+        return candidate.to_string();
+    }
+
+    let path = Path::new(candidate);
+    let stem = path.file_stem().and_then(|value| value.to_str()).unwrap_or(candidate);
+    let extension = path.extension().and_then(|value| value.to_str());
+    let mut counter = 2;
+    loop {
+        let name = match extension {
+            Some(extension) => format!("{} ({}).{}", stem, counter, extension),
+            None => format!("{} ({})", stem, counter),
+        };
+        if used_names.insert(name.to_lowercase()) {
+            return name;
         }
-        conn.execute("DELETE FROM files WHERE id = ?1", params![id])
-            .map_err(|error| error.to_string())?;
+        counter += 1;
     }
-    
-    Ok(())
+}
+
+/// Deletes files, to the trash unless secure delete is on, and reports what happened to
+/// each one. Files whose document cannot be moved are kept and reported as failed.
+#[tauri::command]
+pub fn delete_files(
+    file_ids: Vec<String>,
+    pin: Option<String>,
+    reason: Option<String>,
+) -> Result<Vec<FileDeleteResult>, String> {
+    if file_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.delete_files")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    let shred = SecureDelete::enabled(&conn)?;
+    Trash::delete(&conn, &file_ids, shred, reason.as_deref())
 }
@@ -0,0 +1,35 @@
+use crate::db::get_connection;
+use crate::services::{
+    import_batches::{ImportBatch, ImportBatchReport, ImportBatches, ImportSource},
+    workspace_lock::WorkspaceLock,
+};
+
+/// Starts a batch for files the frontend imports one by one; pass its id to `import_data`
+/// and `import_archive_data`, then call `finish_import_batch`.
+#[tauri::command]
+pub fn begin_import_batch(label: Option<String>) -> Result<i64, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    ImportBatches::begin(&conn, ImportSource::Upload, label.as_deref())
+}
+
+#[tauri::command]
+pub fn finish_import_batch(batch_id: i64, cancelled: Option<bool>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ImportBatches::finish(&conn, batch_id, cancelled.unwrap_or(false))
+}
+
+/// Batches started between `from` and `to` (dates or RFC 3339 timestamps), newest first,
+/// with their imported, duplicate, skipped and failed counts.
+#[tauri::command]
+pub fn list_import_batches(from: Option<String>, to: Option<String>) -> Result<Vec<ImportBatch>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ImportBatches::list(&conn, from.as_deref(), to.as_deref())
+}
+
+/// The batch with what every file in it turned into.
+#[tauri::command]
+pub fn get_batch_report(batch_id: i64) -> Result<ImportBatchReport, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ImportBatches::report(&conn, batch_id)
+}
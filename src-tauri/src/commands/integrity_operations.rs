@@ -0,0 +1,161 @@
+use crate::db::get_connection;
+use crate::services::{
+    app_lock::AppLock,
+    audit_log::AuditLog,
+    duplicate_policy::DuplicatePolicy,
+    file_hasher::{FileHasher, HashAlgorithm},
+    file_integrity::{FileIntegrity, FileVerificationReport, RelinkResult},
+    integrity_scan::{IntegrityReport, IntegrityScan},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use std::path::Path;
+
+#[tauri::command]
+pub fn get_hash_algorithm() -> Result<HashAlgorithm, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    FileHasher::configured(&conn)
+}
+
+/// Applies to files imported from now on; existing files keep their recorded algorithm.
+#[tauri::command]
+pub fn set_hash_algorithm(algorithm: HashAlgorithm, pin: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    FileHasher::set_configured(&conn, algorithm)?;
+    AuditLog::record(
+        &conn,
+        "settings.hash_algorithm",
+        None,
+        Some(serde_json::json!({ "algorithm": algorithm.as_str() })),
+    )
+}
+
+#[tauri::command]
+pub fn get_duplicate_policy() -> Result<DuplicatePolicy, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    DuplicatePolicy::configured(&conn)
+}
+
+/// Applies to imports from now on; files already in the library are left as they are.
+#[tauri::command]
+pub fn set_duplicate_policy(policy: DuplicatePolicy) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    DuplicatePolicy::set_configured(&conn, policy)?;
+    AuditLog::record(
+        &conn,
+        "settings.duplicate_policy",
+        None,
+        Some(serde_json::json!({ "policy": policy.as_str() })),
+    )
+}
+
+/// Re-hashes stored documents and reports any that are missing or no longer match.
+#[tauri::command]
+pub fn verify_file_hashes(file_ids: Option<Vec<String>>) -> Result<FileVerificationReport, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let report = FileIntegrity::verify(&conn, file_ids.as_deref())?;
+    AuditLog::record(
+        &conn,
+        "files.hashes_verified",
+        None,
+        Some(serde_json::json!({
+            "checked": report.checked,
+            "mismatched": report.mismatched,
+            "missing": report.missing,
+        })),
+    )?;
+    Ok(report)
+}
+
+/// Restores a record whose stored copy went missing from a replacement file. A replacement
+/// with a different hash is only taken with `accept_new_hash`, and the change is audited.
+#[tauri::command]
+pub fn relink_file(
+    file_id: String,
+    replacement_path: String,
+    accept_new_hash: Option<bool>,
+) -> Result<RelinkResult, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let result = FileIntegrity::relink(
+        &conn,
+        &file_id,
+        Path::new(&replacement_path),
+        accept_new_hash.unwrap_or(false),
+    )?;
+    let details = if result.hash_matched {
+        serde_json::json!({ "source": replacement_path })
+    } else {
+        serde_json::json!({
+            "source": replacement_path,
+            "previousHash": result.previous_hash,
+            "contentHash": result.content_hash,
+        })
+    };
+    AuditLog::record(
+        &conn,
+        if result.hash_matched {
+            "file.relinked"
+        } else {
+            "file.relinked_new_hash"
+        },
+        Some(&file_id),
+        Some(details),
+    )?;
+    Ok(result)
+}
+
+/// Runs the quick startup checks on demand. Stuck `Processing` files are reported but not
+/// reset, since processing may be running right now.
+#[tauri::command]
+pub fn run_integrity_scan() -> Result<IntegrityReport, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    IntegrityScan::run(&conn, false)
+}
+
+/// The most recent scan, for a UI that opened after the startup event was emitted.
+#[tauri::command]
+pub fn get_last_integrity_report() -> Result<Option<IntegrityReport>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    IntegrityScan::last_report(&conn)
+}
+
+/// Applies the safe repairs, then scans again.
+#[tauri::command]
+pub fn repair_integrity_issues() -> Result<IntegrityReport, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let repaired = IntegrityScan::repair(&conn)?;
+    AuditLog::record(
+        &conn,
+        "integrity.repaired",
+        None,
+        Some(serde_json::json!({ "repaired": repaired })),
+    )?;
+    IntegrityScan::run(&conn, false)
+}
+
+#[tauri::command]
+pub fn get_integrity_scan_on_startup() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    IntegrityScan::enabled_on_startup(&conn)
+}
+
+#[tauri::command]
+pub fn set_integrity_scan_on_startup(enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    IntegrityScan::set_enabled_on_startup(&conn, enabled)?;
+    AuditLog::record(
+        &conn,
+        "settings.integrity_scan_on_startup",
+        None,
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+}
@@ -0,0 +1,58 @@
+use crate::commands::file_operations::store_parsed_details;
+use crate::db::get_connection;
+use crate::services::{
+    invoices::{InvoiceDetail, InvoiceQuery, InvoiceRecord, InvoiceStore},
+    parsed_history::DetailsSource,
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+
+/// Saves an extraction result for a file and returns the structured invoice built from it.
+/// The same checks as any other parsed details update run first, so seller, buyer, dates
+/// and line items are settled before the invoice row is written.
+#[tauri::command]
+pub fn save_parsed_invoice(file_id: String, parsed_details: Value) -> Result<InvoiceDetail, String> {
+    if !parsed_details.is_object() {
+        return Err("Parsed invoice must be a JSON object".to_string());
+    }
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let exists: Option<String> = conn
+        .query_row("SELECT id FROM files WHERE id = ?1", params![file_id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|error| error.to_string())?;
+    if exists.is_none() {
+        return Err(format!("File not found: {}", file_id));
+    }
+
+    store_parsed_details(
+        &conn,
+        &file_id,
+        &parsed_details.to_string(),
+        DetailsSource::Extraction,
+    )?;
+    InvoiceStore::get(&conn, &file_id)?.ok_or_else(|| format!("No invoice stored for file: {}", file_id))
+}
+
+#[tauri::command]
+pub fn get_invoice(file_id: String) -> Result<Option<InvoiceDetail>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    InvoiceStore::get(&conn, &file_id)
+}
+
+#[tauri::command]
+pub fn list_invoices(query: Option<InvoiceQuery>) -> Result<Vec<InvoiceRecord>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    InvoiceStore::list(&conn, &query.unwrap_or_default())
+}
+
+/// Builds invoice rows for parsed files that do not have one yet. Returns how many were added.
+#[tauri::command]
+pub fn sync_invoices() -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    InvoiceStore::backfill(&conn)
+}
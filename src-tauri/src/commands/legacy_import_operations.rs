@@ -0,0 +1,165 @@
+use crate::commands::file_operations::{persist_buffer, FileStatus};
+use crate::commands::validation_operations::apply_parsed_details_checks;
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    batch_import::import_result,
+    import_batches::{ImportBatches, ImportSource},
+    legacy_import::{display_name, ColumnMapping, LegacyImportKind, LegacyImporter, SkippedRow},
+    parsed_history::{DetailsSource, ParsedHistory},
+    parsed_schema::ParsedSchema,
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyImportReport {
+    pub dry_run: bool,
+    pub index_file: Option<String>,
+    pub mapping: Vec<ColumnMapping>,
+    pub matched_rows: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub details_filled: usize,
+    pub unindexed_files: usize,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Migrates invoices kept by hand or in another tool. `kind` is `folder_index` (a folder
+/// of invoices plus a spreadsheet index) or `tool_export` (a CSV/Excel export whose rows
+/// reference attachments). Mapped columns become `parsed_details`, so imported files
+/// land as processed. With `dry_run` only the column mapping and row matching are reported.
+#[tauri::command]
+pub fn import_legacy_data(
+    kind: String,
+    path: String,
+    dry_run: Option<bool>,
+) -> Result<LegacyImportReport, String> {
+    let kind = LegacyImportKind::parse(&kind)?;
+    let plan = LegacyImporter::plan(kind, Path::new(&path))?;
+    let dry_run = dry_run.unwrap_or(false);
+
+    let mut report = LegacyImportReport {
+        dry_run,
+        index_file: plan
+            .index_file
+            .as_deref()
+            .map(|index| index.to_string_lossy().to_string()),
+        mapping: plan.mapping,
+        matched_rows: plan.records.len(),
+        imported: 0,
+        duplicates: 0,
+        details_filled: 0,
+        unindexed_files: plan.unindexed.len(),
+        skipped: plan.skipped,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let batch_id = ImportBatches::begin(&conn, ImportSource::Legacy, Some(&path))?;
+    for record in plan.records {
+        let (file_id, is_new) = match import_document(&conn, batch_id, &record.document) {
+            Ok(result) => result,
+            Err(error) => {
+                report.skipped.push(SkippedRow {
+                    row: record.row,
+                    reason: error,
+                });
+                continue;
+            }
+        };
+        if is_new {
+            report.imported += 1;
+        } else {
+            report.duplicates += 1;
+        }
+        if !record.fields.is_empty() && fill_parsed_details(&conn, &file_id, Value::Object(record.fields))? {
+            report.details_filled += 1;
+        }
+    }
+
+    for document in &plan.unindexed {
+        match import_document(&conn, batch_id, document) {
+            Ok((_, true)) => report.imported += 1,
+            Ok((_, false)) => report.duplicates += 1,
+            Err(_) => {}
+        }
+    }
+    ImportBatches::finish(&conn, batch_id, false)?;
+
+    AuditLog::record(
+        &conn,
+        "legacy.imported",
+        Some(&path),
+        Some(serde_json::json!({
+            "imported": report.imported,
+            "duplicates": report.duplicates,
+            "detailsFilled": report.details_filled,
+            "skippedRows": report.skipped.len(),
+        })),
+    )?;
+    Telemetry::record_feature(&conn, "legacy_import");
+
+    Ok(report)
+}
+
+/// Stores the document through the regular deduplicating import. Returns the file id and
+/// whether it was new.
+fn import_document(conn: &Connection, batch_id: i64, document: &Path) -> Result<(String, bool), String> {
+    let outcome = fs::read(document)
+        .map_err(|error| format!("{}: {}", display_name(document), error))
+        .and_then(|bytes| {
+            persist_buffer(&display_name(document), &bytes, Some(&document.to_string_lossy()))
+        });
+    ImportBatches::record(
+        conn,
+        batch_id,
+        &import_result(document.to_string_lossy().into_owned(), outcome.clone()),
+    )?;
+    let result = outcome?;
+    match result.split_once(':') {
+        Some(("OK", id)) => Ok((id.to_string(), true)),
+        Some(("DUPLICATE", id)) => Ok((id.to_string(), false)),
+        _ => Err(format!("Unexpected import result: {}", result)),
+    }
+}
+
+/// Writes legacy details unless the file already carries extracted ones.
+fn fill_parsed_details(conn: &Connection, file_id: &str, details: Value) -> Result<bool, String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?;
+    if existing.is_some_and(|value| !value.trim().is_empty()) {
+        return Ok(false);
+    }
+
+    let parsed_details = ParsedSchema::upgrade(&details.to_string());
+    ParsedHistory::record(conn, file_id, &parsed_details, DetailsSource::LegacyImport)?;
+    conn.execute(
+        "UPDATE files SET parsed_details = ?1, status = ?2, processed_at = ?3 WHERE id = ?4",
+        params![
+            parsed_details,
+            FileStatus::Processed.as_str(),
+            Utc::now().to_rfc3339(),
+            file_id
+        ],
+    )
+    .map_err(|error| error.to_string())?;
+    apply_parsed_details_checks(conn, file_id, Some(&parsed_details))?;
+
+    Ok(true)
+}
@@ -0,0 +1,212 @@
+use crate::commands::file_operations::store_parsed_details;
+use crate::db::get_connection;
+use crate::services::line_items::LineItemStore;
+use crate::services::parsed_history::DetailsSource;
+use crate::services::parsed_invoice::ITEMS;
+use crate::services::table_extraction::{TableExtraction, TableExtractor, TableLineItem};
+use crate::services::workspace_lock::WorkspaceLock;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LineItemFilters {
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    pub hsn_sac: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineItemHit {
+    pub file_id: String,
+    pub file_name: String,
+    pub stored_path: String,
+    pub invoice_date: Option<String>,
+    pub seller_name: Option<String>,
+    pub position: i64,
+    pub description: Option<String>,
+    pub name: Option<String>,
+    pub hsn_sac: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub rate: Option<f64>,
+    pub amount: Option<f64>,
+}
+
+#[tauri::command]
+pub fn search_line_items(query: String, filters: Option<LineItemFilters>) -> Result<Vec<LineItemHit>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let filters = filters.unwrap_or_default();
+
+    let mut where_clauses = Vec::new();
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    let trimmed = query.trim();
+    if !trimmed.is_empty() {
+        where_clauses.push("(li.description LIKE ? OR li.name LIKE ? OR li.hsn_sac LIKE ?)");
+        let pattern = format!("%{}%", trimmed);
+        params.push(Box::new(pattern.clone()));
+        params.push(Box::new(pattern.clone()));
+        params.push(Box::new(pattern));
+    }
+
+    if let Some(seller_id) = filters.seller_id {
+        where_clauses.push("f.seller_id = ?");
+        params.push(Box::new(seller_id));
+    }
+
+    if let Some(buyer_id) = filters.buyer_id {
+        where_clauses.push("f.buyer_id = ?");
+        params.push(Box::new(buyer_id));
+    }
+
+    if let Some(hsn_sac) = &filters.hsn_sac {
+        where_clauses.push("li.hsn_sac = ?");
+        params.push(Box::new(hsn_sac.clone()));
+    }
+
+    if let Some(date_from) = &filters.date_from {
+        where_clauses.push("f.invoice_date >= ?");
+        params.push(Box::new(date_from.clone()));
+    }
+
+    if let Some(date_to) = &filters.date_to {
+        where_clauses.push("f.invoice_date <= ?");
+        params.push(Box::new(date_to.clone()));
+    }
+
+    if let Some(min_amount) = filters.min_amount {
+        where_clauses.push("li.amount >= ?");
+        params.push(Box::new(min_amount));
+    }
+
+    if let Some(max_amount) = filters.max_amount {
+        where_clauses.push("li.amount <= ?");
+        params.push(Box::new(max_amount));
+    }
+
+    let where_clause = if where_clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", where_clauses.join(" AND "))
+    };
+
+    params.push(Box::new(filters.limit.unwrap_or(100)));
+    params.push(Box::new(filters.offset.unwrap_or(0)));
+
+    let sql = format!(
+        "SELECT f.id, f.file_name, f.stored_path, f.invoice_date, s.name,
+                li.position, li.description, li.name, li.hsn_sac, li.quantity, li.unit, li.rate, li.amount
+         FROM invoice_line_items li
+         JOIN files f ON f.id = li.file_id
+         LEFT JOIN sellers s ON s.id = f.seller_id
+         {}
+         ORDER BY f.invoice_date DESC, f.created_at DESC, li.position ASC
+         LIMIT ? OFFSET ?",
+        where_clause
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|error| error.to_string())?;
+    let hits_iter = stmt
+        .query_map(
+            rusqlite::params_from_iter(params.iter().map(|p| p.as_ref())),
+            |row| {
+                Ok(LineItemHit {
+                    file_id: row.get(0)?,
+                    file_name: row.get(1)?,
+                    stored_path: row.get(2)?,
+                    invoice_date: row.get(3)?,
+                    seller_name: row.get(4)?,
+                    position: row.get(5)?,
+                    description: row.get(6)?,
+                    name: row.get(7)?,
+                    hsn_sac: row.get(8)?,
+                    quantity: row.get(9)?,
+                    unit: row.get(10)?,
+                    rate: row.get(11)?,
+                    amount: row.get(12)?,
+                })
+            },
+        )
+        .map_err(|error| error.to_string())?;
+
+    let mut hits = Vec::new();
+    for hit in hits_iter {
+        hits.push(hit.map_err(|error| error.to_string())?);
+    }
+
+    Ok(hits)
+}
+
+/// Normalizes line items for parsed files that predate the line item table.
+#[tauri::command]
+pub fn sync_line_items() -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, parsed_details FROM files
+             WHERE parsed_details IS NOT NULL
+             AND id NOT IN (SELECT DISTINCT file_id FROM invoice_line_items)",
+        )
+        .map_err(|error| error.to_string())?;
+    let pending = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let mut total = 0;
+    for (file_id, parsed_details) in pending {
+        total += LineItemStore::replace_for_file(&conn, &file_id, Some(&parsed_details))?;
+    }
+
+    Ok(total)
+}
+
+/// Reads line items from the item table in a PDF's text layer. With `apply`, they replace
+/// the items in the file's parsed details.
+#[tauri::command(async)]
+pub fn extract_line_items(file_id: String, apply: Option<bool>) -> Result<TableExtraction, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let extraction = TableExtractor::extract_file(&conn, &file_id)?;
+    if !apply.unwrap_or(false) {
+        return Ok(extraction);
+    }
+    if extraction.items.is_empty() {
+        return Err("No item table was found in this document.".to_string());
+    }
+
+    WorkspaceLock::require_unlocked(&conn)?;
+    let parsed_details: Option<String> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .flatten();
+    let mut details = match parsed_details.as_deref().map(serde_json::from_str::<Value>) {
+        Some(Ok(Value::Object(details))) => details,
+        _ => Map::new(),
+    };
+    let items = extraction.items.iter().map(TableLineItem::to_json).collect();
+    details.insert(ITEMS.to_string(), Value::Array(items));
+    store_parsed_details(
+        &conn,
+        &file_id,
+        &Value::Object(details).to_string(),
+        DetailsSource::Extraction,
+    )?;
+    Ok(extraction)
+}
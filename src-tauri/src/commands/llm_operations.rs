@@ -0,0 +1,159 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    llm_extraction::{LlmExtraction, LlmParseResult, LlmRequestLog},
+    llm_provider::{LlmProviderKind, LlmProviders},
+    ollama::{LocalModelHealth, OllamaClient},
+    processing_pause::ProcessingPause,
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmProviderInfo {
+    pub provider: LlmProviderKind,
+    pub label: String,
+    pub model: String,
+    pub has_key: bool,
+    /// Runs on this machine; `endpoint` is where.
+    pub local: bool,
+    pub endpoint: Option<String>,
+    pub active: bool,
+}
+
+/// Extracts a stored file with `provider`, or the configured provider when not given, using
+/// the active prompts. Failures are stored on the file the same way the frontend stores them.
+#[tauri::command(async)]
+pub fn parse_file_with_llm(
+    file_id: String,
+    provider: Option<LlmProviderKind>,
+    response_schema: Option<Value>,
+) -> Result<LlmParseResult, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    if ProcessingPause::is_paused(&conn)? {
+        return Err("Processing is paused. Resume it before processing files.".to_string());
+    }
+    LlmExtraction::parse_file(&conn, &file_id, provider, response_schema.as_ref())
+}
+
+#[tauri::command]
+pub fn list_llm_providers() -> Result<Vec<LlmProviderInfo>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let active = LlmProviders::configured(&conn)?;
+    LlmProviderKind::ALL
+        .iter()
+        .map(|kind| {
+            Ok(LlmProviderInfo {
+                provider: *kind,
+                label: kind.label().to_string(),
+                model: LlmProviders::model(&conn, *kind)?,
+                has_key: !kind.is_local() && LlmProviders::has_key(*kind)?,
+                local: kind.is_local(),
+                endpoint: match kind.is_local() {
+                    true => Some(LlmProviders::local_endpoint(&conn)?),
+                    false => None,
+                },
+                active: *kind == active,
+            })
+        })
+        .collect()
+}
+
+/// Makes `provider` the one used when `parse_file_with_llm` is not told otherwise. A blank
+/// `model` goes back to the provider's default.
+#[tauri::command]
+pub fn set_llm_provider(provider: LlmProviderKind, model: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    LlmProviders::set_configured(&conn, provider)?;
+    LlmProviders::set_model(&conn, provider, model.as_deref())?;
+    AuditLog::record(
+        &conn,
+        "llm.provider_changed",
+        Some(provider.as_str()),
+        Some(serde_json::json!({ "model": LlmProviders::model(&conn, provider)? })),
+    )
+}
+
+#[tauri::command]
+pub fn set_llm_api_key(provider: LlmProviderKind, api_key: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    LlmProviders::set_key(provider, &api_key)?;
+    AuditLog::record(&conn, "llm.key_saved", Some(provider.as_str()), None)
+}
+
+#[tauri::command]
+pub fn clear_llm_api_key(provider: LlmProviderKind) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    LlmProviders::clear_key(provider)?;
+    AuditLog::record(&conn, "llm.key_cleared", Some(provider.as_str()), None)
+}
+
+/// Every logged extraction attempt for a file, newest first.
+#[tauri::command]
+pub fn list_llm_requests(file_id: String) -> Result<Vec<LlmRequestLog>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    LlmExtraction::history(&conn, &file_id)
+}
+
+/// Pings the local model server at `endpoint`, or the saved one, and lists its models so
+/// one can be picked with `set_llm_provider`.
+#[tauri::command(async)]
+pub fn check_local_llm(endpoint: Option<String>) -> Result<LocalModelHealth, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let endpoint = match endpoint.filter(|endpoint| !endpoint.trim().is_empty()) {
+        Some(endpoint) => endpoint,
+        None => LlmProviders::local_endpoint(&conn)?,
+    };
+    OllamaClient::health(&endpoint)
+}
+
+/// Where the Ollama or llama.cpp server listens. Only addresses on this machine are
+/// accepted; a blank `endpoint` goes back to Ollama's default.
+#[tauri::command]
+pub fn set_local_llm_endpoint(endpoint: Option<String>) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    LlmProviders::set_local_endpoint(&conn, endpoint.as_deref())?;
+    let endpoint = LlmProviders::local_endpoint(&conn)?;
+    AuditLog::record(
+        &conn,
+        "llm.local_endpoint_changed",
+        None,
+        Some(serde_json::json!({ "endpoint": endpoint })),
+    )?;
+    Ok(endpoint)
+}
+
+#[tauri::command]
+pub fn get_llm_temperature() -> Result<f64, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    LlmProviders::temperature(&conn)
+}
+
+/// Sampling temperature for every provider, between 0 and 2; `None` goes back to 0.
+#[tauri::command]
+pub fn set_llm_temperature(temperature: Option<f64>) -> Result<f64, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    LlmProviders::set_temperature(&conn, temperature)?;
+    let temperature = LlmProviders::temperature(&conn)?;
+    AuditLog::record(
+        &conn,
+        "llm.temperature_changed",
+        None,
+        Some(serde_json::json!({ "temperature": temperature })),
+    )?;
+    Ok(temperature)
+}
@@ -0,0 +1,18 @@
+use crate::db::get_connection;
+use crate::services::i18n::{I18n, Locale};
+use crate::services::workspace_lock::WorkspaceLock;
+
+/// Locale used for messages produced by the backend, e.g. `en`, `hi`, `de` or `fr`.
+#[tauri::command]
+pub fn get_locale() -> Result<String, String> {
+    Ok(I18n::locale().as_str().to_string())
+}
+
+#[tauri::command]
+pub fn set_locale(locale: String) -> Result<String, String> {
+    let locale = Locale::parse(&locale)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    I18n::set_locale(&conn, locale)?;
+    Ok(locale.as_str().to_string())
+}
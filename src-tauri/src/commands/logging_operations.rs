@@ -1,26 +1,12 @@
-use crate::db::storage_dir;
+use crate::services::log_statistics::{LogStatistics, LogStatisticsReport};
+use crate::services::log_writer::LogWriter;
 use chrono::Utc;
-use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-
-fn log_file_path() -> Result<PathBuf, String> {
-    let storage = storage_dir().map_err(|error| error.to_string())?;
-    let app_dir = storage
-        .parent()
-        .map(|parent| parent.to_path_buf())
-        .ok_or_else(|| "Unable to determine application directory for logging.".to_string())?;
-
-    let log_dir = app_dir.join("logs");
-    fs::create_dir_all(&log_dir).map_err(|error| error.to_string())?;
-
-    Ok(log_dir.join("invox.log"))
-}
 
 fn sanitize_log_value(value: &str) -> String {
     value.replace('\n', "\\n").replace('\r', "\\r")
 }
 
+/// Queues the line for the background log writer and returns without touching the disk.
 #[tauri::command]
 pub fn append_log_entry(
     level: &str,
@@ -28,13 +14,6 @@ pub fn append_log_entry(
     context: Option<String>,
     metadata: Option<String>,
 ) -> Result<(), String> {
-    let path = log_file_path()?;
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .map_err(|error| error.to_string())?;
-
     let timestamp = Utc::now().to_rfc3339();
     let level_upper = level.to_uppercase();
 
@@ -56,6 +35,11 @@ pub fn append_log_entry(
         }
     }
 
-    writeln!(file, "{line}").map_err(|error| error.to_string())?;
-    Ok(())
+    LogWriter::append(line)
+}
+
+/// Error and warning counts for `period` (`7d`, `30d` or a report period such as `2024-07`).
+#[tauri::command]
+pub fn get_log_statistics(period: String) -> Result<LogStatisticsReport, String> {
+    LogStatistics::collect(&period)
 }
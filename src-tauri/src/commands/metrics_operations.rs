@@ -0,0 +1,14 @@
+use crate::db::get_connection;
+use crate::services::{
+    parse_metrics::{ParseMetrics, ParsingMetrics},
+    report_period::ReportPeriod,
+};
+
+/// Parse success rates and p50/p95 time per stage, overall and per provider, for
+/// `period` or all time.
+#[tauri::command]
+pub fn get_parsing_metrics(period: Option<String>) -> Result<ParsingMetrics, String> {
+    let period = period.as_deref().map(ReportPeriod::parse).transpose()?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParseMetrics::summary(&conn, period.as_ref())
+}
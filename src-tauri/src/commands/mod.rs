@@ -2,8 +2,94 @@ pub mod file_operations;
 pub mod xml_operations;
 pub mod storage_operations;
 pub mod logging_operations;
+pub mod validation_operations;
+pub mod review_operations;
+pub mod duplicate_operations;
+pub mod party_operations;
+pub mod line_item_operations;
+pub mod payment_operations;
+pub mod report_operations;
+pub mod security_operations;
+pub mod audit_operations;
+pub mod notification_operations;
+pub mod search_operations;
+pub mod qr_operations;
+pub mod bank_operations;
+pub mod document_operations;
+pub mod schedule_operations;
+pub mod locale_operations;
+pub mod telemetry_operations;
+pub mod legacy_import_operations;
+pub mod csv_profile_operations;
+pub mod ocr_operations;
+pub mod prompt_operations;
+pub mod usage_operations;
+pub mod reprocess_operations;
+pub mod parsed_history_operations;
+pub mod bundle_operations;
+pub mod integrity_operations;
+pub mod tray_operations;
+pub mod exchange_rate_operations;
+pub mod pipeline_operations;
+pub mod trash_operations;
+pub mod export_operations;
+pub mod cloud_drive_operations;
+pub mod export_profile_operations;
+pub mod watched_folder_operations;
+pub mod import_batch_operations;
+pub mod llm_operations;
+pub mod parse_queue_operations;
+pub mod invoice_operations;
+pub mod template_operations;
+pub mod correction_operations;
+pub mod metrics_operations;
+pub mod tag_operations;
+pub mod backup_operations;
 
 pub use file_operations::*;
 pub use xml_operations::*;
 pub use storage_operations::*;
 pub use logging_operations::*;
+pub use validation_operations::*;
+pub use review_operations::*;
+pub use duplicate_operations::*;
+pub use party_operations::*;
+pub use line_item_operations::*;
+pub use payment_operations::*;
+pub use report_operations::*;
+pub use security_operations::*;
+pub use audit_operations::*;
+pub use notification_operations::*;
+pub use search_operations::*;
+pub use qr_operations::*;
+pub use bank_operations::*;
+pub use document_operations::*;
+pub use schedule_operations::*;
+pub use locale_operations::*;
+pub use telemetry_operations::*;
+pub use legacy_import_operations::*;
+pub use csv_profile_operations::*;
+pub use ocr_operations::*;
+pub use prompt_operations::*;
+pub use usage_operations::*;
+pub use reprocess_operations::*;
+pub use parsed_history_operations::*;
+pub use bundle_operations::*;
+pub use integrity_operations::*;
+pub use tray_operations::*;
+pub use exchange_rate_operations::*;
+pub use pipeline_operations::*;
+pub use trash_operations::*;
+pub use export_operations::*;
+pub use cloud_drive_operations::*;
+pub use export_profile_operations::*;
+pub use watched_folder_operations::*;
+pub use import_batch_operations::*;
+pub use llm_operations::*;
+pub use parse_queue_operations::*;
+pub use invoice_operations::*;
+pub use template_operations::*;
+pub use correction_operations::*;
+pub use metrics_operations::*;
+pub use tag_operations::*;
+pub use backup_operations::*;
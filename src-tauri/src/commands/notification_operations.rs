@@ -0,0 +1,27 @@
+use crate::db::get_connection;
+use crate::services::{
+    notifier::{JobEvent, Notifier},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use tauri::AppHandle;
+
+/// Called by the processing flow once a batch has been saved.
+#[tauri::command]
+pub fn notify_batch_finished(app: AppHandle, processed: usize, failed: usize) {
+    Notifier::notify(&app, JobEvent::BatchProcessed { processed, failed });
+}
+
+#[tauri::command]
+pub fn get_notifications_enabled() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Notifier::enabled(&conn)
+}
+
+#[tauri::command]
+pub fn set_notifications_enabled(enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    Notifier::set_enabled(&conn, enabled)
+}
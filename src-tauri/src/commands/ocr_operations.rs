@@ -0,0 +1,76 @@
+use crate::db::get_connection;
+use crate::services::language_detection::{DocumentLanguage, LanguageDetector};
+use crate::services::ocr_languages::{LanguagePack, OcrLanguages};
+use crate::services::parsed_invoice::BoundingBox;
+use crate::services::region_capture::{RegionCapture, RegionText};
+use crate::services::roles::Roles;
+use crate::services::workspace_lock::WorkspaceLock;
+
+/// The downloadable OCR languages and whether each pack is installed.
+#[tauri::command]
+pub fn list_ocr_languages() -> Result<Vec<LanguagePack>, String> {
+    OcrLanguages::list()
+}
+
+/// Downloads a Tesseract language pack (e.g. `hin`) into the app data dir. Runs off the
+/// main thread so the window stays responsive during the transfer.
+#[tauri::command(async)]
+pub fn download_ocr_language(code: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    drop(conn);
+    OcrLanguages::download(&code).map(|path| path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn remove_ocr_language(code: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    OcrLanguages::remove(&code)
+}
+
+#[tauri::command]
+pub fn get_default_ocr_languages() -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    OcrLanguages::default_languages(&conn)
+}
+
+#[tauri::command]
+pub fn set_default_ocr_languages(languages: Vec<String>) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    OcrLanguages::set_default_languages(&conn, &languages)
+}
+
+/// Languages the file is written in, e.g. `eng+hin`; falls back to the default selection.
+#[tauri::command]
+pub fn get_file_ocr_languages(file_id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    OcrLanguages::for_file(&conn, &file_id)
+}
+
+/// Overrides the languages for one file; an empty list reverts to the default.
+#[tauri::command]
+pub fn set_file_ocr_languages(file_id: String, languages: Vec<String>) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    OcrLanguages::set_for_file(&conn, &file_id, &languages)
+}
+
+/// Languages the processed documents are written in, with how many files each has.
+#[tauri::command]
+pub fn list_document_languages() -> Result<Vec<DocumentLanguage>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    LanguageDetector::in_use(&conn)
+}
+
+/// Reads the text inside `rect` on `page` (1-based), so a field the extraction keeps missing
+/// can be snipped by hand. `rect` is in page fractions from the top left.
+#[tauri::command(async)]
+pub fn extract_region(file_id: String, page: u32, rect: BoundingBox) -> Result<RegionText, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    RegionCapture::extract(&conn, &file_id, page, rect)
+}
@@ -0,0 +1,147 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    batch_parse::{BatchParse, BatchParseSummary},
+    llm_provider::LlmProviderKind,
+    parse_queue::{ParseJob, ParseQueue, ParseQueueStatus},
+    processing_pause::ProcessingPause,
+    roles::Roles,
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use serde_json::Value;
+use tauri::AppHandle;
+
+const DEFAULT_JOB_LIMIT: i64 = 200;
+
+/// Queues files for background extraction. `response_schema` is kept for every queued job;
+/// progress is emitted as `parse-queue://progress`. Returns how many jobs were added.
+#[tauri::command]
+pub fn enqueue_parse_jobs(
+    file_ids: Vec<String>,
+    provider: Option<LlmProviderKind>,
+    response_schema: Option<Value>,
+) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    if let Some(schema) = &response_schema {
+        ParseQueue::set_response_schema(&conn, schema)?;
+    }
+    Telemetry::record_feature(&conn, "parse_queue");
+    ParseQueue::enqueue(&conn, &file_ids, provider)
+}
+
+/// Queues every Unprocessed file that is not queued yet.
+#[tauri::command]
+pub fn enqueue_unprocessed_files(
+    provider: Option<LlmProviderKind>,
+    response_schema: Option<Value>,
+) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    if let Some(schema) = &response_schema {
+        ParseQueue::set_response_schema(&conn, schema)?;
+    }
+    let file_ids = ParseQueue::unprocessed(&conn)?;
+    Telemetry::record_feature(&conn, "parse_queue");
+    ParseQueue::enqueue(&conn, &file_ids, provider)
+}
+
+/// Retries Failed files among `file_ids`, or every Failed file, right away instead of
+/// waiting for their backoff. Returns how many files will be retried.
+#[tauri::command]
+pub fn retry_failed_files(
+    file_ids: Option<Vec<String>>,
+    provider: Option<LlmProviderKind>,
+) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let retried = ParseQueue::retry_failed(&conn, file_ids.as_deref(), provider)?;
+    AuditLog::record(
+        &conn,
+        "parse_queue.retried_failed",
+        None,
+        Some(serde_json::json!({ "files": retried })),
+    )?;
+    Ok(retried)
+}
+
+/// Cancels queued jobs for `file_ids`, or all queued jobs when not given.
+#[tauri::command]
+pub fn cancel_parse_jobs(file_ids: Option<Vec<String>>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParseQueue::cancel(&conn, file_ids.as_deref())
+}
+
+/// Stops one job, queued or running. A running extraction is abandoned mid-request and its
+/// file goes back to Unprocessed.
+#[tauri::command]
+pub fn cancel_parse(job_id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParseQueue::cancel_job(&conn, job_id)
+}
+
+#[tauri::command]
+pub fn get_parse_queue_status() -> Result<ParseQueueStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParseQueue::status(&conn)
+}
+
+#[tauri::command]
+pub fn list_parse_jobs(status: Option<String>, limit: Option<i64>) -> Result<Vec<ParseJob>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParseQueue::list(&conn, status.as_deref(), limit.unwrap_or(DEFAULT_JOB_LIMIT))
+}
+
+/// How many files are extracted at once.
+#[tauri::command]
+pub fn set_parse_workers(count: usize) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ParseQueue::set_workers(&conn, count)?;
+    AuditLog::record(
+        &conn,
+        "parse_queue.workers_changed",
+        None,
+        Some(serde_json::json!({ "workers": count })),
+    )
+}
+
+/// Attempts per file, counting the first, before a failed extraction is left Failed.
+#[tauri::command]
+pub fn set_parse_retry_limit(max_attempts: u32) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ParseQueue::set_max_attempts(&conn, max_attempts)?;
+    AuditLog::record(
+        &conn,
+        "parse_queue.retry_limit_changed",
+        None,
+        Some(serde_json::json!({ "maxAttempts": max_attempts })),
+    )
+}
+
+/// Extracts `file_ids` now, up to `concurrency` at a time (the worker count by default),
+/// and returns once all are done. Each file's start and result are emitted as
+/// `parse-files://progress`.
+#[tauri::command(async)]
+pub fn parse_files(
+    app: AppHandle,
+    file_ids: Vec<String>,
+    provider: Option<LlmProviderKind>,
+    response_schema: Option<Value>,
+    concurrency: Option<usize>,
+) -> Result<BatchParseSummary, String> {
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    if ProcessingPause::is_paused(&conn)? {
+        return Err("Processing is paused. Resume it before processing files.".to_string());
+    }
+    if let Some(schema) = &response_schema {
+        ParseQueue::set_response_schema(&conn, schema)?;
+    }
+    Telemetry::record_feature(&conn, "batch_parse");
+    BatchParse::run(&app, &mut conn, &file_ids, provider, concurrency)
+}
@@ -0,0 +1,30 @@
+use crate::commands::file_operations::store_parsed_details;
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    parsed_history::{DetailsSource, ParsedDetailsVersion, ParsedHistory},
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn get_parsed_history(file_id: String) -> Result<Vec<ParsedDetailsVersion>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ParsedHistory::list(&conn, &file_id)
+}
+
+/// Restores an earlier version. The restored details are saved as a new version, so the
+/// rollback itself can be undone.
+#[tauri::command]
+pub fn rollback_parsed_details(file_id: String, version: i64) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let parsed_details = ParsedHistory::get(&conn, &file_id, version)?;
+    store_parsed_details(&conn, &file_id, &parsed_details, DetailsSource::Rollback)?;
+    AuditLog::record(
+        &conn,
+        "file.parsed_details_rolled_back",
+        Some(&file_id),
+        Some(serde_json::json!({ "version": version })),
+    )?;
+    Ok(parsed_details)
+}
@@ -0,0 +1,127 @@
+use crate::db::get_connection;
+use crate::services::audit_log::AuditLog;
+use crate::services::party_registry::{PartyRow, BUYERS, SELLERS};
+use crate::services::roles::Roles;
+use crate::services::workspace_lock::WorkspaceLock;
+
+#[tauri::command]
+pub fn list_sellers() -> Result<Vec<PartyRow>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    SELLERS.list(&conn)
+}
+
+#[tauri::command]
+pub fn merge_sellers(ids: Vec<i64>, canonical_id: i64) -> Result<(), String> {
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.merge_parties")?;
+    SELLERS.merge(&mut conn, &ids, canonical_id)?;
+    AuditLog::record(
+        &conn,
+        "sellers.merged",
+        Some(&canonical_id.to_string()),
+        Some(serde_json::json!({ "mergedIds": ids })),
+    )
+}
+
+#[tauri::command]
+pub fn add_seller_alias(seller_id: i64, alias: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    SELLERS.add_alias(&conn, seller_id, &alias)
+}
+
+#[tauri::command]
+pub fn remove_seller_alias(alias: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.remove_aliases")?;
+    SELLERS.remove_alias(&conn, &alias)?;
+    AuditLog::record(
+        &conn,
+        "sellers.alias_removed",
+        None,
+        Some(serde_json::json!({ "alias": alias })),
+    )
+}
+
+#[tauri::command]
+pub fn rename_seller(seller_id: i64, name: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    SELLERS.rename(&conn, seller_id, &name)?;
+    AuditLog::record(
+        &conn,
+        "sellers.renamed",
+        Some(&seller_id.to_string()),
+        Some(serde_json::json!({ "name": name })),
+    )
+}
+
+#[tauri::command]
+pub fn sync_sellers() -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    SELLERS.sync(&conn)
+}
+
+#[tauri::command]
+pub fn list_buyers() -> Result<Vec<PartyRow>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    BUYERS.list(&conn)
+}
+
+#[tauri::command]
+pub fn merge_buyers(ids: Vec<i64>, canonical_id: i64) -> Result<(), String> {
+    let mut conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.merge_parties")?;
+    BUYERS.merge(&mut conn, &ids, canonical_id)?;
+    AuditLog::record(
+        &conn,
+        "buyers.merged",
+        Some(&canonical_id.to_string()),
+        Some(serde_json::json!({ "mergedIds": ids })),
+    )
+}
+
+#[tauri::command]
+pub fn add_buyer_alias(buyer_id: i64, alias: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    BUYERS.add_alias(&conn, buyer_id, &alias)
+}
+
+#[tauri::command]
+pub fn remove_buyer_alias(alias: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.remove_aliases")?;
+    BUYERS.remove_alias(&conn, &alias)?;
+    AuditLog::record(
+        &conn,
+        "buyers.alias_removed",
+        None,
+        Some(serde_json::json!({ "alias": alias })),
+    )
+}
+
+#[tauri::command]
+pub fn rename_buyer(buyer_id: i64, name: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    BUYERS.rename(&conn, buyer_id, &name)?;
+    AuditLog::record(
+        &conn,
+        "buyers.renamed",
+        Some(&buyer_id.to_string()),
+        Some(serde_json::json!({ "name": name })),
+    )
+}
+
+#[tauri::command]
+pub fn sync_buyers() -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    BUYERS.sync(&conn)
+}
@@ -0,0 +1,147 @@
+use crate::db::get_connection;
+use crate::services::audit_log::AuditLog;
+use crate::services::parsed_invoice::{ParsedInvoice, GRAND_TOTAL, INVOICE_NUMBER};
+use crate::services::workspace_lock::WorkspaceLock;
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayableRow {
+    pub file_id: String,
+    pub file_name: String,
+    pub seller_name: Option<String>,
+    pub invoice_number: Option<String>,
+    pub amount: Option<f64>,
+    pub invoice_date: Option<String>,
+    pub due_date: String,
+    pub payment_terms: Option<String>,
+    /// Positive when the due date has passed, negative while it is still ahead.
+    pub days_overdue: i64,
+}
+
+fn parse_iso_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Expected a date in YYYY-MM-DD format, got \"{}\".", value))
+}
+
+fn list_unpaid_due_between(from: Option<NaiveDate>, to: NaiveDate, today: NaiveDate) -> Result<Vec<PayableRow>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.id, f.file_name, s.name, f.parsed_details, f.invoice_date, f.due_date, f.payment_terms
+             FROM files f
+             LEFT JOIN sellers s ON s.id = f.seller_id
+             WHERE f.due_date IS NOT NULL
+             AND (?1 IS NULL OR f.due_date >= ?1)
+             AND f.due_date <= ?2
+             AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.file_id = f.id)
+             ORDER BY f.due_date ASC",
+        )
+        .map_err(|error| error.to_string())?;
+
+    let rows_iter = stmt
+        .query_map(
+            params![
+                from.map(|date| date.format("%Y-%m-%d").to_string()),
+                to.format("%Y-%m-%d").to_string()
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                ))
+            },
+        )
+        .map_err(|error| error.to_string())?;
+
+    let mut rows = Vec::new();
+    for row in rows_iter {
+        let (file_id, file_name, seller_name, parsed_details, invoice_date, due_date, payment_terms) =
+            row.map_err(|error| error.to_string())?;
+        let invoice = parsed_details.as_deref().and_then(ParsedInvoice::from_json);
+        let days_overdue = parse_iso_date(&due_date)
+            .map(|due| (today - due).num_days())
+            .unwrap_or(0);
+
+        rows.push(PayableRow {
+            file_id,
+            file_name,
+            seller_name,
+            invoice_number: invoice.as_ref().and_then(|invoice| invoice.text(INVOICE_NUMBER)),
+            amount: invoice.as_ref().and_then(|invoice| invoice.number(GRAND_TOTAL)),
+            invoice_date,
+            due_date,
+            payment_terms,
+            days_overdue,
+        });
+    }
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub fn mark_paid(
+    file_id: String,
+    date: String,
+    reference: Option<String>,
+    amount: Option<f64>,
+) -> Result<(), String> {
+    let paid_on = parse_iso_date(&date)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+
+    conn.query_row("SELECT 1 FROM files WHERE id = ?1", params![file_id], |_row| Ok(()))
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    conn.execute(
+        "INSERT INTO payments (file_id, paid_on, reference, amount) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(file_id) DO UPDATE SET
+           paid_on = excluded.paid_on,
+           reference = excluded.reference,
+           amount = excluded.amount",
+        params![file_id, paid_on.format("%Y-%m-%d").to_string(), reference, amount],
+    )
+    .map_err(|error| error.to_string())?;
+
+    AuditLog::record(
+        &conn,
+        "payment.marked_paid",
+        Some(&file_id),
+        Some(serde_json::json!({ "paidOn": date, "reference": reference, "amount": amount })),
+    )
+}
+
+#[tauri::command]
+pub fn unmark_paid(file_id: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    conn.execute("DELETE FROM payments WHERE file_id = ?1", params![file_id])
+        .map_err(|error| error.to_string())?;
+    AuditLog::record(&conn, "payment.unmarked", Some(&file_id), None)
+}
+
+#[tauri::command]
+pub fn list_overdue_payments(as_of: Option<String>) -> Result<Vec<PayableRow>, String> {
+    let today = match as_of {
+        Some(value) => parse_iso_date(&value)?,
+        None => Utc::now().date_naive(),
+    };
+    list_unpaid_due_between(None, today - Duration::days(1), today)
+}
+
+#[tauri::command]
+pub fn list_upcoming_payments(days: Option<i64>) -> Result<Vec<PayableRow>, String> {
+    let today = Utc::now().date_naive();
+    let horizon = today + Duration::days(days.unwrap_or(7).max(0));
+    list_unpaid_due_between(Some(today), horizon, today)
+}
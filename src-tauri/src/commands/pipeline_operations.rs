@@ -0,0 +1,45 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    folder_pipeline::{FolderPipeline, FolderPipelineSummary, ProcessFolderOptions},
+    notifier::{JobEvent, Notifier},
+    workspace_lock::WorkspaceLock,
+};
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Imports, extracts and exports a whole folder as one job. Progress is emitted as
+/// `pipeline://progress`; the returned summary covers every file in the folder.
+#[tauri::command(async)]
+pub fn process_folder(
+    app: AppHandle,
+    path: String,
+    profile: Option<String>,
+    options: ProcessFolderOptions,
+) -> Result<FolderPipelineSummary, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let summary = FolderPipeline::run(&app, &conn, Path::new(&path), profile, &options)?;
+
+    AuditLog::record(
+        &conn,
+        "pipeline.folder_processed",
+        Some(&path),
+        Some(serde_json::json!({
+            "imported": summary.imported,
+            "duplicates": summary.duplicates,
+            "processed": summary.processed,
+            "failed": summary.failures.len(),
+            "sheetId": summary.sheet_id,
+        })),
+    )?;
+    Notifier::notify(
+        &app,
+        JobEvent::BatchProcessed {
+            processed: summary.processed,
+            failed: summary.failures.len(),
+        },
+    );
+
+    Ok(summary)
+}
@@ -0,0 +1,177 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    gemini::{mime_for, GeminiClient, GeminiRequest},
+    llm_provider::LlmProviders,
+    parsed_schema::ParsedSchema,
+    processing_usage::{ProcessingUsage, UsageRecord},
+    prompt_templates::{ActivePrompts, PromptKind, PromptTemplate, PromptTemplates},
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTestResult {
+    pub template_id: i64,
+    pub version: i64,
+    pub output: Value,
+    /// The file's stored parsed_details, for side-by-side comparison.
+    pub current: Option<Value>,
+    /// Top-level fields whose value differs between `output` and `current`.
+    pub changed_fields: Vec<String>,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Every stored version of a prompt, newest first.
+#[tauri::command]
+pub fn list_prompt_templates(kind: PromptKind) -> Result<Vec<PromptTemplate>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    PromptTemplates::list(&conn, kind)
+}
+
+/// Prompts to extract with. `defaults` are the prompts bundled with the frontend; they
+/// become version 1 the first time, after which the stored active versions win.
+#[tauri::command]
+pub fn get_active_prompts(defaults: ActivePrompts) -> Result<ActivePrompts, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    PromptTemplates::active_or_seed(&conn, &defaults)
+}
+
+/// Saves a new version of a prompt and makes it active.
+#[tauri::command]
+pub fn save_prompt_template(
+    kind: PromptKind,
+    content: String,
+    notes: Option<String>,
+) -> Result<PromptTemplate, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_prompts")?;
+
+    let template = PromptTemplates::save(&conn, kind, &content, notes.as_deref())?;
+    AuditLog::record(
+        &conn,
+        "prompt.saved",
+        Some(&template.id.to_string()),
+        Some(serde_json::json!({ "kind": template.kind, "version": template.version })),
+    )?;
+
+    Ok(template)
+}
+
+/// Rolls a prompt back (or forward) to a stored version.
+#[tauri::command]
+pub fn activate_prompt_template(id: i64) -> Result<PromptTemplate, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_prompts")?;
+
+    let kind = PromptKind::parse(&PromptTemplates::get(&conn, id)?.kind)?;
+    let previous = PromptTemplates::active(&conn, kind)?;
+    let template = PromptTemplates::activate(&conn, id)?;
+    AuditLog::record(
+        &conn,
+        "prompt.activated",
+        Some(&template.id.to_string()),
+        Some(serde_json::json!({
+            "kind": template.kind,
+            "version": template.version,
+            "previousVersion": previous.map(|previous| previous.version),
+        })),
+    )?;
+
+    Ok(template)
+}
+
+/// Runs one prompt version against a stored file without saving anything, paired with
+/// the active version of the other prompt. `response_schema` should be the schema the
+/// frontend sends, so the result matches a real extraction.
+#[tauri::command(async)]
+pub fn test_prompt_template(
+    id: i64,
+    file_id: String,
+    api_key: String,
+    model: String,
+    response_schema: Option<Value>,
+) -> Result<PromptTestResult, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let template = PromptTemplates::get(&conn, id)?;
+    let kind = PromptKind::parse(&template.kind)?;
+    let other_kind = match kind {
+        PromptKind::SystemInstruction => PromptKind::UserPrompt,
+        PromptKind::UserPrompt => PromptKind::SystemInstruction,
+    };
+    let other = PromptTemplates::active(&conn, other_kind)?
+        .ok_or_else(|| format!("No active {} is stored yet.", other_kind.as_str()))?;
+    let (system_instruction, prompt) = match kind {
+        PromptKind::SystemInstruction => (template.content.as_str(), other.content.as_str()),
+        PromptKind::UserPrompt => (other.content.as_str(), template.content.as_str()),
+    };
+
+    let (stored_path, parsed_details): (String, Option<String>) = conn
+        .query_row(
+            "SELECT stored_path, parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+    let path = Path::new(&stored_path);
+    let mime_type =
+        mime_for(path).ok_or_else(|| "This file type cannot be sent for extraction.".to_string())?;
+    let document = std::fs::read(path).map_err(|error| error.to_string())?;
+
+    let response = GeminiClient::new(&api_key, &model)?.generate(&GeminiRequest {
+        system_instruction,
+        prompt,
+        document: &document,
+        mime_type,
+        response_schema: response_schema.as_ref(),
+        temperature: LlmProviders::temperature(&conn)?,
+    })?;
+    ProcessingUsage::record(
+        &conn,
+        &UsageRecord {
+            file_id: Some(file_id),
+            model,
+            profile: Some("prompt_test".to_string()),
+            prompt_tokens: response.prompt_tokens,
+            output_tokens: response.output_tokens,
+        },
+    )?;
+
+    // Both sides at the current schema version, so only real differences are listed.
+    let upgraded = |raw: &str| serde_json::from_str::<Value>(&ParsedSchema::upgrade(raw)).ok();
+    let current: Option<Value> = parsed_details.as_deref().and_then(upgraded);
+    let changed_fields = match (&upgraded(&response.output.to_string()), &current) {
+        (Some(Value::Object(output)), Some(Value::Object(current))) => {
+            let mut fields: Vec<String> = output
+                .keys()
+                .chain(current.keys())
+                .filter(|key| output.get(*key) != current.get(*key))
+                .cloned()
+                .collect();
+            fields.sort();
+            fields.dedup();
+            fields
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(PromptTestResult {
+        template_id: template.id,
+        version: template.version,
+        output: response.output,
+        current,
+        changed_fields,
+        prompt_tokens: response.prompt_tokens,
+        output_tokens: response.output_tokens,
+    })
+}
@@ -0,0 +1,80 @@
+use crate::commands::validation_operations::apply_qr_cross_check;
+use crate::db::get_connection;
+use crate::services::{
+    invoice_qr::{QrPayload, QrPayloadKind},
+    parsed_invoice::ParsedInvoice,
+    qr_scanner::{QrMismatch, QrScanner, ScannedQr},
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileQrCodes {
+    pub file_id: String,
+    pub codes: Vec<ScannedQr>,
+    pub mismatches: Vec<QrMismatch>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceQr {
+    pub kind: QrPayloadKind,
+    pub payload: String,
+    pub png: Vec<u8>,
+}
+
+/// Renders the e-invoice QR (or a payment QR when there is none) of a parsed file as PNG.
+#[tauri::command]
+pub fn generate_invoice_qr(file_id: String) -> Result<InvoiceQr, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let parsed_details: Option<String> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    let invoice = parsed_details
+        .as_deref()
+        .and_then(ParsedInvoice::from_json)
+        .ok_or_else(|| "File has not been processed yet.".to_string())?;
+
+    let payload = QrPayload::for_invoice(&invoice)?;
+    Telemetry::record_feature(&conn, "invoice_qr");
+    let png = payload.to_png()?;
+
+    Ok(InvoiceQr {
+        kind: payload.kind,
+        payload: payload.content,
+        png,
+    })
+}
+
+/// Decodes the QR codes on a file again and re-checks them against its parsed details.
+#[tauri::command]
+pub fn scan_file_qr_codes(file_id: String) -> Result<FileQrCodes, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let codes = QrScanner::scan_file(&conn, &file_id)?;
+    let parsed_details: Option<String> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?;
+    let mismatches = apply_qr_cross_check(&conn, &file_id, parsed_details.as_deref())?;
+    Telemetry::record_feature(&conn, "qr_scan");
+
+    Ok(FileQrCodes {
+        file_id,
+        codes,
+        mismatches,
+    })
+}
@@ -0,0 +1,649 @@
+use crate::db::get_connection;
+use crate::services::{
+    csv_export::CsvProfiles,
+    gst_returns::{GstReturn, GstReturnKind},
+    i18n::I18n,
+    invoice_facts::InvoiceFacts,
+    notifier::{JobEvent, Notifier},
+    parsed_invoice::{ParsedInvoice, GRAND_TOTAL, INVOICE_NUMBER},
+    pdf_report::{format_amount, PdfReport, ReportTable},
+    report_period::ReportPeriod,
+    spreadsheet::{write_workbook, Cell, Sheet},
+    telemetry::Telemetry,
+};
+use chrono::{Local, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GstReturnExportResult {
+    pub path: String,
+    pub b2b_invoices: usize,
+    pub b2cs_rows: usize,
+    pub hsn_rows: usize,
+    pub skipped_files: usize,
+}
+
+/// Writes a GSTR-1 or GSTR-2B style return for `period` as `json`, `xlsx` or `csv`.
+/// CSV output writes one file per section using the named `csv_profile`.
+#[tauri::command]
+pub fn export_gst_return(
+    app: AppHandle,
+    kind: String,
+    period: String,
+    format: String,
+    output_path: String,
+    gstin: Option<String>,
+    csv_profile: Option<String>,
+) -> Result<GstReturnExportResult, String> {
+    let kind = GstReturnKind::parse(&kind)?;
+    let period = ReportPeriod::parse(&period)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let gst_return = GstReturn::build(&conn, kind, period, gstin.as_deref())?;
+
+    let path = Path::new(&output_path);
+    match format.trim().to_ascii_lowercase().as_str() {
+        "json" => {
+            let content = serde_json::to_string_pretty(&gst_return.to_json())
+                .map_err(|error| error.to_string())?;
+            std::fs::write(path, content).map_err(|error| error.to_string())?;
+        }
+        "xlsx" => write_workbook(path, &gst_return.to_sheets())?,
+        "csv" => {
+            CsvProfiles::get(&conn, csv_profile.as_deref())?.write_sheets(path, &gst_return.to_sheets())?;
+        }
+        other => return Err(format!("Unsupported export format \"{}\". Use json, xlsx or csv.", other)),
+    }
+
+    Telemetry::record_export(&conn, "gst_return");
+    Notifier::notify(
+        &app,
+        JobEvent::ExportCompleted {
+            label: "GST return export".to_string(),
+            path: output_path.clone(),
+        },
+    );
+
+    Ok(GstReturnExportResult {
+        path: output_path,
+        b2b_invoices: gst_return.b2b.len(),
+        b2cs_rows: gst_return.b2cs.len(),
+        hsn_rows: gst_return.hsn.len(),
+        skipped_files: gst_return.skipped_files,
+    })
+}
+
+#[derive(Default)]
+struct SpendTotals {
+    invoices: usize,
+    subtotal: f64,
+    tax: f64,
+    total: f64,
+}
+
+impl SpendTotals {
+    fn add(&mut self, subtotal: f64, tax: f64, total: f64) {
+        self.invoices += 1;
+        self.subtotal += subtotal;
+        self.tax += tax;
+        self.total += total;
+    }
+
+    fn cells(&self, label: String) -> Vec<String> {
+        vec![
+            label,
+            self.invoices.to_string(),
+            format_amount(self.subtotal),
+            format_amount(self.tax),
+            format_amount(self.total),
+        ]
+    }
+}
+
+fn spend_summary_tables(conn: &Connection, period: &ReportPeriod) -> Result<Vec<ReportTable>, String> {
+    let facts = InvoiceFacts::load(conn, Some(period))?;
+
+    let mut overall = SpendTotals::default();
+    let mut by_month: BTreeMap<String, SpendTotals> = BTreeMap::new();
+    let mut by_seller: BTreeMap<String, SpendTotals> = BTreeMap::new();
+    for fact in &facts {
+        let month = fact.invoice_date.get(..7).unwrap_or(&fact.invoice_date).to_string();
+        let seller = fact
+            .seller_name
+            .clone()
+            .unwrap_or_else(|| I18n::text("report.unknown_seller").to_string());
+        overall.add(fact.subtotal, fact.tax_total, fact.grand_total);
+        by_month.entry(month).or_default().add(fact.subtotal, fact.tax_total, fact.grand_total);
+        by_seller.entry(seller).or_default().add(fact.subtotal, fact.tax_total, fact.grand_total);
+    }
+
+    let mut sellers: Vec<(String, SpendTotals)> = by_seller.into_iter().collect();
+    sellers.sort_by(|a, b| b.1.total.total_cmp(&a.1.total));
+
+    let columns = vec![
+        "",
+        I18n::text("report.invoices"),
+        I18n::text("report.taxable_value"),
+        I18n::text("report.tax"),
+        I18n::text("report.total"),
+    ];
+    Ok(vec![
+        ReportTable {
+            heading: I18n::text("report.overview").to_string(),
+            columns: columns.clone(),
+            rows: vec![overall.cells(I18n::text("report.all_invoices").to_string())],
+            totals: None,
+        },
+        ReportTable {
+            heading: I18n::text("report.spend_by_month").to_string(),
+            columns: {
+                let mut columns = columns.clone();
+                columns[0] = I18n::text("report.month");
+                columns
+            },
+            rows: by_month
+                .iter()
+                .map(|(month, totals)| totals.cells(month.clone()))
+                .collect(),
+            totals: Some(overall.cells(I18n::text("report.total").to_string())),
+        },
+        ReportTable {
+            heading: I18n::text("report.spend_by_seller").to_string(),
+            columns: {
+                let mut columns = columns;
+                columns[0] = I18n::text("report.seller");
+                columns
+            },
+            rows: sellers
+                .iter()
+                .map(|(seller, totals)| totals.cells(seller.clone()))
+                .collect(),
+            totals: Some(overall.cells(I18n::text("report.total").to_string())),
+        },
+    ])
+}
+
+fn tax_summary_tables(conn: &Connection, period: &ReportPeriod) -> Result<Vec<ReportTable>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(li.cgst_rate, 0) + COALESCE(li.sgst_rate, 0) AS rate,
+                    COUNT(DISTINCT li.file_id),
+                    SUM(COALESCE(li.amount, 0)),
+                    SUM(COALESCE(li.cgst, 0)),
+                    SUM(COALESCE(li.sgst, 0))
+             FROM invoice_line_items li
+             JOIN files f ON f.id = li.file_id
+             WHERE f.invoice_date BETWEEN ?1 AND ?2
+             GROUP BY rate
+             ORDER BY rate ASC",
+        )
+        .map_err(|error| error.to_string())?;
+    let rates = stmt
+        .query_map(params![period.start_iso(), period.end_iso()], |row| {
+            Ok((
+                row.get::<_, f64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, f64>(4)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let (taxable, cgst, sgst) = rates.iter().fold((0.0, 0.0, 0.0), |acc, rate| {
+        (acc.0 + rate.2, acc.1 + rate.3, acc.2 + rate.4)
+    });
+
+    Ok(vec![ReportTable {
+        heading: I18n::text("report.tax_by_rate").to_string(),
+        columns: vec![
+            I18n::text("report.rate"),
+            I18n::text("report.invoices"),
+            I18n::text("report.taxable_value"),
+            I18n::text("report.cgst"),
+            I18n::text("report.sgst"),
+            I18n::text("report.total_tax"),
+        ],
+        rows: rates
+            .iter()
+            .map(|(rate, invoices, taxable, cgst, sgst)| {
+                vec![
+                    format!("{}%", rate),
+                    invoices.to_string(),
+                    format_amount(*taxable),
+                    format_amount(*cgst),
+                    format_amount(*sgst),
+                    format_amount(cgst + sgst),
+                ]
+            })
+            .collect(),
+        totals: Some(vec![
+            I18n::text("report.total").to_string(),
+            String::new(),
+            format_amount(taxable),
+            format_amount(cgst),
+            format_amount(sgst),
+            format_amount(cgst + sgst),
+        ]),
+    }])
+}
+
+/// Renders a printable report for `period`. Supported kinds are `spend_summary` and `tax_summary`.
+#[tauri::command]
+pub fn generate_pdf_report(
+    app: AppHandle,
+    kind: String,
+    period: String,
+    dest: String,
+) -> Result<String, String> {
+    let period = ReportPeriod::parse(&period)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let title = render_pdf_report(&conn, &kind, &period, Path::new(&dest))?;
+
+    Telemetry::record_export(&conn, "pdf_report");
+    Notifier::notify(
+        &app,
+        JobEvent::ExportCompleted {
+            label: format!("{} report", title),
+            path: dest.clone(),
+        },
+    );
+
+    Ok(dest)
+}
+
+/// Writes a `spend_summary` or `tax_summary` PDF to `dest` and returns the report title.
+pub fn render_pdf_report(
+    conn: &Connection,
+    kind: &str,
+    period: &ReportPeriod,
+    dest: &Path,
+) -> Result<String, String> {
+    let (title, tables) = match kind.trim().to_ascii_lowercase().as_str() {
+        "spend_summary" => (
+            I18n::text("report.spend_summary"),
+            spend_summary_tables(conn, period)?,
+        ),
+        "tax_summary" => (
+            I18n::text("report.tax_summary"),
+            tax_summary_tables(conn, period)?,
+        ),
+        other => {
+            return Err(format!(
+                "Unsupported report \"{}\". Use spend_summary or tax_summary.",
+                other
+            ))
+        }
+    };
+
+    let report = PdfReport {
+        title: title.to_string(),
+        subtitle: I18n::format(
+            "report.period",
+            &[
+                ("start", period.start_iso()),
+                ("end", period.end_iso()),
+                ("generated", Local::now().format("%Y-%m-%d %H:%M").to_string()),
+            ],
+        ),
+        tables,
+    };
+    report.write(dest)?;
+
+    Ok(title.to_string())
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateFilters {
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    pub doc_type: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateBucket {
+    pub key: String,
+    pub label: String,
+    pub value: f64,
+    pub invoice_count: usize,
+}
+
+/// Groups invoice totals by `seller`, `month`, `tax_rate` or `doc_type` and reduces each
+/// group with `count`, `sum` or `avg`. Omitting `period` aggregates over all dated invoices.
+#[tauri::command]
+pub fn aggregate_invoices(
+    group_by: String,
+    metric: String,
+    period: Option<String>,
+    filters: Option<AggregateFilters>,
+) -> Result<Vec<AggregateBucket>, String> {
+    let group_by = group_by.trim().to_ascii_lowercase();
+    if !["seller", "month", "tax_rate", "doc_type"].contains(&group_by.as_str()) {
+        return Err(format!(
+            "Unsupported grouping \"{}\". Use seller, month, tax_rate or doc_type.",
+            group_by
+        ));
+    }
+    let metric = metric.trim().to_ascii_lowercase();
+    if !["count", "sum", "avg"].contains(&metric.as_str()) {
+        return Err(format!(
+            "Unsupported metric \"{}\". Use count, sum or avg.",
+            metric
+        ));
+    }
+
+    let period = period.as_deref().map(ReportPeriod::parse).transpose()?;
+    let filters = filters.unwrap_or_default();
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let facts = InvoiceFacts::load(&conn, period.as_ref())?;
+
+    // key -> (label, sum, invoice count)
+    let mut groups: BTreeMap<String, (String, f64, usize)> = BTreeMap::new();
+    for fact in facts {
+        if filters.seller_id.is_some() && fact.seller_id != filters.seller_id {
+            continue;
+        }
+        if filters.buyer_id.is_some() && fact.buyer_id != filters.buyer_id {
+            continue;
+        }
+        if let Some(doc_type) = &filters.doc_type {
+            if !fact
+                .doc_type
+                .as_deref()
+                .is_some_and(|value| value.eq_ignore_ascii_case(doc_type))
+            {
+                continue;
+            }
+        }
+
+        let contributions: Vec<(String, String, f64)> = match group_by.as_str() {
+            "seller" => {
+                let label = fact
+                    .seller_name
+                    .clone()
+                    .unwrap_or_else(|| "Unknown seller".to_string());
+                let key = fact
+                    .seller_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| label.to_lowercase());
+                vec![(key, label, fact.grand_total)]
+            }
+            "month" => {
+                let month = fact
+                    .invoice_date
+                    .get(..7)
+                    .unwrap_or(&fact.invoice_date)
+                    .to_string();
+                vec![(month.clone(), month, fact.grand_total)]
+            }
+            "tax_rate" => fact
+                .by_rate
+                .iter()
+                .map(|(rate, value)| (format!("{:08.2}", rate), format!("{}%", rate), *value))
+                .collect(),
+            _ => {
+                let label = fact.doc_type.clone().unwrap_or_else(|| "Unknown".to_string());
+                vec![(label.to_lowercase(), label, fact.grand_total)]
+            }
+        };
+
+        for (key, label, value) in contributions {
+            let group = groups.entry(key).or_insert_with(|| (label, 0.0, 0));
+            group.1 += value;
+            group.2 += 1;
+        }
+    }
+
+    let mut buckets: Vec<AggregateBucket> = groups
+        .into_iter()
+        .map(|(key, (label, sum, invoice_count))| AggregateBucket {
+            key,
+            label,
+            value: match metric.as_str() {
+                "count" => invoice_count as f64,
+                "avg" => (sum / invoice_count as f64 * 100.0).round() / 100.0,
+                _ => (sum * 100.0).round() / 100.0,
+            },
+            invoice_count,
+        })
+        .collect();
+
+    // Time and rate buckets keep their natural order; categories are ranked by value.
+    if group_by == "seller" || group_by == "doc_type" {
+        buckets.sort_by(|a, b| b.value.total_cmp(&a.value));
+    }
+
+    Ok(buckets)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VendorStatementResult {
+    pub path: String,
+    pub invoice_count: usize,
+    pub total_invoiced: f64,
+    pub total_paid: f64,
+    pub outstanding: f64,
+}
+
+struct VendorStatementRow {
+    invoice_date: String,
+    invoice_number: Option<String>,
+    file_name: String,
+    due_date: Option<String>,
+    amount: f64,
+    paid_on: Option<String>,
+    paid_amount: Option<f64>,
+    status: &'static str,
+}
+
+/// Lists a seller's invoices in `period` with their payment status, as `xlsx`, `csv` or `pdf`.
+#[tauri::command]
+pub fn generate_vendor_statement(
+    app: AppHandle,
+    seller_id: i64,
+    period: String,
+    format: String,
+    dest: String,
+    csv_profile: Option<String>,
+) -> Result<VendorStatementResult, String> {
+    let period = ReportPeriod::parse(&period)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+
+    let seller_name: String = conn
+        .query_row(
+            "SELECT name FROM sellers WHERE id = ?1",
+            params![seller_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Seller not found: {}", seller_id))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.invoice_date, f.file_name, f.due_date, f.parsed_details, p.paid_on, p.amount
+             FROM files f
+             LEFT JOIN payments p ON p.file_id = f.id
+             WHERE f.seller_id = ?1
+             AND f.invoice_date BETWEEN ?2 AND ?3
+             ORDER BY f.invoice_date ASC, f.created_at ASC",
+        )
+        .map_err(|error| error.to_string())?;
+    let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+    let rows = stmt
+        .query_map(params![seller_id, period.start_iso(), period.end_iso()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<f64>>(5)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let mut entries = Vec::new();
+    for (invoice_date, file_name, due_date, parsed_details, paid_on, paid_amount) in rows {
+        let invoice = parsed_details.as_deref().and_then(ParsedInvoice::from_json);
+        let amount = invoice
+            .as_ref()
+            .and_then(|invoice| invoice.number(GRAND_TOTAL))
+            .unwrap_or(0.0);
+        let status = match (&paid_on, &due_date) {
+            (Some(_), _) => "Paid",
+            (None, Some(due)) if *due < today => "Overdue",
+            _ => "Open",
+        };
+        entries.push(VendorStatementRow {
+            invoice_date,
+            invoice_number: invoice.as_ref().and_then(|invoice| invoice.text(INVOICE_NUMBER)),
+            file_name,
+            due_date,
+            amount,
+            // A payment recorded without an amount settles the invoice in full.
+            paid_amount: paid_on.as_ref().map(|_| paid_amount.unwrap_or(amount)),
+            paid_on,
+            status,
+        });
+    }
+
+    let total_invoiced: f64 = entries.iter().map(|entry| entry.amount).sum();
+    let total_paid: f64 = entries.iter().filter_map(|entry| entry.paid_amount).sum();
+    let outstanding = total_invoiced - total_paid;
+
+    let path = Path::new(&dest);
+    match format.trim().to_ascii_lowercase().as_str() {
+        "xlsx" | "csv" => {
+            let mut rows: Vec<Vec<Cell>> = entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        Cell::date(&entry.invoice_date),
+                        Cell::from(entry.invoice_number.clone()),
+                        Cell::Text(entry.file_name.clone()),
+                        entry.due_date.as_deref().map(Cell::date).unwrap_or(Cell::Empty),
+                        Cell::Number(entry.amount),
+                        entry.paid_on.as_deref().map(Cell::date).unwrap_or(Cell::Empty),
+                        Cell::from(entry.paid_amount),
+                        Cell::Text(entry.status.to_string()),
+                    ]
+                })
+                .collect();
+            rows.push(vec![
+                Cell::Text("Total".to_string()),
+                Cell::Empty,
+                Cell::Empty,
+                Cell::Empty,
+                Cell::Number(total_invoiced),
+                Cell::Empty,
+                Cell::Number(total_paid),
+                Cell::Text(format!("Outstanding {:.2}", outstanding)),
+            ]);
+            let sheet = Sheet {
+                name: "Statement".to_string(),
+                headers: [
+                    "Invoice date",
+                    "Invoice number",
+                    "File",
+                    "Due date",
+                    "Amount",
+                    "Paid on",
+                    "Paid amount",
+                    "Status",
+                ].map(String::from).to_vec(),
+                rows,
+            };
+            if format.trim().eq_ignore_ascii_case("csv") {
+                CsvProfiles::get(&conn, csv_profile.as_deref())?.write(path, &sheet)?;
+            } else {
+                write_workbook(path, &[sheet])?;
+            }
+        }
+        "pdf" => {
+            let report = PdfReport {
+                title: format!("Statement of account - {}", seller_name),
+                subtitle: format!(
+                    "Period {} to {} - generated {}",
+                    period.start_iso(),
+                    period.end_iso(),
+                    Local::now().format("%Y-%m-%d %H:%M")
+                ),
+                tables: vec![
+                    ReportTable {
+                        heading: "Invoices".to_string(),
+                        columns: vec!["Invoice", "Date", "Due", "Amount", "Paid", "Status"],
+                        rows: entries
+                            .iter()
+                            .map(|entry| {
+                                vec![
+                                    entry
+                                        .invoice_number
+                                        .clone()
+                                        .unwrap_or_else(|| entry.file_name.clone()),
+                                    entry.invoice_date.clone(),
+                                    entry.due_date.clone().unwrap_or_default(),
+                                    format_amount(entry.amount),
+                                    entry.paid_amount.map(format_amount).unwrap_or_default(),
+                                    entry.status.to_string(),
+                                ]
+                            })
+                            .collect(),
+                        totals: Some(vec![
+                            "Total".to_string(),
+                            String::new(),
+                            String::new(),
+                            format_amount(total_invoiced),
+                            format_amount(total_paid),
+                            String::new(),
+                        ]),
+                    },
+                    ReportTable {
+                        heading: "Summary".to_string(),
+                        columns: vec!["", "Amount"],
+                        rows: vec![
+                            vec!["Invoiced".to_string(), format_amount(total_invoiced)],
+                            vec!["Paid".to_string(), format_amount(total_paid)],
+                        ],
+                        totals: Some(vec!["Outstanding".to_string(), format_amount(outstanding)]),
+                    },
+                ],
+            };
+            report.write(path)?;
+        }
+        other => {
+            return Err(format!(
+                "Unsupported statement format \"{}\". Use xlsx, csv or pdf.",
+                other
+            ))
+        }
+    }
+
+    Telemetry::record_export(&conn, "vendor_statement");
+    Notifier::notify(
+        &app,
+        JobEvent::ExportCompleted {
+            label: "Vendor statement".to_string(),
+            path: dest.clone(),
+        },
+    );
+
+    Ok(VendorStatementResult {
+        path: dest,
+        invoice_count: entries.len(),
+        total_invoiced,
+        total_paid,
+        outstanding,
+    })
+}
@@ -0,0 +1,63 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    reprocess_conflicts::{ExtractionOutcome, FieldChoice, ReprocessConflict, ReprocessConflicts},
+    workspace_lock::WorkspaceLock,
+};
+use std::collections::HashMap;
+
+/// Saves the result of (re)processing a file. Existing details are never overwritten:
+/// a differing result is held as a candidate and reported as a `conflict`.
+#[tauri::command]
+pub fn save_extraction_result(file_id: String, parsed_details: String) -> Result<ExtractionOutcome, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    ReprocessConflicts::submit(&conn, &file_id, &parsed_details)
+}
+
+#[tauri::command]
+pub fn list_reprocess_conflicts() -> Result<Vec<ReprocessConflict>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ReprocessConflicts::list(&conn)
+}
+
+#[tauri::command]
+pub fn get_reprocess_conflict(file_id: String) -> Result<Option<ReprocessConflict>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ReprocessConflicts::get(&conn, &file_id)
+}
+
+/// Applies per-field `current`/`candidate` choices; unlisted fields keep their current value.
+#[tauri::command]
+pub fn resolve_reprocess_conflict(
+    file_id: String,
+    choices: HashMap<String, FieldChoice>,
+) -> Result<String, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let details = ReprocessConflicts::resolve(&conn, &file_id, &choices)?;
+
+    let mut accepted: Vec<&String> = choices
+        .iter()
+        .filter(|(_, choice)| **choice == FieldChoice::Candidate)
+        .map(|(field, _)| field)
+        .collect();
+    accepted.sort();
+    AuditLog::record(
+        &conn,
+        "file.reprocess_resolved",
+        Some(&file_id),
+        Some(serde_json::json!({ "acceptedFields": accepted })),
+    )?;
+
+    Ok(details)
+}
+
+/// Drops the candidate and keeps the current details as they are.
+#[tauri::command]
+pub fn discard_reprocess_conflict(file_id: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    ReprocessConflicts::discard(&conn, &file_id)?;
+    AuditLog::record(&conn, "file.reprocess_discarded", Some(&file_id), None)
+}
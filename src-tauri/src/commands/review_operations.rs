@@ -0,0 +1,136 @@
+use crate::commands::file_operations::FileStatus;
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog, auto_approval::AutoApproval, roles::Roles, workspace_lock::WorkspaceLock,
+};
+use rusqlite::params;
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewFlagRow {
+    pub id: i64,
+    pub source: String,
+    pub field: Option<String>,
+    pub message: String,
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewQueueEntry {
+    pub file_id: String,
+    pub file_name: String,
+    pub status: FileStatus,
+    pub flags: Vec<ReviewFlagRow>,
+}
+
+#[tauri::command]
+pub fn list_review_queue() -> Result<Vec<ReviewQueueEntry>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.id, f.file_name, f.status, r.id, r.source, r.field, r.message, r.details, r.created_at
+             FROM review_flags r
+             JOIN files f ON f.id = r.file_id
+             WHERE r.resolved_at IS NULL
+             ORDER BY f.created_at DESC, f.id, r.created_at ASC, r.id ASC",
+        )
+        .map_err(|error| error.to_string())?;
+
+    let rows_iter = stmt
+        .query_map([], |row| {
+            let status_str: String = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                status_str.parse::<FileStatus>().unwrap_or(FileStatus::Unprocessed),
+                ReviewFlagRow {
+                    id: row.get(3)?,
+                    source: row.get(4)?,
+                    field: row.get(5)?,
+                    message: row.get(6)?,
+                    details: row.get(7)?,
+                    created_at: row.get(8)?,
+                },
+            ))
+        })
+        .map_err(|error| error.to_string())?;
+
+    let mut entries: Vec<ReviewQueueEntry> = Vec::new();
+    for row in rows_iter {
+        let (file_id, file_name, status, flag) = row.map_err(|error| error.to_string())?;
+        match entries.last_mut() {
+            Some(entry) if entry.file_id == file_id => entry.flags.push(flag),
+            _ => entries.push(ReviewQueueEntry {
+                file_id,
+                file_name,
+                status,
+                flags: vec![flag],
+            }),
+        }
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn resolve_review_flag(flag_id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+
+    let updated = conn
+        .execute(
+            "UPDATE review_flags SET resolved_at = CURRENT_TIMESTAMP WHERE id = ?1 AND resolved_at IS NULL",
+            params![flag_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+    if updated == 0 {
+        return Err("Review flag not found or already resolved.".to_string());
+    }
+
+    Ok(())
+}
+
+/// Moves files held for review to Processed. Returns how many were waiting.
+#[tauri::command]
+pub fn approve_files(file_ids: Vec<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let approved = AutoApproval::approve(&conn, &file_ids)?;
+    if !approved.is_empty() {
+        AuditLog::record(
+            &conn,
+            "file.approved",
+            None,
+            Some(serde_json::json!({ "fileIds": approved })),
+        )?;
+    }
+    Ok(approved.len())
+}
+
+#[tauri::command]
+pub fn get_auto_approve_threshold() -> Result<Option<f64>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AutoApproval::threshold(&conn)
+}
+
+/// Extractions with a confidence above `threshold` (0 to 1) are Processed straight away,
+/// the rest wait as NeedsReview. `None` turns auto-approval off. Applies to extractions
+/// from now on.
+#[tauri::command]
+pub fn set_auto_approve_threshold(threshold: Option<f64>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    AutoApproval::set_threshold(&conn, threshold)?;
+    AuditLog::record(
+        &conn,
+        "settings.auto_approve_threshold",
+        None,
+        Some(serde_json::json!({ "threshold": threshold })),
+    )
+}
@@ -0,0 +1,42 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    roles::Roles,
+    scheduler::{Schedule, ScheduleRun, ScheduleUpdate, Scheduler},
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn list_schedules() -> Result<Vec<Schedule>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Scheduler::list(&conn)
+}
+
+/// Changes a schedule's name, cron expression, enabled flag or job options.
+#[tauri::command]
+pub fn update_schedule(id: i64, update: ScheduleUpdate) -> Result<Schedule, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_schedules")?;
+
+    let schedule = Scheduler::update(&conn, id, update)?;
+    AuditLog::record(
+        &conn,
+        "schedule.updated",
+        Some(&schedule.id.to_string()),
+        Some(serde_json::json!({
+            "name": schedule.name,
+            "cron": schedule.cron,
+            "enabled": schedule.enabled,
+        })),
+    )?;
+
+    Ok(schedule)
+}
+
+/// Most recent runs first, optionally for a single schedule.
+#[tauri::command]
+pub fn list_schedule_runs(schedule_id: Option<i64>, limit: Option<i64>) -> Result<Vec<ScheduleRun>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Scheduler::runs(&conn, schedule_id, limit)
+}
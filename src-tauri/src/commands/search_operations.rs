@@ -0,0 +1,225 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    full_text_search::{FileSearchHit, FullTextSearch},
+    llm_provider::LlmProviderKind,
+    parsed_schema::SCHEMA_VERSION,
+    roles::Roles,
+    semantic_search::{SemanticHit, SemanticIndexStatus, SemanticSearch},
+    telemetry::Telemetry,
+    workspace_lock::WorkspaceLock,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+const DEFAULT_LIMIT_PER_KIND: i64 = 20;
+const DEFAULT_SEMANTIC_LIMIT: usize = 10;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum SearchHitKind {
+    File,
+    ParsedField,
+    XmlExport,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    /// File id for file and parsed field hits, XML export id otherwise.
+    pub id: String,
+    pub title: String,
+    /// Parsed field that matched, for parsed field hits.
+    pub field: Option<String>,
+    pub snippet: Option<String>,
+}
+
+/// Walks the parsed JSON and returns the first scalar whose text contains `needle`,
+/// as (field path, value).
+fn find_matching_field(value: &Value, path: &str, needle: &str) -> Option<(String, String)> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .filter(|(key, _)| key.as_str() != SCHEMA_VERSION)
+            .find_map(|(key, child)| {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                find_matching_field(child, &child_path, needle)
+            }),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(index, child)| find_matching_field(child, &format!("{}[{}]", path, index), needle)),
+        Value::String(text) if text.to_lowercase().contains(needle) => Some((path.to_string(), text.clone())),
+        Value::Number(number) if number.to_string().contains(needle) => {
+            Some((path.to_string(), number.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Searches file names, extracted invoice fields and XML export names in one call.
+/// `language` (a Tesseract code such as `hin`) limits file hits to documents in that
+/// language and leaves out XML exports.
+#[tauri::command]
+pub fn global_search(
+    query: String,
+    limit_per_kind: Option<i64>,
+    language: Option<String>,
+) -> Result<Vec<SearchHit>, String> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Telemetry::record_feature(&conn, "global_search");
+    let limit = limit_per_kind.unwrap_or(DEFAULT_LIMIT_PER_KIND).max(1);
+    let pattern = format!("%{}%", needle);
+    let mut hits = Vec::new();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_name FROM files
+             WHERE file_name LIKE ?1
+             AND (?3 IS NULL OR document_language = ?3)
+             ORDER BY created_at DESC
+             LIMIT ?2",
+        )
+        .map_err(|error| error.to_string())?;
+    let file_hits = stmt
+        .query_map(rusqlite::params![pattern, limit, language], |row| {
+            Ok(SearchHit {
+                kind: SearchHitKind::File,
+                id: row.get(0)?,
+                title: row.get(1)?,
+                field: None,
+                snippet: None,
+            })
+        })
+        .map_err(|error| error.to_string())?;
+    for hit in file_hits {
+        hits.push(hit.map_err(|error| error.to_string())?);
+    }
+
+    // LIKE narrows the candidates; the JSON walk then finds which field matched.
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_name, parsed_details FROM files
+             WHERE parsed_details LIKE ?1
+             AND (?3 IS NULL OR document_language = ?3)
+             ORDER BY created_at DESC
+             LIMIT ?2",
+        )
+        .map_err(|error| error.to_string())?;
+    let candidates = stmt
+        .query_map(rusqlite::params![pattern, limit * 3, language], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?;
+    let mut parsed_hits = 0;
+    for candidate in candidates {
+        if parsed_hits >= limit {
+            break;
+        }
+        let (id, file_name, parsed_details) = candidate.map_err(|error| error.to_string())?;
+        let Ok(parsed) = serde_json::from_str::<Value>(&parsed_details) else {
+            continue;
+        };
+        if let Some((field, snippet)) = find_matching_field(&parsed, "", &needle) {
+            hits.push(SearchHit {
+                kind: SearchHitKind::ParsedField,
+                id,
+                title: file_name,
+                field: Some(field),
+                snippet: Some(snippet),
+            });
+            parsed_hits += 1;
+        }
+    }
+
+    if language.is_some() {
+        return Ok(hits);
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, xml_name, created_at FROM xml_files
+             WHERE xml_name LIKE ?1
+             ORDER BY created_at DESC
+             LIMIT ?2",
+        )
+        .map_err(|error| error.to_string())?;
+    let xml_hits = stmt
+        .query_map(rusqlite::params![pattern, limit], |row| {
+            Ok(SearchHit {
+                kind: SearchHitKind::XmlExport,
+                id: row.get::<_, i64>(0)?.to_string(),
+                title: row.get(1)?,
+                field: None,
+                snippet: row.get(2)?,
+            })
+        })
+        .map_err(|error| error.to_string())?;
+    for hit in xml_hits {
+        hits.push(hit.map_err(|error| error.to_string())?);
+    }
+
+    Ok(hits)
+}
+
+/// Files whose name or parsed fields contain every word of `query`, best match first, each
+/// with a snippet of where it matched.
+#[tauri::command]
+pub fn search_files(query: String, limit: Option<i64>) -> Result<Vec<FileSearchHit>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Telemetry::record_feature(&conn, "search_files");
+    FullTextSearch::search(&conn, &query, limit.unwrap_or(DEFAULT_LIMIT_PER_KIND))
+}
+
+/// Invoices ranked by how close their parsed fields are in meaning to `query`. Files parsed
+/// since the last search are embedded first.
+#[tauri::command(async)]
+pub fn semantic_search(query: String, limit: Option<usize>) -> Result<Vec<SemanticHit>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Telemetry::record_feature(&conn, "semantic_search");
+    SemanticSearch::search(&conn, &query, limit.unwrap_or(DEFAULT_SEMANTIC_LIMIT))
+}
+
+/// Embeds every parsed file that is not indexed yet, so the next search starts right away.
+/// Returns how many were embedded.
+#[tauri::command(async)]
+pub fn build_semantic_index() -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    SemanticSearch::refresh(&conn)
+}
+
+#[tauri::command]
+pub fn get_semantic_index_status() -> Result<SemanticIndexStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    SemanticSearch::status(&conn)
+}
+
+/// Which provider embeds invoices for semantic search; `None` follows the extraction
+/// provider.
+#[tauri::command]
+pub fn set_semantic_search_provider(provider: Option<LlmProviderKind>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    SemanticSearch::set_provider(&conn, provider)?;
+    AuditLog::record(
+        &conn,
+        "search.semantic_provider_changed",
+        provider.map(|kind| kind.as_str()),
+        None,
+    )
+}
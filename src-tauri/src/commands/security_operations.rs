@@ -0,0 +1,154 @@
+use crate::db::get_connection;
+use crate::services::{
+    app_lock::{AppLock, AppLockStatus},
+    audit_log::AuditLog,
+    database_encryption::{DatabaseEncryption, DatabaseEncryptionStatus},
+    roles::{Role, Roles},
+    secure_delete::SecureDelete,
+    workspace_lock::{WorkspaceLock, WorkspaceLockStatus},
+};
+
+#[tauri::command]
+pub fn get_app_lock_status() -> Result<AppLockStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AppLock::status(&conn)
+}
+
+#[tauri::command]
+pub fn set_app_lock_pin(current_pin: Option<String>, new_pin: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::require_admin(&conn, "action.change_pin")?;
+    AppLock::set_pin(&conn, current_pin.as_deref(), &new_pin)?;
+    AuditLog::record(&conn, "app_lock.pin_set", None, None)
+}
+
+#[tauri::command]
+pub fn clear_app_lock_pin(pin: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::require_admin(&conn, "action.remove_pin")?;
+    AppLock::clear_pin(&conn, &pin)?;
+    AuditLog::record(&conn, "app_lock.pin_cleared", None, None)
+}
+
+/// Lets the UI check a PIN up front before starting a destructive flow.
+#[tauri::command]
+pub fn verify_app_lock_pin(pin: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AppLock::require(&conn, Some(&pin))
+}
+
+#[tauri::command]
+pub fn get_secure_delete() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    SecureDelete::enabled(&conn)
+}
+
+#[tauri::command]
+pub fn set_secure_delete(enabled: bool, pin: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    SecureDelete::set_enabled(&conn, enabled)?;
+    AuditLog::record(
+        &conn,
+        "settings.secure_delete",
+        None,
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+}
+
+#[tauri::command]
+pub fn get_active_role() -> Result<Role, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::current(&conn)
+}
+
+#[tauri::command]
+pub fn set_active_role(role: Role, pin: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::switch(&conn, role, pin.as_deref())?;
+    AuditLog::record(
+        &conn,
+        "security.role_changed",
+        None,
+        Some(serde_json::json!({ "role": role })),
+    )
+}
+
+#[tauri::command]
+pub fn get_workspace_lock_status() -> Result<WorkspaceLockStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::status(&conn)
+}
+
+/// Makes the workspace read-only: mutating commands fail until it is unlocked, while
+/// reads and exports keep working.
+#[tauri::command]
+pub fn lock_workspace(reason: Option<String>, pin: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::require_admin(&conn, "action.lock_workspace")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    WorkspaceLock::lock(&conn, reason.as_deref())?;
+    AuditLog::record(
+        &conn,
+        "workspace.locked",
+        None,
+        Some(serde_json::json!({ "reason": reason })),
+    )
+}
+
+#[tauri::command]
+pub fn unlock_workspace(pin: Option<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Roles::require_admin(&conn, "action.lock_workspace")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    WorkspaceLock::unlock(&conn)?;
+    AuditLog::record(&conn, "workspace.unlocked", None, None)
+}
+
+#[tauri::command]
+pub fn get_database_encryption() -> Result<DatabaseEncryptionStatus, String> {
+    DatabaseEncryption::status()
+}
+
+/// Encrypts the database with `passphrase`, which is kept in the OS keyring, or changes the
+/// passphrase when it is already encrypted.
+#[tauri::command(async)]
+pub fn encrypt_database(passphrase: String, pin: Option<String>) -> Result<DatabaseEncryptionStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    let rekeyed = DatabaseEncryption::status()?.encrypted;
+    drop(conn);
+
+    DatabaseEncryption::encrypt(&passphrase)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AuditLog::record(
+        &conn,
+        if rekeyed {
+            "database.rekeyed"
+        } else {
+            "database.encrypted"
+        },
+        None,
+        None,
+    )?;
+    DatabaseEncryption::status()
+}
+
+/// Stores the database unencrypted again and forgets the passphrase.
+#[tauri::command(async)]
+pub fn decrypt_database(pin: Option<String>) -> Result<DatabaseEncryptionStatus, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    drop(conn);
+
+    DatabaseEncryption::decrypt()?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    AuditLog::record(&conn, "database.decrypted", None, None)?;
+    DatabaseEncryption::status()
+}
@@ -1,4 +1,8 @@
-use crate::db::storage_dir;
+use crate::db::{get_connection, storage_dir};
+use crate::services::{
+    app_lock::AppLock, audit_log::AuditLog, roles::Roles, secure_delete::SecureDelete,
+    workspace_lock::WorkspaceLock,
+};
 use serde::Serialize;
 use std::fs;
 
@@ -39,7 +43,13 @@ pub fn get_storage_stats() -> Result<StorageStats, String> {
 }
 
 #[tauri::command]
-pub fn clear_processed_files() -> Result<StorageStats, String> {
+pub fn clear_processed_files(pin: Option<String>) -> Result<StorageStats, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.clear_stored_files")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    let shred = SecureDelete::enabled(&conn)?;
+
     let dir = storage_dir().map_err(|error| error.to_string())?;
 
     if dir.exists() {
@@ -48,12 +58,19 @@ pub fn clear_processed_files() -> Result<StorageStats, String> {
             let path = entry.path();
 
             if path.is_file() {
-                fs::remove_file(&path).map_err(|error| error.to_string())?;
+                SecureDelete::remove_file(&path, shred)?;
             } else if path.is_dir() {
-                fs::remove_dir_all(&path).map_err(|error| error.to_string())?;
+                SecureDelete::remove_dir_all(&path, shred)?;
             }
         }
     }
 
+    AuditLog::record(
+        &conn,
+        "storage.cleared",
+        None,
+        Some(serde_json::json!({ "shredded": shred })),
+    )?;
+
     compute_storage_stats()
 }
@@ -0,0 +1,48 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    tags::{Tag, Tags},
+    workspace_lock::WorkspaceLock,
+};
+
+/// Tags the files with every one of `tags`, creating new tags as needed. Returns how many
+/// tags were newly put on a file.
+#[tauri::command]
+pub fn add_tags(file_ids: Vec<String>, tags: Vec<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let added = Tags::add(&conn, &file_ids, &tags)?;
+    if added > 0 {
+        AuditLog::record(
+            &conn,
+            "file.tags_added",
+            None,
+            Some(serde_json::json!({ "fileIds": file_ids, "tags": tags })),
+        )?;
+    }
+    Ok(added)
+}
+
+/// Takes `tags` off the files. Returns how many tags were taken off a file.
+#[tauri::command]
+pub fn remove_tags(file_ids: Vec<String>, tags: Vec<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let removed = Tags::remove(&conn, &file_ids, &tags)?;
+    if removed > 0 {
+        AuditLog::record(
+            &conn,
+            "file.tags_removed",
+            None,
+            Some(serde_json::json!({ "fileIds": file_ids, "tags": tags })),
+        )?;
+    }
+    Ok(removed)
+}
+
+/// Tags in use, by name, with how many files carry each.
+#[tauri::command]
+pub fn list_tags() -> Result<Vec<Tag>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Tags::list(&conn)
+}
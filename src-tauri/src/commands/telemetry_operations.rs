@@ -0,0 +1,26 @@
+use crate::db::get_connection;
+use crate::services::roles::Roles;
+use crate::services::telemetry::{Telemetry, TelemetryPayload};
+use crate::services::workspace_lock::WorkspaceLock;
+
+#[tauri::command]
+pub fn get_telemetry_enabled() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Telemetry::enabled(&conn)
+}
+
+/// Usage telemetry is off until the user opts in; opting out discards unsent counters.
+#[tauri::command]
+pub fn set_telemetry_enabled(enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    Telemetry::set_enabled(&conn, enabled)
+}
+
+/// Shows exactly what the next telemetry upload would send.
+#[tauri::command]
+pub fn preview_telemetry() -> Result<TelemetryPayload, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Telemetry::preview(&conn)
+}
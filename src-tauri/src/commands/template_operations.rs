@@ -0,0 +1,101 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    extraction_templates::{
+        ExtractionTemplate, ExtractionTemplates, TemplateApplyResult, TemplateInput, TemplateRun,
+    },
+    roles::Roles,
+    telemetry::Telemetry,
+    template_selection::TemplateMatch,
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn list_extraction_templates() -> Result<Vec<ExtractionTemplate>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExtractionTemplates::list(&conn)
+}
+
+#[tauri::command]
+pub fn create_extraction_template(template: TemplateInput) -> Result<ExtractionTemplate, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let id = ExtractionTemplates::create(&conn, &template)?;
+    AuditLog::record(
+        &conn,
+        "extraction_template.created",
+        None,
+        Some(serde_json::json!({ "id": id, "name": template.name.trim() })),
+    )?;
+    ExtractionTemplates::get(&conn, id)
+}
+
+#[tauri::command]
+pub fn update_extraction_template(id: i64, template: TemplateInput) -> Result<ExtractionTemplate, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    ExtractionTemplates::update(&conn, id, &template)?;
+    AuditLog::record(
+        &conn,
+        "extraction_template.updated",
+        None,
+        Some(serde_json::json!({ "id": id, "name": template.name.trim() })),
+    )?;
+    ExtractionTemplates::get(&conn, id)
+}
+
+#[tauri::command]
+pub fn delete_extraction_template(id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.delete_templates")?;
+    ExtractionTemplates::delete(&conn, id)?;
+    AuditLog::record(
+        &conn,
+        "extraction_template.deleted",
+        None,
+        Some(serde_json::json!({ "id": id })),
+    )
+}
+
+/// Which template would extract the file when it is parsed, the vendor it was recognised
+/// as and why.
+#[tauri::command]
+pub fn get_template_match(file_id: String) -> Result<TemplateMatch, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExtractionTemplates::explain(&conn, &file_id)
+}
+
+/// Runs a template, saved or still being edited, against a file and returns what it found.
+/// Nothing is saved.
+#[tauri::command]
+pub fn test_extraction_template(template: TemplateInput, file_id: String) -> Result<TemplateRun, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ExtractionTemplates::test(&conn, &template, &file_id)
+}
+
+/// Extracts files with `template_id`, or with the template auto-selected for each file. Files no
+/// template matches are left alone and not part of the result.
+#[tauri::command(async)]
+pub fn apply_extraction_template(
+    file_ids: Vec<String>,
+    template_id: Option<i64>,
+) -> Result<Vec<TemplateApplyResult>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Telemetry::record_feature(&conn, "extraction_templates");
+
+    let mut results = Vec::new();
+    for file_id in &file_ids {
+        if let Some(result) = ExtractionTemplates::apply(&conn, file_id, template_id, false)? {
+            AuditLog::record(
+                &conn,
+                "extraction_template.applied",
+                Some(file_id),
+                Some(serde_json::json!({ "template": result.template_id, "status": result.status })),
+            )?;
+            results.push(result);
+        }
+    }
+    Ok(results)
+}
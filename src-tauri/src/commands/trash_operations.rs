@@ -0,0 +1,66 @@
+use crate::db::get_connection;
+use crate::services::{
+    app_lock::AppLock,
+    audit_log::AuditLog,
+    roles::Roles,
+    trash::{DeletedFile, Trash},
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn list_recently_deleted(limit: Option<i64>) -> Result<Vec<DeletedFile>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Trash::list(&conn, limit.unwrap_or(100))
+}
+
+/// Deletes trashed files for good. Returns how many were purged.
+#[tauri::command]
+pub fn purge_selected(ids: Vec<String>, pin: Option<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.purge_files")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    Trash::purge(&conn, &ids, "manual")
+}
+
+/// Puts trashed files back in the library. Returns how many were restored.
+#[tauri::command]
+pub fn restore_files(file_ids: Vec<String>, pin: Option<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.restore_files")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    Trash::restore(&conn, &file_ids)
+}
+
+/// Deletes files trashed more than `older_than_days` ago for good; 0 empties the trash.
+/// Returns how many were purged.
+#[tauri::command]
+pub fn purge_trash(older_than_days: i64, pin: Option<String>) -> Result<usize, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.purge_files")?;
+    AppLock::require(&conn, pin.as_deref())?;
+    Trash::purge_older_than(&conn, older_than_days)
+}
+
+#[tauri::command]
+pub fn get_trash_retention_days() -> Result<i64, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Trash::retention_days(&conn)
+}
+
+/// 0 keeps deleted files until they are purged by hand.
+#[tauri::command]
+pub fn set_trash_retention_days(days: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    Trash::set_retention_days(&conn, days)?;
+    AuditLog::record(
+        &conn,
+        "settings.trash_retention",
+        None,
+        Some(serde_json::json!({ "days": days })),
+    )
+}
@@ -0,0 +1,66 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog, autostart::Autostart, processing_pause::ProcessingPause, roles::Roles, tray::Tray,
+    workspace_lock::WorkspaceLock,
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn get_background_mode() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    Tray::background_mode(&conn)
+}
+
+/// When enabled, closing the window hides it to the tray instead of quitting.
+#[tauri::command]
+pub fn set_background_mode(enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    Tray::set_background_mode(&conn, enabled)?;
+    AuditLog::record(
+        &conn,
+        "settings.background_mode",
+        None,
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+}
+
+#[tauri::command]
+pub fn get_processing_paused() -> Result<bool, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingPause::is_paused(&conn)
+}
+
+#[tauri::command]
+pub fn set_processing_paused(app: AppHandle, paused: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingPause::set_paused(&app, &conn, paused)?;
+    Tray::sync_pause_label(&app, paused);
+    AuditLog::record(
+        &conn,
+        "processing.paused_changed",
+        None,
+        Some(serde_json::json!({ "paused": paused, "source": "app" })),
+    )
+}
+
+#[tauri::command]
+pub fn get_autostart_enabled(app: AppHandle) -> Result<bool, String> {
+    Autostart::is_enabled(&app)
+}
+
+/// Registers or removes the OS login entry that starts Invox minimized to the tray.
+#[tauri::command]
+pub fn set_autostart_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    Autostart::set_enabled(&app, &conn, enabled)?;
+    AuditLog::record(
+        &conn,
+        "settings.autostart",
+        None,
+        Some(serde_json::json!({ "enabled": enabled })),
+    )
+}
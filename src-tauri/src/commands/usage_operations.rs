@@ -0,0 +1,137 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    processing_usage::{ModelPrice, ParseCostSummary, ProcessingUsage, UsageRecord, UsageSummary},
+    report_period::ReportPeriod,
+    roles::Roles,
+    workspace_lock::WorkspaceLock,
+};
+use chrono::Utc;
+use chrono_tz::America::Los_Angeles;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Called by the processing flow after each extraction request with the token counts
+/// the provider reported. Returns the estimated cost in USD.
+#[tauri::command]
+pub fn record_processing_usage(record: UsageRecord) -> Result<f64, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingUsage::record(&conn, &record)
+}
+
+/// Per-day token and cost series for `period`, with per-profile and per-model breakdowns.
+#[tauri::command]
+pub fn get_usage_summary(period: String) -> Result<UsageSummary, String> {
+    let period = ReportPeriod::parse(&period)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingUsage::summary(&conn, &period)
+}
+
+/// Month-by-month extraction cost for `period`, split by model.
+#[tauri::command]
+pub fn get_parse_cost_summary(period: String) -> Result<ParseCostSummary, String> {
+    let period = ReportPeriod::parse(&period)?;
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingUsage::cost_summary(&conn, &period)
+}
+
+/// Prices from settings first, then the bundled list prices they override.
+#[tauri::command]
+pub fn get_model_pricing() -> Result<Vec<ModelPrice>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ProcessingUsage::pricing(&conn)
+}
+
+/// Replaces the prices entered in settings. Costs already recorded keep the price they
+/// were recorded at.
+#[tauri::command]
+pub fn set_model_pricing(prices: Vec<ModelPrice>) -> Result<Vec<ModelPrice>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    ProcessingUsage::set_pricing(&conn, &prices)?;
+    let models: Vec<&str> = prices.iter().map(|price| price.model.trim()).collect();
+    AuditLog::record(
+        &conn,
+        "usage.pricing_changed",
+        None,
+        Some(serde_json::json!({ "models": models })),
+    )?;
+    ProcessingUsage::pricing(&conn)
+}
+
+/// Request counters the Gemini rate limiter keeps per model. Days follow Pacific time, when
+/// Gemini's daily quotas reset.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiModelUsage {
+    pub model: String,
+    pub day: String,
+    /// Start of the current one-minute window, in milliseconds since the epoch.
+    pub minute_window_start: i64,
+    pub requests_minute: i64,
+    pub requests_day: i64,
+}
+
+#[tauri::command]
+pub fn get_gemini_model_usage(model: String) -> Result<Option<GeminiModelUsage>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    conn.query_row(
+        "SELECT model, day, minute_window_start, requests_minute, requests_day
+         FROM gemini_model_usage WHERE model = ?1",
+        params![model],
+        |row| {
+            Ok(GeminiModelUsage {
+                model: row.get(0)?,
+                day: row.get(1)?,
+                minute_window_start: row.get(2)?,
+                requests_minute: row.get(3)?,
+                requests_day: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub fn save_gemini_model_usage(usage: GeminiModelUsage) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    conn.execute(
+        "INSERT INTO gemini_model_usage (model, day, minute_window_start, requests_minute, requests_day)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(model) DO UPDATE SET
+           day = excluded.day,
+           minute_window_start = excluded.minute_window_start,
+           requests_minute = excluded.requests_minute,
+           requests_day = excluded.requests_day,
+           updated_at = CURRENT_TIMESTAMP",
+        params![
+            usage.model,
+            usage.day,
+            usage.minute_window_start,
+            usage.requests_minute,
+            usage.requests_day
+        ],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
+/// Adds empty counters for models that have none yet.
+#[tauri::command]
+pub fn sync_gemini_model_usage(models: Vec<String>) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    let now = Utc::now();
+    let today = now.with_timezone(&Los_Angeles).format("%Y-%m-%d").to_string();
+    for model in models {
+        conn.execute(
+            "INSERT INTO gemini_model_usage (model, day, minute_window_start, requests_minute, requests_day)
+             VALUES (?1, ?2, ?3, 0, 0)
+             ON CONFLICT(model) DO NOTHING",
+            params![model, today, now.timestamp_millis()],
+        )
+        .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use crate::db::get_connection;
+use crate::services::{
+    arithmetic_validator::{ArithmeticIssue, ArithmeticValidator, DEFAULT_TOLERANCE},
+    date_normalizer::{DateNormalizer, NormalizedDate},
+    document_classifier::{DocType, DocumentClassifier},
+    double_payment::DoublePaymentDetector,
+    file_versions::FileVersions,
+    invoices::InvoiceStore,
+    language_detection::LanguageDetector,
+    line_items::LineItemStore,
+    parsed_invoice::{ParsedInvoice, INVOICE_DATE},
+    party_registry::{BUYERS, SELLERS},
+    payments::PaymentTracker,
+    qr_scanner::{QrMismatch, QrScanner},
+    review_queue::{NewReviewFlag, ReviewQueue},
+    tax_lines::TaxLineStore,
+    tax_id_validator::{TaxIdCheck, TaxIdValidator},
+    workspace_lock::WorkspaceLock,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::json;
+
+pub const TAX_ID_REVIEW_SOURCE: &str = "tax_id";
+pub const INVOICE_DATE_REVIEW_SOURCE: &str = "invoice_date";
+pub const ARITHMETIC_REVIEW_SOURCE: &str = "arithmetic";
+pub const QR_REVIEW_SOURCE: &str = "qr_code";
+
+fn load_parsed_details(conn: &Connection, file_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT parsed_details FROM files WHERE id = ?1",
+        params![file_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .map_err(|error| error.to_string())?
+    .ok_or_else(|| format!("File not found: {}", file_id))
+}
+
+/// Runs every post-extraction step (validation, normalization, master data
+/// linking) against freshly saved `parsed_details`.
+pub fn apply_parsed_details_checks(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+) -> Result<(), String> {
+    apply_tax_id_validation(conn, file_id, parsed_details)?;
+    apply_date_normalization(conn, file_id, parsed_details, None)?;
+    apply_arithmetic_validation(conn, file_id, parsed_details, DEFAULT_TOLERANCE)?;
+    apply_qr_cross_check(conn, file_id, parsed_details)?;
+    apply_language_detection(conn, file_id, parsed_details)?;
+    apply_document_classification(conn, file_id, parsed_details)?;
+    DoublePaymentDetector::update_key(conn, file_id, parsed_details)?;
+    FileVersions::update(conn, file_id, parsed_details)?;
+    SELLERS.assign_file(conn, file_id, parsed_details)?;
+    BUYERS.assign_file(conn, file_id, parsed_details)?;
+    LineItemStore::replace_for_file(conn, file_id, parsed_details)?;
+    TaxLineStore::replace_for_file(conn, file_id, parsed_details)?;
+    PaymentTracker::update_terms(conn, file_id, parsed_details)?;
+    InvoiceStore::replace_for_file(conn, file_id, parsed_details)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileValidation {
+    pub file_id: String,
+    pub file_name: String,
+    /// `None` when the file has no parsed details to check.
+    pub valid: Option<bool>,
+    pub errors: Vec<ArithmeticIssue>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTaxIdValidation {
+    pub file_id: String,
+    pub file_name: String,
+    /// `None` when the file has no tax identifiers to check.
+    pub valid: Option<bool>,
+    pub checks: Vec<TaxIdCheck>,
+}
+
+/// Validates the tax identifiers in `parsed_details`, stores the outcome on the
+/// file record and refreshes the file's tax-id review flags.
+pub fn apply_tax_id_validation(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+) -> Result<Vec<TaxIdCheck>, String> {
+    let checks = parsed_details
+        .and_then(ParsedInvoice::from_json)
+        .map(|invoice| TaxIdValidator::check_invoice(&invoice))
+        .unwrap_or_default();
+
+    let all_valid = if checks.is_empty() {
+        None
+    } else {
+        Some(checks.iter().all(|check| check.valid))
+    };
+
+    conn.execute(
+        "UPDATE files SET tax_ids_valid = ?1 WHERE id = ?2",
+        params![all_valid, file_id],
+    )
+    .map_err(|error| error.to_string())?;
+
+    let flags: Vec<NewReviewFlag> = checks
+        .iter()
+        .filter(|check| !check.valid)
+        .map(|check| NewReviewFlag {
+            field: Some(check.field.clone()),
+            message: check
+                .message
+                .clone()
+                .unwrap_or_else(|| "Invalid tax identifier.".to_string()),
+            details: Some(json!({ "value": check.value, "kind": check.kind })),
+        })
+        .collect();
+
+    ReviewQueue::replace_flags(conn, file_id, TAX_ID_REVIEW_SOURCE, &flags)?;
+
+    Ok(checks)
+}
+
+/// Compares the extracted fields with the QR codes on the document, decoding them the first
+/// time the file is checked, and refreshes the file's QR review flags.
+pub fn apply_qr_cross_check(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+) -> Result<Vec<QrMismatch>, String> {
+    let codes = match QrScanner::stored(conn, file_id)? {
+        Some(codes) => codes,
+        // An unreadable document is left unscanned so a later check can try again.
+        None => QrScanner::scan_file(conn, file_id).unwrap_or_default(),
+    };
+    let mismatches = parsed_details
+        .and_then(ParsedInvoice::from_json)
+        .map(|invoice| QrScanner::cross_check(&codes, &invoice, DEFAULT_TOLERANCE))
+        .unwrap_or_default();
+
+    let flags: Vec<NewReviewFlag> = mismatches
+        .iter()
+        .map(|mismatch| NewReviewFlag {
+            field: Some(mismatch.field.clone()),
+            message: mismatch.message.clone(),
+            details: Some(json!({ "qrValue": mismatch.qr_value, "extractedValue": mismatch.extracted_value })),
+        })
+        .collect();
+    ReviewQueue::replace_flags(conn, file_id, QR_REVIEW_SOURCE, &flags)?;
+
+    Ok(mismatches)
+}
+
+/// Stores the language the parsed document is written in, for filtering listings,
+/// exports and search. Files without parsed details have none.
+pub fn apply_language_detection(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+) -> Result<Option<&'static str>, String> {
+    let language = parsed_details
+        .and_then(|details| serde_json::from_str(details).ok())
+        .and_then(|parsed| LanguageDetector::detect_parsed(&parsed));
+    conn.execute(
+        "UPDATE files SET document_language = ?1 WHERE id = ?2",
+        params![language, file_id],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(language)
+}
+
+/// Replaces the import-time guess with what the parsed details say. A file whose details
+/// say nothing keeps its current type.
+pub fn apply_document_classification(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+) -> Result<Option<DocType>, String> {
+    let doc_type = parsed_details
+        .and_then(|details| serde_json::from_str(details).ok())
+        .and_then(|parsed| DocumentClassifier::classify_parsed(&parsed));
+    if let Some(doc_type) = doc_type {
+        DocumentClassifier::set(conn, file_id, doc_type)?;
+    }
+    Ok(doc_type)
+}
+
+#[tauri::command]
+pub fn validate_file_tax_ids(file_id: String) -> Result<Vec<TaxIdCheck>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let parsed_details = load_parsed_details(&conn, &file_id)?;
+    apply_tax_id_validation(&conn, &file_id, parsed_details.as_deref())
+}
+
+/// Re-checks the GSTIN, VAT and EIN numbers of several files at once, e.g. before a
+/// return is filed, refreshing each file's stored outcome and review flags.
+#[tauri::command]
+pub fn validate_tax_ids(file_ids: Vec<String>) -> Result<Vec<FileTaxIdValidation>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let mut results = Vec::with_capacity(file_ids.len());
+    for file_id in file_ids {
+        let (file_name, parsed_details): (String, Option<String>) = conn
+            .query_row(
+                "SELECT file_name, parsed_details FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let checks = apply_tax_id_validation(&conn, &file_id, parsed_details.as_deref())?;
+        let valid = (!checks.is_empty()).then(|| checks.iter().all(|check| check.valid));
+        results.push(FileTaxIdValidation {
+            file_id,
+            file_name,
+            valid,
+            checks,
+        });
+    }
+    Ok(results)
+}
+
+/// Normalizes the extracted invoice date to ISO, keeping the raw value next to it
+/// and flagging ambiguous or unreadable dates for review.
+pub fn apply_date_normalization(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+    locale: Option<&str>,
+) -> Result<Option<NormalizedDate>, String> {
+    let normalized = parsed_details
+        .and_then(ParsedInvoice::from_json)
+        .and_then(|invoice| invoice.text(INVOICE_DATE))
+        .map(|raw| DateNormalizer::normalize(&raw, locale));
+
+    conn.execute(
+        "UPDATE files SET invoice_date = ?1, invoice_date_raw = ?2 WHERE id = ?3",
+        params![
+            normalized.as_ref().and_then(|date| date.iso.clone()),
+            normalized.as_ref().map(|date| date.raw.clone()),
+            file_id
+        ],
+    )
+    .map_err(|error| error.to_string())?;
+
+    let flags: Vec<NewReviewFlag> = normalized
+        .iter()
+        .filter_map(|date| {
+            date.message.as_ref().map(|message| NewReviewFlag {
+                field: Some(INVOICE_DATE.to_string()),
+                message: message.clone(),
+                details: Some(json!({ "raw": date.raw, "iso": date.iso, "ambiguous": date.ambiguous })),
+            })
+        })
+        .collect();
+
+    ReviewQueue::replace_flags(conn, file_id, INVOICE_DATE_REVIEW_SOURCE, &flags)?;
+
+    Ok(normalized)
+}
+
+#[tauri::command]
+pub fn normalize_invoice_date(
+    file_id: String,
+    locale: Option<String>,
+) -> Result<Option<NormalizedDate>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let parsed_details = load_parsed_details(&conn, &file_id)?;
+    let normalized = apply_date_normalization(&conn, &file_id, parsed_details.as_deref(), locale.as_deref())?;
+    // A due date computed from the payment terms counts from the invoice date.
+    PaymentTracker::update_terms(&conn, &file_id, parsed_details.as_deref())?;
+    Ok(normalized)
+}
+
+/// Checks line, tax and total arithmetic, storing the outcome on the file record
+/// and queueing every discrepancy (with computed vs stated values) for review.
+pub fn apply_arithmetic_validation(
+    conn: &Connection,
+    file_id: &str,
+    parsed_details: Option<&str>,
+    tolerance: f64,
+) -> Result<Vec<ArithmeticIssue>, String> {
+    let invoice = parsed_details.and_then(ParsedInvoice::from_json);
+    let issues = invoice
+        .as_ref()
+        .map(|invoice| ArithmeticValidator::check(invoice, tolerance))
+        .unwrap_or_default();
+
+    let consistent = invoice.as_ref().map(|_| issues.is_empty());
+    let errors = if issues.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&issues).map_err(|error| error.to_string())?)
+    };
+    conn.execute(
+        "UPDATE files SET arithmetic_valid = ?1, validation_errors = ?2 WHERE id = ?3",
+        params![consistent, errors, file_id],
+    )
+    .map_err(|error| error.to_string())?;
+
+    let flags: Vec<NewReviewFlag> = issues
+        .iter()
+        .map(|issue| NewReviewFlag {
+            field: Some(issue.field.clone()),
+            message: issue.message.clone(),
+            details: Some(json!({
+                "check": issue.check,
+                "computed": issue.computed,
+                "stated": issue.stated,
+                "difference": issue.difference,
+            })),
+        })
+        .collect();
+
+    ReviewQueue::replace_flags(conn, file_id, ARITHMETIC_REVIEW_SOURCE, &flags)?;
+
+    Ok(issues)
+}
+
+#[tauri::command]
+pub fn validate_file_arithmetic(
+    file_id: String,
+    tolerance: Option<f64>,
+) -> Result<Vec<ArithmeticIssue>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let parsed_details = load_parsed_details(&conn, &file_id)?;
+    apply_arithmetic_validation(
+        &conn,
+        &file_id,
+        parsed_details.as_deref(),
+        tolerance.unwrap_or(DEFAULT_TOLERANCE),
+    )
+}
+
+/// Re-checks line item sums, tax math and totals for a file and stores the errors found.
+#[tauri::command]
+pub fn validate_file(file_id: String, tolerance: Option<f64>) -> Result<FileValidation, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    let (file_name, parsed_details): (String, Option<String>) = conn
+        .query_row(
+            "SELECT file_name, parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("File not found: {}", file_id))?;
+    let errors = apply_arithmetic_validation(
+        &conn,
+        &file_id,
+        parsed_details.as_deref(),
+        tolerance.unwrap_or(DEFAULT_TOLERANCE),
+    )?;
+    let valid = parsed_details
+        .as_deref()
+        .and_then(ParsedInvoice::from_json)
+        .map(|_| errors.is_empty());
+
+    Ok(FileValidation {
+        file_id,
+        file_name,
+        valid,
+        errors,
+    })
+}
+
+/// Files whose stored validation errors are not empty, most recently updated first.
+#[tauri::command]
+pub fn list_files_with_validation_errors() -> Result<Vec<FileValidation>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+
+    // Files flagged before errors were stored have none to list until they are re-checked.
+    let unrecorded: Vec<(String, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, parsed_details FROM files
+                 WHERE arithmetic_valid = 0 AND validation_errors IS NULL",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?
+    };
+    for (file_id, parsed_details) in unrecorded {
+        apply_arithmetic_validation(&conn, &file_id, parsed_details.as_deref(), DEFAULT_TOLERANCE)?;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_name, validation_errors FROM files
+             WHERE validation_errors IS NOT NULL
+             ORDER BY updated_at DESC",
+        )
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let errors: String = row.get(2)?;
+            Ok(FileValidation {
+                file_id: row.get(0)?,
+                file_name: row.get(1)?,
+                valid: Some(false),
+                errors: serde_json::from_str(&errors).unwrap_or_default(),
+            })
+        })
+        .map_err(|error| error.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())
+}
@@ -0,0 +1,55 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    roles::Roles,
+    watcher::{FolderWatcher, WatchedFolder},
+    workspace_lock::WorkspaceLock,
+};
+
+#[tauri::command]
+pub fn list_watched_folders() -> Result<Vec<WatchedFolder>, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    FolderWatcher::list(&conn)
+}
+
+/// New documents in `path` are imported automatically from now on.
+#[tauri::command]
+pub fn add_watched_folder(path: String, recursive: Option<bool>) -> Result<WatchedFolder, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    let folder = FolderWatcher::add(&conn, &path, recursive.unwrap_or(false))?;
+    FolderWatcher::reload()?;
+    AuditLog::record(
+        &conn,
+        "watched_folder.added",
+        Some(&folder.id.to_string()),
+        Some(serde_json::json!({ "path": folder.path, "recursive": folder.recursive })),
+    )?;
+    Ok(folder)
+}
+
+#[tauri::command]
+pub fn remove_watched_folder(folder_id: i64) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    let folder = FolderWatcher::remove(&conn, folder_id)?;
+    FolderWatcher::reload()?;
+    AuditLog::record(
+        &conn,
+        "watched_folder.removed",
+        Some(&folder_id.to_string()),
+        Some(serde_json::json!({ "path": folder.path })),
+    )
+}
+
+#[tauri::command]
+pub fn set_watched_folder_enabled(folder_id: i64, enabled: bool) -> Result<WatchedFolder, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.change_settings")?;
+    let folder = FolderWatcher::set_enabled(&conn, folder_id, enabled)?;
+    FolderWatcher::reload()?;
+    Ok(folder)
+}
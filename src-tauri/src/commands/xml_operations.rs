@@ -1,7 +1,13 @@
 use crate::db::get_connection;
+use crate::services::audit_log::AuditLog;
+use crate::services::export_manifest::{ExportManifests, ExportRecord};
+use crate::services::roles::Roles;
+use crate::services::telemetry::Telemetry;
+use crate::services::workspace_lock::WorkspaceLock;
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Serialize};
-use serde_json;
+use std::fs;
+use std::path::Path;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,6 +36,26 @@ fn ensure_xml_record_exists(conn: &Connection, xml_id: i64) -> Result<(), String
         .ok_or_else(|| "XML file record not found.".to_string())
 }
 
+/// Adds `file_ids` to the end of the sheet, skipping files it already holds.
+fn add_entries(conn: &Connection, xml_id: i64, file_ids: &[String]) -> Result<(), String> {
+    let next_position: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM xml_file_entries WHERE xml_id = ?1",
+            params![xml_id],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?;
+
+    let mut stmt = conn
+        .prepare("INSERT OR IGNORE INTO xml_file_entries (xml_id, file_id, position) VALUES (?1, ?2, ?3)")
+        .map_err(|error| error.to_string())?;
+    for (offset, file_id) in file_ids.iter().enumerate() {
+        stmt.execute(params![xml_id, file_id, next_position + offset as i64])
+            .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
 fn sanitize_file_name(value: &str, fallback: &str) -> String {
     let mut result = String::with_capacity(value.len());
     let mut last_dash = false;
@@ -57,38 +83,72 @@ fn sanitize_file_name(value: &str, fallback: &str) -> String {
 #[tauri::command]
 pub fn create_xml_for_files(file_ids: Vec<String>, xml_name: String) -> Result<i64, String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
 
-    let file_ids_json = serde_json::to_string(&file_ids).map_err(|error| error.to_string())?;
     let xml_path = sanitize_file_name(&xml_name, "untitled-xml");
 
-    conn.execute(
-        "INSERT INTO xml_files (xml_name, file_ids, xml_path) VALUES (?1, ?2, ?3)",
-        params![xml_name, file_ids_json, xml_path],
+    let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+    tx.execute(
+        "INSERT INTO xml_files (xml_name, xml_path) VALUES (?1, ?2)",
+        params![xml_name, xml_path],
     )
     .map_err(|error| error.to_string())?;
+    let xml_id = tx.last_insert_rowid();
+    add_entries(&tx, xml_id, &file_ids)?;
+    tx.commit().map_err(|error| error.to_string())?;
 
-    let xml_id = conn.last_insert_rowid();
     Ok(xml_id)
 }
 
+#[tauri::command]
+pub fn create_xml_for_buyer(buyer_id: i64, xml_name: String) -> Result<i64, String> {
+    let file_ids = {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        WorkspaceLock::require_unlocked(&conn)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM files WHERE buyer_id = ?1 AND parsed_details IS NOT NULL ORDER BY created_at ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let ids_iter = stmt
+            .query_map(params![buyer_id], |row| row.get::<_, String>(0))
+            .map_err(|error| error.to_string())?;
+
+        let mut file_ids = Vec::new();
+        for id in ids_iter {
+            file_ids.push(id.map_err(|error| error.to_string())?);
+        }
+        file_ids
+    };
+
+    if file_ids.is_empty() {
+        return Err("No processed files found for this buyer.".to_string());
+    }
+
+    create_xml_for_files(file_ids, xml_name)
+}
+
 #[tauri::command]
 pub fn list_xml_files() -> Result<Vec<XmlFileRow>, String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
     
     let mut stmt = conn
-        .prepare("SELECT id, xml_name, created_at, file_ids FROM xml_files ORDER BY created_at DESC")
+        .prepare(
+            "SELECT xml_files.id, xml_files.xml_name, xml_files.created_at, COUNT(entry.file_id)
+             FROM xml_files
+             LEFT JOIN xml_file_entries AS entry ON entry.xml_id = xml_files.id
+             GROUP BY xml_files.id
+             ORDER BY xml_files.created_at DESC",
+        )
         .map_err(|error| error.to_string())?;
         
     let rows_iter = stmt
         .query_map([], |row| {
-            let file_ids_json: String = row.get(3)?;
-            let file_ids: Vec<String> = serde_json::from_str(&file_ids_json).unwrap_or_default();
-            
             Ok(XmlFileRow {
                 id: row.get(0)?,
                 xml_name: row.get(1)?,
                 created_at: row.get(2)?,
-                file_count: file_ids.len(),
+                file_count: row.get::<_, i64>(3)? as usize,
             })
         })
         .map_err(|error| error.to_string())?;
@@ -104,67 +164,104 @@ pub fn list_xml_files() -> Result<Vec<XmlFileRow>, String> {
 #[tauri::command]
 pub fn append_xml_file(xml_id: i64, file_ids: Vec<String>) -> Result<(), String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
-    
-    // Get existing file IDs
-    let mut stmt = conn
-        .prepare("SELECT file_ids FROM xml_files WHERE id = ?1")
-        .map_err(|error| error.to_string())?;
-        
-    let existing_json: String = stmt
-        .query_row(params![xml_id], |row| row.get(0))
-        .map_err(|error| error.to_string())?;
-        
-    let mut existing_ids: Vec<String> = serde_json::from_str(&existing_json).map_err(|error| error.to_string())?;
-    
-    // Append new IDs (avoiding duplicates)
-    for id in file_ids {
-        if !existing_ids.contains(&id) {
-            existing_ids.push(id);
-        }
+    WorkspaceLock::require_unlocked(&conn)?;
+    ensure_xml_record_exists(&conn, xml_id)?;
+
+    let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+    add_entries(&tx, xml_id, &file_ids)?;
+    // Entries are their own table, so touch the sheet to show it changed.
+    tx.execute(
+        "UPDATE xml_files SET updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        params![xml_id],
+    )
+    .map_err(|error| error.to_string())?;
+    tx.commit().map_err(|error| error.to_string())?;
+
+    Ok(())
+}
+
+/// Renames a sheet; the name also sets the file name its XML is offered under.
+#[tauri::command]
+pub fn rename_xml_file(xml_id: i64, xml_name: String) -> Result<(), String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    ensure_xml_record_exists(&conn, xml_id)?;
+
+    let xml_name = xml_name.trim();
+    if xml_name.is_empty() {
+        return Err("Give the sheet a name.".to_string());
     }
-    
-    let new_json = serde_json::to_string(&existing_ids).map_err(|error| error.to_string())?;
-    
     conn.execute(
-        "UPDATE xml_files SET file_ids = ?1 WHERE id = ?2",
-        params![new_json, xml_id],
+        "UPDATE xml_files SET xml_name = ?1, xml_path = ?2 WHERE id = ?3",
+        params![xml_name, sanitize_file_name(xml_name, "untitled-xml"), xml_id],
     )
     .map_err(|error| error.to_string())?;
-    
+
+    AuditLog::record(
+        &conn,
+        "sheet.renamed",
+        Some(&xml_id.to_string()),
+        Some(serde_json::json!({ "xmlName": xml_name })),
+    )?;
     Ok(())
 }
 
+/// Deletes a sheet. Its files, and XML already written from it, are kept.
 #[tauri::command]
-pub fn generate_xml_file(xml_id: i64) -> Result<XmlDownloadResponse, String> {
+pub fn delete_xml_file(xml_id: i64) -> Result<(), String> {
     let conn = get_connection().map_err(|error| error.to_string())?;
+    WorkspaceLock::require_unlocked(&conn)?;
+    Roles::require_admin(&conn, "action.delete_sheets")?;
     ensure_xml_record_exists(&conn, xml_id)?;
 
-    // Get file IDs from the xml_files table
-    let mut stmt = conn
-        .prepare("SELECT file_ids FROM xml_files WHERE id = ?1")
+    let xml_name: String = conn
+        .query_row(
+            "SELECT xml_name FROM xml_files WHERE id = ?1",
+            params![xml_id],
+            |row| row.get(0),
+        )
         .map_err(|error| error.to_string())?;
-    let file_ids_json: String = stmt
-        .query_row(params![xml_id], |row| row.get(0))
+    conn.execute("DELETE FROM xml_files WHERE id = ?1", params![xml_id])
         .map_err(|error| error.to_string())?;
-    let file_ids: Vec<String> =
-        serde_json::from_str(&file_ids_json).map_err(|error| error.to_string())?;
 
-    if file_ids.is_empty() {
+    AuditLog::record(
+        &conn,
+        "sheet.deleted",
+        Some(&xml_id.to_string()),
+        Some(serde_json::json!({ "xmlName": xml_name })),
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn generate_xml_file(xml_id: i64) -> Result<XmlDownloadResponse, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ensure_xml_record_exists(&conn, xml_id)?;
+
+    let file_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM xml_file_entries WHERE xml_id = ?1",
+            params![xml_id],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?;
+    if file_count == 0 {
         return Err("No files associated with this XML export.".to_string());
     }
 
-    // Retrieve parsed details for each file
-    let placeholders = file_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
-    let query = format!(
-        "SELECT id, file_name, parsed_details FROM files WHERE id IN ({})",
-        placeholders
-    );
-    let mut stmt = conn.prepare(&query).map_err(|error| error.to_string())?;
-    let params: Vec<&dyn rusqlite::ToSql> =
-        file_ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+    // Retrieve parsed details for each file, in the order they were added
+    let mut stmt = conn
+        .prepare(
+            "SELECT files.id, files.file_name, files.parsed_details
+             FROM xml_file_entries AS entry
+             JOIN files ON files.id = entry.file_id
+             WHERE entry.xml_id = ?1
+             ORDER BY entry.position ASC",
+        )
+        .map_err(|error| error.to_string())?;
 
     let files_iter = stmt
-        .query_map(params.as_slice(), |row| {
+        .query_map(params![xml_id], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
         })
         .map_err(|error| error.to_string())?;
@@ -192,9 +289,38 @@ pub fn generate_xml_file(xml_id: i64) -> Result<XmlDownloadResponse, String> {
         );
     }
 
+    Telemetry::record_export(&conn, "xml_export");
+
     // Return the content directly
     Ok(XmlDownloadResponse {
         content: xml_content,
         file_count: processed_file_count,
     })
 }
+
+/// Writes the Tally XML the frontend generated for sheet `xml_id` and records its manifest.
+#[tauri::command]
+pub fn save_xml_export(xml_id: i64, path: String, content: String) -> Result<ExportRecord, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    ensure_xml_record_exists(&conn, xml_id)?;
+
+    // Only processed files become vouchers, as in `generate_xml_file`.
+    let mut stmt = conn
+        .prepare(
+            "SELECT entry.file_id
+             FROM xml_file_entries AS entry
+             JOIN files ON files.id = entry.file_id
+             WHERE entry.xml_id = ?1 AND files.parsed_details IS NOT NULL
+             ORDER BY entry.position ASC",
+        )
+        .map_err(|error| error.to_string())?;
+    let exported = stmt
+        .query_map(params![xml_id], |row| row.get::<_, String>(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let output = Path::new(&path);
+    fs::write(output, content).map_err(|error| error.to_string())?;
+    ExportManifests::record(&conn, "xml_export", output, &exported, exported.len())
+}
@@ -1,13 +1,48 @@
 use std::fs;
+use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
 use rusqlite::{Connection, Error as SqlError, Result as SqlResult};
 use tauri_plugin_sql::{Migration, MigrationKind};
 use chrono::Utc;
 use chrono_tz::America::Los_Angeles;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
+use crate::services::database_encryption::DatabaseEncryption;
+use crate::services::secure_delete::SECURE_DELETE_KEY;
 
 const APP_DIR_NAME: &str = "com.invox.ai";
 const DB_FILE_NAME: &str = "app.db";
+/// How long a statement waits for another connection's write lock before failing with
+/// "database is locked".
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connections kept open between commands. More can be checked out at once; those beyond
+/// this are closed when handed back.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+/// How long replacing `app.db` waits for connections in use to be handed back.
+const EXCLUSIVE_WAIT: Duration = Duration::from_secs(30);
+
+/// Open connections waiting to be reused. `generation` goes up whenever they are
+/// discarded, so connections checked out before that are closed instead of returned.
+struct ConnectionPool {
+    idle: Vec<Connection>,
+    generation: u64,
+    /// Connections handed out and not yet dropped.
+    checked_out: usize,
+    /// Set while `app.db` is being replaced; no connections are handed out meanwhile.
+    exclusive: bool,
+}
+
+static POOL: Mutex<ConnectionPool> = Mutex::new(ConnectionPool {
+    idle: Vec::new(),
+    generation: 0,
+    checked_out: 0,
+    exclusive: false,
+});
+/// Signalled whenever a connection is handed back or exclusive access ends.
+static POOL_CHANGED: Condvar = Condvar::new();
+/// The pool generation whose database has had its schema brought up to date.
+static SCHEMA_READY: Mutex<Option<u64>> = Mutex::new(None);
 
 const CORE_SCHEMA: &str = r#"
     CREATE TABLE IF NOT EXISTS files (
@@ -71,6 +106,705 @@ const CORE_SCHEMA: &str = r#"
     END;
 "#;
 
+const REVIEW_FLAGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS review_flags (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      source TEXT NOT NULL,
+      field TEXT,
+      message TEXT NOT NULL,
+      details TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      resolved_at TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS review_flags_file_idx ON review_flags(file_id);
+    CREATE INDEX IF NOT EXISTS review_flags_open_idx ON review_flags(resolved_at);
+"#;
+
+const DOUBLE_PAYMENT_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS double_payment_resolutions (
+      invoice_key TEXT PRIMARY KEY,
+      canonical_file_id TEXT NOT NULL,
+      resolved_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+const SELLERS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS sellers (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL,
+      tax_id TEXT,
+      address TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS sellers_tax_id_idx ON sellers(tax_id);
+
+    CREATE TRIGGER IF NOT EXISTS sellers_touch_updated_at
+    AFTER UPDATE ON sellers
+    FOR EACH ROW
+    WHEN NEW.updated_at <= OLD.updated_at
+    BEGIN
+      UPDATE sellers SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+    END;
+
+    CREATE TABLE IF NOT EXISTS seller_aliases (
+      alias_key TEXT PRIMARY KEY,
+      alias TEXT NOT NULL,
+      seller_id INTEGER NOT NULL REFERENCES sellers(id) ON DELETE CASCADE,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS seller_aliases_seller_idx ON seller_aliases(seller_id);
+"#;
+
+const BUYERS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS buyers (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL,
+      tax_id TEXT,
+      address TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS buyers_tax_id_idx ON buyers(tax_id);
+
+    CREATE TRIGGER IF NOT EXISTS buyers_touch_updated_at
+    AFTER UPDATE ON buyers
+    FOR EACH ROW
+    WHEN NEW.updated_at <= OLD.updated_at
+    BEGIN
+      UPDATE buyers SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+    END;
+
+    CREATE TABLE IF NOT EXISTS buyer_aliases (
+      alias_key TEXT PRIMARY KEY,
+      alias TEXT NOT NULL,
+      buyer_id INTEGER NOT NULL REFERENCES buyers(id) ON DELETE CASCADE,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS buyer_aliases_buyer_idx ON buyer_aliases(buyer_id);
+"#;
+
+const LINE_ITEMS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoice_line_items (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      position INTEGER NOT NULL,
+      description TEXT,
+      name TEXT,
+      hsn_sac TEXT,
+      quantity REAL,
+      unit TEXT,
+      rate REAL,
+      amount REAL,
+      cgst REAL,
+      sgst REAL,
+      cgst_rate REAL,
+      sgst_rate REAL
+    );
+
+    CREATE INDEX IF NOT EXISTS invoice_line_items_file_idx ON invoice_line_items(file_id);
+    CREATE INDEX IF NOT EXISTS invoice_line_items_hsn_idx ON invoice_line_items(hsn_sac);
+
+    CREATE TRIGGER IF NOT EXISTS invoice_line_items_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM invoice_line_items WHERE file_id = OLD.id;
+    END;
+"#;
+
+const PAYMENTS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS payments (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL UNIQUE,
+      paid_on TEXT NOT NULL,
+      reference TEXT,
+      amount REAL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+const SETTINGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS app_settings (
+      key TEXT PRIMARY KEY,
+      value TEXT NOT NULL,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+const AUDIT_LOG_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS audit_log (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      action TEXT NOT NULL,
+      subject TEXT,
+      details TEXT,
+      created_at TEXT NOT NULL,
+      prev_hash TEXT NOT NULL,
+      entry_hash TEXT NOT NULL
+    );
+"#;
+
+const BANK_RECONCILIATION_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS bank_statements (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_name TEXT NOT NULL,
+      source_path TEXT,
+      imported_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS bank_statement_lines (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      statement_id INTEGER NOT NULL REFERENCES bank_statements(id) ON DELETE CASCADE,
+      line_key TEXT NOT NULL UNIQUE,
+      posted_on TEXT NOT NULL,
+      amount REAL NOT NULL,
+      description TEXT,
+      reference TEXT,
+      matched_file_id TEXT,
+      match_score REAL,
+      match_status TEXT NOT NULL DEFAULT 'Unmatched',
+      confirmed_at TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS bank_statement_lines_status_idx ON bank_statement_lines(match_status);
+    CREATE INDEX IF NOT EXISTS bank_statement_lines_file_idx ON bank_statement_lines(matched_file_id);
+"#;
+
+const SCHEDULER_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS schedules (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL UNIQUE,
+      job TEXT NOT NULL,
+      cron TEXT NOT NULL,
+      enabled INTEGER NOT NULL DEFAULT 0,
+      options TEXT,
+      last_run_at TEXT,
+      next_run_at TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS schedule_runs (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      schedule_id INTEGER NOT NULL REFERENCES schedules(id) ON DELETE CASCADE,
+      started_at TEXT NOT NULL,
+      finished_at TEXT,
+      status TEXT NOT NULL DEFAULT 'Running',
+      message TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS schedule_runs_schedule_idx ON schedule_runs(schedule_id, started_at);
+
+    INSERT OR IGNORE INTO schedules (name, job, cron, options) VALUES
+      ('Nightly backup', 'backup', '0 2 * * *', NULL),
+      ('Process pending files', 'auto_process', '*/15 * * * *', NULL),
+      ('Scan watched folders', 'watched_folder_scan', '*/5 * * * *', NULL),
+      ('Monthly spend report', 'report_generation', '0 6 1 * *', '{"kind":"spend_summary","period":"previous_month"}');
+"#;
+
+const TELEMETRY_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS telemetry_counters (
+      name TEXT PRIMARY KEY,
+      value INTEGER NOT NULL DEFAULT 0
+    );
+"#;
+
+const PROMPT_TEMPLATES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS prompt_templates (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      kind TEXT NOT NULL CHECK (kind IN ('system_instruction', 'user_prompt')),
+      version INTEGER NOT NULL,
+      content TEXT NOT NULL,
+      notes TEXT,
+      is_active INTEGER NOT NULL DEFAULT 0,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      UNIQUE(kind, version)
+    );
+
+    CREATE INDEX IF NOT EXISTS prompt_templates_active_idx ON prompt_templates(kind, is_active);
+"#;
+
+const PROCESSING_USAGE_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS processing_usage (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT,
+      model TEXT NOT NULL,
+      profile TEXT NOT NULL DEFAULT 'default',
+      prompt_tokens INTEGER NOT NULL DEFAULT 0,
+      output_tokens INTEGER NOT NULL DEFAULT 0,
+      cost REAL NOT NULL DEFAULT 0,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS processing_usage_created_idx ON processing_usage(created_at);
+"#;
+
+const REPROCESS_CANDIDATES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS reprocess_candidates (
+      file_id TEXT PRIMARY KEY,
+      candidate TEXT NOT NULL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TRIGGER IF NOT EXISTS reprocess_candidates_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM reprocess_candidates WHERE file_id = OLD.id;
+    END;
+"#;
+
+const PARSED_DETAILS_HISTORY_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS parsed_details_history (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      version INTEGER NOT NULL,
+      parsed_details TEXT NOT NULL,
+      source TEXT NOT NULL,
+      role TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      UNIQUE(file_id, version)
+    );
+
+    CREATE TRIGGER IF NOT EXISTS parsed_details_history_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM parsed_details_history WHERE file_id = OLD.id;
+    END;
+"#;
+
+const EXCHANGE_RATES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS exchange_rates (
+      currency TEXT PRIMARY KEY,
+      rate REAL NOT NULL,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+const TRASH_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS deleted_files (
+      file_id TEXT PRIMARY KEY,
+      file_name TEXT NOT NULL,
+      trash_path TEXT,
+      size_bytes INTEGER NOT NULL,
+      content_hash TEXT,
+      hash_algo TEXT,
+      status TEXT,
+      parsed_details TEXT,
+      deleted_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS deleted_files_deleted_at_idx ON deleted_files(deleted_at);
+"#;
+
+const EXPORTS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS exports (
+      id TEXT PRIMARY KEY,
+      kind TEXT NOT NULL,
+      format TEXT NOT NULL,
+      path TEXT NOT NULL,
+      sha256 TEXT NOT NULL,
+      size_bytes INTEGER NOT NULL,
+      row_count INTEGER NOT NULL,
+      manifest TEXT NOT NULL,
+      created_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS exports_created_at_idx ON exports(created_at);
+"#;
+
+const CLOUD_DRIVE_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS cloud_drive_links (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      provider TEXT NOT NULL,
+      client_id TEXT NOT NULL,
+      folder TEXT NOT NULL,
+      folder_name TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      last_synced_at TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS cloud_drive_items (
+      link_id INTEGER NOT NULL,
+      remote_id TEXT NOT NULL,
+      modified_at TEXT,
+      file_id TEXT NOT NULL,
+      imported_at TEXT NOT NULL,
+      PRIMARY KEY (link_id, remote_id)
+    );
+"#;
+
+const EXPORT_PROFILES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS export_profiles (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL UNIQUE,
+      format TEXT NOT NULL,
+      columns TEXT NOT NULL DEFAULT '[]',
+      locale_options TEXT NOT NULL DEFAULT '{}',
+      destination TEXT,
+      naming_template TEXT NOT NULL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      last_run_at TEXT
+    );
+"#;
+
+const WATCHED_FOLDERS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS watched_folders (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      path TEXT NOT NULL UNIQUE,
+      recursive INTEGER NOT NULL DEFAULT 0,
+      enabled INTEGER NOT NULL DEFAULT 1,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      last_scanned_at TEXT
+    );
+"#;
+
+const IMPORT_BATCHES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS import_batches (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      source TEXT NOT NULL,
+      label TEXT,
+      started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      finished_at TEXT,
+      cancelled INTEGER NOT NULL DEFAULT 0
+    );
+
+    CREATE TABLE IF NOT EXISTS import_batch_items (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      batch_id INTEGER NOT NULL REFERENCES import_batches(id) ON DELETE CASCADE,
+      entry TEXT NOT NULL,
+      status TEXT NOT NULL,
+      file_id TEXT,
+      reason TEXT,
+      recorded_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS import_batches_started_idx ON import_batches(started_at);
+    CREATE INDEX IF NOT EXISTS import_batch_items_batch_idx ON import_batch_items(batch_id);
+"#;
+
+const LLM_REQUESTS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS llm_requests (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT,
+      provider TEXT NOT NULL,
+      model TEXT NOT NULL,
+      attempt INTEGER NOT NULL DEFAULT 1,
+      request TEXT NOT NULL,
+      response TEXT,
+      error TEXT,
+      prompt_tokens INTEGER NOT NULL DEFAULT 0,
+      output_tokens INTEGER NOT NULL DEFAULT 0,
+      started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      finished_at TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS llm_requests_file_idx ON llm_requests(file_id);
+
+    CREATE TRIGGER IF NOT EXISTS llm_requests_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM llm_requests WHERE file_id = OLD.id;
+    END;
+"#;
+
+const PARSE_JOBS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS parse_jobs (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      provider TEXT,
+      status TEXT NOT NULL DEFAULT 'queued',
+      error TEXT,
+      enqueued_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      started_at TEXT,
+      finished_at TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS parse_jobs_status_idx ON parse_jobs(status, id);
+    CREATE INDEX IF NOT EXISTS parse_jobs_file_idx ON parse_jobs(file_id);
+
+    CREATE TRIGGER IF NOT EXISTS parse_jobs_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM parse_jobs WHERE file_id = OLD.id;
+    END;
+"#;
+
+const INVOICES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoices (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL UNIQUE,
+      seller_id INTEGER REFERENCES sellers(id) ON DELETE SET NULL,
+      buyer_id INTEGER REFERENCES buyers(id) ON DELETE SET NULL,
+      invoice_number TEXT,
+      invoice_date TEXT,
+      due_date TEXT,
+      voucher_type TEXT,
+      place_of_supply TEXT,
+      currency TEXT,
+      subtotal REAL NOT NULL DEFAULT 0,
+      cgst_total REAL NOT NULL DEFAULT 0,
+      sgst_total REAL NOT NULL DEFAULT 0,
+      tax_total REAL NOT NULL DEFAULT 0,
+      grand_total REAL NOT NULL DEFAULT 0,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS invoices_date_idx ON invoices(invoice_date);
+    CREATE INDEX IF NOT EXISTS invoices_seller_date_idx ON invoices(seller_id, invoice_date);
+    CREATE INDEX IF NOT EXISTS invoices_buyer_date_idx ON invoices(buyer_id, invoice_date);
+
+    CREATE TRIGGER IF NOT EXISTS invoices_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM invoices WHERE file_id = OLD.id;
+    END;
+"#;
+
+const EXTRACTION_TEMPLATES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS extraction_templates (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL UNIQUE,
+      seller_id INTEGER REFERENCES sellers(id) ON DELETE SET NULL,
+      match_pattern TEXT NOT NULL,
+      rules TEXT NOT NULL,
+      item_pattern TEXT,
+      enabled INTEGER NOT NULL DEFAULT 1,
+      use_count INTEGER NOT NULL DEFAULT 0,
+      last_used_at TEXT,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+"#;
+
+const FIELD_CORRECTIONS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS field_corrections (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      field TEXT NOT NULL,
+      original_value TEXT,
+      corrected_value TEXT,
+      extracted_by TEXT,
+      editor TEXT,
+      role TEXT NOT NULL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS field_corrections_file_idx ON field_corrections(file_id);
+    CREATE INDEX IF NOT EXISTS field_corrections_source_idx ON field_corrections(extracted_by, field);
+
+    CREATE TRIGGER IF NOT EXISTS field_corrections_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM field_corrections WHERE file_id = OLD.id;
+    END;
+"#;
+
+const PARSE_METRICS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS parse_metrics (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT,
+      provider TEXT NOT NULL,
+      model TEXT,
+      attempts INTEGER NOT NULL DEFAULT 1,
+      outcome TEXT NOT NULL,
+      error TEXT,
+      document_ms INTEGER NOT NULL DEFAULT 0,
+      model_ms INTEGER NOT NULL DEFAULT 0,
+      save_ms INTEGER NOT NULL DEFAULT 0,
+      total_ms INTEGER NOT NULL DEFAULT 0,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS parse_metrics_created_idx ON parse_metrics(created_at);
+"#;
+
+const INVOICE_EMBEDDINGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoice_embeddings (
+      file_id TEXT PRIMARY KEY,
+      provider TEXT NOT NULL,
+      model TEXT NOT NULL,
+      content_hash TEXT NOT NULL,
+      dimensions INTEGER NOT NULL,
+      vector BLOB NOT NULL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TRIGGER IF NOT EXISTS invoice_embeddings_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM invoice_embeddings WHERE file_id = OLD.id;
+    END;
+"#;
+
+const TAX_LINES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS tax_lines (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      file_id TEXT NOT NULL,
+      rate REAL NOT NULL,
+      taxable_value REAL NOT NULL DEFAULT 0,
+      cgst REAL NOT NULL DEFAULT 0,
+      sgst REAL NOT NULL DEFAULT 0,
+      igst REAL NOT NULL DEFAULT 0,
+      cess REAL NOT NULL DEFAULT 0,
+      source TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS tax_lines_file_idx ON tax_lines(file_id);
+
+    CREATE TRIGGER IF NOT EXISTS tax_lines_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM tax_lines WHERE file_id = OLD.id;
+    END;
+"#;
+
+/// Which files each sheet holds, in voucher order. Sheets used to list them as a JSON array
+/// in `xml_files.file_ids`; those are moved over once and the column is left empty.
+const XML_FILE_ENTRIES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS xml_file_entries (
+      xml_id INTEGER NOT NULL,
+      file_id TEXT NOT NULL,
+      position INTEGER NOT NULL,
+      added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      PRIMARY KEY (xml_id, file_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS xml_file_entries_file_idx ON xml_file_entries(file_id);
+
+    INSERT OR IGNORE INTO xml_file_entries (xml_id, file_id, position)
+      SELECT xml_files.id, entry.value, entry.key
+      FROM xml_files, json_each(xml_files.file_ids) AS entry
+      WHERE json_valid(xml_files.file_ids) AND entry.type = 'text';
+    UPDATE xml_files SET file_ids = '[]' WHERE file_ids <> '[]';
+
+    CREATE TRIGGER IF NOT EXISTS xml_file_entries_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM xml_file_entries WHERE file_id = OLD.id;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS xml_file_entries_delete_with_xml
+    AFTER DELETE ON xml_files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM xml_file_entries WHERE xml_id = OLD.id;
+    END;
+"#;
+
+const TAGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS tags (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS file_tags (
+      file_id TEXT NOT NULL,
+      tag_id INTEGER NOT NULL,
+      created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      PRIMARY KEY (file_id, tag_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS file_tags_tag_idx ON file_tags(tag_id);
+
+    CREATE TRIGGER IF NOT EXISTS file_tags_delete_with_file
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM file_tags WHERE file_id = OLD.id;
+    END;
+"#;
+
+/// Full-text index over file names and the text of their parsed fields, kept in step with
+/// `files` by triggers so every write path updates it. Field locations, the signed QR code
+/// and failure records are left out. Filled from existing files when first created.
+const FILES_FTS_SCHEMA: &str = r#"
+    CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
+      file_id UNINDEXED,
+      file_name,
+      content,
+      tokenize = 'unicode61 remove_diacritics 2'
+    );
+
+    INSERT INTO files_fts (file_id, file_name, content)
+      SELECT id, file_name,
+        CASE WHEN json_valid(parsed_details) AND json_type(parsed_details, '$.error') IS NULL THEN
+          (SELECT group_concat(value, ' ') FROM json_tree(parsed_details)
+           WHERE type IN ('text', 'integer', 'real')
+             AND key IS NOT 'schema_version'
+             AND fullkey NOT LIKE '$."field locations"%'
+             AND fullkey NOT LIKE '$."signed qr code"%')
+        END
+      FROM files
+      WHERE NOT EXISTS (SELECT 1 FROM files_fts);
+
+    CREATE TRIGGER IF NOT EXISTS files_fts_insert
+    AFTER INSERT ON files
+    FOR EACH ROW
+    BEGIN
+      INSERT INTO files_fts (file_id, file_name, content)
+      VALUES (
+        NEW.id,
+        NEW.file_name,
+        CASE WHEN json_valid(NEW.parsed_details) AND json_type(NEW.parsed_details, '$.error') IS NULL THEN
+          (SELECT group_concat(value, ' ') FROM json_tree(NEW.parsed_details)
+           WHERE type IN ('text', 'integer', 'real')
+             AND key IS NOT 'schema_version'
+             AND fullkey NOT LIKE '$."field locations"%'
+             AND fullkey NOT LIKE '$."signed qr code"%')
+        END
+      );
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS files_fts_update
+    AFTER UPDATE OF file_name, parsed_details ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM files_fts WHERE file_id = OLD.id;
+      INSERT INTO files_fts (file_id, file_name, content)
+      VALUES (
+        NEW.id,
+        NEW.file_name,
+        CASE WHEN json_valid(NEW.parsed_details) AND json_type(NEW.parsed_details, '$.error') IS NULL THEN
+          (SELECT group_concat(value, ' ') FROM json_tree(NEW.parsed_details)
+           WHERE type IN ('text', 'integer', 'real')
+             AND key IS NOT 'schema_version'
+             AND fullkey NOT LIKE '$."field locations"%'
+             AND fullkey NOT LIKE '$."signed qr code"%')
+        END
+      );
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS files_fts_delete
+    AFTER DELETE ON files
+    FOR EACH ROW
+    BEGIN
+      DELETE FROM files_fts WHERE file_id = OLD.id;
+    END;
+"#;
+
+const SCHEDULED_EXPORT_SEED: &str = r#"
+    INSERT OR IGNORE INTO schedules (name, job, cron, options) VALUES
+      ('Monthly export', 'export_generation', '0 7 1 * *', '{"period":"previous_month","formats":["xlsx","xml"]}');
+"#;
+
 fn base_data_dir() -> PathBuf {
     let base = dirs::data_dir().unwrap_or_else(|| std::env::current_dir().unwrap());
     base
@@ -98,7 +832,166 @@ pub fn storage_dir() -> std::io::Result<PathBuf> {
     Ok(storage)
 }
 
-pub fn get_connection() -> SqlResult<Connection> {
+pub fn reports_dir() -> std::io::Result<PathBuf> {
+    let dir = ensure_dirs()?;
+    let reports = dir.join("reports");
+    fs::create_dir_all(&reports)?;
+    Ok(reports)
+}
+
+pub fn tessdata_dir() -> std::io::Result<PathBuf> {
+    let dir = ensure_dirs()?;
+    let tessdata = dir.join("tessdata");
+    fs::create_dir_all(&tessdata)?;
+    Ok(tessdata)
+}
+
+pub fn trash_dir() -> std::io::Result<PathBuf> {
+    let dir = ensure_dirs()?;
+    let trash = dir.join("trash");
+    fs::create_dir_all(&trash)?;
+    Ok(trash)
+}
+
+pub fn backups_dir() -> std::io::Result<PathBuf> {
+    let dir = ensure_dirs()?;
+    let backups = dir.join("backups");
+    fs::create_dir_all(&backups)?;
+    Ok(backups)
+}
+
+/// A connection to `app.db` that goes back to the pool when dropped. A transaction left
+/// open is rolled back first.
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    generation: u64,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("pooled connection is present until dropped")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("pooled connection is present until dropped")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        // A connection left in a transaction, or one whose busy timeout cannot be reset
+        // (the parse queue waits longer than other callers), is closed.
+        let reusable = self.conn.take().filter(|conn| {
+            (conn.is_autocommit() || conn.execute_batch("ROLLBACK;").is_ok())
+                && conn.busy_timeout(BUSY_TIMEOUT).is_ok()
+        });
+        let mut pool = pool();
+        pool.checked_out = pool.checked_out.saturating_sub(1);
+        if let Some(conn) = reusable {
+            if pool.generation == self.generation && pool.idle.len() < MAX_IDLE_CONNECTIONS {
+                pool.idle.push(conn);
+            }
+        }
+        POOL_CHANGED.notify_all();
+    }
+}
+
+/// Hands out a connection to `app.db`, reusing an idle one when there is one. The schema is
+/// brought up to date by the first connection opened, not on every call. Waits while the
+/// database is being replaced.
+pub fn get_connection() -> SqlResult<PooledConnection> {
+    let generation = {
+        let mut pool = POOL_CHANGED
+            .wait_while(pool(), |pool| pool.exclusive)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        pool.checked_out += 1;
+        if let Some(conn) = pool.idle.pop() {
+            return Ok(PooledConnection {
+                conn: Some(conn),
+                generation: pool.generation,
+            });
+        }
+        pool.generation
+    };
+    // From here on, dropping the wrapper hands the slot back even if opening fails.
+    let mut pooled = PooledConnection {
+        conn: None,
+        generation,
+    };
+
+    let conn = open_connection()?;
+    {
+        let mut ready = SCHEMA_READY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *ready != Some(generation) {
+            init_schema(&conn)?;
+            *ready = Some(generation);
+        }
+    }
+    apply_connection_settings(&conn)?;
+    pooled.conn = Some(conn);
+    Ok(pooled)
+}
+
+/// Runs `replace` with the only connection open to `app.db`, for work that swaps the file
+/// itself. New connections wait until it returns, and connections in use are waited for;
+/// if they are not handed back within 30 seconds, nothing is run. Pooled connections are
+/// closed, so the next ones open whatever file `replace` leaves behind.
+pub fn with_exclusive_connection<T>(
+    replace: impl FnOnce(Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    {
+        let mut pool = pool();
+        if pool.exclusive {
+            return Err("The database is already being replaced.".to_string());
+        }
+        pool.exclusive = true;
+        let (mut pool, wait) = POOL_CHANGED
+            .wait_timeout_while(pool, EXCLUSIVE_WAIT, |pool| pool.checked_out > 0)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if wait.timed_out() {
+            pool.exclusive = false;
+            POOL_CHANGED.notify_all();
+            return Err(
+                "The database is still in use by background work. Pause processing and try again."
+                    .to_string(),
+            );
+        }
+        pool.generation += 1;
+        pool.idle.clear();
+    }
+
+    let result = open_connection()
+        .map_err(|error| error.to_string())
+        .and_then(replace);
+
+    let mut pool = pool();
+    pool.exclusive = false;
+    POOL_CHANGED.notify_all();
+    result
+}
+
+/// Closes the idle connections and has those checked out closed when handed back. Needed
+/// whenever `app.db` is replaced or per-connection settings change; the next connection
+/// opened also brings the schema up to date again.
+pub fn reset_connections() {
+    let mut pool = pool();
+    pool.generation += 1;
+    pool.idle.clear();
+}
+
+fn pool() -> MutexGuard<'static, ConnectionPool> {
+    POOL.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Opens `app.db`, unlocking it when it is encrypted. Connections use write-ahead logging,
+/// so imports and parsing writing in the background do not block readers. WAL mode is kept
+/// in the file itself, so the plugin-sql pool, whose connections wait 5 seconds on a busy
+/// database by default, uses it too.
+fn open_connection() -> SqlResult<Connection> {
     let path = db_path().map_err(|e| {
         SqlError::SqliteFailure(
             rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
@@ -106,8 +999,14 @@ pub fn get_connection() -> SqlResult<Connection> {
         )
     })?;
 
-    let conn = Connection::open(path)?;
-    init_schema(&conn)?;
+    let conn = Connection::open(&path)?;
+    DatabaseEncryption::unlock(&conn, &path).map_err(|message| {
+        SqlError::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_NOTADB),
+            Some(message),
+        )
+    })?;
+    apply_journal_settings(&conn)?;
     Ok(conn)
 }
 
@@ -133,30 +1032,228 @@ pub fn reset_gemini_model_usage_if_new_day() -> Result<(), String> {
 
 fn init_schema(conn: &Connection) -> SqlResult<()> {
     conn.execute_batch(CORE_SCHEMA)?;
-    ensure_processed_at_column(conn)?;
+    conn.execute_batch(REVIEW_FLAGS_SCHEMA)?;
+    conn.execute_batch(DOUBLE_PAYMENT_SCHEMA)?;
+    conn.execute_batch(SELLERS_SCHEMA)?;
+    conn.execute_batch(BUYERS_SCHEMA)?;
+    conn.execute_batch(LINE_ITEMS_SCHEMA)?;
+    conn.execute_batch(PAYMENTS_SCHEMA)?;
+    conn.execute_batch(SETTINGS_SCHEMA)?;
+    conn.execute_batch(AUDIT_LOG_SCHEMA)?;
+    conn.execute_batch(BANK_RECONCILIATION_SCHEMA)?;
+    conn.execute_batch(SCHEDULER_SCHEMA)?;
+    conn.execute_batch(TELEMETRY_SCHEMA)?;
+    conn.execute_batch(PROMPT_TEMPLATES_SCHEMA)?;
+    conn.execute_batch(PROCESSING_USAGE_SCHEMA)?;
+    conn.execute_batch(REPROCESS_CANDIDATES_SCHEMA)?;
+    conn.execute_batch(PARSED_DETAILS_HISTORY_SCHEMA)?;
+    conn.execute_batch(SCHEDULED_EXPORT_SEED)?;
+    conn.execute_batch(EXCHANGE_RATES_SCHEMA)?;
+    conn.execute_batch(TRASH_SCHEMA)?;
+    conn.execute_batch(EXPORTS_SCHEMA)?;
+    conn.execute_batch(CLOUD_DRIVE_SCHEMA)?;
+    conn.execute_batch(EXPORT_PROFILES_SCHEMA)?;
+    conn.execute_batch(WATCHED_FOLDERS_SCHEMA)?;
+    conn.execute_batch(IMPORT_BATCHES_SCHEMA)?;
+    conn.execute_batch(LLM_REQUESTS_SCHEMA)?;
+    conn.execute_batch(PARSE_JOBS_SCHEMA)?;
+    conn.execute_batch(INVOICES_SCHEMA)?;
+    conn.execute_batch(EXTRACTION_TEMPLATES_SCHEMA)?;
+    conn.execute_batch(FIELD_CORRECTIONS_SCHEMA)?;
+    conn.execute_batch(PARSE_METRICS_SCHEMA)?;
+    conn.execute_batch(INVOICE_EMBEDDINGS_SCHEMA)?;
+    conn.execute_batch(TAX_LINES_SCHEMA)?;
+    conn.execute_batch(XML_FILE_ENTRIES_SCHEMA)?;
+    conn.execute_batch(FILES_FTS_SCHEMA)?;
+    conn.execute_batch(TAGS_SCHEMA)?;
+    rename_column(conn, "files", "hash_sha256", "content_hash")?;
+    ensure_column(conn, "files", "hash_algo", "TEXT NOT NULL DEFAULT 'blake3'")?;
+    ensure_column(conn, "files", "processed_at", "TEXT")?;
+    ensure_column(conn, "files", "tax_ids_valid", "INTEGER")?;
+    ensure_column(conn, "files", "invoice_date", "TEXT")?;
+    ensure_column(conn, "files", "invoice_date_raw", "TEXT")?;
+    ensure_column(conn, "files", "arithmetic_valid", "INTEGER")?;
+    ensure_column(conn, "files", "validation_errors", "TEXT")?;
+    ensure_column(conn, "files", "due_date", "TEXT")?;
+    ensure_column(conn, "files", "payment_terms", "TEXT")?;
+    ensure_column(conn, "files", "duplicate_invoice_key", "TEXT")?;
+    ensure_column(conn, "files", "duplicate_key_checked_at", "TEXT")?;
+    ensure_column(conn, "files", "invoice_synced_at", "TEXT")?;
+    ensure_column(conn, "files", "seller_id", "INTEGER")?;
+    ensure_column(conn, "files", "buyer_id", "INTEGER")?;
+    ensure_column(conn, "files", "ocr_languages", "TEXT")?;
+    ensure_column(conn, "files", "version_key", "TEXT")?;
+    ensure_column(conn, "files", "supersedes", "TEXT")?;
+    ensure_column(conn, "files", "import_batch_id", "INTEGER")?;
+    ensure_column(conn, "files", "parent_file_id", "TEXT")?;
+    ensure_column(conn, "files", "page_range", "TEXT")?;
+    ensure_column(conn, "files", "source", "TEXT")?;
+    ensure_column(conn, "files", "qr_codes", "TEXT")?;
+    ensure_column(conn, "files", "document_language", "TEXT")?;
+    ensure_column(conn, "files", "extracted_by", "TEXT")?;
+    ensure_column(conn, "files", "doc_type", "TEXT")?;
+    ensure_column(conn, "files", "extraction_confidence", "REAL")?;
+    ensure_column(conn, "files", "reviewed_at", "TEXT")?;
+    ensure_column(conn, "cloud_drive_links", "region", "TEXT")?;
+    ensure_column(conn, "cloud_drive_links", "endpoint", "TEXT")?;
+    rename_column(conn, "parse_jobs", "error", "last_error")?;
+    ensure_column(conn, "parse_jobs", "attempts", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "parse_jobs", "next_retry_at", "TEXT")?;
+    ensure_column(conn, "invoice_line_items", "invoice_id", "INTEGER REFERENCES invoices(id) ON DELETE CASCADE")?;
+    ensure_column(conn, "invoice_line_items", "igst", "REAL")?;
+    ensure_column(conn, "invoice_line_items", "igst_rate", "REAL")?;
+    ensure_column(conn, "invoices", "igst_total", "REAL NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "invoices", "cess_total", "REAL NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "extraction_templates", "logo_hash", "TEXT")?;
+    ensure_column(conn, "deleted_files", "snapshot", "TEXT")?;
+    // Identical copies may be kept when duplicate detection is turned off.
+    drop_unique_constraint(conn, "files", "content_hash")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS files_duplicate_invoice_key_idx ON files(duplicate_invoice_key);
+         CREATE INDEX IF NOT EXISTS files_name_size_idx ON files(file_name, size_bytes);
+         CREATE INDEX IF NOT EXISTS files_seller_idx ON files(seller_id);
+         CREATE INDEX IF NOT EXISTS files_buyer_idx ON files(buyer_id);
+         CREATE INDEX IF NOT EXISTS files_due_date_idx ON files(due_date);
+         CREATE INDEX IF NOT EXISTS files_version_key_idx ON files(version_key);
+         CREATE INDEX IF NOT EXISTS files_supersedes_idx ON files(supersedes);
+         CREATE INDEX IF NOT EXISTS files_import_batch_idx ON files(import_batch_id);
+         CREATE INDEX IF NOT EXISTS files_parent_idx ON files(parent_file_id);
+         CREATE INDEX IF NOT EXISTS invoice_line_items_invoice_idx ON invoice_line_items(invoice_id);
+         CREATE TRIGGER IF NOT EXISTS files_duplicate_key_stale
+         AFTER UPDATE OF parsed_details ON files
+         FOR EACH ROW
+         WHEN NEW.parsed_details IS NOT OLD.parsed_details
+         BEGIN
+           UPDATE files SET duplicate_key_checked_at = NULL WHERE id = OLD.id;
+         END;
+         CREATE TRIGGER IF NOT EXISTS files_invoice_stale
+         AFTER UPDATE OF parsed_details ON files
+         FOR EACH ROW
+         WHEN NEW.parsed_details IS NOT OLD.parsed_details
+         BEGIN
+           UPDATE files SET invoice_synced_at = NULL WHERE id = OLD.id;
+         END;",
+    )?;
     Ok(())
 }
 
-fn ensure_processed_at_column(conn: &Connection) -> SqlResult<()> {
-    let mut stmt = conn.prepare("PRAGMA table_info(files)")?;
+/// Runs before the schema is brought up to date, which writes and so may have to wait for
+/// another connection. `synchronous = NORMAL` is durable in WAL mode except against power
+/// loss, which can only lose the last commits, never corrupt the database.
+fn apply_journal_settings(conn: &Connection) -> SqlResult<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // Reports the mode now in effect, which only an in-memory database would refuse.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")
+}
+
+/// Applies per-connection pragmas that depend on backend settings.
+fn apply_connection_settings(conn: &Connection) -> SqlResult<()> {
+    let secure_delete: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![SECURE_DELETE_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if secure_delete.as_deref() == Some("1") {
+        conn.execute_batch("PRAGMA secure_delete = ON;")?;
+    }
+    Ok(())
+}
+
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> SqlResult<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
     let mut has_column = false;
 
     let mut rows = stmt.query([])?;
     while let Some(row) = rows.next()? {
         let name: String = row.get(1)?;
-        if name == "processed_at" {
+        if name == column {
             has_column = true;
             break;
         }
     }
 
     if !has_column {
-        conn.execute("ALTER TABLE files ADD COLUMN processed_at TEXT", [])?;
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
     }
 
     Ok(())
 }
 
+/// Renames a column left over from an older schema. The initial migration still creates
+/// the old name, so this runs on every start and does nothing once the rename is done.
+fn rename_column(conn: &Connection, table: &str, from: &str, to: &str) -> SqlResult<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<SqlResult<Vec<_>>>()?;
+
+    if columns.iter().any(|name| name == from) && !columns.iter().any(|name| name == to) {
+        conn.execute(
+            &format!("ALTER TABLE {} RENAME COLUMN {} TO {}", table, from, to),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// SQLite cannot drop a column constraint, so the table is rebuilt from its own stored
+/// definition without `UNIQUE` on `column`, and its indexes and triggers are recreated.
+/// Nothing references the rebuilt tables by foreign key. Does nothing once the constraint is gone.
+fn drop_unique_constraint(conn: &Connection, table: &str, column: &str) -> SqlResult<()> {
+    let table_sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get(0),
+    )?;
+    let Some(start) = table_sql.find(column) else {
+        return Ok(());
+    };
+    let end = table_sql[start..]
+        .find(',')
+        .map(|offset| start + offset)
+        .unwrap_or(table_sql.len());
+    let Some(unique) = table_sql[start..end].find(" UNIQUE").map(|offset| start + offset) else {
+        return Ok(());
+    };
+    let prefix = format!("CREATE TABLE {}", table);
+    if !table_sql.starts_with(&prefix) {
+        return Ok(());
+    }
+
+    let rebuild = format!("{}_rebuild", table);
+    let rebuilt_sql = format!(
+        "CREATE TABLE {}{}{}",
+        rebuild,
+        &table_sql[prefix.len()..unique],
+        &table_sql[unique + " UNIQUE".len()..]
+    );
+    let mut stmt = conn.prepare(
+        "SELECT sql FROM sqlite_master WHERE tbl_name = ?1 AND type IN ('index', 'trigger') AND sql IS NOT NULL",
+    )?;
+    let dependents = stmt
+        .query_map(params![table], |row| row.get::<_, String>(0))?
+        .collect::<SqlResult<Vec<_>>>()?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&rebuilt_sql)?;
+    tx.execute_batch(&format!(
+        "INSERT INTO {rebuild} SELECT * FROM {table};
+         DROP TABLE {table};
+         ALTER TABLE {rebuild} RENAME TO {table};"
+    ))?;
+    for sql in dependents {
+        tx.execute_batch(&sql)?;
+    }
+    tx.commit()
+}
+
 pub fn schema_migrations() -> Vec<Migration> {
     vec![
         Migration {
@@ -194,5 +1291,209 @@ pub fn schema_migrations() -> Vec<Migration> {
             "#.into(),
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 4,
+            description: "add review flags".into(),
+            sql: REVIEW_FLAGS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "add double payment resolutions".into(),
+            sql: DOUBLE_PAYMENT_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "add seller master data".into(),
+            sql: SELLERS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "add buyer entities".into(),
+            sql: BUYERS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "add normalized invoice line items".into(),
+            sql: LINE_ITEMS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "add payment tracking".into(),
+            sql: PAYMENTS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "add backend settings".into(),
+            sql: SETTINGS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "add hash-chained audit log".into(),
+            sql: AUDIT_LOG_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "add bank statement reconciliation".into(),
+            sql: BANK_RECONCILIATION_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "add scheduler".into(),
+            sql: SCHEDULER_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "add telemetry counters".into(),
+            sql: TELEMETRY_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "add prompt templates".into(),
+            sql: PROMPT_TEMPLATES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "add processing usage".into(),
+            sql: PROCESSING_USAGE_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "add reprocess candidates".into(),
+            sql: REPROCESS_CANDIDATES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 18,
+            description: "add parsed details history".into(),
+            sql: PARSED_DETAILS_HISTORY_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 19,
+            description: "seed monthly export schedule".into(),
+            sql: SCHEDULED_EXPORT_SEED.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 20,
+            description: "add exchange rates".into(),
+            sql: EXCHANGE_RATES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 21,
+            description: "add trash".into(),
+            sql: TRASH_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 22,
+            description: "add export manifests".into(),
+            sql: EXPORTS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 23,
+            description: "add cloud drive links".into(),
+            sql: CLOUD_DRIVE_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 24,
+            description: "add export profiles".into(),
+            sql: EXPORT_PROFILES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 25,
+            description: "add watched folders".into(),
+            sql: WATCHED_FOLDERS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 26,
+            description: "add import batches".into(),
+            sql: IMPORT_BATCHES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 27,
+            description: "add llm requests".into(),
+            sql: LLM_REQUESTS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 28,
+            description: "add parse jobs".into(),
+            sql: PARSE_JOBS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 29,
+            description: "add invoices".into(),
+            sql: INVOICES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 30,
+            description: "add extraction templates".into(),
+            sql: EXTRACTION_TEMPLATES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 31,
+            description: "add field corrections".into(),
+            sql: FIELD_CORRECTIONS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 32,
+            description: "add parse metrics".into(),
+            sql: PARSE_METRICS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 33,
+            description: "add invoice embeddings".into(),
+            sql: INVOICE_EMBEDDINGS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 34,
+            description: "add tax lines".into(),
+            sql: TAX_LINES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 35,
+            description: "move xml file ids into a join table".into(),
+            sql: XML_FILE_ENTRIES_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 36,
+            description: "add full-text search index".into(),
+            sql: FILES_FTS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 37,
+            description: "add file tags".into(),
+            sql: TAGS_SCHEMA.into(),
+            kind: MigrationKind::Up,
+        },
     ]
 }
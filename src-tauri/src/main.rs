@@ -6,37 +6,174 @@ mod filesystem;
 mod services;
 
 use commands::{
-    append_log_entry, append_xml_file, clear_processed_files, copy_file_to_path, create_xml_for_files,
-    delete_files, generate_xml_file, get_storage_stats, import_data, import_file, list_files,
-    list_files_paginated, list_xml_files, open_file_paths, update_file_parsed_details, update_file_status,
-    update_files_status,
+    activate_prompt_template, add_buyer_alias, add_seller_alias, add_tags, add_watched_folder,
+    aggregate_invoices, append_log_entry, append_xml_file, apply_extraction_template, approve_files,
+    backup_database, begin_import_batch, build_semantic_index, cancel_import, cancel_parse,
+    cancel_parse_jobs, check_local_llm, clear_app_lock_pin, clear_llm_api_key,
+    clear_processed_files, confirm_match, copy_file_to_path, correct_parsed_field,
+    create_export_profile, create_extraction_template, create_xml_for_buyer, create_xml_for_files,
+    decrypt_database, delete_csv_profile, delete_exchange_rate, delete_export_profile,
+    delete_extraction_template, delete_files, delete_xml_file, detect_invoice_splits,
+    discard_reprocess_conflict, download_ocr_language, encrypt_database, enqueue_parse_jobs,
+    enqueue_unprocessed_files, export_bundle, export_gst_return, extract_line_items, extract_region,
+    finish_import_batch, generate_invoice_qr, generate_pdf_report, generate_vendor_statement,
+    generate_xml_file, get_active_prompts, get_active_role, get_app_lock_status,
+    get_auto_approve_threshold, get_autostart_enabled, get_background_mode, get_base_currency,
+    get_batch_report, get_converted_sheet_totals, get_correction_stats, get_database_encryption,
+    get_default_ocr_languages, get_duplicate_policy, get_export_manifest, get_export_profile,
+    get_field_provenance, get_file_ocr_languages, get_gemini_model_usage, get_hash_algorithm,
+    get_integrity_scan_on_startup, get_invoice, get_last_integrity_report, get_llm_temperature,
+    get_locale, get_log_statistics, get_model_pricing, get_notifications_enabled,
+    get_parse_cost_summary, get_parse_queue_status, get_parsed_history, get_parsing_metrics,
+    get_processing_paused, get_reprocess_conflict, get_secure_delete, get_semantic_index_status,
+    get_storage_stats, get_telemetry_enabled, get_template_match, get_trash_retention_days,
+    get_usage_summary, get_workspace_lock_status, global_search, import_archive,
+    import_archive_data, import_bank_statement, import_cloud_drive_files, import_data,
+    import_directory, import_file, import_from_clipboard, import_legacy_data, import_paths,
+    link_cloud_drive, link_s3_bucket, list_audit_log, list_buyers, list_cloud_drive_files,
+    list_cloud_drive_links, list_csv_profiles, list_document_languages, list_exchange_rates,
+    list_export_profiles, list_exports, list_extraction_templates, list_field_corrections,
+    list_file_versions, list_files, list_files_needing_review, list_files_paginated,
+    list_files_with_validation_errors, list_import_batches, list_invoices, list_llm_providers,
+    list_llm_requests, list_ocr_languages, list_overdue_payments, list_parse_jobs,
+    list_potential_double_payments, list_prompt_templates, list_recently_deleted,
+    list_reprocess_conflicts, list_review_queue, list_schedule_runs, list_schedules, list_sellers,
+    list_tags, list_unmatched, list_upcoming_payments, list_watched_folders, list_xml_files,
+    lock_workspace, mark_canonical_invoice, mark_paid, merge_buyers, merge_sellers,
+    normalize_invoice_date, notify_batch_finished, open_file_paths, parse_file_with_llm,
+    parse_files, preview_directory_import, preview_telemetry, process_folder, purge_selected,
+    purge_trash, record_processing_usage, relink_file, remove_buyer_alias, remove_ocr_language,
+    remove_seller_alias, remove_tags, remove_watched_folder, rename_buyer, rename_files_by_template,
+    rename_seller, rename_xml_file, repair_integrity_issues, resolve_reprocess_conflict,
+    resolve_review_flag, restore_database, restore_files, retry_failed_files,
+    reveal_original_source, rollback_parsed_details, run_export, run_integrity_scan,
+    save_csv_profile, save_extraction_result, save_gemini_model_usage, save_parsed_invoice,
+    save_prompt_template, save_xml_export, scan_file_qr_codes, search_files, search_line_items,
+    semantic_search, set_active_role, set_app_lock_pin, set_auto_approve_threshold,
+    set_autostart_enabled, set_background_mode, set_base_currency, set_converted_sheet_totals,
+    set_default_ocr_languages, set_document_type, set_duplicate_policy, set_exchange_rate,
+    set_file_ocr_languages, set_hash_algorithm, set_integrity_scan_on_startup, set_llm_api_key,
+    set_llm_provider, set_llm_temperature, set_local_llm_endpoint, set_locale, set_model_pricing,
+    set_notifications_enabled, set_parse_retry_limit, set_parse_workers, set_processing_paused,
+    set_secure_delete, set_semantic_search_provider, set_telemetry_enabled,
+    set_trash_retention_days, set_watched_folder_enabled, split_document, sync_buyers,
+    sync_cloud_drive, sync_gemini_model_usage, sync_invoices, sync_line_items, sync_sellers,
+    test_extraction_template, test_prompt_template, unlink_cloud_drive, unlock_workspace,
+    unmark_paid, update_export_profile, update_extraction_template, update_file_parsed_details,
+    update_file_status, update_files_status, update_schedule, validate_file,
+    validate_file_arithmetic, validate_file_tax_ids, validate_tax_ids, verify_app_lock_pin,
+    verify_audit_chain, verify_export, verify_file_hashes,
 };
 use filesystem::{create_directory, list_directory, read_binary_file, read_file, save_file};
-use db::{reset_gemini_model_usage_if_new_day, schema_migrations};
+use db::{get_connection, reset_gemini_model_usage_if_new_day, schema_migrations};
+use services::autostart::{Autostart, MINIMIZED_ARG};
+use services::double_payment::DoublePaymentDetector;
+use services::integrity_scan::IntegrityScan;
+use services::invoices::InvoiceStore;
+use services::offline_queue::OfflineQueue;
+use services::scheduler::Scheduler;
+use services::telemetry::{Telemetry, CRASHES};
+use services::trash::Trash;
+use services::tray::Tray;
+use services::watcher::FolderWatcher;
+use services::parse_queue::ParseQueue;
+use services::log_writer::LogWriter;
+use services::launch_import::{focus_main_window, LaunchImport};
+use tauri_plugin_autostart::{init as AutostartPlugin, MacosLauncher};
+use tauri_plugin_deep_link::{init as DeepLinkPlugin, DeepLinkExt};
 use tauri_plugin_dialog::init as DialogPlugin;
+use tauri_plugin_notification::init as NotificationPlugin;
+use tauri_plugin_single_instance::init as SingleInstancePlugin;
 use tauri_plugin_sql::Builder as SqlPluginBuilder;
 use tauri_plugin_store::Builder as StorePluginBuilder;
 use std::time::Duration;
 use tokio::time::sleep;
 
 fn main() {
+    let default_panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        Telemetry::track(CRASHES);
+        LogWriter::flush();
+        default_panic_hook(info);
+    }));
+
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before doing any work.
+        .plugin(SingleInstancePlugin(|app, argv, _cwd| {
+            focus_main_window(app);
+            LaunchImport::from_args(app, argv.get(1..).unwrap_or_default());
+        }))
+        .plugin(DeepLinkPlugin())
+        .plugin(AutostartPlugin(MacosLauncher::LaunchAgent, Some(vec![MINIMIZED_ARG])))
         .plugin(
             SqlPluginBuilder::default()
                 .add_migrations("sqlite:app.db", schema_migrations())
                 .build(),
         )
         .plugin(DialogPlugin())
+        .plugin(NotificationPlugin())
         .plugin(StorePluginBuilder::default().build())
-        .setup(|_| {
+        .setup(|app| {
+            Tray::install(app)?;
+            Autostart::apply_launch_state(app.handle());
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            let _ = app.deep_link().register_all();
+            let launch_handle = app.handle().clone();
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            LaunchImport::from_args(&launch_handle, &args);
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                LaunchImport::from_urls(&launch_handle, &urls);
+            }
+            let _ = FolderWatcher::start(app.handle());
+            let _ = ParseQueue::start(app.handle());
+            let url_handle = app.handle().clone();
+            app.deep_link()
+                .on_open_url(move |event| LaunchImport::from_urls(&url_handle, &event.urls()));
+            let scan_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Ok(conn) = get_connection() {
+                    let _ = IntegrityScan::on_startup(&scan_handle, &conn);
+                    let _ = InvoiceStore::backfill(&conn);
+                }
+            });
+            let handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 loop {
                     let _ = reset_gemini_model_usage_if_new_day();
+                    if let Ok(conn) = get_connection() {
+                        let _ = Trash::purge_expired(&conn);
+                    }
+                    sleep(Duration::from_secs(60 * 60)).await;
+                }
+            });
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let _ = DoublePaymentDetector::scan();
+                    sleep(Duration::from_secs(10 * 60)).await;
+                }
+            });
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let _ = Scheduler::run_due(&handle);
+                    sleep(Duration::from_secs(60)).await;
+                }
+            });
+            let resume_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let _ = OfflineQueue::resume_if_online(&resume_handle);
+                    sleep(Duration::from_secs(30)).await;
+                }
+            });
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let _ = Telemetry::send_if_due();
                     sleep(Duration::from_secs(60 * 60)).await;
                 }
             });
             Ok(())
         })
+        .on_window_event(Tray::on_window_event)
         .invoke_handler(tauri::generate_handler![
             list_directory,
             read_binary_file,
@@ -59,8 +196,241 @@ fn main() {
             delete_files,
             open_file_paths,
             copy_file_to_path,
-            clear_processed_files
+            clear_processed_files,
+            validate_file_tax_ids,
+            list_review_queue,
+            resolve_review_flag,
+            normalize_invoice_date,
+            list_potential_double_payments,
+            mark_canonical_invoice,
+            list_sellers,
+            merge_sellers,
+            add_seller_alias,
+            sync_sellers,
+            list_buyers,
+            merge_buyers,
+            add_buyer_alias,
+            sync_buyers,
+            create_xml_for_buyer,
+            search_line_items,
+            sync_line_items,
+            validate_file_arithmetic,
+            mark_paid,
+            unmark_paid,
+            list_overdue_payments,
+            list_upcoming_payments,
+            export_gst_return,
+            generate_pdf_report,
+            aggregate_invoices,
+            get_app_lock_status,
+            set_app_lock_pin,
+            clear_app_lock_pin,
+            verify_app_lock_pin,
+            get_secure_delete,
+            set_secure_delete,
+            list_audit_log,
+            verify_audit_chain,
+            get_active_role,
+            set_active_role,
+            notify_batch_finished,
+            get_notifications_enabled,
+            set_notifications_enabled,
+            global_search,
+            generate_invoice_qr,
+            import_bank_statement,
+            list_unmatched,
+            confirm_match,
+            generate_vendor_statement,
+            rename_files_by_template,
+            detect_invoice_splits,
+            list_schedules,
+            update_schedule,
+            list_schedule_runs,
+            get_locale,
+            set_locale,
+            get_telemetry_enabled,
+            set_telemetry_enabled,
+            preview_telemetry,
+            import_legacy_data,
+            list_csv_profiles,
+            save_csv_profile,
+            delete_csv_profile,
+            list_ocr_languages,
+            download_ocr_language,
+            remove_ocr_language,
+            get_default_ocr_languages,
+            set_default_ocr_languages,
+            get_file_ocr_languages,
+            set_file_ocr_languages,
+            list_prompt_templates,
+            get_active_prompts,
+            save_prompt_template,
+            activate_prompt_template,
+            test_prompt_template,
+            record_processing_usage,
+            get_usage_summary,
+            save_extraction_result,
+            list_reprocess_conflicts,
+            get_reprocess_conflict,
+            resolve_reprocess_conflict,
+            discard_reprocess_conflict,
+            get_parsed_history,
+            rollback_parsed_details,
+            get_field_provenance,
+            export_bundle,
+            get_hash_algorithm,
+            set_hash_algorithm,
+            verify_file_hashes,
+            run_integrity_scan,
+            get_last_integrity_report,
+            repair_integrity_issues,
+            get_integrity_scan_on_startup,
+            set_integrity_scan_on_startup,
+            get_background_mode,
+            set_background_mode,
+            get_processing_paused,
+            set_processing_paused,
+            get_autostart_enabled,
+            set_autostart_enabled,
+            list_exchange_rates,
+            set_exchange_rate,
+            delete_exchange_rate,
+            get_base_currency,
+            set_base_currency,
+            get_converted_sheet_totals,
+            set_converted_sheet_totals,
+            get_duplicate_policy,
+            set_duplicate_policy,
+            process_folder,
+            list_recently_deleted,
+            purge_selected,
+            get_trash_retention_days,
+            set_trash_retention_days,
+            list_exports,
+            get_export_manifest,
+            verify_export,
+            save_xml_export,
+            link_cloud_drive,
+            list_cloud_drive_links,
+            unlink_cloud_drive,
+            list_cloud_drive_files,
+            sync_cloud_drive,
+            get_workspace_lock_status,
+            lock_workspace,
+            unlock_workspace,
+            get_log_statistics,
+            list_export_profiles,
+            get_export_profile,
+            create_export_profile,
+            update_export_profile,
+            delete_export_profile,
+            run_export,
+            list_watched_folders,
+            add_watched_folder,
+            remove_watched_folder,
+            set_watched_folder_enabled,
+            import_archive,
+            import_archive_data,
+            link_s3_bucket,
+            import_cloud_drive_files,
+            import_paths,
+            cancel_import,
+            list_file_versions,
+            begin_import_batch,
+            finish_import_batch,
+            list_import_batches,
+            get_batch_report,
+            relink_file,
+            split_document,
+            import_from_clipboard,
+            preview_directory_import,
+            import_directory,
+            reveal_original_source,
+            parse_file_with_llm,
+            list_llm_providers,
+            set_llm_provider,
+            set_llm_api_key,
+            clear_llm_api_key,
+            list_llm_requests,
+            check_local_llm,
+            set_local_llm_endpoint,
+            enqueue_parse_jobs,
+            enqueue_unprocessed_files,
+            cancel_parse_jobs,
+            get_parse_queue_status,
+            list_parse_jobs,
+            set_parse_workers,
+            retry_failed_files,
+            set_parse_retry_limit,
+            save_parsed_invoice,
+            get_invoice,
+            list_invoices,
+            sync_invoices,
+            list_extraction_templates,
+            create_extraction_template,
+            update_extraction_template,
+            delete_extraction_template,
+            test_extraction_template,
+            apply_extraction_template,
+            get_template_match,
+            validate_file,
+            list_files_with_validation_errors,
+            extract_line_items,
+            validate_tax_ids,
+            scan_file_qr_codes,
+            list_document_languages,
+            correct_parsed_field,
+            list_field_corrections,
+            get_correction_stats,
+            get_parse_cost_summary,
+            get_model_pricing,
+            set_model_pricing,
+            get_llm_temperature,
+            set_llm_temperature,
+            get_parsing_metrics,
+            set_document_type,
+            parse_files,
+            cancel_parse,
+            semantic_search,
+            build_semantic_index,
+            get_semantic_index_status,
+            set_semantic_search_provider,
+            list_files_needing_review,
+            approve_files,
+            get_auto_approve_threshold,
+            set_auto_approve_threshold,
+            extract_region,
+            rename_xml_file,
+            delete_xml_file,
+            search_files,
+            add_tags,
+            remove_tags,
+            list_tags,
+            remove_seller_alias,
+            rename_seller,
+            remove_buyer_alias,
+            rename_buyer,
+            restore_files,
+            purge_trash,
+            backup_database,
+            restore_database,
+            get_database_encryption,
+            encrypt_database,
+            decrypt_database,
+            get_gemini_model_usage,
+            save_gemini_model_usage,
+            sync_gemini_model_usage
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running Invox AI desktop shell");
+        .build(tauri::generate_context!())
+        .expect("error while building Invox AI desktop shell")
+        .run(|_app, _event| {
+            if let tauri::RunEvent::Exit = _event {
+                LogWriter::flush();
+            }
+            // macOS delivers "Open with" and double-clicked documents as an event, not as arguments.
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                LaunchImport::from_urls(_app, &urls);
+            }
+        });
 }
@@ -0,0 +1,113 @@
+use crate::services::llm_provider::{
+    agent, parse_output, post_json, ExtractionRequest, LlmError, LlmProvider, LlmProviderKind, LlmResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+
+const API_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+const MAX_OUTPUT_TOKENS: u32 = 8192;
+
+const IMAGE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp", "image/gif"];
+
+/// Messages API client. The API has no JSON mode, so the response schema is spelled out in
+/// the prompt and the reply is read as JSON.
+pub struct AnthropicClient {
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicClient {
+    pub fn new(api_key: &str, model: &str) -> Result<Self, String> {
+        if api_key.trim().is_empty() {
+            return Err("An Anthropic API key is required.".to_string());
+        }
+        if model.trim().is_empty() {
+            return Err("An Anthropic model is required.".to_string());
+        }
+        Ok(AnthropicClient {
+            api_key: api_key.trim().to_string(),
+            model: model.trim().to_string(),
+        })
+    }
+}
+
+impl LlmProvider for AnthropicClient {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::Anthropic
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn supports(&self, mime_type: &str) -> bool {
+        mime_type == "application/pdf" || IMAGE_MIME_TYPES.contains(&mime_type)
+    }
+
+    fn extract(&self, request: &ExtractionRequest) -> Result<LlmResponse, LlmError> {
+        let source = json!({
+            "type": "base64",
+            "media_type": request.mime_type,
+            "data": STANDARD.encode(request.document),
+        });
+        let document = if request.mime_type == "application/pdf" {
+            json!({ "type": "document", "source": source })
+        } else {
+            json!({ "type": "image", "source": source })
+        };
+        let prompt = match request.response_schema {
+            Some(schema) => format!(
+                "{}\n\nRespond with only a JSON object matching this JSON schema:\n{}",
+                request.prompt, schema
+            ),
+            None => format!("{}\n\nRespond with only a JSON object.", request.prompt),
+        };
+        let body = json!({
+            "model": self.model,
+            "max_tokens": MAX_OUTPUT_TOKENS,
+            "temperature": request.temperature,
+            "system": request.system_instruction,
+            "messages": [{
+                "role": "user",
+                "content": [document, { "type": "text", "text": prompt }],
+            }],
+        });
+
+        let (response, raw) = post_json(
+            agent()
+                .post(API_URL)
+                .set("x-api-key", &self.api_key)
+                .set("anthropic-version", API_VERSION),
+            &body,
+        )
+        .map_err(|error| LlmError {
+            message: format!("Anthropic request failed: {}", error.message),
+            ..error
+        })?;
+
+        let text: String = response
+            .get("content")
+            .and_then(Value::as_array)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|block| block.get("text")?.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let usage = |key: &str| {
+            response
+                .pointer(&format!("/usage/{}", key))
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+        };
+
+        Ok(LlmResponse {
+            output: parse_output(&text),
+            prompt_tokens: usage("input_tokens"),
+            output_tokens: usage("output_tokens"),
+            raw,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use crate::services::{i18n::I18n, settings::Settings};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::{Duration, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+
+const PIN_HASH_KEY: &str = "app_lock.pin_hash";
+const FAILED_ATTEMPTS_KEY: &str = "app_lock.failed_attempts";
+const LOCKED_UNTIL_KEY: &str = "app_lock.locked_until";
+
+/// Failed attempts allowed before the lock engages.
+const MAX_FAILED_ATTEMPTS: i64 = 5;
+const LOCKOUT_MINUTES: i64 = 5;
+const MIN_PIN_LENGTH: usize = 4;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub failed_attempts: i64,
+    /// Unix timestamp (ms) until which verification is refused, if locked out.
+    pub locked_until: Option<i64>,
+}
+
+/// PIN gate for destructive operations on shared machines. When no PIN is
+/// configured every check passes, so the feature is opt-in.
+pub struct AppLock;
+
+impl AppLock {
+    pub fn status(conn: &Connection) -> Result<AppLockStatus, String> {
+        let now = Utc::now().timestamp_millis();
+        Ok(AppLockStatus {
+            enabled: Settings::get(conn, PIN_HASH_KEY)?.is_some(),
+            failed_attempts: Settings::get_i64(conn, FAILED_ATTEMPTS_KEY)?.unwrap_or(0),
+            locked_until: Settings::get_i64(conn, LOCKED_UNTIL_KEY)?.filter(|until| *until > now),
+        })
+    }
+
+    /// Sets or replaces the PIN. Replacing an existing PIN requires the current one.
+    pub fn set_pin(conn: &Connection, current_pin: Option<&str>, new_pin: &str) -> Result<(), String> {
+        Self::require(conn, current_pin)?;
+
+        let new_pin = new_pin.trim();
+        if new_pin.chars().count() < MIN_PIN_LENGTH {
+            return Err(I18n::format(
+                "app_lock.pin_too_short",
+                &[("min", MIN_PIN_LENGTH.to_string())],
+            ));
+        }
+
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(new_pin.as_bytes(), &salt)
+            .map_err(|error| error.to_string())?
+            .to_string();
+        Settings::set(conn, PIN_HASH_KEY, &hash)
+    }
+
+    pub fn clear_pin(conn: &Connection, pin: &str) -> Result<(), String> {
+        Self::require(conn, Some(pin))?;
+        Settings::remove(conn, PIN_HASH_KEY)?;
+        Self::reset_failures(conn)
+    }
+
+    /// Verifies `pin` when a PIN is configured. Repeated failures lock verification
+    /// for a few minutes; a correct PIN resets the failure counter.
+    pub fn require(conn: &Connection, pin: Option<&str>) -> Result<(), String> {
+        let Some(stored) = Settings::get(conn, PIN_HASH_KEY)? else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        if let Some(until) = Settings::get_i64(conn, LOCKED_UNTIL_KEY)? {
+            if until > now.timestamp_millis() {
+                let remaining = (until - now.timestamp_millis() + 59_999) / 60_000;
+                return Err(I18n::format(
+                    "app_lock.locked_out",
+                    &[("minutes", remaining.to_string())],
+                ));
+            }
+        }
+
+        let Some(pin) = pin.map(str::trim).filter(|pin| !pin.is_empty()) else {
+            return Err(I18n::text("app_lock.pin_required").to_string());
+        };
+
+        let parsed = PasswordHash::new(&stored).map_err(|error| error.to_string())?;
+        if Argon2::default().verify_password(pin.as_bytes(), &parsed).is_ok() {
+            return Self::reset_failures(conn);
+        }
+
+        let attempts = Settings::get_i64(conn, FAILED_ATTEMPTS_KEY)?.unwrap_or(0) + 1;
+        if attempts >= MAX_FAILED_ATTEMPTS {
+            let until = now + Duration::minutes(LOCKOUT_MINUTES);
+            Settings::set(conn, LOCKED_UNTIL_KEY, &until.timestamp_millis().to_string())?;
+            Settings::set(conn, FAILED_ATTEMPTS_KEY, "0")?;
+            return Err(I18n::format(
+                "app_lock.locked_now",
+                &[("minutes", LOCKOUT_MINUTES.to_string())],
+            ));
+        }
+
+        Settings::set(conn, FAILED_ATTEMPTS_KEY, &attempts.to_string())?;
+        Err(I18n::format(
+            "app_lock.incorrect_pin",
+            &[("attempts", (MAX_FAILED_ATTEMPTS - attempts).to_string())],
+        ))
+    }
+
+    fn reset_failures(conn: &Connection) -> Result<(), String> {
+        Settings::remove(conn, FAILED_ATTEMPTS_KEY)?;
+        Settings::remove(conn, LOCKED_UNTIL_KEY)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::commands::file_operations::persist_buffer;
+use crate::services::batch_import::{
+    import_result, skipped, ImportEntryResult, ImportEntryStatus, ImportTracker,
+};
+use crate::services::gemini::mime_for;
+use serde::Serialize;
+use std::io::{Read, Seek};
+use std::path::Path;
+use tauri::Runtime;
+use zip::ZipArchive;
+
+/// Entries larger than this once unpacked are skipped rather than read into memory.
+const MAX_ENTRY_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveImportSummary {
+    pub archive: String,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Entries after a cancellation are not listed.
+    pub cancelled: bool,
+    pub entries: Vec<ImportEntryResult>,
+}
+
+/// Unpacks `.zip` bundles in memory and imports every supported document through the same
+/// hashing and duplicate checks as single files. Folders inside the archive are flattened;
+/// nested archives and unsupported files are reported as skipped.
+pub struct ArchiveImport;
+
+impl ArchiveImport {
+    /// The archive counts as one file in `tracker` until it is opened, then as its entries.
+    /// Entries record `source`, the archive's path, as where they came from.
+    pub fn import<R: Runtime, Rd: Read + Seek>(
+        archive_name: &str,
+        source: Option<&str>,
+        reader: Rd,
+        tracker: &mut ImportTracker<R>,
+    ) -> Result<ArchiveImportSummary, String> {
+        let mut archive = ZipArchive::new(reader).map_err(|error| format!("{}: {}", archive_name, error))?;
+        tracker.expand(archive.file_names().filter(|name| !name.ends_with('/')).count());
+        let mut summary = ArchiveImportSummary {
+            archive: archive_name.to_string(),
+            ..ArchiveImportSummary::default()
+        };
+
+        for index in 0..archive.len() {
+            if tracker.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+            let mut entry = archive.by_index(index).map_err(|error| error.to_string())?;
+            if entry.is_dir() {
+                continue;
+            }
+            let entry_name = entry.name().to_string();
+            let file_name = Path::new(&entry_name)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_string();
+            tracker.begin_file(&format!("{}/{}", archive_name, entry_name));
+
+            let skip_reason = if entry_name.starts_with("__MACOSX/") || file_name.starts_with('.') {
+                Some("Hidden or system file.".to_string())
+            } else if entry.encrypted() {
+                Some("Encrypted entries are not supported.".to_string())
+            } else if mime_for(Path::new(&file_name)).is_none() {
+                Some("Not a supported document type.".to_string())
+            } else if entry.size() > MAX_ENTRY_BYTES {
+                Some(format!(
+                    "Larger than {} MB once unpacked.",
+                    MAX_ENTRY_BYTES / 1024 / 1024
+                ))
+            } else {
+                None
+            };
+            let result = match skip_reason {
+                Some(reason) => skipped(&entry_name, &reason),
+                None => {
+                    let mut buffer = Vec::with_capacity(entry.size() as usize);
+                    let outcome = entry
+                        .by_ref()
+                        .take(MAX_ENTRY_BYTES)
+                        .read_to_end(&mut buffer)
+                        .map_err(|error| error.to_string())
+                        .and_then(|_| persist_buffer(&file_name, &buffer, source));
+                    import_result(entry_name, outcome)
+                }
+            };
+
+            match result.status {
+                ImportEntryStatus::Imported => summary.imported += 1,
+                ImportEntryStatus::Duplicate => summary.duplicates += 1,
+                ImportEntryStatus::Skipped => summary.skipped += 1,
+                ImportEntryStatus::Failed => summary.failed += 1,
+            }
+            tracker.finish_file(&result, entry.compressed_size())?;
+            summary.entries.push(result);
+        }
+
+        Ok(summary)
+    }
+}
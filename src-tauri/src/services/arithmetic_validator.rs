@@ -0,0 +1,151 @@
+use crate::services::i18n::I18n;
+use crate::services::parsed_invoice::{ParsedInvoice, GRAND_TOTAL, ITEMS, SUBTOTAL, TAX_TOTAL};
+use serde::{Deserialize, Serialize};
+
+/// Absolute difference (in invoice currency) tolerated before a mismatch is flagged.
+pub const DEFAULT_TOLERANCE: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArithmeticIssue {
+    pub check: String,
+    pub field: String,
+    pub computed: f64,
+    pub stated: f64,
+    pub difference: f64,
+    pub message: String,
+}
+
+pub struct ArithmeticValidator;
+
+impl ArithmeticValidator {
+    /// Cross-checks line amounts, tax components and totals, returning every
+    /// relation whose stated value differs from the computed one by more than `tolerance`.
+    pub fn check(invoice: &ParsedInvoice, tolerance: f64) -> Vec<ArithmeticIssue> {
+        let mut issues = Vec::new();
+        let items = invoice.line_items();
+
+        let mut compare = |check: &str, field: String, computed: f64, stated: f64, label: String| {
+            let difference = round2(stated - computed);
+            if difference.abs() > tolerance {
+                issues.push(ArithmeticIssue {
+                    check: check.to_string(),
+                    field,
+                    computed: round2(computed),
+                    stated: round2(stated),
+                    difference,
+                    message: I18n::format(
+                        "arithmetic.mismatch",
+                        &[
+                            ("label", label),
+                            ("stated", format!("{:.2}", stated)),
+                            ("computed", format!("{:.2}", computed)),
+                            ("difference", format!("{:.2}", difference)),
+                        ],
+                    ),
+                });
+            }
+        };
+
+        for (index, item) in items.iter().enumerate() {
+            let line = index + 1;
+            if let (Some(quantity), Some(rate), Some(amount)) = (item.quantity, item.rate, item.amount) {
+                compare(
+                    "line_amount",
+                    format!("{}[{}].amount", ITEMS, index),
+                    quantity * rate,
+                    amount,
+                    I18n::format("arithmetic.line_amount", &[("line", line.to_string())]),
+                );
+            }
+            if let (Some(amount), Some(rate), Some(cgst)) = (item.amount, item.cgst_rate, item.cgst) {
+                compare(
+                    "line_cgst",
+                    format!("{}[{}].cgst", ITEMS, index),
+                    amount * rate / 100.0,
+                    cgst,
+                    I18n::format("arithmetic.line_cgst", &[("line", line.to_string())]),
+                );
+            }
+            if let (Some(amount), Some(rate), Some(sgst)) = (item.amount, item.sgst_rate, item.sgst) {
+                compare(
+                    "line_sgst",
+                    format!("{}[{}].sgst", ITEMS, index),
+                    amount * rate / 100.0,
+                    sgst,
+                    I18n::format("arithmetic.line_sgst", &[("line", line.to_string())]),
+                );
+            }
+            if let (Some(amount), Some(rate), Some(igst)) = (item.amount, item.igst_rate, item.igst) {
+                compare(
+                    "line_igst",
+                    format!("{}[{}].igst", ITEMS, index),
+                    amount * rate / 100.0,
+                    igst,
+                    I18n::format("arithmetic.line_igst", &[("line", line.to_string())]),
+                );
+            }
+        }
+
+        let line_amounts: Option<f64> = if items.is_empty() {
+            None
+        } else {
+            items.iter().map(|item| item.amount).sum()
+        };
+        let line_taxes: Option<f64> = if items.is_empty() {
+            None
+        } else {
+            items
+                .iter()
+                .map(|item| match (item.cgst, item.sgst, item.igst) {
+                    (None, None, None) => None,
+                    (cgst, sgst, igst) => {
+                        Some(cgst.unwrap_or(0.0) + sgst.unwrap_or(0.0) + igst.unwrap_or(0.0))
+                    }
+                })
+                .sum()
+        };
+
+        let subtotal = invoice.number(SUBTOTAL);
+        let tax_total = invoice.number(TAX_TOTAL);
+        let grand_total = invoice.number(GRAND_TOTAL);
+
+        if let (Some(computed), Some(stated)) = (line_amounts, subtotal) {
+            compare(
+                "subtotal",
+                SUBTOTAL.to_string(),
+                computed,
+                stated,
+                I18n::text("arithmetic.subtotal").to_string(),
+            );
+        }
+
+        if let (Some(computed), Some(stated)) = (line_taxes, tax_total) {
+            compare(
+                "tax_total",
+                TAX_TOTAL.to_string(),
+                computed,
+                stated,
+                I18n::text("arithmetic.tax_total").to_string(),
+            );
+        }
+
+        let base = subtotal.or(line_amounts);
+        let tax = tax_total.or(line_taxes);
+        if let (Some(base), Some(tax), Some(stated)) = (base, tax, grand_total) {
+            compare(
+                "grand_total",
+                GRAND_TOTAL.to_string(),
+                base + tax,
+                stated,
+                I18n::text("arithmetic.grand_total").to_string(),
+            );
+        }
+
+        issues
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,204 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+/// `prev_hash` of the first entry in the chain.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub action: String,
+    pub subject: Option<String>,
+    pub details: Option<Value>,
+    pub created_at: String,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditChainReport {
+    pub valid: bool,
+    pub entries_checked: usize,
+    /// First entry whose hash or link does not match, if any.
+    pub broken_entry_id: Option<i64>,
+    pub message: String,
+}
+
+/// Append-only activity history. Every entry stores the hash of the entry before it,
+/// so editing or removing a row breaks the chain from that point on.
+pub struct AuditLog;
+
+impl AuditLog {
+    /// Appends an entry linked to the last one. Reading the last hash and inserting happen
+    /// under one write lock, so two writers never link to the same predecessor: in their
+    /// own `BEGIN IMMEDIATE` transaction, or in a savepoint of the caller's transaction.
+    pub fn record(
+        conn: &Connection,
+        action: &str,
+        subject: Option<&str>,
+        details: Option<Value>,
+    ) -> Result<(), String> {
+        let (begin, commit, rollback) = if conn.is_autocommit() {
+            ("BEGIN IMMEDIATE;", "COMMIT;", "ROLLBACK;")
+        } else {
+            (
+                "SAVEPOINT audit_record;",
+                "RELEASE audit_record;",
+                "ROLLBACK TO audit_record; RELEASE audit_record;",
+            )
+        };
+        conn.execute_batch(begin).map_err(|error| error.to_string())?;
+        match append(conn, action, subject, details)
+            .and_then(|()| conn.execute_batch(commit).map_err(|error| error.to_string()))
+        {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                let _ = conn.execute_batch(rollback);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn list(conn: &Connection, limit: i64, offset: i64) -> Result<Vec<AuditEntry>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, action, subject, details, created_at, prev_hash, entry_hash
+                 FROM audit_log ORDER BY id DESC LIMIT ?1 OFFSET ?2",
+            )
+            .map_err(|error| error.to_string())?;
+        let entries = stmt
+            .query_map(params![limit, offset], |row| {
+                let details: Option<String> = row.get(3)?;
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    action: row.get(1)?,
+                    subject: row.get(2)?,
+                    details: details.and_then(|raw| serde_json::from_str(&raw).ok()),
+                    created_at: row.get(4)?,
+                    prev_hash: row.get(5)?,
+                    entry_hash: row.get(6)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(entries)
+    }
+
+    /// Recomputes every hash in insertion order and checks each link to its predecessor.
+    pub fn verify(conn: &Connection) -> Result<AuditChainReport, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, action, subject, details, created_at, prev_hash, entry_hash
+                 FROM audit_log ORDER BY id ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let mut rows = stmt.query([]).map_err(|error| error.to_string())?;
+
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut entries_checked = 0;
+        while let Some(row) = rows.next().map_err(|error| error.to_string())? {
+            let id: i64 = row.get(0).map_err(|error| error.to_string())?;
+            let action: String = row.get(1).map_err(|error| error.to_string())?;
+            let subject: Option<String> = row.get(2).map_err(|error| error.to_string())?;
+            let details: Option<String> = row.get(3).map_err(|error| error.to_string())?;
+            let created_at: String = row.get(4).map_err(|error| error.to_string())?;
+            let prev_hash: String = row.get(5).map_err(|error| error.to_string())?;
+            let stored_hash: String = row.get(6).map_err(|error| error.to_string())?;
+
+            let broken = |message: String| AuditChainReport {
+                valid: false,
+                entries_checked,
+                broken_entry_id: Some(id),
+                message,
+            };
+
+            if prev_hash != expected_prev {
+                return Ok(broken(format!(
+                    "Entry {} does not link to the entry before it; an entry was removed or reordered.",
+                    id
+                )));
+            }
+
+            let computed = entry_hash(
+                &prev_hash,
+                &created_at,
+                &action,
+                subject.as_deref(),
+                details.as_deref(),
+            );
+            if computed != stored_hash {
+                return Ok(broken(format!(
+                    "Entry {} was modified after it was recorded.",
+                    id
+                )));
+            }
+
+            entries_checked += 1;
+            expected_prev = stored_hash;
+        }
+
+        Ok(AuditChainReport {
+            valid: true,
+            entries_checked,
+            broken_entry_id: None,
+            message: format!("All {} audit entries are intact.", entries_checked),
+        })
+    }
+}
+
+/// Inserts an entry after the current last one; `record` holds the write lock.
+fn append(
+    conn: &Connection,
+    action: &str,
+    subject: Option<&str>,
+    details: Option<Value>,
+) -> Result<(), String> {
+    let prev_hash: String = conn
+        .query_row(
+            "SELECT entry_hash FROM audit_log ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    let created_at = Utc::now().to_rfc3339();
+    let details = details.map(|value| value.to_string());
+    let entry_hash = entry_hash(&prev_hash, &created_at, action, subject, details.as_deref());
+
+    conn.execute(
+        "INSERT INTO audit_log (action, subject, details, created_at, prev_hash, entry_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![action, subject, details, created_at, prev_hash, entry_hash],
+    )
+    .map_err(|error| error.to_string())?;
+
+    Ok(())
+}
+
+fn entry_hash(
+    prev_hash: &str,
+    created_at: &str,
+    action: &str,
+    subject: Option<&str>,
+    details: Option<&str>,
+) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for part in [
+        prev_hash,
+        created_at,
+        action,
+        subject.unwrap_or(""),
+        details.unwrap_or(""),
+    ] {
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
@@ -0,0 +1,122 @@
+use crate::commands::file_operations::FileStatus;
+use crate::services::parsed_invoice::{
+    ParsedInvoice, GRAND_TOTAL, INVOICE_DATE, INVOICE_NUMBER, SELLER_NAME,
+};
+use crate::services::settings::Settings;
+use rusqlite::{params, Connection, OptionalExtension};
+
+const THRESHOLD_KEY: &str = "auto_approve.threshold";
+/// Fields an invoice cannot be booked without; each missing one lowers the confidence.
+const KEY_TEXT_FIELDS: &[&str] = &[SELLER_NAME, INVOICE_NUMBER, INVOICE_DATE];
+/// Share of the confidence kept for each open review flag on the file.
+const FLAG_FACTOR: f64 = 0.8;
+
+/// Decides whether a fresh extraction can be trusted as it is. Every extraction gets a
+/// confidence between 0 and 1: the share of key fields it found, lowered for each open
+/// review flag its checks raised. With a threshold set, files at or below it are held as
+/// NeedsReview instead of Processed until someone approves them.
+pub struct AutoApproval;
+
+impl AutoApproval {
+    /// `None` when auto-approval is off and every extraction is Processed straight away.
+    pub fn threshold(conn: &Connection) -> Result<Option<f64>, String> {
+        Ok(Settings::get(conn, THRESHOLD_KEY)?.and_then(|value| value.parse().ok()))
+    }
+
+    pub fn set_threshold(conn: &Connection, threshold: Option<f64>) -> Result<(), String> {
+        match threshold {
+            Some(threshold) if !(0.0..=1.0).contains(&threshold) => {
+                Err("Choose a confidence threshold between 0 and 1.".to_string())
+            }
+            Some(threshold) => Settings::set(conn, THRESHOLD_KEY, &threshold.to_string()),
+            None => Settings::remove(conn, THRESHOLD_KEY),
+        }
+    }
+
+    /// Scores the file's stored details and, when they were just marked Processed, holds
+    /// them for review if the score does not exceed the threshold. Files approved by hand
+    /// stay approved when they are extracted again. Returns the confidence, or `None` when
+    /// the file has no details to score.
+    pub fn apply(conn: &Connection, file_id: &str) -> Result<Option<f64>, String> {
+        let row: Option<(Option<String>, String, Option<String>)> = conn
+            .query_row(
+                "SELECT parsed_details, status, reviewed_at FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        let Some((Some(parsed_details), status, reviewed_at)) = row else {
+            return Ok(None);
+        };
+        let Some(invoice) = ParsedInvoice::from_json(&parsed_details) else {
+            return Ok(None);
+        };
+
+        let confidence = Self::confidence(conn, file_id, &invoice)?;
+        conn.execute(
+            "UPDATE files SET extraction_confidence = ?1 WHERE id = ?2",
+            params![confidence, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        let held = match Self::threshold(conn)? {
+            Some(threshold) => confidence <= threshold,
+            None => false,
+        };
+        if held && status == FileStatus::Processed.as_str() && reviewed_at.is_none() {
+            conn.execute(
+                "UPDATE files SET status = ?1, processed_at = NULL WHERE id = ?2",
+                params![FileStatus::NeedsReview.as_str(), file_id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(Some(confidence))
+    }
+
+    /// Moves files held for review to Processed and remembers they were reviewed. Returns
+    /// the ids that were waiting.
+    pub fn approve(conn: &Connection, file_ids: &[String]) -> Result<Vec<String>, String> {
+        let processed_at = chrono::Utc::now().to_rfc3339();
+        let mut approved = Vec::new();
+        for file_id in file_ids {
+            let updated = conn
+                .execute(
+                    "UPDATE files SET status = ?1, processed_at = ?2, reviewed_at = CURRENT_TIMESTAMP
+                     WHERE id = ?3 AND status = ?4",
+                    params![
+                        FileStatus::Processed.as_str(),
+                        processed_at,
+                        file_id,
+                        FileStatus::NeedsReview.as_str()
+                    ],
+                )
+                .map_err(|error| error.to_string())?;
+            if updated > 0 {
+                approved.push(file_id.clone());
+            }
+        }
+        Ok(approved)
+    }
+
+    fn confidence(conn: &Connection, file_id: &str, invoice: &ParsedInvoice) -> Result<f64, String> {
+        let found = KEY_TEXT_FIELDS
+            .iter()
+            .filter(|key| invoice.text(key).is_some())
+            .count()
+            + usize::from(invoice.number(GRAND_TOTAL).is_some())
+            + usize::from(!invoice.line_items().is_empty());
+        let completeness = found as f64 / (KEY_TEXT_FIELDS.len() + 2) as f64;
+
+        let open_flags: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM review_flags WHERE file_id = ?1 AND resolved_at IS NULL",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        let confidence = completeness * FLAG_FACTOR.powi(open_flags);
+        // Two decimals are plenty to compare against the threshold and to show.
+        Ok((confidence * 100.0).round() / 100.0)
+    }
+}
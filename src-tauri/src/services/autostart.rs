@@ -0,0 +1,55 @@
+use crate::services::scheduler::{ScheduledJob, Scheduler};
+use crate::services::tray::Tray;
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_autostart::ManagerExt;
+
+/// Passed by the OS autostart entry; the app then starts hidden in the tray.
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+/// Jobs that must be active for unattended ingestion.
+const UNATTENDED_JOBS: [ScheduledJob; 2] = [ScheduledJob::WatchedFolderScan, ScheduledJob::AutoProcess];
+
+/// Launch-on-login. The OS entry itself is the source of truth, so the option survives a
+/// reinstall of the database and a user removing it from the OS settings is respected.
+pub struct Autostart;
+
+impl Autostart {
+    pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+        app.autolaunch().is_enabled().map_err(|error| error.to_string())
+    }
+
+    /// Enabling also turns on background mode and the watched-folder and processing schedules,
+    /// since a minimized start is pointless if closing quits or nothing runs in the background.
+    pub fn set_enabled<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        enabled: bool,
+    ) -> Result<(), String> {
+        let autolaunch = app.autolaunch();
+        if !enabled {
+            return autolaunch.disable().map_err(|error| error.to_string());
+        }
+
+        autolaunch.enable().map_err(|error| error.to_string())?;
+        Tray::set_background_mode(conn, true)?;
+        for job in UNATTENDED_JOBS {
+            Scheduler::set_job_enabled(conn, job, true)?;
+        }
+        Ok(())
+    }
+
+    pub fn launched_minimized() -> bool {
+        std::env::args().any(|arg| arg == MINIMIZED_ARG)
+    }
+
+    /// Hides the main window when started from the login entry.
+    pub fn apply_launch_state<R: Runtime>(app: &AppHandle<R>) {
+        if !Self::launched_minimized() {
+            return;
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+}
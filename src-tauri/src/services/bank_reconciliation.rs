@@ -0,0 +1,389 @@
+use crate::services::{
+    date_normalizer::DateNormalizer,
+    parsed_invoice::{ParsedInvoice, GRAND_TOTAL, INVOICE_NUMBER, SELLER_NAME},
+};
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+
+/// Amounts closer than this are treated as equal.
+const AMOUNT_TOLERANCE: f64 = 0.01;
+/// Payments are expected no earlier than a week before the invoice date and no later
+/// than this many days after it (or after the due date, when known).
+const DATE_WINDOW_DAYS: i64 = 120;
+/// Minimum score for a candidate to be stored as a suggestion.
+const SUGGESTION_THRESHOLD: f64 = 0.6;
+
+const DATE_HEADERS: &[&str] = &[
+    "date",
+    "txn date",
+    "transaction date",
+    "value date",
+    "posting date",
+    "posted",
+];
+const DESCRIPTION_HEADERS: &[&str] = &[
+    "description",
+    "narration",
+    "particulars",
+    "details",
+    "memo",
+    "remarks",
+];
+const REFERENCE_HEADERS: &[&str] = &[
+    "reference",
+    "ref",
+    "ref no",
+    "ref no.",
+    "chq / ref no.",
+    "cheque no",
+    "utr",
+];
+const AMOUNT_HEADERS: &[&str] = &["amount", "transaction amount"];
+const DEBIT_HEADERS: &[&str] = &["debit", "withdrawal", "withdrawal amt.", "withdrawals", "dr"];
+const CREDIT_HEADERS: &[&str] = &["credit", "deposit", "deposit amt.", "deposits", "cr"];
+
+#[derive(Debug, Clone)]
+pub struct StatementLine {
+    pub posted_on: String,
+    /// Negative for money leaving the account.
+    pub amount: f64,
+    pub description: Option<String>,
+    pub reference: Option<String>,
+}
+
+impl StatementLine {
+    /// Stable identity used to skip lines that were already imported from an overlapping statement.
+    pub fn line_key(&self) -> String {
+        let raw = format!(
+            "{}|{:.2}|{}|{}",
+            self.posted_on,
+            self.amount,
+            self.description.as_deref().unwrap_or(""),
+            self.reference.as_deref().unwrap_or("")
+        );
+        blake3::hash(raw.as_bytes()).to_hex().to_string()
+    }
+}
+
+pub struct StatementParser;
+
+impl StatementParser {
+    /// Parses a CSV or OFX statement, choosing the format from the extension or content.
+    pub fn parse(file_name: &str, content: &str, locale: Option<&str>) -> Result<Vec<StatementLine>, String> {
+        let lower = file_name.to_lowercase();
+        if lower.ends_with(".ofx") || lower.ends_with(".qfx") || content.contains("<OFX>") {
+            Self::parse_ofx(content)
+        } else {
+            Self::parse_csv(content, locale)
+        }
+    }
+
+    fn parse_csv(content: &str, locale: Option<&str>) -> Result<Vec<StatementLine>, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|error| error.to_string())?
+            .iter()
+            .map(|header| header.to_lowercase())
+            .collect();
+        let find = |candidates: &[&str]| {
+            headers
+                .iter()
+                .position(|header| candidates.contains(&header.as_str()))
+        };
+
+        let date_col =
+            find(DATE_HEADERS).ok_or_else(|| "The statement has no recognizable date column.".to_string())?;
+        let description_col = find(DESCRIPTION_HEADERS);
+        let reference_col = find(REFERENCE_HEADERS);
+        let amount_col = find(AMOUNT_HEADERS);
+        let debit_col = find(DEBIT_HEADERS);
+        let credit_col = find(CREDIT_HEADERS);
+        if amount_col.is_none() && debit_col.is_none() && credit_col.is_none() {
+            return Err("The statement has no amount, debit or credit column.".to_string());
+        }
+
+        let mut lines = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|error| error.to_string())?;
+            let cell = |col: Option<usize>| {
+                col.and_then(|index| record.get(index))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+
+            let Some(posted_on) =
+                cell(Some(date_col)).and_then(|raw| DateNormalizer::normalize(&raw, locale).iso)
+            else {
+                continue;
+            };
+
+            let amount = match amount_col {
+                Some(_) => cell(amount_col).and_then(|raw| parse_amount(&raw)),
+                None => {
+                    let debit = cell(debit_col).and_then(|raw| parse_amount(&raw)).unwrap_or(0.0);
+                    let credit = cell(credit_col).and_then(|raw| parse_amount(&raw)).unwrap_or(0.0);
+                    Some(credit - debit.abs())
+                }
+            };
+            let Some(amount) = amount.filter(|value| value.abs() > AMOUNT_TOLERANCE) else {
+                continue;
+            };
+
+            lines.push(StatementLine {
+                posted_on,
+                amount,
+                description: cell(description_col),
+                reference: cell(reference_col),
+            });
+        }
+
+        Ok(lines)
+    }
+
+    fn parse_ofx(content: &str) -> Result<Vec<StatementLine>, String> {
+        let mut lines = Vec::new();
+        for block in content.split("<STMTTRN>").skip(1) {
+            let block = block.split("</STMTTRN>").next().unwrap_or(block);
+            let Some(posted_on) = ofx_value(block, "DTPOSTED")
+                .and_then(|raw| NaiveDate::parse_from_str(raw.get(..8)?, "%Y%m%d").ok())
+                .map(|date| date.format("%Y-%m-%d").to_string())
+            else {
+                continue;
+            };
+            let Some(amount) = ofx_value(block, "TRNAMT").and_then(|raw| parse_amount(&raw)) else {
+                continue;
+            };
+
+            let description = match (ofx_value(block, "NAME"), ofx_value(block, "MEMO")) {
+                (Some(name), Some(memo)) => Some(format!("{} {}", name, memo)),
+                (name, memo) => name.or(memo),
+            };
+            let reference = ofx_value(block, "CHECKNUM")
+                .or_else(|| ofx_value(block, "REFNUM"))
+                .or_else(|| ofx_value(block, "FITID"));
+
+            lines.push(StatementLine {
+                posted_on,
+                amount,
+                description,
+                reference,
+            });
+        }
+
+        if lines.is_empty() {
+            return Err("No transactions were found in the OFX file.".to_string());
+        }
+        Ok(lines)
+    }
+}
+
+/// Reads `<TAG>value` from OFX, which may or may not close its leaf elements.
+fn ofx_value(block: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = block.find(&open)? + open.len();
+    let rest = &block[start..];
+    let end = rest.find('<').unwrap_or(rest.len());
+    let value = rest[..end].trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Parses bank amounts such as "1,234.50", "(250.00)" or "1 234,50 DR".
+pub fn parse_amount(raw: &str) -> Option<f64> {
+    let trimmed = raw.trim();
+    let upper = trimmed.to_uppercase();
+    let negative = trimmed.starts_with('-')
+        || (trimmed.starts_with('(') && trimmed.ends_with(')'))
+        || upper.ends_with("DR");
+
+    let mut digits: String = trimmed
+        .chars()
+        .filter(|ch| ch.is_ascii_digit() || *ch == '.' || *ch == ',')
+        .collect();
+    // A lone comma followed by two digits is a decimal separator ("1234,50").
+    if !digits.contains('.') && digits.rfind(',').is_some_and(|index| digits.len() - index == 3) {
+        let index = digits.rfind(',').unwrap_or_default();
+        digits.replace_range(index..index + 1, ".");
+    }
+    let value: f64 = digits.replace(',', "").parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+struct OpenInvoice {
+    file_id: String,
+    amount: f64,
+    invoice_number: Option<String>,
+    seller_name: Option<String>,
+    invoice_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+}
+
+pub struct BankMatcher;
+
+impl BankMatcher {
+    /// Scores unconfirmed statement lines against unpaid invoices and stores the best
+    /// candidate of each line as a suggestion. Returns the number of suggestions.
+    pub fn suggest(conn: &Connection) -> Result<usize, String> {
+        let invoices = load_open_invoices(conn)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, posted_on, amount, description, reference FROM bank_statement_lines
+                 WHERE match_status <> 'Confirmed'",
+            )
+            .map_err(|error| error.to_string())?;
+        let lines = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    StatementLine {
+                        posted_on: row.get(1)?,
+                        amount: row.get(2)?,
+                        description: row.get(3)?,
+                        reference: row.get(4)?,
+                    },
+                ))
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut suggested = 0;
+        for (line_id, line) in lines {
+            let best = invoices
+                .iter()
+                .map(|invoice| (invoice, score(&line, invoice)))
+                .filter(|(_, score)| *score >= SUGGESTION_THRESHOLD)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            let (file_id, match_score, status) = match best {
+                Some((invoice, score)) => (Some(invoice.file_id.clone()), Some(score), "Suggested"),
+                None => (None, None, "Unmatched"),
+            };
+            if file_id.is_some() {
+                suggested += 1;
+            }
+
+            conn.execute(
+                "UPDATE bank_statement_lines SET matched_file_id = ?1, match_score = ?2, match_status = ?3
+                 WHERE id = ?4",
+                params![file_id, match_score, status, line_id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+
+        Ok(suggested)
+    }
+}
+
+fn load_open_invoices(conn: &Connection) -> Result<Vec<OpenInvoice>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.id, f.parsed_details, f.invoice_date, f.due_date, s.name
+             FROM files f
+             LEFT JOIN sellers s ON s.id = f.seller_id
+             WHERE f.parsed_details IS NOT NULL
+             AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.file_id = f.id)
+             AND NOT EXISTS (
+               SELECT 1 FROM bank_statement_lines l
+               WHERE l.matched_file_id = f.id AND l.match_status = 'Confirmed'
+             )",
+        )
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?;
+
+    let parse_date =
+        |value: Option<String>| value.and_then(|value| NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok());
+
+    let mut invoices = Vec::new();
+    for row in rows {
+        let (file_id, parsed_details, invoice_date, due_date, seller_name) =
+            row.map_err(|error| error.to_string())?;
+        let Some(invoice) = ParsedInvoice::from_json(&parsed_details) else {
+            continue;
+        };
+        let Some(amount) = invoice.number(GRAND_TOTAL) else {
+            continue;
+        };
+        invoices.push(OpenInvoice {
+            file_id,
+            amount,
+            invoice_number: invoice.text(INVOICE_NUMBER),
+            seller_name: seller_name.or_else(|| invoice.text(SELLER_NAME)),
+            invoice_date: parse_date(invoice_date),
+            due_date: parse_date(due_date),
+        });
+    }
+
+    Ok(invoices)
+}
+
+/// Amount equality is required; date proximity, a reference to the invoice number and
+/// the seller's name in the narration raise the score.
+fn score(line: &StatementLine, invoice: &OpenInvoice) -> f64 {
+    if (line.amount.abs() - invoice.amount).abs() > AMOUNT_TOLERANCE {
+        return 0.0;
+    }
+    let mut score = 0.5;
+
+    if let (Ok(posted), Some(invoice_date)) = (
+        NaiveDate::parse_from_str(&line.posted_on, "%Y-%m-%d"),
+        invoice.invoice_date,
+    ) {
+        let anchor = invoice.due_date.unwrap_or(invoice_date);
+        let days_after_invoice = (posted - invoice_date).num_days();
+        if days_after_invoice < -7 || (posted - anchor).num_days() > DATE_WINDOW_DAYS {
+            return 0.0;
+        }
+        let distance = (posted - anchor).num_days().abs() as f64;
+        score += 0.2 * (1.0 - (distance / DATE_WINDOW_DAYS as f64).min(1.0));
+    }
+
+    let narration = format!(
+        "{} {}",
+        line.description.as_deref().unwrap_or(""),
+        line.reference.as_deref().unwrap_or("")
+    );
+    let narration_key = alphanumeric(&narration);
+
+    if let Some(number) = invoice.invoice_number.as_deref().map(alphanumeric) {
+        if number.len() >= 3 && narration_key.contains(&number) {
+            score += 0.3;
+        }
+    }
+
+    if let Some(seller) = &invoice.seller_name {
+        let narration_lower = narration.to_lowercase();
+        let matches_seller = seller
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .any(|word| word.len() >= 4 && narration_lower.contains(&word));
+        if matches_seller {
+            score += 0.1;
+        }
+    }
+
+    (score * 100.0).round() / 100.0
+}
+
+fn alphanumeric(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric())
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect()
+}
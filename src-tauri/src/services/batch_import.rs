@@ -0,0 +1,347 @@
+use crate::commands::file_operations::persist_document;
+use crate::db::{get_connection, PooledConnection};
+use crate::services::archive_import::ArchiveImport;
+use crate::services::gemini::mime_for;
+use crate::services::image_preprocess::PreprocessOptions;
+use crate::services::import_batches::{ImportBatches, ImportSource};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Runtime};
+use uuid::Uuid;
+
+/// Emitted with an [`ImportProgress`] when a batch starts, after every file and when it ends.
+pub const IMPORT_PROGRESS_EVENT: &str = "import://progress";
+
+/// Cancellation flags of the imports currently running, by import id.
+static RUNNING: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+
+fn running() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    RUNNING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportEntryStatus {
+    Imported,
+    Duplicate,
+    Skipped,
+    Failed,
+}
+
+impl ImportEntryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportEntryStatus::Imported => "imported",
+            ImportEntryStatus::Duplicate => "duplicate",
+            ImportEntryStatus::Skipped => "skipped",
+            ImportEntryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "imported" => Ok(ImportEntryStatus::Imported),
+            "duplicate" => Ok(ImportEntryStatus::Duplicate),
+            "skipped" => Ok(ImportEntryStatus::Skipped),
+            "failed" => Ok(ImportEntryStatus::Failed),
+            other => Err(format!("Unknown import status: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEntryResult {
+    /// The file's path, or its path inside the archive it came from.
+    pub entry: String,
+    pub status: ImportEntryStatus,
+    pub file_id: Option<String>,
+    /// Why the entry was skipped or failed.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportPhase {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    pub import_id: String,
+    /// The import batch the files are recorded in.
+    pub batch_id: i64,
+    pub phase: ImportPhase,
+    /// Grows when an archive is opened and its documents are counted.
+    pub total_files: usize,
+    pub completed_files: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub current_file: Option<String>,
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchImportSummary {
+    pub import_id: String,
+    pub batch_id: i64,
+    /// Files after the cancellation were left alone; everything before it was kept.
+    pub cancelled: bool,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub entries: Vec<ImportEntryResult>,
+}
+
+/// Counts a batch import, records each file in its import batch, emits
+/// [`IMPORT_PROGRESS_EVENT`] as it goes and carries its cancellation flag. Cancellation is
+/// checked between files, so a document is either fully stored and recorded or not touched
+/// at all.
+pub struct ImportTracker<'a, R: Runtime> {
+    app: &'a AppHandle<R>,
+    conn: PooledConnection,
+    cancelled: Arc<AtomicBool>,
+    progress: ImportProgress,
+}
+
+impl<'a, R: Runtime> ImportTracker<'a, R> {
+    /// Registers the import so [`BatchImport::cancel`] can reach it. The frontend picks the
+    /// id so it can cancel before the command returns; one is generated when it does not.
+    pub fn start(
+        app: &'a AppHandle<R>,
+        import_id: Option<String>,
+        batch_id: i64,
+        total_files: usize,
+        total_bytes: u64,
+    ) -> Result<Self, String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let import_id = import_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        {
+            let mut running = running().lock().map_err(|error| error.to_string())?;
+            if running.contains_key(&import_id) {
+                return Err(format!("Import {} is already running.", import_id));
+            }
+            running.insert(import_id.clone(), cancelled.clone());
+        }
+
+        let tracker = ImportTracker {
+            app,
+            conn,
+            cancelled,
+            progress: ImportProgress {
+                import_id,
+                batch_id,
+                phase: ImportPhase::Running,
+                total_files,
+                completed_files: 0,
+                imported: 0,
+                duplicates: 0,
+                skipped: 0,
+                failed: 0,
+                current_file: None,
+                bytes_processed: 0,
+                total_bytes,
+            },
+        };
+        tracker.emit();
+        Ok(tracker)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Replaces one counted file with the `files` it turned out to contain.
+    pub fn expand(&mut self, files: usize) {
+        self.progress.total_files = (self.progress.total_files + files).saturating_sub(1);
+    }
+
+    pub fn begin_file(&mut self, name: &str) {
+        self.progress.current_file = Some(name.to_string());
+        self.emit();
+    }
+
+    pub fn finish_file(&mut self, entry: &ImportEntryResult, bytes: u64) -> Result<(), String> {
+        ImportBatches::record(&self.conn, self.progress.batch_id, entry)?;
+        self.progress.completed_files += 1;
+        self.progress.bytes_processed += bytes;
+        match entry.status {
+            ImportEntryStatus::Imported => self.progress.imported += 1,
+            ImportEntryStatus::Duplicate => self.progress.duplicates += 1,
+            ImportEntryStatus::Skipped => self.progress.skipped += 1,
+            ImportEntryStatus::Failed => self.progress.failed += 1,
+        }
+        self.emit();
+        Ok(())
+    }
+
+    /// Emits the final progress and unregisters the import.
+    pub fn finish(mut self) -> ImportProgress {
+        self.progress.phase = if self.is_cancelled() {
+            ImportPhase::Cancelled
+        } else {
+            ImportPhase::Completed
+        };
+        self.progress.current_file = None;
+        self.emit();
+        self.progress.clone()
+    }
+
+    fn emit(&self) {
+        let _ = self.app.emit(IMPORT_PROGRESS_EVENT, &self.progress);
+    }
+}
+
+impl<R: Runtime> Drop for ImportTracker<'_, R> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = running().lock() {
+            running.remove(&self.progress.import_id);
+        }
+    }
+}
+
+/// Imports documents and `.zip` archives picked by path as one cancellable batch.
+pub struct BatchImport;
+
+impl BatchImport {
+    /// Asks a running import to stop after its current file. False when no import has the id.
+    pub fn cancel(import_id: &str) -> Result<bool, String> {
+        let running = running().lock().map_err(|error| error.to_string())?;
+        Ok(running
+            .get(import_id)
+            .map(|flag| flag.store(true, Ordering::SeqCst))
+            .is_some())
+    }
+
+    /// `label` names the batch, e.g. the folder the paths were picked from.
+    pub fn import_paths<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        import_id: Option<String>,
+        paths: &[String],
+        label: Option<&str>,
+        preprocess: Option<&PreprocessOptions>,
+    ) -> Result<BatchImportSummary, String> {
+        let total_bytes = paths
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let batch_id = ImportBatches::begin(conn, ImportSource::Paths, label)?;
+        let mut tracker = ImportTracker::start(app, import_id, batch_id, paths.len(), total_bytes)?;
+        let mut entries = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            if tracker.is_cancelled() {
+                break;
+            }
+            let file_name = Path::new(path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("file")
+                .to_string();
+
+            if file_name.to_lowercase().ends_with(".zip") {
+                let archive = fs::File::open(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|file| {
+                        ArchiveImport::import(&file_name, Some(path), io::BufReader::new(file), &mut tracker)
+                    });
+                match archive {
+                    Ok(summary) => entries.extend(summary.entries.into_iter().map(|mut entry| {
+                        entry.entry = format!("{}/{}", path, entry.entry);
+                        entry
+                    })),
+                    Err(error) => {
+                        let entry = import_result(path.clone(), Err(error));
+                        tracker.finish_file(&entry, 0)?;
+                        entries.push(entry);
+                    }
+                }
+                continue;
+            }
+
+            tracker.begin_file(&file_name);
+            let (entry, bytes) = if mime_for(Path::new(&file_name)).is_none() {
+                (
+                    skipped(path, "Not a supported document type."),
+                    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0),
+                )
+            } else {
+                match fs::File::open(path).and_then(|mut file| {
+                    let mut buffer = Vec::new();
+                    file.read_to_end(&mut buffer).map(|_| buffer)
+                }) {
+                    Ok(buffer) => (
+                        import_result(path.clone(), persist_document(&file_name, &buffer, Some(path), preprocess)),
+                        buffer.len() as u64,
+                    ),
+                    Err(error) => (import_result(path.clone(), Err(error.to_string())), 0),
+                }
+            };
+            tracker.finish_file(&entry, bytes)?;
+            entries.push(entry);
+        }
+
+        let progress = tracker.finish();
+        ImportBatches::finish(conn, batch_id, progress.phase == ImportPhase::Cancelled)?;
+        Ok(BatchImportSummary {
+            import_id: progress.import_id,
+            batch_id,
+            cancelled: progress.phase == ImportPhase::Cancelled,
+            imported: progress.imported,
+            duplicates: progress.duplicates,
+            skipped: progress.skipped,
+            failed: progress.failed,
+            entries,
+        })
+    }
+}
+
+pub(crate) fn skipped(entry: &str, reason: &str) -> ImportEntryResult {
+    ImportEntryResult {
+        entry: entry.to_string(),
+        status: ImportEntryStatus::Skipped,
+        file_id: None,
+        reason: Some(reason.to_string()),
+    }
+}
+
+/// Turns a `persist_buffer` response ("OK:id" or "DUPLICATE:id") into an entry result.
+pub(crate) fn import_result(entry: String, outcome: Result<String, String>) -> ImportEntryResult {
+    let (status, file_id, reason) = match outcome {
+        Ok(response) => match response.split_once(':') {
+            Some(("DUPLICATE", id)) => (ImportEntryStatus::Duplicate, Some(id.to_string()), None),
+            Some((_, id)) => (ImportEntryStatus::Imported, Some(id.to_string()), None),
+            None => (
+                ImportEntryStatus::Failed,
+                None,
+                Some("Unexpected import result.".to_string()),
+            ),
+        },
+        Err(error) => (ImportEntryStatus::Failed, None, Some(error)),
+    };
+    ImportEntryResult {
+        entry,
+        status,
+        file_id,
+        reason,
+    }
+}
@@ -0,0 +1,247 @@
+use crate::commands::file_operations::FileStatus;
+use crate::services::llm_provider::LlmProviderKind;
+use crate::services::parse_cancellation::ParseCancellation;
+use crate::services::parse_queue::{self, ParseQueue, MAX_WORKERS};
+use crate::services::processing_pause::ProcessingPause;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with a [`BatchParseProgress`] when each file of a batch starts and finishes.
+pub const PARSE_FILES_PROGRESS_EVENT: &str = "parse-files://progress";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchParseFile {
+    pub file_id: String,
+    pub file_name: Option<String>,
+    /// `queued`, `running` while it is extracted, then `applied`, `unchanged`, `conflict`,
+    /// `failed`, `cancelled` or `skipped`.
+    pub status: &'static str,
+    /// `template:<name>` or `<provider>:<model>`.
+    pub extracted_by: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchParseProgress {
+    pub batch_id: String,
+    #[serde(flatten)]
+    pub file: BatchParseFile,
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchParseSummary {
+    pub batch_id: String,
+    pub total: usize,
+    pub applied: usize,
+    pub unchanged: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub skipped: usize,
+    /// In the order the files were given.
+    pub files: Vec<BatchParseFile>,
+}
+
+/// Extracts a selection of files right away, a few at a time, instead of through the
+/// persistent queue. Each file is tried once; failures stay Failed for the queue's retry.
+pub struct BatchParse;
+
+impl BatchParse {
+    /// Claims the files in one transaction, so two batches or a batch and the queue never
+    /// extract the same file, then runs up to `concurrency` extractions at once, or
+    /// `parse_queue.workers` when not given.
+    pub fn run<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &mut Connection,
+        file_ids: &[String],
+        provider: Option<LlmProviderKind>,
+        concurrency: Option<usize>,
+    ) -> Result<BatchParseSummary, String> {
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        let concurrency = match concurrency {
+            Some(count) => count.clamp(1, MAX_WORKERS),
+            None => ParseQueue::workers(conn)?,
+        };
+
+        let mut files: Vec<BatchParseFile> = Vec::with_capacity(file_ids.len());
+        let mut claimed: Vec<(usize, String)> = Vec::new();
+        let tx = conn.transaction().map_err(|error| error.to_string())?;
+        for file_id in file_ids {
+            let row: Option<(String, String)> = tx
+                .query_row(
+                    "SELECT file_name, status FROM files WHERE id = ?1",
+                    params![file_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let skipped = |file_name: Option<String>, reason: &str| BatchParseFile {
+                file_id: file_id.clone(),
+                file_name,
+                status: "skipped",
+                extracted_by: None,
+                error: Some(reason.to_string()),
+                duration_ms: 0,
+            };
+            match row {
+                None => files.push(skipped(None, "File not found.")),
+                Some((file_name, _)) if files.iter().any(|file| &file.file_id == file_id) => {
+                    files.push(skipped(Some(file_name), "Listed twice."))
+                }
+                Some((file_name, status)) if status == FileStatus::Processing.as_str() => {
+                    files.push(skipped(Some(file_name), "Already being processed."))
+                }
+                Some((file_name, status)) => {
+                    tx.execute(
+                        "UPDATE files SET status = ?1 WHERE id = ?2",
+                        params![FileStatus::Processing.as_str(), file_id],
+                    )
+                    .map_err(|error| error.to_string())?;
+                    claimed.push((files.len(), status));
+                    files.push(BatchParseFile {
+                        file_id: file_id.clone(),
+                        file_name: Some(file_name),
+                        status: "queued",
+                        extracted_by: None,
+                        error: None,
+                        duration_ms: 0,
+                    });
+                }
+            }
+        }
+        tx.commit().map_err(|error| error.to_string())?;
+
+        let claimed_ids: Vec<String> = claimed
+            .iter()
+            .map(|(index, _)| files[*index].file_id.clone())
+            .collect();
+        // The queue would otherwise extract them a second time.
+        ParseQueue::cancel(conn, Some(&claimed_ids))?;
+
+        let total = files.len();
+        let completed = AtomicUsize::new(total - claimed.len());
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(files);
+        let emit = |file: BatchParseFile, completed: usize| {
+            let _ = app.emit(
+                PARSE_FILES_PROGRESS_EVENT,
+                BatchParseProgress {
+                    batch_id: batch_id.clone(),
+                    file,
+                    completed,
+                    total,
+                },
+            );
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency.min(claimed.len()) {
+                scope.spawn(|| {
+                    while let Some((index, previous_status)) =
+                        claimed.get(next.fetch_add(1, Ordering::SeqCst))
+                    {
+                        let file = lock(&results)[*index].clone();
+                        let file = parse_one(&file, previous_status, provider, |running| {
+                            emit(running, completed.load(Ordering::SeqCst))
+                        });
+                        let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                        lock(&results)[*index] = file.clone();
+                        emit(file, done);
+                    }
+                });
+            }
+        });
+
+        let files = results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let count = |status: &str| files.iter().filter(|file| file.status == status).count();
+        Ok(BatchParseSummary {
+            batch_id,
+            total,
+            applied: count("applied"),
+            unchanged: count("unchanged"),
+            conflicts: count("conflict"),
+            failed: count("failed"),
+            cancelled: count("cancelled"),
+            skipped: count("skipped"),
+            files,
+        })
+    }
+}
+
+/// Extracts one claimed file. A file that was never sent, because processing was paused
+/// meanwhile or the provider could not be reached at all, goes back to `previous_status`; a
+/// cancelled one goes back to Unprocessed.
+fn parse_one(
+    file: &BatchParseFile,
+    previous_status: &str,
+    provider: Option<LlmProviderKind>,
+    started: impl Fn(BatchParseFile),
+) -> BatchParseFile {
+    let clock = Instant::now();
+    let mut file = file.clone();
+    let outcome = parse_queue::connection().and_then(|conn| {
+        let release = |status: &str| {
+            conn.execute(
+                "UPDATE files SET status = ?1 WHERE id = ?2 AND status = ?3",
+                params![status, file.file_id, FileStatus::Processing.as_str()],
+            )
+            .map_err(|error| error.to_string())
+        };
+        if ProcessingPause::is_paused(&conn)? {
+            release(previous_status)?;
+            return Ok(None);
+        }
+        started(BatchParseFile {
+            status: "running",
+            ..file.clone()
+        });
+        let extraction = ParseQueue::extract_file(&conn, &file.file_id, provider);
+        match &extraction {
+            Err(error) if ParseCancellation::is_cancelled_error(error) => {
+                release(FileStatus::Unprocessed.as_str())?;
+            }
+            Err(_) => {
+                release(previous_status)?;
+            }
+            Ok(_) => {}
+        }
+        extraction.map(Some)
+    });
+    match outcome {
+        Ok(Some(extraction)) => {
+            file.status = extraction.status;
+            file.extracted_by = Some(extraction.extracted_by);
+        }
+        Ok(None) => {
+            file.status = "skipped";
+            file.error = Some("Processing is paused.".to_string());
+        }
+        Err(error) if ParseCancellation::is_cancelled_error(&error) => {
+            file.status = "cancelled";
+            file.error = Some(error);
+        }
+        Err(error) => {
+            file.status = "failed";
+            file.error = Some(error);
+        }
+    }
+    file.duration_ms = clock.elapsed().as_millis() as i64;
+    file
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
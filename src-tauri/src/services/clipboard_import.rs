@@ -0,0 +1,47 @@
+use arboard::{Clipboard, Error as ClipboardError};
+use chrono::Local;
+use image::{ImageFormat, RgbaImage};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+pub enum ClipboardContent {
+    /// Files copied in a file manager.
+    Files(Vec<PathBuf>),
+    /// A copied image or screenshot, encoded as PNG and named after the time it was pasted.
+    Image { file_name: String, png: Vec<u8> },
+}
+
+/// Reads importable content from the OS clipboard. Copied files win over image data, since
+/// file managers often put a thumbnail next to the file list.
+pub struct ClipboardImport;
+
+impl ClipboardImport {
+    pub fn read() -> Result<ClipboardContent, String> {
+        let mut clipboard = Clipboard::new().map_err(|error| error.to_string())?;
+
+        match clipboard.get().file_list() {
+            Ok(files) if !files.is_empty() => return Ok(ClipboardContent::Files(files)),
+            Ok(_) | Err(ClipboardError::ContentNotAvailable) => {}
+            Err(error) => return Err(error.to_string()),
+        }
+
+        let image = match clipboard.get_image() {
+            Ok(image) => image,
+            Err(ClipboardError::ContentNotAvailable) => {
+                return Err("The clipboard holds no image or files to import.".to_string())
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        let pixels = RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned())
+            .ok_or_else(|| "The clipboard image could not be read.".to_string())?;
+        let mut png = Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut png, ImageFormat::Png)
+            .map_err(|error| error.to_string())?;
+
+        Ok(ClipboardContent::Image {
+            file_name: format!("Clipboard {}.png", Local::now().format("%Y-%m-%d %H%M%S")),
+            png: png.into_inner(),
+        })
+    }
+}
@@ -0,0 +1,58 @@
+use crate::services::cloud_drive::CloudFile;
+use serde::Serialize;
+use std::io::Read;
+
+/// Documents larger than this are left in remote storage.
+pub const MAX_DOWNLOAD_BYTES: u64 = 50 * 1024 * 1024;
+/// Upper bound on pages fetched by [`CloudConnector::list_all`], so a runaway listing stops.
+const MAX_PAGES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFilePage {
+    pub files: Vec<CloudFile>,
+    /// Pass back to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// A remote folder Invox can list and download from. Each provider pages its listing
+/// differently; the cursor is whatever the provider needs to continue (a page token, a
+/// Dropbox cursor, a Graph next link or an S3 continuation token).
+pub trait CloudConnector {
+    /// Files directly in the folder, one page at a time; subfolders are not followed.
+    fn list_page(&self, cursor: Option<&str>) -> Result<CloudFilePage, String>;
+
+    /// The file's content, failing when it is larger than [`MAX_DOWNLOAD_BYTES`].
+    fn download(&self, file: &CloudFile) -> Result<Vec<u8>, String>;
+
+    /// Where the file can be found again: a web link when the provider has one, otherwise
+    /// its location in the provider's own terms.
+    fn source_url(&self, file: &CloudFile) -> String;
+
+    fn list_all(&self) -> Result<Vec<CloudFile>, String> {
+        let mut files = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let page = self.list_page(cursor.as_deref())?;
+            files.extend(page.files);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(files),
+            }
+        }
+        Err("The remote folder has too many files to list.".to_string())
+    }
+}
+
+/// Reads a download body, stopping once it passes [`MAX_DOWNLOAD_BYTES`].
+pub fn read_limited(reader: impl Read) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|error| error.to_string())?;
+    if bytes.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err("The file is too large to import.".to_string());
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,913 @@
+use crate::commands::file_operations::persist_buffer;
+use crate::services::batch_import::import_result;
+use crate::services::import_batches::{ImportBatches, ImportSource};
+use crate::services::cloud_connector::{read_limited, CloudConnector, CloudFilePage, MAX_DOWNLOAD_BYTES};
+use crate::services::gemini::mime_for;
+use crate::services::s3_connector::S3Connector;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const KEYRING_SERVICE: &str = "invox-ai";
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+/// How long `link` waits for the browser to come back with the authorization code.
+const AUTHORIZATION_TIMEOUT_SECS: u64 = 5 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudProvider {
+    /// Folder is the folder id from its Drive URL.
+    GoogleDrive,
+    /// Folder is a path such as `/Invoices`.
+    Dropbox,
+    /// Folder is a path relative to the drive root, such as `Invoices/2024`.
+    OneDrive,
+    /// Folder is `bucket` or `bucket/prefix`. Linked with an access key instead of OAuth.
+    S3,
+}
+
+/// Where a provider's OAuth flow runs, and the parameters asking for read-only offline access.
+struct OAuthEndpoints {
+    authorize_url: &'static str,
+    token_url: &'static str,
+    authorize_params: &'static [(&'static str, &'static str)],
+}
+
+impl CloudProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloudProvider::GoogleDrive => "google_drive",
+            CloudProvider::Dropbox => "dropbox",
+            CloudProvider::OneDrive => "one_drive",
+            CloudProvider::S3 => "s3",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "google_drive" => Ok(CloudProvider::GoogleDrive),
+            "dropbox" => Ok(CloudProvider::Dropbox),
+            "one_drive" => Ok(CloudProvider::OneDrive),
+            "s3" => Ok(CloudProvider::S3),
+            other => Err(format!("Unknown cloud drive: {}", other)),
+        }
+    }
+
+    /// Read-only scopes plus whatever each provider needs to hand out a refresh token.
+    /// `None` for providers that do not use OAuth.
+    fn oauth(&self) -> Option<OAuthEndpoints> {
+        match self {
+            CloudProvider::GoogleDrive => Some(OAuthEndpoints {
+                authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+                token_url: "https://oauth2.googleapis.com/token",
+                authorize_params: &[
+                    ("scope", "https://www.googleapis.com/auth/drive.readonly"),
+                    ("access_type", "offline"),
+                    ("prompt", "consent"),
+                ],
+            }),
+            CloudProvider::Dropbox => Some(OAuthEndpoints {
+                authorize_url: "https://www.dropbox.com/oauth2/authorize",
+                token_url: "https://api.dropboxapi.com/oauth2/token",
+                authorize_params: &[
+                    ("scope", "files.metadata.read files.content.read"),
+                    ("token_access_type", "offline"),
+                ],
+            }),
+            CloudProvider::OneDrive => Some(OAuthEndpoints {
+                authorize_url: "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+                token_url: "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+                authorize_params: &[("scope", "offline_access Files.Read")],
+            }),
+            CloudProvider::S3 => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCloudDriveOptions {
+    pub provider: CloudProvider,
+    /// OAuth client registered by the business for a desktop app.
+    pub client_id: String,
+    /// Only Google desktop clients have one.
+    pub client_secret: Option<String>,
+    pub folder: String,
+    pub folder_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkS3Options {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Defaults to `us-east-1`.
+    pub region: Option<String>,
+    /// `bucket` or `bucket/prefix`.
+    pub folder: String,
+    /// Host of an S3-compatible store; AWS when unset.
+    pub endpoint: Option<String>,
+    pub folder_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudDriveLink {
+    pub id: i64,
+    pub provider: CloudProvider,
+    pub folder: String,
+    pub folder_name: Option<String>,
+    /// S3 only.
+    pub region: Option<String>,
+    /// S3-compatible stores only.
+    pub endpoint: Option<String>,
+    pub created_at: String,
+    pub last_synced_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFile {
+    pub remote_id: String,
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+    /// Already pulled into the library.
+    pub imported: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncFailure {
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncSummary {
+    pub link_id: i64,
+    pub listed: usize,
+    pub imported: usize,
+    /// New in the drive but already in the library under the duplicate policy.
+    pub duplicates: usize,
+    /// Not documents Invox can read, or too large to download.
+    pub skipped: usize,
+    pub failures: Vec<CloudSyncFailure>,
+    /// The import batch downloaded files were recorded in; none when nothing was downloaded.
+    pub batch_id: Option<i64>,
+}
+
+/// Secrets kept in the OS keyring, never in the database.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredCredentials {
+    refresh_token: String,
+    client_secret: Option<String>,
+}
+
+/// The S3 secret key, kept in the keyring like OAuth refresh tokens.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredS3Credentials {
+    secret_access_key: String,
+}
+
+/// Remote folders linked over OAuth (Google Drive, Dropbox, OneDrive) or with an access key
+/// (S3). Each provider is a [`CloudConnector`]; files are imported through the same dedup
+/// pipeline as local files and `cloud_drive_items` remembers what was pulled so unchanged
+/// files are not fetched again.
+pub struct CloudDrive;
+
+impl CloudDrive {
+    /// Runs the authorization-code flow with PKCE: opens the consent page in the browser and
+    /// waits on a loopback port for the redirect. Blocks until the user finishes or it times out.
+    pub fn link(conn: &Connection, options: &LinkCloudDriveOptions) -> Result<CloudDriveLink, String> {
+        if options.client_id.trim().is_empty() {
+            return Err("An OAuth client id is required.".to_string());
+        }
+        if options.folder.trim().is_empty() {
+            return Err("Choose the folder to import from.".to_string());
+        }
+        let provider = options.provider;
+        let endpoints = provider
+            .oauth()
+            .ok_or_else(|| "S3 buckets are linked with an access key.".to_string())?;
+
+        let listener = TcpListener::bind("127.0.0.1:0").map_err(|error| error.to_string())?;
+        let port = listener.local_addr().map_err(|error| error.to_string())?.port();
+        let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+        let verifier = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        let state = uuid::Uuid::new_v4().simple().to_string();
+
+        let mut query = vec![
+            ("client_id", options.client_id.trim()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("state", state.as_str()),
+        ];
+        query.extend_from_slice(endpoints.authorize_params);
+        open::that(format!("{}?{}", endpoints.authorize_url, encode_query(&query)))
+            .map_err(|error| format!("Could not open the browser: {}", error))?;
+
+        let code = wait_for_code(&listener, &state)?;
+        let client_secret = options
+            .client_secret
+            .as_deref()
+            .map(str::trim)
+            .filter(|secret| !secret.is_empty());
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("client_id", options.client_id.trim()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("code_verifier", verifier.as_str()),
+        ];
+        if let Some(secret) = client_secret {
+            form.push(("client_secret", secret));
+        }
+        let tokens = token_request(&endpoints, &form)?;
+        let refresh_token = tokens
+            .get("refresh_token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "The drive did not grant offline access. Try linking again.".to_string())?;
+
+        conn.execute(
+            "INSERT INTO cloud_drive_links (provider, client_id, folder, folder_name) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                provider.as_str(),
+                options.client_id.trim(),
+                options.folder.trim(),
+                options.folder_name
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        let link_id = conn.last_insert_rowid();
+        let stored = StoredCredentials {
+            refresh_token: refresh_token.to_string(),
+            client_secret: client_secret.map(str::to_string),
+        };
+        let saved = serde_json::to_string(&stored)
+            .map_err(|error| error.to_string())
+            .and_then(|secret| {
+                keyring_entry(link_id)?
+                    .set_password(&secret)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = saved {
+            let _ = conn.execute("DELETE FROM cloud_drive_links WHERE id = ?1", params![link_id]);
+            return Err(format!(
+                "Could not save the drive credentials in the keyring: {}",
+                error
+            ));
+        }
+
+        Self::get(conn, link_id)
+    }
+
+    /// Links a bucket prefix. The key is checked by listing the prefix before anything is saved.
+    pub fn link_s3(conn: &Connection, options: &LinkS3Options) -> Result<CloudDriveLink, String> {
+        let region = options.region.as_deref().unwrap_or_default();
+        let connector = S3Connector::new(
+            &options.access_key_id,
+            &options.secret_access_key,
+            region,
+            &options.folder,
+            options.endpoint.as_deref(),
+        )?;
+        connector.list_page(None)?;
+
+        conn.execute(
+            "INSERT INTO cloud_drive_links (provider, client_id, folder, folder_name, region, endpoint)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                CloudProvider::S3.as_str(),
+                options.access_key_id.trim(),
+                options.folder.trim().trim_matches('/'),
+                options.folder_name,
+                connector.region(),
+                connector.endpoint()
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        let link_id = conn.last_insert_rowid();
+        let stored = StoredS3Credentials {
+            secret_access_key: options.secret_access_key.trim().to_string(),
+        };
+        let saved = serde_json::to_string(&stored)
+            .map_err(|error| error.to_string())
+            .and_then(|secret| {
+                keyring_entry(link_id)?
+                    .set_password(&secret)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = saved {
+            let _ = conn.execute("DELETE FROM cloud_drive_links WHERE id = ?1", params![link_id]);
+            return Err(format!(
+                "Could not save the bucket credentials in the keyring: {}",
+                error
+            ));
+        }
+
+        Self::get(conn, link_id)
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<CloudDriveLink>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, provider, folder, folder_name, region, endpoint, created_at, last_synced_at
+                 FROM cloud_drive_links ORDER BY id",
+            )
+            .map_err(|error| error.to_string())?;
+        let links = stmt
+            .query_map([], map_link)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        links.into_iter().collect()
+    }
+
+    pub fn get(conn: &Connection, link_id: i64) -> Result<CloudDriveLink, String> {
+        conn.query_row(
+            "SELECT id, provider, folder, folder_name, region, endpoint, created_at, last_synced_at
+             FROM cloud_drive_links WHERE id = ?1",
+            params![link_id],
+            map_link,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Cloud drive link not found: {}", link_id))?
+    }
+
+    /// Forgets the link and its keyring entry. Imported files stay in the library.
+    pub fn unlink(conn: &Connection, link_id: i64) -> Result<(), String> {
+        Self::get(conn, link_id)?;
+        match keyring_entry(link_id)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(error) => return Err(error.to_string()),
+        }
+        conn.execute(
+            "DELETE FROM cloud_drive_items WHERE link_id = ?1",
+            params![link_id],
+        )
+        .map_err(|error| error.to_string())?;
+        conn.execute("DELETE FROM cloud_drive_links WHERE id = ?1", params![link_id])
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// One page of the linked folder, marked with whether each file was already imported.
+    /// Pass the returned cursor to get the next page.
+    pub fn list_files(conn: &Connection, link_id: i64, cursor: Option<&str>) -> Result<CloudFilePage, String> {
+        let mut page = connector(conn, link_id)?.list_page(cursor)?;
+        for file in &mut page.files {
+            file.imported = is_pulled(conn, link_id, file)?;
+        }
+        Ok(page)
+    }
+
+    /// Downloads and imports documents that are new or changed since the last sync.
+    pub fn sync(conn: &Connection, link_id: i64) -> Result<CloudSyncSummary, String> {
+        let connector = connector(conn, link_id)?;
+        let files = connector.list_all()?;
+        let mut summary = CloudSyncSummary {
+            link_id,
+            listed: files.len(),
+            ..CloudSyncSummary::default()
+        };
+
+        for file in files {
+            if is_pulled(conn, link_id, &file)? {
+                continue;
+            }
+            pull(conn, link_id, connector.as_ref(), &file, &mut summary)?;
+        }
+        if let Some(batch_id) = summary.batch_id {
+            ImportBatches::finish(conn, batch_id, false)?;
+        }
+
+        conn.execute(
+            "UPDATE cloud_drive_links SET last_synced_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), link_id],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(summary)
+    }
+
+    /// Imports the chosen files from the linked folder, even ones pulled before; the duplicate
+    /// policy still decides whether a new copy is kept.
+    pub fn import_files(
+        conn: &Connection,
+        link_id: i64,
+        remote_ids: &[String],
+    ) -> Result<CloudSyncSummary, String> {
+        let connector = connector(conn, link_id)?;
+        let files: Vec<CloudFile> = connector
+            .list_all()?
+            .into_iter()
+            .filter(|file| remote_ids.contains(&file.remote_id))
+            .collect();
+        let mut summary = CloudSyncSummary {
+            link_id,
+            listed: files.len(),
+            ..CloudSyncSummary::default()
+        };
+        for remote_id in remote_ids {
+            if !files.iter().any(|file| &file.remote_id == remote_id) {
+                summary.failures.push(CloudSyncFailure {
+                    name: remote_id.clone(),
+                    error: "The file is no longer in the linked folder.".to_string(),
+                });
+            }
+        }
+        for file in &files {
+            pull(conn, link_id, connector.as_ref(), file, &mut summary)?;
+        }
+        if let Some(batch_id) = summary.batch_id {
+            ImportBatches::finish(conn, batch_id, false)?;
+        }
+        Ok(summary)
+    }
+}
+
+/// The connector for a linked folder, with credentials from the keyring.
+fn connector(conn: &Connection, link_id: i64) -> Result<Box<dyn CloudConnector>, String> {
+    let link = CloudDrive::get(conn, link_id)?;
+    if link.provider != CloudProvider::S3 {
+        return Ok(Box::new(DriveSession::open(conn, link_id)?));
+    }
+
+    let access_key_id: String = conn
+        .query_row(
+            "SELECT client_id FROM cloud_drive_links WHERE id = ?1",
+            params![link_id],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?;
+    let secret = keyring_entry(link_id)?
+        .get_password()
+        .map_err(|error| format!("The bucket credentials are missing from the keyring: {}", error))?;
+    let stored: StoredS3Credentials = serde_json::from_str(&secret).map_err(|error| error.to_string())?;
+    Ok(Box::new(S3Connector::new(
+        &access_key_id,
+        &stored.secret_access_key,
+        link.region.as_deref().unwrap_or_default(),
+        &link.folder,
+        link.endpoint.as_deref(),
+    )?))
+}
+
+/// Downloads one file, imports it and remembers it in `cloud_drive_items`. The summary's
+/// import batch is started with the first download.
+fn pull(
+    conn: &Connection,
+    link_id: i64,
+    connector: &dyn CloudConnector,
+    file: &CloudFile,
+    summary: &mut CloudSyncSummary,
+) -> Result<(), String> {
+    if mime_for(Path::new(&file.name)).is_none() || file.size_bytes > MAX_DOWNLOAD_BYTES {
+        summary.skipped += 1;
+        return Ok(());
+    }
+    let batch_id = match summary.batch_id {
+        Some(batch_id) => batch_id,
+        None => {
+            let label = CloudDrive::get(conn, link_id)?;
+            *summary.batch_id.insert(ImportBatches::begin(
+                conn,
+                ImportSource::CloudDrive,
+                Some(label.folder_name.as_deref().unwrap_or(&label.folder)),
+            )?)
+        }
+    };
+    let imported = connector
+        .download(file)
+        .and_then(|bytes| persist_buffer(&file.name, &bytes, Some(&connector.source_url(file))));
+    ImportBatches::record(conn, batch_id, &import_result(file.name.clone(), imported.clone()))?;
+    match imported.as_deref().map(|outcome| outcome.split_once(':')) {
+        Ok(Some((kind, file_id))) => {
+            if kind == "DUPLICATE" {
+                summary.duplicates += 1;
+            } else {
+                summary.imported += 1;
+            }
+            conn.execute(
+                "INSERT OR REPLACE INTO cloud_drive_items (link_id, remote_id, modified_at, file_id, imported_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![link_id, file.remote_id, file.modified_at, file_id, Utc::now().to_rfc3339()],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(None) => summary.failures.push(CloudSyncFailure {
+            name: file.name.clone(),
+            error: "Unexpected import result.".to_string(),
+        }),
+        Err(error) => summary.failures.push(CloudSyncFailure {
+            name: file.name.clone(),
+            error: error.clone(),
+        }),
+    }
+    Ok(())
+}
+
+/// A linked OAuth folder with a fresh access token.
+struct DriveSession {
+    provider: CloudProvider,
+    folder: String,
+    access_token: String,
+    agent: ureq::Agent,
+}
+
+impl DriveSession {
+    fn open(conn: &Connection, link_id: i64) -> Result<Self, String> {
+        let (provider, client_id, folder): (String, String, String) = conn
+            .query_row(
+                "SELECT provider, client_id, folder FROM cloud_drive_links WHERE id = ?1",
+                params![link_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("Cloud drive link not found: {}", link_id))?;
+        let provider = CloudProvider::parse(&provider)?;
+
+        let secret = keyring_entry(link_id)?
+            .get_password()
+            .map_err(|error| format!("The drive credentials are missing from the keyring: {}", error))?;
+        let stored: StoredCredentials = serde_json::from_str(&secret).map_err(|error| error.to_string())?;
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", stored.refresh_token.as_str()),
+            ("client_id", client_id.as_str()),
+        ];
+        if let Some(secret) = stored.client_secret.as_deref() {
+            form.push(("client_secret", secret));
+        }
+        let endpoints = provider
+            .oauth()
+            .ok_or_else(|| "S3 buckets are linked with an access key.".to_string())?;
+        let tokens = token_request(&endpoints, &form)?;
+        let access_token = tokens
+            .get("access_token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "The drive did not return an access token. Link it again.".to_string())?
+            .to_string();
+        // Providers that rotate refresh tokens invalidate the old one.
+        if let Some(rotated) = tokens.get("refresh_token").and_then(Value::as_str) {
+            let stored = StoredCredentials {
+                refresh_token: rotated.to_string(),
+                client_secret: stored.client_secret,
+            };
+            let secret = serde_json::to_string(&stored).map_err(|error| error.to_string())?;
+            keyring_entry(link_id)?
+                .set_password(&secret)
+                .map_err(|error| error.to_string())?;
+        }
+
+        Ok(DriveSession {
+            provider,
+            folder,
+            access_token,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .build(),
+        })
+    }
+
+    fn bearer(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+}
+
+impl CloudConnector for DriveSession {
+    fn list_page(&self, cursor: Option<&str>) -> Result<CloudFilePage, String> {
+        let mut files = Vec::new();
+        let next_cursor = match self.provider {
+            CloudProvider::GoogleDrive => {
+                let query = format!(
+                    "'{}' in parents and trashed = false and mimeType != 'application/vnd.google-apps.folder'",
+                    self.folder.replace('\'', "\\'")
+                );
+                let mut request = self
+                    .agent
+                    .get("https://www.googleapis.com/drive/v3/files")
+                    .set("Authorization", &self.bearer())
+                    .query("q", &query)
+                    .query("fields", "nextPageToken, files(id, name, size, modifiedTime)")
+                    .query("pageSize", "1000");
+                if let Some(token) = cursor {
+                    request = request.query("pageToken", token);
+                }
+                let page = read_json(request.call())?;
+                for file in page.get("files").and_then(Value::as_array).into_iter().flatten() {
+                    files.push(cloud_file(
+                        file.get("id"),
+                        file.get("name"),
+                        file.get("size")
+                            .and_then(Value::as_str)
+                            .and_then(|size| size.parse().ok()),
+                        file.get("modifiedTime"),
+                    ));
+                }
+                page.get("nextPageToken")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            }
+            CloudProvider::Dropbox => {
+                let (url, body) = match cursor {
+                    Some(cursor) => (
+                        "https://api.dropboxapi.com/2/files/list_folder/continue",
+                        json!({ "cursor": cursor }),
+                    ),
+                    None => (
+                        "https://api.dropboxapi.com/2/files/list_folder",
+                        json!({ "path": self.folder.trim_end_matches('/') }),
+                    ),
+                };
+                let page = read_json(
+                    self.agent
+                        .post(url)
+                        .set("Authorization", &self.bearer())
+                        .set("Content-Type", "application/json")
+                        .send_string(&body.to_string()),
+                )?;
+                for entry in page
+                    .get("entries")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    if entry.get(".tag").and_then(Value::as_str) != Some("file") {
+                        continue;
+                    }
+                    files.push(cloud_file(
+                        entry.get("id"),
+                        entry.get("name"),
+                        entry.get("size").and_then(Value::as_u64),
+                        entry.get("server_modified"),
+                    ));
+                }
+                if page.get("has_more").and_then(Value::as_bool) == Some(true) {
+                    page.get("cursor").and_then(Value::as_str).map(str::to_string)
+                } else {
+                    None
+                }
+            }
+            CloudProvider::OneDrive => {
+                let url = match cursor {
+                    // Graph hands back the full URL of the next page.
+                    Some(next_link) => next_link.to_string(),
+                    None => {
+                        let path = self
+                            .folder
+                            .trim_matches('/')
+                            .split('/')
+                            .map(|segment| urlencoding::encode(segment).into_owned())
+                            .collect::<Vec<_>>()
+                            .join("/");
+                        format!(
+                            "https://graph.microsoft.com/v1.0/me/drive/root:/{}:/children?$top=200",
+                            path
+                        )
+                    }
+                };
+                if !url.starts_with("https://graph.microsoft.com/") {
+                    return Err("Invalid page cursor.".to_string());
+                }
+                let page = read_json(self.agent.get(&url).set("Authorization", &self.bearer()).call())?;
+                for item in page.get("value").and_then(Value::as_array).into_iter().flatten() {
+                    if item.get("file").is_none() {
+                        continue;
+                    }
+                    files.push(cloud_file(
+                        item.get("id"),
+                        item.get("name"),
+                        item.get("size").and_then(Value::as_u64),
+                        item.get("lastModifiedDateTime"),
+                    ));
+                }
+                page.get("@odata.nextLink")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            }
+            CloudProvider::S3 => return Err("S3 buckets are read through the S3 connector.".to_string()),
+        };
+        Ok(CloudFilePage { files, next_cursor })
+    }
+
+    fn source_url(&self, file: &CloudFile) -> String {
+        match self.provider {
+            CloudProvider::GoogleDrive => format!(
+                "https://drive.google.com/file/d/{}/view",
+                urlencoding::encode(&file.remote_id)
+            ),
+            CloudProvider::Dropbox => format!(
+                "https://www.dropbox.com/home{}?preview={}",
+                self.folder.trim_end_matches('/'),
+                urlencoding::encode(&file.name)
+            ),
+            CloudProvider::OneDrive | CloudProvider::S3 => format!(
+                "{}:/{}/{}",
+                self.provider.as_str(),
+                self.folder.trim_matches('/'),
+                file.name
+            ),
+        }
+    }
+
+    fn download(&self, file: &CloudFile) -> Result<Vec<u8>, String> {
+        let response = match self.provider {
+            CloudProvider::GoogleDrive => self
+                .agent
+                .get(&format!(
+                    "https://www.googleapis.com/drive/v3/files/{}",
+                    urlencoding::encode(&file.remote_id)
+                ))
+                .set("Authorization", &self.bearer())
+                .query("alt", "media")
+                .call(),
+            CloudProvider::Dropbox => self
+                .agent
+                .post("https://content.dropboxapi.com/2/files/download")
+                .set("Authorization", &self.bearer())
+                .set("Dropbox-API-Arg", &json!({ "path": file.remote_id }).to_string())
+                .call(),
+            CloudProvider::OneDrive => self
+                .agent
+                .get(&format!(
+                    "https://graph.microsoft.com/v1.0/me/drive/items/{}/content",
+                    urlencoding::encode(&file.remote_id)
+                ))
+                .set("Authorization", &self.bearer())
+                .call(),
+            CloudProvider::S3 => return Err("S3 buckets are read through the S3 connector.".to_string()),
+        }
+        .map_err(request_error)?;
+
+        read_limited(response.into_reader())
+    }
+}
+
+fn map_link(row: &Row) -> rusqlite::Result<Result<CloudDriveLink, String>> {
+    let provider: String = row.get(1)?;
+    Ok(CloudProvider::parse(&provider).and_then(|provider| {
+        Ok(CloudDriveLink {
+            id: row.get(0).map_err(|error| error.to_string())?,
+            provider,
+            folder: row.get(2).map_err(|error| error.to_string())?,
+            folder_name: row.get(3).map_err(|error| error.to_string())?,
+            region: row.get(4).map_err(|error| error.to_string())?,
+            endpoint: row.get(5).map_err(|error| error.to_string())?,
+            created_at: row.get(6).map_err(|error| error.to_string())?,
+            last_synced_at: row.get(7).map_err(|error| error.to_string())?,
+        })
+    }))
+}
+
+fn cloud_file(
+    id: Option<&Value>,
+    name: Option<&Value>,
+    size_bytes: Option<u64>,
+    modified_at: Option<&Value>,
+) -> CloudFile {
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    CloudFile {
+        remote_id: text(id).unwrap_or_default(),
+        name: text(name).unwrap_or_default(),
+        size_bytes: size_bytes.unwrap_or(0),
+        modified_at: text(modified_at),
+        imported: false,
+    }
+}
+
+/// Pulled before and not modified in the drive since.
+fn is_pulled(conn: &Connection, link_id: i64, file: &CloudFile) -> Result<bool, String> {
+    let pulled: Option<Option<String>> = conn
+        .query_row(
+            "SELECT modified_at FROM cloud_drive_items WHERE link_id = ?1 AND remote_id = ?2",
+            params![link_id, file.remote_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?;
+    Ok(pulled.is_some_and(|modified_at| modified_at == file.modified_at))
+}
+
+fn keyring_entry(link_id: i64) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("cloud-drive-{}", link_id))
+        .map_err(|error| error.to_string())
+}
+
+fn token_request(endpoints: &OAuthEndpoints, form: &[(&str, &str)]) -> Result<Value, String> {
+    read_json(
+        ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .post(endpoints.token_url)
+            .send_form(form),
+    )
+}
+
+fn read_json(response: Result<ureq::Response, ureq::Error>) -> Result<Value, String> {
+    let body = response
+        .map_err(request_error)?
+        .into_string()
+        .map_err(|error| error.to_string())?;
+    serde_json::from_str(&body).map_err(|error| error.to_string())
+}
+
+fn request_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(401, _) | ureq::Error::Status(403, _) => {
+            "The drive refused access. Link it again.".to_string()
+        }
+        ureq::Error::Status(404, _) => "The linked folder was not found in the drive.".to_string(),
+        other => format!("Cloud drive request failed: {}", other),
+    }
+}
+
+fn encode_query(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Accepts redirects on `listener` until one carries `state`, and returns its code.
+fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|error| error.to_string())?;
+    let deadline = Instant::now() + Duration::from_secs(AUTHORIZATION_TIMEOUT_SECS);
+    while Instant::now() < deadline {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(200));
+                continue;
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        stream.set_nonblocking(false).map_err(|error| error.to_string())?;
+        let mut request_line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .map_err(|error| error.to_string())?;
+
+        let query = request_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|target| target.split_once('?'))
+            .map(|(_, query)| query)
+            .unwrap_or_default();
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .and_then(|(_, value)| {
+                    urlencoding::decode(&value.replace('+', " "))
+                        .ok()
+                        .map(|value| value.into_owned())
+                })
+        };
+
+        let outcome = match (param("state"), param("code"), param("error")) {
+            (Some(returned), Some(code), _) if returned == state => Some(Ok(code)),
+            (Some(returned), _, Some(error)) if returned == state => {
+                Some(Err(format!("The drive did not grant access: {}", error)))
+            }
+            _ => None,
+        };
+        let message = match &outcome {
+            Some(Ok(_)) => "Invox AI is linked. You can close this tab.",
+            Some(Err(_)) => "Linking was cancelled. You can close this tab.",
+            None => "Waiting for the drive to respond.",
+        };
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nConnection: close\r\n\r\n<p>{}</p>",
+            message
+        );
+        if let Some(outcome) = outcome {
+            return outcome;
+        }
+    }
+    Err("Timed out waiting for the drive to authorize Invox AI.".to_string())
+}
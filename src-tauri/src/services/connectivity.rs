@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+/// The extraction provider's host. Any HTTP answer, even an error status, proves it is
+/// reachable; only transport failures (DNS, refused, timeout) count as offline.
+const PROBE_URL: &str = "https://generativelanguage.googleapis.com/";
+const PROBE_TIMEOUT_SECS: u64 = 5;
+
+pub struct Connectivity;
+
+impl Connectivity {
+    pub fn is_online() -> bool {
+        let result = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+            .build()
+            .head(PROBE_URL)
+            .call();
+        matches!(result, Ok(_) | Err(ureq::Error::Status(..)))
+    }
+}
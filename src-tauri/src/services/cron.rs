@@ -0,0 +1,154 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+/// How far ahead `next_after` searches before giving up (e.g. for "0 0 31 2 *").
+const SEARCH_LIMIT_DAYS: i64 = 366 * 5;
+
+/// A five-field cron expression: minute, hour, day of month, month, day of week.
+///
+/// Fields accept `*`, single values, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// comma-separated lists. Day of week runs 0-6 from Sunday (7 is also Sunday).
+/// The shortcuts `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are accepted.
+#[derive(Debug, Clone)]
+pub struct CronExpression {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Invalid cron expression \"{}\": expected 5 fields (minute hour day month weekday).",
+                expression
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day of week")?;
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, "day of month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            days_of_week,
+            day_of_month_any: fields[2] == "*",
+            day_of_week_any: fields[4] == "*",
+        })
+    }
+
+    /// The first matching minute strictly after `after`, in local time.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(SEARCH_LIMIT_DAYS);
+        let mut current = start;
+
+        while current < limit {
+            if !self.months[current.month() as usize] {
+                current = start_of_next_month(current)?;
+                continue;
+            }
+            if !self.day_matches(current.date()) {
+                current = current.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours[current.hour() as usize] {
+                current = current.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes[current.minute() as usize] {
+                current += Duration::minutes(1);
+                continue;
+            }
+
+            // Times skipped by a DST change do not exist locally; move on to the next match.
+            match Local.from_local_datetime(&current).earliest() {
+                Some(found) => return Some(found),
+                None => current += Duration::minutes(1),
+            }
+        }
+
+        None
+    }
+
+    /// Classic cron semantics: when both day fields are restricted, either may match.
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let by_month = self.days_of_month[date.day() as usize];
+        let by_week = self.days_of_week[date.weekday().num_days_from_sunday() as usize];
+        match (self.day_of_month_any, self.day_of_week_any) {
+            (true, true) => true,
+            (false, true) => by_month,
+            (true, false) => by_week,
+            (false, false) => by_month || by_week,
+        }
+    }
+}
+
+fn start_of_next_month(current: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = if current.month() == 12 {
+        (current.year() + 1, 1)
+    } else {
+        (current.year(), current.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+/// Expands one field into a lookup table indexed by value (`0..=max`).
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<Vec<bool>, String> {
+    let invalid = || format!("Invalid {} field \"{}\".", name, field);
+    let mut allowed = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                start.parse::<u32>().map_err(|_| invalid())?,
+                end.parse::<u32>().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse::<u32>().map_err(|_| invalid())?;
+            // "5/15" means "from 5, every 15".
+            (value, if part.contains('/') { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "{} in \"{}\" must be between {} and {}.",
+                name, field, min, max
+            ));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
@@ -0,0 +1,187 @@
+use crate::services::settings::Settings;
+use crate::services::spreadsheet::{Cell, Sheet};
+use chrono::format::{Item, StrftimeItems};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const PROFILES_KEY: &str = "export.csv_profiles";
+pub const DEFAULT_PROFILE: &str = "default";
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvDelimiter {
+    Comma,
+    Semicolon,
+    Tab,
+}
+
+impl CsvDelimiter {
+    fn as_byte(&self) -> u8 {
+        match self {
+            CsvDelimiter::Comma => b',',
+            CsvDelimiter::Semicolon => b';',
+            CsvDelimiter::Tab => b'\t',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecimalSeparator {
+    Dot,
+    Comma,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark, which Excel needs to detect the encoding.
+    Utf8Bom,
+}
+
+/// How CSV files are written. Stored per export profile; missing fields fall back to
+/// plain RFC 4180 output with ISO dates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvOptions {
+    pub delimiter: CsvDelimiter,
+    pub decimal_separator: DecimalSeparator,
+    /// chrono `strftime` pattern, e.g. `%d.%m.%Y`.
+    pub date_format: String,
+    pub encoding: CsvEncoding,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: CsvDelimiter::Comma,
+            decimal_separator: DecimalSeparator::Dot,
+            date_format: "%Y-%m-%d".to_string(),
+            encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.date_format.trim().is_empty()
+            || StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error))
+        {
+            return Err(format!("Invalid date format \"{}\".", self.date_format));
+        }
+        Ok(())
+    }
+
+    /// Writes `sheet` to `path`, quoting fields that contain the delimiter.
+    pub fn write(&self, path: &Path, sheet: &Sheet) -> Result<(), String> {
+        let mut file = File::create(path).map_err(|error| error.to_string())?;
+        if self.encoding == CsvEncoding::Utf8Bom {
+            file.write_all(UTF8_BOM).map_err(|error| error.to_string())?;
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter.as_byte())
+            .from_writer(file);
+        writer
+            .write_record(&sheet.headers)
+            .map_err(|error| error.to_string())?;
+        for row in &sheet.rows {
+            writer
+                .write_record(row.iter().map(|cell| self.format_cell(cell)))
+                .map_err(|error| error.to_string())?;
+        }
+        writer.flush().map_err(|error| error.to_string())
+    }
+
+    /// Writes one file per sheet. A single sheet goes to `path` itself; otherwise each
+    /// sheet gets `<stem>-<sheet name>.csv` next to it. Returns the written paths.
+    pub fn write_sheets(&self, path: &Path, sheets: &[Sheet]) -> Result<Vec<PathBuf>, String> {
+        if let [sheet] = sheets {
+            self.write(path, sheet)?;
+            return Ok(vec![path.to_path_buf()]);
+        }
+
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("export");
+        let mut written = Vec::new();
+        for sheet in sheets {
+            let dest = path.with_file_name(format!("{}-{}.csv", stem, sheet.name.to_lowercase()));
+            self.write(&dest, sheet)?;
+            written.push(dest);
+        }
+        Ok(written)
+    }
+
+    fn format_cell(&self, cell: &Cell) -> String {
+        match cell {
+            Cell::Text(value) => value.clone(),
+            Cell::Number(value) => {
+                let value = value.to_string();
+                match self.decimal_separator {
+                    DecimalSeparator::Dot => value,
+                    DecimalSeparator::Comma => value.replace('.', ","),
+                }
+            }
+            Cell::Date(date) => date.format(&self.date_format).to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+/// Named CSV settings, kept as one JSON map in app settings.
+pub struct CsvProfiles;
+
+impl CsvProfiles {
+    pub fn list(conn: &Connection) -> Result<BTreeMap<String, CsvOptions>, String> {
+        let mut profiles: BTreeMap<String, CsvOptions> = match Settings::get(conn, PROFILES_KEY)? {
+            Some(raw) => serde_json::from_str(&raw).map_err(|error| error.to_string())?,
+            None => BTreeMap::new(),
+        };
+        profiles.entry(DEFAULT_PROFILE.to_string()).or_default();
+        Ok(profiles)
+    }
+
+    /// Options for `profile`, or the default profile when none is named.
+    pub fn get(conn: &Connection, profile: Option<&str>) -> Result<CsvOptions, String> {
+        let name = profile.unwrap_or(DEFAULT_PROFILE);
+        Self::list(conn)?
+            .remove(name)
+            .ok_or_else(|| format!("CSV export profile not found: {}", name))
+    }
+
+    pub fn save(conn: &Connection, name: &str, options: CsvOptions) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty.".to_string());
+        }
+        options.validate()?;
+        let mut profiles = Self::list(conn)?;
+        profiles.insert(name.to_string(), options);
+        Self::store(conn, &profiles)
+    }
+
+    pub fn delete(conn: &Connection, name: &str) -> Result<(), String> {
+        if name == DEFAULT_PROFILE {
+            return Err("The default CSV profile cannot be deleted.".to_string());
+        }
+        let mut profiles = Self::list(conn)?;
+        if profiles.remove(name).is_none() {
+            return Err(format!("CSV export profile not found: {}", name));
+        }
+        Self::store(conn, &profiles)
+    }
+
+    fn store(conn: &Connection, profiles: &BTreeMap<String, CsvOptions>) -> Result<(), String> {
+        let raw = serde_json::to_string(profiles).map_err(|error| error.to_string())?;
+        Settings::set(conn, PROFILES_KEY, &raw)
+    }
+}
@@ -0,0 +1,266 @@
+use crate::db::{backups_dir, db_path, get_connection, reset_connections, schema_migrations};
+use crate::services::database_encryption::DatabaseEncryption;
+use chrono::{Local, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Prefix of the timestamped copies scheduled backups write, which pruning matches on.
+const BACKUP_PREFIX: &str = "invox-backup-";
+const BACKUP_EXTENSION: &str = "db";
+/// Prefix of the copy taken before a restore, which scheduled backups never prune.
+const PRE_RESTORE_PREFIX: &str = "invox-pre-restore-";
+/// Pages copied per step; other connections can write between steps.
+const PAGES_PER_STEP: i32 = 256;
+const STEP_PAUSE: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRowCount {
+    pub table: String,
+    /// 0 for a table that did not exist.
+    pub before: i64,
+    pub after: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub backup_path: String,
+    /// The database as it was before the restore, in case it has to be undone.
+    pub safety_copy_path: String,
+    /// Every table in either database, by name.
+    pub tables: Vec<TableRowCount>,
+}
+
+/// Copies of `app.db` taken with SQLite's online backup API, so imports and parsing can
+/// carry on while the copy is made. Documents in the storage folder are not included.
+pub struct DatabaseBackup;
+
+impl DatabaseBackup {
+    /// Writes a consistent copy of the database to `target`. The copy is written next to
+    /// it first and renamed into place, so an interrupted backup never leaves a partial
+    /// file under the requested name.
+    pub fn create(conn: &Connection, target: &Path) -> Result<BackupResult, String> {
+        if target.is_dir() {
+            return Err("Choose a file to back up to, not a folder.".to_string());
+        }
+        if let Some(parent) = target.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+        }
+
+        let partial = target.with_extension("partial");
+        let _ = fs::remove_file(&partial);
+        if let Err(error) = copy_to(conn, &partial) {
+            let _ = fs::remove_file(&partial);
+            return Err(error);
+        }
+        fs::rename(&partial, target).map_err(|error| error.to_string())?;
+
+        let size_bytes = fs::metadata(target).map_err(|error| error.to_string())?.len();
+        Ok(BackupResult {
+            path: target.to_string_lossy().into_owned(),
+            size_bytes,
+            created_at: Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Backs up to a new timestamped file in `directory` and deletes the oldest timestamped
+    /// copies there beyond `keep`. Other files in the folder are left alone.
+    pub fn create_timestamped(
+        conn: &Connection,
+        directory: &Path,
+        keep: usize,
+    ) -> Result<BackupResult, String> {
+        let name = format!(
+            "{}{}.{}",
+            BACKUP_PREFIX,
+            Local::now().format("%Y%m%d-%H%M%S"),
+            BACKUP_EXTENSION
+        );
+        let result = Self::create(conn, &directory.join(name))?;
+
+        let mut copies: Vec<PathBuf> = fs::read_dir(directory)
+            .map_err(|error| error.to_string())?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().and_then(|extension| extension.to_str()) == Some(BACKUP_EXTENSION)
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+            })
+            .collect();
+        // The timestamp sorts lexically, newest last.
+        copies.sort();
+        let excess = copies.len().saturating_sub(keep.max(1));
+        for path in copies.into_iter().take(excess) {
+            fs::remove_file(&path).map_err(|error| error.to_string())?;
+        }
+
+        Ok(result)
+    }
+
+    /// Replaces the database with the backup at `source`. The backup is checked first and
+    /// the current database copied to the backups folder. The backup API then writes the
+    /// backup over the live database in a single step, which SQLite applies as one write
+    /// transaction: connections other commands hold open see either the old database or the
+    /// restored one, never a mix, so they do not need closing. The schema is brought up to
+    /// date afterwards, so backups made by older versions of the app can be restored.
+    /// Documents deleted since the backup was made show up in the integrity scan.
+    pub fn restore(conn: &mut Connection, source: &Path) -> Result<RestoreReport, String> {
+        validate(source)?;
+        let live = db_path().map_err(|error| error.to_string())?;
+        if fs::canonicalize(source).ok() == fs::canonicalize(&live).ok() {
+            return Err("Choose a backup, not the database in use.".to_string());
+        }
+        match (
+            DatabaseEncryption::is_encrypted(source),
+            DatabaseEncryption::is_encrypted(&live),
+        ) {
+            (true, false) => {
+                return Err("The backup is encrypted; turn on database encryption first.".to_string())
+            }
+            (false, true) => {
+                return Err("The backup is not encrypted; turn off database encryption first.".to_string())
+            }
+            _ => {}
+        }
+
+        let before = row_counts(conn)?;
+        let safety_copy = Self::create(
+            conn,
+            &backups_dir().map_err(|error| error.to_string())?.join(format!(
+                "{}{}.{}",
+                PRE_RESTORE_PREFIX,
+                Local::now().format("%Y%m%d-%H%M%S"),
+                BACKUP_EXTENSION
+            )),
+        )?;
+
+        {
+            let backup = DatabaseEncryption::open(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            Backup::new(&backup, conn)
+                .map_err(|error| error.to_string())?
+                .run_to_completion(-1, STEP_PAUSE, None)
+                .map_err(|error| format!("The backup could not be restored: {}", error))?;
+        }
+
+        // Pooled connections have the restored schema cached, and the next connection opened
+        // brings it up to date.
+        reset_connections();
+        let restored = get_connection().map_err(|error| error.to_string())?;
+        let after = row_counts(&restored)?;
+        let mut tables: Vec<String> = before.keys().chain(after.keys()).cloned().collect();
+        tables.sort();
+        tables.dedup();
+
+        Ok(RestoreReport {
+            backup_path: source.to_string_lossy().into_owned(),
+            safety_copy_path: safety_copy.path,
+            tables: tables
+                .into_iter()
+                .map(|table| TableRowCount {
+                    before: before.get(&table).copied().unwrap_or(0),
+                    after: after.get(&table).copied().unwrap_or(0),
+                    table,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Checks that `source` is an intact Invox AI database no newer than this version of the
+/// app.
+fn validate(source: &Path) -> Result<(), String> {
+    if !source.is_file() {
+        return Err(format!("Backup not found: {}", source.display()));
+    }
+    let not_a_backup = || "The file is not an Invox AI backup.".to_string();
+    let backup = DatabaseEncryption::open(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let integrity: String = backup
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|_| not_a_backup())?;
+    if integrity != "ok" {
+        return Err(format!("The backup is damaged: {}", integrity));
+    }
+
+    let has_table = |name: &str| {
+        backup
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [name],
+                |_row| Ok(()),
+            )
+            .optional()
+            .map(|found| found.is_some())
+            .map_err(|error| error.to_string())
+    };
+    if !has_table("files")? {
+        return Err(not_a_backup());
+    }
+    if has_table("_sqlx_migrations")? {
+        let version: Option<i64> = backup
+            .query_row("SELECT MAX(version) FROM _sqlx_migrations", [], |row| row.get(0))
+            .map_err(|error| error.to_string())?;
+        let latest = schema_migrations()
+            .iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or(0);
+        if version.unwrap_or(0) > latest {
+            return Err(
+                "The backup was made by a newer version of Invox AI; update the app first.".to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Rows in each ordinary table; full-text and other virtual tables are rebuilt from them.
+fn row_counts(conn: &Connection) -> Result<BTreeMap<String, i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM pragma_table_list
+             WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .map_err(|error| error.to_string())?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let mut counts = BTreeMap::new();
+    for table in tables {
+        let count: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| {
+                row.get(0)
+            })
+            .map_err(|error| error.to_string())?;
+        counts.insert(table, count);
+    }
+    Ok(counts)
+}
+
+fn copy_to(conn: &Connection, path: &Path) -> Result<(), String> {
+    let mut destination = Connection::open(path).map_err(|error| error.to_string())?;
+    DatabaseEncryption::key_like_live(&destination)?;
+    let backup = Backup::new(conn, &mut destination).map_err(|error| error.to_string())?;
+    backup
+        .run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)
+        .map_err(|error| format!("The backup could not be completed: {}", error))
+}
@@ -0,0 +1,197 @@
+use crate::db::{db_path, with_exclusive_connection};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+const KEYRING_SERVICE: &str = "invox-ai";
+const KEYRING_USER: &str = "database-passphrase";
+/// First bytes of every plaintext SQLite file; SQLCipher encrypts them with the rest.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+const MIN_PASSPHRASE_CHARS: usize = 12;
+
+/// The keyring is slow to query, so the passphrase is read once per run.
+static PASSPHRASE: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseEncryptionStatus {
+    pub encrypted: bool,
+    /// Whether the OS keyring holds a passphrase for the database.
+    pub passphrase_stored: bool,
+}
+
+/// At-rest encryption of `app.db` with SQLCipher. The passphrase lives in the OS keyring and
+/// SQLCipher derives the key from it with PBKDF2 and a salt kept in the file, so the
+/// passphrase alone is enough to open a copy elsewhere. Whether the database is encrypted
+/// is read from the file itself, as there is nowhere readable to keep a setting before it is
+/// unlocked.
+pub struct DatabaseEncryption;
+
+impl DatabaseEncryption {
+    /// A missing or empty file is not encrypted.
+    pub fn is_encrypted(path: &Path) -> bool {
+        let mut header = [0u8; 16];
+        match File::open(path).and_then(|mut file| file.read_exact(&mut header)) {
+            Ok(()) => &header != SQLITE_HEADER,
+            Err(_) => false,
+        }
+    }
+
+    pub fn status() -> Result<DatabaseEncryptionStatus, String> {
+        Ok(DatabaseEncryptionStatus {
+            encrypted: Self::is_encrypted(&db_path().map_err(|error| error.to_string())?),
+            passphrase_stored: passphrase()?.is_some(),
+        })
+    }
+
+    /// Keys a connection just opened on `path` when the file is encrypted. Runs before
+    /// anything else reads from the connection.
+    pub fn unlock(conn: &Connection, path: &Path) -> Result<(), String> {
+        if !Self::is_encrypted(path) {
+            return Ok(());
+        }
+        let passphrase = passphrase()?.ok_or_else(|| {
+            "The database is encrypted and its passphrase is not in the keyring.".to_string()
+        })?;
+        conn.pragma_update(None, "key", &passphrase)
+            .map_err(|error| error.to_string())?;
+        // A wrong key only shows once a page is read.
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_row| Ok(()))
+            .map_err(|_| "The passphrase in the keyring does not unlock the database.".to_string())
+    }
+
+    /// Opens a database file other than `app.db`, such as a backup, unlocking it with the
+    /// stored passphrase when it is encrypted.
+    pub fn open(path: &Path, flags: OpenFlags) -> Result<Connection, String> {
+        let conn = Connection::open_with_flags(path, flags).map_err(|error| error.to_string())?;
+        Self::unlock(&conn, path)?;
+        Ok(conn)
+    }
+
+    /// Keys a new, empty database the way `app.db` is keyed, so copies of an encrypted
+    /// database are encrypted too.
+    pub fn key_like_live(conn: &Connection) -> Result<(), String> {
+        if !Self::is_encrypted(&db_path().map_err(|error| error.to_string())?) {
+            return Ok(());
+        }
+        let passphrase = passphrase()?.ok_or_else(|| {
+            "The database is encrypted and its passphrase is not in the keyring.".to_string()
+        })?;
+        conn.pragma_update(None, "key", &passphrase)
+            .map_err(|error| error.to_string())
+    }
+
+    /// Encrypts a plaintext database with `passphrase`, or changes the passphrase of an
+    /// encrypted one. The encrypted copy is written next to `app.db` and renamed over it;
+    /// SQLCipher cannot rekey a database in WAL mode in place, and its backup API does not
+    /// copy between differently keyed databases. No other connection is open meanwhile, so
+    /// nothing written during the copy is left behind in the old file.
+    pub fn encrypt(passphrase: &str) -> Result<(), String> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(format!(
+                "Use a passphrase of at least {} characters.",
+                MIN_PASSPHRASE_CHARS
+            ));
+        }
+        let path = db_path().map_err(|error| error.to_string())?;
+        let current = self::passphrase()?;
+
+        with_exclusive_connection(|conn| {
+            let encrypted = path.with_extension("encrypting");
+            export(&conn, &encrypted, passphrase)?;
+            checkpoint(conn)?;
+            // Stored before the swap, so the new file is never on disk without its
+            // passphrase in the keyring; a failed swap puts the previous passphrase back.
+            store_passphrase(Some(passphrase))?;
+            if let Err(error) = fs::rename(&encrypted, &path) {
+                let _ = fs::remove_file(&encrypted);
+                store_passphrase(current.as_deref())?;
+                return Err(error.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /// Writes the database back as plaintext and removes the passphrase from the keyring.
+    pub fn decrypt() -> Result<(), String> {
+        let path = db_path().map_err(|error| error.to_string())?;
+        if !Self::is_encrypted(&path) {
+            return Err("The database is not encrypted.".to_string());
+        }
+
+        with_exclusive_connection(|conn| {
+            let plaintext = path.with_extension("decrypting");
+            export(&conn, &plaintext, "")?;
+            checkpoint(conn)?;
+            fs::rename(&plaintext, &path).map_err(|error| error.to_string())?;
+            store_passphrase(None)
+        })
+    }
+}
+
+/// Copies every table, index and trigger into a new database at `target`, keyed with
+/// `passphrase`; an empty passphrase writes plaintext.
+fn export(conn: &Connection, target: &Path, passphrase: &str) -> Result<(), String> {
+    if target.exists() {
+        fs::remove_file(target).map_err(|error| error.to_string())?;
+    }
+    conn.execute(
+        "ATTACH DATABASE ?1 AS export KEY ?2",
+        params![target.to_string_lossy(), passphrase],
+    )
+    .map_err(|error| error.to_string())?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('export')", [], |_row| Ok(()))
+        .map_err(|error| format!("The database could not be re-encrypted: {}", error));
+    conn.execute("DETACH DATABASE export", [])
+        .map_err(|error| error.to_string())?;
+    if exported.is_err() {
+        let _ = fs::remove_file(target);
+    }
+    exported
+}
+
+/// Moves everything in the write-ahead log into `app.db`, empties the log and closes the
+/// last connection before the file is replaced, so the log is not replayed onto the new
+/// file and Windows lets the file be renamed over.
+fn checkpoint(conn: Connection) -> Result<(), String> {
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_row| Ok(()))
+        .map_err(|error| error.to_string())?;
+    conn.close().map_err(|(_, error)| error.to_string())
+}
+
+fn passphrase() -> Result<Option<String>, String> {
+    let cache = PASSPHRASE.get_or_init(|| RwLock::new(None));
+    if let Some(passphrase) = cache.read().map_err(|error| error.to_string())?.clone() {
+        return Ok(Some(passphrase));
+    }
+    let stored = match keyring_entry()?.get_password() {
+        Ok(passphrase) => Some(passphrase),
+        Err(keyring::Error::NoEntry) => None,
+        Err(error) => return Err(error.to_string()),
+    };
+    *cache.write().map_err(|error| error.to_string())? = stored.clone();
+    Ok(stored)
+}
+
+fn store_passphrase(passphrase: Option<&str>) -> Result<(), String> {
+    match passphrase {
+        Some(passphrase) => keyring_entry()?
+            .set_password(passphrase)
+            .map_err(|error| format!("Could not save the passphrase in the keyring: {}", error))?,
+        None => match keyring_entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(error) => return Err(error.to_string()),
+        },
+    }
+    let cache = PASSPHRASE.get_or_init(|| RwLock::new(None));
+    *cache.write().map_err(|error| error.to_string())? = passphrase.map(str::to_string);
+    Ok(())
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|error| error.to_string())
+}
@@ -0,0 +1,230 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+pub const DEFAULT_DATE_LOCALE: &str = "en-IN";
+
+const MONTH_NAMES: &[(&str, u32)] = &[
+    ("january", 1),
+    ("february", 2),
+    ("march", 3),
+    ("april", 4),
+    ("may", 5),
+    ("june", 6),
+    ("july", 7),
+    ("august", 8),
+    ("september", 9),
+    ("sept", 9),
+    ("october", 10),
+    ("november", 11),
+    ("december", 12),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedDate {
+    pub raw: String,
+    pub iso: Option<String>,
+    pub ambiguous: bool,
+    pub message: Option<String>,
+}
+
+enum Token {
+    Number(String),
+    Month(u32),
+}
+
+pub struct DateNormalizer;
+
+impl DateNormalizer {
+    /// Picks the numeric field order used when a date like `03/04/24` could be read either way.
+    pub fn order_for_locale(locale: &str) -> DateOrder {
+        let normalized = locale.trim().replace('_', "-").to_lowercase();
+        let language = normalized.split('-').next().unwrap_or("");
+        let region = normalized.split('-').nth(1).unwrap_or("");
+
+        match (language, region) {
+            ("zh" | "ja" | "ko" | "hu" | "lt", _) => DateOrder::YearMonthDay,
+            (_, "us" | "ph" | "fm" | "pw") => DateOrder::MonthDayYear,
+            _ => DateOrder::DayMonthYear,
+        }
+    }
+
+    pub fn normalize(raw: &str, locale: Option<&str>) -> NormalizedDate {
+        let order = Self::order_for_locale(locale.unwrap_or(DEFAULT_DATE_LOCALE));
+        let mut result = NormalizedDate {
+            raw: raw.to_string(),
+            iso: None,
+            ambiguous: false,
+            message: None,
+        };
+
+        match parse_date(raw, order) {
+            Ok((date, ambiguous)) => {
+                result.iso = Some(date.format("%Y-%m-%d").to_string());
+                result.ambiguous = ambiguous;
+                if ambiguous {
+                    result.message = Some(format!(
+                        "Date \"{}\" is ambiguous; interpreted as {}.",
+                        raw.trim(),
+                        date.format("%Y-%m-%d")
+                    ));
+                }
+            }
+            Err(message) => result.message = Some(message),
+        }
+
+        result
+    }
+}
+
+fn tokenize(raw: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    let flush = |current: &mut String, tokens: &mut Vec<Token>| {
+        if current.is_empty() {
+            return;
+        }
+        if current.chars().all(|ch| ch.is_ascii_digit()) {
+            tokens.push(Token::Number(current.clone()));
+        } else {
+            let word = current.to_lowercase();
+            let month = MONTH_NAMES
+                .iter()
+                .find(|(name, _)| word.len() >= 3 && name.starts_with(word.as_str()))
+                .map(|(_, month)| *month);
+            if let Some(month) = month {
+                tokens.push(Token::Month(month));
+            }
+        }
+        current.clear();
+    };
+
+    for ch in raw.chars() {
+        if ch.is_ascii_alphanumeric() {
+            let is_digit = ch.is_ascii_digit();
+            if current
+                .chars()
+                .last()
+                .is_some_and(|last| last.is_ascii_digit() != is_digit)
+            {
+                flush(&mut current, &mut tokens);
+            }
+            current.push(ch);
+        } else {
+            flush(&mut current, &mut tokens);
+        }
+    }
+    flush(&mut current, &mut tokens);
+
+    tokens
+}
+
+fn expand_year(value: &str) -> Option<i32> {
+    let year: i32 = value.parse().ok()?;
+    match value.len() {
+        4 => Some(year),
+        2 if year < 70 => Some(2000 + year),
+        2 => Some(1900 + year),
+        _ => None,
+    }
+}
+
+fn build_date(year: i32, month: u32, day: u32) -> Result<NaiveDate, String> {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| format!("{:04}-{:02}-{:02} is not a valid calendar date.", year, month, day))
+}
+
+fn parse_date(raw: &str, order: DateOrder) -> Result<(NaiveDate, bool), String> {
+    let tokens = tokenize(raw);
+    let unparseable = || format!("Unrecognized date format: \"{}\".", raw.trim());
+
+    let month_position = tokens.iter().position(|token| matches!(token, Token::Month(_)));
+    if let Some(position) = month_position {
+        let month = match tokens[position] {
+            Token::Month(month) => month,
+            Token::Number(_) => unreachable!(),
+        };
+        let numbers: Vec<&String> = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Number(value) => Some(value),
+                Token::Month(_) => None,
+            })
+            .collect();
+        if numbers.len() != 2 {
+            return Err(unparseable());
+        }
+
+        let (day, year) = if numbers[0].len() == 4 {
+            (numbers[1], numbers[0])
+        } else {
+            (numbers[0], numbers[1])
+        };
+        let day: u32 = day.parse().map_err(|_| unparseable())?;
+        let year = expand_year(year).ok_or_else(unparseable)?;
+        return build_date(year, month, day).map(|date| (date, false));
+    }
+
+    let numbers: Vec<&String> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Number(value) => Some(value),
+            Token::Month(_) => None,
+        })
+        .collect();
+
+    if numbers.len() == 1 && numbers[0].len() == 8 {
+        let value = numbers[0];
+        let year: i32 = value[0..4].parse().map_err(|_| unparseable())?;
+        let month: u32 = value[4..6].parse().map_err(|_| unparseable())?;
+        let day: u32 = value[6..8].parse().map_err(|_| unparseable())?;
+        return build_date(year, month, day).map(|date| (date, false));
+    }
+
+    let parse = |value: &str| value.parse::<u32>().map_err(|_| unparseable());
+
+    // ISO-style dates may carry a trailing time component, which is ignored.
+    if numbers.len() >= 3 && numbers[0].len() == 4 {
+        let year = expand_year(numbers[0]).ok_or_else(unparseable)?;
+        return build_date(year, parse(numbers[1])?, parse(numbers[2])?).map(|date| (date, false));
+    }
+
+    if numbers.len() != 3 {
+        return Err(unparseable());
+    }
+
+    if numbers[2].len() != 4 && order == DateOrder::YearMonthDay {
+        let year = expand_year(numbers[0]).ok_or_else(unparseable)?;
+        let date = build_date(year, parse(numbers[1])?, parse(numbers[2])?)?;
+        return Ok((date, true));
+    }
+
+    let year = expand_year(numbers[2]).ok_or_else(unparseable)?;
+    let first = parse(numbers[0])?;
+    let second = parse(numbers[1])?;
+
+    let (day, month) = match order {
+        DateOrder::MonthDayYear => (second, first),
+        DateOrder::DayMonthYear | DateOrder::YearMonthDay => (first, second),
+    };
+
+    match build_date(year, month, day) {
+        Ok(date) => {
+            let ambiguous = first != second && first <= 12 && second <= 12;
+            Ok((date, ambiguous))
+        }
+        // The preferred order produced an impossible date (e.g. 13/04 read month-first),
+        // so the other reading is the only valid one.
+        Err(error) => build_date(year, day, month)
+            .map(|date| (date, false))
+            .map_err(|_| error),
+    }
+}
@@ -0,0 +1,139 @@
+use crate::services::gemini::mime_for;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryFilters {
+    /// Extensions to import, without the dot. Every supported document type when empty.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    pub max_size_bytes: Option<u64>,
+    /// Date or RFC 3339 timestamp; files last modified before it are left out.
+    pub modified_after: Option<String>,
+    /// Walk subfolders too. Defaults to true.
+    pub recursive: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryEntry {
+    pub path: String,
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+    pub included: bool,
+    /// Which filter left the file out.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryPreview {
+    pub directory: String,
+    pub included_files: usize,
+    pub included_bytes: u64,
+    pub excluded_files: usize,
+    pub entries: Vec<DirectoryEntry>,
+}
+
+/// Lists a dropped folder against the user's filters so the import can be confirmed first.
+/// Hidden files and folders are not listed.
+pub struct DirectoryImport;
+
+impl DirectoryImport {
+    pub fn preview(directory: &Path, filters: &DirectoryFilters) -> Result<DirectoryPreview, String> {
+        if !directory.is_dir() {
+            return Err(format!("Not a folder: {}", directory.display()));
+        }
+        let extensions: Vec<String> = filters
+            .extensions
+            .iter()
+            .map(|extension| extension.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|extension| !extension.is_empty())
+            .collect();
+        let modified_after = filters.modified_after.as_deref().map(parse_cutoff).transpose()?;
+
+        let mut entries = Vec::new();
+        for path in walk(directory, filters.recursive.unwrap_or(true))? {
+            let metadata = fs::metadata(&path).map_err(|error| error.to_string())?;
+            let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            let extension = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+
+            let reason = if mime_for(&path).is_none() {
+                Some("Not a supported document type.".to_string())
+            } else if !extensions.is_empty() && !extensions.contains(&extension) {
+                Some(format!("Extension .{} is not selected.", extension))
+            } else if filters.max_size_bytes.is_some_and(|max| metadata.len() > max) {
+                Some("Larger than the size limit.".to_string())
+            } else if modified_after.is_some_and(|cutoff| modified.is_none_or(|modified| modified < cutoff)) {
+                Some("Modified before the cut-off date.".to_string())
+            } else {
+                None
+            };
+
+            entries.push(DirectoryEntry {
+                path: path.to_string_lossy().to_string(),
+                file_name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                size_bytes: metadata.len(),
+                modified_at: modified.map(|modified| modified.to_rfc3339()),
+                included: reason.is_none(),
+                reason,
+            });
+        }
+
+        let included = entries.iter().filter(|entry| entry.included);
+        Ok(DirectoryPreview {
+            directory: directory.to_string_lossy().to_string(),
+            included_files: included.clone().count(),
+            included_bytes: included.map(|entry| entry.size_bytes).sum(),
+            excluded_files: entries.iter().filter(|entry| !entry.included).count(),
+            entries,
+        })
+    }
+}
+
+fn parse_cutoff(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|_| format!("Invalid modified-after date: {}", value))
+}
+
+/// Files under `directory`, sorted by path, skipping hidden entries.
+fn walk(directory: &Path, recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).map_err(|error| error.to_string())? {
+            let path = entry.map_err(|error| error.to_string())?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
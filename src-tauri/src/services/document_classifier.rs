@@ -0,0 +1,148 @@
+use crate::services::parsed_invoice::VOUCHER_TYPE;
+use lopdf::Document;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Parsed-details key a provider may fill with the kind of document it read.
+pub const DOCUMENT_TYPE: &str = "document type";
+
+/// Only the start of the text is searched; titles are printed at the top.
+const TITLE_WINDOW: usize = 1500;
+
+/// Title phrases of each kind of document. The earliest phrase in the text decides, since
+/// an invoice mentions its purchase order and a credit note the invoice it corrects.
+const TITLE_PHRASES: &[(DocType, &[&str])] = &[
+    (
+        DocType::CreditNote,
+        &[
+            "credit note",
+            "credit memo",
+            "credit voucher",
+            "gutschrift",
+            "facture d'avoir",
+        ],
+    ),
+    (
+        DocType::PurchaseOrder,
+        &["purchase order", "bestellung", "bon de commande"],
+    ),
+    (
+        DocType::Receipt,
+        &["receipt", "cash memo", "payment received", "quittung", "reçu"],
+    ),
+    (
+        DocType::Invoice,
+        &["tax invoice", "invoice", "bill of supply", "rechnung", "facture"],
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocType {
+    Invoice,
+    Receipt,
+    CreditNote,
+    PurchaseOrder,
+    Other,
+}
+
+impl DocType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocType::Invoice => "invoice",
+            DocType::Receipt => "receipt",
+            DocType::CreditNote => "credit_note",
+            DocType::PurchaseOrder => "purchase_order",
+            DocType::Other => "other",
+        }
+    }
+
+    /// Accepts the stored form as well as the spaced one models answer with, e.g.
+    /// `Credit Note`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "invoice" => Ok(DocType::Invoice),
+            "receipt" => Ok(DocType::Receipt),
+            "credit_note" => Ok(DocType::CreditNote),
+            "purchase_order" => Ok(DocType::PurchaseOrder),
+            "other" => Ok(DocType::Other),
+            other => Err(format!("Unknown document type: {}", other)),
+        }
+    }
+}
+
+/// Tells invoices apart from receipts, credit notes and purchase orders, first from the
+/// imported file and again once it is parsed.
+pub struct DocumentClassifier;
+
+impl DocumentClassifier {
+    /// A guess at import time from a PDF's text layer, or else the file name. `None` when
+    /// neither says anything, as for most scans, until the file is parsed.
+    pub fn classify_import(file_name: &str, buffer: &[u8]) -> Option<DocType> {
+        let is_pdf = file_name.to_lowercase().ends_with(".pdf");
+        let from_text = is_pdf
+            .then(|| first_page_text(buffer))
+            .flatten()
+            .and_then(|text| Self::classify_text(&text));
+        from_text.or_else(|| title_match(&file_name.replace(['_', '-', '.'], " ")))
+    }
+
+    /// `Other` for text without any known title.
+    pub fn classify_text(text: &str) -> Option<DocType> {
+        if text.trim().is_empty() {
+            return None;
+        }
+        Some(title_match(text).unwrap_or(DocType::Other))
+    }
+
+    /// The provider's own answer when it gave one, otherwise what the voucher type says,
+    /// e.g. a Tally `Credit Note`.
+    pub fn classify_parsed(parsed: &Value) -> Option<DocType> {
+        if let Some(stated) = parsed.get(DOCUMENT_TYPE).and_then(Value::as_str) {
+            if let Ok(doc_type) = DocType::parse(stated) {
+                return Some(doc_type);
+            }
+        }
+        parsed
+            .get(VOUCHER_TYPE)
+            .and_then(Value::as_str)
+            .and_then(title_match)
+    }
+
+    /// Returns whether the file exists.
+    pub fn set(conn: &Connection, file_id: &str, doc_type: DocType) -> Result<bool, String> {
+        let updated = conn
+            .execute(
+                "UPDATE files SET doc_type = ?1 WHERE id = ?2",
+                params![doc_type.as_str(), file_id],
+            )
+            .map_err(|error| error.to_string())?;
+        Ok(updated > 0)
+    }
+}
+
+fn title_match(text: &str) -> Option<DocType> {
+    let lower = text.to_lowercase();
+    let end = lower
+        .char_indices()
+        .nth(TITLE_WINDOW)
+        .map(|(index, _)| index)
+        .unwrap_or(lower.len());
+    let window = &lower[..end];
+    TITLE_PHRASES
+        .iter()
+        .flat_map(|(doc_type, phrases)| {
+            phrases
+                .iter()
+                .filter_map(move |phrase| window.find(phrase).map(|position| (position, *doc_type)))
+        })
+        .min_by_key(|(position, _)| *position)
+        .map(|(_, doc_type)| doc_type)
+}
+
+fn first_page_text(buffer: &[u8]) -> Option<String> {
+    let document = Document::load_mem(buffer).ok()?;
+    let first = *document.get_pages().keys().next()?;
+    document.extract_text(&[first]).ok()
+}
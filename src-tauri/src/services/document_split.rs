@@ -0,0 +1,161 @@
+use crate::services::multi_invoice::PageRange;
+use image::{DynamicImage, GrayImage, ImageBuffer, Luma, LumaA, Rgb, Rgba};
+use lopdf::Document;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, Compression, DeflateLevel, TiffEncoder};
+use tiff::ColorType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitFormat {
+    Pdf,
+    Tiff,
+}
+
+impl SplitFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "pdf" => Some(SplitFormat::Pdf),
+            "tif" | "tiff" => Some(SplitFormat::Tiff),
+            _ => None,
+        }
+    }
+}
+
+/// Cuts a multi-page PDF or TIFF into one document per page range, in the source format.
+/// The source file is only read.
+pub struct DocumentSplitter;
+
+impl DocumentSplitter {
+    pub fn split(path: &Path, ranges: &[PageRange]) -> Result<Vec<Vec<u8>>, String> {
+        let format = SplitFormat::from_path(path)
+            .ok_or_else(|| "Only PDF and TIFF documents can be split.".to_string())?;
+        if ranges.is_empty() {
+            return Err("No page ranges given.".to_string());
+        }
+
+        match format {
+            SplitFormat::Pdf => {
+                let document =
+                    Document::load(path).map_err(|error| format!("Failed to read PDF: {}", error))?;
+                let page_count = document.get_pages().len() as u32;
+                check_ranges(ranges, page_count)?;
+                ranges
+                    .iter()
+                    .map(|range| {
+                        let mut part = document.clone();
+                        let outside: Vec<u32> = (1..=page_count)
+                            .filter(|page| *page < range.start || *page > range.end)
+                            .collect();
+                        part.delete_pages(&outside);
+                        part.prune_objects();
+                        let mut bytes = Vec::new();
+                        part.save_to(&mut bytes).map_err(|error| error.to_string())?;
+                        Ok(bytes)
+                    })
+                    .collect()
+            }
+            SplitFormat::Tiff => {
+                let pages = tiff_pages(&fs::read(path).map_err(|error| error.to_string())?)?;
+                check_ranges(ranges, pages.len() as u32)?;
+                ranges
+                    .iter()
+                    .map(|range| encode_tiff(&pages[range.start as usize - 1..range.end as usize]))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn check_ranges(ranges: &[PageRange], page_count: u32) -> Result<(), String> {
+    match ranges
+        .iter()
+        .find(|range| range.start == 0 || range.end < range.start || range.end > page_count)
+    {
+        Some(range) => Err(format!(
+            "Page range {}-{} is outside the document's {} pages.",
+            range.start, range.end, page_count
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Decodes every page of a TIFF. Bilevel, 8- and 16-bit grayscale and 8-bit RGB(A) pages
+/// are supported, which covers what document scanners write.
+pub fn tiff_pages(bytes: &[u8]) -> Result<Vec<DynamicImage>, String> {
+    let mut decoder =
+        Decoder::new(Cursor::new(bytes)).map_err(|error| format!("Failed to read TIFF: {}", error))?;
+    let mut pages = Vec::new();
+    loop {
+        pages.push(
+            decode_page(&mut decoder).map_err(|error| format!("TIFF page {}: {}", pages.len() + 1, error))?,
+        );
+        if !decoder.more_images() {
+            return Ok(pages);
+        }
+        decoder.next_image().map_err(|error| error.to_string())?;
+    }
+}
+
+fn decode_page(decoder: &mut Decoder<Cursor<&[u8]>>) -> Result<DynamicImage, String> {
+    let (width, height) = decoder.dimensions().map_err(|error| error.to_string())?;
+    let color = decoder.colortype().map_err(|error| error.to_string())?;
+    let data = decoder.read_image().map_err(|error| error.to_string())?;
+    let invalid = || "Truncated page data.".to_string();
+
+    let page = match (color, data) {
+        (ColorType::Gray(1), DecodingResult::U8(data)) => {
+            // Rows are padded to whole bytes; WhiteIsZero pages arrive already inverted.
+            let row_bytes = (width as usize).div_ceil(8);
+            if data.len() < row_bytes * height as usize {
+                return Err(invalid());
+            }
+            DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+                let byte = data[y as usize * row_bytes + x as usize / 8];
+                Luma([if byte & (0x80 >> (x % 8)) != 0 { 255 } else { 0 }])
+            }))
+        }
+        (ColorType::Gray(8), DecodingResult::U8(data)) => DynamicImage::ImageLuma8(
+            ImageBuffer::<Luma<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+        ),
+        (ColorType::Gray(16), DecodingResult::U16(data)) => DynamicImage::ImageLuma8(
+            ImageBuffer::<Luma<u8>, _>::from_raw(
+                width,
+                height,
+                data.iter().map(|value| (value >> 8) as u8).collect(),
+            )
+            .ok_or_else(invalid)?,
+        ),
+        (ColorType::GrayA(8), DecodingResult::U8(data)) => DynamicImage::ImageLumaA8(
+            ImageBuffer::<LumaA<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+        ),
+        (ColorType::RGB(8), DecodingResult::U8(data)) => DynamicImage::ImageRgb8(
+            ImageBuffer::<Rgb<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+        ),
+        (ColorType::RGBA(8), DecodingResult::U8(data)) => DynamicImage::ImageRgba8(
+            ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data).ok_or_else(invalid)?,
+        ),
+        (color, _) => return Err(format!("Unsupported page format {:?}.", color)),
+    };
+    Ok(page)
+}
+
+/// Writes the pages as one multi-page, deflate-compressed TIFF.
+fn encode_tiff(pages: &[DynamicImage]) -> Result<Vec<u8>, String> {
+    let mut output = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(output.by_ref())
+        .map_err(|error| error.to_string())?
+        .with_compression(Compression::Deflate(DeflateLevel::Balanced));
+    for page in pages {
+        let (width, height) = (page.width(), page.height());
+        match page {
+            DynamicImage::ImageLuma8(image) => encoder.write_image::<colortype::Gray8>(width, height, image),
+            DynamicImage::ImageRgb8(image) => encoder.write_image::<colortype::RGB8>(width, height, image),
+            other => encoder.write_image::<colortype::RGBA8>(width, height, &other.to_rgba8()),
+        }
+        .map_err(|error| error.to_string())?;
+    }
+    Ok(output.into_inner())
+}
@@ -0,0 +1,146 @@
+use crate::db::get_connection;
+use crate::services::{
+    parsed_invoice::{ParsedInvoice, GRAND_TOTAL, INVOICE_NUMBER, SELLER_NAME},
+    review_queue::{NewReviewFlag, ReviewQueue},
+};
+use rusqlite::{params, Connection};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+pub const DOUBLE_PAYMENT_REVIEW_SOURCE: &str = "double_payment";
+
+pub struct DoublePaymentDetector;
+
+impl DoublePaymentDetector {
+    /// Builds the seller + invoice number + amount key two copies of the same bill share.
+    pub fn invoice_key(invoice: &ParsedInvoice) -> Option<String> {
+        let seller: String = invoice
+            .text(SELLER_NAME)?
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|ch| ch.to_ascii_lowercase())
+            .collect();
+        let number: String = invoice
+            .text(INVOICE_NUMBER)?
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|ch| ch.to_ascii_uppercase())
+            .collect();
+        let amount = invoice.number(GRAND_TOTAL)?;
+
+        if seller.is_empty() || number.is_empty() {
+            return None;
+        }
+
+        Some(format!("{}|{}|{:.2}", seller, number, amount))
+    }
+
+    pub fn update_key(conn: &Connection, file_id: &str, parsed_details: Option<&str>) -> Result<(), String> {
+        let key = parsed_details
+            .and_then(ParsedInvoice::from_json)
+            .and_then(|invoice| Self::invoice_key(&invoice));
+
+        conn.execute(
+            "UPDATE files SET duplicate_invoice_key = ?1, duplicate_key_checked_at = CURRENT_TIMESTAMP
+             WHERE id = ?2",
+            params![key, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(())
+    }
+
+    /// Computes the key of files whose parsed details changed since it was last
+    /// checked, including those that legitimately have none, and refreshes the
+    /// review flags of every unresolved group of files sharing a key. Returns the
+    /// number of unresolved groups.
+    pub fn scan() -> Result<usize, String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, parsed_details FROM files
+                 WHERE duplicate_key_checked_at IS NULL AND parsed_details IS NOT NULL",
+            )
+            .map_err(|error| error.to_string())?;
+        let pending = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        for (file_id, parsed_details) in pending {
+            Self::update_key(&conn, &file_id, Some(&parsed_details))?;
+        }
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT f.duplicate_invoice_key, f.id, f.file_name
+                 FROM files f
+                 WHERE f.duplicate_invoice_key IN (
+                   SELECT duplicate_invoice_key FROM files
+                   WHERE duplicate_invoice_key IS NOT NULL
+                   GROUP BY duplicate_invoice_key HAVING COUNT(*) > 1
+                 )
+                 AND f.duplicate_invoice_key NOT IN (SELECT invoice_key FROM double_payment_resolutions)
+                 ORDER BY f.created_at ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut groups: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for (key, file_id, file_name) in rows {
+            groups.entry(key).or_default().push((file_id, file_name));
+        }
+
+        let mut flagged: HashSet<String> = HashSet::new();
+        for members in groups.values() {
+            for (file_id, _) in members {
+                flagged.insert(file_id.clone());
+                if ReviewQueue::has_open_flags(&conn, file_id, DOUBLE_PAYMENT_REVIEW_SOURCE)? {
+                    continue;
+                }
+
+                let others: Vec<&str> = members
+                    .iter()
+                    .filter(|(other_id, _)| other_id != file_id)
+                    .map(|(_, name)| name.as_str())
+                    .collect();
+                let flag = NewReviewFlag {
+                    field: Some(INVOICE_NUMBER.to_string()),
+                    message: format!(
+                        "Possible double payment: same seller, invoice number and amount as {}.",
+                        others.join(", ")
+                    ),
+                    details: Some(json!({
+                        "fileIds": members.iter().map(|(id, _)| id).collect::<Vec<_>>()
+                    })),
+                };
+                ReviewQueue::replace_flags(&conn, file_id, DOUBLE_PAYMENT_REVIEW_SOURCE, &[flag])?;
+            }
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT file_id FROM review_flags WHERE source = ?1 AND resolved_at IS NULL")
+            .map_err(|error| error.to_string())?;
+        let stale = stmt
+            .query_map(params![DOUBLE_PAYMENT_REVIEW_SOURCE], |row| row.get::<_, String>(0))
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        for file_id in stale.iter().filter(|id| !flagged.contains(*id)) {
+            ReviewQueue::replace_flags(&conn, file_id, DOUBLE_PAYMENT_REVIEW_SOURCE, &[])?;
+        }
+
+        Ok(groups.len())
+    }
+}
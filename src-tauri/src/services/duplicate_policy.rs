@@ -0,0 +1,47 @@
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const DUPLICATE_POLICY_KEY: &str = "files.duplicate_policy";
+
+/// How imports decide that a document is already in the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// Byte-identical content, under any recorded hash algorithm.
+    ContentHash,
+    /// Same file name and size. Catches re-scanned copies whose bytes differ.
+    NameAndSize,
+    /// Every import is kept, even identical copies.
+    None,
+}
+
+impl DuplicatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicatePolicy::ContentHash => "content_hash",
+            DuplicatePolicy::NameAndSize => "name_and_size",
+            DuplicatePolicy::None => "none",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "content_hash" => Ok(DuplicatePolicy::ContentHash),
+            "name_and_size" => Ok(DuplicatePolicy::NameAndSize),
+            "none" => Ok(DuplicatePolicy::None),
+            other => Err(format!("Unsupported duplicate policy: {}", other)),
+        }
+    }
+
+    pub fn configured(conn: &Connection) -> Result<Self, String> {
+        match Settings::get(conn, DUPLICATE_POLICY_KEY)? {
+            Some(value) => Self::parse(&value),
+            None => Ok(DuplicatePolicy::ContentHash),
+        }
+    }
+
+    pub fn set_configured(conn: &Connection, policy: DuplicatePolicy) -> Result<(), String> {
+        Settings::set(conn, DUPLICATE_POLICY_KEY, policy.as_str())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::services::settings::Settings;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+const BASE_CURRENCY_KEY: &str = "currency.base";
+const CONVERTED_TOTALS_KEY: &str = "currency.converted_totals";
+const DEFAULT_BASE_CURRENCY: &str = "INR";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRate {
+    pub currency: String,
+    /// Units of the base currency for one unit of `currency`.
+    pub rate: f64,
+    pub updated_at: String,
+}
+
+/// Manually maintained conversion rates into the base currency, used for converted totals.
+/// Rates are relative to the base currency, so changing the base clears them.
+pub struct ExchangeRates;
+
+impl ExchangeRates {
+    pub fn base_currency(conn: &Connection) -> Result<String, String> {
+        Ok(Settings::get(conn, BASE_CURRENCY_KEY)?.unwrap_or_else(|| DEFAULT_BASE_CURRENCY.to_string()))
+    }
+
+    pub fn set_base_currency(conn: &Connection, currency: &str) -> Result<(), String> {
+        let currency = currency_code(currency)?;
+        if currency == Self::base_currency(conn)? {
+            return Ok(());
+        }
+        conn.execute("DELETE FROM exchange_rates", [])
+            .map_err(|error| error.to_string())?;
+        Settings::set(conn, BASE_CURRENCY_KEY, &currency)
+    }
+
+    /// Whether sheets with several currencies also get a grand total converted to the base.
+    pub fn converted_totals(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, CONVERTED_TOTALS_KEY)?.as_deref() == Some("1"))
+    }
+
+    pub fn set_converted_totals(conn: &Connection, enabled: bool) -> Result<(), String> {
+        Settings::set(conn, CONVERTED_TOTALS_KEY, if enabled { "1" } else { "0" })
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<ExchangeRate>, String> {
+        let mut stmt = conn
+            .prepare("SELECT currency, rate, updated_at FROM exchange_rates ORDER BY currency")
+            .map_err(|error| error.to_string())?;
+        let rates = stmt
+            .query_map([], |row| {
+                Ok(ExchangeRate {
+                    currency: row.get(0)?,
+                    rate: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(rates)
+    }
+
+    pub fn set(conn: &Connection, currency: &str, rate: f64) -> Result<(), String> {
+        let currency = currency_code(currency)?;
+        if currency == Self::base_currency(conn)? {
+            return Err(format!("{} is the base currency.", currency));
+        }
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err("The exchange rate must be a positive number.".to_string());
+        }
+        conn.execute(
+            "INSERT INTO exchange_rates (currency, rate, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(currency) DO UPDATE SET rate = excluded.rate, updated_at = excluded.updated_at",
+            params![currency, rate, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    pub fn delete(conn: &Connection, currency: &str) -> Result<(), String> {
+        conn.execute(
+            "DELETE FROM exchange_rates WHERE currency = ?1",
+            params![currency_code(currency)?],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Every known rate by currency code, including the base currency at 1.
+    pub fn rates(conn: &Connection) -> Result<HashMap<String, f64>, String> {
+        let mut rates: HashMap<String, f64> = Self::list(conn)?
+            .into_iter()
+            .map(|rate| (rate.currency, rate.rate))
+            .collect();
+        rates.insert(Self::base_currency(conn)?, 1.0);
+        Ok(rates)
+    }
+}
+
+/// Normalizes an ISO 4217 code such as " usd " to "USD".
+pub fn currency_code(value: &str) -> Result<String, String> {
+    let code = value.trim().to_ascii_uppercase();
+    if code.len() == 3 && code.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        Ok(code)
+    } else {
+        Err(format!(
+            "\"{}\" is not a three-letter currency code.",
+            value.trim()
+        ))
+    }
+}
@@ -0,0 +1,488 @@
+use crate::services::exchange_rates::{currency_code, ExchangeRates};
+use crate::services::parsed_invoice::{
+    ParsedInvoice, BUYER_NAME, CURRENCY, GRAND_TOTAL, INVOICE_DATE, INVOICE_NUMBER, SELLER_GSTIN,
+    SELLER_NAME, SUBTOTAL, TAX_TOTAL,
+};
+use crate::services::spreadsheet::{write_workbook, Cell, Sheet};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const DOCUMENTS_DIR: &str = "documents";
+const MANIFEST_NAME: &str = "manifest.json";
+/// Zip bundles are assembled in a temp folder with this prefix before being archived.
+pub const BUNDLE_STAGING_PREFIX: &str = "invox-bundle-";
+/// Headers of the invoice sheet, in column order.
+pub const INVOICE_COLUMNS: &[&str] = &[
+    "Row",
+    "Invoice Number",
+    "Date",
+    "Seller",
+    "Seller GSTIN",
+    "Buyer",
+    "Currency",
+    "Subtotal",
+    "Tax",
+    "Grand Total",
+    "Document",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Xml,
+    Xlsx,
+}
+
+impl BundleFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "xml" => Ok(BundleFormat::Xml),
+            "xlsx" => Ok(BundleFormat::Xlsx),
+            other => Err(format!(
+                "Unsupported bundle format \"{}\". Use xml or xlsx.",
+                other
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BundleFormat::Xml => "xml",
+            BundleFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn export_name(&self) -> &'static str {
+        match self {
+            BundleFormat::Xml => "invoices.xml",
+            BundleFormat::Xlsx => "invoices.xlsx",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    /// 1-based position of the invoice in the export: the voucher order in XML, the data row in XLSX.
+    pub row: usize,
+    pub file_id: String,
+    pub invoice_number: Option<String>,
+    pub seller_name: Option<String>,
+    pub original_file_name: String,
+    /// Path of the copied source document inside the bundle.
+    pub document: Option<String>,
+    pub hash: String,
+    pub hash_algo: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub created_at: String,
+    pub export: String,
+    pub entries: Vec<ManifestEntry>,
+    /// Files that were requested but have no parsed details and were left out.
+    pub skipped_file_ids: Vec<String>,
+}
+
+struct BundleFile {
+    id: String,
+    file_name: String,
+    stored_path: String,
+    hash: String,
+    hash_algo: String,
+    invoice: ParsedInvoice,
+    /// Printed currency, or the base currency when none was extracted.
+    currency: String,
+}
+
+/// An audit-ready export: the generated XML or XLSX next to copies of the source
+/// documents, named by invoice number, and a manifest tying each exported row to its file.
+pub struct ExportBundle {
+    format: BundleFormat,
+    files: Vec<BundleFile>,
+    skipped_file_ids: Vec<String>,
+    base_currency: String,
+    /// Set when mixed-currency sheets should end with a total converted to the base currency.
+    rates: Option<HashMap<String, f64>>,
+}
+
+impl ExportBundle {
+    /// Loads `file_ids` in the given order; that order is the row order of the export.
+    pub fn load(conn: &Connection, file_ids: &[String], format: BundleFormat) -> Result<Self, String> {
+        let base_currency = ExchangeRates::base_currency(conn)?;
+        let rates = if ExchangeRates::converted_totals(conn)? {
+            Some(ExchangeRates::rates(conn)?)
+        } else {
+            None
+        };
+        let mut files = Vec::new();
+        let mut skipped_file_ids = Vec::new();
+
+        for file_id in file_ids {
+            let row: Option<(String, String, String, String, Option<String>)> = conn
+                .query_row(
+                    "SELECT file_name, stored_path, content_hash, hash_algo, parsed_details FROM files WHERE id = ?1",
+                    params![file_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let Some((file_name, stored_path, hash, hash_algo, parsed_details)) = row else {
+                return Err(format!("File not found: {}", file_id));
+            };
+
+            match parsed_details.as_deref().and_then(ParsedInvoice::from_json) {
+                Some(invoice) => files.push(BundleFile {
+                    id: file_id.clone(),
+                    file_name,
+                    stored_path,
+                    hash,
+                    hash_algo,
+                    currency: invoice
+                        .text(CURRENCY)
+                        .and_then(|currency| currency_code(&currency).ok())
+                        .unwrap_or_else(|| base_currency.clone()),
+                    invoice,
+                }),
+                None => skipped_file_ids.push(file_id.clone()),
+            }
+        }
+
+        if files.is_empty() {
+            return Err(
+                "None of the files have been processed yet. Process files before exporting a bundle."
+                    .to_string(),
+            );
+        }
+
+        Ok(ExportBundle {
+            format,
+            files,
+            skipped_file_ids,
+            base_currency,
+            rates,
+        })
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Files that made it into the export, in row order.
+    pub fn file_ids(&self) -> Vec<String> {
+        self.files.iter().map(|file| file.id.clone()).collect()
+    }
+
+    /// Writes the bundle into `destination`, a new folder, or a `.zip` archive when `zip` is set.
+    /// XML exports need the generated `xml_content`; XLSX exports are built here.
+    pub fn write(
+        &self,
+        destination: &Path,
+        xml_content: Option<&str>,
+        zip: bool,
+    ) -> Result<BundleManifest, String> {
+        let occupied = destination.exists()
+            && (zip
+                || fs::read_dir(destination)
+                    .map_err(|error| error.to_string())?
+                    .next()
+                    .is_some());
+        if occupied {
+            return Err(format!("{} already exists.", destination.display()));
+        }
+        let has_xml = xml_content.is_some_and(|content| !content.trim().is_empty());
+        if self.format == BundleFormat::Xml && !has_xml {
+            return Err("Generate the XML before exporting a bundle.".to_string());
+        }
+
+        let staging = if zip {
+            std::env::temp_dir().join(format!("{}{}", BUNDLE_STAGING_PREFIX, uuid::Uuid::new_v4()))
+        } else {
+            destination.to_path_buf()
+        };
+        let result = self.write_folder(&staging, xml_content);
+        let result = match result {
+            Ok(manifest) if zip => zip_folder(&staging, destination).map(|_| manifest),
+            other => other,
+        };
+        if zip || result.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        result
+    }
+
+    fn write_folder(&self, folder: &Path, xml_content: Option<&str>) -> Result<BundleManifest, String> {
+        let documents = folder.join(DOCUMENTS_DIR);
+        fs::create_dir_all(&documents).map_err(|error| error.to_string())?;
+
+        let mut used_names = HashSet::new();
+        let mut entries = Vec::with_capacity(self.files.len());
+        for (index, file) in self.files.iter().enumerate() {
+            let invoice_number = file.invoice.text(INVOICE_NUMBER);
+            let source = Path::new(&file.stored_path);
+            let document = if source.exists() {
+                let name = document_name(file, invoice_number.as_deref(), &mut used_names);
+                fs::copy(source, documents.join(&name)).map_err(|error| error.to_string())?;
+                Some(format!("{}/{}", DOCUMENTS_DIR, name))
+            } else {
+                None
+            };
+
+            entries.push(ManifestEntry {
+                row: index + 1,
+                file_id: file.id.clone(),
+                invoice_number,
+                seller_name: file.invoice.text(SELLER_NAME),
+                original_file_name: file.file_name.clone(),
+                document,
+                hash: file.hash.clone(),
+                hash_algo: file.hash_algo.clone(),
+            });
+        }
+
+        let export_path = folder.join(self.format.export_name());
+        match self.format {
+            BundleFormat::Xml => {
+                fs::write(&export_path, xml_content.unwrap_or_default()).map_err(|error| error.to_string())?
+            }
+            BundleFormat::Xlsx => {
+                let documents: Vec<Option<String>> =
+                    entries.iter().map(|entry| entry.document.clone()).collect();
+                write_workbook(&export_path, &[self.invoice_sheet(&documents)])?
+            }
+        }
+
+        let manifest = BundleManifest {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            export: self.format.export_name().to_string(),
+            entries,
+            skipped_file_ids: self.skipped_file_ids.clone(),
+        };
+        let content = serde_json::to_string_pretty(&manifest).map_err(|error| error.to_string())?;
+        fs::write(folder.join(MANIFEST_NAME), content).map_err(|error| error.to_string())?;
+
+        Ok(manifest)
+    }
+
+    /// Just the invoice sheet, without documents or manifest.
+    pub fn write_sheet(&self, path: &Path) -> Result<(), String> {
+        write_workbook(path, &[self.sheet()])
+    }
+
+    /// The invoice sheet with its currency total rows, for callers that write it themselves.
+    pub fn sheet(&self) -> Sheet {
+        self.invoice_sheet(&[])
+    }
+
+    /// `documents` holds the bundle path of each row's source document, when copied.
+    fn invoice_sheet(&self, documents: &[Option<String>]) -> Sheet {
+        let mut rows: Vec<Vec<Cell>> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let invoice = &file.invoice;
+                vec![
+                    Cell::Number((index + 1) as f64),
+                    invoice.text(INVOICE_NUMBER).into(),
+                    invoice
+                        .text(INVOICE_DATE)
+                        .map(|date| Cell::date(&date))
+                        .unwrap_or(Cell::Empty),
+                    invoice.text(SELLER_NAME).into(),
+                    invoice.text(SELLER_GSTIN).into(),
+                    invoice.text(BUYER_NAME).into(),
+                    Cell::Text(file.currency.clone()),
+                    invoice.number(SUBTOTAL).into(),
+                    invoice.number(TAX_TOTAL).into(),
+                    invoice.number(GRAND_TOTAL).into(),
+                    documents.get(index).cloned().flatten().into(),
+                ]
+            })
+            .collect();
+        rows.extend(self.currency_total_rows());
+
+        Sheet {
+            name: "Invoices".to_string(),
+            headers: INVOICE_COLUMNS.iter().map(|header| header.to_string()).collect(),
+            rows,
+        }
+    }
+
+    /// Amounts in different currencies are never summed together: a sheet with several
+    /// currencies gets one subtotal row per currency and, when enabled, a grand total converted
+    /// to the base currency. Single-currency sheets are left as they are.
+    fn currency_total_rows(&self) -> Vec<Vec<Cell>> {
+        let mut totals: BTreeMap<&str, CurrencyTotal> = BTreeMap::new();
+        for file in &self.files {
+            totals
+                .entry(file.currency.as_str())
+                .or_default()
+                .add(&file.invoice);
+        }
+        if totals.len() < 2 {
+            return Vec::new();
+        }
+
+        let mut rows: Vec<Vec<Cell>> = totals
+            .iter()
+            .map(|(currency, total)| total.row(format!("Total {}", currency), currency))
+            .collect();
+
+        if let Some(rates) = &self.rates {
+            let missing: Vec<&str> = totals
+                .keys()
+                .copied()
+                .filter(|currency| !rates.contains_key(*currency))
+                .collect();
+            let label = format!("Grand total in {}", self.base_currency);
+            if missing.is_empty() {
+                let converted = totals
+                    .iter()
+                    .fold(CurrencyTotal::default(), |mut sum, (currency, total)| {
+                        sum.add_converted(total, rates[*currency]);
+                        sum
+                    });
+                rows.push(converted.row(label, &self.base_currency));
+            } else {
+                rows.push(vec![
+                    Cell::Empty,
+                    Cell::Text(label),
+                    Cell::Text(format!("No exchange rate for {}", missing.join(", "))),
+                ]);
+            }
+        }
+        rows
+    }
+}
+
+#[derive(Default)]
+struct CurrencyTotal {
+    subtotal: f64,
+    tax: f64,
+    grand_total: f64,
+}
+
+impl CurrencyTotal {
+    fn add(&mut self, invoice: &ParsedInvoice) {
+        self.subtotal += invoice.number(SUBTOTAL).unwrap_or(0.0);
+        self.tax += invoice.number(TAX_TOTAL).unwrap_or(0.0);
+        self.grand_total += invoice.number(GRAND_TOTAL).unwrap_or(0.0);
+    }
+
+    fn add_converted(&mut self, other: &CurrencyTotal, rate: f64) {
+        self.subtotal += other.subtotal * rate;
+        self.tax += other.tax * rate;
+        self.grand_total += other.grand_total * rate;
+    }
+
+    /// A row aligned with the invoice columns, labelled in the invoice number column.
+    fn row(&self, label: String, currency: &str) -> Vec<Cell> {
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+        vec![
+            Cell::Empty,
+            Cell::Text(label),
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Text(currency.to_string()),
+            Cell::Number(round(self.subtotal)),
+            Cell::Number(round(self.tax)),
+            Cell::Number(round(self.grand_total)),
+        ]
+    }
+}
+
+/// `<invoice number>.<ext>`, falling back to the original file name, made unique within the bundle.
+fn document_name(file: &BundleFile, invoice_number: Option<&str>, used: &mut HashSet<String>) -> String {
+    let original = Path::new(&file.file_name);
+    let extension = Path::new(&file.stored_path)
+        .extension()
+        .or_else(|| original.extension())
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    let fallback = original
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(&file.id);
+    let stem = match sanitize(invoice_number.unwrap_or_default()) {
+        stem if stem.is_empty() => match sanitize(fallback) {
+            stem if stem.is_empty() => file.id.clone(),
+            stem => stem,
+        },
+        stem => stem,
+    };
+
+    let with_extension = |stem: &str| match &extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem.to_string(),
+    };
+    let mut name = with_extension(&stem);
+    let mut counter = 2;
+    while !used.insert(name.to_ascii_lowercase()) {
+        name = with_extension(&format!("{}-{}", stem, counter));
+        counter += 1;
+    }
+    name
+}
+
+/// Invoice numbers often contain `/`; keep them readable as file names.
+fn sanitize(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for ch in value.trim().chars() {
+        if ch.is_alphanumeric() || "-_.".contains(ch) {
+            result.push(ch);
+        } else if !result.ends_with('-') {
+            result.push('-');
+        }
+    }
+    result.trim_matches(|ch| ch == '-' || ch == '.').to_string()
+}
+
+fn zip_folder(folder: &Path, destination: &Path) -> Result<(), String> {
+    let partial = destination.with_extension("zip.part");
+    let mut writer = ZipWriter::new(File::create(&partial).map_err(|error| error.to_string())?);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut pending = vec![PathBuf::from(folder)];
+    while let Some(dir) = pending.pop() {
+        let mut children = fs::read_dir(&dir)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        children.sort_by_key(|entry| entry.path());
+        for child in children {
+            let path = child.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = path
+                .strip_prefix(folder)
+                .map_err(|error| error.to_string())?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            writer
+                .start_file(name, options)
+                .map_err(|error| error.to_string())?;
+            let mut source = File::open(&path).map_err(|error| error.to_string())?;
+            io::copy(&mut source, &mut writer).map_err(|error| error.to_string())?;
+        }
+    }
+
+    writer
+        .finish()
+        .map_err(|error| error.to_string())?
+        .flush()
+        .map_err(|error| error.to_string())?;
+    fs::rename(&partial, destination).map_err(|error| error.to_string())
+}
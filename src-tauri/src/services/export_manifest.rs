@@ -0,0 +1,200 @@
+use crate::services::parsed_invoice::{ParsedInvoice, INVOICE_NUMBER};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Suffix of the manifest written next to every export.
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedFile {
+    pub file_id: String,
+    pub invoice_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRecord {
+    pub id: String,
+    /// What produced the export, e.g. `export_bundle` or `xml_export`.
+    pub kind: String,
+    /// Extension of the output: `xlsx`, `xml` or `zip`.
+    pub format: String,
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+    /// Invoice rows in the export, excluding headers and total rows.
+    pub row_count: usize,
+    pub files: Vec<ExportedFile>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportVerificationStatus {
+    Intact,
+    Modified,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportVerification {
+    pub export_id: String,
+    pub path: String,
+    pub status: ExportVerificationStatus,
+    pub expected_sha256: String,
+    /// `None` when the output no longer exists.
+    pub actual_sha256: Option<String>,
+    pub checked_at: String,
+}
+
+/// Manifests for generated exports. Each export gets a `<output>.manifest.json` next to it and
+/// a row in `exports`, so the output can be checked against its checksum later.
+pub struct ExportManifests;
+
+impl ExportManifests {
+    /// Hashes `output`, records it with the files it contains and writes the manifest beside it.
+    pub fn record(
+        conn: &Connection,
+        kind: &str,
+        output: &Path,
+        file_ids: &[String],
+        row_count: usize,
+    ) -> Result<ExportRecord, String> {
+        let files = file_ids
+            .iter()
+            .map(|file_id| {
+                let parsed_details: Option<String> = conn
+                    .query_row(
+                        "SELECT parsed_details FROM files WHERE id = ?1",
+                        params![file_id],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|error| error.to_string())?
+                    .flatten();
+                Ok(ExportedFile {
+                    file_id: file_id.clone(),
+                    invoice_number: parsed_details
+                        .as_deref()
+                        .and_then(ParsedInvoice::from_json)
+                        .and_then(|invoice| invoice.text(INVOICE_NUMBER)),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let record = ExportRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            format: output
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default()
+                .to_ascii_lowercase(),
+            path: output.to_string_lossy().into_owned(),
+            sha256: sha256_file(output)?,
+            size_bytes: fs::metadata(output).map_err(|error| error.to_string())?.len(),
+            row_count,
+            files,
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        let manifest = serde_json::to_string_pretty(&record).map_err(|error| error.to_string())?;
+        conn.execute(
+            "INSERT INTO exports (id, kind, format, path, sha256, size_bytes, row_count, manifest, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record.id,
+                record.kind,
+                record.format,
+                record.path,
+                record.sha256,
+                record.size_bytes as i64,
+                record.row_count as i64,
+                manifest,
+                record.created_at
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        fs::write(manifest_path(output), manifest).map_err(|error| error.to_string())?;
+
+        Ok(record)
+    }
+
+    pub fn get(conn: &Connection, export_id: &str) -> Result<ExportRecord, String> {
+        conn.query_row(
+            "SELECT manifest FROM exports WHERE id = ?1",
+            params![export_id],
+            read_manifest,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Export not found: {}", export_id))?
+    }
+
+    /// Most recent first.
+    pub fn list(conn: &Connection, limit: i64) -> Result<Vec<ExportRecord>, String> {
+        let mut stmt = conn
+            .prepare("SELECT manifest FROM exports ORDER BY created_at DESC LIMIT ?1")
+            .map_err(|error| error.to_string())?;
+        let records = stmt
+            .query_map(params![limit.max(1)], read_manifest)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        records.into_iter().collect()
+    }
+
+    /// Re-hashes the output and compares it with the checksum recorded when it was written.
+    pub fn verify(conn: &Connection, export_id: &str) -> Result<ExportVerification, String> {
+        let record = Self::get(conn, export_id)?;
+        let path = PathBuf::from(&record.path);
+        let actual_sha256 = if path.is_file() {
+            Some(sha256_file(&path)?)
+        } else {
+            None
+        };
+        let status = match &actual_sha256 {
+            None => ExportVerificationStatus::Missing,
+            Some(actual) if *actual == record.sha256 => ExportVerificationStatus::Intact,
+            Some(_) => ExportVerificationStatus::Modified,
+        };
+        Ok(ExportVerification {
+            export_id: record.id,
+            path: record.path,
+            status,
+            expected_sha256: record.sha256,
+            actual_sha256,
+            checked_at: Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+fn read_manifest(row: &Row) -> rusqlite::Result<Result<ExportRecord, String>> {
+    let manifest: String = row.get(0)?;
+    Ok(serde_json::from_str(&manifest).map_err(|error| error.to_string()))
+}
+
+fn manifest_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Streams the file so large zip bundles are not read into memory.
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|error| error.to_string())?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(|error| error.to_string())?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
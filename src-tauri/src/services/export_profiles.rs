@@ -0,0 +1,507 @@
+use crate::commands::xml_operations::create_xml_for_files;
+use crate::db::reports_dir;
+use crate::services::csv_export::CsvOptions;
+use crate::services::document_classifier::DocType;
+use crate::services::export_bundle::{BundleFormat, ExportBundle, INVOICE_COLUMNS};
+use crate::services::export_manifest::{ExportManifests, ExportRecord};
+use crate::services::report_period::ReportPeriod;
+use crate::services::scheduler::{ScheduledXmlExport, SCHEDULED_XML_EVENT};
+use crate::services::spreadsheet::{write_workbook, Cell, Sheet};
+use crate::services::telemetry::Telemetry;
+use chrono::{Local, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Runtime};
+
+const NAMING_PLACEHOLDERS: &[&str] = &["profile", "period", "date"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportProfileFormat {
+    Xlsx,
+    Csv,
+    TallyXml,
+}
+
+impl ExportProfileFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "xlsx" => Ok(ExportProfileFormat::Xlsx),
+            "csv" => Ok(ExportProfileFormat::Csv),
+            "tally_xml" | "tally" | "xml" => Ok(ExportProfileFormat::TallyXml),
+            other => Err(format!(
+                "Unsupported export format \"{}\". Use xlsx, csv or tally_xml.",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportProfileFormat::Xlsx => "xlsx",
+            ExportProfileFormat::Csv => "csv",
+            ExportProfileFormat::TallyXml => "tally_xml",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportProfileFormat::Xlsx => "xlsx",
+            ExportProfileFormat::Csv => "csv",
+            ExportProfileFormat::TallyXml => "xml",
+        }
+    }
+}
+
+/// One output column: an invoice sheet column, optionally under a different header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+    /// One of the invoice sheet headers, e.g. `Invoice Number`.
+    pub source: String,
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProfile {
+    pub id: i64,
+    pub name: String,
+    pub format: ExportProfileFormat,
+    /// Empty keeps every invoice sheet column. Ignored for Tally XML.
+    pub columns: Vec<ColumnMapping>,
+    /// Delimiter, decimal separator, date format and encoding for CSV output.
+    pub locale_options: CsvOptions,
+    /// Folder the export is written to; the reports folder when unset.
+    pub destination: Option<String>,
+    /// File name without extension, e.g. `invoices-{period}`.
+    pub naming_template: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProfileInput {
+    pub name: String,
+    pub format: String,
+    #[serde(default)]
+    pub columns: Vec<ColumnMapping>,
+    #[serde(default)]
+    pub locale_options: CsvOptions,
+    pub destination: Option<String>,
+    pub naming_template: String,
+}
+
+/// Which processed invoices a profile run exports. Everything is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFilter {
+    /// Invoice date range, in any form `ReportPeriod` accepts.
+    pub period: Option<String>,
+    pub status: Option<String>,
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    /// Document language as a Tesseract code, e.g. `deu`.
+    pub language: Option<String>,
+    /// E.g. only `invoice`, to keep credit notes out of an invoice sheet.
+    pub doc_type: Option<DocType>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRunResult {
+    pub profile_id: i64,
+    pub path: String,
+    pub file_count: usize,
+    /// Recorded manifest for XLSX and CSV output.
+    pub export: Option<ExportRecord>,
+    /// Sheet handed to the frontend to build and save the Tally XML.
+    pub xml_id: Option<i64>,
+}
+
+/// Saved export settings, so a recurring export is one `run` away.
+pub struct ExportProfiles;
+
+impl ExportProfiles {
+    pub fn list(conn: &Connection) -> Result<Vec<ExportProfile>, String> {
+        let mut stmt = conn
+            .prepare(&format!("{} ORDER BY name COLLATE NOCASE", SELECT_PROFILE))
+            .map_err(|error| error.to_string())?;
+        let profiles = stmt
+            .query_map([], map_profile)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        profiles.into_iter().collect()
+    }
+
+    pub fn get(conn: &Connection, profile_id: i64) -> Result<ExportProfile, String> {
+        conn.query_row(
+            &format!("{} WHERE id = ?1", SELECT_PROFILE),
+            params![profile_id],
+            map_profile,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Export profile not found: {}", profile_id))?
+    }
+
+    pub fn create(conn: &Connection, input: &ExportProfileInput) -> Result<ExportProfile, String> {
+        let values = ProfileValues::from_input(input)?;
+        conn.execute(
+            "INSERT INTO export_profiles (name, format, columns, locale_options, destination, naming_template)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                values.name,
+                values.format,
+                values.columns,
+                values.locale_options,
+                values.destination,
+                values.naming_template
+            ],
+        )
+        .map_err(|error| unique_name_error(error, &values.name))?;
+        Self::get(conn, conn.last_insert_rowid())
+    }
+
+    pub fn update(
+        conn: &Connection,
+        profile_id: i64,
+        input: &ExportProfileInput,
+    ) -> Result<ExportProfile, String> {
+        Self::get(conn, profile_id)?;
+        let values = ProfileValues::from_input(input)?;
+        conn.execute(
+            "UPDATE export_profiles SET name = ?2, format = ?3, columns = ?4, locale_options = ?5,
+               destination = ?6, naming_template = ?7, updated_at = ?8
+             WHERE id = ?1",
+            params![
+                profile_id,
+                values.name,
+                values.format,
+                values.columns,
+                values.locale_options,
+                values.destination,
+                values.naming_template,
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|error| unique_name_error(error, &values.name))?;
+        Self::get(conn, profile_id)
+    }
+
+    pub fn delete(conn: &Connection, profile_id: i64) -> Result<(), String> {
+        let deleted = conn
+            .execute("DELETE FROM export_profiles WHERE id = ?1", params![profile_id])
+            .map_err(|error| error.to_string())?;
+        if deleted == 0 {
+            return Err(format!("Export profile not found: {}", profile_id));
+        }
+        Ok(())
+    }
+
+    /// Exports the processed invoices matching `filter` with the profile's settings. Tally XML
+    /// is built by the frontend, so those runs create the XML sheet and hand it over like
+    /// scheduled exports do.
+    pub fn run<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        profile_id: i64,
+        filter: &ExportFilter,
+    ) -> Result<ExportRunResult, String> {
+        let profile = Self::get(conn, profile_id)?;
+        let period = filter.period.as_deref().map(ReportPeriod::parse).transpose()?;
+        let file_ids = matching_files(conn, period.as_ref(), filter)?;
+        if file_ids.is_empty() {
+            return Err("No processed invoices match the filter.".to_string());
+        }
+
+        let directory = match profile.destination.as_deref() {
+            Some(destination) => PathBuf::from(destination),
+            None => reports_dir().map_err(|error| error.to_string())?,
+        };
+        std::fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+        let period_label = filter.period.as_deref().map(str::trim).unwrap_or("all");
+        let file_name = render_name(&profile.naming_template, &profile.name, period_label);
+        let dest = directory.join(format!("{}.{}", file_name, profile.format.extension()));
+        let path = dest.to_string_lossy().to_string();
+
+        let (file_count, export, xml_id) = match profile.format {
+            ExportProfileFormat::TallyXml => {
+                let xml_id =
+                    create_xml_for_files(file_ids.clone(), format!("{} {}", profile.name, period_label))?;
+                app.emit(
+                    SCHEDULED_XML_EVENT,
+                    ScheduledXmlExport {
+                        xml_id,
+                        path: path.clone(),
+                    },
+                )
+                .map_err(|error| error.to_string())?;
+                (file_ids.len(), None, Some(xml_id))
+            }
+            format => {
+                let bundle = ExportBundle::load(conn, &file_ids, BundleFormat::Xlsx)?;
+                let sheet = map_columns(bundle.sheet(), &profile.columns)?;
+                if format == ExportProfileFormat::Csv {
+                    profile.locale_options.write(&dest, &sheet)?;
+                } else {
+                    write_workbook(&dest, &[sheet])?;
+                }
+                let export = ExportManifests::record(
+                    conn,
+                    "export_profile",
+                    &dest,
+                    &bundle.file_ids(),
+                    bundle.file_count(),
+                )?;
+                (bundle.file_count(), Some(export), None)
+            }
+        };
+
+        conn.execute(
+            "UPDATE export_profiles SET last_run_at = ?2 WHERE id = ?1",
+            params![profile_id, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| error.to_string())?;
+        Telemetry::record_export(conn, "export_profile");
+
+        Ok(ExportRunResult {
+            profile_id,
+            path,
+            file_count,
+            export,
+            xml_id,
+        })
+    }
+}
+
+const SELECT_PROFILE: &str = "SELECT id, name, format, columns, locale_options, destination, naming_template,
+       created_at, updated_at, last_run_at
+     FROM export_profiles";
+
+/// Validated input, serialized for storage.
+struct ProfileValues {
+    name: String,
+    format: &'static str,
+    columns: String,
+    locale_options: String,
+    destination: Option<String>,
+    naming_template: String,
+}
+
+impl ProfileValues {
+    fn from_input(input: &ExportProfileInput) -> Result<Self, String> {
+        let name = input.name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty.".to_string());
+        }
+        if input.format.trim().eq_ignore_ascii_case("ubl") {
+            return Err("UBL export is not supported yet.".to_string());
+        }
+        let format = ExportProfileFormat::parse(&input.format)?;
+        for column in &input.columns {
+            if !INVOICE_COLUMNS.contains(&column.source.as_str()) {
+                return Err(format!(
+                    "Unknown column \"{}\". Available: {}",
+                    column.source,
+                    INVOICE_COLUMNS.join(", ")
+                ));
+            }
+        }
+        input.locale_options.validate()?;
+        let naming_template = validate_template(&input.naming_template)?;
+
+        Ok(ProfileValues {
+            name: name.to_string(),
+            format: format.as_str(),
+            columns: serde_json::to_string(&input.columns).map_err(|error| error.to_string())?,
+            locale_options: serde_json::to_string(&input.locale_options)
+                .map_err(|error| error.to_string())?,
+            destination: input
+                .destination
+                .as_deref()
+                .map(str::trim)
+                .filter(|destination| !destination.is_empty())
+                .map(str::to_string),
+            naming_template,
+        })
+    }
+}
+
+fn map_profile(row: &Row) -> rusqlite::Result<Result<ExportProfile, String>> {
+    let format: String = row.get(2)?;
+    let columns: String = row.get(3)?;
+    let locale_options: String = row.get(4)?;
+    Ok((|| {
+        Ok(ExportProfile {
+            id: row.get(0).map_err(|error| error.to_string())?,
+            name: row.get(1).map_err(|error| error.to_string())?,
+            format: ExportProfileFormat::parse(&format)?,
+            columns: serde_json::from_str(&columns).map_err(|error| error.to_string())?,
+            locale_options: serde_json::from_str(&locale_options).map_err(|error| error.to_string())?,
+            destination: row.get(5).map_err(|error| error.to_string())?,
+            naming_template: row.get(6).map_err(|error| error.to_string())?,
+            created_at: row.get(7).map_err(|error| error.to_string())?,
+            updated_at: row.get(8).map_err(|error| error.to_string())?,
+            last_run_at: row.get(9).map_err(|error| error.to_string())?,
+        })
+    })())
+}
+
+fn unique_name_error(error: rusqlite::Error, name: &str) -> String {
+    match error {
+        rusqlite::Error::SqliteFailure(failure, _)
+            if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            format!("An export profile named \"{}\" already exists.", name)
+        }
+        other => other.to_string(),
+    }
+}
+
+fn validate_template(template: &str) -> Result<String, String> {
+    let template = template.trim();
+    if template.is_empty() {
+        return Err("Naming template cannot be empty.".to_string());
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|offset| start + offset)
+            .ok_or_else(|| "Naming template has an unclosed placeholder.".to_string())?;
+        let name = &rest[start + 1..end];
+        if !NAMING_PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "Unknown placeholder {{{}}}. Supported: {{profile}}, {{period}}, {{date}}",
+                name
+            ));
+        }
+        rest = &rest[end + 1..];
+    }
+    Ok(template.to_string())
+}
+
+/// Fills the naming template and strips characters that are not allowed in file names.
+fn render_name(template: &str, profile: &str, period: &str) -> String {
+    let rendered = template
+        .replace("{profile}", profile)
+        .replace("{period}", period)
+        .replace("{date}", &Local::now().format("%Y-%m-%d").to_string());
+    let cleaned: String = rendered
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            ch if ch.is_control() => '-',
+            ch => ch,
+        })
+        .collect();
+    match cleaned.trim().trim_matches('.') {
+        "" => "export".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Keeps and renames the mapped columns, in mapping order. Total rows are shorter than
+/// invoice rows, so missing cells stay empty.
+fn map_columns(sheet: Sheet, columns: &[ColumnMapping]) -> Result<Sheet, String> {
+    if columns.is_empty() {
+        return Ok(sheet);
+    }
+    let indexes = columns
+        .iter()
+        .map(|column| {
+            sheet
+                .headers
+                .iter()
+                .position(|header| *header == column.source)
+                .ok_or_else(|| format!("Unknown column \"{}\".", column.source))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Sheet {
+        name: sheet.name,
+        headers: columns
+            .iter()
+            .map(|column| {
+                column
+                    .header
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|header| !header.is_empty())
+                    .unwrap_or(&column.source)
+                    .to_string()
+            })
+            .collect(),
+        rows: sheet
+            .rows
+            .into_iter()
+            .map(|row| {
+                let mut cells: Vec<Option<Cell>> = row.into_iter().map(Some).collect();
+                indexes
+                    .iter()
+                    .map(|index| {
+                        cells
+                            .get_mut(*index)
+                            .and_then(Option::take)
+                            .unwrap_or(Cell::Empty)
+                    })
+                    .collect()
+            })
+            .collect(),
+    })
+}
+
+fn matching_files(
+    conn: &Connection,
+    period: Option<&ReportPeriod>,
+    filter: &ExportFilter,
+) -> Result<Vec<String>, String> {
+    let mut where_clauses = vec!["parsed_details IS NOT NULL"];
+    let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if let Some(period) = period {
+        where_clauses.push("invoice_date BETWEEN ? AND ?");
+        values.push(Box::new(period.start_iso()));
+        values.push(Box::new(period.end_iso()));
+    }
+    if let Some(status) = &filter.status {
+        where_clauses.push("status = ?");
+        values.push(Box::new(status.clone()));
+    }
+    if let Some(seller_id) = filter.seller_id {
+        where_clauses.push("seller_id = ?");
+        values.push(Box::new(seller_id));
+    }
+    if let Some(buyer_id) = filter.buyer_id {
+        where_clauses.push("buyer_id = ?");
+        values.push(Box::new(buyer_id));
+    }
+    if let Some(language) = &filter.language {
+        where_clauses.push("document_language = ?");
+        values.push(Box::new(language.clone()));
+    }
+    if let Some(doc_type) = filter.doc_type {
+        where_clauses.push("doc_type = ?");
+        values.push(Box::new(doc_type.as_str()));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id FROM files WHERE {} ORDER BY invoice_date, created_at",
+            where_clauses.join(" AND ")
+        ))
+        .map_err(|error| error.to_string())?;
+    let ids = stmt
+        .query_map(
+            rusqlite::params_from_iter(values.iter().map(|value| value.as_ref())),
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(ids)
+}
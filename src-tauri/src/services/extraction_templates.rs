@@ -0,0 +1,629 @@
+use crate::commands::file_operations::{update_file_status, FileStatus};
+use crate::commands::reprocess_operations::save_extraction_result;
+use crate::services::field_corrections::FieldCorrections;
+use crate::services::parse_metrics::{ParseMetrics, ParseOutcome, ParseRun, StageClock};
+use crate::services::parsed_invoice::{ITEMS, SELLER_ADDRESS, SELLER_GSTIN, SELLER_NAME};
+use crate::services::table_extraction::{TableExtractor, TableLineItem};
+use crate::services::template_selection::{TemplateDocument, TemplateMatch, TemplateSelector};
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Named groups an item pattern can capture, and the line item key each one fills.
+const ITEM_GROUPS: &[(&str, &str, bool)] = &[
+    ("description", "description", false),
+    ("name", "name", false),
+    ("hsn", "HSN/SAC", false),
+    ("quantity", "quantity", true),
+    ("unit", "unit", false),
+    ("rate", "rate", true),
+    ("amount", "amount", true),
+    ("cgst", "cgst", true),
+    ("sgst", "sgst", true),
+    ("cgst_rate", "cgst_rate", true),
+    ("sgst_rate", "sgst_rate", true),
+    ("igst", "igst", true),
+    ("igst_rate", "igst_rate", true),
+];
+
+const TEMPLATE_COLUMNS: &str = "t.id, t.name, t.seller_id, s.name, t.match_pattern, t.rules, t.item_pattern,
+     t.enabled, t.use_count, t.last_used_at, t.created_at, t.updated_at, t.logo_hash";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorPosition {
+    /// Rest of the anchor's line.
+    #[default]
+    Right,
+    /// Next non-empty line after the anchor.
+    Below,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateRule {
+    /// Key in `parsed_details`, e.g. `invoce number` or `grand total`.
+    pub field: String,
+    /// Label the value is printed next to, matched case-insensitively.
+    #[serde(default)]
+    pub anchor: Option<String>,
+    #[serde(default)]
+    pub position: AnchorPosition,
+    /// Applied to the anchored text, or to the whole document without an anchor. The first
+    /// capture group is the value when the pattern has one, otherwise the whole match.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Stores the value as a number, ignoring currency symbols and thousands separators.
+    #[serde(default)]
+    pub numeric: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInput {
+    pub name: String,
+    pub seller_id: Option<i64>,
+    /// Identifies the vendor's documents, typically their GSTIN or letterhead name.
+    pub match_pattern: String,
+    pub rules: Vec<TemplateRule>,
+    /// Matched against each line; named groups (`description`, `hsn`, `quantity`, `unit`,
+    /// `rate`, `amount`, ...) become a line item.
+    pub item_pattern: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionTemplate {
+    pub id: i64,
+    pub name: String,
+    pub seller_id: Option<i64>,
+    pub seller_name: Option<String>,
+    pub match_pattern: String,
+    pub rules: Vec<TemplateRule>,
+    pub item_pattern: Option<String>,
+    pub enabled: bool,
+    pub use_count: i64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Logo of the last document extracted with this template by hand; recognises the
+    /// vendor's letterhead when auto-selecting.
+    pub logo_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateFieldResult {
+    pub field: String,
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateRun {
+    /// Whether the match pattern was found in the document.
+    pub matched: bool,
+    pub fields: Vec<TemplateFieldResult>,
+    pub item_count: usize,
+    /// Every rule found a value.
+    pub complete: bool,
+    pub parsed_details: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateApplyResult {
+    pub template_id: i64,
+    pub template_name: String,
+    pub file_id: String,
+    /// `applied`, `unchanged` or `conflict`, as for any extraction result.
+    pub status: &'static str,
+    pub missing_fields: Vec<String>,
+    pub item_count: usize,
+}
+
+struct CompiledTemplate {
+    matcher: Regex,
+    rules: Vec<(TemplateRule, Option<Regex>)>,
+    items: Option<Regex>,
+}
+
+/// Deterministic extraction for recurring vendors: a template recognises a vendor's
+/// documents by `match_pattern` and reads each field with a regex, a label anchor, or
+/// both, from the PDF text layer. No model call is made, so it costs nothing and gives
+/// the same answer every time.
+pub struct ExtractionTemplates;
+
+impl ExtractionTemplates {
+    pub fn list(conn: &Connection) -> Result<Vec<ExtractionTemplate>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM extraction_templates t
+                 LEFT JOIN sellers s ON s.id = t.seller_id
+                 ORDER BY t.name COLLATE NOCASE",
+                TEMPLATE_COLUMNS
+            ))
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map([], template_from_row)
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn get(conn: &Connection, id: i64) -> Result<ExtractionTemplate, String> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM extraction_templates t
+                 LEFT JOIN sellers s ON s.id = t.seller_id
+                 WHERE t.id = ?1",
+                TEMPLATE_COLUMNS
+            ),
+            params![id],
+            template_from_row,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Extraction template not found: {}", id))
+    }
+
+    pub fn create(conn: &Connection, input: &TemplateInput) -> Result<i64, String> {
+        let name = validate(input)?;
+        let rules = serde_json::to_string(&input.rules).map_err(|error| error.to_string())?;
+        conn.execute(
+            "INSERT INTO extraction_templates (name, seller_id, match_pattern, rules, item_pattern, enabled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                name,
+                input.seller_id,
+                input.match_pattern.trim(),
+                rules,
+                item_pattern(input),
+                input.enabled
+            ],
+        )
+        .map_err(|error| unique_name_error(error, &name))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn update(conn: &Connection, id: i64, input: &TemplateInput) -> Result<(), String> {
+        let name = validate(input)?;
+        let rules = serde_json::to_string(&input.rules).map_err(|error| error.to_string())?;
+        let changed = conn
+            .execute(
+                "UPDATE extraction_templates
+                 SET name = ?1, seller_id = ?2, match_pattern = ?3, rules = ?4, item_pattern = ?5,
+                     enabled = ?6, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?7",
+                params![
+                    name,
+                    input.seller_id,
+                    input.match_pattern.trim(),
+                    rules,
+                    item_pattern(input),
+                    input.enabled,
+                    id
+                ],
+            )
+            .map_err(|error| unique_name_error(error, &name))?;
+        if changed == 0 {
+            return Err(format!("Extraction template not found: {}", id));
+        }
+        Ok(())
+    }
+
+    pub fn delete(conn: &Connection, id: i64) -> Result<(), String> {
+        conn.execute("DELETE FROM extraction_templates WHERE id = ?1", params![id])
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Runs a template, saved or not, against a stored file without saving anything.
+    pub fn test(conn: &Connection, input: &TemplateInput, file_id: &str) -> Result<TemplateRun, String> {
+        validate(input)?;
+        let compiled = compile(&input.match_pattern, &input.rules, input.item_pattern.as_deref())?;
+        let document = TemplateDocument::load(conn, file_id)?;
+        let mut run = compiled.run(&document.text);
+        if let Some(seller_id) = input.seller_id {
+            fill_seller(conn, seller_id, &mut run.parsed_details)?;
+        }
+        Ok(run)
+    }
+
+    /// Extracts a file with `template_id`, or with the template [`TemplateSelector`] picks
+    /// for it. Returns `None` when no template matches, or when
+    /// `require_complete` is set and a rule found nothing, so the caller can fall back to a
+    /// model.
+    pub fn apply(
+        conn: &Connection,
+        file_id: &str,
+        template_id: Option<i64>,
+        require_complete: bool,
+    ) -> Result<Option<TemplateApplyResult>, String> {
+        // Spares reading the PDF for every queued file when no template is set up.
+        if template_id.is_none() && !Self::any_enabled(conn)? {
+            return Ok(None);
+        }
+        let mut clock = StageClock::start();
+        let document = TemplateDocument::load(conn, file_id)?;
+        let document_ms = clock.lap();
+        let template = match template_id {
+            Some(id) => {
+                let template = Self::get(conn, id)?;
+                if !compile_template(&template)?.matcher.is_match(&document.text) {
+                    return Err(format!(
+                        "The template \"{}\" does not match this document.",
+                        template.name
+                    ));
+                }
+                TemplateSelector::remember_logo(conn, template.id, &document)?;
+                template
+            }
+            None => match TemplateSelector::select(conn, &document)?.template {
+                Some(template) => template,
+                None => return Ok(None),
+            },
+        };
+
+        let mut run = compile_template(&template)?.run(&document.text);
+        if template.item_pattern.is_none() {
+            add_table_items(conn, file_id, &mut run);
+        }
+        if require_complete && !run.complete {
+            return Ok(None);
+        }
+        if run.fields.iter().all(|field| field.value.is_none()) && run.item_count == 0 {
+            return Err(format!(
+                "The template \"{}\" found no values in this document.",
+                template.name
+            ));
+        }
+        if let Some(seller_id) = template.seller_id {
+            fill_seller(conn, seller_id, &mut run.parsed_details)?;
+        }
+        let model_ms = clock.lap();
+
+        update_file_status(file_id.to_string(), FileStatus::Processed)?;
+        let saved = save_extraction_result(file_id.to_string(), run.parsed_details.to_string())?;
+        if saved.status != "conflict" {
+            FieldCorrections::set_extracted_by(conn, file_id, &format!("template:{}", template.name))?;
+        }
+        ParseMetrics::record(
+            conn,
+            &ParseRun {
+                file_id,
+                provider: "template",
+                model: Some(&template.name),
+                attempts: 1,
+                outcome: ParseOutcome::from_saved(saved.status),
+                error: None,
+                document_ms,
+                model_ms,
+                save_ms: clock.lap(),
+                total_ms: clock.total(),
+            },
+        )?;
+        conn.execute(
+            "UPDATE extraction_templates
+             SET use_count = use_count + 1, last_used_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![template.id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(Some(TemplateApplyResult {
+            template_id: template.id,
+            template_name: template.name,
+            file_id: file_id.to_string(),
+            status: saved.status,
+            missing_fields: run
+                .fields
+                .into_iter()
+                .filter(|field| field.value.is_none())
+                .map(|field| field.field)
+                .collect(),
+            item_count: run.item_count,
+        }))
+    }
+
+    /// What [`Self::apply`] would pick for a file, and why.
+    pub fn explain(conn: &Connection, file_id: &str) -> Result<TemplateMatch, String> {
+        TemplateSelector::select(conn, &TemplateDocument::load(conn, file_id)?)
+    }
+
+    /// Whether the template's match pattern is in the text. A template whose pattern no
+    /// longer compiles never matches.
+    pub fn matches(template: &ExtractionTemplate, text: &str) -> bool {
+        compile_template(template).is_ok_and(|compiled| compiled.matcher.is_match(text))
+    }
+
+    fn any_enabled(conn: &Connection) -> Result<bool, String> {
+        conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM extraction_templates WHERE enabled = 1)",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())
+    }
+}
+
+impl CompiledTemplate {
+    fn run(&self, text: &str) -> TemplateRun {
+        let lines: Vec<&str> = text.lines().map(str::trim).collect();
+        let mut details = Map::new();
+        let fields: Vec<TemplateFieldResult> = self
+            .rules
+            .iter()
+            .map(|(rule, pattern)| {
+                let value = rule_value(rule, pattern.as_ref(), text, &lines);
+                if let Some(value) = &value {
+                    details.insert(rule.field.clone(), value.clone());
+                }
+                TemplateFieldResult {
+                    field: rule.field.clone(),
+                    value,
+                }
+            })
+            .collect();
+
+        let items: Vec<Value> = self
+            .items
+            .as_ref()
+            .map(|pattern| {
+                lines
+                    .iter()
+                    .filter_map(|line| item_value(pattern, line))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let item_count = items.len();
+        if !items.is_empty() {
+            details.insert(ITEMS.to_string(), Value::Array(items));
+        }
+
+        TemplateRun {
+            matched: self.matcher.is_match(text),
+            complete: fields.iter().all(|field| field.value.is_some()),
+            fields,
+            item_count,
+            parsed_details: Value::Object(details),
+        }
+    }
+}
+
+fn rule_value(rule: &TemplateRule, pattern: Option<&Regex>, text: &str, lines: &[&str]) -> Option<Value> {
+    let haystack = match &rule.anchor {
+        Some(anchor) => anchored_text(anchor, rule.position, lines)?,
+        None => text.to_string(),
+    };
+    let raw = match pattern {
+        Some(pattern) => {
+            let captures = pattern.captures(&haystack)?;
+            captures
+                .get(1)
+                .or_else(|| captures.get(0))?
+                .as_str()
+                .trim()
+                .to_string()
+        }
+        None => haystack.trim().to_string(),
+    };
+    if raw.is_empty() {
+        return None;
+    }
+    if rule.numeric {
+        parse_amount(&raw).map(Value::from)
+    } else {
+        Some(Value::String(raw))
+    }
+}
+
+fn anchored_text(anchor: &str, position: AnchorPosition, lines: &[&str]) -> Option<String> {
+    let needle = anchor.trim().to_lowercase();
+    let (index, line) = lines
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.to_lowercase().contains(&needle))?;
+    match position {
+        AnchorPosition::Right => {
+            let start = line.to_lowercase().find(&needle)? + needle.len();
+            let rest = line.get(start..)?;
+            Some(
+                rest.trim_start_matches([':', '-', '#', '.', ' ', '\t'])
+                    .to_string(),
+            )
+        }
+        AnchorPosition::Below => lines[index + 1..]
+            .iter()
+            .find(|line| !line.is_empty())
+            .map(|line| line.to_string()),
+    }
+}
+
+fn item_value(pattern: &Regex, line: &str) -> Option<Value> {
+    let captures = pattern.captures(line)?;
+    let mut item = Map::new();
+    for (group, key, numeric) in ITEM_GROUPS {
+        let Some(raw) = captures.name(group).map(|value| value.as_str().trim()) else {
+            continue;
+        };
+        let value = if *numeric {
+            parse_amount(raw).map(Value::from)
+        } else {
+            (!raw.is_empty()).then(|| Value::String(raw.to_string()))
+        };
+        if let Some(value) = value {
+            item.insert(key.to_string(), value);
+        }
+    }
+    (!item.is_empty()).then_some(Value::Object(item))
+}
+
+/// `₹ 1,18,000.00` and `(1,200.50)` style amounts; parentheses mean a negative amount.
+pub(crate) fn parse_amount(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    let start = raw.find(|character: char| character.is_ascii_digit())?;
+    let negative = (raw.starts_with('(') && raw.ends_with(')')) || raw[..start].ends_with('-');
+    let digits: String = raw[start..]
+        .chars()
+        .take_while(|character| character.is_ascii_digit() || matches!(character, ',' | '.'))
+        .filter(|character| *character != ',')
+        .collect();
+    let value = digits.trim_end_matches('.').parse::<f64>().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Line items from the document's item table, for templates without an item pattern.
+fn add_table_items(conn: &Connection, file_id: &str, run: &mut TemplateRun) {
+    let Ok(extraction) = TableExtractor::extract_file(conn, file_id) else {
+        return;
+    };
+    if let (false, Value::Object(details)) = (extraction.items.is_empty(), &mut run.parsed_details) {
+        run.item_count = extraction.items.len();
+        let items = extraction.items.iter().map(TableLineItem::to_json).collect();
+        details.insert(ITEMS.to_string(), Value::Array(items));
+    }
+}
+
+/// Seller details the template's vendor already has in master data, for fields no rule set.
+fn fill_seller(conn: &Connection, seller_id: i64, details: &mut Value) -> Result<(), String> {
+    let seller: Option<(String, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT name, tax_id, address FROM sellers WHERE id = ?1",
+            params![seller_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?;
+    let (Some((name, tax_id, address)), Value::Object(fields)) = (seller, details) else {
+        return Ok(());
+    };
+    for (key, value) in [
+        (SELLER_NAME, Some(name)),
+        (SELLER_GSTIN, tax_id),
+        (SELLER_ADDRESS, address),
+    ] {
+        if let Some(value) = value {
+            fields.entry(key).or_insert(Value::String(value));
+        }
+    }
+    Ok(())
+}
+
+fn validate(input: &TemplateInput) -> Result<String, String> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty.".to_string());
+    }
+    if input.match_pattern.trim().is_empty() {
+        return Err("A match pattern is required to recognise the vendor's documents.".to_string());
+    }
+    if input.rules.is_empty() && item_pattern(input).is_none() {
+        return Err("Add at least one field rule or an item pattern.".to_string());
+    }
+    for rule in &input.rules {
+        if rule.field.trim().is_empty() {
+            return Err("Every rule needs a field.".to_string());
+        }
+        if rule
+            .anchor
+            .as_deref()
+            .is_none_or(|anchor| anchor.trim().is_empty())
+            && rule
+                .pattern
+                .as_deref()
+                .is_none_or(|pattern| pattern.trim().is_empty())
+        {
+            return Err(format!(
+                "The rule for \"{}\" needs an anchor or a pattern.",
+                rule.field
+            ));
+        }
+    }
+    compile(&input.match_pattern, &input.rules, input.item_pattern.as_deref())?;
+    Ok(name.to_string())
+}
+
+fn item_pattern(input: &TemplateInput) -> Option<&str> {
+    input
+        .item_pattern
+        .as_deref()
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+}
+
+fn compile_template(template: &ExtractionTemplate) -> Result<CompiledTemplate, String> {
+    compile(
+        &template.match_pattern,
+        &template.rules,
+        template.item_pattern.as_deref(),
+    )
+}
+
+fn compile(
+    match_pattern: &str,
+    rules: &[TemplateRule],
+    item_pattern: Option<&str>,
+) -> Result<CompiledTemplate, String> {
+    let build = |pattern: &str, label: &str| {
+        RegexBuilder::new(pattern.trim())
+            .case_insensitive(true)
+            .multi_line(true)
+            .build()
+            .map_err(|error| format!("Invalid {}: {}", label, error))
+    };
+    let rules = rules
+        .iter()
+        .map(|rule| {
+            let pattern = rule
+                .pattern
+                .as_deref()
+                .filter(|pattern| !pattern.trim().is_empty())
+                .map(|pattern| build(pattern, &format!("pattern for \"{}\"", rule.field)))
+                .transpose()?;
+            Ok((rule.clone(), pattern))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(CompiledTemplate {
+        matcher: build(match_pattern, "match pattern")?,
+        rules,
+        items: item_pattern
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(|pattern| build(pattern, "item pattern"))
+            .transpose()?,
+    })
+}
+
+fn unique_name_error(error: rusqlite::Error, name: &str) -> String {
+    if error.to_string().contains("UNIQUE constraint failed") {
+        format!("A template named \"{}\" already exists.", name)
+    } else {
+        error.to_string()
+    }
+}
+
+fn template_from_row(row: &Row) -> rusqlite::Result<ExtractionTemplate> {
+    let rules: String = row.get(5)?;
+    Ok(ExtractionTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        seller_id: row.get(2)?,
+        seller_name: row.get(3)?,
+        match_pattern: row.get(4)?,
+        rules: serde_json::from_str(&rules).unwrap_or_default(),
+        item_pattern: row.get(6)?,
+        enabled: row.get(7)?,
+        use_count: row.get(8)?,
+        last_used_at: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+        logo_hash: row.get(12)?,
+    })
+}
@@ -0,0 +1,263 @@
+use crate::commands::file_operations::store_parsed_details;
+use crate::services::parsed_history::DetailsSource;
+use crate::services::report_period::ReportPeriod;
+use crate::services::roles::Roles;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use serde_json::Value;
+
+const CORRECTION_COLUMNS: &str =
+    "c.id, c.file_id, f.file_name, c.field, c.original_value, c.corrected_value, c.extracted_by, c.editor, c.role, c.created_at";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldCorrection {
+    pub id: i64,
+    pub file_id: String,
+    pub file_name: Option<String>,
+    /// Path into `parsed_details`, e.g. `grand total` or `items[2].rate`.
+    pub field: String,
+    pub original_value: Value,
+    pub corrected_value: Value,
+    /// What produced the original value, e.g. `template:Acme` or `gemini:gemini-2.5-flash`.
+    pub extracted_by: Option<String>,
+    pub editor: Option<String>,
+    pub role: String,
+    pub created_at: String,
+}
+
+/// How often one field needed correcting after one extractor.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionStat {
+    pub extracted_by: Option<String>,
+    /// Item fields are counted together, as `items[].rate`.
+    pub field: String,
+    pub corrections: i64,
+    pub files: i64,
+}
+
+/// Manual edits of single extracted values, kept next to what produced the value so the
+/// accuracy of each template and model can be followed over time.
+pub struct FieldCorrections;
+
+impl FieldCorrections {
+    /// Sets one value in the file's parsed details and records the change. Returns `None`
+    /// when the value was already the same.
+    pub fn correct(
+        conn: &Connection,
+        file_id: &str,
+        field: &str,
+        value: Value,
+        editor: Option<&str>,
+    ) -> Result<Option<FieldCorrection>, String> {
+        let (parsed_details, extracted_by): (Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT parsed_details, extracted_by FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let mut details = match parsed_details.as_deref().map(serde_json::from_str) {
+            Some(Ok(Value::Object(details))) => Value::Object(details),
+            _ => return Err("This file has no parsed details to correct.".to_string()),
+        };
+
+        let slot = field_slot(&mut details, field)?;
+        let original = slot.take();
+        if original == value {
+            *slot = original;
+            return Ok(None);
+        }
+        *slot = value.clone();
+
+        store_parsed_details(conn, file_id, &details.to_string(), DetailsSource::ManualEdit)?;
+        conn.execute(
+            "INSERT INTO field_corrections (
+               file_id, field, original_value, corrected_value, extracted_by, editor, role
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                file_id,
+                field,
+                original.to_string(),
+                value.to_string(),
+                extracted_by,
+                editor.map(str::trim).filter(|editor| !editor.is_empty()),
+                Roles::current(conn)?.as_str()
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM field_corrections c LEFT JOIN files f ON f.id = c.file_id WHERE c.id = ?1",
+                CORRECTION_COLUMNS
+            ),
+            params![conn.last_insert_rowid()],
+            correction_from_row,
+        )
+        .map(Some)
+        .map_err(|error| error.to_string())
+    }
+
+    /// Corrections newest first, for one file or across the workspace.
+    pub fn list(
+        conn: &Connection,
+        file_id: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Vec<FieldCorrection>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM field_corrections c LEFT JOIN files f ON f.id = c.file_id
+                 WHERE (?1 IS NULL OR c.file_id = ?1)
+                 ORDER BY c.created_at DESC, c.id DESC
+                 LIMIT ?2",
+                CORRECTION_COLUMNS
+            ))
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params![file_id, limit.unwrap_or(-1)], correction_from_row)
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    /// Correction counts per extractor and field, optionally within a period of
+    /// correction dates, most corrected first.
+    pub fn stats(conn: &Connection, period: Option<&str>) -> Result<Vec<CorrectionStat>, String> {
+        let period = period.map(ReportPeriod::parse).transpose()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.extracted_by, c.field, c.file_id FROM field_corrections c
+                 WHERE (?1 IS NULL OR DATE(c.created_at) >= ?1)
+                 AND (?2 IS NULL OR DATE(c.created_at) <= ?2)",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    period.as_ref().map(ReportPeriod::start_iso),
+                    period.as_ref().map(ReportPeriod::end_iso)
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut stats: Vec<(CorrectionStat, Vec<String>)> = Vec::new();
+        for (extracted_by, field, file_id) in rows {
+            let field = item_field(&field);
+            let index = match stats
+                .iter()
+                .position(|(stat, _)| stat.extracted_by == extracted_by && stat.field == field)
+            {
+                Some(index) => index,
+                None => {
+                    stats.push((
+                        CorrectionStat {
+                            extracted_by,
+                            field,
+                            corrections: 0,
+                            files: 0,
+                        },
+                        Vec::new(),
+                    ));
+                    stats.len() - 1
+                }
+            };
+            let (stat, files) = &mut stats[index];
+            stat.corrections += 1;
+            if !files.contains(&file_id) {
+                files.push(file_id);
+                stat.files += 1;
+            }
+        }
+
+        let mut stats: Vec<CorrectionStat> = stats.into_iter().map(|(stat, _)| stat).collect();
+        stats.sort_by(|a, b| {
+            b.corrections
+                .cmp(&a.corrections)
+                .then_with(|| a.field.cmp(&b.field))
+        });
+        Ok(stats)
+    }
+
+    /// Records what extracted the file's current details, for later corrections to refer to.
+    pub fn set_extracted_by(conn: &Connection, file_id: &str, extracted_by: &str) -> Result<(), String> {
+        conn.execute(
+            "UPDATE files SET extracted_by = ?1 WHERE id = ?2",
+            params![extracted_by, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
+
+/// The value at `field`, a top-level key optionally followed by `[index]` and a key of
+/// the item, as in `items[2].rate`. A missing top-level or item key is created.
+fn field_slot<'a>(details: &'a mut Value, field: &str) -> Result<&'a mut Value, String> {
+    let invalid = || format!("Invalid field: {}", field);
+    let (key, item) = match field.split_once('[') {
+        Some((key, rest)) => {
+            let (index, item_key) = rest.split_once("].").ok_or_else(invalid)?;
+            let index: usize = index.parse().map_err(|_| invalid())?;
+            (key, Some((index, item_key)))
+        }
+        None => (field, None),
+    };
+    if key.trim().is_empty() {
+        return Err(invalid());
+    }
+
+    let object = details.as_object_mut().ok_or_else(invalid)?;
+    let value = object.entry(key.to_string()).or_insert(Value::Null);
+    let Some((index, item_key)) = item else {
+        return Ok(value);
+    };
+    let item = value
+        .as_array_mut()
+        .and_then(|items| items.get_mut(index))
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| format!("{} has no item {}.", key, index))?;
+    if item_key.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok(item.entry(item_key.to_string()).or_insert(Value::Null))
+}
+
+/// `items[2].rate` becomes `items[].rate`, so every line's corrections add up.
+fn item_field(field: &str) -> String {
+    match (field.find('['), field.find(']')) {
+        (Some(open), Some(close)) if open < close => format!("{}[]{}", &field[..open], &field[close + 1..]),
+        _ => field.to_string(),
+    }
+}
+
+fn correction_from_row(row: &Row) -> rusqlite::Result<FieldCorrection> {
+    let json = |value: Option<String>| {
+        value
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or(Value::Null)
+    };
+    Ok(FieldCorrection {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_name: row.get(2)?,
+        field: row.get(3)?,
+        original_value: json(row.get(4)?),
+        corrected_value: json(row.get(5)?),
+        extracted_by: row.get(6)?,
+        editor: row.get(7)?,
+        role: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
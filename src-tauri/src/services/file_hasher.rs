@@ -1,10 +1,60 @@
-use blake3;
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const HASH_ALGORITHM_KEY: &str = "files.hash_algorithm";
+
+/// Content hash recorded for each imported file in `files.hash_algo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Blake3,
+    /// For checking files with standard tools such as `sha256sum`.
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 2] = [HashAlgorithm::Blake3, HashAlgorithm::Sha256];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "sha256" | "sha-256" => Ok(HashAlgorithm::Sha256),
+            other => Err(format!("Unsupported hash algorithm: {}", other)),
+        }
+    }
+}
 
 pub struct FileHasher;
 
 impl FileHasher {
-    pub fn calculate_hash(buffer: &[u8]) -> String {
-        let hash = blake3::hash(buffer);
-        hash.to_hex().to_string()
+    pub fn calculate_hash(algorithm: HashAlgorithm, buffer: &[u8]) -> String {
+        match algorithm {
+            HashAlgorithm::Blake3 => blake3::hash(buffer).to_hex().to_string(),
+            HashAlgorithm::Sha256 => Sha256::digest(buffer)
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        }
+    }
+
+    /// Algorithm used for newly imported files. Existing files keep the one they were hashed with.
+    pub fn configured(conn: &Connection) -> Result<HashAlgorithm, String> {
+        match Settings::get(conn, HASH_ALGORITHM_KEY)? {
+            Some(value) => HashAlgorithm::parse(&value),
+            None => Ok(HashAlgorithm::Blake3),
+        }
+    }
+
+    pub fn set_configured(conn: &Connection, algorithm: HashAlgorithm) -> Result<(), String> {
+        Settings::set(conn, HASH_ALGORITHM_KEY, algorithm.as_str())
     }
 }
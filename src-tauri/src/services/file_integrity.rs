@@ -0,0 +1,170 @@
+use crate::services::file_hasher::{FileHasher, HashAlgorithm};
+use crate::services::file_storage::FileStorage;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashStatus {
+    Ok,
+    Mismatch,
+    Missing,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHashCheck {
+    pub file_id: String,
+    pub file_name: String,
+    pub stored_path: String,
+    pub hash_algo: HashAlgorithm,
+    pub expected: String,
+    /// Hash of the stored file as it is now; absent when the file is missing.
+    pub actual: Option<String>,
+    pub status: HashStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVerificationReport {
+    pub checked: usize,
+    pub mismatched: usize,
+    pub missing: usize,
+    pub files: Vec<FileHashCheck>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkResult {
+    pub file_id: String,
+    pub stored_path: String,
+    /// False when the replacement differed and its hash was accepted instead.
+    pub hash_matched: bool,
+    pub previous_hash: String,
+    pub content_hash: String,
+}
+
+/// Re-hashes stored documents with the algorithm each was imported with.
+pub struct FileIntegrity;
+
+impl FileIntegrity {
+    /// Checks `file_ids`, or every file when none are given.
+    pub fn verify(conn: &Connection, file_ids: Option<&[String]>) -> Result<FileVerificationReport, String> {
+        let filter = match file_ids {
+            Some([]) => return Ok(Self::report(Vec::new())),
+            Some(ids) => format!(
+                "WHERE id IN ({})",
+                ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+            ),
+            None => String::new(),
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, file_name, stored_path, hash_algo, content_hash FROM files {} ORDER BY created_at",
+                filter
+            ))
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params_from_iter(file_ids.unwrap_or_default()), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut files = Vec::with_capacity(rows.len());
+        for (file_id, file_name, stored_path, hash_algo, expected) in rows {
+            let hash_algo = HashAlgorithm::parse(&hash_algo)?;
+            let actual = fs::read(&stored_path)
+                .ok()
+                .map(|buffer| FileHasher::calculate_hash(hash_algo, &buffer));
+            let status = match &actual {
+                None => HashStatus::Missing,
+                Some(actual) if actual.eq_ignore_ascii_case(&expected) => HashStatus::Ok,
+                Some(_) => HashStatus::Mismatch,
+            };
+            files.push(FileHashCheck {
+                file_id,
+                file_name,
+                stored_path,
+                hash_algo,
+                expected,
+                actual,
+                status,
+            });
+        }
+
+        Ok(Self::report(files))
+    }
+
+    /// Points a record whose stored copy is gone at `replacement`, copying it into storage.
+    /// The replacement must hash to the recorded value unless `accept_new_hash` is set, in
+    /// which case the record takes the replacement's hash and size.
+    pub fn relink(
+        conn: &Connection,
+        file_id: &str,
+        replacement: &Path,
+        accept_new_hash: bool,
+    ) -> Result<RelinkResult, String> {
+        let (file_name, stored_path, hash_algo, expected): (String, String, String, String) = conn
+            .query_row(
+                "SELECT file_name, stored_path, hash_algo, content_hash FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        if Path::new(&stored_path).is_file() {
+            return Err(format!("The stored copy of {} is not missing.", file_name));
+        }
+
+        let hash_algo = HashAlgorithm::parse(&hash_algo)?;
+        let buffer =
+            fs::read(replacement).map_err(|error| format!("{}: {}", replacement.display(), error))?;
+        let actual = FileHasher::calculate_hash(hash_algo, &buffer);
+        let hash_matched = actual.eq_ignore_ascii_case(&expected);
+        if !hash_matched && !accept_new_hash {
+            return Err(format!(
+                "{} is not the same document as {}: its {} hash differs.",
+                replacement.display(),
+                file_name,
+                hash_algo.as_str()
+            ));
+        }
+
+        let stored = FileStorage::save_file(file_id, &file_name, &buffer)?;
+        let stored_path = stored.to_string_lossy().into_owned();
+        conn.execute(
+            "UPDATE files SET stored_path = ?1, content_hash = ?2, size_bytes = ?3 WHERE id = ?4",
+            params![stored_path, actual, buffer.len() as i64, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(RelinkResult {
+            file_id: file_id.to_string(),
+            stored_path,
+            hash_matched,
+            previous_hash: expected,
+            content_hash: actual,
+        })
+    }
+
+    fn report(files: Vec<FileHashCheck>) -> FileVerificationReport {
+        let count = |status: HashStatus| files.iter().filter(|file| file.status == status).count();
+        FileVerificationReport {
+            checked: files.len(),
+            mismatched: count(HashStatus::Mismatch),
+            missing: count(HashStatus::Missing),
+            files,
+        }
+    }
+}
@@ -1,16 +1,18 @@
 use crate::db::get_connection;
-use rusqlite::params;
+use crate::services::file_hasher::HashAlgorithm;
+use rusqlite::{params, OptionalExtension};
 
 pub struct FileMetadata;
 
 impl FileMetadata {
-    pub fn check_duplicate(hash: &str) -> Result<Option<String>, String> {
+    pub fn check_duplicate(hash: &str, algorithm: HashAlgorithm) -> Result<Option<String>, String> {
         let conn = get_connection().map_err(|error| error.to_string())?;
         let mut stmt = conn
-            .prepare("SELECT id FROM files WHERE hash_sha256 = ?1 LIMIT 1")
+            .prepare("SELECT id FROM files WHERE content_hash = ?1 AND hash_algo = ?2 LIMIT 1")
             .map_err(|error| error.to_string())?;
 
-        let existing: Result<String, _> = stmt.query_row(params![hash], |row| row.get(0));
+        let existing: Result<String, _> =
+            stmt.query_row(params![hash, algorithm.as_str()], |row| row.get(0));
 
         match existing {
             Ok(id) => Ok(Some(id)),
@@ -19,24 +21,39 @@ impl FileMetadata {
         }
     }
 
+    pub fn find_by_name_and_size(file_name: &str, size: i64) -> Result<Option<String>, String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        conn.query_row(
+            "SELECT id FROM files WHERE file_name = ?1 AND size_bytes = ?2 ORDER BY created_at LIMIT 1",
+            params![file_name, size],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())
+    }
+
     pub fn save_metadata(
         id: &str,
         hash: &str,
+        algorithm: HashAlgorithm,
         file_name: &str,
         stored_path: &str,
         size: i64,
+        source: Option<&str>,
     ) -> Result<(), String> {
         let conn = get_connection().map_err(|error| error.to_string())?;
         
         conn.execute(
-            "INSERT INTO files (id, hash_sha256, file_name, stored_path, size_bytes, parsed_details)
-             VALUES (?1, ?2, ?3, ?4, ?5, NULL)",
+            "INSERT INTO files (id, content_hash, hash_algo, file_name, stored_path, size_bytes, parsed_details, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, ?7)",
             params![
                 id,
                 hash,
+                algorithm.as_str(),
                 file_name,
                 stored_path,
-                size
+                size,
+                source
             ],
         )
         .map_err(|error| error.to_string())?;
@@ -0,0 +1,126 @@
+use crate::services::parsed_invoice::{
+    ParsedInvoice, BUYER_NAME, GRAND_TOTAL, INVOICE_DATE, INVOICE_NUMBER, SELLER_NAME, VOUCHER_TYPE,
+};
+use std::path::Path;
+
+const PLACEHOLDERS: &[&str] = &[
+    "seller",
+    "buyer",
+    "invoice_number",
+    "date",
+    "total",
+    "doc_type",
+    "original",
+];
+
+/// Values a file name template can reference, gathered from the file row and its parsed details.
+pub struct TemplateContext<'a> {
+    pub original_name: &'a str,
+    pub invoice_date: Option<&'a str>,
+    pub invoice: Option<&'a ParsedInvoice>,
+}
+
+/// A validated file name template such as `{seller}-{invoice_number}-{date}`.
+pub struct FileNameTemplate {
+    template: String,
+}
+
+impl FileNameTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err("Template cannot be empty.".to_string());
+        }
+
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|offset| start + offset)
+                .ok_or_else(|| "Template has an unclosed placeholder.".to_string())?;
+            let name = &rest[start + 1..end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Unknown placeholder {{{}}}. Supported: {}",
+                    name,
+                    PLACEHOLDERS
+                        .iter()
+                        .map(|placeholder| format!("{{{}}}", placeholder))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            rest = &rest[end + 1..];
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+        })
+    }
+
+    /// Resolves every placeholder and keeps the original extension.
+    /// Returns `None` when the result would be empty after sanitizing.
+    pub fn render(&self, context: &TemplateContext) -> Option<String> {
+        let original = Path::new(context.original_name);
+        let stem = original
+            .file_stem()
+            .and_then(|value| value.to_str())
+            .unwrap_or(context.original_name);
+        let extension = original.extension().and_then(|value| value.to_str());
+
+        let mut rendered = self.template.clone();
+        for placeholder in PLACEHOLDERS {
+            let token = format!("{{{}}}", placeholder);
+            if !rendered.contains(&token) {
+                continue;
+            }
+            let value = Self::resolve(placeholder, stem, context).unwrap_or_default();
+            rendered = rendered.replace(&token, &sanitize(&value));
+        }
+
+        let rendered = rendered
+            .trim_matches(|c: char| c == '-' || c == '_' || c == '.' || c.is_whitespace())
+            .to_string();
+        if rendered.is_empty() {
+            return None;
+        }
+
+        Some(match extension {
+            Some(extension) => format!("{}.{}", rendered, extension),
+            None => rendered,
+        })
+    }
+
+    fn resolve(placeholder: &str, stem: &str, context: &TemplateContext) -> Option<String> {
+        let invoice = context.invoice;
+        match placeholder {
+            "seller" => invoice.and_then(|invoice| invoice.text(SELLER_NAME)),
+            "buyer" => invoice.and_then(|invoice| invoice.text(BUYER_NAME)),
+            "invoice_number" => invoice.and_then(|invoice| invoice.text(INVOICE_NUMBER)),
+            "date" => context
+                .invoice_date
+                .map(str::to_string)
+                .or_else(|| invoice.and_then(|invoice| invoice.text(INVOICE_DATE))),
+            "total" => invoice
+                .and_then(|invoice| invoice.number(GRAND_TOTAL))
+                .map(|total| format!("{:.2}", total)),
+            "doc_type" => invoice.and_then(|invoice| invoice.text(VOUCHER_TYPE)),
+            "original" => Some(stem.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Drops characters that are not safe in file names on any supported platform.
+fn sanitize(value: &str) -> String {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -20,7 +20,14 @@ impl FileStorage {
             storage.join(format!("{}.{}", id, ext))
         };
 
-        fs::write(&stored_path, buffer).map_err(|error| error.to_string())?;
+        // Written beside the final name and renamed, so an interrupted import never leaves a
+        // truncated document in storage.
+        let partial_path = stored_path.with_extension("part");
+        fs::write(&partial_path, buffer).map_err(|error| error.to_string())?;
+        if let Err(error) = fs::rename(&partial_path, &stored_path) {
+            let _ = fs::remove_file(&partial_path);
+            return Err(error.to_string());
+        }
 
         Ok(stored_path)
     }
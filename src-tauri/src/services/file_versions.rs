@@ -0,0 +1,144 @@
+use crate::services::parsed_invoice::{ParsedInvoice, INVOICE_NUMBER, SELLER_NAME};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    pub id: String,
+    pub file_name: String,
+    pub content_hash: String,
+    pub created_at: String,
+    pub supersedes: Option<String>,
+    pub latest: bool,
+}
+
+/// Links re-issued copies of an invoice. A file with the same seller and invoice number as
+/// an older one but different content supersedes it; both blobs stay in storage and file
+/// listings show only the newest version unless asked for all of them.
+pub struct FileVersions;
+
+impl FileVersions {
+    /// Seller + invoice number. Unlike the double-payment key the amount is left out, since a
+    /// corrected copy often changes it.
+    pub fn version_key(invoice: &ParsedInvoice) -> Option<String> {
+        let seller: String = invoice
+            .text(SELLER_NAME)?
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|ch| ch.to_ascii_lowercase())
+            .collect();
+        let number: String = invoice
+            .text(INVOICE_NUMBER)?
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|ch| ch.to_ascii_uppercase())
+            .collect();
+
+        if seller.is_empty() || number.is_empty() {
+            return None;
+        }
+
+        Some(format!("{}|{}", seller, number))
+    }
+
+    /// Stores the file's version key and splices it into its chain between the versions
+    /// imported just before and just after it, so an older copy extracted late still lands
+    /// behind the newer one.
+    pub fn update(conn: &Connection, file_id: &str, parsed_details: Option<&str>) -> Result<(), String> {
+        let key = parsed_details
+            .and_then(ParsedInvoice::from_json)
+            .and_then(|invoice| Self::version_key(&invoice));
+        let (previous_key, content_hash, created_at): (Option<String>, String, String) = conn
+            .query_row(
+                "SELECT version_key, content_hash, created_at FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|error| error.to_string())?;
+        if previous_key == key {
+            return Ok(());
+        }
+
+        // The file no longer belongs to its old chain: reconnect around it.
+        conn.execute(
+            "UPDATE files SET supersedes = (SELECT supersedes FROM files WHERE id = ?1)
+             WHERE supersedes = ?1",
+            params![file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        conn.execute(
+            "UPDATE files SET version_key = ?1, supersedes = NULL WHERE id = ?2",
+            params![key, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        let Some(key) = key else {
+            return Ok(());
+        };
+
+        let older: Option<String> = conn
+            .query_row(
+                "SELECT id FROM files
+                 WHERE version_key = ?1 AND id != ?2 AND content_hash != ?3 AND created_at <= ?4
+                 ORDER BY created_at DESC LIMIT 1",
+                params![key, file_id, content_hash, created_at],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        let newer: Option<String> = conn
+            .query_row(
+                "SELECT id FROM files
+                 WHERE version_key = ?1 AND id != ?2 AND content_hash != ?3 AND created_at > ?4
+                 ORDER BY created_at ASC LIMIT 1",
+                params![key, file_id, content_hash, created_at],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+
+        conn.execute(
+            "UPDATE files SET supersedes = ?1 WHERE id = ?2",
+            params![older, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        if let Some(newer) = newer {
+            conn.execute(
+                "UPDATE files SET supersedes = ?1 WHERE id = ?2",
+                params![file_id, newer],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Every version of the file's invoice, oldest first.
+    pub fn history(conn: &Connection, file_id: &str) -> Result<Vec<FileVersion>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_name, content_hash, created_at, supersedes,
+                        NOT EXISTS (SELECT 1 FROM files n WHERE n.supersedes = f.id)
+                 FROM files f
+                 WHERE f.id = ?1
+                    OR (f.version_key IS NOT NULL
+                        AND f.version_key = (SELECT version_key FROM files WHERE id = ?1))
+                 ORDER BY f.created_at ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let versions = stmt
+            .query_map(params![file_id], |row| {
+                Ok(FileVersion {
+                    id: row.get(0)?,
+                    file_name: row.get(1)?,
+                    content_hash: row.get(2)?,
+                    created_at: row.get(3)?,
+                    supersedes: row.get(4)?,
+                    latest: row.get(5)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(versions)
+    }
+}
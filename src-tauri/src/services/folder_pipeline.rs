@@ -0,0 +1,405 @@
+use crate::commands::file_operations::{import_file, update_file_status, FileStatus};
+use crate::commands::reprocess_operations::save_extraction_result;
+use crate::commands::xml_operations::{append_xml_file, create_xml_for_files};
+use crate::services::connectivity::Connectivity;
+use crate::services::gemini::{mime_for, GeminiClient, GeminiRequest, GeminiResponse};
+use crate::services::import_batches::{ImportBatches, ImportSource};
+use crate::services::llm_provider::{LlmProviderKind, LlmProviders};
+use crate::services::parse_metrics::{ParseMetrics, ParseOutcome, ParseRun, StageClock};
+use crate::services::processing_pause::ProcessingPause;
+use crate::services::processing_usage::{ProcessingUsage, UsageRecord};
+use crate::services::prompt_templates::{ActivePrompts, PromptTemplates};
+use crate::services::scheduler::{ScheduledXmlExport, SCHEDULED_XML_EVENT};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with a [`FolderPipelineProgress`] after every file in each stage.
+pub const FOLDER_PIPELINE_PROGRESS_EVENT: &str = "pipeline://progress";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    Import,
+    Extract,
+    Export,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderPipelineProgress {
+    pub stage: PipelineStage,
+    pub completed: usize,
+    pub total: usize,
+    pub file_name: Option<String>,
+}
+
+/// Everything the backend needs to extract without the frontend. The API key, model, prompts
+/// and schema are the ones the frontend would use itself.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessFolderOptions {
+    pub api_key: String,
+    pub model: String,
+    /// Bundled prompts; the stored active versions win, as for `get_active_prompts`.
+    pub prompts: ActivePrompts,
+    pub response_schema: Option<Value>,
+    #[serde(default)]
+    pub recursive: bool,
+    /// Existing sheet to append to.
+    pub sheet_id: Option<i64>,
+    /// Name of a new sheet, used when `sheet_id` is not set.
+    pub sheet_name: Option<String>,
+    /// Where to write the Tally XML for the sheet, if wanted.
+    pub xml_output_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineFailure {
+    pub file_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderPipelineSummary {
+    pub folder: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub imported: usize,
+    /// Already in the library; reused, and only extracted if never processed.
+    pub duplicates: usize,
+    /// Files in the folder that are not documents Invox can read.
+    pub unsupported: usize,
+    pub processed: usize,
+    pub waiting_for_network: usize,
+    pub failures: Vec<PipelineFailure>,
+    /// Extractions that differed from existing details and wait for review.
+    pub conflicts: usize,
+    pub sheet_id: Option<i64>,
+    pub xml_output_path: Option<String>,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+/// "Dump a folder, get outputs": imports every document in a folder, extracts the new
+/// ones, adds them to a sheet and optionally hands the sheet's XML to the frontend to write.
+pub struct FolderPipeline;
+
+impl FolderPipeline {
+    pub fn run<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        folder: &Path,
+        profile: Option<String>,
+        options: &ProcessFolderOptions,
+    ) -> Result<FolderPipelineSummary, String> {
+        if !folder.is_dir() {
+            return Err(format!("{} is not a folder.", folder.display()));
+        }
+        if ProcessingPause::is_paused(conn)? {
+            return Err("Processing is paused. Resume it before processing a folder.".to_string());
+        }
+        let client = GeminiClient::new(&options.api_key, &options.model)?;
+        let prompts = PromptTemplates::active_or_seed(conn, &options.prompts)?;
+        let temperature = LlmProviders::temperature(conn)?;
+
+        let mut summary = FolderPipelineSummary {
+            folder: folder.to_string_lossy().into_owned(),
+            started_at: Utc::now().to_rfc3339(),
+            ..FolderPipelineSummary::default()
+        };
+
+        let (documents, unsupported) = collect_documents(folder, options.recursive)?;
+        summary.unsupported = unsupported;
+        let mut file_ids = Vec::with_capacity(documents.len());
+        let batch_id = ImportBatches::begin(conn, ImportSource::FolderPipeline, Some(&summary.folder))?;
+        for (index, path) in documents.iter().enumerate() {
+            let display = path.to_string_lossy().into_owned();
+            match import_file(display.clone(), Some(batch_id), None).map(|outcome| {
+                outcome
+                    .split_once(':')
+                    .map(|(kind, id)| (kind.to_string(), id.to_string()))
+            }) {
+                Ok(Some((kind, id))) => {
+                    if kind == "DUPLICATE" {
+                        summary.duplicates += 1;
+                    } else {
+                        summary.imported += 1;
+                    }
+                    if !file_ids.contains(&id) {
+                        file_ids.push(id);
+                    }
+                }
+                Ok(None) => summary.failures.push(PipelineFailure {
+                    file_name: display,
+                    error: "Unexpected import result.".to_string(),
+                }),
+                Err(error) => summary.failures.push(PipelineFailure {
+                    file_name: display,
+                    error,
+                }),
+            }
+            progress(app, PipelineStage::Import, index + 1, documents.len(), path);
+        }
+        ImportBatches::finish(conn, batch_id, false)?;
+
+        let pending = unprocessed(conn, &file_ids)?;
+        for (index, (file_id, file_name, stored_path)) in pending.iter().enumerate() {
+            update_file_status(file_id.clone(), FileStatus::Processing)?;
+            let mut clock = StageClock::start();
+            let document = read_document(Path::new(stored_path));
+            let document_ms = clock.lap();
+            let response = document.and_then(|(mime_type, document)| {
+                Self::extract(&client, &prompts, options, temperature, mime_type, &document)
+            });
+            let model_ms = clock.lap();
+            let outcome = response.and_then(|response| {
+                summary.prompt_tokens += response.prompt_tokens;
+                summary.output_tokens += response.output_tokens;
+                summary.cost += ProcessingUsage::record(
+                    conn,
+                    &UsageRecord {
+                        file_id: Some(file_id.clone()),
+                        model: options.model.clone(),
+                        profile: profile.clone(),
+                        prompt_tokens: response.prompt_tokens,
+                        output_tokens: response.output_tokens,
+                    },
+                )?;
+                update_file_status(file_id.clone(), FileStatus::Processed)?;
+                save_extraction_result(file_id.clone(), response.output.to_string())
+            });
+            let save_ms = clock.lap();
+
+            let record_metric = |outcome: ParseOutcome, error: Option<&str>| {
+                ParseMetrics::record(
+                    conn,
+                    &ParseRun {
+                        file_id,
+                        provider: LlmProviderKind::Gemini.as_str(),
+                        model: Some(&options.model),
+                        attempts: 1,
+                        outcome,
+                        error,
+                        document_ms,
+                        model_ms,
+                        save_ms,
+                        total_ms: clock.total(),
+                    },
+                )
+            };
+            match outcome {
+                Ok(result) => {
+                    record_metric(ParseOutcome::from_saved(result.status), None)?;
+                    summary.processed += 1;
+                    if result.status == "conflict" {
+                        summary.conflicts += 1;
+                    }
+                }
+                // Same as the frontend: retried by the offline queue once the network is back.
+                Err(error) if !Connectivity::is_online() => {
+                    record_metric(ParseOutcome::WaitingForNetwork, Some(&error))?;
+                    update_file_status(file_id.clone(), FileStatus::WaitingForNetwork)?;
+                    summary.waiting_for_network += 1;
+                }
+                Err(error) => {
+                    record_metric(ParseOutcome::Failed, Some(&error))?;
+                    update_file_status(file_id.clone(), FileStatus::Failed)?;
+                    conn.execute(
+                        "UPDATE files SET parsed_details = ?1 WHERE id = ?2",
+                        params![serde_json::json!({ "error": error }).to_string(), file_id],
+                    )
+                    .map_err(|error| error.to_string())?;
+                    summary.failures.push(PipelineFailure {
+                        file_name: file_name.clone(),
+                        error,
+                    });
+                }
+            }
+            progress(
+                app,
+                PipelineStage::Extract,
+                index + 1,
+                pending.len(),
+                Path::new(file_name),
+            );
+        }
+
+        Self::export(app, conn, &file_ids, options, &mut summary)?;
+        summary.finished_at = Utc::now().to_rfc3339();
+        Ok(summary)
+    }
+
+    fn extract(
+        client: &GeminiClient,
+        prompts: &ActivePrompts,
+        options: &ProcessFolderOptions,
+        temperature: f64,
+        mime_type: &str,
+        document: &[u8],
+    ) -> Result<GeminiResponse, String> {
+        let response = client.generate(&GeminiRequest {
+            system_instruction: &prompts.system_instruction,
+            prompt: &prompts.user_prompt,
+            document,
+            mime_type,
+            response_schema: options.response_schema.as_ref(),
+            temperature,
+        })?;
+        if response.output.get("_raw").is_some() {
+            return Err("The model did not return valid JSON.".to_string());
+        }
+        Ok(response)
+    }
+
+    /// Adds the processed files to the sheet and queues the XML. Files that failed or wait
+    /// for the network are left out; they can be added once they are processed.
+    fn export<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        file_ids: &[String],
+        options: &ProcessFolderOptions,
+        summary: &mut FolderPipelineSummary,
+    ) -> Result<(), String> {
+        let processed = processed(conn, file_ids)?;
+        if processed.is_empty() {
+            return Ok(());
+        }
+        let wants_sheet =
+            options.sheet_id.is_some() || options.sheet_name.is_some() || options.xml_output_path.is_some();
+        if !wants_sheet {
+            return Ok(());
+        }
+
+        let sheet_id = match options.sheet_id {
+            Some(sheet_id) => {
+                append_xml_file(sheet_id, processed.clone())?;
+                sheet_id
+            }
+            None => {
+                let name = options
+                    .sheet_name
+                    .clone()
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| folder_name(Path::new(&summary.folder)));
+                create_xml_for_files(processed.clone(), name)?
+            }
+        };
+        summary.sheet_id = Some(sheet_id);
+        progress(app, PipelineStage::Export, 1, 1, Path::new(&summary.folder));
+
+        if let Some(path) = &options.xml_output_path {
+            app.emit(
+                SCHEDULED_XML_EVENT,
+                ScheduledXmlExport {
+                    xml_id: sheet_id,
+                    path: path.clone(),
+                },
+            )
+            .map_err(|error| error.to_string())?;
+            summary.xml_output_path = Some(path.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Supported documents in `folder`, sorted by path, and how many other files were skipped.
+pub(crate) fn collect_documents(folder: &Path, recursive: bool) -> Result<(Vec<PathBuf>, usize), String> {
+    let mut documents = Vec::new();
+    let mut unsupported = 0;
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).map_err(|error| error.to_string())? {
+            let path = entry.map_err(|error| error.to_string())?.path();
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if mime_for(&path).is_some() {
+                documents.push(path);
+            } else {
+                unsupported += 1;
+            }
+        }
+    }
+    documents.sort();
+    Ok((documents, unsupported))
+}
+
+/// Files among `file_ids` that still need extraction: (id, file name, stored path).
+fn read_document(path: &Path) -> Result<(&'static str, Vec<u8>), String> {
+    let mime_type =
+        mime_for(path).ok_or_else(|| "This file type cannot be sent for extraction.".to_string())?;
+    let document = fs::read(path).map_err(|error| error.to_string())?;
+    Ok((mime_type, document))
+}
+
+fn unprocessed(conn: &Connection, file_ids: &[String]) -> Result<Vec<(String, String, String)>, String> {
+    let mut files = Vec::new();
+    for file_id in file_ids {
+        let row: (String, String, String) = conn
+            .query_row(
+                "SELECT file_name, stored_path, status FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|error| error.to_string())?;
+        if row.2 != FileStatus::Processed.as_str() && row.2 != FileStatus::NeedsReview.as_str() {
+            files.push((file_id.clone(), row.0, row.1));
+        }
+    }
+    Ok(files)
+}
+
+fn processed(conn: &Connection, file_ids: &[String]) -> Result<Vec<String>, String> {
+    let mut ids = Vec::new();
+    for file_id in file_ids {
+        let status: String = conn
+            .query_row(
+                "SELECT status FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        if status == FileStatus::Processed.as_str() {
+            ids.push(file_id.clone());
+        }
+    }
+    Ok(ids)
+}
+
+fn folder_name(folder: &Path) -> String {
+    folder
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("Folder import")
+        .to_string()
+}
+
+fn progress<R: Runtime>(
+    app: &AppHandle<R>,
+    stage: PipelineStage,
+    completed: usize,
+    total: usize,
+    path: &Path,
+) {
+    let _ = app.emit(
+        FOLDER_PIPELINE_PROGRESS_EVENT,
+        FolderPipelineProgress {
+            stage,
+            completed,
+            total,
+            file_name: path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(str::to_string),
+        },
+    );
+}
@@ -0,0 +1,71 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// Marks around the matched terms in a snippet.
+const MATCH_START: &str = "<mark>";
+const MATCH_END: &str = "</mark>";
+/// Tokens of context a snippet shows around the match.
+const SNIPPET_TOKENS: i64 = 12;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchHit {
+    pub file_id: String,
+    pub file_name: String,
+    /// The best-matching stretch of the file name or parsed fields, with the matched terms
+    /// between `<mark>` and `</mark>`; everything else is text as stored.
+    pub snippet: String,
+    /// BM25 relevance; higher is better.
+    pub score: f64,
+}
+
+/// Ranked search over `files_fts`, which the database keeps in step with each file's name
+/// and parsed fields. Every word of the query must appear, as a whole word or the start of
+/// one, so "acme inv" finds "ACME Industries Invoice 42".
+pub struct FullTextSearch;
+
+impl FullTextSearch {
+    pub fn search(conn: &Connection, query: &str, limit: i64) -> Result<Vec<FileSearchHit>, String> {
+        let Some(expression) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT file_id, file_name, snippet(files_fts, -1, ?2, ?3, '…', ?4), bm25(files_fts)
+                 FROM files_fts
+                 WHERE files_fts MATCH ?1
+                 ORDER BY rank
+                 LIMIT ?5",
+            )
+            .map_err(|error| error.to_string())?;
+        let hits = stmt
+            .query_map(
+                params![expression, MATCH_START, MATCH_END, SNIPPET_TOKENS, limit.max(1)],
+                |row| {
+                    Ok(FileSearchHit {
+                        file_id: row.get(0)?,
+                        file_name: row.get(1)?,
+                        snippet: row.get(2)?,
+                        // bm25() is lower for better matches.
+                        score: -row.get::<_, f64>(3)?,
+                    })
+                },
+            )
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(hits)
+    }
+}
+
+/// Quotes each word of `query` as a prefix term, so punctuation and FTS5 operators typed by
+/// the user are searched for rather than parsed. `None` when the query has no words.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
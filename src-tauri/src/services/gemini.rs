@@ -0,0 +1,214 @@
+use crate::services::llm_provider::{
+    agent, parse_output, post_json, ExtractionRequest, LlmError, LlmProvider, LlmProviderKind, LlmResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+use std::path::Path;
+
+const API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+
+/// Document types Gemini reads inline.
+const SUPPORTED_MIME_TYPES: &[&str] = &[
+    "application/pdf",
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    "image/heic",
+];
+
+const MIME_BY_EXTENSION: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("webp", "image/webp"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("bmp", "image/bmp"),
+    ("heic", "image/heic"),
+];
+
+/// Same extension table the frontend uses when it uploads documents inline.
+pub fn mime_for(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    MIME_BY_EXTENSION
+        .iter()
+        .find(|(candidate, _)| *candidate == extension)
+        .map(|(_, mime)| *mime)
+}
+
+pub struct GeminiRequest<'a> {
+    pub system_instruction: &'a str,
+    pub prompt: &'a str,
+    pub document: &'a [u8],
+    pub mime_type: &'a str,
+    pub response_schema: Option<&'a Value>,
+    pub temperature: f64,
+}
+
+#[derive(Debug)]
+pub struct GeminiResponse {
+    pub output: Value,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Minimal REST client for `generateContent`, mirroring the request the frontend sends
+/// through `@google/genai` so backend runs see the same prompt and config.
+pub struct GeminiClient {
+    api_key: String,
+    model: String,
+}
+
+impl GeminiClient {
+    pub fn new(api_key: &str, model: &str) -> Result<Self, String> {
+        if api_key.trim().is_empty() {
+            return Err("A Gemini API key is required.".to_string());
+        }
+        if model.trim().is_empty() {
+            return Err("A Gemini model is required.".to_string());
+        }
+        Ok(GeminiClient {
+            api_key: api_key.trim().to_string(),
+            model: model.trim().to_string(),
+        })
+    }
+
+    pub fn generate(&self, request: &GeminiRequest) -> Result<GeminiResponse, String> {
+        let response = self.send(request)?;
+        Ok(GeminiResponse {
+            output: response.output,
+            prompt_tokens: response.prompt_tokens,
+            output_tokens: response.output_tokens,
+        })
+    }
+
+    fn send(&self, request: &GeminiRequest) -> Result<LlmResponse, LlmError> {
+        let mut generation_config = json!({
+            "responseMimeType": "application/json",
+            "temperature": request.temperature,
+        });
+        if let Some(schema) = request.response_schema {
+            generation_config["responseJsonSchema"] = schema.clone();
+        }
+        let body = json!({
+            "systemInstruction": { "parts": [{ "text": request.system_instruction }] },
+            "contents": [{
+                "role": "user",
+                "parts": [
+                    { "text": request.prompt },
+                    { "inlineData": { "mimeType": request.mime_type, "data": STANDARD.encode(request.document) } },
+                ],
+            }],
+            "generationConfig": generation_config,
+        });
+
+        let (response, raw) = post_json(
+            agent()
+                .post(&format!("{}/{}:generateContent", API_BASE, self.model))
+                .set("x-goog-api-key", &self.api_key),
+            &body,
+        )
+        .map_err(|error| LlmError {
+            message: format!("Gemini request failed: {}", error.message),
+            ..error
+        })?;
+
+        let text: String = response
+            .pointer("/candidates/0/content/parts")
+            .and_then(Value::as_array)
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| part.get("text")?.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let usage = |key: &str| {
+            response
+                .pointer(&format!("/usageMetadata/{}", key))
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+        };
+
+        Ok(LlmResponse {
+            output: parse_output(&text),
+            prompt_tokens: usage("promptTokenCount"),
+            output_tokens: usage("candidatesTokenCount"),
+            raw,
+        })
+    }
+}
+
+impl LlmProvider for GeminiClient {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::Gemini
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn supports(&self, mime_type: &str) -> bool {
+        SUPPORTED_MIME_TYPES.contains(&mime_type)
+    }
+
+    fn extract(&self, request: &ExtractionRequest) -> Result<LlmResponse, LlmError> {
+        self.send(&GeminiRequest {
+            system_instruction: request.system_instruction,
+            prompt: request.prompt,
+            document: request.document,
+            mime_type: request.mime_type,
+            response_schema: request.response_schema,
+            temperature: request.temperature,
+        })
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let model = LlmProviderKind::Gemini.embedding_model().unwrap_or_default();
+        let requests: Vec<Value> = texts
+            .iter()
+            .map(|text| {
+                json!({
+                    "model": format!("models/{}", model),
+                    "content": { "parts": [{ "text": text }] },
+                })
+            })
+            .collect();
+        let (response, _) = post_json(
+            agent()
+                .post(&format!("{}/{}:batchEmbedContents", API_BASE, model))
+                .set("x-goog-api-key", &self.api_key),
+            &json!({ "requests": requests }),
+        )
+        .map_err(|error| LlmError {
+            message: format!("Gemini embeddings request failed: {}", error.message),
+            ..error
+        })?;
+        let vectors: Vec<Vec<f32>> = response
+            .get("embeddings")
+            .and_then(Value::as_array)
+            .map(|embeddings| {
+                embeddings
+                    .iter()
+                    .filter_map(|embedding| embedding.get("values")?.as_array())
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(Value::as_f64)
+                            .map(|value| value as f32)
+                            .collect()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if vectors.len() != texts.len() {
+            return Err(LlmError::fatal(format!(
+                "Expected {} embeddings but received {}.",
+                texts.len(),
+                vectors.len()
+            )));
+        }
+        Ok(vectors)
+    }
+}
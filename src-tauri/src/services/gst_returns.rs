@@ -0,0 +1,522 @@
+use crate::services::{
+    parsed_invoice::{
+        ParsedInvoice, BUYER_GSTIN, BUYER_NAME, GRAND_TOTAL, INVOICE_NUMBER, SELLER_GSTIN, SELLER_NAME,
+    },
+    report_period::ReportPeriod,
+    spreadsheet::{Cell, Sheet},
+    tax_id_validator::{TaxIdKind, TaxIdValidator},
+    tax_lines::{self, TaxLineStore},
+};
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GstReturnKind {
+    /// Outward supplies: invoices we issued, grouped by recipient.
+    Gstr1,
+    /// Inward supplies: invoices we received, grouped by supplier.
+    Gstr2b,
+}
+
+impl GstReturnKind {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().replace('-', "").as_str() {
+            "gstr1" => Ok(Self::Gstr1),
+            "gstr2b" => Ok(Self::Gstr2b),
+            other => Err(format!("Unsupported GST return \"{}\". Use gstr1 or gstr2b.", other)),
+        }
+    }
+}
+
+/// Taxable value and tax of one invoice at a single GST rate.
+#[derive(Debug, Clone, Default)]
+pub struct RateLine {
+    pub rate: f64,
+    pub taxable_value: f64,
+    pub igst: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub cess: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnInvoice {
+    pub counterparty_gstin: Option<String>,
+    pub counterparty_name: Option<String>,
+    pub invoice_number: Option<String>,
+    pub invoice_date: String,
+    pub invoice_value: f64,
+    pub place_of_supply: Option<String>,
+    pub lines: Vec<RateLine>,
+}
+
+#[derive(Debug, Clone)]
+pub struct B2csRow {
+    pub place_of_supply: Option<String>,
+    pub inter_state: bool,
+    pub totals: RateLine,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HsnRow {
+    pub hsn_sac: String,
+    pub description: Option<String>,
+    pub unit: Option<String>,
+    pub quantity: f64,
+    pub total_value: f64,
+    pub totals: RateLine,
+}
+
+/// Processed invoices of a period aggregated into the sections of a GST return.
+pub struct GstReturn {
+    pub kind: GstReturnKind,
+    pub gstin: Option<String>,
+    pub period: ReportPeriod,
+    pub b2b: Vec<ReturnInvoice>,
+    pub b2cs: Vec<B2csRow>,
+    pub hsn: Vec<HsnRow>,
+    pub skipped_files: usize,
+}
+
+struct StoredLine {
+    hsn_sac: Option<String>,
+    description: Option<String>,
+    unit: Option<String>,
+    quantity: Option<f64>,
+    amount: Option<f64>,
+    tax: Option<f64>,
+    rate: Option<f64>,
+}
+
+impl GstReturn {
+    /// Builds the return from files whose normalized invoice date falls inside `period`.
+    /// When `gstin` is given only invoices filed under that registration are included.
+    pub fn build(
+        conn: &Connection,
+        kind: GstReturnKind,
+        period: ReportPeriod,
+        gstin: Option<&str>,
+    ) -> Result<Self, String> {
+        let gstin = gstin.map(TaxIdValidator::normalize).filter(|value| !value.is_empty());
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, parsed_details, invoice_date FROM files
+                 WHERE parsed_details IS NOT NULL
+                 AND invoice_date BETWEEN ?1 AND ?2
+                 ORDER BY invoice_date ASC, created_at ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let files = stmt
+            .query_map(params![period.start_iso(), period.end_iso()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut result = Self {
+            kind,
+            gstin: gstin.clone(),
+            period,
+            b2b: Vec::new(),
+            b2cs: Vec::new(),
+            hsn: Vec::new(),
+            skipped_files: 0,
+        };
+        let mut b2cs: BTreeMap<(Option<String>, bool, i64), B2csRow> = BTreeMap::new();
+        let mut hsn: BTreeMap<(String, i64), HsnRow> = BTreeMap::new();
+
+        for (file_id, parsed_details, invoice_date) in files {
+            let Some(invoice) = ParsedInvoice::from_json(&parsed_details) else {
+                result.skipped_files += 1;
+                continue;
+            };
+
+            let (own_field, counterparty_field, counterparty_name_field) = match kind {
+                GstReturnKind::Gstr1 => (SELLER_GSTIN, BUYER_GSTIN, BUYER_NAME),
+                GstReturnKind::Gstr2b => (BUYER_GSTIN, SELLER_GSTIN, SELLER_NAME),
+            };
+            let own_gstin = invoice.text(own_field).map(|value| TaxIdValidator::normalize(&value));
+            if let Some(filter) = &gstin {
+                if own_gstin.as_deref() != Some(filter.as_str()) {
+                    continue;
+                }
+            }
+
+            let counterparty_gstin = invoice
+                .text(counterparty_field)
+                .map(|value| TaxIdValidator::normalize(&value))
+                .filter(|value| TaxIdValidator::detect_kind(value) == TaxIdKind::Gstin);
+
+            // Inward supplies from unregistered suppliers do not appear in GSTR-2B.
+            if kind == GstReturnKind::Gstr2b && counterparty_gstin.is_none() {
+                result.skipped_files += 1;
+                continue;
+            }
+
+            let place_of_supply = tax_lines::place_of_supply(&invoice);
+            let inter_state = tax_lines::is_inter_state(&invoice, place_of_supply.as_deref());
+
+            let stored = load_lines(conn, &file_id)?;
+            let lines = rate_lines(conn, &file_id, &invoice)?;
+            let taxable: f64 = lines.iter().map(|line| line.taxable_value).sum();
+            let tax: f64 = lines
+                .iter()
+                .map(|line| line.igst + line.cgst + line.sgst + line.cess)
+                .sum();
+            let invoice_value = invoice.number(GRAND_TOTAL).unwrap_or(taxable + tax);
+
+            if kind == GstReturnKind::Gstr1 {
+                for line in &stored {
+                    let Some(hsn_sac) = line.hsn_sac.clone() else {
+                        continue;
+                    };
+                    let rate = line.rate.unwrap_or(0.0);
+                    let row = hsn.entry((hsn_sac.clone(), rate_key(rate))).or_insert_with(|| HsnRow {
+                        hsn_sac,
+                        description: line.description.clone(),
+                        unit: line.unit.clone(),
+                        totals: RateLine { rate, ..RateLine::default() },
+                        ..HsnRow::default()
+                    });
+                    let amount = line.amount.unwrap_or(0.0);
+                    let line_tax = line.tax.unwrap_or(0.0);
+                    row.quantity += line.quantity.unwrap_or(0.0);
+                    row.total_value += amount + line_tax;
+                    add_tax(&mut row.totals, amount, line_tax, inter_state);
+                }
+
+                if counterparty_gstin.is_none() {
+                    for line in &lines {
+                        let row = b2cs
+                            .entry((place_of_supply.clone(), inter_state, rate_key(line.rate)))
+                            .or_insert_with(|| B2csRow {
+                                place_of_supply: place_of_supply.clone(),
+                                inter_state,
+                                totals: RateLine { rate: line.rate, ..RateLine::default() },
+                            });
+                        row.totals.taxable_value += line.taxable_value;
+                        row.totals.igst += line.igst;
+                        row.totals.cgst += line.cgst;
+                        row.totals.sgst += line.sgst;
+                        row.totals.cess += line.cess;
+                    }
+                    continue;
+                }
+            }
+
+            result.b2b.push(ReturnInvoice {
+                counterparty_gstin,
+                counterparty_name: invoice.text(counterparty_name_field),
+                invoice_number: invoice.text(INVOICE_NUMBER),
+                invoice_date,
+                invoice_value,
+                place_of_supply,
+                lines,
+            });
+        }
+
+        result.b2cs = b2cs.into_values().collect();
+        result.hsn = hsn.into_values().collect();
+        Ok(result)
+    }
+
+    /// Serializes the return using the section and field names of the GSTN offline JSON.
+    pub fn to_json(&self) -> Value {
+        let mut by_counterparty: BTreeMap<String, (Option<String>, Vec<Value>)> = BTreeMap::new();
+        for invoice in &self.b2b {
+            let entry = by_counterparty
+                .entry(invoice.counterparty_gstin.clone().unwrap_or_default())
+                .or_insert_with(|| (invoice.counterparty_name.clone(), Vec::new()));
+            let items: Vec<Value> = invoice
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(index, line)| {
+                    json!({
+                        "num": index + 1,
+                        "itm_det": rate_line_json(line),
+                    })
+                })
+                .collect();
+            entry.1.push(json!({
+                "inum": invoice.invoice_number,
+                "idt": gstn_date(&invoice.invoice_date),
+                "val": round2(invoice.invoice_value),
+                "pos": invoice.place_of_supply,
+                "rchrg": "N",
+                "inv_typ": "R",
+                "itms": items,
+            }));
+        }
+
+        let b2b: Vec<Value> = by_counterparty
+            .into_iter()
+            .map(|(ctin, (name, invoices))| match self.kind {
+                GstReturnKind::Gstr1 => json!({ "ctin": ctin, "inv": invoices }),
+                GstReturnKind::Gstr2b => json!({ "ctin": ctin, "trdnm": name, "inv": invoices }),
+            })
+            .collect();
+
+        match self.kind {
+            GstReturnKind::Gstr1 => {
+                let b2cs: Vec<Value> = self
+                    .b2cs
+                    .iter()
+                    .map(|row| {
+                        let mut value = rate_line_json(&row.totals);
+                        value["sply_ty"] = json!(if row.inter_state { "INTER" } else { "INTRA" });
+                        value["pos"] = json!(row.place_of_supply);
+                        value["typ"] = json!("OE");
+                        value
+                    })
+                    .collect();
+                let hsn: Vec<Value> = self
+                    .hsn
+                    .iter()
+                    .enumerate()
+                    .map(|(index, row)| {
+                        let mut value = rate_line_json(&row.totals);
+                        value["num"] = json!(index + 1);
+                        value["hsn_sc"] = json!(row.hsn_sac);
+                        value["desc"] = json!(row.description);
+                        value["uqc"] = json!(row.unit);
+                        value["qty"] = json!(round2(row.quantity));
+                        value["val"] = json!(round2(row.total_value));
+                        value
+                    })
+                    .collect();
+
+                json!({
+                    "gstin": self.gstin,
+                    "fp": self.period.gst_return_period(),
+                    "b2b": b2b,
+                    "b2cs": b2cs,
+                    "hsn": { "data": hsn },
+                })
+            }
+            GstReturnKind::Gstr2b => json!({
+                "gstin": self.gstin,
+                "rtnprd": self.period.gst_return_period(),
+                "docdata": { "b2b": b2b },
+            }),
+        }
+    }
+
+    /// One worksheet per section, one row per invoice rate (B2B) or aggregate (B2CS, HSN).
+    pub fn to_sheets(&self) -> Vec<Sheet> {
+        let counterparty_header = match self.kind {
+            GstReturnKind::Gstr1 => "GSTIN of Recipient",
+            GstReturnKind::Gstr2b => "GSTIN of Supplier",
+        };
+        let mut b2b_rows = Vec::new();
+        for invoice in &self.b2b {
+            for line in &invoice.lines {
+                b2b_rows.push(vec![
+                    Cell::from(invoice.counterparty_gstin.clone()),
+                    Cell::from(invoice.counterparty_name.clone()),
+                    Cell::from(invoice.invoice_number.clone()),
+                    Cell::Text(gstn_date(&invoice.invoice_date)),
+                    Cell::Number(round2(invoice.invoice_value)),
+                    Cell::from(place_of_supply_label(invoice.place_of_supply.as_deref())),
+                    Cell::Number(line.rate),
+                    Cell::Number(round2(line.taxable_value)),
+                    Cell::Number(round2(line.igst)),
+                    Cell::Number(round2(line.cgst)),
+                    Cell::Number(round2(line.sgst)),
+                ]);
+            }
+        }
+
+        let mut sheets = vec![Sheet {
+            name: "b2b".to_string(),
+            headers: [
+                counterparty_header,
+                "Name",
+                "Invoice Number",
+                "Invoice Date",
+                "Invoice Value",
+                "Place Of Supply",
+                "Rate",
+                "Taxable Value",
+                "Integrated Tax",
+                "Central Tax",
+                "State/UT Tax",
+            ].map(String::from).to_vec(),
+            rows: b2b_rows,
+        }];
+
+        if self.kind == GstReturnKind::Gstr1 {
+            sheets.push(Sheet {
+                name: "b2cs".to_string(),
+                headers: [
+                    "Type",
+                    "Place Of Supply",
+                    "Rate",
+                    "Taxable Value",
+                    "Integrated Tax",
+                    "Central Tax",
+                    "State/UT Tax",
+                ].map(String::from).to_vec(),
+                rows: self
+                    .b2cs
+                    .iter()
+                    .map(|row| {
+                        vec![
+                            Cell::Text(if row.inter_state { "INTER" } else { "INTRA" }.to_string()),
+                            Cell::from(place_of_supply_label(row.place_of_supply.as_deref())),
+                            Cell::Number(row.totals.rate),
+                            Cell::Number(round2(row.totals.taxable_value)),
+                            Cell::Number(round2(row.totals.igst)),
+                            Cell::Number(round2(row.totals.cgst)),
+                            Cell::Number(round2(row.totals.sgst)),
+                        ]
+                    })
+                    .collect(),
+            });
+            sheets.push(Sheet {
+                name: "hsn".to_string(),
+                headers: [
+                    "HSN",
+                    "Description",
+                    "UQC",
+                    "Total Quantity",
+                    "Total Value",
+                    "Rate",
+                    "Taxable Value",
+                    "Integrated Tax",
+                    "Central Tax",
+                    "State/UT Tax",
+                ].map(String::from).to_vec(),
+                rows: self
+                    .hsn
+                    .iter()
+                    .map(|row| {
+                        vec![
+                            Cell::Text(row.hsn_sac.clone()),
+                            Cell::from(row.description.clone()),
+                            Cell::from(row.unit.clone()),
+                            Cell::Number(round2(row.quantity)),
+                            Cell::Number(round2(row.total_value)),
+                            Cell::Number(row.totals.rate),
+                            Cell::Number(round2(row.totals.taxable_value)),
+                            Cell::Number(round2(row.totals.igst)),
+                            Cell::Number(round2(row.totals.cgst)),
+                            Cell::Number(round2(row.totals.sgst)),
+                        ]
+                    })
+                    .collect(),
+            });
+        }
+
+        sheets
+    }
+}
+
+fn load_lines(conn: &Connection, file_id: &str) -> Result<Vec<StoredLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT hsn_sac, description, name, unit, quantity, amount, cgst, sgst, cgst_rate, sgst_rate,
+                    igst, igst_rate
+             FROM invoice_line_items WHERE file_id = ?1 ORDER BY position ASC",
+        )
+        .map_err(|error| error.to_string())?;
+    let lines = stmt
+        .query_map(params![file_id], |row| {
+            let cgst: Option<f64> = row.get(6)?;
+            let sgst: Option<f64> = row.get(7)?;
+            let cgst_rate: Option<f64> = row.get(8)?;
+            let sgst_rate: Option<f64> = row.get(9)?;
+            let igst: Option<f64> = row.get(10)?;
+            let igst_rate: Option<f64> = row.get(11)?;
+            Ok(StoredLine {
+                hsn_sac: row.get(0)?,
+                description: row.get::<_, Option<String>>(1)?.or(row.get(2)?),
+                unit: row.get(3)?,
+                quantity: row.get(4)?,
+                amount: row.get(5)?,
+                tax: sum_present(sum_present(cgst, sgst), igst),
+                rate: igst_rate.or(sum_present(cgst_rate, sgst_rate)),
+            })
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(lines)
+}
+
+/// The file's stored tax lines by GST rate, worked out from the invoice for files whose
+/// details were saved before tax lines were kept.
+fn rate_lines(conn: &Connection, file_id: &str, invoice: &ParsedInvoice) -> Result<Vec<RateLine>, String> {
+    let mut lines = TaxLineStore::for_file(conn, file_id)?;
+    if lines.is_empty() {
+        lines = TaxLineStore::compute(invoice);
+    }
+    Ok(lines
+        .into_iter()
+        .map(|line| RateLine {
+            rate: line.rate,
+            taxable_value: line.taxable_value,
+            igst: line.igst,
+            cgst: line.cgst,
+            sgst: line.sgst,
+            cess: line.cess,
+        })
+        .collect())
+}
+
+/// Inter-state supplies carry IGST; intra-state tax is split evenly into CGST and SGST.
+fn add_tax(line: &mut RateLine, taxable: f64, tax: f64, inter_state: bool) {
+    line.taxable_value += taxable;
+    if inter_state {
+        line.igst += tax;
+    } else {
+        line.cgst += tax / 2.0;
+        line.sgst += tax / 2.0;
+    }
+}
+
+fn rate_line_json(line: &RateLine) -> Value {
+    json!({
+        "rt": line.rate,
+        "txval": round2(line.taxable_value),
+        "iamt": round2(line.igst),
+        "camt": round2(line.cgst),
+        "samt": round2(line.sgst),
+        "csamt": round2(line.cess),
+    })
+}
+
+fn place_of_supply_label(code: Option<&str>) -> Option<String> {
+    let code = code?;
+    Some(match TaxIdValidator::gst_state_name(code) {
+        Some(name) => format!("{}-{}", code, name),
+        None => code.to_string(),
+    })
+}
+
+/// Converts an ISO date to the DD-MM-YYYY form used by the GST portal.
+fn gstn_date(iso: &str) -> String {
+    let parts: Vec<&str> = iso.split('-').collect();
+    match parts.as_slice() {
+        [year, month, day] => format!("{}-{}-{}", day, month, year),
+        _ => iso.to_string(),
+    }
+}
+
+fn sum_present(left: Option<f64>, right: Option<f64>) -> Option<f64> {
+    match (left, right) {
+        (None, None) => None,
+        (left, right) => Some(left.unwrap_or(0.0) + right.unwrap_or(0.0)),
+    }
+}
+
+fn rate_key(rate: f64) -> i64 {
+    (rate * 100.0).round() as i64
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
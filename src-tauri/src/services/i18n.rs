@@ -0,0 +1,660 @@
+use crate::db::get_connection;
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
+
+const LOCALE_KEY: &str = "ui.locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Hi,
+    De,
+    Fr,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::Hi, Locale::De, Locale::Fr];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Hi => "hi",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+        }
+    }
+
+    /// Accepts plain language codes as well as tags like `de-DE` or `fr_CA`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let language = value
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.as_str() == language)
+            .ok_or_else(|| format!("Unsupported locale: {}", value))
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Hi => HI,
+            Locale::De => DE,
+            Locale::Fr => FR,
+        }
+    }
+}
+
+static CURRENT_LOCALE: OnceLock<RwLock<Locale>> = OnceLock::new();
+
+/// Translates user-facing backend strings (errors, validation messages, report labels)
+/// into the locale stored in settings. Missing translations fall back to English.
+pub struct I18n;
+
+impl I18n {
+    /// The active locale, read from settings once and cached for the process.
+    pub fn locale() -> Locale {
+        let lock = CURRENT_LOCALE.get_or_init(|| {
+            let stored = get_connection()
+                .ok()
+                .and_then(|conn| Settings::get(&conn, LOCALE_KEY).ok().flatten())
+                .and_then(|value| Locale::parse(&value).ok());
+            RwLock::new(stored.unwrap_or(Locale::En))
+        });
+        lock.read().map(|locale| *locale).unwrap_or(Locale::En)
+    }
+
+    pub fn set_locale(conn: &Connection, locale: Locale) -> Result<(), String> {
+        Settings::set(conn, LOCALE_KEY, locale.as_str())?;
+        let lock = CURRENT_LOCALE.get_or_init(|| RwLock::new(locale));
+        if let Ok(mut current) = lock.write() {
+            *current = locale;
+        }
+        Ok(())
+    }
+
+    pub fn text(key: &'static str) -> &'static str {
+        Self::lookup(Self::locale(), key)
+            .or_else(|| Self::lookup(Locale::En, key))
+            .unwrap_or(key)
+    }
+
+    /// Translates `key` and fills `{name}` placeholders from `args`.
+    pub fn format(key: &'static str, args: &[(&str, String)]) -> String {
+        args.iter()
+            .fold(Self::text(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+        locale
+            .catalog()
+            .iter()
+            .find(|(entry, _)| *entry == key)
+            .map(|(_, text)| *text)
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    (
+        "app_lock.pin_too_short",
+        "The PIN must have at least {min} characters.",
+    ),
+    (
+        "app_lock.locked_out",
+        "Too many incorrect PIN attempts. Try again in {minutes} minute(s).",
+    ),
+    ("app_lock.pin_required", "This action requires the app PIN."),
+    (
+        "app_lock.locked_now",
+        "Incorrect PIN. The app is locked for {minutes} minutes.",
+    ),
+    (
+        "app_lock.incorrect_pin",
+        "Incorrect PIN. {attempts} attempt(s) left before lockout.",
+    ),
+    (
+        "roles.admin_required",
+        "Operators are not allowed to {action}. Switch to the admin role first.",
+    ),
+    ("action.delete_files", "delete files"),
+    ("action.clear_stored_files", "clear stored files"),
+    ("action.change_settings", "change settings"),
+    ("action.change_pin", "change the app PIN"),
+    ("action.remove_pin", "remove the app PIN"),
+    ("action.change_schedules", "change schedules"),
+    ("action.change_prompts", "change extraction prompts"),
+    ("action.purge_files", "purge deleted files"),
+    ("action.restore_files", "restore deleted files"),
+    ("action.lock_workspace", "lock or unlock the workspace"),
+    ("action.restore_database", "restore the database from a backup"),
+    ("action.delete_sheets", "delete sheets"),
+    ("action.delete_csv_profiles", "delete CSV profiles"),
+    ("action.delete_export_profiles", "delete export profiles"),
+    ("action.delete_templates", "delete extraction templates"),
+    ("action.merge_parties", "merge sellers or buyers"),
+    ("action.remove_aliases", "remove seller or buyer aliases"),
+    (
+        "workspace.locked",
+        "The workspace is locked and read-only. An admin must unlock it before changes can be made.",
+    ),
+    (
+        "workspace.locked_with_reason",
+        "The workspace is locked and read-only ({reason}). An admin must unlock it before changes can be made.",
+    ),
+    ("tax_id.unrecognized", "Unrecognized tax identifier format."),
+    (
+        "tax_id.same_as_seller",
+        "Buyer tax ID is identical to the seller tax ID.",
+    ),
+    ("tax_id.gstin_length", "GSTIN must be 15 characters long."),
+    ("tax_id.unknown_state", "Unknown GST state code {code}."),
+    ("tax_id.invalid_pan", "GSTIN does not contain a valid PAN."),
+    ("tax_id.invalid_entity_code", "GSTIN entity code is invalid."),
+    ("tax_id.missing_z", "GSTIN must have 'Z' as its 14th character."),
+    (
+        "tax_id.checksum_mismatch",
+        "GSTIN checksum mismatch (expected {expected}, found {found}).",
+    ),
+    (
+        "tax_id.unsupported_characters",
+        "GSTIN contains unsupported characters.",
+    ),
+    (
+        "tax_id.unsupported_vat_country",
+        "Unsupported VAT country prefix {country}.",
+    ),
+    (
+        "tax_id.vat_format",
+        "VAT number does not match the {country} format.",
+    ),
+    (
+        "tax_id.vat_check_digit",
+        "VAT number check digit does not match for {country}.",
+    ),
+    (
+        "tax_id.place_of_supply_mismatch",
+        "Buyer GSTIN is registered in {registered} but the place of supply is {place}.",
+    ),
+    (
+        "tax_id.ein_prefix",
+        "EIN prefix {prefix} has not been assigned by the IRS.",
+    ),
+    (
+        "qr.mismatch",
+        "The QR code gives {field} as {qr}, but {extracted} was extracted.",
+    ),
+    (
+        "arithmetic.mismatch",
+        "{label} is {stated} but the computed value is {computed} (difference {difference}).",
+    ),
+    ("arithmetic.line_amount", "Line {line} amount"),
+    ("arithmetic.line_cgst", "Line {line} CGST"),
+    ("arithmetic.line_sgst", "Line {line} SGST"),
+    ("arithmetic.line_igst", "Line {line} IGST"),
+    ("arithmetic.subtotal", "Subtotal"),
+    ("arithmetic.tax_total", "Tax total"),
+    ("arithmetic.grand_total", "Grand total"),
+    ("report.spend_summary", "Spend summary"),
+    ("report.tax_summary", "Tax summary"),
+    ("report.period", "Period {start} to {end} - generated {generated}"),
+    ("report.overview", "Overview"),
+    ("report.spend_by_month", "Spend by month"),
+    ("report.spend_by_seller", "Spend by seller"),
+    ("report.tax_by_rate", "Tax by GST rate"),
+    ("report.all_invoices", "All invoices"),
+    ("report.unknown_seller", "Unknown seller"),
+    ("report.total", "Total"),
+    ("report.month", "Month"),
+    ("report.seller", "Seller"),
+    ("report.rate", "Rate"),
+    ("report.invoices", "Invoices"),
+    ("report.taxable_value", "Taxable value"),
+    ("report.tax", "Tax"),
+    ("report.cgst", "CGST"),
+    ("report.sgst", "SGST"),
+    ("report.total_tax", "Total tax"),
+    ("notify.batch_finished", "Processing finished"),
+    (
+        "notify.batch_succeeded",
+        "{processed} file(s) processed successfully.",
+    ),
+    (
+        "notify.batch_finished_with_errors",
+        "Processing finished with errors",
+    ),
+    (
+        "notify.batch_failed",
+        "{processed} file(s) processed, {failed} failed.",
+    ),
+    ("notify.export_ready", "{label} ready"),
+    ("notify.saved_to", "Saved to {path}"),
+    ("tray.open", "Open Invox AI"),
+    ("tray.import_files", "Import files…"),
+    ("tray.pause_processing", "Pause processing"),
+    ("tray.resume_processing", "Resume processing"),
+    ("tray.quit", "Quit"),
+    ("notify.scheduled_job_failed", "Scheduled job \"{name}\" failed"),
+    ("notify.import_failed", "Could not import {name}"),
+];
+
+// Report labels are left out on purpose: the built-in PDF fonts only cover Latin-1,
+// so Devanagari headings would come out garbled and English is used instead.
+const HI: &[(&str, &str)] = &[
+    ("app_lock.pin_too_short", "पिन में कम से कम {min} अक्षर होने चाहिए।"),
+    (
+        "app_lock.locked_out",
+        "बहुत अधिक गलत पिन प्रयास। {minutes} मिनट बाद फिर से प्रयास करें।",
+    ),
+    ("app_lock.pin_required", "इस कार्य के लिए ऐप पिन आवश्यक है।"),
+    (
+        "app_lock.locked_now",
+        "गलत पिन। ऐप {minutes} मिनट के लिए लॉक कर दिया गया है।",
+    ),
+    (
+        "app_lock.incorrect_pin",
+        "गलत पिन। लॉक होने से पहले {attempts} प्रयास शेष हैं।",
+    ),
+    (
+        "roles.admin_required",
+        "ऑपरेटर को {action} की अनुमति नहीं है। पहले एडमिन भूमिका पर स्विच करें।",
+    ),
+    ("action.delete_files", "फ़ाइलें हटाने"),
+    ("action.clear_stored_files", "संग्रहीत फ़ाइलें साफ़ करने"),
+    ("action.change_settings", "सेटिंग्स बदलने"),
+    ("action.change_pin", "ऐप पिन बदलने"),
+    ("action.remove_pin", "ऐप पिन हटाने"),
+    ("action.change_schedules", "शेड्यूल बदलने"),
+    ("action.change_prompts", "एक्सट्रैक्शन प्रॉम्प्ट बदलने"),
+    ("action.purge_files", "हटाई गई फ़ाइलें स्थायी रूप से मिटाने"),
+    ("action.restore_files", "हटाई गई फ़ाइलें वापस लाने"),
+    ("action.lock_workspace", "वर्कस्पेस लॉक या अनलॉक करने"),
+    ("action.restore_database", "बैकअप से डेटाबेस पुनर्स्थापित करने"),
+    ("action.delete_sheets", "शीट हटाने"),
+    ("action.delete_csv_profiles", "CSV प्रोफ़ाइल हटाने"),
+    ("action.delete_export_profiles", "एक्सपोर्ट प्रोफ़ाइल हटाने"),
+    ("action.delete_templates", "एक्सट्रैक्शन टेम्पलेट हटाने"),
+    ("action.merge_parties", "विक्रेता या खरीदार मर्ज करने"),
+    ("action.remove_aliases", "विक्रेता या खरीदार के उपनाम हटाने"),
+    (
+        "workspace.locked",
+        "वर्कस्पेस लॉक है और केवल पढ़ने के लिए है। बदलाव करने से पहले किसी एडमिन को इसे अनलॉक करना होगा।",
+    ),
+    (
+        "workspace.locked_with_reason",
+        "वर्कस्पेस लॉक है और केवल पढ़ने के लिए है ({reason})। बदलाव करने से पहले किसी एडमिन को इसे अनलॉक करना होगा।",
+    ),
+    ("tax_id.unrecognized", "कर पहचान संख्या का प्रारूप पहचाना नहीं गया।"),
+    (
+        "tax_id.same_as_seller",
+        "खरीदार की कर पहचान संख्या विक्रेता के समान है।",
+    ),
+    ("tax_id.gstin_length", "GSTIN 15 अक्षरों का होना चाहिए।"),
+    ("tax_id.unknown_state", "अज्ञात GST राज्य कोड {code}।"),
+    ("tax_id.invalid_pan", "GSTIN में मान्य PAN नहीं है।"),
+    ("tax_id.invalid_entity_code", "GSTIN का इकाई कोड अमान्य है।"),
+    ("tax_id.missing_z", "GSTIN का 14वाँ अक्षर 'Z' होना चाहिए।"),
+    (
+        "tax_id.checksum_mismatch",
+        "GSTIN चेकसम मेल नहीं खाता (अपेक्षित {expected}, मिला {found})।",
+    ),
+    ("tax_id.unsupported_characters", "GSTIN में असमर्थित अक्षर हैं।"),
+    (
+        "tax_id.unsupported_vat_country",
+        "असमर्थित VAT देश उपसर्ग {country}।",
+    ),
+    ("tax_id.vat_format", "VAT संख्या {country} प्रारूप से मेल नहीं खाती।"),
+    ("tax_id.vat_check_digit", "VAT संख्या का चेक अंक {country} के लिए मेल नहीं खाता।"),
+    (
+        "tax_id.place_of_supply_mismatch",
+        "खरीदार का GSTIN {registered} में पंजीकृत है, लेकिन आपूर्ति का स्थान {place} है।",
+    ),
+    (
+        "tax_id.ein_prefix",
+        "EIN उपसर्ग {prefix} IRS द्वारा आवंटित नहीं किया गया है।",
+    ),
+    (
+        "qr.mismatch",
+        "QR कोड में {field} {qr} है, लेकिन {extracted} निकाला गया।",
+    ),
+    (
+        "arithmetic.mismatch",
+        "{label} {stated} है, लेकिन गणना किया गया मान {computed} है (अंतर {difference})।",
+    ),
+    ("arithmetic.line_amount", "पंक्ति {line} राशि"),
+    ("arithmetic.line_cgst", "पंक्ति {line} CGST"),
+    ("arithmetic.line_sgst", "पंक्ति {line} SGST"),
+    ("arithmetic.line_igst", "पंक्ति {line} IGST"),
+    ("arithmetic.subtotal", "उप-योग"),
+    ("arithmetic.tax_total", "कुल कर"),
+    ("arithmetic.grand_total", "कुल योग"),
+    ("notify.batch_finished", "प्रोसेसिंग पूरी हुई"),
+    ("notify.batch_succeeded", "{processed} फ़ाइल(ें) सफलतापूर्वक प्रोसेस हुईं।"),
+    ("notify.batch_finished_with_errors", "प्रोसेसिंग त्रुटियों के साथ पूरी हुई"),
+    (
+        "notify.batch_failed",
+        "{processed} फ़ाइल(ें) प्रोसेस हुईं, {failed} विफल रहीं।",
+    ),
+    ("notify.export_ready", "{label} तैयार है"),
+    ("notify.saved_to", "{path} में सहेजा गया"),
+    ("tray.open", "Invox AI खोलें"),
+    ("tray.import_files", "फ़ाइलें इम्पोर्ट करें…"),
+    ("tray.pause_processing", "प्रोसेसिंग रोकें"),
+    ("tray.resume_processing", "प्रोसेसिंग फिर शुरू करें"),
+    ("tray.quit", "बंद करें"),
+    ("notify.scheduled_job_failed", "निर्धारित कार्य \"{name}\" विफल रहा"),
+    ("notify.import_failed", "{name} इम्पोर्ट नहीं हो सका"),
+];
+
+const DE: &[(&str, &str)] = &[
+    (
+        "app_lock.pin_too_short",
+        "Die PIN muss mindestens {min} Zeichen haben.",
+    ),
+    (
+        "app_lock.locked_out",
+        "Zu viele falsche PIN-Eingaben. Versuchen Sie es in {minutes} Minute(n) erneut.",
+    ),
+    (
+        "app_lock.pin_required",
+        "Für diese Aktion ist die App-PIN erforderlich.",
+    ),
+    (
+        "app_lock.locked_now",
+        "Falsche PIN. Die App ist für {minutes} Minuten gesperrt.",
+    ),
+    (
+        "app_lock.incorrect_pin",
+        "Falsche PIN. Noch {attempts} Versuch(e) bis zur Sperre.",
+    ),
+    (
+        "roles.admin_required",
+        "Bediener dürfen keine {action}. Wechseln Sie zuerst zur Administratorrolle.",
+    ),
+    ("action.delete_files", "Dateien löschen"),
+    ("action.clear_stored_files", "gespeicherten Dateien leeren"),
+    ("action.change_settings", "Einstellungen ändern"),
+    ("action.change_pin", "App-PIN ändern"),
+    ("action.remove_pin", "App-PIN entfernen"),
+    ("action.change_schedules", "Zeitpläne ändern"),
+    ("action.change_prompts", "Extraktions-Prompts ändern"),
+    ("action.purge_files", "gelöschte Dateien endgültig entfernen"),
+    ("action.restore_files", "gelöschte Dateien wiederherstellen"),
+    ("action.lock_workspace", "den Arbeitsbereich sperren oder entsperren"),
+    ("action.restore_database", "die Datenbank aus einer Sicherung wiederherstellen"),
+    ("action.delete_sheets", "Tabellen löschen"),
+    ("action.delete_csv_profiles", "CSV-Profile löschen"),
+    ("action.delete_export_profiles", "Exportprofile löschen"),
+    ("action.delete_templates", "Extraktionsvorlagen löschen"),
+    ("action.merge_parties", "Verkäufer oder Käufer zusammenführen"),
+    ("action.remove_aliases", "Alias von Verkäufern oder Käufern entfernen"),
+    (
+        "workspace.locked",
+        "Der Arbeitsbereich ist gesperrt und schreibgeschützt. Ein Administrator muss ihn entsperren, bevor Änderungen möglich sind.",
+    ),
+    (
+        "workspace.locked_with_reason",
+        "Der Arbeitsbereich ist gesperrt und schreibgeschützt ({reason}). Ein Administrator muss ihn entsperren, bevor Änderungen möglich sind.",
+    ),
+    ("tax_id.unrecognized", "Unbekanntes Format der Steuernummer."),
+    (
+        "tax_id.same_as_seller",
+        "Die Steuernummer des Käufers ist identisch mit der des Verkäufers.",
+    ),
+    ("tax_id.gstin_length", "Die GSTIN muss 15 Zeichen lang sein."),
+    ("tax_id.unknown_state", "Unbekannter GST-Bundesstaatcode {code}."),
+    ("tax_id.invalid_pan", "Die GSTIN enthält keine gültige PAN."),
+    (
+        "tax_id.invalid_entity_code",
+        "Der Rechtsträgercode der GSTIN ist ungültig.",
+    ),
+    ("tax_id.missing_z", "Das 14. Zeichen der GSTIN muss 'Z' sein."),
+    (
+        "tax_id.checksum_mismatch",
+        "GSTIN-Prüfsumme stimmt nicht (erwartet {expected}, gefunden {found}).",
+    ),
+    (
+        "tax_id.unsupported_characters",
+        "Die GSTIN enthält nicht unterstützte Zeichen.",
+    ),
+    (
+        "tax_id.unsupported_vat_country",
+        "Nicht unterstütztes USt-Länderpräfix {country}.",
+    ),
+    (
+        "tax_id.vat_format",
+        "Die USt-IdNr. entspricht nicht dem Format für {country}.",
+    ),
+    (
+        "tax_id.vat_check_digit",
+        "Die Prüfziffer der USt-IdNr. für {country} stimmt nicht.",
+    ),
+    (
+        "tax_id.place_of_supply_mismatch",
+        "Die GSTIN des Käufers ist in {registered} registriert, der Leistungsort ist jedoch {place}.",
+    ),
+    (
+        "tax_id.ein_prefix",
+        "Das EIN-Präfix {prefix} wurde vom IRS nicht vergeben.",
+    ),
+    (
+        "qr.mismatch",
+        "Laut QR-Code ist {field} {qr}, extrahiert wurde jedoch {extracted}.",
+    ),
+    (
+        "arithmetic.mismatch",
+        "{label} ist {stated}, berechnet wurde jedoch {computed} (Differenz {difference}).",
+    ),
+    ("arithmetic.line_amount", "Betrag Zeile {line}"),
+    ("arithmetic.line_cgst", "CGST Zeile {line}"),
+    ("arithmetic.line_sgst", "SGST Zeile {line}"),
+    ("arithmetic.line_igst", "IGST Zeile {line}"),
+    ("arithmetic.subtotal", "Zwischensumme"),
+    ("arithmetic.tax_total", "Steuer gesamt"),
+    ("arithmetic.grand_total", "Gesamtbetrag"),
+    ("report.spend_summary", "Ausgabenübersicht"),
+    ("report.tax_summary", "Steuerübersicht"),
+    (
+        "report.period",
+        "Zeitraum {start} bis {end} - erstellt {generated}",
+    ),
+    ("report.overview", "Überblick"),
+    ("report.spend_by_month", "Ausgaben nach Monat"),
+    ("report.spend_by_seller", "Ausgaben nach Verkäufer"),
+    ("report.tax_by_rate", "Steuer nach GST-Satz"),
+    ("report.all_invoices", "Alle Rechnungen"),
+    ("report.unknown_seller", "Unbekannter Verkäufer"),
+    ("report.total", "Summe"),
+    ("report.month", "Monat"),
+    ("report.seller", "Verkäufer"),
+    ("report.rate", "Satz"),
+    ("report.invoices", "Rechnungen"),
+    ("report.taxable_value", "Steuerpflichtiger Betrag"),
+    ("report.tax", "Steuer"),
+    ("report.total_tax", "Steuer gesamt"),
+    ("notify.batch_finished", "Verarbeitung abgeschlossen"),
+    (
+        "notify.batch_succeeded",
+        "{processed} Datei(en) erfolgreich verarbeitet.",
+    ),
+    (
+        "notify.batch_finished_with_errors",
+        "Verarbeitung mit Fehlern abgeschlossen",
+    ),
+    (
+        "notify.batch_failed",
+        "{processed} Datei(en) verarbeitet, {failed} fehlgeschlagen.",
+    ),
+    ("notify.export_ready", "{label} ist fertig"),
+    ("notify.saved_to", "Gespeichert unter {path}"),
+    ("tray.open", "Invox AI öffnen"),
+    ("tray.import_files", "Dateien importieren…"),
+    ("tray.pause_processing", "Verarbeitung pausieren"),
+    ("tray.resume_processing", "Verarbeitung fortsetzen"),
+    ("tray.quit", "Beenden"),
+    (
+        "notify.scheduled_job_failed",
+        "Geplanter Auftrag \"{name}\" fehlgeschlagen",
+    ),
+    ("notify.import_failed", "{name} konnte nicht importiert werden"),
+];
+
+const FR: &[(&str, &str)] = &[
+    (
+        "app_lock.pin_too_short",
+        "Le code PIN doit comporter au moins {min} caractères.",
+    ),
+    (
+        "app_lock.locked_out",
+        "Trop de codes PIN incorrects. Réessayez dans {minutes} minute(s).",
+    ),
+    (
+        "app_lock.pin_required",
+        "Cette action nécessite le code PIN de l'application.",
+    ),
+    (
+        "app_lock.locked_now",
+        "Code PIN incorrect. L'application est verrouillée pendant {minutes} minutes.",
+    ),
+    (
+        "app_lock.incorrect_pin",
+        "Code PIN incorrect. Il reste {attempts} tentative(s) avant le verrouillage.",
+    ),
+    (
+        "roles.admin_required",
+        "Les opérateurs ne sont pas autorisés à {action}. Passez d'abord au rôle administrateur.",
+    ),
+    ("action.delete_files", "supprimer des fichiers"),
+    ("action.clear_stored_files", "vider les fichiers stockés"),
+    ("action.change_settings", "modifier les paramètres"),
+    ("action.change_pin", "modifier le code PIN"),
+    ("action.remove_pin", "supprimer le code PIN"),
+    ("action.change_schedules", "modifier les planifications"),
+    ("action.change_prompts", "modifier les prompts d'extraction"),
+    ("action.purge_files", "purger les fichiers supprimés"),
+    ("action.restore_files", "restaurer les fichiers supprimés"),
+    ("action.lock_workspace", "verrouiller ou déverrouiller l'espace de travail"),
+    ("action.restore_database", "restaurer la base de données depuis une sauvegarde"),
+    ("action.delete_sheets", "supprimer des feuilles"),
+    ("action.delete_csv_profiles", "supprimer des profils CSV"),
+    ("action.delete_export_profiles", "supprimer des profils d'export"),
+    ("action.delete_templates", "supprimer des modèles d'extraction"),
+    ("action.merge_parties", "fusionner des vendeurs ou des acheteurs"),
+    ("action.remove_aliases", "supprimer des alias de vendeurs ou d'acheteurs"),
+    (
+        "workspace.locked",
+        "L'espace de travail est verrouillé en lecture seule. Un administrateur doit le déverrouiller avant toute modification.",
+    ),
+    (
+        "workspace.locked_with_reason",
+        "L'espace de travail est verrouillé en lecture seule ({reason}). Un administrateur doit le déverrouiller avant toute modification.",
+    ),
+    ("tax_id.unrecognized", "Format d'identifiant fiscal non reconnu."),
+    (
+        "tax_id.same_as_seller",
+        "L'identifiant fiscal de l'acheteur est identique à celui du vendeur.",
+    ),
+    ("tax_id.gstin_length", "Le GSTIN doit comporter 15 caractères."),
+    ("tax_id.unknown_state", "Code d'État GST inconnu {code}."),
+    ("tax_id.invalid_pan", "Le GSTIN ne contient pas de PAN valide."),
+    (
+        "tax_id.invalid_entity_code",
+        "Le code d'entité du GSTIN est invalide.",
+    ),
+    ("tax_id.missing_z", "Le 14e caractère du GSTIN doit être 'Z'."),
+    (
+        "tax_id.checksum_mismatch",
+        "Somme de contrôle du GSTIN incorrecte (attendu {expected}, trouvé {found}).",
+    ),
+    (
+        "tax_id.unsupported_characters",
+        "Le GSTIN contient des caractères non pris en charge.",
+    ),
+    (
+        "tax_id.unsupported_vat_country",
+        "Préfixe de pays TVA non pris en charge : {country}.",
+    ),
+    (
+        "tax_id.vat_format",
+        "Le numéro de TVA ne correspond pas au format {country}.",
+    ),
+    (
+        "tax_id.vat_check_digit",
+        "La clé de contrôle du numéro de TVA {country} est incorrecte.",
+    ),
+    (
+        "tax_id.place_of_supply_mismatch",
+        "Le GSTIN de l'acheteur est enregistré en {registered} mais le lieu de fourniture est {place}.",
+    ),
+    (
+        "tax_id.ein_prefix",
+        "Le préfixe EIN {prefix} n'a pas été attribué par l'IRS.",
+    ),
+    (
+        "qr.mismatch",
+        "Le code QR indique {qr} pour {field}, mais {extracted} a été extrait.",
+    ),
+    (
+        "arithmetic.mismatch",
+        "{label} vaut {stated} mais la valeur calculée est {computed} (écart {difference}).",
+    ),
+    ("arithmetic.line_amount", "Montant ligne {line}"),
+    ("arithmetic.line_cgst", "CGST ligne {line}"),
+    ("arithmetic.line_sgst", "SGST ligne {line}"),
+    ("arithmetic.line_igst", "IGST ligne {line}"),
+    ("arithmetic.subtotal", "Sous-total"),
+    ("arithmetic.tax_total", "Total des taxes"),
+    ("arithmetic.grand_total", "Total général"),
+    ("report.spend_summary", "Synthèse des dépenses"),
+    ("report.tax_summary", "Synthèse fiscale"),
+    (
+        "report.period",
+        "Période du {start} au {end} - générée le {generated}",
+    ),
+    ("report.overview", "Vue d'ensemble"),
+    ("report.spend_by_month", "Dépenses par mois"),
+    ("report.spend_by_seller", "Dépenses par vendeur"),
+    ("report.tax_by_rate", "Taxes par taux de GST"),
+    ("report.all_invoices", "Toutes les factures"),
+    ("report.unknown_seller", "Vendeur inconnu"),
+    ("report.total", "Total"),
+    ("report.month", "Mois"),
+    ("report.seller", "Vendeur"),
+    ("report.rate", "Taux"),
+    ("report.invoices", "Factures"),
+    ("report.taxable_value", "Base imposable"),
+    ("report.tax", "Taxes"),
+    ("report.total_tax", "Total des taxes"),
+    ("notify.batch_finished", "Traitement terminé"),
+    (
+        "notify.batch_succeeded",
+        "{processed} fichier(s) traité(s) avec succès.",
+    ),
+    (
+        "notify.batch_finished_with_errors",
+        "Traitement terminé avec des erreurs",
+    ),
+    (
+        "notify.batch_failed",
+        "{processed} fichier(s) traité(s), {failed} en échec.",
+    ),
+    ("notify.export_ready", "{label} prêt"),
+    ("notify.saved_to", "Enregistré dans {path}"),
+    ("tray.open", "Ouvrir Invox AI"),
+    ("tray.import_files", "Importer des fichiers…"),
+    ("tray.pause_processing", "Suspendre le traitement"),
+    ("tray.resume_processing", "Reprendre le traitement"),
+    ("tray.quit", "Quitter"),
+    (
+        "notify.scheduled_job_failed",
+        "La tâche planifiée « {name} » a échoué",
+    ),
+    ("notify.import_failed", "Impossible d'importer {name}"),
+];
@@ -0,0 +1,241 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::metadata::Orientation;
+use image::{DynamicImage, GrayImage, ImageBuffer, ImageDecoder, ImageFormat, ImageReader, Pixel};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Skew beyond this is assumed to be a deliberately rotated page, not a crooked photo.
+const MAX_SKEW_DEGREES: f32 = 10.0;
+const SKEW_STEP_DEGREES: f32 = 0.25;
+/// Smaller corrections are not worth the resampling blur.
+const MIN_SKEW_DEGREES: f32 = 0.3;
+/// Width the page is scaled down to while measuring skew.
+const SKEW_SAMPLE_WIDTH: u32 = 800;
+/// Share of the darkest and brightest pixels clipped when stretching contrast.
+const CONTRAST_CLIP: f64 = 0.01;
+const JPEG_QUALITY: u8 = 90;
+
+/// Clean-up steps for photographed invoices, chosen per import. Every step is off unless
+/// asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreprocessOptions {
+    /// Bakes the camera's EXIF orientation into the pixels.
+    #[serde(default)]
+    pub auto_rotate: bool,
+    /// Straightens pages photographed at a slight angle.
+    #[serde(default)]
+    pub deskew: bool,
+    /// Stretches washed-out or dim photos to the full tonal range.
+    #[serde(default)]
+    pub normalize_contrast: bool,
+    #[serde(default)]
+    pub grayscale: bool,
+}
+
+impl PreprocessOptions {
+    pub fn any(&self) -> bool {
+        self.auto_rotate || self.deskew || self.normalize_contrast || self.grayscale
+    }
+}
+
+/// Rewrites photographed invoices before they are stored, keeping their image format.
+/// PDFs and other documents are left alone.
+pub struct ImagePreprocessor;
+
+impl ImagePreprocessor {
+    /// The processed image, or `None` when `file_name` is not a PNG, JPEG, WebP or TIFF
+    /// image or there is nothing to change.
+    pub fn apply(
+        file_name: &str,
+        bytes: &[u8],
+        options: &PreprocessOptions,
+    ) -> Result<Option<Vec<u8>>, String> {
+        if !options.any() {
+            return Ok(None);
+        }
+        let Some(format) = ImageFormat::from_path(file_name).ok().filter(|format| {
+            matches!(
+                format,
+                ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Tiff
+            )
+        }) else {
+            return Ok(None);
+        };
+
+        let mut decoder = ImageReader::with_format(Cursor::new(bytes), format)
+            .into_decoder()
+            .map_err(|error| format!("{}: {}", file_name, error))?;
+        let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+        let mut image =
+            DynamicImage::from_decoder(decoder).map_err(|error| format!("{}: {}", file_name, error))?;
+        if *options
+            == (PreprocessOptions {
+                auto_rotate: true,
+                ..PreprocessOptions::default()
+            })
+            && orientation == Orientation::NoTransforms
+        {
+            return Ok(None);
+        }
+        // Re-encoding drops the EXIF tag, so the orientation is applied whenever the image
+        // is rewritten; otherwise it would come out sideways.
+        image.apply_orientation(orientation);
+
+        let image = if options.grayscale || matches!(image, DynamicImage::ImageLuma8(_)) {
+            DynamicImage::ImageLuma8(process(image.to_luma8(), options))
+        } else {
+            DynamicImage::ImageRgba8(process(image.to_rgba8(), options))
+        };
+        encode(image, format).map(Some)
+    }
+}
+
+fn process<P: Pixel<Subpixel = u8>>(
+    mut image: ImageBuffer<P, Vec<u8>>,
+    options: &PreprocessOptions,
+) -> ImageBuffer<P, Vec<u8>> {
+    if options.normalize_contrast {
+        stretch_contrast(&mut image);
+    }
+    if options.deskew {
+        if let Some(angle) = detect_skew(&luma(&image)) {
+            image = rotate(&image, angle);
+        }
+    }
+    image
+}
+
+fn luma<P: Pixel<Subpixel = u8>>(image: &ImageBuffer<P, Vec<u8>>) -> GrayImage {
+    GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        image.get_pixel(x, y).to_luma()
+    })
+}
+
+/// Maps the luminance range between the clipped darkest and brightest pixels onto 0-255,
+/// applying the same curve to every colour channel.
+fn stretch_contrast<P: Pixel<Subpixel = u8>>(image: &mut ImageBuffer<P, Vec<u8>>) {
+    let mut histogram = [0u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.to_luma()[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    let clip = (total as f64 * CONTRAST_CLIP) as u64;
+    let percentile = |levels: &[usize]| {
+        let mut seen = 0;
+        levels
+            .iter()
+            .find(|&&level| {
+                seen += histogram[level];
+                seen > clip
+            })
+            .map_or(0.0, |&level| level as f32)
+    };
+    let levels: Vec<usize> = (0..256).collect();
+    let low = percentile(&levels);
+    let levels: Vec<usize> = levels.into_iter().rev().collect();
+    let high = percentile(&levels);
+    if high - low < 16.0 {
+        return;
+    }
+
+    let colour_channels = (P::CHANNEL_COUNT - u8::from(P::HAS_ALPHA)) as usize;
+    let scale = 255.0 / (high - low);
+    for pixel in image.pixels_mut() {
+        for channel in pixel.channels_mut().iter_mut().take(colour_channels) {
+            *channel = ((*channel as f32 - low) * scale).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Angle (degrees, clockwise) of the text lines, found by projecting dark pixels onto rows
+/// at each candidate angle: the projection is sharpest when the rows follow the lines.
+fn detect_skew(page: &GrayImage) -> Option<f32> {
+    let sample = if page.width() > SKEW_SAMPLE_WIDTH {
+        let height = (page.height() as u64 * SKEW_SAMPLE_WIDTH as u64 / page.width() as u64).max(1) as u32;
+        imageops::resize(page, SKEW_SAMPLE_WIDTH, height, FilterType::Triangle)
+    } else {
+        page.clone()
+    };
+
+    let mean = sample.pixels().map(|pixel| pixel[0] as u64).sum::<u64>() / sample.len().max(1) as u64;
+    let threshold = (mean as f32 * 0.7) as u8;
+    let ink: Vec<(f32, f32)> = sample
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel[0] < threshold)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    if ink.len() < 100 {
+        return None;
+    }
+
+    let diagonal = (sample.width() as f32).hypot(sample.height() as f32);
+    let offset = diagonal;
+    let mut rows = vec![0u32; (diagonal * 2.0) as usize + 2];
+    let mut best = (0.0f32, 0u64);
+    let steps = (MAX_SKEW_DEGREES / SKEW_STEP_DEGREES) as i32;
+    for step in -steps..=steps {
+        let angle = step as f32 * SKEW_STEP_DEGREES;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        rows.iter_mut().for_each(|count| *count = 0);
+        for (x, y) in &ink {
+            rows[(y * cos - x * sin + offset) as usize] += 1;
+        }
+        let sharpness: u64 = rows.iter().map(|&count| count as u64 * count as u64).sum();
+        if sharpness > best.1 {
+            best = (angle, sharpness);
+        }
+    }
+
+    (best.0.abs() >= MIN_SKEW_DEGREES).then_some(best.0)
+}
+
+/// Rotates the content by `-angle` around the centre with bilinear sampling, keeping the
+/// canvas size and filling the uncovered corners with white.
+fn rotate<P: Pixel<Subpixel = u8>>(image: &ImageBuffer<P, Vec<u8>>, angle: f32) -> ImageBuffer<P, Vec<u8>> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (width, height) = (image.width(), image.height());
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let channels = P::CHANNEL_COUNT as usize;
+
+    let mut rotated = ImageBuffer::<P, Vec<u8>>::new(width, height);
+    for (x, y, pixel) in rotated.enumerate_pixels_mut() {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        let sx = dx * cos - dy * sin + cx - 0.5;
+        let sy = dx * sin + dy * cos + cy - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let sample = |x: f32, y: f32, channel: usize| -> f32 {
+            if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+                255.0
+            } else {
+                image.get_pixel(x as u32, y as u32).channels()[channel] as f32
+            }
+        };
+        for (channel, value) in pixel.channels_mut().iter_mut().enumerate().take(channels) {
+            let top = sample(x0, y0, channel) * (1.0 - fx) + sample(x0 + 1.0, y0, channel) * fx;
+            let bottom =
+                sample(x0, y0 + 1.0, channel) * (1.0 - fx) + sample(x0 + 1.0, y0 + 1.0, channel) * fx;
+            *value = (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    rotated
+}
+
+fn encode(image: DynamicImage, format: ImageFormat) -> Result<Vec<u8>, String> {
+    let mut output = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::Jpeg => {
+            let image = match image {
+                DynamicImage::ImageLuma8(_) => image,
+                other => DynamicImage::ImageRgb8(other.to_rgb8()),
+            };
+            image.write_with_encoder(JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY))
+        }
+        ImageFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8()).write_to(&mut output, format),
+        _ => image.write_to(&mut output, format),
+    }
+    .map_err(|error| error.to_string())?;
+    Ok(output.into_inner())
+}
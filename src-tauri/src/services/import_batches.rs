@@ -0,0 +1,223 @@
+use crate::db::get_connection;
+use crate::services::batch_import::{import_result, ImportEntryResult, ImportEntryStatus};
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+/// Where a batch came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// Files picked or dropped in the app.
+    Upload,
+    /// Paths handed to `import_paths` or `import_archive`.
+    Paths,
+    WatchedFolder,
+    CloudDrive,
+    FolderPipeline,
+    /// Files opened with Invox or sent through an `invox://import` link.
+    Launch,
+    Legacy,
+    Clipboard,
+}
+
+impl ImportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::Upload => "upload",
+            ImportSource::Paths => "paths",
+            ImportSource::WatchedFolder => "watched_folder",
+            ImportSource::CloudDrive => "cloud_drive",
+            ImportSource::FolderPipeline => "folder_pipeline",
+            ImportSource::Launch => "launch",
+            ImportSource::Legacy => "legacy",
+            ImportSource::Clipboard => "clipboard",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatch {
+    pub id: i64,
+    pub source: String,
+    /// Folder, archive or drive the batch was imported from, when there is one.
+    pub label: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub cancelled: bool,
+    pub imported: i64,
+    pub duplicates: i64,
+    pub skipped: i64,
+    pub failed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatchItem {
+    pub entry: String,
+    pub status: ImportEntryStatus,
+    /// The new file, or the existing one a duplicate matched.
+    pub file_id: Option<String>,
+    pub reason: Option<String>,
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatchReport {
+    pub batch: ImportBatch,
+    pub items: Vec<ImportBatchItem>,
+}
+
+const BATCH_COLUMNS: &str = "b.id, b.source, b.label, b.started_at, b.finished_at, b.cancelled,
+    COALESCE(SUM(i.status = 'imported'), 0), COALESCE(SUM(i.status = 'duplicate'), 0),
+    COALESCE(SUM(i.status = 'skipped'), 0), COALESCE(SUM(i.status = 'failed'), 0)
+    FROM import_batches b LEFT JOIN import_batch_items i ON i.batch_id = b.id";
+
+/// Groups every import into a batch with a manifest of what each file turned into, and
+/// stamps new files with the batch that brought them in.
+pub struct ImportBatches;
+
+impl ImportBatches {
+    pub fn begin(conn: &Connection, source: ImportSource, label: Option<&str>) -> Result<i64, String> {
+        conn.execute(
+            "INSERT INTO import_batches (source, label, started_at) VALUES (?1, ?2, ?3)",
+            params![source.as_str(), label, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn record(conn: &Connection, batch_id: i64, entry: &ImportEntryResult) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO import_batch_items (batch_id, entry, status, file_id, reason, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                batch_id,
+                entry.entry,
+                entry.status.as_str(),
+                entry.file_id,
+                entry.reason,
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        if entry.status == ImportEntryStatus::Imported {
+            conn.execute(
+                "UPDATE files SET import_batch_id = ?1 WHERE id = ?2",
+                params![batch_id, entry.file_id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(conn: &Connection, batch_id: i64, cancelled: bool) -> Result<(), String> {
+        conn.execute(
+            "UPDATE import_batches SET finished_at = ?2, cancelled = ?3 WHERE id = ?1",
+            params![batch_id, Utc::now().to_rfc3339(), cancelled],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Records a `persist_buffer` outcome into `batch_id`, or into a finished batch of its
+    /// own when the import was not part of one.
+    pub fn record_outcome(
+        batch_id: Option<i64>,
+        source: ImportSource,
+        entry: &str,
+        outcome: &Result<String, String>,
+    ) -> Result<(), String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let result = import_result(entry.to_string(), outcome.clone());
+        match batch_id {
+            Some(batch_id) => Self::record(&conn, batch_id, &result),
+            None => {
+                let batch_id = Self::begin(&conn, source, None)?;
+                Self::record(&conn, batch_id, &result)?;
+                Self::finish(&conn, batch_id, false)
+            }
+        }
+    }
+
+    /// Batches started in the range, newest first. `from` and `to` are dates or RFC 3339
+    /// timestamps; a bare `to` date includes the whole day.
+    pub fn list(conn: &Connection, from: Option<&str>, to: Option<&str>) -> Result<Vec<ImportBatch>, String> {
+        let to = to.map(|to| match NaiveDate::parse_from_str(to, "%Y-%m-%d") {
+            Ok(date) => (date + Duration::days(1)).to_string(),
+            Err(_) => to.to_string(),
+        });
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} WHERE (?1 IS NULL OR b.started_at >= ?1) AND (?2 IS NULL OR b.started_at < ?2)
+                 GROUP BY b.id ORDER BY b.started_at DESC, b.id DESC",
+                BATCH_COLUMNS
+            ))
+            .map_err(|error| error.to_string())?;
+        let batches = stmt
+            .query_map(params![from, to], map_batch)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(batches)
+    }
+
+    pub fn report(conn: &Connection, batch_id: i64) -> Result<ImportBatchReport, String> {
+        let batch = conn
+            .query_row(
+                &format!("SELECT {} WHERE b.id = ?1 GROUP BY b.id", BATCH_COLUMNS),
+                params![batch_id],
+                map_batch,
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("Import batch not found: {}", batch_id))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT entry, status, file_id, reason, recorded_at FROM import_batch_items
+                 WHERE batch_id = ?1 ORDER BY id",
+            )
+            .map_err(|error| error.to_string())?;
+        let items = stmt
+            .query_map(params![batch_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .map_err(|error| error.to_string())?
+            .map(|row| {
+                let (entry, status, file_id, reason, recorded_at) = row.map_err(|error| error.to_string())?;
+                Ok(ImportBatchItem {
+                    entry,
+                    status: ImportEntryStatus::parse(&status)?,
+                    file_id,
+                    reason,
+                    recorded_at,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(ImportBatchReport { batch, items })
+    }
+}
+
+fn map_batch(row: &Row) -> rusqlite::Result<ImportBatch> {
+    Ok(ImportBatch {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        label: row.get(2)?,
+        started_at: row.get(3)?,
+        finished_at: row.get(4)?,
+        cancelled: row.get(5)?,
+        imported: row.get(6)?,
+        duplicates: row.get(7)?,
+        skipped: row.get(8)?,
+        failed: row.get(9)?,
+    })
+}
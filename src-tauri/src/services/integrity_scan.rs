@@ -0,0 +1,261 @@
+use crate::commands::file_operations::FileStatus;
+use crate::db::{reports_dir, tessdata_dir};
+use crate::services::export_bundle::BUNDLE_STAGING_PREFIX;
+use crate::services::settings::Settings;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with the [`IntegrityReport`] after the startup scan.
+pub const INTEGRITY_SCAN_EVENT: &str = "integrity://scan-completed";
+
+const SCAN_ON_STARTUP_KEY: &str = "integrity.scan_on_startup";
+const LAST_REPORT_KEY: &str = "integrity.last_report";
+/// Temp files younger than this may belong to a download or export that is still running.
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MissingFile,
+    StuckProcessing,
+    DanglingExportMembership,
+    StaleTempFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    /// File id, XML export id or temp path the issue is about.
+    pub subject: String,
+    pub detail: String,
+    pub suggestion: String,
+    /// Already fixed by the scan itself.
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub scanned_at: String,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// Quick consistency checks between the database and the files on disk. Unlike
+/// `FileIntegrity::verify`, nothing is re-hashed, so it is cheap enough to run on every launch.
+pub struct IntegrityScan;
+
+impl IntegrityScan {
+    pub fn enabled_on_startup(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, SCAN_ON_STARTUP_KEY)?.as_deref() != Some("0"))
+    }
+
+    pub fn set_enabled_on_startup(conn: &Connection, enabled: bool) -> Result<(), String> {
+        Settings::set(conn, SCAN_ON_STARTUP_KEY, if enabled { "1" } else { "0" })
+    }
+
+    /// Runs at launch, before the frontend can have started any processing, so every file
+    /// still marked `Processing` was left behind by a previous session and is reset.
+    pub fn on_startup<R: Runtime>(app: &AppHandle<R>, conn: &Connection) -> Result<(), String> {
+        if !Self::enabled_on_startup(conn)? {
+            return Ok(());
+        }
+        let report = Self::run(conn, true)?;
+        app.emit(INTEGRITY_SCAN_EVENT, &report)
+            .map_err(|error| error.to_string())
+    }
+
+    /// Scans and stores the report. `reset_stuck` must only be set when no processing can be
+    /// in flight.
+    pub fn run(conn: &Connection, reset_stuck: bool) -> Result<IntegrityReport, String> {
+        let mut issues = Vec::new();
+        missing_files(conn, &mut issues)?;
+        stuck_processing(conn, reset_stuck, &mut issues)?;
+        dangling_memberships(conn, &mut issues)?;
+        for path in stale_temp_files() {
+            issues.push(IntegrityIssue {
+                kind: IssueKind::StaleTempFile,
+                subject: path.to_string_lossy().into_owned(),
+                detail: "Left over from an interrupted download or export.".to_string(),
+                suggestion: "Run repair to delete it.".to_string(),
+                repaired: false,
+            });
+        }
+
+        let report = IntegrityReport {
+            scanned_at: Utc::now().to_rfc3339(),
+            issues,
+        };
+        let stored = serde_json::to_string(&report).map_err(|error| error.to_string())?;
+        Settings::set(conn, LAST_REPORT_KEY, &stored)?;
+        Ok(report)
+    }
+
+    pub fn last_report(conn: &Connection) -> Result<Option<IntegrityReport>, String> {
+        Ok(Settings::get(conn, LAST_REPORT_KEY)?.and_then(|stored| serde_json::from_str(&stored).ok()))
+    }
+
+    /// Applies the safe repairs: drops export memberships of deleted files and deletes stale
+    /// temp files. Missing documents are left for the user to re-import or delete.
+    pub fn repair(conn: &Connection) -> Result<usize, String> {
+        let mut repaired = conn
+            .execute(
+                "DELETE FROM xml_file_entries WHERE file_id NOT IN (SELECT id FROM files)",
+                [],
+            )
+            .map_err(|error| error.to_string())?;
+
+        for path in stale_temp_files() {
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if removed.is_ok() {
+                repaired += 1;
+            }
+        }
+
+        Ok(repaired)
+    }
+}
+
+fn missing_files(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT id, file_name, stored_path FROM files ORDER BY created_at")
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    for (file_id, file_name, stored_path) in rows {
+        if Path::new(&stored_path).is_file() {
+            continue;
+        }
+        issues.push(IntegrityIssue {
+            kind: IssueKind::MissingFile,
+            subject: file_id,
+            detail: format!("The stored copy of {} is missing ({}).", file_name, stored_path),
+            suggestion: "Relink the record to a copy of the original document, or delete it.".to_string(),
+            repaired: false,
+        });
+    }
+    Ok(())
+}
+
+fn stuck_processing(conn: &Connection, reset: bool, issues: &mut Vec<IntegrityIssue>) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT id, file_name FROM files WHERE status = ?1")
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map(params![FileStatus::Processing.as_str()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    if reset && !rows.is_empty() {
+        conn.execute(
+            "UPDATE files SET status = ?1 WHERE status = ?2",
+            params![FileStatus::Unprocessed.as_str(), FileStatus::Processing.as_str()],
+        )
+        .map_err(|error| error.to_string())?;
+    }
+
+    for (file_id, file_name) in rows {
+        issues.push(IntegrityIssue {
+            kind: IssueKind::StuckProcessing,
+            subject: file_id,
+            detail: format!("{} was still marked as processing.", file_name),
+            suggestion: if reset {
+                "Reset to unprocessed; process it again.".to_string()
+            } else {
+                "Restart the app to reset it if no processing is running.".to_string()
+            },
+            repaired: reset,
+        });
+    }
+    Ok(())
+}
+
+fn dangling_memberships(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<(), String> {
+    for (xml_id, dangling) in dangling_counts(conn)? {
+        issues.push(IntegrityIssue {
+            kind: IssueKind::DanglingExportMembership,
+            subject: xml_id.to_string(),
+            detail: format!("XML export {} lists {} deleted file(s).", xml_id, dangling),
+            suggestion: "Run repair to remove them from the export.".to_string(),
+            repaired: false,
+        });
+    }
+    Ok(())
+}
+
+/// Sheets listing files that no longer exist, with how many each lists.
+fn dangling_counts(conn: &Connection) -> Result<Vec<(i64, i64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT xml_id, COUNT(*) FROM xml_file_entries
+             WHERE file_id NOT IN (SELECT id FROM files)
+             GROUP BY xml_id",
+        )
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(rows)
+}
+
+/// Interrupted language pack downloads in the tessdata folder and partial report files,
+/// both left as `.part` files, plus export bundle staging folders.
+fn stale_temp_files() -> Vec<PathBuf> {
+    let is_stale = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age >= STALE_AFTER)
+    };
+    let entries = |dir: PathBuf| -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut stale = Vec::new();
+    for dir in [tessdata_dir(), reports_dir()].into_iter().flatten() {
+        stale.extend(
+            entries(dir)
+                .into_iter()
+                .filter(|path| path.extension().is_some_and(|extension| extension == "part")),
+        );
+    }
+    stale.extend(entries(std::env::temp_dir()).into_iter().filter(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BUNDLE_STAGING_PREFIX))
+    }));
+    stale.retain(|path| is_stale(path));
+    stale
+}
@@ -0,0 +1,127 @@
+use crate::services::{
+    parsed_invoice::{
+        ParsedInvoice, ParsedLineItem, GRAND_TOTAL, SELLER_NAME, SUBTOTAL, TAX_TOTAL, VOUCHER_TYPE,
+    },
+    report_period::ReportPeriod,
+};
+use rusqlite::{params, Connection};
+
+/// Per-invoice figures shared by the reporting commands.
+pub struct InvoiceFact {
+    pub invoice_date: String,
+    pub seller_id: Option<i64>,
+    pub seller_name: Option<String>,
+    pub buyer_id: Option<i64>,
+    pub doc_type: Option<String>,
+    pub subtotal: f64,
+    pub tax_total: f64,
+    pub grand_total: f64,
+    /// Taxable value plus tax per GST rate (CGST + SGST, or IGST), from the line items or the
+    /// totals.
+    pub by_rate: Vec<(f64, f64)>,
+}
+
+pub struct InvoiceFacts;
+
+/// Subtotal, tax and grand total of an invoice. Totals the model did not read are derived
+/// from the line items, and the grand total from the other two.
+pub fn invoice_totals(invoice: &ParsedInvoice, items: &[ParsedLineItem]) -> (f64, f64, f64) {
+    let line_amounts: f64 = items.iter().filter_map(|item| item.amount).sum();
+    let line_taxes: f64 = items
+        .iter()
+        .map(|item| item.cgst.unwrap_or(0.0) + item.sgst.unwrap_or(0.0) + item.igst.unwrap_or(0.0))
+        .sum();
+    let subtotal = invoice.number(SUBTOTAL).unwrap_or(line_amounts);
+    let tax_total = invoice.number(TAX_TOTAL).unwrap_or(line_taxes);
+    let grand_total = invoice.number(GRAND_TOTAL).unwrap_or(subtotal + tax_total);
+    (subtotal, tax_total, grand_total)
+}
+
+impl InvoiceFacts {
+    /// Loads every parsed file with a normalized invoice date, limited to `period` when given.
+    pub fn load(conn: &Connection, period: Option<&ReportPeriod>) -> Result<Vec<InvoiceFact>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT f.invoice_date, f.seller_id, s.name, f.buyer_id, f.parsed_details
+                 FROM files f
+                 LEFT JOIN sellers s ON s.id = f.seller_id
+                 WHERE f.parsed_details IS NOT NULL
+                 AND f.invoice_date IS NOT NULL
+                 AND (?1 IS NULL OR f.invoice_date >= ?1)
+                 AND (?2 IS NULL OR f.invoice_date <= ?2)
+                 ORDER BY f.invoice_date ASC, f.created_at ASC",
+            )
+            .map_err(|error| error.to_string())?;
+
+        let rows = stmt
+            .query_map(
+                params![
+                    period.map(ReportPeriod::start_iso),
+                    period.map(ReportPeriod::end_iso)
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<i64>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<i64>>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                },
+            )
+            .map_err(|error| error.to_string())?;
+
+        let mut facts = Vec::new();
+        for row in rows {
+            let (invoice_date, seller_id, seller_name, buyer_id, parsed_details) =
+                row.map_err(|error| error.to_string())?;
+            let Some(invoice) = ParsedInvoice::from_json(&parsed_details) else {
+                continue;
+            };
+
+            let items = invoice.line_items();
+            let (subtotal, tax_total, grand_total) = invoice_totals(&invoice, &items);
+
+            let mut by_rate: Vec<(f64, f64)> = Vec::new();
+            if items.is_empty() {
+                let rate = if subtotal > 0.0 {
+                    (tax_total / subtotal * 10000.0).round() / 100.0
+                } else {
+                    0.0
+                };
+                by_rate.push((rate, grand_total));
+            }
+            for item in &items {
+                let rate = match item.igst_rate {
+                    Some(rate) => rate,
+                    None => item.cgst_rate.unwrap_or(0.0) + item.sgst_rate.unwrap_or(0.0),
+                };
+                let value = item.amount.unwrap_or(0.0)
+                    + item.cgst.unwrap_or(0.0)
+                    + item.sgst.unwrap_or(0.0)
+                    + item.igst.unwrap_or(0.0);
+                match by_rate
+                    .iter_mut()
+                    .find(|(existing, _)| (*existing - rate).abs() < 0.001)
+                {
+                    Some(entry) => entry.1 += value,
+                    None => by_rate.push((rate, value)),
+                }
+            }
+
+            facts.push(InvoiceFact {
+                invoice_date,
+                seller_id,
+                seller_name: seller_name.or_else(|| invoice.text(SELLER_NAME)),
+                buyer_id,
+                doc_type: invoice.text(VOUCHER_TYPE),
+                subtotal,
+                tax_total,
+                grand_total,
+                by_rate,
+            });
+        }
+
+        Ok(facts)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::services::parsed_invoice::{
+    ParsedInvoice, GRAND_TOTAL, IBAN, INVOICE_NUMBER, IRN, SELLER_NAME, SIGNED_QR_CODE, UPI_ID,
+};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+/// Pixels per QR module in the rendered PNG.
+const MODULE_PIXELS: usize = 8;
+/// Blank modules around the code, as required by the QR specification.
+const QUIET_ZONE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QrPayloadKind {
+    /// Signed QR issued by the Indian Invoice Registration Portal.
+    EInvoice,
+    /// UPI payment intent (`upi://pay`).
+    Upi,
+    /// EPC069-12 SEPA credit transfer ("GiroCode").
+    Epc,
+}
+
+pub struct QrPayload {
+    pub kind: QrPayloadKind,
+    pub content: String,
+}
+
+impl QrPayload {
+    /// Picks the QR to print for an invoice. The e-invoice QR is signed by the IRP, so it
+    /// can only be reproduced from the extracted signed payload, never synthesized here.
+    pub fn for_invoice(invoice: &ParsedInvoice) -> Result<Self, String> {
+        if let Some(signed) = invoice.text(SIGNED_QR_CODE) {
+            return Ok(Self {
+                kind: QrPayloadKind::EInvoice,
+                content: signed,
+            });
+        }
+
+        if invoice.text(IRN).is_some() {
+            return Err(
+                "The invoice has an IRN but its signed QR code was not extracted. Re-run extraction on a clearer copy."
+                    .to_string(),
+            );
+        }
+
+        let seller = invoice.text(SELLER_NAME).unwrap_or_default();
+        let amount = invoice.number(GRAND_TOTAL);
+        let reference = invoice.text(INVOICE_NUMBER).unwrap_or_default();
+
+        if let Some(upi_id) = invoice.text(UPI_ID) {
+            let mut content = format!("upi://pay?pa={}&pn={}&cu=INR", encode(&upi_id), encode(&seller));
+            if let Some(amount) = amount {
+                content.push_str(&format!("&am={:.2}", amount));
+            }
+            if !reference.is_empty() {
+                content.push_str(&format!("&tn={}", encode(&format!("Invoice {}", reference))));
+            }
+            return Ok(Self {
+                kind: QrPayloadKind::Upi,
+                content,
+            });
+        }
+
+        if let Some(iban) = invoice.text(IBAN) {
+            let iban: String = iban.chars().filter(|ch| !ch.is_whitespace()).collect();
+            let amount = amount.map(|value| format!("EUR{:.2}", value)).unwrap_or_default();
+            let content = [
+                "BCD",
+                "002",
+                "1",
+                "SCT",
+                "",
+                &seller.chars().take(70).collect::<String>(),
+                &iban,
+                &amount,
+                "",
+                "",
+                &reference.chars().take(140).collect::<String>(),
+            ]
+            .join("\n");
+            return Ok(Self {
+                kind: QrPayloadKind::Epc,
+                content,
+            });
+        }
+
+        Err("No signed e-invoice QR, UPI ID or IBAN was extracted for this invoice.".to_string())
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        // EPC069-12 mandates error correction level M; the others use the same for consistency.
+        let code = QrCode::with_error_correction_level(self.content.as_bytes(), EcLevel::M)
+            .map_err(|error| error.to_string())?;
+        let modules = code.width();
+        let colors = code.to_colors();
+        let size = (modules + 2 * QUIET_ZONE) * MODULE_PIXELS;
+
+        let mut pixels = vec![255u8; size * size];
+        for (index, color) in colors.iter().enumerate() {
+            if *color != Color::Dark {
+                continue;
+            }
+            let left = (index % modules + QUIET_ZONE) * MODULE_PIXELS;
+            let top = (index / modules + QUIET_ZONE) * MODULE_PIXELS;
+            for y in top..top + MODULE_PIXELS {
+                pixels[y * size + left..y * size + left + MODULE_PIXELS].fill(0);
+            }
+        }
+
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, size as u32, size as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|error| error.to_string())?;
+        writer.finish().map_err(|error| error.to_string())?;
+
+        Ok(png_bytes)
+    }
+}
+
+/// Percent-encodes a value for use in a URI query string.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,326 @@
+use crate::services::invoice_facts::invoice_totals;
+use crate::services::line_items::LineItemStore;
+use crate::services::parsed_invoice::{
+    ParsedInvoice, CURRENCY, INVOICE_NUMBER, PLACE_OF_SUPPLY, VOUCHER_TYPE,
+};
+use crate::services::report_period::ReportPeriod;
+use crate::services::tax_lines::{TaxLine, TaxLineStore};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+const INVOICE_COLUMNS: &str = "i.id, i.file_id, f.file_name, i.seller_id, s.name, i.buyer_id, b.name,
+     i.invoice_number, i.invoice_date, i.due_date, i.voucher_type, i.place_of_supply, i.currency,
+     i.subtotal, i.cgst_total, i.sgst_total, i.tax_total, i.grand_total, i.updated_at,
+     i.igst_total, i.cess_total";
+
+const INVOICE_JOINS: &str = "FROM invoices i
+     JOIN files f ON f.id = i.file_id
+     LEFT JOIN sellers s ON s.id = i.seller_id
+     LEFT JOIN buyers b ON b.id = i.buyer_id";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceRecord {
+    pub id: i64,
+    pub file_id: String,
+    pub file_name: String,
+    pub seller_id: Option<i64>,
+    pub seller_name: Option<String>,
+    pub buyer_id: Option<i64>,
+    pub buyer_name: Option<String>,
+    pub invoice_number: Option<String>,
+    /// ISO date, when the extracted date could be normalized.
+    pub invoice_date: Option<String>,
+    pub due_date: Option<String>,
+    pub voucher_type: Option<String>,
+    pub place_of_supply: Option<String>,
+    pub currency: Option<String>,
+    pub subtotal: f64,
+    pub cgst_total: f64,
+    pub sgst_total: f64,
+    pub igst_total: f64,
+    pub cess_total: f64,
+    pub tax_total: f64,
+    pub grand_total: f64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceLineItemRecord {
+    pub position: i64,
+    pub description: Option<String>,
+    pub name: Option<String>,
+    pub hsn_sac: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub rate: Option<f64>,
+    pub amount: Option<f64>,
+    pub cgst: Option<f64>,
+    pub sgst: Option<f64>,
+    pub cgst_rate: Option<f64>,
+    pub sgst_rate: Option<f64>,
+    pub igst: Option<f64>,
+    pub igst_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceDetail {
+    #[serde(flatten)]
+    pub invoice: InvoiceRecord,
+    pub line_items: Vec<InvoiceLineItemRecord>,
+    /// Taxable value and GST components per rate.
+    pub tax_lines: Vec<TaxLine>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceQuery {
+    /// Same forms as the reports: `2024`, `2024-07`, `2024-Q2` or `2024-04-01..2024-06-30`.
+    pub period: Option<String>,
+    pub seller_id: Option<i64>,
+    pub buyer_id: Option<i64>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// One row per parsed file in `invoices`, with its line items in `invoice_line_items` and
+/// its tax split in `tax_lines`. All are derived from `parsed_details`, which stays the record of what was extracted
+/// or edited, and are rewritten whenever it changes.
+pub struct InvoiceStore;
+
+impl InvoiceStore {
+    /// Rewrites the file's invoice row from `parsed_details` and links its line items to it.
+    /// Run after the seller, buyer, date, payment-term and tax-line steps, whose results it
+    /// copies.
+    /// Files without parsed details lose their row.
+    pub fn replace_for_file(
+        conn: &Connection,
+        file_id: &str,
+        parsed_details: Option<&str>,
+    ) -> Result<Option<i64>, String> {
+        conn.execute(
+            "UPDATE files SET invoice_synced_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        let Some(invoice) = parsed_details.and_then(ParsedInvoice::from_json) else {
+            conn.execute("DELETE FROM invoices WHERE file_id = ?1", params![file_id])
+                .map_err(|error| error.to_string())?;
+            return Ok(None);
+        };
+
+        let items = invoice.line_items();
+        let (subtotal, tax_total, grand_total) = invoice_totals(&invoice, &items);
+        let tax_lines = TaxLineStore::for_file(conn, file_id)?;
+        let component = |amount: fn(&TaxLine) -> f64| tax_lines.iter().map(amount).sum::<f64>();
+
+        conn.execute(
+            "INSERT INTO invoices (
+               file_id, seller_id, buyer_id, invoice_number, invoice_date, due_date, voucher_type,
+               place_of_supply, currency, subtotal, cgst_total, sgst_total, igst_total, cess_total,
+               tax_total, grand_total
+             )
+             SELECT id, seller_id, buyer_id, ?2, invoice_date, due_date, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
+                    ?11, ?12
+             FROM files WHERE id = ?1
+             ON CONFLICT(file_id) DO UPDATE SET
+               seller_id = excluded.seller_id,
+               buyer_id = excluded.buyer_id,
+               invoice_number = excluded.invoice_number,
+               invoice_date = excluded.invoice_date,
+               due_date = excluded.due_date,
+               voucher_type = excluded.voucher_type,
+               place_of_supply = excluded.place_of_supply,
+               currency = excluded.currency,
+               subtotal = excluded.subtotal,
+               cgst_total = excluded.cgst_total,
+               sgst_total = excluded.sgst_total,
+               igst_total = excluded.igst_total,
+               cess_total = excluded.cess_total,
+               tax_total = excluded.tax_total,
+               grand_total = excluded.grand_total,
+               updated_at = CURRENT_TIMESTAMP",
+            params![
+                file_id,
+                invoice.text(INVOICE_NUMBER),
+                invoice.text(VOUCHER_TYPE),
+                invoice.text(PLACE_OF_SUPPLY),
+                invoice.text(CURRENCY),
+                subtotal,
+                component(|line| line.cgst),
+                component(|line| line.sgst),
+                component(|line| line.igst),
+                component(|line| line.cess),
+                tax_total,
+                grand_total
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+
+        let invoice_id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM invoices WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        conn.execute(
+            "UPDATE invoice_line_items SET invoice_id = ?1 WHERE file_id = ?2",
+            params![invoice_id, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(invoice_id)
+    }
+
+    /// Builds invoice rows, with their line items and tax lines, for parsed files that
+    /// predate those tables or whose parsed details changed without them being rebuilt.
+    /// Files are marked once done, even those with no invoice or tax lines to build, so it
+    /// is cheap enough to run at startup.
+    pub fn backfill(conn: &Connection) -> Result<usize, String> {
+        let pending: Vec<(String, String)> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, parsed_details FROM files
+                     WHERE parsed_details IS NOT NULL AND invoice_synced_at IS NULL",
+                )
+                .map_err(|error| error.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|error| error.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|error| error.to_string())?
+        };
+
+        let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+        let mut created = 0;
+        for (file_id, parsed_details) in pending {
+            LineItemStore::replace_for_file(&tx, &file_id, Some(&parsed_details))?;
+            TaxLineStore::replace_for_file(&tx, &file_id, Some(&parsed_details))?;
+            if Self::replace_for_file(&tx, &file_id, Some(&parsed_details))?.is_some() {
+                created += 1;
+            }
+        }
+        tx.commit().map_err(|error| error.to_string())?;
+        Ok(created)
+    }
+
+    pub fn get(conn: &Connection, file_id: &str) -> Result<Option<InvoiceDetail>, String> {
+        let invoice = conn
+            .query_row(
+                &format!(
+                    "SELECT {} {} WHERE i.file_id = ?1",
+                    INVOICE_COLUMNS, INVOICE_JOINS
+                ),
+                params![file_id],
+                invoice_from_row,
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        let Some(invoice) = invoice else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT position, description, name, hsn_sac, quantity, unit, rate, amount,
+                        cgst, sgst, cgst_rate, sgst_rate, igst, igst_rate
+                 FROM invoice_line_items
+                 WHERE invoice_id = ?1
+                 ORDER BY position",
+            )
+            .map_err(|error| error.to_string())?;
+        let line_items = stmt
+            .query_map(params![invoice.id], |row| {
+                Ok(InvoiceLineItemRecord {
+                    position: row.get(0)?,
+                    description: row.get(1)?,
+                    name: row.get(2)?,
+                    hsn_sac: row.get(3)?,
+                    quantity: row.get(4)?,
+                    unit: row.get(5)?,
+                    rate: row.get(6)?,
+                    amount: row.get(7)?,
+                    cgst: row.get(8)?,
+                    sgst: row.get(9)?,
+                    cgst_rate: row.get(10)?,
+                    sgst_rate: row.get(11)?,
+                    igst: row.get(12)?,
+                    igst_rate: row.get(13)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let tax_lines = TaxLineStore::for_file(conn, file_id)?;
+
+        Ok(Some(InvoiceDetail {
+            invoice,
+            line_items,
+            tax_lines,
+        }))
+    }
+
+    /// Invoices by date, newest first. A period leaves out invoices without a normalized date.
+    pub fn list(conn: &Connection, query: &InvoiceQuery) -> Result<Vec<InvoiceRecord>, String> {
+        let period = query.period.as_deref().map(ReportPeriod::parse).transpose()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} {}
+                 WHERE (?1 IS NULL OR i.invoice_date >= ?1)
+                 AND (?2 IS NULL OR i.invoice_date <= ?2)
+                 AND (?3 IS NULL OR i.seller_id = ?3)
+                 AND (?4 IS NULL OR i.buyer_id = ?4)
+                 ORDER BY i.invoice_date IS NULL, i.invoice_date DESC, i.id DESC
+                 LIMIT ?5 OFFSET ?6",
+                INVOICE_COLUMNS, INVOICE_JOINS
+            ))
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    period.as_ref().map(ReportPeriod::start_iso),
+                    period.as_ref().map(ReportPeriod::end_iso),
+                    query.seller_id,
+                    query.buyer_id,
+                    query.limit.unwrap_or(-1),
+                    query.offset.unwrap_or(0).max(0)
+                ],
+                invoice_from_row,
+            )
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+}
+
+fn invoice_from_row(row: &Row) -> rusqlite::Result<InvoiceRecord> {
+    Ok(InvoiceRecord {
+        id: row.get(0)?,
+        file_id: row.get(1)?,
+        file_name: row.get(2)?,
+        seller_id: row.get(3)?,
+        seller_name: row.get(4)?,
+        buyer_id: row.get(5)?,
+        buyer_name: row.get(6)?,
+        invoice_number: row.get(7)?,
+        invoice_date: row.get(8)?,
+        due_date: row.get(9)?,
+        voucher_type: row.get(10)?,
+        place_of_supply: row.get(11)?,
+        currency: row.get(12)?,
+        subtotal: row.get(13)?,
+        cgst_total: row.get(14)?,
+        sgst_total: row.get(15)?,
+        tax_total: row.get(16)?,
+        grand_total: row.get(17)?,
+        updated_at: row.get(18)?,
+        igst_total: row.get(19)?,
+        cess_total: row.get(20)?,
+    })
+}
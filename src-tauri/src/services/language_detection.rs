@@ -0,0 +1,215 @@
+use crate::services::ocr_languages::OcrLanguages;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parsed-details key a provider may fill with the document language; used as is when it
+/// names a known language.
+pub const DOCUMENT_LANGUAGE: &str = "document language";
+
+/// A Latin-script language needs this many of its words before it is reported.
+const MIN_WORD_HITS: usize = 2;
+
+/// Unicode blocks of the non-Latin scripts invoices arrive in, with the language each is
+/// read as. Devanagari is reported as Hindi, the far more common of its invoice languages.
+const SCRIPTS: &[(u32, u32, &str)] = &[
+    (0x0600, 0x06FF, "ara"),
+    (0x0900, 0x097F, "hin"),
+    (0x0980, 0x09FF, "ben"),
+    (0x0A00, 0x0A7F, "pan"),
+    (0x0A80, 0x0AFF, "guj"),
+    (0x0B80, 0x0BFF, "tam"),
+    (0x0C00, 0x0C7F, "tel"),
+    (0x0C80, 0x0CFF, "kan"),
+    (0x0D00, 0x0D7F, "mal"),
+    (0x3040, 0x30FF, "jpn"),
+    (0x4E00, 0x9FFF, "chi_sim"),
+];
+
+/// Letters only Urdu adds to the Arabic script.
+const URDU_LETTERS: &[char] = &['ٹ', 'ڈ', 'ڑ', 'ں', 'ے', 'ھ'];
+
+/// Common words and invoice vocabulary of the Latin-script languages. Words shared between
+/// languages are left out, so each hit points at one language.
+const LATIN_WORDS: &[(&str, &[&str])] = &[
+    (
+        "eng",
+        &[
+            "the", "and", "of", "invoice", "amount", "tax", "bill", "payable", "quantity", "with",
+        ],
+    ),
+    (
+        "deu",
+        &[
+            "und", "der", "die", "das", "rechnung", "betrag", "mwst", "ust", "gesamt", "menge", "straße",
+        ],
+    ),
+    (
+        "fra",
+        &[
+            "et",
+            "les",
+            "des",
+            "facture",
+            "montant",
+            "tva",
+            "quantité",
+            "prix",
+            "rue",
+            "société",
+        ],
+    ),
+    (
+        "spa",
+        &[
+            "y", "los", "del", "factura", "importe", "iva", "cantidad", "precio", "calle", "empresa",
+        ],
+    ),
+    (
+        "ita",
+        &[
+            "il",
+            "di",
+            "della",
+            "fattura",
+            "importo",
+            "totale",
+            "quantità",
+            "prezzo",
+            "via",
+            "partita",
+        ],
+    ),
+    (
+        "por",
+        &[
+            "o",
+            "do",
+            "da",
+            "fatura",
+            "valor",
+            "quantidade",
+            "preço",
+            "rua",
+            "nota",
+            "fiscal",
+        ],
+    ),
+    (
+        "nld",
+        &[
+            "het", "en", "van", "factuur", "bedrag", "btw", "aantal", "prijs", "straat", "totaal",
+        ],
+    ),
+];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentLanguage {
+    pub code: String,
+    pub name: Option<&'static str>,
+    pub file_count: i64,
+}
+
+/// Guesses a document's language from its extracted text, as a Tesseract language code so
+/// it lines up with the OCR language packs.
+pub struct LanguageDetector;
+
+impl LanguageDetector {
+    /// The script with the most letters decides; Latin text is told apart by its words.
+    pub fn detect(text: &str) -> Option<&'static str> {
+        let mut letters: HashMap<&'static str, usize> = HashMap::new();
+        let mut latin = 0;
+        for ch in text.chars().filter(|ch| ch.is_alphabetic()) {
+            let code = ch as u32;
+            match SCRIPTS
+                .iter()
+                .find(|(start, end, _)| (*start..=*end).contains(&code))
+            {
+                Some((_, _, language)) => *letters.entry(language).or_default() += 1,
+                None if ch.is_ascii_alphabetic() || ('\u{C0}'..='\u{24F}').contains(&ch) => latin += 1,
+                None => {}
+            }
+        }
+
+        let script = letters
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .filter(|(_, count)| **count > latin)
+            .map(|(language, _)| *language);
+        match script {
+            Some("ara") if text.chars().any(|ch| URDU_LETTERS.contains(&ch)) => Some("urd"),
+            // Kanji alone cannot tell Japanese from Chinese; kana can.
+            Some("chi_sim") if letters.contains_key("jpn") => Some("jpn"),
+            Some(language) => Some(language),
+            None if latin > 0 => latin_language(text),
+            None => None,
+        }
+    }
+
+    /// The language of a parsed invoice: the provider's own answer when it gave one,
+    /// otherwise a guess from every extracted text value.
+    pub fn detect_parsed(parsed: &Value) -> Option<&'static str> {
+        if let Some(stated) = parsed.get(DOCUMENT_LANGUAGE).and_then(Value::as_str) {
+            if let Some(code) = OcrLanguages::code_for(stated) {
+                return Some(code);
+            }
+        }
+        let mut text = String::new();
+        collect_text(parsed, &mut text);
+        Self::detect(&text)
+    }
+
+    /// Languages detected across the workspace's files, most common first, to filter by.
+    pub fn in_use(conn: &Connection) -> Result<Vec<DocumentLanguage>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT document_language, COUNT(*) FROM files
+                 WHERE document_language IS NOT NULL
+                 GROUP BY document_language
+                 ORDER BY COUNT(*) DESC, document_language",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let code: String = row.get(0)?;
+                Ok(DocumentLanguage {
+                    name: OcrLanguages::name(&code),
+                    code,
+                    file_count: row.get(1)?,
+                })
+            })
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+}
+
+fn latin_language(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|ch: char| !ch.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut best: Option<(&'static str, usize)> = None;
+    for (language, vocabulary) in LATIN_WORDS {
+        let hits = words.iter().filter(|word| vocabulary.contains(word)).count();
+        if hits >= MIN_WORD_HITS && best.is_none_or(|(_, best_hits)| hits > best_hits) {
+            best = Some((language, hits));
+        }
+    }
+    best.map(|(language, _)| language)
+}
+
+fn collect_text(value: &Value, text: &mut String) {
+    match value {
+        Value::String(value) => {
+            text.push_str(value);
+            text.push(' ');
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_text(item, text)),
+        Value::Object(map) => map.values().for_each(|item| collect_text(item, text)),
+        _ => {}
+    }
+}
@@ -0,0 +1,113 @@
+use crate::commands::file_operations::import_file;
+use crate::db::get_connection;
+use crate::services::gemini::mime_for;
+use crate::services::import_batches::{ImportBatches, ImportSource};
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Url};
+
+/// Emitted with a [`LaunchImportResult`] after documents handed to the app were imported.
+pub const LAUNCH_IMPORT_EVENT: &str = "launch://files-imported";
+
+/// Custom scheme: `invox://import?path=/abs/path/invoice.pdf` (the `path` may repeat).
+pub const URL_SCHEME: &str = "invox";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchImportResult {
+    pub imported: Vec<String>,
+    /// Ids of files that were already in the library.
+    pub duplicates: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Imports documents the OS hands to Invox: "Open with", double-clicking an associated
+/// file, `invox://` links, or a second launch forwarded by the single-instance plugin.
+pub struct LaunchImport;
+
+impl LaunchImport {
+    /// Arguments of a launch, without the executable itself. Flags are ignored, and so are
+    /// `invox://` links, which the deep-link plugin delivers through [`Self::from_urls`].
+    pub fn from_args(app: &AppHandle, args: &[String]) {
+        let paths = args
+            .iter()
+            .filter(|arg| !arg.starts_with('-'))
+            .flat_map(|arg| match Url::parse(arg) {
+                // A single-letter scheme is a Windows drive, not a URL.
+                Ok(url) if url.scheme() == URL_SCHEME => Vec::new(),
+                Ok(url) if url.scheme().len() > 1 => paths_from_url(&url),
+                _ => vec![PathBuf::from(arg)],
+            })
+            .collect();
+        Self::import(app, paths);
+    }
+
+    pub fn from_urls(app: &AppHandle, urls: &[Url]) {
+        Self::import(app, urls.iter().flat_map(paths_from_url).collect());
+    }
+
+    /// Documents picked from the tray's "Import files…" dialog.
+    pub fn from_paths(app: &AppHandle, paths: Vec<PathBuf>) {
+        Self::import(app, paths);
+    }
+
+    /// Imports off the main thread, then brings the window forward and tells the UI.
+    fn import(app: &AppHandle, paths: Vec<PathBuf>) {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| path.is_file() && mime_for(path).is_some())
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || Self::import_paths(&app, paths));
+    }
+
+    fn import_paths(app: &AppHandle, paths: Vec<PathBuf>) {
+        let mut result = LaunchImportResult::default();
+        let conn = get_connection().ok();
+        let batch_id = conn
+            .as_ref()
+            .and_then(|conn| ImportBatches::begin(conn, ImportSource::Launch, None).ok());
+        for path in paths {
+            let display = path.to_string_lossy().into_owned();
+            match import_file(display.clone(), batch_id, None) {
+                Ok(outcome) => match outcome.split_once(':') {
+                    Some(("OK", id)) => result.imported.push(id.to_string()),
+                    Some(("DUPLICATE", id)) => result.duplicates.push(id.to_string()),
+                    _ => result.failed.push(display),
+                },
+                Err(_) => result.failed.push(display),
+            }
+        }
+        if let (Some(conn), Some(batch_id)) = (&conn, batch_id) {
+            let _ = ImportBatches::finish(conn, batch_id, false);
+        }
+
+        focus_main_window(app);
+        let _ = app.emit(LAUNCH_IMPORT_EVENT, result);
+    }
+}
+
+/// `file://` URLs and `invox://import?path=…` links; anything else yields nothing.
+fn paths_from_url(url: &Url) -> Vec<PathBuf> {
+    match url.scheme() {
+        "file" => url.to_file_path().into_iter().collect(),
+        URL_SCHEME => url
+            .query_pairs()
+            .filter(|(key, _)| key == "path")
+            .map(|(_, value)| PathBuf::from(value.into_owned()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn focus_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
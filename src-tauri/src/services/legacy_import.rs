@@ -0,0 +1,459 @@
+use crate::services::bank_reconciliation::parse_amount;
+use crate::services::parsed_invoice::{
+    BUYER_GSTIN, BUYER_NAME, DUE_DATE, GRAND_TOTAL, INVOICE_DATE, INVOICE_NUMBER, PLACE_OF_SUPPLY,
+    SELLER_GSTIN, SELLER_NAME, SUBTOTAL, TAX_TOTAL, VOUCHER_TYPE,
+};
+use crate::services::spreadsheet::{read_table, Table};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Pseudo field for the column that names the invoice document.
+const FILE_FIELD: &str = "file";
+
+const INDEX_EXTENSIONS: &[&str] = &["xlsx", "xls", "ods", "csv"];
+const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg", "tif", "tiff", "webp", "xml"];
+const NUMERIC_FIELDS: &[&str] = &[SUBTOTAL, TAX_TOTAL, GRAND_TOTAL];
+
+/// Header spellings used by spreadsheets and other invoice tools, per parsed_details key.
+/// Headers are compared after lowercasing and collapsing punctuation to single spaces.
+const COLUMN_ALIASES: &[(&str, &[&str])] = &[
+    (
+        FILE_FIELD,
+        &[
+            "file",
+            "file name",
+            "filename",
+            "document",
+            "attachment",
+            "file path",
+            "path",
+            "pdf",
+        ],
+    ),
+    (
+        SELLER_NAME,
+        &[
+            "seller",
+            "seller name",
+            "vendor",
+            "vendor name",
+            "supplier",
+            "supplier name",
+            "party",
+            "party name",
+        ],
+    ),
+    (
+        SELLER_GSTIN,
+        &[
+            "seller gstin",
+            "vendor gstin",
+            "supplier gstin",
+            "gstin",
+            "gst no",
+            "gstin uin",
+            "party gstin",
+        ],
+    ),
+    (
+        BUYER_NAME,
+        &["buyer", "buyer name", "customer", "customer name", "bill to"],
+    ),
+    (BUYER_GSTIN, &["buyer gstin", "customer gstin"]),
+    (
+        INVOICE_NUMBER,
+        &[
+            "invoice number",
+            "invoice no",
+            "invoice",
+            "inv no",
+            "bill no",
+            "bill number",
+            "voucher no",
+            "voucher number",
+            "document number",
+        ],
+    ),
+    (
+        INVOICE_DATE,
+        &[
+            "date",
+            "invoice date",
+            "bill date",
+            "voucher date",
+            "document date",
+        ],
+    ),
+    (DUE_DATE, &["due date", "payment due", "due on"]),
+    (PLACE_OF_SUPPLY, &["place of supply", "pos"]),
+    (
+        SUBTOTAL,
+        &[
+            "subtotal",
+            "sub total",
+            "taxable value",
+            "taxable amount",
+            "net amount",
+            "amount before tax",
+        ],
+    ),
+    (
+        TAX_TOTAL,
+        &[
+            "tax",
+            "tax total",
+            "tax amount",
+            "total tax",
+            "gst",
+            "gst amount",
+            "vat",
+            "vat amount",
+        ],
+    ),
+    (
+        GRAND_TOTAL,
+        &[
+            "total",
+            "grand total",
+            "invoice total",
+            "invoice amount",
+            "amount",
+            "gross amount",
+            "total amount",
+        ],
+    ),
+    (VOUCHER_TYPE, &["voucher type", "document type", "type"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyImportKind {
+    /// A folder of invoice documents plus a spreadsheet index describing them.
+    FolderWithIndex,
+    /// A CSV/Excel export from another invoice tool whose rows point at attachments.
+    ToolExport,
+}
+
+impl LegacyImportKind {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "folder_index" | "folder" => Ok(LegacyImportKind::FolderWithIndex),
+            "tool_export" | "export" => Ok(LegacyImportKind::ToolExport),
+            other => Err(format!(
+                "Unsupported legacy source \"{}\". Use folder_index or tool_export.",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+    pub column: String,
+    pub field: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    /// 1-based spreadsheet row, counting the header row.
+    pub row: usize,
+    pub reason: String,
+}
+
+/// One spreadsheet row matched to its document, with fields in `parsed_details` shape.
+pub struct LegacyRecord {
+    pub row: usize,
+    pub document: PathBuf,
+    pub fields: Map<String, Value>,
+}
+
+pub struct LegacyPlan {
+    pub index_file: Option<PathBuf>,
+    pub mapping: Vec<ColumnMapping>,
+    pub records: Vec<LegacyRecord>,
+    pub skipped: Vec<SkippedRow>,
+    /// Documents in the folder that no index row refers to; imported without details.
+    pub unindexed: Vec<PathBuf>,
+}
+
+pub struct LegacyImporter;
+
+impl LegacyImporter {
+    /// Reads the index, maps its columns and pairs every row with a document.
+    /// Nothing is written; the caller imports the plan.
+    pub fn plan(kind: LegacyImportKind, path: &Path) -> Result<LegacyPlan, String> {
+        let (index_file, base_dir, documents) = match kind {
+            LegacyImportKind::FolderWithIndex => {
+                if !path.is_dir() {
+                    return Err(format!("{} is not a folder.", path.display()));
+                }
+                let (index, documents) = scan_folder(path)?;
+                (index, path.to_path_buf(), documents)
+            }
+            LegacyImportKind::ToolExport => {
+                if !path.is_file() {
+                    return Err(format!("{} is not a file.", path.display()));
+                }
+                let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                (Some(path.to_path_buf()), base, Vec::new())
+            }
+        };
+
+        let Some(index_path) = index_file else {
+            return Ok(LegacyPlan {
+                index_file: None,
+                mapping: Vec::new(),
+                records: Vec::new(),
+                skipped: Vec::new(),
+                unindexed: documents,
+            });
+        };
+
+        let table = read_table(&index_path)?;
+        let columns = map_columns(&table);
+        if columns.iter().all(|(_, field)| *field == FILE_FIELD) {
+            return Err("None of the index columns could be matched to invoice fields.".to_string());
+        }
+        let file_column = columns
+            .iter()
+            .find(|(_, field)| *field == FILE_FIELD)
+            .map(|(index, _)| *index);
+
+        let mut records = Vec::new();
+        let mut skipped = Vec::new();
+        let mut used = HashSet::new();
+        for (offset, row) in table.rows.iter().enumerate() {
+            let row_number = offset + 2;
+            let fields = row_fields(row, &columns);
+
+            let document = match file_column
+                .and_then(|index| row.get(index))
+                .filter(|value| !value.is_empty())
+            {
+                Some(reference) => resolve_reference(&base_dir, reference, &documents),
+                None => fields
+                    .get(INVOICE_NUMBER)
+                    .and_then(Value::as_str)
+                    .and_then(|number| find_by_invoice_number(number, &documents)),
+            };
+
+            let Some(document) = document else {
+                skipped.push(SkippedRow {
+                    row: row_number,
+                    reason: "No matching invoice document was found.".to_string(),
+                });
+                continue;
+            };
+            if !used.insert(document.clone()) {
+                skipped.push(SkippedRow {
+                    row: row_number,
+                    reason: format!(
+                        "{} is already described by an earlier row.",
+                        display_name(&document)
+                    ),
+                });
+                continue;
+            }
+
+            records.push(LegacyRecord {
+                row: row_number,
+                document,
+                fields,
+            });
+        }
+
+        let unindexed = documents
+            .into_iter()
+            .filter(|document| !used.contains(document))
+            .collect();
+        let mapping = columns
+            .iter()
+            .map(|(index, field)| ColumnMapping {
+                column: table.headers[*index].clone(),
+                field: field.to_string(),
+            })
+            .collect();
+
+        Ok(LegacyPlan {
+            index_file: Some(index_path),
+            mapping,
+            records,
+            skipped,
+            unindexed,
+        })
+    }
+}
+
+pub fn display_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn extension_in(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Splits a folder into its spreadsheet index (preferring a file named like "index")
+/// and the invoice documents next to it.
+fn scan_folder(folder: &Path) -> Result<(Option<PathBuf>, Vec<PathBuf>), String> {
+    let mut spreadsheets = Vec::new();
+    let mut documents = Vec::new();
+    for entry in fs::read_dir(folder).map_err(|error| error.to_string())? {
+        let path = entry.map_err(|error| error.to_string())?.path();
+        if !path.is_file() {
+            continue;
+        }
+        if extension_in(&path, INDEX_EXTENSIONS) {
+            spreadsheets.push(path);
+        } else if extension_in(&path, DOCUMENT_EXTENSIONS) {
+            documents.push(path);
+        }
+    }
+    documents.sort();
+
+    let index = match spreadsheets.len() {
+        0 => None,
+        1 => spreadsheets.pop(),
+        _ => {
+            let named: Vec<PathBuf> = spreadsheets
+                .iter()
+                .filter(|path| display_name(path).to_lowercase().contains("index"))
+                .cloned()
+                .collect();
+            match named.as_slice() {
+                [index] => Some(index.clone()),
+                _ => {
+                    return Err(format!(
+                        "Found several spreadsheets ({}); name the index file \"index\".",
+                        spreadsheets
+                            .iter()
+                            .map(|path| display_name(path))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok((index, documents))
+}
+
+fn normalize_header(header: &str) -> String {
+    header
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pairs column indexes with parsed_details keys; each key is taken by its first matching column.
+fn map_columns(table: &Table) -> Vec<(usize, &'static str)> {
+    let mut mapped: Vec<(usize, &'static str)> = Vec::new();
+    for (index, header) in table.headers.iter().enumerate() {
+        let header = normalize_header(header);
+        let field = COLUMN_ALIASES
+            .iter()
+            .find(|(field, aliases)| {
+                aliases.contains(&header.as_str()) && !mapped.iter().any(|(_, taken)| taken == field)
+            })
+            .map(|(field, _)| *field);
+        if let Some(field) = field {
+            mapped.push((index, field));
+        }
+    }
+    mapped
+}
+
+fn row_fields(row: &[String], columns: &[(usize, &'static str)]) -> Map<String, Value> {
+    let mut fields = Map::new();
+    for (index, field) in columns {
+        if *field == FILE_FIELD {
+            continue;
+        }
+        let Some(raw) = row
+            .get(*index)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        else {
+            continue;
+        };
+        let value = if NUMERIC_FIELDS.contains(field) {
+            match parse_amount(raw).and_then(serde_json::Number::from_f64) {
+                Some(number) => Value::Number(number),
+                None => continue,
+            }
+        } else {
+            Value::String(raw.to_string())
+        };
+        fields.insert(field.to_string(), value);
+    }
+    fields
+}
+
+/// Accepts absolute paths, paths relative to the index, and bare names with or without extension.
+fn resolve_reference(base_dir: &Path, reference: &str, documents: &[PathBuf]) -> Option<PathBuf> {
+    let candidate = Path::new(reference);
+    let direct = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        base_dir.join(candidate)
+    };
+    if direct.is_file() {
+        return Some(direct);
+    }
+
+    let wanted = reference.trim().to_lowercase();
+    documents
+        .iter()
+        .find(|document| {
+            let name = display_name(document).to_lowercase();
+            let stem = document
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_lowercase();
+            name == wanted || stem == wanted
+        })
+        .cloned()
+}
+
+/// Falls back to files named after their invoice number, e.g. `Acme_INV-0042.pdf`.
+fn find_by_invoice_number(number: &str, documents: &[PathBuf]) -> Option<PathBuf> {
+    let key: String = number
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    if key.is_empty() {
+        return None;
+    }
+    let matches: Vec<&PathBuf> = documents
+        .iter()
+        .filter(|document| {
+            let stem: String = document
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase();
+            stem.contains(&key)
+        })
+        .collect();
+    match matches.as_slice() {
+        [single] => Some((*single).clone()),
+        _ => None,
+    }
+}
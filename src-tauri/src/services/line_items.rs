@@ -0,0 +1,56 @@
+use crate::services::parsed_invoice::ParsedInvoice;
+use rusqlite::{params, Connection};
+
+pub struct LineItemStore;
+
+impl LineItemStore {
+    /// Replaces the normalized line items of a file with the items in `parsed_details`.
+    pub fn replace_for_file(
+        conn: &Connection,
+        file_id: &str,
+        parsed_details: Option<&str>,
+    ) -> Result<usize, String> {
+        conn.execute(
+            "DELETE FROM invoice_line_items WHERE file_id = ?1",
+            params![file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        let items = parsed_details
+            .and_then(ParsedInvoice::from_json)
+            .map(|invoice| invoice.line_items())
+            .unwrap_or_default();
+
+        let mut stmt = conn
+            .prepare(
+                "INSERT INTO invoice_line_items (
+                   file_id, position, description, name, hsn_sac, quantity, unit, rate, amount,
+                   cgst, sgst, cgst_rate, sgst_rate, igst, igst_rate
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            )
+            .map_err(|error| error.to_string())?;
+
+        for (position, item) in items.iter().enumerate() {
+            stmt.execute(params![
+                file_id,
+                position as i64,
+                item.description,
+                item.name,
+                item.hsn_sac,
+                item.quantity,
+                item.unit,
+                item.rate,
+                item.amount,
+                item.cgst,
+                item.sgst,
+                item.cgst_rate,
+                item.sgst_rate,
+                item.igst,
+                item.igst_rate,
+            ])
+            .map_err(|error| error.to_string())?;
+        }
+
+        Ok(items.len())
+    }
+}
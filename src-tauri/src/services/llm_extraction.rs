@@ -0,0 +1,398 @@
+use crate::commands::file_operations::{update_file_status, FileStatus};
+use crate::commands::reprocess_operations::save_extraction_result;
+use crate::services::connectivity::Connectivity;
+use crate::services::gemini::mime_for;
+use crate::services::llm_provider::{
+    ExtractionRequest, LlmError, LlmProvider, LlmProviderKind, LlmProviders, LlmResponse,
+};
+use crate::services::processing_usage::{ProcessingUsage, UsageRecord};
+use crate::services::field_corrections::FieldCorrections;
+use crate::services::ocr_languages::OcrLanguages;
+use crate::services::parse_cancellation::{CancelToken, ParseCancellation, CANCELLED};
+use crate::services::parse_metrics::{ParseMetrics, ParseOutcome, ParseRun, StageClock};
+use crate::services::prompt_templates::{PromptKind, PromptTemplates};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF_SECS: u64 = 2;
+const MAX_BACKOFF_SECS: u64 = 30;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmRequestLog {
+    pub id: i64,
+    pub file_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub attempt: u32,
+    pub request: Value,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmParseResult {
+    pub file_id: String,
+    pub provider: LlmProviderKind,
+    pub model: String,
+    /// `applied`, `unchanged` or `conflict`, as from `save_extraction_result`.
+    pub status: &'static str,
+    pub attempts: u32,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+/// Runs an extraction against a provider, retrying rate limits and server errors with
+/// exponential backoff. Every attempt is logged to `llm_requests`; the document itself is
+/// not stored, only its name, type and size. A parse can be cancelled while it waits on the
+/// provider or between attempts.
+pub struct LlmExtraction;
+
+impl LlmExtraction {
+    /// Returns the response and how many attempts it took.
+    pub fn run(
+        conn: &Connection,
+        file_id: Option<&str>,
+        provider: &Arc<dyn LlmProvider>,
+        request: &ExtractionRequest,
+        cancel: &CancelToken,
+    ) -> Result<(LlmResponse, u32), String> {
+        if !provider.supports(request.mime_type) {
+            return Err(format!(
+                "{} cannot read {} files.",
+                provider.kind().label(),
+                request.mime_type
+            ));
+        }
+        let summary = json!({
+            "provider": provider.kind().as_str(),
+            "model": provider.model(),
+            "fileName": request.file_name,
+            "mimeType": request.mime_type,
+            "documentBytes": request.document.len(),
+            "systemInstruction": request.system_instruction,
+            "prompt": request.prompt,
+            "responseSchema": request.response_schema,
+        })
+        .to_string();
+
+        let mut attempt = 1;
+        loop {
+            let log_id = start(conn, file_id, provider.as_ref(), attempt, &summary)?;
+            match send(provider, request, cancel) {
+                Ok(response) if response.output.get("_raw").is_some() => {
+                    let message = "The model did not return valid JSON.";
+                    finish(
+                        conn,
+                        log_id,
+                        Some(&response.raw),
+                        Some(message),
+                        response.prompt_tokens,
+                        response.output_tokens,
+                    )?;
+                    return Err(message.to_string());
+                }
+                Ok(response) => {
+                    finish(
+                        conn,
+                        log_id,
+                        Some(&response.raw),
+                        None,
+                        response.prompt_tokens,
+                        response.output_tokens,
+                    )?;
+                    return Ok((response, attempt));
+                }
+                Err(error) => {
+                    finish(conn, log_id, None, Some(&error.message), 0, 0)?;
+                    if !error.retryable || attempt >= MAX_ATTEMPTS {
+                        return Err(error.message);
+                    }
+                    if cancel.sleep(backoff(attempt, error.retry_after)) {
+                        return Err(CANCELLED.to_string());
+                    }
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Extracts a stored file with `provider`, or the configured one, using the active
+    /// prompts, and saves the result through `save_extraction_result`. Failures are stored on
+    /// the file the same way the frontend stores them; a cancelled parse leaves it
+    /// Unprocessed and saves nothing.
+    pub fn parse_file(
+        conn: &Connection,
+        file_id: &str,
+        provider: Option<LlmProviderKind>,
+        response_schema: Option<&Value>,
+    ) -> Result<LlmParseResult, String> {
+        let kind = match provider {
+            Some(kind) => kind,
+            None => LlmProviders::configured(conn)?,
+        };
+        let client = LlmProviders::connect(conn, kind)?;
+        let guard = ParseCancellation::register(file_id);
+        let cancel = guard.token();
+        let mut clock = StageClock::start();
+
+        let active = |prompt_kind: PromptKind| {
+            PromptTemplates::active(conn, prompt_kind)?
+                .map(|template| template.content)
+                .ok_or_else(|| format!("No active {} is stored yet.", prompt_kind.as_str()))
+        };
+        let system_instruction = active(PromptKind::SystemInstruction)?;
+        let mut prompt = active(PromptKind::UserPrompt)?;
+        if let Some(hint) = OcrLanguages::extraction_hint(conn, file_id)? {
+            prompt = format!("{}\n\n{}", prompt, hint);
+        }
+
+        let (file_name, stored_path): (String, String) = conn
+            .query_row(
+                "SELECT file_name, stored_path FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let path = Path::new(&stored_path);
+        let mime_type =
+            mime_for(path).ok_or_else(|| "This file type cannot be sent for extraction.".to_string())?;
+        let document = std::fs::read(path).map_err(|error| error.to_string())?;
+        cancel.check()?;
+
+        update_file_status(file_id.to_string(), FileStatus::Processing)?;
+        let request = ExtractionRequest {
+            system_instruction: &system_instruction,
+            prompt: &prompt,
+            document: &document,
+            mime_type,
+            file_name: &file_name,
+            response_schema,
+            temperature: LlmProviders::temperature(conn)?,
+        };
+        let document_ms = clock.lap();
+        let last_request = last_request_id(conn)?;
+        let run = Self::run(conn, Some(file_id), &client, &request, cancel);
+        let model_ms = clock.lap();
+        let outcome = run.and_then(|(response, attempts)| {
+            let cost = ProcessingUsage::record(
+                conn,
+                &UsageRecord {
+                    file_id: Some(file_id.to_string()),
+                    model: client.model().to_string(),
+                    profile: None,
+                    prompt_tokens: response.prompt_tokens,
+                    output_tokens: response.output_tokens,
+                },
+            )?;
+            cancel.check()?;
+            update_file_status(file_id.to_string(), FileStatus::Processed)?;
+            let saved = save_extraction_result(file_id.to_string(), response.output.to_string())?;
+            if saved.status != "conflict" {
+                FieldCorrections::set_extracted_by(
+                    conn,
+                    file_id,
+                    &format!("{}:{}", kind.as_str(), client.model()),
+                )?;
+            }
+            Ok(LlmParseResult {
+                file_id: file_id.to_string(),
+                provider: kind,
+                model: client.model().to_string(),
+                status: saved.status,
+                attempts,
+                prompt_tokens: response.prompt_tokens,
+                output_tokens: response.output_tokens,
+                cost,
+            })
+        });
+        let save_ms = clock.lap();
+
+        let attempts = match &outcome {
+            Ok(result) => result.attempts,
+            Err(_) => attempts_since(conn, file_id, last_request)?,
+        };
+        let record_metric = |outcome: ParseOutcome, error: Option<&str>| {
+            ParseMetrics::record(
+                conn,
+                &ParseRun {
+                    file_id,
+                    provider: kind.as_str(),
+                    model: Some(client.model()),
+                    attempts,
+                    outcome,
+                    error,
+                    document_ms,
+                    model_ms,
+                    save_ms,
+                    total_ms: clock.total(),
+                },
+            )
+        };
+        match outcome {
+            Ok(result) => {
+                record_metric(ParseOutcome::from_saved(result.status), None)?;
+                Ok(result)
+            }
+            Err(error) if ParseCancellation::is_cancelled_error(&error) => {
+                record_metric(ParseOutcome::Cancelled, None)?;
+                update_file_status(file_id.to_string(), FileStatus::Unprocessed)?;
+                Err(error)
+            }
+            // Same as the frontend: retried by the offline queue once the network is back.
+            Err(error) if !kind.is_local() && !Connectivity::is_online() => {
+                record_metric(ParseOutcome::WaitingForNetwork, Some(&error))?;
+                update_file_status(file_id.to_string(), FileStatus::WaitingForNetwork)?;
+                Err(error)
+            }
+            Err(error) => {
+                record_metric(ParseOutcome::Failed, Some(&error))?;
+                update_file_status(file_id.to_string(), FileStatus::Failed)?;
+                conn.execute(
+                    "UPDATE files SET parsed_details = ?1 WHERE id = ?2",
+                    params![serde_json::json!({ "error": error }).to_string(), file_id],
+                )
+                .map_err(|error| error.to_string())?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Logged attempts for a file, newest first.
+    pub fn history(conn: &Connection, file_id: &str) -> Result<Vec<LlmRequestLog>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_id, provider, model, attempt, request, response, error,
+                        prompt_tokens, output_tokens, started_at, finished_at
+                 FROM llm_requests
+                 WHERE file_id = ?1
+                 ORDER BY id DESC",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params![file_id], |row| {
+                let request: String = row.get(5)?;
+                Ok(LlmRequestLog {
+                    id: row.get(0)?,
+                    file_id: row.get(1)?,
+                    provider: row.get(2)?,
+                    model: row.get(3)?,
+                    attempt: row.get(4)?,
+                    request: serde_json::from_str(&request).unwrap_or(Value::Null),
+                    response: row.get(6)?,
+                    error: row.get(7)?,
+                    prompt_tokens: row.get(8)?,
+                    output_tokens: row.get(9)?,
+                    started_at: row.get(10)?,
+                    finished_at: row.get(11)?,
+                })
+            })
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// Sends one attempt from its own thread, so a cancelled parse stops waiting on the provider
+/// right away.
+fn send(
+    provider: &Arc<dyn LlmProvider>,
+    request: &ExtractionRequest,
+    cancel: &CancelToken,
+) -> Result<LlmResponse, LlmError> {
+    let provider = Arc::clone(provider);
+    let system_instruction = request.system_instruction.to_string();
+    let prompt = request.prompt.to_string();
+    let document = request.document.to_vec();
+    let mime_type = request.mime_type.to_string();
+    let file_name = request.file_name.to_string();
+    let response_schema = request.response_schema.cloned();
+    let temperature = request.temperature;
+    cancel
+        .run(move || {
+            provider.extract(&ExtractionRequest {
+                system_instruction: &system_instruction,
+                prompt: &prompt,
+                document: &document,
+                mime_type: &mime_type,
+                file_name: &file_name,
+                response_schema: response_schema.as_ref(),
+                temperature,
+            })
+        })
+        .map_err(LlmError::fatal)?
+}
+
+fn start(
+    conn: &Connection,
+    file_id: Option<&str>,
+    provider: &dyn LlmProvider,
+    attempt: u32,
+    summary: &str,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO llm_requests (file_id, provider, model, attempt, request) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            file_id,
+            provider.kind().as_str(),
+            provider.model(),
+            attempt,
+            summary
+        ],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn finish(
+    conn: &Connection,
+    log_id: i64,
+    response: Option<&str>,
+    error: Option<&str>,
+    prompt_tokens: i64,
+    output_tokens: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE llm_requests
+         SET response = ?2, error = ?3, prompt_tokens = ?4, output_tokens = ?5, finished_at = CURRENT_TIMESTAMP
+         WHERE id = ?1",
+        params![log_id, response, error, prompt_tokens, output_tokens],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
+fn last_request_id(conn: &Connection) -> Result<i64, String> {
+    conn.query_row("SELECT COALESCE(MAX(id), 0) FROM llm_requests", [], |row| row.get(0))
+        .map_err(|error| error.to_string())
+}
+
+/// Attempts logged for a file after `last_request`, for parses that gave up.
+fn attempts_since(conn: &Connection, file_id: &str, last_request: i64) -> Result<u32, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM llm_requests WHERE file_id = ?1 AND id > ?2",
+        params![file_id, last_request],
+        |row| row.get(0),
+    )
+    .map_err(|error| error.to_string())
+}
+
+/// The provider's Retry-After when given, otherwise 2s, 4s, 8s… capped at 30s.
+fn backoff(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| Duration::from_secs(BASE_BACKOFF_SECS << (attempt - 1).min(4)))
+        .min(Duration::from_secs(MAX_BACKOFF_SECS))
+}
@@ -0,0 +1,333 @@
+use crate::services::anthropic::AnthropicClient;
+use crate::services::gemini::GeminiClient;
+use crate::services::ollama::{self, OllamaClient};
+use crate::services::openai::OpenAiClient;
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+const KEYRING_SERVICE: &str = "invox-ai";
+const PROVIDER_KEY: &str = "llm.provider";
+const LOCAL_ENDPOINT_KEY: &str = "llm.ollama.endpoint";
+const TEMPERATURE_KEY: &str = "llm.temperature";
+/// Providers accept 0 to 2; higher values only make extraction less repeatable.
+const MAX_TEMPERATURE: f64 = 2.0;
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LlmProviderKind {
+    #[serde(rename = "gemini")]
+    Gemini,
+    #[serde(rename = "openai")]
+    OpenAi,
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    #[serde(rename = "ollama")]
+    Ollama,
+}
+
+impl LlmProviderKind {
+    pub const ALL: [LlmProviderKind; 4] = [
+        LlmProviderKind::Gemini,
+        LlmProviderKind::OpenAi,
+        LlmProviderKind::Anthropic,
+        LlmProviderKind::Ollama,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Gemini => "gemini",
+            LlmProviderKind::OpenAi => "openai",
+            LlmProviderKind::Anthropic => "anthropic",
+            LlmProviderKind::Ollama => "ollama",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gemini" => Ok(LlmProviderKind::Gemini),
+            "openai" => Ok(LlmProviderKind::OpenAi),
+            "anthropic" => Ok(LlmProviderKind::Anthropic),
+            "ollama" => Ok(LlmProviderKind::Ollama),
+            other => Err(format!("Unsupported extraction provider: {}", other)),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Gemini => "Gemini",
+            LlmProviderKind::OpenAi => "OpenAI",
+            LlmProviderKind::Anthropic => "Anthropic",
+            LlmProviderKind::Ollama => "Local model (Ollama)",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Gemini => "gemini-2.5-flash",
+            LlmProviderKind::OpenAi => "gpt-4.1-mini",
+            LlmProviderKind::Anthropic => "claude-sonnet-4-5",
+            LlmProviderKind::Ollama => "qwen2.5vl:7b",
+        }
+    }
+
+    /// Model semantic search embeds invoice text with; `None` when the provider has no
+    /// embeddings API.
+    pub fn embedding_model(&self) -> Option<&'static str> {
+        match self {
+            LlmProviderKind::Gemini => Some("gemini-embedding-001"),
+            LlmProviderKind::OpenAi => Some("text-embedding-3-small"),
+            LlmProviderKind::Anthropic => None,
+            LlmProviderKind::Ollama => Some("nomic-embed-text"),
+        }
+    }
+
+    /// Runs on this machine: needs no API key and keeps documents off the network.
+    pub fn is_local(&self) -> bool {
+        matches!(self, LlmProviderKind::Ollama)
+    }
+
+    fn model_key(&self) -> String {
+        format!("llm.{}.model", self.as_str())
+    }
+}
+
+pub struct ExtractionRequest<'a> {
+    pub system_instruction: &'a str,
+    pub prompt: &'a str,
+    pub document: &'a [u8],
+    pub mime_type: &'a str,
+    pub file_name: &'a str,
+    pub response_schema: Option<&'a Value>,
+    pub temperature: f64,
+}
+
+#[derive(Debug)]
+pub struct LlmResponse {
+    pub output: Value,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    /// Response body as received, kept in the request log.
+    pub raw: String,
+}
+
+#[derive(Debug)]
+pub struct LlmError {
+    pub message: String,
+    /// Rate limits, server errors and dropped connections; worth another attempt.
+    pub retryable: bool,
+    /// How long the provider asked us to wait before retrying.
+    pub retry_after: Option<Duration>,
+}
+
+impl LlmError {
+    pub fn fatal(message: impl Into<String>) -> Self {
+        LlmError {
+            message: message.into(),
+            retryable: false,
+            retry_after: None,
+        }
+    }
+}
+
+impl From<LlmError> for String {
+    fn from(error: LlmError) -> Self {
+        error.message
+    }
+}
+
+/// A model API that turns a document into the invoice JSON. Implementations send the
+/// document inline with the system instruction and prompt, asking for JSON output.
+pub trait LlmProvider: Send + Sync {
+    fn kind(&self) -> LlmProviderKind;
+
+    fn model(&self) -> &str;
+
+    /// Checked before anything is sent, so unsupported documents fail without a request.
+    fn supports(&self, mime_type: &str) -> bool;
+
+    fn extract(&self, request: &ExtractionRequest) -> Result<LlmResponse, LlmError>;
+
+    /// One vector per text, in order, from the provider's embedding model.
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let _ = texts;
+        Err(LlmError::fatal(format!(
+            "{} cannot create embeddings.",
+            self.kind().label()
+        )))
+    }
+}
+
+/// Which provider extracts, with which model, and the API keys, which live in the OS
+/// keyring rather than the renderer.
+pub struct LlmProviders;
+
+impl LlmProviders {
+    pub fn configured(conn: &Connection) -> Result<LlmProviderKind, String> {
+        match Settings::get(conn, PROVIDER_KEY)? {
+            Some(value) => LlmProviderKind::parse(&value),
+            None => Ok(LlmProviderKind::Gemini),
+        }
+    }
+
+    pub fn set_configured(conn: &Connection, kind: LlmProviderKind) -> Result<(), String> {
+        Settings::set(conn, PROVIDER_KEY, kind.as_str())
+    }
+
+    pub fn model(conn: &Connection, kind: LlmProviderKind) -> Result<String, String> {
+        Ok(Settings::get(conn, &kind.model_key())?
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or_else(|| kind.default_model().to_string()))
+    }
+
+    /// `None` goes back to the provider's default model.
+    pub fn set_model(conn: &Connection, kind: LlmProviderKind, model: Option<&str>) -> Result<(), String> {
+        match model.map(str::trim).filter(|model| !model.is_empty()) {
+            Some(model) => Settings::set(conn, &kind.model_key(), model),
+            None => Settings::remove(conn, &kind.model_key()),
+        }
+    }
+
+    pub fn has_key(kind: LlmProviderKind) -> Result<bool, String> {
+        Ok(Self::key(kind)?.is_some())
+    }
+
+    pub fn set_key(kind: LlmProviderKind, api_key: &str) -> Result<(), String> {
+        if kind.is_local() {
+            return Err(format!("{} needs no API key.", kind.label()));
+        }
+        if api_key.trim().is_empty() {
+            return Err(format!("Enter the {} API key.", kind.label()));
+        }
+        keyring_entry(kind)?
+            .set_password(api_key.trim())
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn clear_key(kind: LlmProviderKind) -> Result<(), String> {
+        match keyring_entry(kind)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn local_endpoint(conn: &Connection) -> Result<String, String> {
+        Ok(Settings::get(conn, LOCAL_ENDPOINT_KEY)?
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .unwrap_or_else(|| ollama::DEFAULT_ENDPOINT.to_string()))
+    }
+
+    /// `None` goes back to Ollama's default port on localhost.
+    pub fn set_local_endpoint(conn: &Connection, endpoint: Option<&str>) -> Result<(), String> {
+        match endpoint.map(str::trim).filter(|endpoint| !endpoint.is_empty()) {
+            Some(endpoint) => Settings::set(conn, LOCAL_ENDPOINT_KEY, &ollama::normalize_endpoint(endpoint)?),
+            None => Settings::remove(conn, LOCAL_ENDPOINT_KEY),
+        }
+    }
+
+    /// Sampling temperature sent with every extraction request; 0 unless changed.
+    pub fn temperature(conn: &Connection) -> Result<f64, String> {
+        Ok(Settings::get(conn, TEMPERATURE_KEY)?
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| (0.0..=MAX_TEMPERATURE).contains(value))
+            .unwrap_or(0.0))
+    }
+
+    /// `None` goes back to 0, the most repeatable output.
+    pub fn set_temperature(conn: &Connection, temperature: Option<f64>) -> Result<(), String> {
+        match temperature {
+            Some(value) if !(0.0..=MAX_TEMPERATURE).contains(&value) => {
+                Err(format!("Temperature must be between 0 and {}.", MAX_TEMPERATURE))
+            }
+            Some(value) => Settings::set(conn, TEMPERATURE_KEY, &value.to_string()),
+            None => Settings::remove(conn, TEMPERATURE_KEY),
+        }
+    }
+
+    /// A client for `kind` with its stored key, or local endpoint, and configured model.
+    pub fn connect(conn: &Connection, kind: LlmProviderKind) -> Result<Arc<dyn LlmProvider>, String> {
+        let model = Self::model(conn, kind)?;
+        let api_key = || Self::key(kind)?.ok_or_else(|| format!("No {} API key is saved.", kind.label()));
+        Ok(match kind {
+            LlmProviderKind::Gemini => Arc::new(GeminiClient::new(&api_key()?, &model)?),
+            LlmProviderKind::OpenAi => Arc::new(OpenAiClient::new(&api_key()?, &model)?),
+            LlmProviderKind::Anthropic => Arc::new(AnthropicClient::new(&api_key()?, &model)?),
+            LlmProviderKind::Ollama => Arc::new(OllamaClient::new(&Self::local_endpoint(conn)?, &model)?),
+        })
+    }
+
+    fn key(kind: LlmProviderKind) -> Result<Option<String>, String> {
+        match keyring_entry(kind)?.get_password() {
+            Ok(key) => Ok(Some(key)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+fn keyring_entry(kind: LlmProviderKind) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("llm-{}", kind.as_str())).map_err(|error| error.to_string())
+}
+
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+}
+
+/// Sends a JSON body and returns the parsed response with its raw text. HTTP 429 and 5xx
+/// responses and transport failures are retryable; other statuses are not.
+pub fn post_json(request: ureq::Request, body: &Value) -> Result<(Value, String), LlmError> {
+    match request
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+    {
+        Ok(response) => {
+            let raw = response.into_string().map_err(|error| LlmError {
+                message: error.to_string(),
+                retryable: true,
+                retry_after: None,
+            })?;
+            let value = serde_json::from_str(&raw)
+                .map_err(|error| LlmError::fatal(format!("Unreadable response: {}", error)))?;
+            Ok((value, raw))
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            let retry_after = response
+                .header("retry-after")
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let body = response.into_string().unwrap_or_default();
+            let detail = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| value.pointer("/error/message")?.as_str().map(str::to_string))
+                .unwrap_or(body);
+            Err(LlmError {
+                message: format!("HTTP {}: {}", status, detail.trim()),
+                retryable: status == 429 || status >= 500,
+                retry_after,
+            })
+        }
+        Err(error) => Err(LlmError {
+            message: error.to_string(),
+            retryable: true,
+            retry_after: None,
+        }),
+    }
+}
+
+/// Reads the model's text as JSON, allowing for a Markdown code fence around it. Text that
+/// is not JSON comes back as `{"_raw": text}`.
+pub fn parse_output(text: &str) -> Value {
+    let trimmed = text.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|inner| inner.strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    serde_json::from_str(unfenced).unwrap_or(json!({ "_raw": text }))
+}
@@ -0,0 +1,173 @@
+use crate::services::log_writer::{log_file_path, LogWriter};
+use crate::services::report_period::ReportPeriod;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+const TOP_CONTEXTS: usize = 5;
+const RECENT_CRITICAL: usize = 10;
+/// Context used for entries logged without one.
+const NO_CONTEXT: &str = "(none)";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyLogCounts {
+    pub date: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextCount {
+    pub context: String,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntrySummary {
+    pub timestamp: String,
+    pub level: String,
+    pub context: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStatisticsReport {
+    pub start: String,
+    pub end: String,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Every day of the period, oldest first, including days without entries.
+    pub days: Vec<DailyLogCounts>,
+    pub top_error_contexts: Vec<ContextCount>,
+    /// Latest error entries, newest first.
+    pub recent_critical: Vec<LogEntrySummary>,
+}
+
+/// Error and warning counts from `invox.log`, for the Settings screen.
+pub struct LogStatistics;
+
+impl LogStatistics {
+    /// `period` is `Nd` for the last N days (e.g. `7d`) or anything `ReportPeriod` accepts.
+    pub fn collect(period: &str) -> Result<LogStatisticsReport, String> {
+        let (start, end) = resolve(period)?;
+        let mut days: BTreeMap<NaiveDate, DailyLogCounts> = BTreeMap::new();
+        let mut day = start;
+        while day <= end {
+            days.insert(
+                day,
+                DailyLogCounts {
+                    date: day.to_string(),
+                    errors: 0,
+                    warnings: 0,
+                },
+            );
+            day += Duration::days(1);
+        }
+
+        // Count lines still waiting in the writer too.
+        LogWriter::flush();
+        let path = log_file_path()?;
+        let mut contexts: HashMap<String, usize> = HashMap::new();
+        let mut critical = Vec::new();
+        if path.exists() {
+            let file = File::open(&path).map_err(|error| error.to_string())?;
+            for line in BufReader::new(file).lines() {
+                let Some(entry) = line.ok().as_deref().and_then(parse_line) else {
+                    continue;
+                };
+                let Some(counts) = entry.date.and_then(|date| days.get_mut(&date)) else {
+                    continue;
+                };
+                match entry.summary.level.as_str() {
+                    "ERROR" | "CRITICAL" | "FATAL" => {
+                        counts.errors += 1;
+                        let context = entry
+                            .summary
+                            .context
+                            .clone()
+                            .unwrap_or_else(|| NO_CONTEXT.to_string());
+                        *contexts.entry(context).or_default() += 1;
+                        critical.push(entry.summary);
+                    }
+                    "WARN" | "WARNING" => counts.warnings += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut top_error_contexts: Vec<ContextCount> = contexts
+            .into_iter()
+            .map(|(context, errors)| ContextCount { context, errors })
+            .collect();
+        top_error_contexts.sort_by(|a, b| b.errors.cmp(&a.errors).then_with(|| a.context.cmp(&b.context)));
+        top_error_contexts.truncate(TOP_CONTEXTS);
+        let recent_critical = critical.into_iter().rev().take(RECENT_CRITICAL).collect();
+
+        let days: Vec<DailyLogCounts> = days.into_values().collect();
+        Ok(LogStatisticsReport {
+            start: start.to_string(),
+            end: end.to_string(),
+            errors: days.iter().map(|day| day.errors).sum(),
+            warnings: days.iter().map(|day| day.warnings).sum(),
+            days,
+            top_error_contexts,
+            recent_critical,
+        })
+    }
+}
+
+struct ParsedLine {
+    /// Local date of the entry.
+    date: Option<NaiveDate>,
+    summary: LogEntrySummary,
+}
+
+/// Parses `<rfc3339> [LEVEL] (context) message :: metadata`, the format `append_log_entry` writes.
+fn parse_line(line: &str) -> Option<ParsedLine> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    let rest = rest.strip_prefix('[')?;
+    let (level, rest) = rest.split_once(']')?;
+    let rest = rest.trim_start();
+    let (context, rest) = match rest.strip_prefix('(').and_then(|rest| rest.split_once(") ")) {
+        Some((context, rest)) => (Some(context.to_string()), rest),
+        None => (None, rest),
+    };
+    let message = rest.split_once(" :: ").map_or(rest, |(message, _)| message);
+
+    Some(ParsedLine {
+        date: DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Local).date_naive()),
+        summary: LogEntrySummary {
+            timestamp: timestamp.to_string(),
+            level: level.to_string(),
+            context,
+            message: message.to_string(),
+        },
+    })
+}
+
+fn resolve(period: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let trimmed = period.trim();
+    if let Some(days) = trimmed
+        .strip_suffix('d')
+        .and_then(|days| days.parse::<i64>().ok())
+    {
+        if !(1..=366).contains(&days) {
+            return Err("Use between 1d and 366d.".to_string());
+        }
+        let today = Local::now().date_naive();
+        return Ok((today - Duration::days(days - 1), today));
+    }
+    let period = ReportPeriod::parse(trimmed)?;
+    if (period.end - period.start).num_days() > 366 {
+        return Err("Log statistics cover at most a year.".to_string());
+    }
+    Ok((period.start, period.end))
+}
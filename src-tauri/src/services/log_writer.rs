@@ -0,0 +1,146 @@
+use crate::db::storage_dir;
+use chrono::Utc;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lines waiting to be written before new ones are dropped.
+const QUEUE_CAPACITY: usize = 4096;
+/// Most lines written per batch before the file is flushed.
+const BATCH_SIZE: usize = 256;
+/// How long `flush` waits for the writer, including for room in a full queue, before giving up.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+enum LogMessage {
+    Line(String),
+    Flush(SyncSender<()>),
+}
+
+struct LogQueue {
+    sender: SyncSender<LogMessage>,
+    dropped: AtomicUsize,
+}
+
+/// Only set once the log file is open, so a failed open is retried by the next line.
+static QUEUE: OnceLock<LogQueue> = OnceLock::new();
+
+pub fn log_file_path() -> Result<PathBuf, String> {
+    let storage = storage_dir().map_err(|error| error.to_string())?;
+    let app_dir = storage
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .ok_or_else(|| "Unable to determine application directory for logging.".to_string())?;
+
+    let log_dir = app_dir.join("logs");
+    fs::create_dir_all(&log_dir).map_err(|error| error.to_string())?;
+
+    Ok(log_dir.join("invox.log"))
+}
+
+/// Appends log lines from a background thread so bursts of UI logging never wait on disk.
+/// Lines are queued on a bounded channel and written in batches; when the queue is full
+/// lines are dropped and the count is written with the next batch.
+pub struct LogWriter;
+
+impl LogWriter {
+    pub fn append(line: String) -> Result<(), String> {
+        let queue = Self::queue()?;
+        match queue.sender.try_send(LogMessage::Line(line)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                queue.dropped.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err("The log writer has stopped.".to_string()),
+        }
+    }
+
+    /// Waits until every queued line is on disk, for at most `FLUSH_TIMEOUT` so a stuck
+    /// writer cannot hang the caller. Called on exit and from the panic hook.
+    pub fn flush() {
+        let Some(queue) = QUEUE.get() else {
+            return;
+        };
+        let deadline = Instant::now() + FLUSH_TIMEOUT;
+        let (done, wait) = mpsc::sync_channel(1);
+        let mut message = LogMessage::Flush(done);
+        loop {
+            match queue.sender.try_send(message) {
+                Ok(()) => break,
+                Err(TrySendError::Full(returned)) if Instant::now() < deadline => {
+                    message = returned;
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(_) => return,
+            }
+        }
+        let _ = wait.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+
+    fn queue() -> Result<&'static LogQueue, String> {
+        if let Some(queue) = QUEUE.get() {
+            return Ok(queue);
+        }
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file_path()?)
+            .map_err(|error| format!("Unable to open the log file: {}", error))?;
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        thread::Builder::new()
+            .name("invox-log-writer".to_string())
+            .spawn(move || write_loop(file, receiver))
+            .map_err(|error| error.to_string())?;
+        // A writer started by a concurrent first line loses the race; dropping its sender
+        // stops its thread.
+        let _ = QUEUE.set(LogQueue {
+            sender,
+            dropped: AtomicUsize::new(0),
+        });
+        QUEUE
+            .get()
+            .ok_or_else(|| "Unable to open the log file.".to_string())
+    }
+}
+
+fn write_loop(file: File, receiver: Receiver<LogMessage>) {
+    let mut writer = BufWriter::new(file);
+    let mut waiting = Vec::new();
+    while let Ok(first) = receiver.recv() {
+        let mut pending = Some(first);
+        let mut written = 0;
+        while let Some(message) = pending.take() {
+            match message {
+                LogMessage::Line(line) => {
+                    let _ = writeln!(writer, "{line}");
+                    written += 1;
+                }
+                LogMessage::Flush(done) => waiting.push(done),
+            }
+            if written < BATCH_SIZE {
+                pending = receiver.try_recv().ok();
+            }
+        }
+
+        if let Some(queue) = QUEUE.get() {
+            let dropped = queue.dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                let _ = writeln!(
+                    writer,
+                    "{} [WARN] (Logger) Dropped {dropped} log entries while the writer was busy",
+                    Utc::now().to_rfc3339()
+                );
+            }
+        }
+        let _ = writer.flush();
+        for done in waiting.drain(..) {
+            let _ = done.send(());
+        }
+    }
+}
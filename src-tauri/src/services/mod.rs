@@ -1,3 +1,95 @@
 pub mod file_hasher;
 pub mod file_storage;
 pub mod file_metadata;
+pub mod line_items;
+pub mod date_normalizer;
+pub mod double_payment;
+pub mod parsed_invoice;
+pub mod party_registry;
+pub mod review_queue;
+pub mod tax_id_validator;
+pub mod arithmetic_validator;
+pub mod payments;
+pub mod report_period;
+pub mod spreadsheet;
+pub mod gst_returns;
+pub mod invoice_facts;
+pub mod pdf_report;
+pub mod settings;
+pub mod app_lock;
+pub mod secure_delete;
+pub mod audit_log;
+pub mod roles;
+pub mod notifier;
+pub mod invoice_qr;
+pub mod bank_reconciliation;
+pub mod file_name_template;
+pub mod multi_invoice;
+pub mod cron;
+pub mod scheduler;
+pub mod i18n;
+pub mod telemetry;
+pub mod legacy_import;
+pub mod csv_export;
+pub mod ocr_languages;
+pub mod gemini;
+pub mod prompt_templates;
+pub mod processing_usage;
+pub mod connectivity;
+pub mod offline_queue;
+pub mod reprocess_conflicts;
+pub mod parsed_history;
+pub mod export_bundle;
+pub mod file_integrity;
+pub mod integrity_scan;
+pub mod launch_import;
+pub mod processing_pause;
+pub mod tray;
+pub mod autostart;
+pub mod exchange_rates;
+pub mod duplicate_policy;
+pub mod folder_pipeline;
+pub mod trash;
+pub mod export_manifest;
+pub mod cloud_drive;
+pub mod workspace_lock;
+pub mod log_writer;
+pub mod log_statistics;
+pub mod export_profiles;
+pub mod watcher;
+pub mod archive_import;
+pub mod cloud_connector;
+pub mod s3_connector;
+pub mod batch_import;
+pub mod file_versions;
+pub mod import_batches;
+pub mod image_preprocess;
+pub mod document_split;
+pub mod clipboard_import;
+pub mod directory_import;
+pub mod llm_provider;
+pub mod openai;
+pub mod anthropic;
+pub mod llm_extraction;
+pub mod ollama;
+pub mod parse_queue;
+pub mod invoices;
+pub mod extraction_templates;
+pub mod template_selection;
+pub mod table_extraction;
+pub mod qr_scanner;
+pub mod language_detection;
+pub mod field_corrections;
+pub mod parse_metrics;
+pub mod document_classifier;
+pub mod batch_parse;
+pub mod parse_cancellation;
+pub mod semantic_search;
+pub mod parsed_schema;
+pub mod auto_approval;
+pub mod tax_lines;
+pub mod region_capture;
+pub mod full_text_search;
+pub mod tags;
+pub mod database_backup;
+pub mod database_encryption;
@@ -0,0 +1,299 @@
+use crate::services::document_split::{tiff_pages, SplitFormat};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Phrases that normally appear only on the first page of an invoice.
+const START_MARKERS: &[&str] = &[
+    "tax invoice",
+    "bill of supply",
+    "credit note",
+    "debit note",
+    "commercial invoice",
+    "proforma invoice",
+    "page 1 of",
+];
+
+/// Phrases that mark a page as the tail of the previous invoice.
+const CONTINUATION_MARKERS: &[&str] = &["continued", "carried forward", "brought forward", "c/f", "b/f"];
+
+const INVOICE_NUMBER_LABELS: &[&str] = &["invoice no", "invoice number", "invoice #", "bill no", "inv no"];
+
+/// A scanned page whose images are this much smaller than the document's median is
+/// treated as a blank separator sheet.
+const BLANK_SCAN_RATIO: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PageKind {
+    InvoiceStart,
+    Continuation,
+    Blank,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageClassification {
+    pub page: u32,
+    pub kind: PageKind,
+    pub invoice_number: Option<String>,
+    pub reason: String,
+}
+
+/// An inclusive, 1-based page range holding one logical invoice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitProposal {
+    pub page_count: u32,
+    pub is_multi_invoice: bool,
+    pub pages: Vec<PageClassification>,
+    pub ranges: Vec<PageRange>,
+}
+
+struct PageSignals {
+    text: String,
+    /// Image data on a PDF page, or dark pixels on a TIFF page.
+    image_bytes: usize,
+}
+
+pub struct MultiInvoiceDetector;
+
+impl MultiInvoiceDetector {
+    /// Classifies every page of a PDF or TIFF and proposes where to split it.
+    ///
+    /// Pages with a text layer are classified from invoice headings, "Page x of y"
+    /// footers and invoice numbers. Image-only scans, including every TIFF, carry no
+    /// text, so for those only blank separator sheets can be used as split points.
+    pub fn detect(path: &Path) -> Result<SplitProposal, String> {
+        let signals = if SplitFormat::from_path(path) == Some(SplitFormat::Tiff) {
+            Self::tiff_signals(path)?
+        } else {
+            let document = Document::load(path).map_err(|error| format!("Failed to read PDF: {}", error))?;
+            Self::page_signals(&document)
+        };
+        let pages = Self::classify(&signals);
+        let ranges = Self::propose_ranges(&pages);
+
+        Ok(SplitProposal {
+            page_count: signals.len() as u32,
+            is_multi_invoice: ranges.len() > 1,
+            pages,
+            ranges,
+        })
+    }
+
+    fn page_signals(document: &Document) -> Vec<PageSignals> {
+        document
+            .get_pages()
+            .into_iter()
+            .map(|(number, page_id)| PageSignals {
+                text: document.extract_text(&[number]).unwrap_or_default(),
+                image_bytes: Self::image_bytes(document, page_id),
+            })
+            .collect()
+    }
+
+    fn tiff_signals(path: &Path) -> Result<Vec<PageSignals>, String> {
+        let pages = tiff_pages(&fs::read(path).map_err(|error| error.to_string())?)?;
+        Ok(pages
+            .into_iter()
+            .map(|page| PageSignals {
+                text: String::new(),
+                image_bytes: page.to_luma8().pixels().filter(|pixel| pixel[0] < 128).count(),
+            })
+            .collect())
+    }
+
+    fn image_bytes(document: &Document, page_id: ObjectId) -> usize {
+        let (inline, inherited) = document.get_page_resources(page_id);
+        let mut resources: Vec<&Dictionary> = inline.into_iter().collect();
+        resources.extend(
+            inherited
+                .into_iter()
+                .filter_map(|id| document.get_dictionary(id).ok()),
+        );
+
+        resources
+            .into_iter()
+            .filter_map(|resources| resources.get(b"XObject").ok())
+            .filter_map(|xobjects| resolve(document, xobjects).as_dict().ok())
+            .flat_map(|xobjects| xobjects.iter())
+            .filter_map(|(_, object)| resolve(document, object).as_stream().ok())
+            .filter(|stream| {
+                stream
+                    .dict
+                    .get(b"Subtype")
+                    .and_then(Object::as_name_str)
+                    .map(|subtype| subtype == "Image")
+                    .unwrap_or(false)
+            })
+            .map(|stream| stream.content.len())
+            .sum()
+    }
+
+    fn classify(signals: &[PageSignals]) -> Vec<PageClassification> {
+        let median_image_bytes = median(
+            signals
+                .iter()
+                .map(|page| page.image_bytes)
+                .filter(|bytes| *bytes > 0),
+        );
+        let mut previous_number: Option<String> = None;
+
+        signals
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let text = page.text.to_lowercase();
+                let has_text = text.chars().any(char::is_alphanumeric);
+                let invoice_number = if has_text {
+                    invoice_number(&page.text)
+                } else {
+                    None
+                };
+
+                let (kind, reason) = if !has_text {
+                    let blank_scan = median_image_bytes
+                        .map(|median| (page.image_bytes as f64) < median * BLANK_SCAN_RATIO)
+                        .unwrap_or(false);
+                    if page.image_bytes == 0 || blank_scan {
+                        (
+                            PageKind::Blank,
+                            "No text and no meaningful image content".to_string(),
+                        )
+                    } else {
+                        (PageKind::Unknown, "Scanned page without a text layer".to_string())
+                    }
+                } else if let Some(page_of) = page_of(&text) {
+                    if page_of == 1 {
+                        (PageKind::InvoiceStart, "Footer reads \"Page 1 of\"".to_string())
+                    } else {
+                        (
+                            PageKind::Continuation,
+                            format!("Footer reads \"Page {} of\"", page_of),
+                        )
+                    }
+                } else if let (Some(current), Some(previous)) = (&invoice_number, &previous_number) {
+                    if current.eq_ignore_ascii_case(previous) {
+                        (PageKind::Continuation, format!("Same invoice number {}", current))
+                    } else {
+                        (PageKind::InvoiceStart, format!("New invoice number {}", current))
+                    }
+                } else if CONTINUATION_MARKERS.iter().any(|marker| text.contains(marker)) {
+                    (PageKind::Continuation, "Continuation marker found".to_string())
+                } else if let Some(marker) = START_MARKERS.iter().find(|marker| text.contains(*marker)) {
+                    (PageKind::InvoiceStart, format!("Heading \"{}\" found", marker))
+                } else if index == 0 {
+                    (PageKind::InvoiceStart, "First page".to_string())
+                } else {
+                    (PageKind::Continuation, "No invoice heading found".to_string())
+                };
+
+                if invoice_number.is_some() {
+                    previous_number = invoice_number.clone();
+                }
+
+                PageClassification {
+                    page: index as u32 + 1,
+                    kind,
+                    invoice_number,
+                    reason,
+                }
+            })
+            .collect()
+    }
+
+    /// Groups pages into ranges: a new range begins at every invoice start (unless the
+    /// current range has not seen one yet) and blank pages close the current range.
+    fn propose_ranges(pages: &[PageClassification]) -> Vec<PageRange> {
+        let mut ranges = Vec::new();
+        let mut current: Option<(PageRange, bool)> = None;
+
+        for page in pages {
+            match page.kind {
+                PageKind::Blank => {
+                    if let Some((range, _)) = current.take() {
+                        ranges.push(range);
+                    }
+                }
+                kind => {
+                    let is_start = kind == PageKind::InvoiceStart;
+                    current = match current.take() {
+                        Some((range, has_start)) if is_start && has_start => {
+                            ranges.push(range);
+                            Some((
+                                PageRange {
+                                    start: page.page,
+                                    end: page.page,
+                                },
+                                true,
+                            ))
+                        }
+                        Some((mut range, has_start)) => {
+                            range.end = page.page;
+                            Some((range, has_start || is_start))
+                        }
+                        None => Some((
+                            PageRange {
+                                start: page.page,
+                                end: page.page,
+                            },
+                            is_start,
+                        )),
+                    };
+                }
+            }
+        }
+
+        if let Some((range, _)) = current {
+            ranges.push(range);
+        }
+        ranges
+    }
+}
+
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => document.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+fn median(values: impl Iterator<Item = usize>) -> Option<f64> {
+    let mut values: Vec<usize> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2] as f64)
+}
+
+/// Reads the page number from footers like "Page 2 of 3".
+fn page_of(text: &str) -> Option<u32> {
+    let index = text.find("page ")?;
+    let mut words = text[index + 5..].split_whitespace();
+    let number = words.next()?.parse::<u32>().ok()?;
+    (words.next()? == "of").then_some(number)
+}
+
+/// Takes the first token after an "Invoice No" style label.
+fn invoice_number(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    INVOICE_NUMBER_LABELS.iter().find_map(|label| {
+        let index = lower.find(label)? + label.len();
+        text.get(index..)?
+            .trim_start_matches(|c: char| c == '.' || c == ':' || c == '#' || c.is_whitespace())
+            .split_whitespace()
+            .next()
+            .map(|token| token.trim_matches(|c: char| c == ',' || c == ';').to_string())
+            .filter(|token| token.chars().any(|c| c.is_ascii_digit()))
+    })
+}
@@ -0,0 +1,72 @@
+use crate::db::get_connection;
+use crate::services::{i18n::I18n, settings::Settings};
+use rusqlite::Connection;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+const NOTIFICATIONS_ENABLED_KEY: &str = "notifications.enabled";
+
+/// Long-running work that is worth a native notification once it ends.
+pub enum JobEvent {
+    BatchProcessed { processed: usize, failed: usize },
+    ExportCompleted { label: String, path: String },
+    ScheduledJobFailed { name: String, message: String },
+    ImportFailed { file_name: String, message: String },
+}
+
+impl JobEvent {
+    fn title_and_body(&self) -> (String, String) {
+        match self {
+            JobEvent::BatchProcessed { processed, failed: 0 } => (
+                I18n::text("notify.batch_finished").to_string(),
+                I18n::format("notify.batch_succeeded", &[("processed", processed.to_string())]),
+            ),
+            JobEvent::BatchProcessed { processed, failed } => (
+                I18n::text("notify.batch_finished_with_errors").to_string(),
+                I18n::format(
+                    "notify.batch_failed",
+                    &[("processed", processed.to_string()), ("failed", failed.to_string())],
+                ),
+            ),
+            JobEvent::ExportCompleted { label, path } => (
+                I18n::format("notify.export_ready", &[("label", label.clone())]),
+                I18n::format("notify.saved_to", &[("path", path.clone())]),
+            ),
+            JobEvent::ScheduledJobFailed { name, message } => (
+                I18n::format("notify.scheduled_job_failed", &[("name", name.clone())]),
+                message.clone(),
+            ),
+            JobEvent::ImportFailed { file_name, message } => (
+                I18n::format("notify.import_failed", &[("name", file_name.clone())]),
+                message.clone(),
+            ),
+        }
+    }
+}
+
+pub struct Notifier;
+
+impl Notifier {
+    pub fn enabled(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, NOTIFICATIONS_ENABLED_KEY)?.as_deref() != Some("0"))
+    }
+
+    pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<(), String> {
+        Settings::set(conn, NOTIFICATIONS_ENABLED_KEY, if enabled { "1" } else { "0" })
+    }
+
+    /// Shows a desktop notification unless the user turned them off. Notifications are
+    /// best effort: a failure here must never fail the job that triggered it.
+    pub fn notify<R: Runtime>(app: &AppHandle<R>, event: JobEvent) {
+        let enabled = get_connection()
+            .map_err(|error| error.to_string())
+            .and_then(|conn| Self::enabled(&conn))
+            .unwrap_or(true);
+        if !enabled {
+            return;
+        }
+
+        let (title, body) = event.title_and_body();
+        let _ = app.notification().builder().title(title).body(body).show();
+    }
+}
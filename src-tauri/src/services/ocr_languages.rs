@@ -0,0 +1,218 @@
+use crate::db::tessdata_dir;
+use crate::services::settings::Settings;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+const DEFAULT_LANGUAGES_KEY: &str = "ocr.default_languages";
+const DOWNLOAD_URL: &str = "https://github.com/tesseract-ocr/tessdata_fast/raw/main";
+/// The largest `tessdata_fast` models are well under this; anything bigger is not a language pack.
+const MAX_PACK_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Tesseract language codes offered for download, with their display names.
+const CATALOG: &[(&str, &str)] = &[
+    ("eng", "English"),
+    ("hin", "Hindi"),
+    ("ben", "Bengali"),
+    ("guj", "Gujarati"),
+    ("kan", "Kannada"),
+    ("mal", "Malayalam"),
+    ("mar", "Marathi"),
+    ("pan", "Punjabi"),
+    ("tam", "Tamil"),
+    ("tel", "Telugu"),
+    ("urd", "Urdu"),
+    ("deu", "German"),
+    ("fra", "French"),
+    ("spa", "Spanish"),
+    ("ita", "Italian"),
+    ("por", "Portuguese"),
+    ("nld", "Dutch"),
+    ("ara", "Arabic"),
+    ("chi_sim", "Chinese (Simplified)"),
+    ("jpn", "Japanese"),
+];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguagePack {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub installed: bool,
+    pub size_bytes: Option<u64>,
+}
+
+/// Tesseract `traineddata` files kept under the app data dir, and which languages a file is
+/// written in. The selection also reaches extraction as a hint in the prompt. Selections are
+/// stored as Tesseract `+`-joined codes, e.g. `eng+hin`.
+pub struct OcrLanguages;
+
+impl OcrLanguages {
+    pub fn list() -> Result<Vec<LanguagePack>, String> {
+        let dir = tessdata_dir().map_err(|error| error.to_string())?;
+        Ok(CATALOG
+            .iter()
+            .map(|(code, name)| {
+                let size_bytes = fs::metadata(dir.join(pack_file(code)))
+                    .ok()
+                    .map(|metadata| metadata.len());
+                LanguagePack {
+                    code,
+                    name,
+                    installed: size_bytes.is_some(),
+                    size_bytes,
+                }
+            })
+            .collect())
+    }
+
+    /// Fetches a pack into the tessdata dir. The download goes to a temporary file first,
+    /// so an interrupted transfer never leaves a truncated pack behind.
+    pub fn download(code: &str) -> Result<PathBuf, String> {
+        let code = catalog_code(code)?;
+        let dir = tessdata_dir().map_err(|error| error.to_string())?;
+        let dest = dir.join(pack_file(code));
+
+        let response = ureq::AgentBuilder::new()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .get(&format!("{}/{}", DOWNLOAD_URL, pack_file(code)))
+            .call()
+            .map_err(|error| format!("Could not download the {} language pack: {}", code, error))?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_PACK_BYTES + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|error| error.to_string())?;
+        if bytes.is_empty() || bytes.len() as u64 > MAX_PACK_BYTES {
+            return Err(format!("The {} language pack download was not valid.", code));
+        }
+
+        let partial = dir.join(format!("{}.part", pack_file(code)));
+        fs::write(&partial, &bytes).map_err(|error| error.to_string())?;
+        fs::rename(&partial, &dest).map_err(|error| error.to_string())?;
+        Ok(dest)
+    }
+
+    pub fn remove(code: &str) -> Result<(), String> {
+        let code = catalog_code(code)?;
+        let path = tessdata_dir()
+            .map_err(|error| error.to_string())?
+            .join(pack_file(code));
+        if !path.exists() {
+            return Err(format!("The {} language pack is not installed.", code));
+        }
+        fs::remove_file(path).map_err(|error| error.to_string())
+    }
+
+    pub fn default_languages(conn: &Connection) -> Result<String, String> {
+        Ok(Settings::get(conn, DEFAULT_LANGUAGES_KEY)?.unwrap_or_else(|| "eng".to_string()))
+    }
+
+    pub fn set_default_languages(conn: &Connection, languages: &[String]) -> Result<String, String> {
+        let joined = Self::validate(languages)?.ok_or_else(|| "Select at least one language.".to_string())?;
+        Settings::set(conn, DEFAULT_LANGUAGES_KEY, &joined)?;
+        Ok(joined)
+    }
+
+    /// Sets the languages for one file; an empty selection falls back to the default.
+    pub fn set_for_file(conn: &Connection, file_id: &str, languages: &[String]) -> Result<String, String> {
+        let joined = Self::validate(languages)?;
+        let updated = conn
+            .execute(
+                "UPDATE files SET ocr_languages = ?1 WHERE id = ?2",
+                params![joined, file_id],
+            )
+            .map_err(|error| error.to_string())?;
+        if updated == 0 {
+            return Err(format!("File not found: {}", file_id));
+        }
+        Self::for_file(conn, file_id)
+    }
+
+    /// The languages a file is written in: its own selection, else the default.
+    pub fn for_file(conn: &Connection, file_id: &str) -> Result<String, String> {
+        let selected: Option<Option<String>> = conn
+            .query_row(
+                "SELECT ocr_languages FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        match selected {
+            None => Err(format!("File not found: {}", file_id)),
+            Some(Some(languages)) => Ok(languages),
+            Some(None) => Self::default_languages(conn),
+        }
+    }
+
+    /// A catalogue language given by its code or English name, e.g. `hin` or `Hindi`.
+    pub fn code_for(language: &str) -> Option<&'static str> {
+        let wanted = language.trim();
+        CATALOG
+            .iter()
+            .find(|(code, name)| code.eq_ignore_ascii_case(wanted) || name.eq_ignore_ascii_case(wanted))
+            .map(|(code, _)| *code)
+    }
+
+    pub fn name(code: &str) -> Option<&'static str> {
+        CATALOG
+            .iter()
+            .find(|(catalog_code, _)| *catalog_code == code)
+            .map(|(_, name)| *name)
+    }
+
+    /// A prompt line telling the model which languages to expect in the file, so scripts
+    /// other than Latin are read and kept as written. English alone needs no hint.
+    pub fn extraction_hint(conn: &Connection, file_id: &str) -> Result<Option<String>, String> {
+        let languages = Self::for_file(conn, file_id)?;
+        let names: Vec<&str> = languages.split('+').filter_map(Self::name).collect();
+        if names.is_empty() || names == ["English"] {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "The document may be written in {}. Read text in every one of these languages, keep names, \
+             addresses and item descriptions in their original script, and write numbers and dates \
+             as they would be in English.",
+            names.join(", ")
+        )))
+    }
+
+    /// Every language must be installed, so OCR never starts with a missing pack. Repeats
+    /// are dropped.
+    fn validate(languages: &[String]) -> Result<Option<String>, String> {
+        let dir = tessdata_dir().map_err(|error| error.to_string())?;
+        let mut codes: Vec<&str> = Vec::new();
+        for language in languages {
+            let code = catalog_code(language)?;
+            if !dir.join(pack_file(code)).exists() {
+                return Err(format!(
+                    "The {} language pack is not installed. Download it first.",
+                    code
+                ));
+            }
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+        Ok((!codes.is_empty()).then(|| codes.join("+")))
+    }
+}
+
+fn pack_file(code: &str) -> String {
+    format!("{}.traineddata", code)
+}
+
+/// Only catalogue codes are accepted, which also keeps arbitrary paths out of file names.
+fn catalog_code(code: &str) -> Result<&'static str, String> {
+    let wanted = code.trim().to_ascii_lowercase();
+    CATALOG
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| *code == wanted)
+        .ok_or_else(|| format!("Unknown OCR language: {}", code))
+}
@@ -0,0 +1,47 @@
+use crate::commands::file_operations::FileStatus;
+use crate::db::get_connection;
+use crate::services::connectivity::Connectivity;
+use crate::services::processing_pause::ProcessingPause;
+use crate::services::scheduler::AUTO_PROCESS_EVENT;
+use crate::services::workspace_lock::WorkspaceLock;
+use rusqlite::params;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Files whose extraction could not reach the provider wait as `WaitingForNetwork`
+/// instead of failing, and are handed back to the pipeline once it is reachable again.
+pub struct OfflineQueue;
+
+impl OfflineQueue {
+    /// Called from the background loop. Cheap while nothing is waiting: the network is only
+    /// probed when there are queued files. Returns how many files were re-queued.
+    pub fn resume_if_online<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let waiting: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM files WHERE status = ?1",
+                params![FileStatus::WaitingForNetwork.as_str()],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        if waiting == 0
+            || !Connectivity::is_online()
+            || ProcessingPause::is_paused(&conn)?
+            || WorkspaceLock::is_locked(&conn)?
+        {
+            return Ok(0);
+        }
+
+        let resumed = conn
+            .execute(
+                "UPDATE files SET status = ?1 WHERE status = ?2",
+                params![
+                    FileStatus::Unprocessed.as_str(),
+                    FileStatus::WaitingForNetwork.as_str()
+                ],
+            )
+            .map_err(|error| error.to_string())?;
+        app.emit(AUTO_PROCESS_EVENT, resumed)
+            .map_err(|error| error.to_string())?;
+        Ok(resumed)
+    }
+}
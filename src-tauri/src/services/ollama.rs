@@ -0,0 +1,239 @@
+use crate::services::llm_provider::{
+    parse_output, post_json, ExtractionRequest, LlmError, LlmProvider, LlmProviderKind, LlmResponse,
+};
+use crate::services::openai::read_embeddings;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use lopdf::Document;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:11434";
+
+/// Local models on a CPU can take minutes over a dense page.
+const REQUEST_TIMEOUT_SECS: u64 = 600;
+const HEALTH_TIMEOUT_SECS: u64 = 5;
+
+const IMAGE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp"];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModelHealth {
+    pub endpoint: String,
+    pub reachable: bool,
+    /// Models the server has pulled or loaded.
+    pub models: Vec<String>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// A model served on this machine by Ollama or a llama.cpp server, through the
+/// OpenAI-compatible API both expose. Images go in as they are; PDFs are sent as their text
+/// layer, since local models cannot read PDF files.
+pub struct OllamaClient {
+    endpoint: String,
+    model: String,
+}
+
+impl OllamaClient {
+    pub fn new(endpoint: &str, model: &str) -> Result<Self, String> {
+        if model.trim().is_empty() {
+            return Err("Choose a local model first.".to_string());
+        }
+        Ok(OllamaClient {
+            endpoint: normalize_endpoint(endpoint)?,
+            model: model.trim().to_string(),
+        })
+    }
+
+    /// Whether the server answers, and which models it offers.
+    pub fn health(endpoint: &str) -> Result<LocalModelHealth, String> {
+        let endpoint = normalize_endpoint(endpoint)?;
+        let started = Instant::now();
+        let result = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(HEALTH_TIMEOUT_SECS))
+            .build()
+            .get(&format!("{}/v1/models", endpoint))
+            .call()
+            .map_err(|error| error.to_string())
+            .and_then(|response| response.into_string().map_err(|error| error.to_string()))
+            .and_then(|body| serde_json::from_str::<Value>(&body).map_err(|error| error.to_string()));
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        Ok(match result {
+            Ok(body) => LocalModelHealth {
+                endpoint,
+                reachable: true,
+                models: body
+                    .get("data")
+                    .and_then(Value::as_array)
+                    .map(|models| {
+                        models
+                            .iter()
+                            .filter_map(|model| model.get("id")?.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                latency_ms,
+                error: None,
+            },
+            Err(error) => LocalModelHealth {
+                endpoint,
+                reachable: false,
+                models: Vec::new(),
+                latency_ms,
+                error: Some(error),
+            },
+        })
+    }
+}
+
+impl LlmProvider for OllamaClient {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::Ollama
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn supports(&self, mime_type: &str) -> bool {
+        mime_type == "application/pdf" || IMAGE_MIME_TYPES.contains(&mime_type)
+    }
+
+    fn extract(&self, request: &ExtractionRequest) -> Result<LlmResponse, LlmError> {
+        let content = if request.mime_type == "application/pdf" {
+            let text = pdf_text(request.document)?;
+            json!([{
+                "type": "text",
+                "text": format!("{}\n\nText of {}:\n{}", request.prompt, request.file_name, text),
+            }])
+        } else {
+            json!([
+                { "type": "text", "text": request.prompt },
+                {
+                    "type": "image_url",
+                    "image_url": {
+                        "url": format!("data:{};base64,{}", request.mime_type, STANDARD.encode(request.document)),
+                    },
+                },
+            ])
+        };
+        let response_format = match request.response_schema {
+            Some(schema) => json!({
+                "type": "json_schema",
+                "json_schema": { "name": "invoice", "schema": schema },
+            }),
+            None => json!({ "type": "json_object" }),
+        };
+        let body = json!({
+            "model": self.model,
+            "temperature": request.temperature,
+            "stream": false,
+            "messages": [
+                { "role": "system", "content": request.system_instruction },
+                { "role": "user", "content": content },
+            ],
+            "response_format": response_format,
+        });
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build();
+        let (response, raw) = post_json(
+            agent.post(&format!("{}/v1/chat/completions", self.endpoint)),
+            &body,
+        )
+        .map_err(|error| LlmError {
+            message: format!(
+                "Local model request failed: {}. Check that the model server is running at {}.",
+                error.message, self.endpoint
+            ),
+            ..error
+        })?;
+
+        let text = response
+            .pointer("/choices/0/message/content")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let usage = |key: &str| {
+            response
+                .pointer(&format!("/usage/{}", key))
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+        };
+
+        Ok(LlmResponse {
+            output: parse_output(text),
+            prompt_tokens: usage("prompt_tokens"),
+            output_tokens: usage("completion_tokens"),
+            raw,
+        })
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let body = json!({
+            "model": LlmProviderKind::Ollama.embedding_model(),
+            "input": texts,
+        });
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build();
+        let (response, _) = post_json(agent.post(&format!("{}/v1/embeddings", self.endpoint)), &body)
+            .map_err(|error| LlmError {
+                message: format!(
+                    "Local embeddings request failed: {}. Check that the embedding model is pulled on {}.",
+                    error.message, self.endpoint
+                ),
+                ..error
+            })?;
+        read_embeddings(&response, texts.len())
+    }
+}
+
+/// Trims the endpoint and checks it points at this machine, so documents sent to the
+/// "local" provider cannot end up on another host.
+pub fn normalize_endpoint(endpoint: &str) -> Result<String, String> {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    let authority = endpoint
+        .strip_prefix("http://")
+        .or_else(|| endpoint.strip_prefix("https://"))
+        .ok_or_else(|| {
+            format!(
+                "Enter the local model endpoint as an http:// URL, not {}.",
+                endpoint
+            )
+        })?
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    let local = host.eq_ignore_ascii_case("localhost")
+        || host == "::1"
+        || host
+            .parse::<std::net::Ipv4Addr>()
+            .is_ok_and(|address| address.is_loopback());
+    if !local {
+        return Err(format!(
+            "The local model endpoint must be on this machine (localhost), not {}.",
+            host
+        ));
+    }
+    Ok(endpoint.to_string())
+}
+
+fn pdf_text(bytes: &[u8]) -> Result<String, LlmError> {
+    let document = Document::load_mem(bytes)
+        .map_err(|error| LlmError::fatal(format!("The PDF could not be read: {}", error)))?;
+    let pages: Vec<u32> = document.get_pages().keys().copied().collect();
+    let text = document.extract_text(&pages).unwrap_or_default();
+    if text.trim().is_empty() {
+        return Err(LlmError::fatal(
+            "This PDF has no text layer. Local models can read scanned invoices as images only.",
+        ));
+    }
+    Ok(text)
+}
@@ -0,0 +1,152 @@
+use crate::services::llm_provider::{
+    agent, parse_output, post_json, ExtractionRequest, LlmError, LlmProvider, LlmProviderKind, LlmResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Value};
+
+const API_URL: &str = "https://api.openai.com/v1/chat/completions";
+const EMBEDDINGS_URL: &str = "https://api.openai.com/v1/embeddings";
+
+/// Images go in as `image_url` parts; PDFs as `file` parts.
+const IMAGE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp", "image/gif"];
+
+/// Chat Completions client. Asks for structured output against the response schema when
+/// one is given, otherwise for a JSON object.
+pub struct OpenAiClient {
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiClient {
+    pub fn new(api_key: &str, model: &str) -> Result<Self, String> {
+        if api_key.trim().is_empty() {
+            return Err("An OpenAI API key is required.".to_string());
+        }
+        if model.trim().is_empty() {
+            return Err("An OpenAI model is required.".to_string());
+        }
+        Ok(OpenAiClient {
+            api_key: api_key.trim().to_string(),
+            model: model.trim().to_string(),
+        })
+    }
+}
+
+impl LlmProvider for OpenAiClient {
+    fn kind(&self) -> LlmProviderKind {
+        LlmProviderKind::OpenAi
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn supports(&self, mime_type: &str) -> bool {
+        mime_type == "application/pdf" || IMAGE_MIME_TYPES.contains(&mime_type)
+    }
+
+    fn extract(&self, request: &ExtractionRequest) -> Result<LlmResponse, LlmError> {
+        let data_url = format!(
+            "data:{};base64,{}",
+            request.mime_type,
+            STANDARD.encode(request.document)
+        );
+        let document = if request.mime_type == "application/pdf" {
+            json!({ "type": "file", "file": { "filename": request.file_name, "file_data": data_url } })
+        } else {
+            json!({ "type": "image_url", "image_url": { "url": data_url } })
+        };
+        let response_format = match request.response_schema {
+            Some(schema) => json!({
+                "type": "json_schema",
+                "json_schema": { "name": "invoice", "schema": schema, "strict": false },
+            }),
+            None => json!({ "type": "json_object" }),
+        };
+        let body = json!({
+            "model": self.model,
+            "temperature": request.temperature,
+            "messages": [
+                { "role": "system", "content": request.system_instruction },
+                { "role": "user", "content": [{ "type": "text", "text": request.prompt }, document] },
+            ],
+            "response_format": response_format,
+        });
+
+        let (response, raw) = post_json(
+            agent()
+                .post(API_URL)
+                .set("Authorization", &format!("Bearer {}", self.api_key)),
+            &body,
+        )
+        .map_err(|error| LlmError {
+            message: format!("OpenAI request failed: {}", error.message),
+            ..error
+        })?;
+
+        let text = response
+            .pointer("/choices/0/message/content")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let usage = |key: &str| {
+            response
+                .pointer(&format!("/usage/{}", key))
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+        };
+
+        Ok(LlmResponse {
+            output: parse_output(text),
+            prompt_tokens: usage("prompt_tokens"),
+            output_tokens: usage("completion_tokens"),
+            raw,
+        })
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let body = json!({
+            "model": LlmProviderKind::OpenAi.embedding_model(),
+            "input": texts,
+        });
+        let (response, _) = post_json(
+            agent()
+                .post(EMBEDDINGS_URL)
+                .set("Authorization", &format!("Bearer {}", self.api_key)),
+            &body,
+        )
+        .map_err(|error| LlmError {
+            message: format!("OpenAI embeddings request failed: {}", error.message),
+            ..error
+        })?;
+        read_embeddings(&response, texts.len())
+    }
+}
+
+/// Vectors from an OpenAI-style `/embeddings` response, in input order.
+pub fn read_embeddings(response: &Value, expected: usize) -> Result<Vec<Vec<f32>>, LlmError> {
+    let mut data: Vec<&Value> = response
+        .get("data")
+        .and_then(Value::as_array)
+        .map(|data| data.iter().collect())
+        .unwrap_or_default();
+    data.sort_by_key(|entry| entry.get("index").and_then(Value::as_u64).unwrap_or(0));
+    let vectors: Vec<Vec<f32>> = data
+        .iter()
+        .filter_map(|entry| entry.get("embedding")?.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|value| value as f32)
+                .collect()
+        })
+        .collect();
+    if vectors.len() != expected {
+        return Err(LlmError::fatal(format!(
+            "Expected {} embeddings but received {}.",
+            expected,
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
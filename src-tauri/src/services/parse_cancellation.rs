@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Error a parse ends with once it is cancelled.
+pub const CANCELLED: &str = "Parsing was cancelled.";
+
+/// How often waiting work looks at its token.
+const POLL: Duration = Duration::from_millis(200);
+
+/// Parses running right now, by file.
+static RUNNING: Mutex<Option<HashMap<String, CancelToken>>> = Mutex::new(None);
+
+/// Shared flag a running parse checks between steps and while it waits on the network.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// `Err(CANCELLED)` once cancelled, for `?` between steps.
+    pub fn check(&self) -> Result<(), String> {
+        match self.is_cancelled() {
+            true => Err(CANCELLED.to_string()),
+            false => Ok(()),
+        }
+    }
+
+    /// Sleeps for `duration` unless cancelled first. Returns whether it was cancelled.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let until = Instant::now() + duration;
+        while !self.is_cancelled() {
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            thread::sleep(left.min(POLL));
+        }
+        true
+    }
+
+    /// Runs `work` on its own thread and waits for it, giving up as soon as the token is
+    /// cancelled. Blocking requests cannot be interrupted, so abandoned work runs to its own
+    /// end in the background and its result is dropped.
+    pub fn run<T: Send + 'static>(&self, work: impl FnOnce() -> T + Send + 'static) -> Result<T, String> {
+        self.check()?;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        thread::Builder::new()
+            .name("invox-parse-request".to_string())
+            .spawn(move || {
+                let _ = sender.send(work());
+            })
+            .map_err(|error| error.to_string())?;
+        loop {
+            match receiver.recv_timeout(POLL) {
+                Ok(value) => return Ok(value),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => self.check()?,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err("The extraction request stopped unexpectedly.".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Keeps a file's token registered while its parse runs.
+pub struct ParseGuard {
+    file_id: String,
+    token: CancelToken,
+    /// Only the guard that registered the token removes it.
+    owner: bool,
+}
+
+impl ParseGuard {
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+}
+
+impl Drop for ParseGuard {
+    fn drop(&mut self) {
+        if self.owner {
+            if let Some(running) = lock().as_mut() {
+                running.remove(&self.file_id);
+            }
+        }
+    }
+}
+
+/// Cancellation tokens of the parses in flight, so a parse started by the queue, a batch or
+/// the UI can be stopped from anywhere by its file.
+pub struct ParseCancellation;
+
+impl ParseCancellation {
+    /// Registers a token for `file_id` until the guard is dropped. A parse started inside
+    /// another one for the same file, e.g. a queue job running an LLM extraction, shares the
+    /// outer token.
+    pub fn register(file_id: &str) -> ParseGuard {
+        let mut running = lock();
+        let running = running.get_or_insert_with(HashMap::new);
+        let (token, owner) = match running.get(file_id) {
+            Some(token) => (token.clone(), false),
+            None => {
+                let token = CancelToken::default();
+                running.insert(file_id.to_string(), token.clone());
+                (token, true)
+            }
+        };
+        ParseGuard {
+            file_id: file_id.to_string(),
+            token,
+            owner,
+        }
+    }
+
+    /// Cancels the parse running for `file_id`. Returns whether one was running.
+    pub fn cancel(file_id: &str) -> bool {
+        match lock().as_ref().and_then(|running| running.get(file_id)) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a parse ended because it was cancelled rather than because it failed.
+    pub fn is_cancelled_error(error: &str) -> bool {
+        error == CANCELLED
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<HashMap<String, CancelToken>>> {
+    RUNNING.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,287 @@
+use crate::services::report_period::ReportPeriod;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseOutcome {
+    Applied,
+    Unchanged,
+    Conflict,
+    Failed,
+    WaitingForNetwork,
+    Cancelled,
+}
+
+impl ParseOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseOutcome::Applied => "applied",
+            ParseOutcome::Unchanged => "unchanged",
+            ParseOutcome::Conflict => "conflict",
+            ParseOutcome::Failed => "failed",
+            ParseOutcome::WaitingForNetwork => "waiting_for_network",
+            ParseOutcome::Cancelled => "cancelled",
+        }
+    }
+
+    /// From the status `save_extraction_result` reports.
+    pub fn from_saved(status: &str) -> Self {
+        match status {
+            "unchanged" => ParseOutcome::Unchanged,
+            "conflict" => ParseOutcome::Conflict,
+            _ => ParseOutcome::Applied,
+        }
+    }
+}
+
+/// Where a parse spends its time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseStage {
+    /// Reading the document, its text layer or OCR output, and building the request.
+    Document,
+    /// Waiting on the provider, retries included, or running a template's rules.
+    Model,
+    /// Storing the result and the checks that run on it.
+    Save,
+}
+
+/// Milliseconds between calls, so each stage of a parse can be timed in turn.
+pub struct StageClock {
+    started: Instant,
+    last: Instant,
+}
+
+impl StageClock {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        StageClock {
+            started: now,
+            last: now,
+        }
+    }
+
+    /// Time since the previous lap, or since the start.
+    pub fn lap(&mut self) -> i64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_millis() as i64;
+        self.last = now;
+        elapsed
+    }
+
+    pub fn total(&self) -> i64 {
+        self.started.elapsed().as_millis() as i64
+    }
+}
+
+/// One finished parse of one file.
+pub struct ParseRun<'a> {
+    pub file_id: &'a str,
+    /// Provider name, or `template` for extraction templates.
+    pub provider: &'a str,
+    pub model: Option<&'a str>,
+    pub attempts: u32,
+    pub outcome: ParseOutcome,
+    pub error: Option<&'a str>,
+    pub document_ms: i64,
+    pub model_ms: i64,
+    pub save_ms: i64,
+    pub total_ms: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub p50_ms: i64,
+    pub p95_ms: i64,
+    pub max_ms: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderMetrics {
+    /// `None` for the totals across every provider.
+    pub provider: Option<String>,
+    pub parses: i64,
+    pub succeeded: i64,
+    pub conflicts: i64,
+    pub failed: i64,
+    pub waiting_for_network: i64,
+    pub cancelled: i64,
+    /// Share of parses that produced a result, conflicts included, from 0 to 1. Cancelled
+    /// parses are left out.
+    pub success_rate: f64,
+    pub average_attempts: f64,
+    pub total: LatencyStats,
+    pub document: LatencyStats,
+    pub model: LatencyStats,
+    pub save: LatencyStats,
+    /// The stage with the highest median time.
+    pub bottleneck: Option<ParseStage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsingMetrics {
+    pub overall: ProviderMetrics,
+    /// Busiest provider first.
+    pub by_provider: Vec<ProviderMetrics>,
+}
+
+struct MetricRow {
+    provider: String,
+    attempts: i64,
+    outcome: String,
+    document_ms: i64,
+    model_ms: i64,
+    save_ms: i64,
+    total_ms: i64,
+}
+
+/// Duration, attempt count and outcome of every parse, to tell whether reading documents
+/// or waiting on the model is what slows processing down.
+pub struct ParseMetrics;
+
+impl ParseMetrics {
+    pub fn record(conn: &Connection, run: &ParseRun) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO parse_metrics (
+               file_id, provider, model, attempts, outcome, error, document_ms, model_ms, save_ms, total_ms
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                run.file_id,
+                run.provider,
+                run.model,
+                run.attempts,
+                run.outcome.as_str(),
+                run.error,
+                run.document_ms.max(0),
+                run.model_ms.max(0),
+                run.save_ms.max(0),
+                run.total_ms.max(0)
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Success rates and latency percentiles, overall and per provider, optionally within
+    /// a period of parse dates.
+    pub fn summary(conn: &Connection, period: Option<&ReportPeriod>) -> Result<ParsingMetrics, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT provider, attempts, outcome, document_ms, model_ms, save_ms, total_ms
+                 FROM parse_metrics
+                 WHERE (?1 IS NULL OR DATE(created_at) >= ?1)
+                 AND (?2 IS NULL OR DATE(created_at) <= ?2)",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    period.map(ReportPeriod::start_iso),
+                    period.map(ReportPeriod::end_iso)
+                ],
+                |row| {
+                    Ok(MetricRow {
+                        provider: row.get(0)?,
+                        attempts: row.get(1)?,
+                        outcome: row.get(2)?,
+                        document_ms: row.get(3)?,
+                        model_ms: row.get(4)?,
+                        save_ms: row.get(5)?,
+                        total_ms: row.get(6)?,
+                    })
+                },
+            )
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut providers: Vec<&str> = Vec::new();
+        for row in &rows {
+            if !providers.contains(&row.provider.as_str()) {
+                providers.push(&row.provider);
+            }
+        }
+        let mut by_provider: Vec<ProviderMetrics> = providers
+            .into_iter()
+            .map(|provider| {
+                let runs: Vec<&MetricRow> = rows.iter().filter(|row| row.provider == provider).collect();
+                provider_metrics(Some(provider.to_string()), &runs)
+            })
+            .collect();
+        by_provider.sort_by_key(|metrics| Reverse(metrics.parses));
+
+        Ok(ParsingMetrics {
+            overall: provider_metrics(None, &rows.iter().collect::<Vec<_>>()),
+            by_provider,
+        })
+    }
+}
+
+fn provider_metrics(provider: Option<String>, rows: &[&MetricRow]) -> ProviderMetrics {
+    let count = |outcomes: &[&str]| {
+        rows.iter()
+            .filter(|row| outcomes.contains(&row.outcome.as_str()))
+            .count() as i64
+    };
+    let parses = rows.len() as i64;
+    let succeeded = count(&["applied", "unchanged", "conflict"]);
+    let cancelled = count(&["cancelled"]);
+    let latency = |value: fn(&MetricRow) -> i64| {
+        let mut values: Vec<i64> = rows.iter().map(|row| value(row)).collect();
+        values.sort_unstable();
+        LatencyStats {
+            p50_ms: percentile(&values, 50),
+            p95_ms: percentile(&values, 95),
+            max_ms: values.last().copied().unwrap_or(0),
+        }
+    };
+    let document = latency(|row| row.document_ms);
+    let model = latency(|row| row.model_ms);
+    let save = latency(|row| row.save_ms);
+    let bottleneck = [
+        (ParseStage::Document, document.p50_ms),
+        (ParseStage::Model, model.p50_ms),
+        (ParseStage::Save, save.p50_ms),
+    ]
+    .into_iter()
+    .filter(|(_, p50)| *p50 > 0)
+    .max_by_key(|(_, p50)| *p50)
+    .map(|(stage, _)| stage);
+
+    ProviderMetrics {
+        provider,
+        parses,
+        succeeded,
+        conflicts: count(&["conflict"]),
+        failed: count(&["failed"]),
+        waiting_for_network: count(&["waiting_for_network"]),
+        cancelled,
+        success_rate: match parses - cancelled {
+            0 => 0.0,
+            finished => succeeded as f64 / finished as f64,
+        },
+        average_attempts: match parses {
+            0 => 0.0,
+            _ => rows.iter().map(|row| row.attempts).sum::<i64>() as f64 / parses as f64,
+        },
+        total: latency(|row| row.total_ms),
+        document,
+        model,
+        save,
+        bottleneck,
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[i64], percent: usize) -> i64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
@@ -0,0 +1,669 @@
+use crate::commands::file_operations::FileStatus;
+use crate::db::{get_connection, PooledConnection};
+use crate::services::extraction_templates::ExtractionTemplates;
+use crate::services::llm_extraction::LlmExtraction;
+use crate::services::llm_provider::LlmProviderKind;
+use crate::services::notifier::{JobEvent, Notifier};
+use crate::services::parse_cancellation::ParseCancellation;
+use crate::services::processing_pause::ProcessingPause;
+use crate::services::settings::Settings;
+use crate::services::workspace_lock::WorkspaceLock;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with a [`ParseQueueProgress`] whenever a job starts or finishes.
+pub const PARSE_QUEUE_PROGRESS_EVENT: &str = "parse-queue://progress";
+
+const WORKERS_KEY: &str = "parse_queue.workers";
+const RESPONSE_SCHEMA_KEY: &str = "parse_queue.response_schema";
+const MAX_ATTEMPTS_KEY: &str = "parse_queue.max_attempts";
+const DEFAULT_WORKERS: usize = 2;
+pub const MAX_WORKERS: usize = 8;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub const MAX_ATTEMPTS_LIMIT: u32 = 10;
+/// Failed jobs wait 1, 2, 4… minutes before their next attempt, at most an hour.
+const RETRY_BASE_SECS: u64 = 60;
+const RETRY_MAX_SECS: u64 = 60 * 60;
+/// How often an idle queue looks for work it was not woken for, e.g. after a resume.
+const IDLE_POLL: Duration = Duration::from_secs(5);
+/// Workers write to the database at the same time as the UI does.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseJobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl ParseJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseJobStatus::Queued => "queued",
+            ParseJobStatus::Running => "running",
+            ParseJobStatus::Succeeded => "succeeded",
+            ParseJobStatus::Failed => "failed",
+            ParseJobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseJob {
+    pub id: i64,
+    pub file_id: String,
+    pub file_name: Option<String>,
+    /// Provider the job was queued for; the configured one when not set.
+    pub provider: Option<String>,
+    pub status: String,
+    pub attempts: u32,
+    /// When a failed job is tried again; only set while it waits.
+    pub next_retry_at: Option<String>,
+    pub last_error: Option<String>,
+    pub enqueued_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseQueueStatus {
+    pub queued: i64,
+    /// Queued jobs waiting out their retry backoff.
+    pub retrying: i64,
+    pub running: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    pub workers: usize,
+    pub max_attempts: u32,
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseQueueProgress {
+    pub job_id: i64,
+    pub file_id: String,
+    pub status: &'static str,
+    pub attempt: u32,
+    pub error: Option<String>,
+    pub next_retry_at: Option<String>,
+    pub queued: i64,
+    pub running: i64,
+}
+
+/// How one file was extracted.
+#[derive(Debug, Clone)]
+pub struct FileExtraction {
+    /// `applied`, `unchanged` or `conflict`, as from `save_extraction_result`.
+    pub status: &'static str,
+    /// `template:<name>` or `<provider>:<model>`.
+    pub extracted_by: String,
+}
+
+struct ClaimedJob {
+    id: i64,
+    file_id: String,
+    provider: Option<String>,
+    /// Including the one being started.
+    attempts: u32,
+}
+
+static STARTED: OnceLock<()> = OnceLock::new();
+/// Jobs being extracted right now.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+/// Set when there may be new work; the dispatcher sleeps on it between rounds.
+static WAKE_PENDING: Mutex<bool> = Mutex::new(false);
+static WAKE: Condvar = Condvar::new();
+/// (processed, failed) since the queue was last empty, for the "batch finished" notification.
+static SESSION: Mutex<(usize, usize)> = Mutex::new((0, 0));
+
+/// Persistent extraction queue. A dispatcher thread hands queued jobs to up to
+/// `parse_queue.workers` worker threads, each of which runs [`LlmExtraction::parse_file`]
+/// unless an extraction template reads the file completely.
+/// A failed job is queued again with exponential backoff until it has been tried
+/// `parse_queue.max_attempts` times. Jobs left running by a crash or quit are queued again
+/// on the next start.
+pub struct ParseQueue;
+
+impl ParseQueue {
+    /// Starts the dispatcher. Called once at startup.
+    pub fn start<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        if STARTED.set(()).is_err() {
+            return Ok(());
+        }
+        Self::recover(&*connection()?)?;
+        let app = app.clone();
+        thread::Builder::new()
+            .name("invox-parse-queue".to_string())
+            .spawn(move || loop {
+                let _ = fill(&app);
+                wait();
+            })
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Queues files that are not already queued or running. Returns how many were added.
+    pub fn enqueue(
+        conn: &Connection,
+        file_ids: &[String],
+        provider: Option<LlmProviderKind>,
+    ) -> Result<usize, String> {
+        let mut added = 0;
+        for file_id in file_ids {
+            added += conn
+                .execute(
+                    "INSERT INTO parse_jobs (file_id, provider)
+                     SELECT ?1, ?2
+                     WHERE EXISTS (SELECT 1 FROM files WHERE id = ?1)
+                       AND NOT EXISTS (
+                         SELECT 1 FROM parse_jobs WHERE file_id = ?1 AND status IN ('queued', 'running')
+                       )",
+                    params![file_id, provider.map(|kind| kind.as_str())],
+                )
+                .map_err(|error| error.to_string())?;
+        }
+        if added > 0 {
+            wake();
+        }
+        Ok(added)
+    }
+
+    /// Unprocessed files, oldest first. Documents that were split are left out; their
+    /// parts are extracted instead.
+    pub fn unprocessed(conn: &Connection) -> Result<Vec<String>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM files
+                 WHERE status = ?1
+                   AND NOT EXISTS (SELECT 1 FROM files AS part WHERE part.parent_file_id = files.id)
+                 ORDER BY created_at, id",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params![FileStatus::Unprocessed.as_str()], |row| row.get(0))
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<String>, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    /// Queues Failed files among `file_ids`, or every Failed file, for another attempt now.
+    /// Files already waiting out a backoff skip the rest of it. Returns how many files will
+    /// be retried.
+    pub fn retry_failed(
+        conn: &Connection,
+        file_ids: Option<&[String]>,
+        provider: Option<LlmProviderKind>,
+    ) -> Result<usize, String> {
+        let failed: Vec<String> = {
+            let mut stmt = conn
+                .prepare("SELECT id FROM files WHERE status = ?1 ORDER BY created_at, id")
+                .map_err(|error| error.to_string())?;
+            let rows = stmt
+                .query_map(params![FileStatus::Failed.as_str()], |row| row.get(0))
+                .map_err(|error| error.to_string())?;
+            rows.collect::<Result<Vec<String>, _>>()
+                .map_err(|error| error.to_string())?
+        };
+        let selected: Vec<String> = match file_ids {
+            Some(file_ids) => failed.into_iter().filter(|id| file_ids.contains(id)).collect(),
+            None => failed,
+        };
+
+        let mut brought_forward = 0;
+        for file_id in &selected {
+            brought_forward += conn
+                .execute(
+                    "UPDATE parse_jobs SET next_retry_at = NULL
+                     WHERE file_id = ?1 AND status = ?2 AND next_retry_at IS NOT NULL",
+                    params![file_id, ParseJobStatus::Queued.as_str()],
+                )
+                .map_err(|error| error.to_string())?;
+        }
+        if brought_forward > 0 {
+            wake();
+        }
+        Ok(brought_forward + Self::enqueue(conn, &selected, provider)?)
+    }
+
+    /// Cancels queued jobs for `file_ids`, or every queued job. Running jobs finish.
+    pub fn cancel(conn: &Connection, file_ids: Option<&[String]>) -> Result<usize, String> {
+        let sql = "UPDATE parse_jobs SET status = ?1, finished_at = CURRENT_TIMESTAMP
+                   WHERE status = ?2";
+        let cancelled = match file_ids {
+            Some(file_ids) => {
+                let mut cancelled = 0;
+                for file_id in file_ids {
+                    cancelled += conn
+                        .execute(
+                            &format!("{} AND file_id = ?3", sql),
+                            params![
+                                ParseJobStatus::Cancelled.as_str(),
+                                ParseJobStatus::Queued.as_str(),
+                                file_id
+                            ],
+                        )
+                        .map_err(|error| error.to_string())?;
+                }
+                cancelled
+            }
+            None => conn
+                .execute(
+                    sql,
+                    params![
+                        ParseJobStatus::Cancelled.as_str(),
+                        ParseJobStatus::Queued.as_str()
+                    ],
+                )
+                .map_err(|error| error.to_string())?,
+        };
+        Ok(cancelled)
+    }
+
+    /// Cancels one job. A queued job is simply dropped; a running one has its extraction
+    /// aborted, stops waiting on the provider and leaves its file Unprocessed.
+    pub fn cancel_job(conn: &Connection, job_id: i64) -> Result<(), String> {
+        let (file_id, status): (String, String) = conn
+            .query_row(
+                "SELECT file_id, status FROM parse_jobs WHERE id = ?1",
+                params![job_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("Parse job not found: {}", job_id))?;
+        if status == ParseJobStatus::Queued.as_str() {
+            finish(conn, job_id, ParseJobStatus::Cancelled, None, None)?;
+        } else if status == ParseJobStatus::Running.as_str() {
+            // The worker records the job as cancelled once its extraction stops.
+            if !ParseCancellation::cancel(&file_id) {
+                finish(conn, job_id, ParseJobStatus::Cancelled, None, None)?;
+                reset_file(conn, &file_id)?;
+            }
+        } else {
+            return Err("This parse job has already finished.".to_string());
+        }
+        wake();
+        Ok(())
+    }
+
+    pub fn status(conn: &Connection) -> Result<ParseQueueStatus, String> {
+        let mut status = ParseQueueStatus {
+            workers: Self::workers(conn)?,
+            max_attempts: Self::max_attempts(conn)?,
+            paused: ProcessingPause::is_paused(conn)?,
+            ..ParseQueueStatus::default()
+        };
+        let mut stmt = conn
+            .prepare("SELECT status, COUNT(*) FROM parse_jobs GROUP BY status")
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|error| error.to_string())?;
+        for row in rows {
+            let (name, count) = row.map_err(|error| error.to_string())?;
+            match name.as_str() {
+                "queued" => status.queued = count,
+                "running" => status.running = count,
+                "succeeded" => status.succeeded = count,
+                "failed" => status.failed = count,
+                "cancelled" => status.cancelled = count,
+                _ => {}
+            }
+        }
+        status.retrying = conn
+            .query_row(
+                "SELECT COUNT(*) FROM parse_jobs WHERE status = ?1 AND next_retry_at IS NOT NULL",
+                params![ParseJobStatus::Queued.as_str()],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        Ok(status)
+    }
+
+    /// Most recent jobs first, optionally only those in `status`.
+    pub fn list(conn: &Connection, status: Option<&str>, limit: i64) -> Result<Vec<ParseJob>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT job.id, job.file_id, files.file_name, job.provider, job.status, job.attempts,
+                        job.next_retry_at, job.last_error, job.enqueued_at, job.started_at,
+                        job.finished_at
+                 FROM parse_jobs AS job
+                 LEFT JOIN files ON files.id = job.file_id
+                 WHERE ?1 IS NULL OR job.status = ?1
+                 ORDER BY job.id DESC
+                 LIMIT ?2",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params![status, limit.max(1)], |row| {
+                Ok(ParseJob {
+                    id: row.get(0)?,
+                    file_id: row.get(1)?,
+                    file_name: row.get(2)?,
+                    provider: row.get(3)?,
+                    status: row.get(4)?,
+                    attempts: row.get(5)?,
+                    next_retry_at: row.get(6)?,
+                    last_error: row.get(7)?,
+                    enqueued_at: row.get(8)?,
+                    started_at: row.get(9)?,
+                    finished_at: row.get(10)?,
+                })
+            })
+            .map_err(|error| error.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn workers(conn: &Connection) -> Result<usize, String> {
+        Ok(Settings::get_i64(conn, WORKERS_KEY)?
+            .map(|count| count.clamp(1, MAX_WORKERS as i64) as usize)
+            .unwrap_or(DEFAULT_WORKERS))
+    }
+
+    /// Takes effect on the next dispatch; running jobs are not interrupted.
+    pub fn set_workers(conn: &Connection, count: usize) -> Result<(), String> {
+        if !(1..=MAX_WORKERS).contains(&count) {
+            return Err(format!("Choose between 1 and {} workers.", MAX_WORKERS));
+        }
+        Settings::set(conn, WORKERS_KEY, &count.to_string())?;
+        wake();
+        Ok(())
+    }
+
+    /// Attempts per job, counting the first, before it is left Failed.
+    pub fn max_attempts(conn: &Connection) -> Result<u32, String> {
+        Ok(Settings::get_i64(conn, MAX_ATTEMPTS_KEY)?
+            .map(|count| count.clamp(1, MAX_ATTEMPTS_LIMIT as i64) as u32)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS))
+    }
+
+    pub fn set_max_attempts(conn: &Connection, count: u32) -> Result<(), String> {
+        if !(1..=MAX_ATTEMPTS_LIMIT).contains(&count) {
+            return Err(format!("Choose between 1 and {} attempts.", MAX_ATTEMPTS_LIMIT));
+        }
+        Settings::set(conn, MAX_ATTEMPTS_KEY, &count.to_string())
+    }
+
+    /// The schema queued jobs ask the model for; the frontend owns it, so it is stored
+    /// whenever files are queued.
+    pub fn response_schema(conn: &Connection) -> Result<Option<Value>, String> {
+        Ok(Settings::get(conn, RESPONSE_SCHEMA_KEY)?.and_then(|schema| serde_json::from_str(&schema).ok()))
+    }
+
+    pub fn set_response_schema(conn: &Connection, schema: &Value) -> Result<(), String> {
+        Settings::set(conn, RESPONSE_SCHEMA_KEY, &schema.to_string())
+    }
+
+    /// Extracts one file with a vendor template when one reads every field, otherwise with
+    /// `provider` or the configured one, asking for the stored response schema. A file
+    /// meant for a specific provider always goes to it.
+    pub fn extract_file(
+        conn: &Connection,
+        file_id: &str,
+        provider: Option<LlmProviderKind>,
+    ) -> Result<FileExtraction, String> {
+        let guard = ParseCancellation::register(file_id);
+        if provider.is_none() {
+            if let Ok(Some(result)) = ExtractionTemplates::apply(conn, file_id, None, true) {
+                return Ok(FileExtraction {
+                    status: result.status,
+                    extracted_by: format!("template:{}", result.template_name),
+                });
+            }
+        }
+        guard.token().check()?;
+        let schema = Self::response_schema(conn)?;
+        let result = LlmExtraction::parse_file(conn, file_id, provider, schema.as_ref())?;
+        Ok(FileExtraction {
+            status: result.status,
+            extracted_by: format!("{}:{}", result.provider.as_str(), result.model),
+        })
+    }
+
+    fn recover(conn: &Connection) -> Result<(), String> {
+        conn.execute(
+            "UPDATE files SET status = ?1
+             WHERE status = ?2
+               AND id IN (SELECT file_id FROM parse_jobs WHERE status = ?3)",
+            params![
+                FileStatus::Unprocessed.as_str(),
+                FileStatus::Processing.as_str(),
+                ParseJobStatus::Running.as_str()
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        conn.execute(
+            "UPDATE parse_jobs SET status = ?1, started_at = NULL WHERE status = ?2",
+            params![ParseJobStatus::Queued.as_str(), ParseJobStatus::Running.as_str()],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
+
+/// Lets the dispatcher look for work now instead of at its next poll.
+pub fn wake() {
+    let mut pending = WAKE_PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *pending = true;
+    WAKE.notify_all();
+}
+
+fn wait() {
+    let pending = WAKE_PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (mut pending, _) = WAKE
+        .wait_timeout_while(pending, IDLE_POLL, |pending| !*pending)
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *pending = false;
+}
+
+pub(crate) fn connection() -> Result<PooledConnection, String> {
+    let conn = get_connection().map_err(|error| error.to_string())?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|error| error.to_string())?;
+    Ok(conn)
+}
+
+/// Starts queued jobs until every worker is busy, unless processing is paused or the
+/// workspace is locked.
+fn fill<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let conn = connection()?;
+    if ProcessingPause::is_paused(&conn)? || WorkspaceLock::is_locked(&conn)? {
+        return Ok(());
+    }
+    let workers = ParseQueue::workers(&conn)?;
+    while ACTIVE.load(Ordering::SeqCst) < workers {
+        let Some(job) = claim(&conn)? else {
+            break;
+        };
+        progress(app, &conn, &job, ParseJobStatus::Running, None, None);
+        let job_id = job.id;
+        // Registered before the worker starts, so the job can be cancelled at any point.
+        let guard = ParseCancellation::register(&job.file_id);
+        ACTIVE.fetch_add(1, Ordering::SeqCst);
+        let worker_app = app.clone();
+        let spawned = thread::Builder::new()
+            .name("invox-parse-worker".to_string())
+            .spawn(move || {
+                run(&worker_app, &job);
+                drop(guard);
+                ACTIVE.fetch_sub(1, Ordering::SeqCst);
+                wake();
+            });
+        if let Err(error) = spawned {
+            ACTIVE.fetch_sub(1, Ordering::SeqCst);
+            finish(&conn, job_id, ParseJobStatus::Queued, None, None)?;
+            return Err(error.to_string());
+        }
+    }
+
+    if ACTIVE.load(Ordering::SeqCst) == 0 && ParseQueue::status(&conn)?.queued == 0 {
+        let (processed, failed) =
+            std::mem::take(&mut *SESSION.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        if processed + failed > 0 {
+            Notifier::notify(app, JobEvent::BatchProcessed { processed, failed });
+        }
+    }
+    Ok(())
+}
+
+fn claim(conn: &Connection) -> Result<Option<ClaimedJob>, String> {
+    let job = conn
+        .query_row(
+            "SELECT id, file_id, provider, attempts FROM parse_jobs
+             WHERE status = ?1 AND (next_retry_at IS NULL OR next_retry_at <= CURRENT_TIMESTAMP)
+             ORDER BY id
+             LIMIT 1",
+            params![ParseJobStatus::Queued.as_str()],
+            |row| {
+                Ok(ClaimedJob {
+                    id: row.get(0)?,
+                    file_id: row.get(1)?,
+                    provider: row.get(2)?,
+                    attempts: row.get::<_, u32>(3)? + 1,
+                })
+            },
+        )
+        .optional()
+        .map_err(|error| error.to_string())?;
+    if let Some(job) = &job {
+        conn.execute(
+            "UPDATE parse_jobs
+             SET status = ?1, started_at = CURRENT_TIMESTAMP, attempts = attempts + 1, next_retry_at = NULL
+             WHERE id = ?2",
+            params![ParseJobStatus::Running.as_str(), job.id],
+        )
+        .map_err(|error| error.to_string())?;
+    }
+    Ok(job)
+}
+
+fn run<R: Runtime>(app: &AppHandle<R>, job: &ClaimedJob) {
+    let mut max_attempts = DEFAULT_MAX_ATTEMPTS;
+    let outcome = connection().and_then(|conn| {
+        max_attempts = ParseQueue::max_attempts(&conn)?;
+        let provider = job.provider.as_deref().map(LlmProviderKind::parse).transpose()?;
+        ParseQueue::extract_file(&conn, &job.file_id, provider).map(|_| ())
+    });
+    let (status, error, retry_in) = match outcome {
+        Ok(_) => (ParseJobStatus::Succeeded, None, None),
+        Err(error) if ParseCancellation::is_cancelled_error(&error) => {
+            if let Ok(conn) = connection() {
+                let _ = reset_file(&conn, &job.file_id);
+            }
+            (ParseJobStatus::Cancelled, Some(error), None)
+        }
+        Err(error) if job.attempts < max_attempts => (
+            ParseJobStatus::Queued,
+            Some(error),
+            Some(retry_delay(job.attempts)),
+        ),
+        Err(error) => (ParseJobStatus::Failed, Some(error), None),
+    };
+
+    {
+        let mut session = SESSION.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match status {
+            ParseJobStatus::Succeeded => session.0 += 1,
+            ParseJobStatus::Failed => session.1 += 1,
+            _ => {}
+        }
+    }
+    if let Ok(conn) = connection() {
+        let _ = finish(&conn, job.id, status, error.as_deref(), retry_in);
+        let next_retry_at = match retry_in {
+            Some(_) => conn
+                .query_row(
+                    "SELECT next_retry_at FROM parse_jobs WHERE id = ?1",
+                    params![job.id],
+                    |row| row.get(0),
+                )
+                .ok()
+                .flatten(),
+            None => None,
+        };
+        progress(app, &conn, job, status, error, next_retry_at);
+    }
+}
+
+/// Puts a file whose parse was cancelled back to Unprocessed, unless it already moved on.
+fn reset_file(conn: &Connection, file_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE files SET status = ?1 WHERE id = ?2 AND status = ?3",
+        params![
+            FileStatus::Unprocessed.as_str(),
+            file_id,
+            FileStatus::Processing.as_str()
+        ],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
+/// Seconds until the next attempt after `attempts` failed ones.
+fn retry_delay(attempts: u32) -> u64 {
+    (RETRY_BASE_SECS << attempts.saturating_sub(1).min(10)).min(RETRY_MAX_SECS)
+}
+
+/// Records a job's outcome. `retry_in` puts a failed job back in the queue for later.
+fn finish(
+    conn: &Connection,
+    job_id: i64,
+    status: ParseJobStatus,
+    error: Option<&str>,
+    retry_in: Option<u64>,
+) -> Result<(), String> {
+    let finished = !matches!(status, ParseJobStatus::Queued | ParseJobStatus::Running);
+    conn.execute(
+        "UPDATE parse_jobs
+         SET status = ?2,
+             last_error = COALESCE(?3, last_error),
+             finished_at = CASE WHEN ?4 THEN CURRENT_TIMESTAMP END,
+             started_at = CASE WHEN ?4 THEN started_at END,
+             next_retry_at = CASE WHEN ?5 IS NULL THEN NULL ELSE datetime('now', '+' || ?5 || ' seconds') END
+         WHERE id = ?1",
+        params![job_id, status.as_str(), error, finished, retry_in],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
+
+fn progress<R: Runtime>(
+    app: &AppHandle<R>,
+    conn: &Connection,
+    job: &ClaimedJob,
+    status: ParseJobStatus,
+    error: Option<String>,
+    next_retry_at: Option<String>,
+) {
+    let counts = ParseQueue::status(conn).unwrap_or_default();
+    let _ = app.emit(
+        PARSE_QUEUE_PROGRESS_EVENT,
+        ParseQueueProgress {
+            job_id: job.id,
+            file_id: job.file_id.clone(),
+            status: status.as_str(),
+            attempt: job.attempts,
+            error,
+            next_retry_at,
+            queued: counts.queued,
+            running: counts.running,
+        },
+    );
+}
@@ -0,0 +1,144 @@
+use crate::services::roles::Roles;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+/// What wrote a version of a file's parsed details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailsSource {
+    /// Details that were already stored before history tracking saw the file.
+    Initial,
+    Extraction,
+    ManualEdit,
+    ConflictResolution,
+    LegacyImport,
+    Rollback,
+    /// The same details rewritten at a newer `schema_version`.
+    SchemaUpgrade,
+}
+
+impl DetailsSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetailsSource::Initial => "initial",
+            DetailsSource::Extraction => "extraction",
+            DetailsSource::ManualEdit => "manual_edit",
+            DetailsSource::ConflictResolution => "conflict_resolution",
+            DetailsSource::LegacyImport => "legacy_import",
+            DetailsSource::Rollback => "rollback",
+            DetailsSource::SchemaUpgrade => "schema_upgrade",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedDetailsVersion {
+    pub version: i64,
+    pub parsed_details: Value,
+    pub source: String,
+    /// Role active when the version was written.
+    pub role: Option<String>,
+    pub created_at: String,
+}
+
+/// Every value a file's `parsed_details` has held, numbered per file from 1.
+pub struct ParsedHistory;
+
+impl ParsedHistory {
+    /// Records `parsed_details` as the file's next version. Call before overwriting the
+    /// column: if the file has no history yet, the value being replaced is kept as version 1.
+    pub fn record(
+        conn: &Connection,
+        file_id: &str,
+        parsed_details: &str,
+        source: DetailsSource,
+    ) -> Result<i64, String> {
+        let latest: Option<i64> = conn
+            .query_row(
+                "SELECT MAX(version) FROM parsed_details_history WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        let role = Roles::current(conn)?.as_str();
+
+        let mut version = latest.unwrap_or(0);
+        if latest.is_none() {
+            let existing: Option<String> = conn
+                .query_row(
+                    "SELECT parsed_details FROM files WHERE id = ?1",
+                    params![file_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?
+                .flatten();
+            if let Some(existing) =
+                existing.filter(|value| !value.trim().is_empty() && value != parsed_details)
+            {
+                version += 1;
+                insert(conn, file_id, version, &existing, DetailsSource::Initial, None)?;
+            }
+        }
+
+        version += 1;
+        insert(conn, file_id, version, parsed_details, source, Some(role))?;
+        Ok(version)
+    }
+
+    /// Newest version first.
+    pub fn list(conn: &Connection, file_id: &str) -> Result<Vec<ParsedDetailsVersion>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT version, parsed_details, source, role, created_at
+                 FROM parsed_details_history
+                 WHERE file_id = ?1
+                 ORDER BY version DESC",
+            )
+            .map_err(|error| error.to_string())?;
+        let versions = stmt
+            .query_map(params![file_id], |row| {
+                let raw: String = row.get(1)?;
+                Ok(ParsedDetailsVersion {
+                    version: row.get(0)?,
+                    parsed_details: serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+                    source: row.get(2)?,
+                    role: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(versions)
+    }
+
+    pub fn get(conn: &Connection, file_id: &str, version: i64) -> Result<String, String> {
+        conn.query_row(
+            "SELECT parsed_details FROM parsed_details_history WHERE file_id = ?1 AND version = ?2",
+            params![file_id, version],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Version {} of file {} not found.", version, file_id))
+    }
+}
+
+fn insert(
+    conn: &Connection,
+    file_id: &str,
+    version: i64,
+    parsed_details: &str,
+    source: DetailsSource,
+    role: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO parsed_details_history (file_id, version, parsed_details, source, role)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![file_id, version, parsed_details, source.as_str(), role],
+    )
+    .map_err(|error| error.to_string())?;
+    Ok(())
+}
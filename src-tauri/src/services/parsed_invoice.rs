@@ -0,0 +1,213 @@
+use crate::services::parsed_schema::ParsedSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const SELLER_NAME: &str = "seller name";
+pub const SELLER_ADDRESS: &str = "seller address";
+pub const SELLER_GSTIN: &str = "seller gstin";
+pub const BUYER_NAME: &str = "buyer name";
+pub const BUYER_ADDRESS: &str = "buyer address";
+pub const BUYER_GSTIN: &str = "buyer gstin";
+pub const INVOICE_NUMBER: &str = "invoce number";
+pub const INVOICE_DATE: &str = "date";
+pub const DUE_DATE: &str = "due date";
+pub const PAYMENT_TERMS: &str = "payment terms";
+pub const IRN: &str = "irn";
+pub const SIGNED_QR_CODE: &str = "signed qr code";
+pub const UPI_ID: &str = "upi id";
+pub const IBAN: &str = "iban";
+pub const VOUCHER_TYPE: &str = "voucher type";
+pub const PLACE_OF_SUPPLY: &str = "place of supply";
+pub const CURRENCY: &str = "currency";
+pub const SUBTOTAL: &str = "subtotal";
+pub const TAX_TOTAL: &str = "tax total";
+pub const GRAND_TOTAL: &str = "grand total";
+pub const ITEMS: &str = "items";
+pub const TAX_BREAKUP: &str = "tax breakup";
+pub const FIELD_LOCATIONS: &str = "field locations";
+
+/// Gemini reports boxes on a 0-1000 grid regardless of page size.
+const BOX_SCALE: f64 = 1000.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedLineItem {
+    pub description: Option<String>,
+    pub name: Option<String>,
+    pub hsn_sac: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub rate: Option<f64>,
+    pub amount: Option<f64>,
+    pub cgst: Option<f64>,
+    pub sgst: Option<f64>,
+    pub cgst_rate: Option<f64>,
+    pub sgst_rate: Option<f64>,
+    pub igst: Option<f64>,
+    pub igst_rate: Option<f64>,
+}
+
+/// One row of the GST summary table printed on the invoice.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedTaxRow {
+    pub rate: Option<f64>,
+    pub taxable_value: Option<f64>,
+    pub cgst: Option<f64>,
+    pub sgst: Option<f64>,
+    pub igst: Option<f64>,
+    pub cess: Option<f64>,
+}
+
+/// Page area a value was read from, as fractions (0-1) of the page width and height.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub x_min: f64,
+    pub y_min: f64,
+    pub x_max: f64,
+    pub y_max: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldProvenance {
+    /// Top-level key, or `items[N].<key>` for a line item value.
+    pub field: String,
+    /// 1-based page number.
+    pub page: u32,
+    pub bounding_box: BoundingBox,
+}
+
+/// Read-only view over the `parsed_details` JSON written by the extraction flow, upgraded
+/// to the current schema version first.
+pub struct ParsedInvoice {
+    fields: Map<String, Value>,
+}
+
+impl ParsedInvoice {
+    pub fn from_json(raw: &str) -> Option<Self> {
+        match serde_json::from_str::<Value>(&ParsedSchema::upgrade(raw)).ok()? {
+            Value::Object(fields) => Some(Self { fields }),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, key: &str) -> Option<String> {
+        text_value(self.fields.get(key)?)
+    }
+
+    pub fn number(&self, key: &str) -> Option<f64> {
+        number_value(self.fields.get(key)?)
+    }
+
+    pub fn line_items(&self) -> Vec<ParsedLineItem> {
+        let Some(Value::Array(items)) = self.fields.get(ITEMS) else {
+            return Vec::new();
+        };
+
+        items
+            .iter()
+            .filter_map(Value::as_object)
+            .map(|item| {
+                let text = |key: &str| item.get(key).and_then(text_value);
+                let number = |key: &str| item.get(key).and_then(number_value);
+                ParsedLineItem {
+                    description: text("description"),
+                    name: text("name"),
+                    hsn_sac: text("HSN/SAC"),
+                    quantity: number("quantity"),
+                    unit: text("unit"),
+                    rate: number("rate"),
+                    amount: number("amount"),
+                    cgst: number("cgst"),
+                    sgst: number("sgst"),
+                    cgst_rate: number("cgst_rate"),
+                    sgst_rate: number("sgst_rate"),
+                    igst: number("igst"),
+                    igst_rate: number("igst_rate"),
+                }
+            })
+            .collect()
+    }
+
+    pub fn tax_breakup(&self) -> Vec<ParsedTaxRow> {
+        let Some(Value::Array(rows)) = self.fields.get(TAX_BREAKUP) else {
+            return Vec::new();
+        };
+
+        rows.iter()
+            .filter_map(Value::as_object)
+            .map(|row| {
+                let number = |key: &str| row.get(key).and_then(number_value);
+                ParsedTaxRow {
+                    rate: number("rate"),
+                    taxable_value: number("taxable value"),
+                    cgst: number("cgst"),
+                    sgst: number("sgst"),
+                    igst: number("igst"),
+                    cess: number("cess"),
+                }
+            })
+            .collect()
+    }
+
+    /// Where each value was found on the document. Entries with a malformed box are skipped.
+    pub fn provenance(&self) -> Vec<FieldProvenance> {
+        let Some(Value::Array(locations)) = self.fields.get(FIELD_LOCATIONS) else {
+            return Vec::new();
+        };
+
+        locations
+            .iter()
+            .filter_map(Value::as_object)
+            .filter_map(|location| {
+                let field = location.get("field").and_then(text_value)?;
+                let page = location.get("page").and_then(number_value)?;
+                let bounds: Vec<f64> = location
+                    .get("box")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(number_value)
+                    .map(|value| (value / BOX_SCALE).clamp(0.0, 1.0))
+                    .collect();
+                let [y_min, x_min, y_max, x_max] = bounds[..] else {
+                    return None;
+                };
+                if page < 1.0 || x_min > x_max || y_min > y_max {
+                    return None;
+                }
+                Some(FieldProvenance {
+                    field,
+                    page: page as u32,
+                    bounding_box: BoundingBox {
+                        x_min,
+                        y_min,
+                        x_max,
+                        y_max,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn text_value(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.trim().to_string(),
+        Value::Number(number) => number.to_string(),
+        _ => return None,
+    };
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+pub fn number_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().replace(',', "").parse::<f64>().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,277 @@
+use crate::services::parsed_history::{DetailsSource, ParsedHistory};
+use crate::services::parsed_invoice::{
+    number_value, BUYER_ADDRESS, BUYER_GSTIN, BUYER_NAME, CURRENCY, DUE_DATE, GRAND_TOTAL, IBAN,
+    INVOICE_DATE, INVOICE_NUMBER, IRN, ITEMS, PAYMENT_TERMS, PLACE_OF_SUPPLY, SELLER_ADDRESS, SELLER_GSTIN,
+    SELLER_NAME, SIGNED_QR_CODE, SUBTOTAL, TAX_TOTAL, UPI_ID, VOUCHER_TYPE,
+};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+/// Key every stored payload carries its shape version under.
+pub const SCHEMA_VERSION: &str = "schema_version";
+/// Version details are written at.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Top-level text fields, coerced to strings when a provider answered with a number.
+const TEXT_FIELDS: &[&str] = &[
+    SELLER_NAME,
+    SELLER_ADDRESS,
+    SELLER_GSTIN,
+    BUYER_NAME,
+    BUYER_ADDRESS,
+    BUYER_GSTIN,
+    INVOICE_NUMBER,
+    "voucher number",
+    "reference number",
+    INVOICE_DATE,
+    "reference date",
+    DUE_DATE,
+    PAYMENT_TERMS,
+    IRN,
+    SIGNED_QR_CODE,
+    UPI_ID,
+    IBAN,
+    VOUCHER_TYPE,
+    PLACE_OF_SUPPLY,
+    CURRENCY,
+    "document type",
+    "document language",
+];
+const NUMBER_FIELDS: &[&str] = &[SUBTOTAL, TAX_TOTAL, GRAND_TOTAL];
+const ITEM_TEXT_FIELDS: &[&str] = &["description", "name", "HSN/SAC", "unit"];
+const ITEM_NUMBER_FIELDS: &[&str] = &[
+    "quantity",
+    "rate",
+    "amount",
+    "cgst",
+    "sgst",
+    "cgst_rate",
+    "sgst_rate",
+    "igst",
+    "igst_rate",
+];
+/// Spellings older payloads used for a key, and the key they mean now.
+const RENAMED_FIELDS: &[(&str, &str)] = &[("invoice number", INVOICE_NUMBER)];
+const RENAMED_ITEM_FIELDS: &[(&str, &str)] = &[("hsn_sac", "HSN/SAC"), ("hsn", "HSN/SAC")];
+
+/// Details saved before the payload carried a version: whatever the frontend sent at the
+/// time. Amounts could be printed strings such as `"1,180.00"`, `items` could be missing or
+/// null, and some providers spelled `invoice number` correctly.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParsedDetailsV1 {
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LineItemV2 {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "HSN/SAC", default, skip_serializing_if = "Option::is_none")]
+    pub hsn_sac: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgst: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sgst: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgst_rate: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sgst_rate: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub igst: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub igst_rate: Option<Number>,
+    /// Keys added to the item schema since, kept as they are.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Details as the current schema defines them: amounts are numbers, `items` is always a
+/// list and every payload says which version it is. Fields added to the schema later are
+/// carried in `other` until a new version types them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedDetailsV2 {
+    pub schema_version: u32,
+    #[serde(rename = "seller name", default, skip_serializing_if = "Option::is_none")]
+    pub seller_name: Option<String>,
+    #[serde(rename = "seller gstin", default, skip_serializing_if = "Option::is_none")]
+    pub seller_gstin: Option<String>,
+    #[serde(rename = "buyer name", default, skip_serializing_if = "Option::is_none")]
+    pub buyer_name: Option<String>,
+    #[serde(rename = "buyer gstin", default, skip_serializing_if = "Option::is_none")]
+    pub buyer_gstin: Option<String>,
+    #[serde(rename = "invoce number", default, skip_serializing_if = "Option::is_none")]
+    pub invoice_number: Option<String>,
+    #[serde(rename = "date", default, skip_serializing_if = "Option::is_none")]
+    pub invoice_date: Option<String>,
+    #[serde(rename = "currency", default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(rename = "subtotal", default, skip_serializing_if = "Option::is_none")]
+    pub subtotal: Option<Number>,
+    #[serde(rename = "tax total", default, skip_serializing_if = "Option::is_none")]
+    pub tax_total: Option<Number>,
+    #[serde(rename = "grand total", default, skip_serializing_if = "Option::is_none")]
+    pub grand_total: Option<Number>,
+    #[serde(default)]
+    pub items: Vec<LineItemV2>,
+    /// The remaining text fields and anything else the payload holds, such as field
+    /// locations.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// A stored payload at the version it was written.
+#[derive(Debug, Clone)]
+pub enum ParsedDetails {
+    V1(ParsedDetailsV1),
+    V2(Box<ParsedDetailsV2>),
+}
+
+impl ParsedDetails {
+    /// `None` for text that is not an invoice payload: failure records, unreadable model
+    /// output and anything but a JSON object, as well as payloads from a newer version of
+    /// the app, which are left for it to read.
+    pub fn from_json(raw: &str) -> Option<Self> {
+        let Value::Object(fields) = serde_json::from_str::<Value>(raw).ok()? else {
+            return None;
+        };
+        if fields.contains_key("error") || fields.contains_key("_raw") {
+            return None;
+        }
+        match fields.get(SCHEMA_VERSION).and_then(Value::as_u64) {
+            None | Some(1) => Some(ParsedDetails::V1(ParsedDetailsV1 { fields })),
+            Some(2) => Some(
+                // A typed read only fails for hand-edited details; those get the v1 clean-up.
+                match serde_json::from_value(Value::Object(fields.clone())) {
+                    Ok(details) => ParsedDetails::V2(Box::new(details)),
+                    Err(_) => ParsedDetails::V1(ParsedDetailsV1 { fields }),
+                },
+            ),
+            Some(_) => None,
+        }
+    }
+
+    /// Brings the payload up to the current version, one version at a time.
+    pub fn upgrade(self) -> ParsedDetailsV2 {
+        match self {
+            ParsedDetails::V1(details) => details.into(),
+            ParsedDetails::V2(details) => *details,
+        }
+    }
+}
+
+impl From<ParsedDetailsV1> for ParsedDetailsV2 {
+    fn from(details: ParsedDetailsV1) -> Self {
+        let mut fields = details.fields;
+        rename_keys(&mut fields, RENAMED_FIELDS);
+        coerce(&mut fields, TEXT_FIELDS, NUMBER_FIELDS);
+        let items: Vec<Value> = match fields.remove(ITEMS) {
+            Some(Value::Array(items)) => items
+                .into_iter()
+                .filter_map(|item| match item {
+                    Value::Object(mut item) => {
+                        rename_keys(&mut item, RENAMED_ITEM_FIELDS);
+                        coerce(&mut item, ITEM_TEXT_FIELDS, ITEM_NUMBER_FIELDS);
+                        Some(Value::Object(item))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        fields.insert(ITEMS.to_string(), Value::Array(items));
+        fields.insert(SCHEMA_VERSION.to_string(), Value::from(2));
+        // Every typed key was coerced above, so this only fails on a malformed payload, which
+        // is then kept untyped rather than dropped.
+        serde_json::from_value(Value::Object(fields.clone())).unwrap_or_else(|_| ParsedDetailsV2 {
+            schema_version: 2,
+            other: fields,
+            ..ParsedDetailsV2::default()
+        })
+    }
+}
+
+/// Versioning of `parsed_details`. Details are stamped with the current version whenever
+/// they are written; older ones are upgraded when read, and written back the first time a
+/// file list reads them, with the original kept in the file's history.
+pub struct ParsedSchema;
+
+impl ParsedSchema {
+    /// `raw` at the current version, for details about to be stored or just read. Text that
+    /// is not an invoice payload, or is already current, comes back as it was.
+    pub fn upgrade(raw: &str) -> String {
+        match ParsedDetails::from_json(raw) {
+            Some(ParsedDetails::V2(details)) if details.schema_version == CURRENT_SCHEMA_VERSION => {
+                raw.to_string()
+            }
+            None => raw.to_string(),
+            Some(details) => serde_json::to_string(&details.upgrade()).unwrap_or_else(|_| raw.to_string()),
+        }
+    }
+
+    /// A file's stored details at the current version, writing the upgrade back when they
+    /// were older.
+    pub fn load(conn: &Connection, file_id: &str, raw: Option<String>) -> Result<Option<String>, String> {
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+        let upgraded = Self::upgrade(&raw);
+        if upgraded != raw {
+            ParsedHistory::record(conn, file_id, &upgraded, DetailsSource::SchemaUpgrade)?;
+            conn.execute(
+                "UPDATE files SET parsed_details = ?1 WHERE id = ?2 AND parsed_details = ?3",
+                params![upgraded, file_id, raw],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(Some(upgraded))
+    }
+}
+
+fn rename_keys(fields: &mut Map<String, Value>, renames: &[(&str, &str)]) {
+    for (old, new) in renames {
+        if let Some(value) = fields.remove(*old) {
+            if matches!(fields.get(*new), None | Some(Value::Null)) {
+                fields.insert(new.to_string(), value);
+            }
+        }
+    }
+}
+
+/// Numbers in text fields become text; text in number fields becomes a number, or null
+/// when it does not read as one.
+fn coerce(fields: &mut Map<String, Value>, text_fields: &[&str], number_fields: &[&str]) {
+    for key in text_fields {
+        if let Some(value) = fields.get_mut(*key) {
+            *value = match value.take() {
+                Value::String(text) => Value::String(text),
+                Value::Null => Value::Null,
+                Value::Number(number) => Value::String(number.to_string()),
+                Value::Bool(flag) => Value::String(flag.to_string()),
+                other => Value::String(other.to_string()),
+            };
+        }
+    }
+    for key in number_fields {
+        if let Some(value) = fields.get_mut(*key) {
+            *value = match value.take() {
+                Value::Number(number) => Value::Number(number),
+                other => number_value(&other)
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+            };
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use crate::services::{
+    parsed_invoice::{
+        ParsedInvoice, BUYER_ADDRESS, BUYER_GSTIN, BUYER_NAME, SELLER_ADDRESS, SELLER_GSTIN,
+        SELLER_NAME,
+    },
+    tax_id_validator::TaxIdValidator,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+const LEGAL_SUFFIXES: &[&str] = &[
+    "pvt",
+    "private",
+    "ltd",
+    "limited",
+    "llp",
+    "llc",
+    "inc",
+    "incorporated",
+    "corp",
+    "corporation",
+    "co",
+    "company",
+    "gmbh",
+    "plc",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyRow {
+    pub id: i64,
+    pub name: String,
+    pub tax_id: Option<String>,
+    pub address: Option<String>,
+    pub aliases: Vec<String>,
+    pub file_count: i64,
+    pub created_at: String,
+}
+
+/// Master data for one side of an invoice (sellers or buyers). Both sides share
+/// the same table layout: a party table, an alias table and a link column on `files`.
+pub struct PartyRegistry {
+    pub label: &'static str,
+    pub table: &'static str,
+    pub alias_table: &'static str,
+    /// Column holding the party id, both on the alias table and on `files`.
+    pub id_column: &'static str,
+    pub name_field: &'static str,
+    pub tax_id_field: &'static str,
+    pub address_field: &'static str,
+}
+
+pub const SELLERS: PartyRegistry = PartyRegistry {
+    label: "Seller",
+    table: "sellers",
+    alias_table: "seller_aliases",
+    id_column: "seller_id",
+    name_field: SELLER_NAME,
+    tax_id_field: SELLER_GSTIN,
+    address_field: SELLER_ADDRESS,
+};
+
+pub const BUYERS: PartyRegistry = PartyRegistry {
+    label: "Buyer",
+    table: "buyers",
+    alias_table: "buyer_aliases",
+    id_column: "buyer_id",
+    name_field: BUYER_NAME,
+    tax_id_field: BUYER_GSTIN,
+    address_field: BUYER_ADDRESS,
+};
+
+impl PartyRegistry {
+    /// Reduces a party name to the key aliases are matched on, so that
+    /// "ACME Corp" and "Acme Corporation Pvt. Ltd." resolve to the same party.
+    pub fn alias_key(name: &str) -> String {
+        let cleaned: String = name
+            .chars()
+            .map(|ch| if ch.is_alphanumeric() { ch.to_ascii_lowercase() } else { ' ' })
+            .collect();
+
+        let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+        while words.len() > 1 && words.last().is_some_and(|word| LEGAL_SUFFIXES.contains(word)) {
+            words.pop();
+        }
+
+        words.join(" ")
+    }
+
+    pub fn find_by_alias(&self, conn: &Connection, name: &str) -> Result<Option<i64>, String> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM {} WHERE alias_key = ?1",
+                self.id_column, self.alias_table
+            ),
+            params![Self::alias_key(name)],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())
+    }
+
+    pub fn add_alias(&self, conn: &Connection, party_id: i64, alias: &str) -> Result<(), String> {
+        let key = Self::alias_key(alias);
+        if key.is_empty() {
+            return Err("Alias must contain letters or digits.".to_string());
+        }
+
+        conn.execute(
+            &format!(
+                "INSERT INTO {table} (alias_key, alias, {column}) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(alias_key) DO UPDATE SET {column} = excluded.{column}",
+                table = self.alias_table,
+                column = self.id_column
+            ),
+            params![key, alias.trim(), party_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(())
+    }
+
+    /// Drops an alias so the name no longer resolves to its party. A party's own name cannot
+    /// be dropped, since parsing would add it straight back.
+    pub fn remove_alias(&self, conn: &Connection, alias: &str) -> Result<(), String> {
+        let key = Self::alias_key(alias);
+        let party_name: Option<String> = conn
+            .query_row(
+                &format!(
+                    "SELECT p.name FROM {alias_table} a JOIN {table} p ON p.id = a.{column}
+                     WHERE a.alias_key = ?1",
+                    alias_table = self.alias_table,
+                    table = self.table,
+                    column = self.id_column
+                ),
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        match party_name {
+            None => Err(format!("Alias not found: {}", alias.trim())),
+            Some(name) if Self::alias_key(&name) == key => Err(format!(
+                "\"{}\" is the {}'s own name; rename the {} instead.",
+                alias.trim(),
+                self.label.to_lowercase(),
+                self.label.to_lowercase()
+            )),
+            Some(_) => {
+                conn.execute(
+                    &format!("DELETE FROM {} WHERE alias_key = ?1", self.alias_table),
+                    params![key],
+                )
+                .map_err(|error| error.to_string())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Changes the name a party is shown and reported under. The old name stays an alias, so
+    /// invoices printed with it keep resolving to the party.
+    pub fn rename(&self, conn: &Connection, party_id: i64, name: &str) -> Result<(), String> {
+        if Self::alias_key(name).is_empty() {
+            return Err("Name must contain letters or digits.".to_string());
+        }
+        let renamed = conn
+            .execute(
+                &format!("UPDATE {} SET name = ?1 WHERE id = ?2", self.table),
+                params![name.trim(), party_id],
+            )
+            .map_err(|error| error.to_string())?;
+        if renamed == 0 {
+            return Err(format!("{} not found: {}", self.label, party_id));
+        }
+        self.add_alias(conn, party_id, name)
+    }
+
+    /// Finds the party for an extracted name/tax id pair, creating it on first sight.
+    pub fn resolve_or_create(
+        &self,
+        conn: &Connection,
+        name: &str,
+        tax_id: Option<&str>,
+        address: Option<&str>,
+    ) -> Result<Option<i64>, String> {
+        if Self::alias_key(name).is_empty() {
+            return Ok(None);
+        }
+
+        let tax_id = tax_id.map(TaxIdValidator::normalize).filter(|value| !value.is_empty());
+        let by_tax_id = match &tax_id {
+            Some(tax_id) => conn
+                .query_row(
+                    &format!("SELECT id FROM {} WHERE tax_id = ?1", self.table),
+                    params![tax_id],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?,
+            None => None,
+        };
+
+        let party_id = match by_tax_id.or(self.find_by_alias(conn, name)?) {
+            Some(party_id) => {
+                conn.execute(
+                    &format!(
+                        "UPDATE {} SET
+                           tax_id = COALESCE(tax_id, ?1),
+                           address = COALESCE(address, ?2)
+                         WHERE id = ?3",
+                        self.table
+                    ),
+                    params![tax_id, address, party_id],
+                )
+                .map_err(|error| error.to_string())?;
+                party_id
+            }
+            None => {
+                conn.execute(
+                    &format!("INSERT INTO {} (name, tax_id, address) VALUES (?1, ?2, ?3)", self.table),
+                    params![name.trim(), tax_id, address],
+                )
+                .map_err(|error| error.to_string())?;
+                conn.last_insert_rowid()
+            }
+        };
+
+        if self.find_by_alias(conn, name)?.is_none() {
+            self.add_alias(conn, party_id, name)?;
+        }
+
+        Ok(Some(party_id))
+    }
+
+    /// Links a file to its party based on freshly saved `parsed_details`.
+    pub fn assign_file(
+        &self,
+        conn: &Connection,
+        file_id: &str,
+        parsed_details: Option<&str>,
+    ) -> Result<Option<i64>, String> {
+        let invoice = parsed_details.and_then(ParsedInvoice::from_json);
+        let party_id = match invoice {
+            Some(invoice) => match invoice.text(self.name_field) {
+                Some(name) => self.resolve_or_create(
+                    conn,
+                    &name,
+                    invoice.text(self.tax_id_field).as_deref(),
+                    invoice.text(self.address_field).as_deref(),
+                )?,
+                None => None,
+            },
+            None => None,
+        };
+
+        conn.execute(
+            &format!("UPDATE files SET {} = ?1 WHERE id = ?2", self.id_column),
+            params![party_id, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(party_id)
+    }
+
+    pub fn list(&self, conn: &Connection) -> Result<Vec<PartyRow>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT p.id, p.name, p.tax_id, p.address, p.created_at,
+                        (SELECT COUNT(*) FROM files f WHERE f.{column} = p.id)
+                 FROM {table} p
+                 ORDER BY p.name COLLATE NOCASE",
+                table = self.table,
+                column = self.id_column
+            ))
+            .map_err(|error| error.to_string())?;
+
+        let rows_iter = stmt
+            .query_map([], |row| {
+                Ok(PartyRow {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    tax_id: row.get(2)?,
+                    address: row.get(3)?,
+                    aliases: Vec::new(),
+                    file_count: row.get(5)?,
+                    created_at: row.get(4)?,
+                })
+            })
+            .map_err(|error| error.to_string())?;
+
+        let mut parties = Vec::new();
+        for row in rows_iter {
+            parties.push(row.map_err(|error| error.to_string())?);
+        }
+
+        let mut alias_stmt = conn
+            .prepare(&format!(
+                "SELECT alias FROM {} WHERE {} = ?1 ORDER BY alias",
+                self.alias_table, self.id_column
+            ))
+            .map_err(|error| error.to_string())?;
+        for party in &mut parties {
+            let aliases = alias_stmt
+                .query_map(params![party.id], |row| row.get::<_, String>(0))
+                .map_err(|error| error.to_string())?;
+            for alias in aliases {
+                party.aliases.push(alias.map_err(|error| error.to_string())?);
+            }
+        }
+
+        Ok(parties)
+    }
+
+    /// Folds `ids` into `canonical_id`: aliases and files move over and the
+    /// merged names become aliases of the canonical party.
+    pub fn merge(&self, conn: &mut Connection, ids: &[i64], canonical_id: i64) -> Result<(), String> {
+        let tx = conn.transaction().map_err(|error| error.to_string())?;
+
+        let canonical_exists = tx
+            .query_row(
+                &format!("SELECT 1 FROM {} WHERE id = ?1", self.table),
+                params![canonical_id],
+                |_row| Ok(()),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .is_some();
+        if !canonical_exists {
+            return Err(format!("Canonical {} not found.", self.label.to_lowercase()));
+        }
+
+        for id in ids.iter().copied().filter(|id| *id != canonical_id) {
+            let merged: Option<(String, Option<String>, Option<String>)> = tx
+                .query_row(
+                    &format!("SELECT name, tax_id, address FROM {} WHERE id = ?1", self.table),
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let Some((name, tax_id, address)) = merged else {
+                continue;
+            };
+
+            tx.execute(
+                &format!(
+                    "UPDATE {table} SET {column} = ?1 WHERE {column} = ?2",
+                    table = self.alias_table,
+                    column = self.id_column
+                ),
+                params![canonical_id, id],
+            )
+            .map_err(|error| error.to_string())?;
+            self.add_alias(&tx, canonical_id, &name)?;
+
+            tx.execute(
+                &format!(
+                    "UPDATE files SET {column} = ?1 WHERE {column} = ?2",
+                    column = self.id_column
+                ),
+                params![canonical_id, id],
+            )
+            .map_err(|error| error.to_string())?;
+
+            tx.execute(&format!("DELETE FROM {} WHERE id = ?1", self.table), params![id])
+                .map_err(|error| error.to_string())?;
+
+            tx.execute(
+                &format!(
+                    "UPDATE {} SET tax_id = COALESCE(tax_id, ?1), address = COALESCE(address, ?2) WHERE id = ?3",
+                    self.table
+                ),
+                params![tax_id, address, canonical_id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+
+        tx.commit().map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Links every parsed file that has no party yet, e.g. files processed before
+    /// the master table existed. Returns the number of files linked.
+    pub fn sync(&self, conn: &Connection) -> Result<usize, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, parsed_details FROM files WHERE {} IS NULL AND parsed_details IS NOT NULL",
+                self.id_column
+            ))
+            .map_err(|error| error.to_string())?;
+        let pending = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut linked = 0;
+        for (file_id, parsed_details) in pending {
+            if self.assign_file(conn, &file_id, Some(&parsed_details))?.is_some() {
+                linked += 1;
+            }
+        }
+
+        Ok(linked)
+    }
+}
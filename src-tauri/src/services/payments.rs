@@ -0,0 +1,89 @@
+use crate::services::{
+    date_normalizer::DateNormalizer,
+    parsed_invoice::{ParsedInvoice, DUE_DATE, PAYMENT_TERMS},
+};
+use chrono::{Duration, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Terms that mean the invoice is due the day it is issued.
+const IMMEDIATE_TERMS: &[&str] = &[
+    "due on receipt",
+    "upon receipt",
+    "on receipt",
+    "immediate",
+    "cash on delivery",
+];
+
+pub struct PaymentTracker;
+
+impl PaymentTracker {
+    /// Stores the raw payment terms of a parsed invoice and its due date: the printed one,
+    /// normalized, or else the invoice date plus the days the terms allow ("Net 30").
+    /// Runs after the invoice date is normalized, which it reads from the file record.
+    pub fn update_terms(conn: &Connection, file_id: &str, parsed_details: Option<&str>) -> Result<(), String> {
+        let invoice = parsed_details.and_then(ParsedInvoice::from_json);
+        let payment_terms = invoice.as_ref().and_then(|invoice| invoice.text(PAYMENT_TERMS));
+        let due_date = match invoice
+            .as_ref()
+            .and_then(|invoice| invoice.text(DUE_DATE))
+            .and_then(|raw| DateNormalizer::normalize(&raw, None).iso)
+        {
+            Some(due_date) => Some(due_date),
+            None => match payment_terms.as_deref().and_then(Self::term_days) {
+                Some(days) => Self::invoice_date(conn, file_id)?
+                    .map(|invoice_date| (invoice_date + Duration::days(days)).format("%Y-%m-%d").to_string()),
+                None => None,
+            },
+        };
+
+        conn.execute(
+            "UPDATE files SET due_date = ?1, payment_terms = ?2 WHERE id = ?3",
+            params![due_date, payment_terms, file_id],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(())
+    }
+
+    /// Days after the invoice date that `terms` give for payment: "Net 30", "30 days",
+    /// "2/10 Net 45" (the net period, not the discount one) or 0 for "Due on receipt".
+    /// `None` when the terms name no period, e.g. "Against delivery challan".
+    pub fn term_days(terms: &str) -> Option<i64> {
+        let terms = terms.to_lowercase();
+        if IMMEDIATE_TERMS.iter().any(|immediate| terms.contains(immediate)) {
+            return Some(0);
+        }
+        let after_net = terms.find("net").map(|index| &terms[index + 3..]);
+        if let Some(days) = after_net.and_then(leading_number) {
+            return Some(days);
+        }
+        // "Payable within 15 days", "30 days credit"
+        let words: Vec<&str> = terms
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+        words
+            .windows(2)
+            .find(|pair| pair[1].starts_with("day"))
+            .and_then(|pair| pair[0].parse().ok())
+    }
+
+    fn invoice_date(conn: &Connection, file_id: &str) -> Result<Option<NaiveDate>, String> {
+        let invoice_date: Option<String> = conn
+            .query_row("SELECT invoice_date FROM files WHERE id = ?1", params![file_id], |row| row.get(0))
+            .optional()
+            .map_err(|error| error.to_string())?
+            .flatten();
+        Ok(invoice_date.and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()))
+    }
+}
+
+/// The number `text` starts with, after any spaces or dashes ("Net-30", "net 45").
+fn leading_number(text: &str) -> Option<i64> {
+    let digits: String = text
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == ':')
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
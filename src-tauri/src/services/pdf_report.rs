@@ -0,0 +1,207 @@
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Point,
+};
+use std::{fs::File, io::BufWriter, path::Path};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const ROW_HEIGHT: f32 = 6.0;
+const BODY_SIZE: f32 = 9.0;
+/// Average Helvetica glyph width relative to the font size, used to right-align and truncate cells.
+const GLYPH_WIDTH: f32 = 0.5;
+const PT_TO_MM: f32 = 0.3528;
+
+/// A titled table; the first column is left aligned, the rest hold right-aligned figures.
+pub struct ReportTable {
+    pub heading: String,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+    pub totals: Option<Vec<String>>,
+}
+
+pub struct PdfReport {
+    pub title: String,
+    pub subtitle: String,
+    pub tables: Vec<ReportTable>,
+}
+
+struct Cursor {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+    page: usize,
+}
+
+impl Cursor {
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.page += 1;
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.footer();
+    }
+
+    fn footer(&self) {
+        self.layer.use_text(
+            format!("Page {}", self.page),
+            8.0,
+            Mm(PAGE_WIDTH - MARGIN - 12.0),
+            Mm(MARGIN / 2.0),
+            &self.regular,
+        );
+    }
+
+    /// Starts a new page when fewer than `needed` millimetres remain above the bottom margin.
+    fn reserve(&mut self, needed: f32) -> bool {
+        if self.y - needed < MARGIN {
+            self.new_page();
+            return true;
+        }
+        false
+    }
+
+    fn rule(&self, y: f32) {
+        self.layer.set_outline_thickness(0.4);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn row(&mut self, cells: &[String], widths: &[f32], bold: bool) {
+        let font = if bold { &self.bold } else { &self.regular };
+        let mut x = MARGIN;
+        for (index, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            let text = fit(cell, *width - 2.0);
+            if index == 0 {
+                self.layer.use_text(text, BODY_SIZE, Mm(x), Mm(self.y), font);
+            } else {
+                let text_width = text_width(&text, BODY_SIZE);
+                self.layer
+                    .use_text(text, BODY_SIZE, Mm(x + width - text_width), Mm(self.y), font);
+            }
+            x += width;
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    fn table(&mut self, table: &ReportTable) {
+        let usable = PAGE_WIDTH - 2.0 * MARGIN;
+        let columns = table.columns.len().max(1);
+        let first = if columns == 1 { usable } else { usable * 0.4 };
+        let rest = if columns == 1 { 0.0 } else { (usable - first) / (columns - 1) as f32 };
+        let widths: Vec<f32> = (0..columns).map(|index| if index == 0 { first } else { rest }).collect();
+        let header: Vec<String> = table.columns.iter().map(|column| column.to_string()).collect();
+
+        self.reserve(ROW_HEIGHT * 4.0);
+        self.layer
+            .use_text(table.heading.as_str(), 12.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= ROW_HEIGHT * 1.5;
+        self.row(&header, &widths, true);
+        self.rule(self.y + ROW_HEIGHT - 1.5);
+
+        if table.rows.is_empty() {
+            self.row(&["No invoices in this period.".to_string()], &[usable], false);
+        }
+
+        for row in &table.rows {
+            if self.reserve(ROW_HEIGHT) {
+                self.row(&header, &widths, true);
+                self.rule(self.y + ROW_HEIGHT - 1.5);
+            }
+            self.row(row, &widths, false);
+        }
+
+        if let Some(totals) = &table.totals {
+            self.reserve(ROW_HEIGHT);
+            self.rule(self.y + ROW_HEIGHT - 1.5);
+            self.row(totals, &widths, true);
+        }
+
+        self.y -= ROW_HEIGHT;
+    }
+}
+
+impl PdfReport {
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let (doc, page, layer) =
+            PdfDocument::new(self.title.as_str(), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|error| error.to_string())?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|error| error.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let mut cursor = Cursor {
+            doc,
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+            page: 1,
+        };
+        cursor.footer();
+
+        cursor
+            .layer
+            .use_text(self.title.as_str(), 18.0, Mm(MARGIN), Mm(cursor.y), &cursor.bold);
+        cursor.y -= ROW_HEIGHT * 1.5;
+        cursor
+            .layer
+            .use_text(self.subtitle.as_str(), 10.0, Mm(MARGIN), Mm(cursor.y), &cursor.regular);
+        cursor.y -= ROW_HEIGHT * 2.0;
+
+        for table in &self.tables {
+            cursor.table(table);
+        }
+
+        let file = File::create(path).map_err(|error| error.to_string())?;
+        cursor
+            .doc
+            .save(&mut BufWriter::new(file))
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// Formats an amount with two decimals and thousands separators.
+pub fn format_amount(value: f64) -> String {
+    let formatted = format!("{:.2}", value.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+    let mut grouped = String::new();
+    for (index, ch) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    let sign = if value < -0.005 { "-" } else { "" };
+    format!("{}{}.{}", sign, grouped, fraction)
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * GLYPH_WIDTH * PT_TO_MM
+}
+
+/// Truncates `text` with an ellipsis so that it fits `width` millimetres. The builtin
+/// fonts only cover Latin-1, so other characters are replaced.
+fn fit(text: &str, width: f32) -> String {
+    let sanitized: String = text
+        .chars()
+        .map(|ch| if (ch as u32) < 256 { ch } else { '?' })
+        .collect();
+    let max_chars = (width / (BODY_SIZE * GLYPH_WIDTH * PT_TO_MM)).floor().max(3.0) as usize;
+    if sanitized.chars().count() <= max_chars {
+        return sanitized;
+    }
+    let truncated: String = sanitized.chars().take(max_chars - 3).collect();
+    format!("{}...", truncated)
+}
@@ -0,0 +1,24 @@
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with the new paused state whenever it changes.
+pub const PROCESSING_PAUSED_EVENT: &str = "processing://paused-changed";
+
+const PAUSED_KEY: &str = "processing.paused";
+
+/// While paused, imports keep arriving but nothing hands files to extraction: scheduled
+/// auto-processing and the offline queue leave them waiting until processing resumes.
+pub struct ProcessingPause;
+
+impl ProcessingPause {
+    pub fn is_paused(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, PAUSED_KEY)?.as_deref() == Some("1"))
+    }
+
+    pub fn set_paused<R: Runtime>(app: &AppHandle<R>, conn: &Connection, paused: bool) -> Result<(), String> {
+        Settings::set(conn, PAUSED_KEY, if paused { "1" } else { "0" })?;
+        app.emit(PROCESSING_PAUSED_EVENT, paused)
+            .map_err(|error| error.to_string())
+    }
+}
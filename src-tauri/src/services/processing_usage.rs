@@ -0,0 +1,328 @@
+use crate::services::report_period::ReportPeriod;
+use crate::services::settings::Settings;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Prices entered in settings, as a JSON list of `ModelPrice`; they win over the defaults.
+const PRICING_KEY: &str = "llm.pricing";
+
+/// List prices in USD per million tokens: (model prefix, input, output).
+/// Longer prefixes are listed first so `gemini-2.5-flash-lite` is not priced as `gemini-2.5-flash`.
+const MODEL_PRICING: &[(&str, f64, f64)] = &[
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("claude-haiku-4-5", 1.00, 5.00),
+    ("claude-sonnet-4", 3.00, 15.00),
+];
+
+/// USD per million tokens for models whose name starts with `model`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// Entered in settings rather than one of the bundled list prices.
+    #[serde(default)]
+    pub custom: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    pub file_id: Option<String>,
+    pub model: String,
+    pub profile: Option<String>,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub day: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBreakdown {
+    pub name: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    pub start: String,
+    pub end: String,
+    pub totals: UsageTotals,
+    /// One entry per day in the period, including days without any requests.
+    pub daily: Vec<DailyUsage>,
+    pub by_profile: Vec<UsageBreakdown>,
+    pub by_model: Vec<UsageBreakdown>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyCost {
+    /// `YYYY-MM`.
+    pub month: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    pub by_model: Vec<UsageBreakdown>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseCostSummary {
+    pub start: String,
+    pub end: String,
+    pub totals: UsageTotals,
+    /// Months with at least one request, oldest first.
+    pub months: Vec<MonthlyCost>,
+}
+
+/// Token counts and estimated cost of every extraction request.
+pub struct ProcessingUsage;
+
+impl ProcessingUsage {
+    /// Stores one request and returns its estimated cost in USD.
+    pub fn record(conn: &Connection, record: &UsageRecord) -> Result<f64, String> {
+        let cost = estimate_cost(
+            &Self::pricing(conn)?,
+            &record.model,
+            record.prompt_tokens,
+            record.output_tokens,
+        );
+        conn.execute(
+            "INSERT INTO processing_usage (file_id, model, profile, prompt_tokens, output_tokens, cost)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                record.file_id,
+                record.model,
+                record.profile.as_deref().unwrap_or("default"),
+                record.prompt_tokens.max(0),
+                record.output_tokens.max(0),
+                cost
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(cost)
+    }
+
+    pub fn summary(conn: &Connection, period: &ReportPeriod) -> Result<UsageSummary, String> {
+        let (start, end) = (period.start_iso(), period.end_iso());
+
+        let mut daily = Vec::new();
+        let grouped = grouped_totals(conn, "date(created_at)", &start, &end)?;
+        let mut day = period.start;
+        while day <= period.end {
+            let key = day.format("%Y-%m-%d").to_string();
+            let totals = grouped
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, totals)| *totals)
+                .unwrap_or_default();
+            daily.push(DailyUsage { day: key, totals });
+            day = match day.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        let breakdown = |column: &str| -> Result<Vec<UsageBreakdown>, String> {
+            let mut rows: Vec<UsageBreakdown> = grouped_totals(conn, column, &start, &end)?
+                .into_iter()
+                .map(|(name, totals)| UsageBreakdown { name, totals })
+                .collect();
+            rows.sort_by(|a, b| b.totals.cost.total_cmp(&a.totals.cost));
+            Ok(rows)
+        };
+        let by_profile = breakdown("profile")?;
+        let by_model = breakdown("model")?;
+
+        let totals = by_model.iter().fold(UsageTotals::default(), |mut sum, row| {
+            add_totals(&mut sum, &row.totals);
+            sum
+        });
+
+        Ok(UsageSummary {
+            start,
+            end,
+            totals,
+            daily,
+            by_profile,
+            by_model,
+        })
+    }
+
+    /// What each month of `period` cost, with the models that cost it.
+    pub fn cost_summary(conn: &Connection, period: &ReportPeriod) -> Result<ParseCostSummary, String> {
+        let (start, end) = (period.start_iso(), period.end_iso());
+        let mut months: Vec<MonthlyCost> = Vec::new();
+        for (group, totals) in grouped_totals(
+            conn,
+            "strftime('%Y-%m', created_at) || '|' || model",
+            &start,
+            &end,
+        )? {
+            let (month, model) = group.split_once('|').unwrap_or((group.as_str(), ""));
+            let index = match months.iter().position(|row| row.month == month) {
+                Some(index) => index,
+                None => {
+                    months.push(MonthlyCost {
+                        month: month.to_string(),
+                        totals: UsageTotals::default(),
+                        by_model: Vec::new(),
+                    });
+                    months.len() - 1
+                }
+            };
+            let row = &mut months[index];
+            add_totals(&mut row.totals, &totals);
+            row.by_model.push(UsageBreakdown {
+                name: model.to_string(),
+                totals,
+            });
+        }
+
+        months.sort_by(|a, b| a.month.cmp(&b.month));
+        let mut totals = UsageTotals::default();
+        for month in months.iter_mut() {
+            month
+                .by_model
+                .sort_by(|a, b| b.totals.cost.total_cmp(&a.totals.cost));
+            add_totals(&mut totals, &month.totals);
+        }
+        Ok(ParseCostSummary {
+            start,
+            end,
+            totals,
+            months,
+        })
+    }
+
+    /// Prices from settings followed by the bundled list prices.
+    pub fn pricing(conn: &Connection) -> Result<Vec<ModelPrice>, String> {
+        let mut pricing: Vec<ModelPrice> = match Settings::get(conn, PRICING_KEY)? {
+            Some(json) => serde_json::from_str(&json).map_err(|error| error.to_string())?,
+            None => Vec::new(),
+        };
+        pricing.iter_mut().for_each(|price| price.custom = true);
+        pricing.extend(MODEL_PRICING.iter().map(|(model, input, output)| ModelPrice {
+            model: model.to_string(),
+            input_per_million: *input,
+            output_per_million: *output,
+            custom: false,
+        }));
+        Ok(pricing)
+    }
+
+    /// Replaces the prices entered in settings; an empty list goes back to the bundled
+    /// prices. Only affects requests recorded from now on.
+    pub fn set_pricing(conn: &Connection, prices: &[ModelPrice]) -> Result<(), String> {
+        let mut stored: Vec<ModelPrice> = Vec::new();
+        for price in prices {
+            let model = price.model.trim();
+            if model.is_empty() {
+                return Err("Each price needs a model name.".to_string());
+            }
+            if !(price.input_per_million >= 0.0 && price.output_per_million >= 0.0) {
+                return Err(format!("Prices for {} cannot be negative.", model));
+            }
+            if stored.iter().any(|other| other.model == model) {
+                return Err(format!("{} is priced twice.", model));
+            }
+            stored.push(ModelPrice {
+                model: model.to_string(),
+                custom: true,
+                ..price.clone()
+            });
+        }
+        match stored.is_empty() {
+            true => Settings::remove(conn, PRICING_KEY),
+            false => Settings::set(
+                conn,
+                PRICING_KEY,
+                &serde_json::to_string(&stored).map_err(|error| error.to_string())?,
+            ),
+        }
+    }
+}
+
+fn add_totals(sum: &mut UsageTotals, totals: &UsageTotals) {
+    sum.requests += totals.requests;
+    sum.prompt_tokens += totals.prompt_tokens;
+    sum.output_tokens += totals.output_tokens;
+    sum.cost += totals.cost;
+}
+
+/// The longest matching prefix sets the price; on a tie the price from settings wins, as it
+/// comes first.
+fn estimate_cost(pricing: &[ModelPrice], model: &str, prompt_tokens: i64, output_tokens: i64) -> f64 {
+    let mut best: Option<&ModelPrice> = None;
+    for price in pricing.iter().filter(|price| model.starts_with(&price.model)) {
+        if best.is_none_or(|best| price.model.len() > best.model.len()) {
+            best = Some(price);
+        }
+    }
+    best.map(|price| {
+        (prompt_tokens.max(0) as f64 * price.input_per_million
+            + output_tokens.max(0) as f64 * price.output_per_million)
+            / 1_000_000.0
+    })
+    .unwrap_or(0.0)
+}
+
+fn grouped_totals(
+    conn: &Connection,
+    group_by: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<(String, UsageTotals)>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {group}, COUNT(*), SUM(prompt_tokens), SUM(output_tokens), SUM(cost)
+             FROM processing_usage
+             WHERE date(created_at) BETWEEN ?1 AND ?2
+             GROUP BY {group}",
+            group = group_by
+        ))
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map(params![start, end], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                UsageTotals {
+                    requests: row.get(1)?,
+                    prompt_tokens: row.get(2)?,
+                    output_tokens: row.get(3)?,
+                    cost: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(rows)
+}
@@ -0,0 +1,180 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptKind {
+    SystemInstruction,
+    UserPrompt,
+}
+
+impl PromptKind {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "system_instruction" => Ok(PromptKind::SystemInstruction),
+            "user_prompt" => Ok(PromptKind::UserPrompt),
+            other => Err(format!("Unknown prompt kind: {}", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptKind::SystemInstruction => "system_instruction",
+            PromptKind::UserPrompt => "user_prompt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub kind: String,
+    pub version: i64,
+    pub content: String,
+    pub notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+/// The prompts extraction should use right now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivePrompts {
+    pub system_instruction: String,
+    pub user_prompt: String,
+}
+
+const TEMPLATE_COLUMNS: &str = "id, kind, version, content, notes, is_active, created_at";
+
+fn template_from_row(row: &Row) -> rusqlite::Result<PromptTemplate> {
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        version: row.get(2)?,
+        content: row.get(3)?,
+        notes: row.get(4)?,
+        is_active: row.get::<_, i64>(5)? != 0,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Versioned extraction prompts. Versions are never edited or deleted: saving creates a
+/// new active version and rolling back re-activates an old one, so every prompt that
+/// ever produced parsed_details stays on record.
+pub struct PromptTemplates;
+
+impl PromptTemplates {
+    pub fn list(conn: &Connection, kind: PromptKind) -> Result<Vec<PromptTemplate>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM prompt_templates WHERE kind = ?1 ORDER BY version DESC",
+                TEMPLATE_COLUMNS
+            ))
+            .map_err(|error| error.to_string())?;
+        let templates = stmt
+            .query_map(params![kind.as_str()], template_from_row)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(templates)
+    }
+
+    pub fn get(conn: &Connection, id: i64) -> Result<PromptTemplate, String> {
+        conn.query_row(
+            &format!("SELECT {} FROM prompt_templates WHERE id = ?1", TEMPLATE_COLUMNS),
+            params![id],
+            template_from_row,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Prompt template not found: {}", id))
+    }
+
+    pub fn active(conn: &Connection, kind: PromptKind) -> Result<Option<PromptTemplate>, String> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM prompt_templates WHERE kind = ?1 AND is_active = 1",
+                TEMPLATE_COLUMNS
+            ),
+            params![kind.as_str()],
+            template_from_row,
+        )
+        .optional()
+        .map_err(|error| error.to_string())
+    }
+
+    /// Active prompts, recording `defaults` (the prompts shipped with the app) as version 1
+    /// of any kind that has no history yet.
+    pub fn active_or_seed(conn: &Connection, defaults: &ActivePrompts) -> Result<ActivePrompts, String> {
+        let resolve = |kind: PromptKind, default: &str| -> Result<String, String> {
+            match Self::active(conn, kind)? {
+                Some(template) => Ok(template.content),
+                None => {
+                    let has_history: bool = conn
+                        .query_row(
+                            "SELECT EXISTS(SELECT 1 FROM prompt_templates WHERE kind = ?1)",
+                            params![kind.as_str()],
+                            |row| row.get(0),
+                        )
+                        .map_err(|error| error.to_string())?;
+                    if !has_history {
+                        Self::save(conn, kind, default, Some("Built-in prompt"))?;
+                    }
+                    Ok(default.to_string())
+                }
+            }
+        };
+
+        Ok(ActivePrompts {
+            system_instruction: resolve(PromptKind::SystemInstruction, &defaults.system_instruction)?,
+            user_prompt: resolve(PromptKind::UserPrompt, &defaults.user_prompt)?,
+        })
+    }
+
+    /// Stores `content` as the next version of `kind` and makes it active.
+    pub fn save(
+        conn: &Connection,
+        kind: PromptKind,
+        content: &str,
+        notes: Option<&str>,
+    ) -> Result<PromptTemplate, String> {
+        if content.trim().is_empty() {
+            return Err("Prompt content cannot be empty.".to_string());
+        }
+        let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+        let version: i64 = tx
+            .query_row(
+                "SELECT COALESCE(MAX(version), 0) + 1 FROM prompt_templates WHERE kind = ?1",
+                params![kind.as_str()],
+                |row| row.get(0),
+            )
+            .map_err(|error| error.to_string())?;
+        tx.execute(
+            "UPDATE prompt_templates SET is_active = 0 WHERE kind = ?1",
+            params![kind.as_str()],
+        )
+        .map_err(|error| error.to_string())?;
+        tx.execute(
+            "INSERT INTO prompt_templates (kind, version, content, notes, is_active) VALUES (?1, ?2, ?3, ?4, 1)",
+            params![kind.as_str(), version, content, notes],
+        )
+        .map_err(|error| error.to_string())?;
+        let id = tx.last_insert_rowid();
+        tx.commit().map_err(|error| error.to_string())?;
+
+        Self::get(conn, id)
+    }
+
+    /// Makes an earlier (or later) version the active one.
+    pub fn activate(conn: &Connection, id: i64) -> Result<PromptTemplate, String> {
+        let template = Self::get(conn, id)?;
+        conn.execute(
+            "UPDATE prompt_templates SET is_active = (id = ?1) WHERE kind = ?2",
+            params![id, template.kind],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Self::get(conn, id)
+    }
+}
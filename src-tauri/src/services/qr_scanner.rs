@@ -0,0 +1,401 @@
+use crate::services::date_normalizer::DateNormalizer;
+use crate::services::document_split::tiff_pages;
+use crate::services::i18n::I18n;
+use crate::services::invoice_qr::QrPayloadKind;
+use crate::services::parsed_invoice::{
+    ParsedInvoice, BUYER_GSTIN, GRAND_TOTAL, IBAN, INVOICE_DATE, INVOICE_NUMBER, IRN, SELLER_GSTIN, UPI_ID,
+};
+use crate::services::tax_id_validator::TaxIdValidator;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, ImageFormat};
+use lopdf::{Dictionary, Document, Object, Stream};
+use rqrr::PreparedImage;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Codes embedded at one pixel per module are scaled up until they are at least this wide.
+const MIN_CODE_PIXELS: u32 = 256;
+
+/// What a QR code on the document says, with the invoice fields it carries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedQr {
+    /// `None` for codes that are not an e-invoice, UPI or EPC payload.
+    pub kind: Option<QrPayloadKind>,
+    pub content: String,
+    /// 1-based page, for PDFs and multi-page TIFFs.
+    pub page: Option<u32>,
+    pub irn: Option<String>,
+    pub seller_gstin: Option<String>,
+    pub buyer_gstin: Option<String>,
+    pub invoice_number: Option<String>,
+    pub invoice_date: Option<String>,
+    pub grand_total: Option<f64>,
+    pub item_count: Option<i64>,
+    pub upi_id: Option<String>,
+    pub iban: Option<String>,
+}
+
+/// A field where the QR payload and the extracted invoice disagree.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrMismatch {
+    pub field: String,
+    pub qr_value: String,
+    pub extracted_value: String,
+    pub message: String,
+}
+
+/// Finds and decodes QR codes on a stored document: the IRP-signed QR of Indian e-invoices
+/// and the UPI or EPC payment codes printed on Indian and EU invoices. Scanned images and
+/// PDFs with embedded raster images are read; codes drawn as vector paths are not.
+pub struct QrScanner;
+
+impl QrScanner {
+    pub fn scan_path(path: &Path) -> Result<Vec<ScannedQr>, String> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let pages: Vec<(Option<u32>, GrayImage)> = match extension.as_str() {
+            "pdf" => pdf_images(path)?,
+            "tif" | "tiff" => tiff_pages(&fs::read(path).map_err(|error| error.to_string())?)?
+                .into_iter()
+                .enumerate()
+                .map(|(index, page)| (Some(index as u32 + 1), page.to_luma8()))
+                .collect(),
+            _ => {
+                if ImageFormat::from_extension(&extension).is_none() {
+                    return Ok(Vec::new());
+                }
+                let image = image::open(path).map_err(|error| format!("Failed to read image: {}", error))?;
+                vec![(None, image.to_luma8())]
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut codes = Vec::new();
+        for (page, image) in pages {
+            for content in decode_image(&image) {
+                if seen.insert(content.clone()) {
+                    codes.push(parse_payload(content, page));
+                }
+            }
+        }
+        Ok(codes)
+    }
+
+    /// Scans the file and keeps what was found in `files.qr_codes`, so later checks can
+    /// read it without decoding the document again.
+    pub fn scan_file(conn: &Connection, file_id: &str) -> Result<Vec<ScannedQr>, String> {
+        let stored_path: String = conn
+            .query_row(
+                "SELECT stored_path FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let codes = Self::scan_path(Path::new(&stored_path))?;
+        conn.execute(
+            "UPDATE files SET qr_codes = ?1 WHERE id = ?2",
+            params![
+                serde_json::to_string(&codes).map_err(|error| error.to_string())?,
+                file_id
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(codes)
+    }
+
+    /// The codes stored for the file, or `None` when it has not been scanned yet.
+    pub fn stored(conn: &Connection, file_id: &str) -> Result<Option<Vec<ScannedQr>>, String> {
+        let stored: Option<String> = conn
+            .query_row(
+                "SELECT qr_codes FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        Ok(stored.map(|codes| serde_json::from_str(&codes).unwrap_or_default()))
+    }
+
+    /// Compares every field a QR payload carries with the extracted value. Fields missing
+    /// on either side are not compared; totals may differ by `tolerance` for rounding.
+    pub fn cross_check(codes: &[ScannedQr], invoice: &ParsedInvoice, tolerance: f64) -> Vec<QrMismatch> {
+        let mut mismatches = Vec::new();
+        for code in codes {
+            let mut compare = |field: &str, qr: &Option<String>, normalize: fn(&str) -> String| {
+                if let (Some(qr), Some(extracted)) = (qr, invoice.text(field)) {
+                    if normalize(qr) != normalize(&extracted) {
+                        mismatches.push(mismatch(field, qr.clone(), extracted));
+                    }
+                }
+            };
+            compare(IRN, &code.irn, |value| value.trim().to_lowercase());
+            compare(SELLER_GSTIN, &code.seller_gstin, TaxIdValidator::normalize);
+            compare(BUYER_GSTIN, &code.buyer_gstin, TaxIdValidator::normalize);
+            compare(INVOICE_NUMBER, &code.invoice_number, TaxIdValidator::normalize);
+            compare(UPI_ID, &code.upi_id, |value| value.trim().to_lowercase());
+            compare(IBAN, &code.iban, TaxIdValidator::normalize);
+
+            if let (Some(qr), Some(extracted)) = (&code.invoice_date, invoice.text(INVOICE_DATE)) {
+                let extracted_iso = DateNormalizer::normalize(&extracted, None).iso;
+                if extracted_iso.is_some() && extracted_iso.as_ref() != Some(qr) {
+                    mismatches.push(mismatch(INVOICE_DATE, qr.clone(), extracted));
+                }
+            }
+
+            if let (Some(qr), Some(extracted)) = (code.grand_total, invoice.number(GRAND_TOTAL)) {
+                if (qr - extracted).abs() > tolerance {
+                    mismatches.push(mismatch(
+                        GRAND_TOTAL,
+                        format!("{:.2}", qr),
+                        format!("{:.2}", extracted),
+                    ));
+                }
+            }
+        }
+        mismatches
+    }
+}
+
+fn mismatch(field: &str, qr_value: String, extracted_value: String) -> QrMismatch {
+    let message = I18n::format(
+        "qr.mismatch",
+        &[
+            ("field", field.to_string()),
+            ("qr", qr_value.clone()),
+            ("extracted", extracted_value.clone()),
+        ],
+    );
+    QrMismatch {
+        field: field.to_string(),
+        qr_value,
+        extracted_value,
+        message,
+    }
+}
+
+fn decode_image(image: &GrayImage) -> Vec<String> {
+    let shortest = image.width().min(image.height()).max(1);
+    let scaled;
+    let image = if shortest < MIN_CODE_PIXELS {
+        let factor = MIN_CODE_PIXELS.div_ceil(shortest);
+        scaled = imageops::resize(
+            image,
+            image.width() * factor,
+            image.height() * factor,
+            FilterType::Nearest,
+        );
+        &scaled
+    } else {
+        image
+    };
+
+    let mut prepared =
+        PreparedImage::prepare_from_greyscale(image.width() as usize, image.height() as usize, |x, y| {
+            image.get_pixel(x as u32, y as u32)[0]
+        });
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_, content)| content)
+        .collect()
+}
+
+/// Every raster image on every page. JPEG and Flate-compressed images are decoded; other
+/// encodings (JBIG2, JPEG 2000, CCITT) are skipped.
+pub fn pdf_images(path: &Path) -> Result<Vec<(Option<u32>, GrayImage)>, String> {
+    let document = Document::load(path).map_err(|error| format!("The PDF could not be read: {}", error))?;
+    let mut images = Vec::new();
+    for (number, page_id) in document.get_pages() {
+        let (inline, inherited) = document.get_page_resources(page_id);
+        let mut resources: Vec<&Dictionary> = inline.into_iter().collect();
+        resources.extend(
+            inherited
+                .into_iter()
+                .filter_map(|id| document.get_dictionary(id).ok()),
+        );
+        let streams = resources
+            .into_iter()
+            .filter_map(|resources| resources.get(b"XObject").ok())
+            .filter_map(|xobjects| resolve(&document, xobjects).as_dict().ok())
+            .flat_map(|xobjects| xobjects.iter())
+            .filter_map(|(_, object)| resolve(&document, object).as_stream().ok())
+            .filter(|stream| {
+                stream
+                    .dict
+                    .get(b"Subtype")
+                    .and_then(Object::as_name_str)
+                    .is_ok_and(|subtype| subtype == "Image")
+            });
+        for stream in streams {
+            if let Some(image) = decode_pdf_image(stream) {
+                images.push((Some(number), image));
+            }
+        }
+    }
+    Ok(images)
+}
+
+fn decode_pdf_image(stream: &Stream) -> Option<GrayImage> {
+    let filters = stream.filters().unwrap_or_default();
+    if filters.iter().any(|filter| filter == "DCTDecode") {
+        return image::load_from_memory_with_format(&stream.content, ImageFormat::Jpeg)
+            .ok()
+            .map(|image| image.to_luma8());
+    }
+
+    let width = stream.dict.get(b"Width").and_then(Object::as_i64).ok()? as u32;
+    let height = stream.dict.get(b"Height").and_then(Object::as_i64).ok()? as u32;
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .unwrap_or(1);
+    let data = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        // lopdf declines to decompress image streams, so decode a copy without the subtype.
+        let mut dict = stream.dict.clone();
+        dict.remove(b"Subtype");
+        Stream::new(dict, stream.content.clone())
+            .decompressed_content()
+            .ok()?
+    };
+
+    let pixels = width as usize * height as usize;
+    match bits {
+        1 => {
+            let row_bytes = (width as usize).div_ceil(8);
+            if data.len() < row_bytes * height as usize {
+                return None;
+            }
+            Some(GrayImage::from_fn(width, height, |x, y| {
+                let byte = data[y as usize * row_bytes + x as usize / 8];
+                image::Luma([if byte & (0x80 >> (x % 8)) != 0 { 255 } else { 0 }])
+            }))
+        }
+        8 if pixels > 0 => {
+            let image = match data.len() / pixels {
+                1 => DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, data[..pixels].to_vec())?),
+                3 => DynamicImage::ImageRgb8(image::RgbImage::from_raw(
+                    width,
+                    height,
+                    data[..pixels * 3].to_vec(),
+                )?),
+                4 => {
+                    // DeviceCMYK: darkness is the sum of the inks.
+                    let gray = data
+                        .chunks_exact(4)
+                        .take(pixels)
+                        .map(|cmyk| 255 - (cmyk.iter().map(|ink| *ink as u32).sum::<u32>().min(255)) as u8)
+                        .collect();
+                    DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, gray)?)
+                }
+                _ => return None,
+            };
+            Some(image.to_luma8())
+        }
+        _ => None,
+    }
+}
+
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => document.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+fn parse_payload(content: String, page: Option<u32>) -> ScannedQr {
+    let mut code = ScannedQr {
+        kind: None,
+        content,
+        page,
+        irn: None,
+        seller_gstin: None,
+        buyer_gstin: None,
+        invoice_number: None,
+        invoice_date: None,
+        grand_total: None,
+        item_count: None,
+        upi_id: None,
+        iban: None,
+    };
+    let content = code.content.trim().to_string();
+
+    if let Some(data) = signed_invoice_data(&content) {
+        let text = |key: &str| data.get(key).and_then(Value::as_str).map(str::to_string);
+        code.kind = Some(QrPayloadKind::EInvoice);
+        code.irn = text("Irn");
+        code.seller_gstin = text("SellerGstin");
+        code.buyer_gstin = text("BuyerGstin");
+        code.invoice_number = text("DocNo");
+        // The IRP writes dates as dd/mm/yyyy.
+        code.invoice_date =
+            text("DocDt").and_then(|date| DateNormalizer::normalize(&date, Some("en-IN")).iso);
+        code.grand_total = data.get("TotInvVal").and_then(Value::as_f64);
+        code.item_count = data.get("ItemCnt").and_then(Value::as_i64);
+    } else if let Some(query) = content
+        .get(..10)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("upi://pay?"))
+        .map(|_| &content[10..])
+    {
+        code.kind = Some(QrPayloadKind::Upi);
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = urlencoding::decode(value)
+                .map(|value| value.into_owned())
+                .unwrap_or_else(|_| value.to_string());
+            match key.to_ascii_lowercase().as_str() {
+                "pa" => code.upi_id = Some(value),
+                "am" => code.grand_total = value.parse().ok(),
+                _ => {}
+            }
+        }
+    } else if content.starts_with("BCD\n") || content.starts_with("BCD\r\n") {
+        // EPC069-12: BCD, version, charset, SCT, BIC, name, IBAN, amount, then remittance lines.
+        let lines: Vec<&str> = content.lines().map(str::trim).collect();
+        code.kind = Some(QrPayloadKind::Epc);
+        code.iban = lines
+            .get(6)
+            .filter(|iban| !iban.is_empty())
+            .map(|iban| iban.to_string());
+        code.grand_total = lines
+            .get(7)
+            .and_then(|amount| amount.strip_prefix("EUR"))
+            .and_then(|amount| amount.parse().ok());
+    }
+    code
+}
+
+/// The invoice summary inside an IRP-signed QR: a JWT whose payload's `data` claim is itself
+/// a JSON document. The signature is not verified here.
+fn signed_invoice_data(content: &str) -> Option<Value> {
+    let mut parts = content.split('.');
+    let (_, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let claims: Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?).ok()?;
+    let data = claims.get("data")?;
+    let data = match data {
+        Value::String(text) => serde_json::from_str(text).ok()?,
+        other => other.clone(),
+    };
+    data.get("Irn").is_some().then_some(data)
+}
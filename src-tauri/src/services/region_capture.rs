@@ -0,0 +1,193 @@
+use crate::services::document_split::tiff_pages;
+use crate::services::llm_extraction::LlmExtraction;
+use crate::services::llm_provider::{ExtractionRequest, LlmProviders};
+use crate::services::ocr_languages::OcrLanguages;
+use crate::services::parse_cancellation::CancelToken;
+use crate::services::parsed_invoice::{text_value, BoundingBox};
+use crate::services::processing_usage::{ProcessingUsage, UsageRecord};
+use crate::services::qr_scanner::pdf_images;
+use crate::services::table_extraction::TableExtractor;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, ImageFormat};
+use lopdf::Document;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Narrow snips are scaled up to at least this width before they are read, so a single
+/// number still has legible glyphs.
+const MIN_CROP_PIXELS: u32 = 600;
+const SYSTEM_INSTRUCTION: &str = "You transcribe text from a cropped region of an invoice. \
+     Answer with JSON only.";
+const PROMPT: &str = "The image is a region a user selected on an invoice page. Transcribe every \
+     piece of text in it exactly as printed, line by line, top to bottom, without correcting, \
+     translating or reformatting it. Answer as {\"text\": \"...\"}, with an empty string when the \
+     region holds no text.";
+
+/// Where the text of a region was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionSource {
+    /// The PDF's own text, taken as it is.
+    TextLayer,
+    /// A crop of the page image, read by the extraction model.
+    Ocr,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionText {
+    pub file_id: String,
+    pub page: u32,
+    pub rect: BoundingBox,
+    pub text: String,
+    pub source: RegionSource,
+    /// Estimated cost in USD of the model request, 0 for text-layer reads.
+    pub cost: f64,
+}
+
+/// Reads one rectangle of a page, for fields the automatic extraction keeps missing. PDFs
+/// with a text layer are read directly; scanned pages and photos are cropped and only the
+/// crop is sent to the extraction provider, which is cheaper and more reliable than
+/// re-reading the whole document. The caller stores the text as a correction or uses it
+/// as a template anchor.
+pub struct RegionCapture;
+
+impl RegionCapture {
+    /// `page` is 1-based; `rect` is in fractions of the page, measured from the top left,
+    /// like field locations.
+    pub fn extract(
+        conn: &Connection,
+        file_id: &str,
+        page: u32,
+        rect: BoundingBox,
+    ) -> Result<RegionText, String> {
+        let valid =
+            |min: f64, max: f64| (0.0..=1.0).contains(&min) && (0.0..=1.0).contains(&max) && min < max;
+        if !valid(rect.x_min, rect.x_max) || !valid(rect.y_min, rect.y_max) {
+            return Err("Select a region inside the page.".to_string());
+        }
+        if page == 0 {
+            return Err("Pages are numbered from 1.".to_string());
+        }
+
+        let (file_name, stored_path): (String, String) = conn
+            .query_row(
+                "SELECT file_name, stored_path FROM files WHERE id = ?1",
+                params![file_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let path = Path::new(&stored_path);
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+
+        let result = |text: String, source: RegionSource, cost: f64| RegionText {
+            file_id: file_id.to_string(),
+            page,
+            rect,
+            text,
+            source,
+            cost,
+        };
+
+        let page_image = match extension.as_str() {
+            "pdf" => {
+                let document =
+                    Document::load(path).map_err(|error| format!("The PDF could not be read: {}", error))?;
+                if let Some(text) = TableExtractor::text_in_area(&document, page, &rect)? {
+                    if !text.trim().is_empty() {
+                        return Ok(result(text, RegionSource::TextLayer, 0.0));
+                    }
+                }
+                // A scanned page is one image covering the page; smaller ones are logos and
+                // stamps.
+                pdf_images(path)?
+                    .into_iter()
+                    .filter(|(image_page, _)| *image_page == Some(page))
+                    .map(|(_, image)| image)
+                    .max_by_key(|image| image.width() as u64 * image.height() as u64)
+                    .map(DynamicImage::ImageLuma8)
+            }
+            "tif" | "tiff" => tiff_pages(&std::fs::read(path).map_err(|error| error.to_string())?)?
+                .into_iter()
+                .nth(page as usize - 1),
+            _ => {
+                if ImageFormat::from_extension(&extension).is_none() {
+                    return Err("Regions can only be read from PDFs and images.".to_string());
+                }
+                if page != 1 {
+                    return Err(format!("The document has no page {}.", page));
+                }
+                Some(image::open(path).map_err(|error| format!("Failed to read image: {}", error))?)
+            }
+        };
+        let page_image = page_image.ok_or_else(|| format!("Page {} has no text or image to read.", page))?;
+
+        let crop = crop(&page_image.to_luma8(), &rect);
+        let mut png = Vec::new();
+        DynamicImage::ImageLuma8(crop)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|error| error.to_string())?;
+
+        let kind = LlmProviders::configured(conn)?;
+        let client = LlmProviders::connect(conn, kind)?;
+        let mut prompt = PROMPT.to_string();
+        if let Some(hint) = OcrLanguages::extraction_hint(conn, file_id)? {
+            prompt = format!("{}\n\n{}", prompt, hint);
+        }
+        let request = ExtractionRequest {
+            system_instruction: SYSTEM_INSTRUCTION,
+            prompt: &prompt,
+            document: &png,
+            mime_type: "image/png",
+            file_name: &format!("{} (page {} region).png", file_name, page),
+            response_schema: None,
+            temperature: 0.0,
+        };
+        let (response, _) =
+            LlmExtraction::run(conn, Some(file_id), &client, &request, &CancelToken::default())?;
+        let cost = ProcessingUsage::record(
+            conn,
+            &UsageRecord {
+                file_id: Some(file_id.to_string()),
+                model: client.model().to_string(),
+                profile: None,
+                prompt_tokens: response.prompt_tokens,
+                output_tokens: response.output_tokens,
+            },
+        )?;
+        let text = response
+            .output
+            .get("text")
+            .and_then(text_value)
+            .unwrap_or_default();
+        Ok(result(text, RegionSource::Ocr, cost))
+    }
+}
+
+/// The part of `page` inside `rect`, scaled up when it is narrower than `MIN_CROP_PIXELS`.
+fn crop(page: &GrayImage, rect: &BoundingBox) -> GrayImage {
+    let (width, height) = (page.width() as f64, page.height() as f64);
+    let x = (rect.x_min * width).floor() as u32;
+    let y = (rect.y_min * height).floor() as u32;
+    let crop_width = ((rect.x_max * width).ceil() as u32).saturating_sub(x).max(1);
+    let crop_height = ((rect.y_max * height).ceil() as u32).saturating_sub(y).max(1);
+    let region = imageops::crop_imm(page, x, y, crop_width, crop_height).to_image();
+    if region.width() >= MIN_CROP_PIXELS {
+        return region;
+    }
+    let scale = MIN_CROP_PIXELS as f64 / region.width() as f64;
+    imageops::resize(
+        &region,
+        MIN_CROP_PIXELS,
+        ((region.height() as f64 * scale).round() as u32).max(1),
+        FilterType::CatmullRom,
+    )
+}
@@ -0,0 +1,77 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Inclusive date range a report is built for.
+#[derive(Debug, Clone)]
+pub struct ReportPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl ReportPeriod {
+    /// Accepts a month ("2024-07"), quarter ("2024-Q2"), year ("2024")
+    /// or explicit range ("2024-04-01..2024-06-30").
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let trimmed = value.trim();
+        let invalid = || {
+            format!(
+                "Unsupported period \"{}\". Use YYYY, YYYY-MM, YYYY-Qn or YYYY-MM-DD..YYYY-MM-DD.",
+                value
+            )
+        };
+
+        if let Some((from, to)) = trimmed.split_once("..") {
+            let start = NaiveDate::parse_from_str(from.trim(), "%Y-%m-%d").map_err(|_| invalid())?;
+            let end = NaiveDate::parse_from_str(to.trim(), "%Y-%m-%d").map_err(|_| invalid())?;
+            if end < start {
+                return Err(format!("Period end {} is before its start {}.", end, start));
+            }
+            return Ok(Self { start, end });
+        }
+
+        let mut parts = trimmed.splitn(2, '-');
+        let year: i32 = parts
+            .next()
+            .filter(|part| part.len() == 4)
+            .and_then(|part| part.parse().ok())
+            .ok_or_else(invalid)?;
+
+        let (first_month, months) = match parts.next() {
+            None => (1, 12),
+            Some(part) if part.starts_with(['Q', 'q']) => {
+                let quarter: u32 = part[1..].parse().map_err(|_| invalid())?;
+                if !(1..=4).contains(&quarter) {
+                    return Err(invalid());
+                }
+                ((quarter - 1) * 3 + 1, 3)
+            }
+            Some(part) => {
+                let month: u32 = part.parse().map_err(|_| invalid())?;
+                if !(1..=12).contains(&month) {
+                    return Err(invalid());
+                }
+                (month, 1)
+            }
+        };
+
+        let start = NaiveDate::from_ymd_opt(year, first_month, 1).ok_or_else(invalid)?;
+        let end = start
+            .checked_add_months(chrono::Months::new(months))
+            .and_then(|next| next.pred_opt())
+            .ok_or_else(invalid)?;
+
+        Ok(Self { start, end })
+    }
+
+    pub fn start_iso(&self) -> String {
+        self.start.format("%Y-%m-%d").to_string()
+    }
+
+    pub fn end_iso(&self) -> String {
+        self.end.format("%Y-%m-%d").to_string()
+    }
+
+    /// Return period in the GSTN "MMYYYY" notation, taken from the last month of the range.
+    pub fn gst_return_period(&self) -> String {
+        format!("{:02}{}", self.end.month(), self.end.year())
+    }
+}
@@ -0,0 +1,277 @@
+use crate::commands::file_operations::store_parsed_details;
+use crate::services::auto_approval::AutoApproval;
+use crate::services::parsed_history::DetailsSource;
+use crate::services::parsed_invoice::FIELD_LOCATIONS;
+use crate::services::parsed_schema::ParsedSchema;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldChoice {
+    Current,
+    Candidate,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDifference {
+    pub field: String,
+    pub current: Value,
+    pub candidate: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionOutcome {
+    /// `applied`, `unchanged` or `conflict`.
+    pub status: &'static str,
+    pub differences: Vec<FieldDifference>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReprocessConflict {
+    pub file_id: String,
+    pub file_name: String,
+    pub created_at: String,
+    pub differences: Vec<FieldDifference>,
+}
+
+/// Keeps reviewed data safe from reprocessing. A new extraction for a file that already
+/// has details is held as a candidate until someone picks, field by field, which value wins.
+pub struct ReprocessConflicts;
+
+impl ReprocessConflicts {
+    /// Saves an extraction result. It is written straight away when the file has no usable
+    /// details yet; otherwise any difference is parked as a candidate for review.
+    pub fn submit(conn: &Connection, file_id: &str, candidate: &str) -> Result<ExtractionOutcome, String> {
+        let current = current_details(conn, file_id)?;
+        // Compared at the same schema version as the current details.
+        let candidate = &ParsedSchema::upgrade(candidate);
+        let candidate_fields = match serde_json::from_str::<Value>(candidate) {
+            Ok(Value::Object(fields)) => fields,
+            _ => return Err("The extraction result is not a JSON object.".to_string()),
+        };
+
+        let Some(current_fields) = current else {
+            store_parsed_details(conn, file_id, candidate, DetailsSource::Extraction)?;
+            Self::discard(conn, file_id)?;
+            AutoApproval::apply(conn, file_id)?;
+            return Ok(ExtractionOutcome {
+                status: "applied",
+                differences: Vec::new(),
+            });
+        };
+
+        let differences = diff(&current_fields, &candidate_fields);
+        // The caller marked the file Processed; held details stay held.
+        AutoApproval::apply(conn, file_id)?;
+        if differences.is_empty() {
+            Self::discard(conn, file_id)?;
+            return Ok(ExtractionOutcome {
+                status: "unchanged",
+                differences,
+            });
+        }
+
+        conn.execute(
+            "INSERT INTO reprocess_candidates (file_id, candidate) VALUES (?1, ?2)
+             ON CONFLICT(file_id) DO UPDATE SET candidate = excluded.candidate, created_at = CURRENT_TIMESTAMP",
+            params![file_id, candidate],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(ExtractionOutcome {
+            status: "conflict",
+            differences,
+        })
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<ReprocessConflict>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT r.file_id FROM reprocess_candidates r
+                 JOIN files f ON f.id = r.file_id
+                 ORDER BY r.created_at DESC",
+            )
+            .map_err(|error| error.to_string())?;
+        let file_ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut conflicts = Vec::new();
+        for file_id in file_ids {
+            if let Some(conflict) = Self::get(conn, &file_id)? {
+                conflicts.push(conflict);
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// The pending candidate for a file, diffed against its details as they are now.
+    pub fn get(conn: &Connection, file_id: &str) -> Result<Option<ReprocessConflict>, String> {
+        let Some(candidate) = load_candidate(conn, file_id)? else {
+            return Ok(None);
+        };
+        let current = current_details(conn, file_id)?.unwrap_or_default();
+        Ok(Some(ReprocessConflict {
+            file_id: file_id.to_string(),
+            file_name: candidate.file_name,
+            created_at: candidate.created_at,
+            differences: diff(&current, &candidate.fields),
+        }))
+    }
+
+    /// Merges the candidate into the current details. Fields without a choice keep their
+    /// current value. Returns the details that were saved.
+    pub fn resolve(
+        conn: &Connection,
+        file_id: &str,
+        choices: &HashMap<String, FieldChoice>,
+    ) -> Result<String, String> {
+        let candidate = load_candidate(conn, file_id)?
+            .ok_or_else(|| format!("No reprocessing conflict is pending for file {}", file_id))?;
+        let mut merged = current_details(conn, file_id)?.unwrap_or_default();
+
+        for (field, choice) in choices {
+            if *choice == FieldChoice::Current || field == FIELD_LOCATIONS {
+                continue;
+            }
+            match candidate.fields.get(field) {
+                Some(value) => {
+                    merged.insert(field.clone(), value.clone());
+                }
+                None => {
+                    merged.remove(field);
+                }
+            }
+        }
+
+        merge_locations(&mut merged, &candidate.fields, choices);
+
+        let details = Value::Object(merged).to_string();
+        store_parsed_details(conn, file_id, &details, DetailsSource::ConflictResolution)?;
+        Self::discard(conn, file_id)?;
+        Ok(details)
+    }
+
+    pub fn discard(conn: &Connection, file_id: &str) -> Result<(), String> {
+        conn.execute(
+            "DELETE FROM reprocess_candidates WHERE file_id = ?1",
+            params![file_id],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
+
+/// Stored details worth protecting: a JSON object that is not a failure record.
+fn current_details(conn: &Connection, file_id: &str) -> Result<Option<Map<String, Value>>, String> {
+    let stored: Option<Option<String>> = conn
+        .query_row(
+            "SELECT parsed_details FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?;
+    let stored = stored.ok_or_else(|| format!("File not found: {}", file_id))?;
+
+    Ok(stored
+        .and_then(|raw| serde_json::from_str::<Value>(&ParsedSchema::upgrade(&raw)).ok())
+        .and_then(|value| match value {
+            Value::Object(fields) if !fields.contains_key("error") && !fields.contains_key("_raw") => {
+                Some(fields)
+            }
+            _ => None,
+        }))
+}
+
+struct Candidate {
+    file_name: String,
+    fields: Map<String, Value>,
+    created_at: String,
+}
+
+fn load_candidate(conn: &Connection, file_id: &str) -> Result<Option<Candidate>, String> {
+    let row: Option<(String, String, String)> = conn
+        .query_row(
+            "SELECT f.file_name, r.candidate, r.created_at FROM reprocess_candidates r
+             JOIN files f ON f.id = r.file_id
+             WHERE r.file_id = ?1",
+            params![file_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?;
+
+    Ok(row.map(|(file_name, candidate, created_at)| Candidate {
+        file_name,
+        fields: match serde_json::from_str(&candidate) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        },
+        created_at,
+    }))
+}
+
+/// Keeps field locations in step with the merged values: a field taken from the candidate
+/// takes its locations along. Line item locations follow the `items` choice.
+fn merge_locations(
+    merged: &mut Map<String, Value>,
+    candidate: &Map<String, Value>,
+    choices: &HashMap<String, FieldChoice>,
+) {
+    let from_candidate = |location: &Value| {
+        let field = location.get("field").and_then(Value::as_str).unwrap_or_default();
+        let field = field.split_once('[').map_or(field, |(key, _)| key);
+        choices.get(field) == Some(&FieldChoice::Candidate)
+    };
+    let locations = |fields: &Map<String, Value>| -> Vec<Value> {
+        match fields.get(FIELD_LOCATIONS) {
+            Some(Value::Array(locations)) => locations.clone(),
+            _ => Vec::new(),
+        }
+    };
+
+    let mut kept: Vec<Value> = locations(merged)
+        .into_iter()
+        .filter(|location| !from_candidate(location))
+        .collect();
+    kept.extend(locations(candidate).into_iter().filter(from_candidate));
+    if kept.is_empty() {
+        merged.remove(FIELD_LOCATIONS);
+    } else {
+        merged.insert(FIELD_LOCATIONS.to_string(), Value::Array(kept));
+    }
+}
+
+/// Top-level fields that differ; line items are compared as a whole. Field locations are
+/// not reviewable values and never count as a difference.
+fn diff(current: &Map<String, Value>, candidate: &Map<String, Value>) -> Vec<FieldDifference> {
+    let mut fields: Vec<&String> = current
+        .keys()
+        .chain(candidate.keys())
+        .filter(|field| *field != FIELD_LOCATIONS)
+        .collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let current = current.get(field).cloned().unwrap_or(Value::Null);
+            let candidate = candidate.get(field).cloned().unwrap_or(Value::Null);
+            (current != candidate).then(|| FieldDifference {
+                field: field.clone(),
+                current,
+                candidate,
+            })
+        })
+        .collect()
+}
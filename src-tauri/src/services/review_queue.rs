@@ -0,0 +1,47 @@
+use rusqlite::{params, Connection};
+use serde_json::Value;
+
+pub struct NewReviewFlag {
+    pub field: Option<String>,
+    pub message: String,
+    pub details: Option<Value>,
+}
+
+pub struct ReviewQueue;
+
+impl ReviewQueue {
+    /// Replaces the open flags a validation `source` raised for a file with a fresh set.
+    pub fn replace_flags(
+        conn: &Connection,
+        file_id: &str,
+        source: &str,
+        flags: &[NewReviewFlag],
+    ) -> Result<(), String> {
+        conn.execute(
+            "DELETE FROM review_flags WHERE file_id = ?1 AND source = ?2 AND resolved_at IS NULL",
+            params![file_id, source],
+        )
+        .map_err(|error| error.to_string())?;
+
+        for flag in flags {
+            let details = flag.details.as_ref().map(Value::to_string);
+            conn.execute(
+                "INSERT INTO review_flags (file_id, source, field, message, details)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![file_id, source, flag.field, flag.message, details],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn has_open_flags(conn: &Connection, file_id: &str, source: &str) -> Result<bool, String> {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM review_flags WHERE file_id = ?1 AND source = ?2 AND resolved_at IS NULL)",
+            params![file_id, source],
+            |row| row.get(0),
+        )
+        .map_err(|error| error.to_string())
+    }
+}
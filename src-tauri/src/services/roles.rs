@@ -0,0 +1,54 @@
+use crate::services::{app_lock::AppLock, i18n::I18n, settings::Settings};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const ACTIVE_ROLE_KEY: &str = "security.active_role";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    Admin,
+    Operator,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "Admin",
+            Role::Operator => "Operator",
+        }
+    }
+}
+
+/// Backend-enforced roles. Operators can import and process files; deleting,
+/// purging and changing settings needs the admin role.
+pub struct Roles;
+
+impl Roles {
+    /// Installs without a stored role behave as before and run as admin.
+    pub fn current(conn: &Connection) -> Result<Role, String> {
+        Ok(match Settings::get(conn, ACTIVE_ROLE_KEY)?.as_deref() {
+            Some("Operator") => Role::Operator,
+            _ => Role::Admin,
+        })
+    }
+
+    /// Switching to operator is always allowed; switching to admin needs the app PIN
+    /// when one is configured.
+    pub fn switch(conn: &Connection, role: Role, pin: Option<&str>) -> Result<(), String> {
+        if role == Role::Admin {
+            AppLock::require(conn, pin)?;
+        }
+        Settings::set(conn, ACTIVE_ROLE_KEY, role.as_str())
+    }
+
+    /// `action` is an i18n key such as `action.delete_files`, used in the error message.
+    pub fn require_admin(conn: &Connection, action: &'static str) -> Result<(), String> {
+        match Self::current(conn)? {
+            Role::Admin => Ok(()),
+            Role::Operator => Err(I18n::format(
+                "roles.admin_required",
+                &[("action", I18n::text(action).to_string())],
+            )),
+        }
+    }
+}
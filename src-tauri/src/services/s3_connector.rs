@@ -0,0 +1,274 @@
+use crate::services::cloud_connector::{read_limited, CloudConnector, CloudFilePage};
+use crate::services::cloud_drive::CloudFile;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+const EMPTY_PAYLOAD_SHA256: &str = "e3b0c44298fc1c149afbfc4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// A bucket prefix read with an access key, signed with AWS Signature Version 4. Works with
+/// S3 itself and with S3-compatible stores through a custom endpoint.
+pub struct S3Connector {
+    agent: ureq::Agent,
+    access_key_id: String,
+    secret_access_key: String,
+    region: String,
+    bucket: String,
+    /// Key prefix of the "folder", ending in `/` unless it is the bucket root.
+    prefix: String,
+    /// Host of an S3-compatible store, addressed path-style. AWS buckets use virtual-hosted
+    /// addressing instead.
+    endpoint: Option<String>,
+    /// `http` only when the endpoint was given with that scheme, e.g. a local MinIO.
+    scheme: &'static str,
+}
+
+impl S3Connector {
+    /// `folder` is `bucket` or `bucket/prefix`.
+    pub fn new(
+        access_key_id: &str,
+        secret_access_key: &str,
+        region: &str,
+        folder: &str,
+        endpoint: Option<&str>,
+    ) -> Result<Self, String> {
+        let (bucket, prefix) = folder
+            .trim()
+            .trim_matches('/')
+            .split_once('/')
+            .map(|(bucket, prefix)| (bucket.to_string(), format!("{}/", prefix.trim_matches('/'))))
+            .unwrap_or_else(|| (folder.trim().trim_matches('/').to_string(), String::new()));
+        if bucket.is_empty() {
+            return Err("Enter the bucket to import from.".to_string());
+        }
+        if access_key_id.trim().is_empty() || secret_access_key.trim().is_empty() {
+            return Err("An access key id and secret are required.".to_string());
+        }
+        let region = match region.trim() {
+            "" => "us-east-1",
+            region => region,
+        };
+        let endpoint = endpoint.map(str::trim).unwrap_or_default();
+        let (scheme, host) = match endpoint.strip_prefix("http://") {
+            Some(host) => ("http", host),
+            None => ("https", endpoint.trim_start_matches("https://")),
+        };
+        let endpoint = Some(host.trim_end_matches('/').to_string()).filter(|host| !host.is_empty());
+
+        Ok(S3Connector {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .build(),
+            access_key_id: access_key_id.trim().to_string(),
+            secret_access_key: secret_access_key.trim().to_string(),
+            region: region.to_string(),
+            bucket,
+            prefix,
+            endpoint,
+            scheme,
+        })
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// The endpoint with its scheme, as it should be stored to reconnect.
+    pub fn endpoint(&self) -> Option<String> {
+        self.endpoint
+            .as_ref()
+            .map(|host| format!("{}://{}", self.scheme, host))
+    }
+
+    /// Host and path of `key` (empty for the bucket itself).
+    fn location(&self, key: &str) -> (String, String) {
+        let key = key
+            .split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        match &self.endpoint {
+            Some(endpoint) => (endpoint.clone(), format!("/{}/{}", self.bucket, key)),
+            None => (
+                format!("{}.s3.{}.amazonaws.com", self.bucket, self.region),
+                format!("/{}", key),
+            ),
+        }
+    }
+
+    /// Signed GET of `key` with the given query parameters.
+    fn get(&self, key: &str, query: &[(&str, &str)]) -> Result<ureq::Response, String> {
+        let (host, path) = self.location(key);
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let mut pairs: Vec<(String, String)> = query
+            .iter()
+            .map(|(key, value)| {
+                (
+                    urlencoding::encode(key).into_owned(),
+                    urlencoding::encode(value).into_owned(),
+                )
+            })
+            .collect();
+        pairs.sort();
+        let canonical_query = pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "GET\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            path, canonical_query, host, EMPTY_PAYLOAD_SHA256, amz_date, signed_headers, EMPTY_PAYLOAD_SHA256
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+            amz_date,
+            scope,
+            Sha256::digest(canonical_request.as_bytes())
+        );
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac(&key, part.as_bytes()),
+        );
+        let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+        let mut url = format!("{}://{}{}", self.scheme, host, path);
+        if !canonical_query.is_empty() {
+            url = format!("{}?{}", url, canonical_query);
+        }
+        self.agent
+            .get(&url)
+            .set("x-amz-date", &amz_date)
+            .set("x-amz-content-sha256", EMPTY_PAYLOAD_SHA256)
+            .set(
+                "Authorization",
+                &format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            )
+            .call()
+            .map_err(request_error)
+    }
+}
+
+impl CloudConnector for S3Connector {
+    /// The object in the AWS console, or an `s3://` URI for S3-compatible stores.
+    fn source_url(&self, file: &CloudFile) -> String {
+        match self.endpoint {
+            Some(_) => format!("s3://{}/{}", self.bucket, file.remote_id),
+            None => format!(
+                "https://s3.console.aws.amazon.com/s3/object/{}?region={}&prefix={}",
+                self.bucket,
+                self.region,
+                urlencoding::encode(&file.remote_id)
+            ),
+        }
+    }
+
+    fn list_page(&self, cursor: Option<&str>) -> Result<CloudFilePage, String> {
+        let mut query = vec![
+            ("list-type", "2"),
+            ("delimiter", "/"),
+            ("max-keys", "1000"),
+            ("prefix", self.prefix.as_str()),
+        ];
+        if let Some(cursor) = cursor {
+            query.push(("continuation-token", cursor));
+        }
+        let body = self
+            .get("", &query)?
+            .into_string()
+            .map_err(|error| error.to_string())?;
+
+        let files = elements(&body, "Contents")
+            .into_iter()
+            .filter_map(|contents| {
+                let key = element_text(contents, "Key")?;
+                let name = key.rsplit('/').next().unwrap_or_default().to_string();
+                // Zero-byte "folder" placeholders end in a slash.
+                if name.is_empty() {
+                    return None;
+                }
+                Some(CloudFile {
+                    name,
+                    size_bytes: element_text(contents, "Size")
+                        .and_then(|size| size.parse().ok())
+                        .unwrap_or(0),
+                    modified_at: element_text(contents, "LastModified"),
+                    remote_id: key,
+                    imported: false,
+                })
+            })
+            .collect();
+        let next_cursor = if element_text(&body, "IsTruncated").as_deref() == Some("true") {
+            element_text(&body, "NextContinuationToken")
+        } else {
+            None
+        };
+        Ok(CloudFilePage { files, next_cursor })
+    }
+
+    fn download(&self, file: &CloudFile) -> Result<Vec<u8>, String> {
+        read_limited(self.get(&file.remote_id, &[])?.into_reader())
+    }
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Inner XML of every `<tag>` element, in document order. ListObjectsV2 responses are flat
+/// enough that this avoids pulling in an XML parser.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else {
+            break;
+        };
+        found.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    elements(xml, tag).first().map(|text| {
+        text.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    })
+}
+
+fn request_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(401, _) | ureq::Error::Status(403, _) => {
+            "The bucket refused access. Check the access key and its permissions.".to_string()
+        }
+        ureq::Error::Status(404, _) => "The bucket or object was not found.".to_string(),
+        ureq::Error::Status(301, _) => "The bucket is in a different region.".to_string(),
+        other => format!("S3 request failed: {}", other),
+    }
+}
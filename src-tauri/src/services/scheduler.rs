@@ -0,0 +1,621 @@
+use crate::commands::report_operations::render_pdf_report;
+use crate::commands::xml_operations::create_xml_for_files;
+use crate::db::{backups_dir, get_connection, reports_dir};
+use crate::services::cloud_drive::CloudDrive;
+use crate::services::cron::CronExpression;
+use crate::services::database_backup::DatabaseBackup;
+use crate::services::export_bundle::{BundleFormat, ExportBundle};
+use crate::services::export_manifest::ExportManifests;
+use crate::services::notifier::{JobEvent, Notifier};
+use crate::services::processing_pause::ProcessingPause;
+use crate::services::report_period::ReportPeriod;
+use crate::services::telemetry::Telemetry;
+use crate::services::watcher::FolderWatcher;
+use crate::services::workspace_lock::WorkspaceLock;
+use chrono::{DateTime, Datelike, Local, Months, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Event asking the frontend to run the processing pipeline over pending files.
+pub const AUTO_PROCESS_EVENT: &str = "scheduler://auto-process";
+/// Event asking the frontend to render a scheduled XML export; carries a [`ScheduledXmlExport`].
+pub const SCHEDULED_XML_EVENT: &str = "scheduler://xml-export";
+
+const RUN_HISTORY_LIMIT: i64 = 200;
+/// Timestamped backups a backup schedule keeps when its options do not say.
+const DEFAULT_BACKUPS_KEPT: u64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledJob {
+    Backup,
+    AutoProcess,
+    WatchedFolderScan,
+    ReportGeneration,
+    ExportGeneration,
+    CloudDriveSync,
+}
+
+impl ScheduledJob {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "backup" => Ok(ScheduledJob::Backup),
+            "auto_process" => Ok(ScheduledJob::AutoProcess),
+            "watched_folder_scan" => Ok(ScheduledJob::WatchedFolderScan),
+            "report_generation" => Ok(ScheduledJob::ReportGeneration),
+            "export_generation" => Ok(ScheduledJob::ExportGeneration),
+            "cloud_drive_sync" => Ok(ScheduledJob::CloudDriveSync),
+            other => Err(format!("Unknown scheduled job: {}", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledJob::Backup => "backup",
+            ScheduledJob::AutoProcess => "auto_process",
+            ScheduledJob::WatchedFolderScan => "watched_folder_scan",
+            ScheduledJob::ReportGeneration => "report_generation",
+            ScheduledJob::ExportGeneration => "export_generation",
+            ScheduledJob::CloudDriveSync => "cloud_drive_sync",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Succeeded => "Succeeded",
+            RunStatus::Failed => "Failed",
+            RunStatus::Skipped => "Skipped",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub id: i64,
+    pub name: String,
+    pub job: String,
+    pub cron: String,
+    pub enabled: bool,
+    pub options: Option<Value>,
+    pub last_run_at: Option<String>,
+    pub next_run_at: Option<String>,
+    pub updated_at: String,
+}
+
+/// The Tally XML is built by the frontend, so scheduled XML exports are handed over to it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledXmlExport {
+    pub xml_id: i64,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub id: i64,
+    pub schedule_id: i64,
+    pub schedule_name: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: String,
+    pub message: Option<String>,
+}
+
+/// Fields of a schedule that can be changed; `None` leaves the stored value as is.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleUpdate {
+    pub name: Option<String>,
+    pub cron: Option<String>,
+    pub enabled: Option<bool>,
+    pub options: Option<Value>,
+}
+
+const SCHEDULE_COLUMNS: &str = "id, name, job, cron, enabled, options, last_run_at, next_run_at, updated_at";
+
+fn schedule_from_row(row: &Row) -> rusqlite::Result<Schedule> {
+    let options: Option<String> = row.get(5)?;
+    Ok(Schedule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        job: row.get(2)?,
+        cron: row.get(3)?,
+        enabled: row.get::<_, i64>(4)? != 0,
+        options: options.and_then(|raw| serde_json::from_str(&raw).ok()),
+        last_run_at: row.get(6)?,
+        next_run_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+pub struct Scheduler;
+
+impl Scheduler {
+    pub fn list(conn: &Connection) -> Result<Vec<Schedule>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM schedules ORDER BY name",
+                SCHEDULE_COLUMNS
+            ))
+            .map_err(|error| error.to_string())?;
+        let schedules = stmt
+            .query_map([], schedule_from_row)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(schedules)
+    }
+
+    pub fn get(conn: &Connection, id: i64) -> Result<Schedule, String> {
+        conn.query_row(
+            &format!("SELECT {} FROM schedules WHERE id = ?1", SCHEDULE_COLUMNS),
+            params![id],
+            schedule_from_row,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Schedule not found: {}", id))
+    }
+
+    /// Enables or disables every schedule running `job`.
+    pub fn set_job_enabled(conn: &Connection, job: ScheduledJob, enabled: bool) -> Result<(), String> {
+        let ids: Vec<i64> = Self::list(conn)?
+            .into_iter()
+            .filter(|schedule| schedule.job == job.as_str() && schedule.enabled != enabled)
+            .map(|schedule| schedule.id)
+            .collect();
+        for id in ids {
+            Self::update(
+                conn,
+                id,
+                ScheduleUpdate {
+                    enabled: Some(enabled),
+                    ..ScheduleUpdate::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Applies `update`, validating the cron expression and recomputing the next run.
+    pub fn update(conn: &Connection, id: i64, update: ScheduleUpdate) -> Result<Schedule, String> {
+        let current = Self::get(conn, id)?;
+
+        let name = match update.name {
+            Some(name) if name.trim().is_empty() => return Err("Schedule name cannot be empty.".to_string()),
+            Some(name) => name.trim().to_string(),
+            None => current.name,
+        };
+        let cron = update
+            .cron
+            .map(|cron| cron.trim().to_string())
+            .unwrap_or(current.cron);
+        let expression = CronExpression::parse(&cron)?;
+        let enabled = update.enabled.unwrap_or(current.enabled);
+        let options = match update.options {
+            Some(Value::Null) => None,
+            Some(options) => Some(options),
+            None => current.options,
+        };
+        let next_run_at = if enabled {
+            expression
+                .next_after(Local::now())
+                .map(|next| next.with_timezone(&Utc).to_rfc3339())
+        } else {
+            None
+        };
+
+        conn.execute(
+            "UPDATE schedules
+             SET name = ?1, cron = ?2, enabled = ?3, options = ?4, next_run_at = ?5, updated_at = ?6
+             WHERE id = ?7",
+            params![
+                name,
+                cron,
+                enabled as i64,
+                options.map(|options| options.to_string()),
+                next_run_at,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Self::get(conn, id)
+    }
+
+    pub fn runs(
+        conn: &Connection,
+        schedule_id: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<Vec<ScheduleRun>, String> {
+        let limit = limit.unwrap_or(50).clamp(1, RUN_HISTORY_LIMIT);
+        let mut stmt = conn
+            .prepare(
+                "SELECT r.id, r.schedule_id, s.name, r.started_at, r.finished_at, r.status, r.message
+                 FROM schedule_runs r
+                 JOIN schedules s ON s.id = r.schedule_id
+                 WHERE ?1 IS NULL OR r.schedule_id = ?1
+                 ORDER BY r.id DESC
+                 LIMIT ?2",
+            )
+            .map_err(|error| error.to_string())?;
+        let runs = stmt
+            .query_map(params![schedule_id, limit], |row| {
+                Ok(ScheduleRun {
+                    id: row.get(0)?,
+                    schedule_id: row.get(1)?,
+                    schedule_name: row.get(2)?,
+                    started_at: row.get(3)?,
+                    finished_at: row.get(4)?,
+                    status: row.get(5)?,
+                    message: row.get(6)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(runs)
+    }
+
+    /// Runs every enabled schedule whose next run time has passed. Called once a minute
+    /// from the background loop; a failing job is logged and never stops the others.
+    pub fn run_due<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let now = Utc::now();
+
+        for schedule in Self::list(&conn)?.into_iter().filter(|schedule| schedule.enabled) {
+            let expression = match CronExpression::parse(&schedule.cron) {
+                Ok(expression) => expression,
+                Err(_) => continue,
+            };
+            let due_at = schedule
+                .next_run_at
+                .as_deref()
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+
+            match due_at {
+                Some(due_at) if due_at > now => continue,
+                Some(_) => Self::run(app, &conn, &schedule)?,
+                // No run time computed yet: schedule the first run rather than firing at once.
+                None => {}
+            }
+
+            let next_run_at = expression
+                .next_after(Local::now())
+                .map(|next| next.with_timezone(&Utc).to_rfc3339());
+            conn.execute(
+                "UPDATE schedules SET next_run_at = ?1 WHERE id = ?2",
+                params![next_run_at, schedule.id],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    fn run<R: Runtime>(app: &AppHandle<R>, conn: &Connection, schedule: &Schedule) -> Result<(), String> {
+        let started_at = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO schedule_runs (schedule_id, started_at) VALUES (?1, ?2)",
+            params![schedule.id, started_at],
+        )
+        .map_err(|error| error.to_string())?;
+        let run_id = conn.last_insert_rowid();
+
+        let (status, message) = match Self::execute(app, conn, schedule) {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                Notifier::notify(
+                    app,
+                    JobEvent::ScheduledJobFailed {
+                        name: schedule.name.clone(),
+                        message: error.clone(),
+                    },
+                );
+                (RunStatus::Failed, error)
+            }
+        };
+
+        let finished_at = Utc::now().to_rfc3339();
+        conn.execute(
+            "UPDATE schedule_runs SET finished_at = ?1, status = ?2, message = ?3 WHERE id = ?4",
+            params![finished_at, status.as_str(), message, run_id],
+        )
+        .map_err(|error| error.to_string())?;
+        conn.execute(
+            "UPDATE schedules SET last_run_at = ?1 WHERE id = ?2",
+            params![started_at, schedule.id],
+        )
+        .map_err(|error| error.to_string())?;
+        conn.execute(
+            "DELETE FROM schedule_runs WHERE schedule_id = ?1 AND id NOT IN (
+               SELECT id FROM schedule_runs WHERE schedule_id = ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            params![schedule.id, RUN_HISTORY_LIMIT],
+        )
+        .map_err(|error| error.to_string())?;
+
+        Ok(())
+    }
+
+    fn execute<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        schedule: &Schedule,
+    ) -> Result<(RunStatus, String), String> {
+        let options = schedule.options.clone().unwrap_or(Value::Null);
+        match ScheduledJob::parse(&schedule.job)? {
+            ScheduledJob::ReportGeneration => Self::generate_report(app, conn, &options),
+            ScheduledJob::ExportGeneration => Self::generate_export(app, conn, &options),
+            ScheduledJob::CloudDriveSync => Self::sync_cloud_drives(conn, &options),
+            ScheduledJob::AutoProcess => {
+                if ProcessingPause::is_paused(conn)? {
+                    return Ok((RunStatus::Skipped, "Processing is paused.".to_string()));
+                }
+                if WorkspaceLock::is_locked(conn)? {
+                    return Ok((RunStatus::Skipped, "The workspace is locked.".to_string()));
+                }
+                let pending: i64 = conn
+                    .query_row(
+                        "SELECT COUNT(*) FROM files WHERE status = 'Unprocessed'",
+                        [],
+                        |row| row.get(0),
+                    )
+                    .map_err(|error| error.to_string())?;
+                if pending == 0 {
+                    return Ok((RunStatus::Skipped, "No unprocessed files.".to_string()));
+                }
+                // Extraction runs in the frontend, so hand the batch over to it.
+                app.emit(AUTO_PROCESS_EVENT, pending)
+                    .map_err(|error| error.to_string())?;
+                Ok((
+                    RunStatus::Succeeded,
+                    format!("Queued {} file(s) for processing.", pending),
+                ))
+            }
+            ScheduledJob::Backup => Self::backup(conn, &options),
+            ScheduledJob::WatchedFolderScan => Self::scan_watched_folders(app, conn),
+        }
+    }
+
+    /// Options: an optional `directory` and `keep`, how many timestamped copies to keep there
+    /// (7 by default).
+    fn backup(conn: &Connection, options: &Value) -> Result<(RunStatus, String), String> {
+        let directory = match options.get("directory").and_then(Value::as_str) {
+            Some(directory) => PathBuf::from(directory),
+            None => backups_dir().map_err(|error| error.to_string())?,
+        };
+        let keep = options
+            .get("keep")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_BACKUPS_KEPT);
+        std::fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+
+        let result = DatabaseBackup::create_timestamped(conn, &directory, keep as usize)?;
+        Ok((RunStatus::Succeeded, format!("Saved to {}", result.path)))
+    }
+
+    /// Catches up on documents that reached watched folders while the app was closed.
+    fn scan_watched_folders<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+    ) -> Result<(RunStatus, String), String> {
+        if FolderWatcher::list(conn)?.iter().all(|folder| !folder.enabled) {
+            return Ok((
+                RunStatus::Skipped,
+                "No watched folders are configured.".to_string(),
+            ));
+        }
+        if WorkspaceLock::is_locked(conn)? {
+            return Ok((RunStatus::Skipped, "The workspace is locked.".to_string()));
+        }
+        let scan = FolderWatcher::scan(app, conn)?;
+        Ok((
+            RunStatus::Succeeded,
+            format!(
+                "Scanned {} folder(s): {} imported, {} duplicate(s), {} failed.",
+                scan.folders, scan.imported, scan.duplicates, scan.failed
+            ),
+        ))
+    }
+
+    /// Options: an optional `linkId`; every linked drive is synced when it is missing.
+    fn sync_cloud_drives(conn: &Connection, options: &Value) -> Result<(RunStatus, String), String> {
+        if WorkspaceLock::is_locked(conn)? {
+            return Ok((RunStatus::Skipped, "The workspace is locked.".to_string()));
+        }
+        let links = match options.get("linkId").and_then(Value::as_i64) {
+            Some(link_id) => vec![CloudDrive::get(conn, link_id)?],
+            None => CloudDrive::list(conn)?,
+        };
+        if links.is_empty() {
+            return Ok((RunStatus::Skipped, "No cloud drives are linked.".to_string()));
+        }
+
+        let mut messages = Vec::with_capacity(links.len());
+        let mut failed = false;
+        for link in links {
+            let label = link.folder_name.clone().unwrap_or_else(|| link.folder.clone());
+            match CloudDrive::sync(conn, link.id) {
+                Ok(summary) => {
+                    messages.push(format!(
+                        "{}: {} imported, {} duplicate(s), {} failed",
+                        label,
+                        summary.imported,
+                        summary.duplicates,
+                        summary.failures.len()
+                    ));
+                }
+                Err(error) => {
+                    failed = true;
+                    messages.push(format!("{}: {}", label, error));
+                }
+            }
+        }
+        // A drive that could not be reached fails the run so the user is notified.
+        if failed {
+            return Err(format!("{}.", messages.join("; ")));
+        }
+        Ok((RunStatus::Succeeded, format!("{}.", messages.join("; "))))
+    }
+
+    /// Options: `kind` (`spend_summary` or `tax_summary`), `period` (`previous_month`,
+    /// `current_month` or any period `ReportPeriod` accepts) and an optional `directory`.
+    fn generate_report<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        options: &Value,
+    ) -> Result<(RunStatus, String), String> {
+        let kind = options
+            .get("kind")
+            .and_then(Value::as_str)
+            .unwrap_or("spend_summary");
+        let period = resolve_period(
+            options
+                .get("period")
+                .and_then(Value::as_str)
+                .unwrap_or("previous_month"),
+        )?;
+        let directory = match options.get("directory").and_then(Value::as_str) {
+            Some(directory) => PathBuf::from(directory),
+            None => reports_dir().map_err(|error| error.to_string())?,
+        };
+        std::fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+
+        let dest = directory.join(format!("{}-{}.pdf", kind, period));
+        let title = render_pdf_report(conn, kind, &ReportPeriod::parse(&period)?, &dest)?;
+        let path = dest.to_string_lossy().to_string();
+        Telemetry::record_export(conn, "scheduled_report");
+
+        Notifier::notify(
+            app,
+            JobEvent::ExportCompleted {
+                label: title,
+                path: path.clone(),
+            },
+        );
+
+        Ok((RunStatus::Succeeded, format!("Saved to {}", path)))
+    }
+
+    /// Options: `period` (as for reports), an optional `directory` and `formats`, a list of
+    /// `xlsx` and `xml` (both by default). Exports the processed invoices dated in the period;
+    /// XML exports are also saved as a sheet so they show up in the app.
+    fn generate_export<R: Runtime>(
+        app: &AppHandle<R>,
+        conn: &Connection,
+        options: &Value,
+    ) -> Result<(RunStatus, String), String> {
+        let period = resolve_period(
+            options
+                .get("period")
+                .and_then(Value::as_str)
+                .unwrap_or("previous_month"),
+        )?;
+        let formats: Vec<BundleFormat> = match options.get("formats").and_then(Value::as_array) {
+            Some(formats) => formats
+                .iter()
+                .filter_map(Value::as_str)
+                .map(BundleFormat::parse)
+                .collect::<Result<_, _>>()?,
+            None => vec![BundleFormat::Xlsx, BundleFormat::Xml],
+        };
+        if formats.is_empty() {
+            return Ok((RunStatus::Skipped, "No export formats are selected.".to_string()));
+        }
+
+        let file_ids = processed_files_in(conn, &ReportPeriod::parse(&period)?)?;
+        if file_ids.is_empty() {
+            return Ok((
+                RunStatus::Skipped,
+                format!("No processed invoices dated in {}.", period),
+            ));
+        }
+        let directory = match options.get("directory").and_then(Value::as_str) {
+            Some(directory) => PathBuf::from(directory),
+            None => reports_dir().map_err(|error| error.to_string())?,
+        };
+        std::fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+
+        let mut saved = Vec::new();
+        for format in formats {
+            let dest = directory.join(format!("invoices-{}.{}", period, format.extension()));
+            let path = dest.to_string_lossy().to_string();
+            match format {
+                BundleFormat::Xlsx => {
+                    let bundle = ExportBundle::load(conn, &file_ids, format)?;
+                    bundle.write_sheet(&dest)?;
+                    ExportManifests::record(
+                        conn,
+                        "scheduled_export",
+                        &dest,
+                        &bundle.file_ids(),
+                        bundle.file_count(),
+                    )?;
+                    Telemetry::record_export(conn, "scheduled_export");
+                    Notifier::notify(
+                        app,
+                        JobEvent::ExportCompleted {
+                            label: format!("Invoices {}", period),
+                            path: path.clone(),
+                        },
+                    );
+                    saved.push(format!("saved {}", path));
+                }
+                BundleFormat::Xml => {
+                    let xml_id = create_xml_for_files(file_ids.clone(), format!("Invoices {}", period))?;
+                    app.emit(SCHEDULED_XML_EVENT, ScheduledXmlExport { xml_id, path: path.clone() })
+                        .map_err(|error| error.to_string())?;
+                    saved.push(format!("queued XML sheet {} for {}", xml_id, path));
+                }
+            }
+        }
+
+        Ok((
+            RunStatus::Succeeded,
+            format!("{} invoice(s): {}.", file_ids.len(), saved.join("; ")),
+        ))
+    }
+}
+
+fn processed_files_in(conn: &Connection, period: &ReportPeriod) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM files
+             WHERE invoice_date BETWEEN ?1 AND ?2 AND parsed_details IS NOT NULL
+             ORDER BY invoice_date, created_at",
+        )
+        .map_err(|error| error.to_string())?;
+    let ids = stmt
+        .query_map(params![period.start_iso(), period.end_iso()], |row| row.get(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(ids)
+}
+
+fn resolve_period(value: &str) -> Result<String, String> {
+    let today = Local::now().date_naive();
+    let month = match value {
+        "current_month" => today,
+        "previous_month" => today
+            .checked_sub_months(Months::new(1))
+            .ok_or_else(|| "Could not resolve the previous month.".to_string())?,
+        other => return Ok(other.to_string()),
+    };
+    Ok(format!("{:04}-{:02}", month.year(), month.month()))
+}
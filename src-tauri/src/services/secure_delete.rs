@@ -0,0 +1,70 @@
+use crate::db::reset_connections;
+use crate::services::settings::Settings;
+use rusqlite::Connection;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+pub const SECURE_DELETE_KEY: &str = "security.secure_delete";
+const OVERWRITE_CHUNK: usize = 64 * 1024;
+
+pub struct SecureDelete;
+
+impl SecureDelete {
+    pub fn enabled(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, SECURE_DELETE_KEY)?.as_deref() == Some("1"))
+    }
+
+    /// Persists the setting, which connections opened from then on apply. Enabling it also
+    /// vacuums the database so pages freed before the setting was turned on are scrubbed as
+    /// well.
+    pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<(), String> {
+        Settings::set(conn, SECURE_DELETE_KEY, if enabled { "1" } else { "0" })?;
+        reset_connections();
+        if enabled {
+            conn.execute_batch("PRAGMA secure_delete = ON; VACUUM;")
+                .map_err(|error| error.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Removes a file, first overwriting its contents with zeros when `shred` is set.
+    /// On SSDs and copy-on-write filesystems the overwrite is best effort.
+    pub fn remove_file(path: &Path, shred: bool) -> Result<(), String> {
+        if shred {
+            let length = fs::metadata(path).map_err(|error| error.to_string())?.len();
+            let mut file = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(|error| error.to_string())?;
+            let zeros = vec![0u8; OVERWRITE_CHUNK];
+            let mut remaining = length;
+            while remaining > 0 {
+                let chunk = remaining.min(OVERWRITE_CHUNK as u64) as usize;
+                file.write_all(&zeros[..chunk])
+                    .map_err(|error| error.to_string())?;
+                remaining -= chunk as u64;
+            }
+            file.sync_all().map_err(|error| error.to_string())?;
+        }
+
+        fs::remove_file(path).map_err(|error| error.to_string())
+    }
+
+    pub fn remove_dir_all(path: &Path, shred: bool) -> Result<(), String> {
+        if shred {
+            for entry in fs::read_dir(path).map_err(|error| error.to_string())? {
+                let entry_path = entry.map_err(|error| error.to_string())?.path();
+                if entry_path.is_dir() {
+                    Self::remove_dir_all(&entry_path, true)?;
+                } else {
+                    Self::remove_file(&entry_path, true)?;
+                }
+            }
+        }
+
+        fs::remove_dir_all(path).map_err(|error| error.to_string())
+    }
+}
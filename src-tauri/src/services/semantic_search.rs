@@ -0,0 +1,313 @@
+use crate::commands::file_operations::FileStatus;
+use crate::services::llm_provider::{LlmProvider, LlmProviderKind, LlmProviders};
+use crate::services::parsed_invoice::{
+    text_value, FIELD_LOCATIONS, INVOICE_NUMBER, IRN, SELLER_NAME, SIGNED_QR_CODE,
+};
+use crate::services::parsed_schema::SCHEMA_VERSION;
+use crate::services::settings::Settings;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+const PROVIDER_KEY: &str = "semantic_search.provider";
+/// Texts per embeddings request.
+const BATCH_SIZE: usize = 32;
+/// Longer invoices are cut; the start holds the parties and the first items.
+const MAX_TEXT_CHARS: usize = 8000;
+/// Fields that are codes or coordinates rather than words worth matching.
+const SKIPPED_KEYS: &[&str] = &[FIELD_LOCATIONS, SIGNED_QR_CODE, IRN, SCHEMA_VERSION];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticHit {
+    pub file_id: String,
+    pub file_name: String,
+    pub seller_name: Option<String>,
+    pub invoice_number: Option<String>,
+    /// Cosine similarity between the query and the invoice, up to 1.
+    pub score: f32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticIndexStatus {
+    pub provider: LlmProviderKind,
+    pub model: String,
+    /// Parsed files with an up-to-date embedding.
+    pub indexed: i64,
+    /// Parsed files in total.
+    pub total: i64,
+}
+
+/// Finds invoices by meaning rather than keywords, e.g. "the invoice about forklift
+/// repairs". The parsed fields of every Processed file are embedded once, and again when
+/// they change, into `invoice_embeddings`; a search embeds the query and ranks the stored
+/// vectors by cosine similarity.
+pub struct SemanticSearch;
+
+impl SemanticSearch {
+    /// The provider embeddings come from: the one chosen for search, or else the extraction
+    /// provider when it has an embeddings API.
+    pub fn provider(conn: &Connection) -> Result<LlmProviderKind, String> {
+        let kind = match Settings::get(conn, PROVIDER_KEY)? {
+            Some(value) => LlmProviderKind::parse(&value)?,
+            None => LlmProviders::configured(conn)?,
+        };
+        match kind.embedding_model() {
+            Some(_) => Ok(kind),
+            None => Err(format!(
+                "{} has no embeddings API. Choose Gemini, OpenAI or a local model for semantic search.",
+                kind.label()
+            )),
+        }
+    }
+
+    /// `None` follows the extraction provider again.
+    pub fn set_provider(conn: &Connection, kind: Option<LlmProviderKind>) -> Result<(), String> {
+        match kind {
+            Some(kind) if kind.embedding_model().is_none() => {
+                Err(format!("{} has no embeddings API.", kind.label()))
+            }
+            Some(kind) => Settings::set(conn, PROVIDER_KEY, kind.as_str()),
+            None => Settings::remove(conn, PROVIDER_KEY),
+        }
+    }
+
+    pub fn status(conn: &Connection) -> Result<SemanticIndexStatus, String> {
+        let kind = Self::provider(conn)?;
+        let candidates = candidates(conn)?;
+        let model = kind.embedding_model().unwrap_or_default();
+        let indexed = candidates
+            .iter()
+            .filter(|candidate| candidate.stored_hash.as_deref() == Some(candidate.hash(model).as_str()))
+            .count() as i64;
+        Ok(SemanticIndexStatus {
+            provider: kind,
+            model: model.to_string(),
+            indexed,
+            total: candidates.len() as i64,
+        })
+    }
+
+    /// Embeds parsed files that have no embedding yet, or whose fields or embedding model
+    /// changed since. Returns how many were embedded.
+    pub fn refresh(conn: &Connection) -> Result<usize, String> {
+        let kind = Self::provider(conn)?;
+        let provider = LlmProviders::connect(conn, kind)?;
+        Self::refresh_with(conn, kind, provider.as_ref())
+    }
+
+    /// Brings the index up to date, then returns the `limit` invoices closest to `query`.
+    pub fn search(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SemanticHit>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let kind = Self::provider(conn)?;
+        let provider = LlmProviders::connect(conn, kind)?;
+        Self::refresh_with(conn, kind, provider.as_ref())?;
+        let query_vector = provider
+            .embed(&[query.to_string()])?
+            .pop()
+            .map(normalized)
+            .ok_or_else(|| "The query could not be embedded.".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT files.id, files.file_name, files.parsed_details, embedding.vector
+                 FROM invoice_embeddings AS embedding
+                 JOIN files ON files.id = embedding.file_id
+                 WHERE embedding.model = ?1 AND files.status = ?2",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    kind.embedding_model().unwrap_or_default(),
+                    FileStatus::Processed.as_str()
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Vec<u8>>(3)?,
+                    ))
+                },
+            )
+            .map_err(|error| error.to_string())?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            let (file_id, file_name, parsed_details, vector) = row.map_err(|error| error.to_string())?;
+            let vector = decode(&vector);
+            if vector.len() != query_vector.len() {
+                continue;
+            }
+            let parsed = parsed_details
+                .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+                .unwrap_or(Value::Null);
+            hits.push(SemanticHit {
+                file_id,
+                file_name,
+                seller_name: parsed.get(SELLER_NAME).and_then(text_value),
+                invoice_number: parsed.get(INVOICE_NUMBER).and_then(text_value),
+                score: dot(&query_vector, &vector),
+            });
+        }
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        hits.truncate(limit.max(1));
+        Ok(hits)
+    }
+
+    fn refresh_with(
+        conn: &Connection,
+        kind: LlmProviderKind,
+        provider: &dyn LlmProvider,
+    ) -> Result<usize, String> {
+        let model = kind.embedding_model().unwrap_or_default();
+        let stale: Vec<(Candidate, String)> = candidates(conn)?
+            .into_iter()
+            .filter_map(|candidate| {
+                let hash = candidate.hash(model);
+                (candidate.stored_hash.as_deref() != Some(hash.as_str())).then_some((candidate, hash))
+            })
+            .collect();
+
+        for batch in stale.chunks(BATCH_SIZE) {
+            let texts: Vec<String> = batch
+                .iter()
+                .map(|(candidate, _)| candidate.text.clone())
+                .collect();
+            let vectors = provider.embed(&texts)?;
+            for ((candidate, hash), vector) in batch.iter().zip(vectors) {
+                let vector = normalized(vector);
+                conn.execute(
+                    "INSERT INTO invoice_embeddings (file_id, provider, model, content_hash, dimensions, vector)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                     ON CONFLICT(file_id) DO UPDATE SET
+                       provider = excluded.provider,
+                       model = excluded.model,
+                       content_hash = excluded.content_hash,
+                       dimensions = excluded.dimensions,
+                       vector = excluded.vector,
+                       created_at = CURRENT_TIMESTAMP",
+                    params![
+                        candidate.file_id,
+                        kind.as_str(),
+                        model,
+                        hash,
+                        vector.len() as i64,
+                        encode(&vector)
+                    ],
+                )
+                .map_err(|error| error.to_string())?;
+            }
+        }
+        Ok(stale.len())
+    }
+}
+
+struct Candidate {
+    file_id: String,
+    text: String,
+    stored_hash: Option<String>,
+}
+
+impl Candidate {
+    /// Changes with the text and with the model, so switching models embeds everything again.
+    fn hash(&self, model: &str) -> String {
+        blake3::hash(format!("{}\n{}", model, self.text).as_bytes())
+            .to_hex()
+            .to_string()
+    }
+}
+
+/// Processed files with parsed fields, and the hash their stored embedding was made from.
+fn candidates(conn: &Connection) -> Result<Vec<Candidate>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT files.id, files.file_name, files.parsed_details, embedding.content_hash
+             FROM files
+             LEFT JOIN invoice_embeddings AS embedding ON embedding.file_id = files.id
+             WHERE files.status = ?1 AND files.parsed_details IS NOT NULL",
+        )
+        .map_err(|error| error.to_string())?;
+    let rows = stmt
+        .query_map(params![FileStatus::Processed.as_str()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(|error| error.to_string())?;
+
+    let mut candidates = Vec::new();
+    for row in rows {
+        let (file_id, file_name, parsed_details, stored_hash) = row.map_err(|error| error.to_string())?;
+        let Ok(parsed) = serde_json::from_str::<Value>(&parsed_details) else {
+            continue;
+        };
+        candidates.push(Candidate {
+            file_id,
+            text: document_text(&file_name, &parsed),
+            stored_hash,
+        });
+    }
+    Ok(candidates)
+}
+
+/// The file name and every parsed field as `key: value` lines.
+fn document_text(file_name: &str, parsed: &Value) -> String {
+    let mut lines = vec![file_name.to_string()];
+    collect_lines(parsed, "", &mut lines);
+    lines.join("\n").chars().take(MAX_TEXT_CHARS).collect()
+}
+
+fn collect_lines(value: &Value, key: &str, lines: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (child_key, child) in map {
+                if !child_key.starts_with('_') && !SKIPPED_KEYS.contains(&child_key.as_str()) {
+                    collect_lines(child, child_key, lines);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_lines(item, key, lines);
+            }
+        }
+        _ => {
+            if let Some(text) = text_value(value) {
+                lines.push(format!("{}: {}", key, text));
+            }
+        }
+    }
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn encode(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
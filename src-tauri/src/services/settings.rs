@@ -0,0 +1,37 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Backend-owned settings. Values that the backend has to enforce (security, retention)
+/// live here rather than in the frontend store, where the UI could simply skip them.
+pub struct Settings;
+
+impl Settings {
+    pub fn get(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+        conn.query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| error.to_string())
+    }
+
+    pub fn set(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP",
+            params![key, value],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    pub fn remove(conn: &Connection, key: &str) -> Result<(), String> {
+        conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    pub fn get_i64(conn: &Connection, key: &str) -> Result<Option<i64>, String> {
+        Ok(Self::get(conn, key)?.and_then(|value| value.parse().ok()))
+    }
+}
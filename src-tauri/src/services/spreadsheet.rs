@@ -0,0 +1,144 @@
+use calamine::{open_workbook_auto, Data, Reader};
+use chrono::NaiveDate;
+use rust_xlsxwriter::{Format, Workbook};
+use std::path::Path;
+
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    /// Written as ISO text in workbooks and in the profile's date format in CSV files.
+    Date(NaiveDate),
+    Empty,
+}
+
+impl Cell {
+    /// A date cell for ISO `YYYY-MM-DD` values, falling back to text for anything else.
+    pub fn date(value: &str) -> Self {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Cell::Date)
+            .unwrap_or_else(|_| Cell::Text(value.to_string()))
+    }
+}
+
+impl From<Option<String>> for Cell {
+    fn from(value: Option<String>) -> Self {
+        value.map(Cell::Text).unwrap_or(Cell::Empty)
+    }
+}
+
+impl From<Option<f64>> for Cell {
+    fn from(value: Option<f64>) -> Self {
+        value.map(Cell::Number).unwrap_or(Cell::Empty)
+    }
+}
+
+/// A single worksheet: a bold header row followed by data rows.
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+pub fn write_workbook(path: &Path, sheets: &[Sheet]) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+
+    for sheet in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(&sheet.name).map_err(|error| error.to_string())?;
+
+        for (col, header) in sheet.headers.iter().enumerate() {
+            worksheet
+                .write_string_with_format(0, col as u16, header, &header_format)
+                .map_err(|error| error.to_string())?;
+        }
+
+        for (index, row) in sheet.rows.iter().enumerate() {
+            let row_number = index as u32 + 1;
+            for (col, cell) in row.iter().enumerate() {
+                match cell {
+                    Cell::Text(value) => {
+                        worksheet
+                            .write_string(row_number, col as u16, value)
+                            .map_err(|error| error.to_string())?;
+                    }
+                    Cell::Number(value) => {
+                        worksheet
+                            .write_number(row_number, col as u16, *value)
+                            .map_err(|error| error.to_string())?;
+                    }
+                    Cell::Date(value) => {
+                        worksheet
+                            .write_string(row_number, col as u16, value.format("%Y-%m-%d").to_string())
+                            .map_err(|error| error.to_string())?;
+                    }
+                    Cell::Empty => {}
+                }
+            }
+        }
+    }
+
+    workbook.save(path).map_err(|error| error.to_string())
+}
+
+/// A sheet read back as text: the first non-empty row becomes the headers.
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Reads the first worksheet of an `.xlsx`, `.xls` or `.ods` file, or a `.csv` file.
+/// Excel dates come back as ISO `YYYY-MM-DD` strings.
+pub fn read_table(path: &Path) -> Result<Table, String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    let mut rows: Vec<Vec<String>> = if extension == "csv" {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|error| error.to_string())?;
+        reader
+            .records()
+            .map(|record| {
+                record
+                    .map(|record| record.iter().map(str::to_string).collect())
+                    .map_err(|error| error.to_string())
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        let mut workbook = open_workbook_auto(path).map_err(|error| error.to_string())?;
+        let range = workbook
+            .worksheet_range_at(0)
+            .ok_or_else(|| "The workbook has no worksheets.".to_string())?
+            .map_err(|error| error.to_string())?;
+        range
+            .rows()
+            .map(|row| row.iter().map(cell_text).collect())
+            .collect()
+    };
+
+    rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+    if rows.is_empty() {
+        return Err("The spreadsheet is empty.".to_string());
+    }
+    let headers = rows.remove(0);
+    Ok(Table { headers, rows })
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", *value as i64),
+        Data::DateTime(_) => cell
+            .as_date()
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| cell.to_string()),
+        other => other.to_string().trim().to_string(),
+    }
+}
@@ -0,0 +1,929 @@
+use crate::services::extraction_templates::parse_amount;
+use crate::services::parsed_invoice::BoundingBox;
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Header labels per column, tried in order: longer labels come first so "unit price" wins
+/// over "unit".
+const HEADER_KEYWORDS: &[(&str, TableColumn)] = &[
+    ("description of goods", TableColumn::Description),
+    ("taxable value", TableColumn::Amount),
+    // Tax and discount columns, checked before "amount" so "CGST Amount" is not the line amount.
+    ("discount", TableColumn::Other),
+    ("cgst", TableColumn::Other),
+    ("sgst", TableColumn::Other),
+    ("igst", TableColumn::Other),
+    ("disc", TableColumn::Other),
+    ("tax", TableColumn::Other),
+    ("gst", TableColumn::Other),
+    ("net amount", TableColumn::Amount),
+    ("unit price", TableColumn::Rate),
+    ("particulars", TableColumn::Description),
+    ("description", TableColumn::Description),
+    ("quantity", TableColumn::Quantity),
+    ("hsn/sac", TableColumn::HsnSac),
+    ("product", TableColumn::Description),
+    ("amount", TableColumn::Amount),
+    ("item", TableColumn::Description),
+    ("goods", TableColumn::Description),
+    ("price", TableColumn::Rate),
+    ("total", TableColumn::Amount),
+    ("value", TableColumn::Amount),
+    ("rate", TableColumn::Rate),
+    ("unit", TableColumn::Unit),
+    ("uom", TableColumn::Unit),
+    ("per", TableColumn::Unit),
+    ("hsn", TableColumn::HsnSac),
+    ("sac", TableColumn::HsnSac),
+    ("qty", TableColumn::Quantity),
+    ("s.no", TableColumn::Serial),
+    ("sl", TableColumn::Serial),
+    ("sr", TableColumn::Serial),
+    ("no", TableColumn::Serial),
+    ("#", TableColumn::Serial),
+];
+
+/// A row whose first text starts with one of these closes the table.
+const STOP_LABELS: &[&str] = &[
+    "total",
+    "sub total",
+    "subtotal",
+    "grand total",
+    "taxable",
+    "amount in words",
+    "round off",
+    "less",
+    "add",
+    "cgst",
+    "sgst",
+    "igst",
+];
+
+/// Average glyph width as a fraction of the font size; the layout only needs cell extents
+/// roughly right, and reading real glyph widths would mean parsing every font program.
+const GLYPH_WIDTH: f64 = 0.5;
+/// Runs closer than this (in font sizes) belong to the same cell.
+const CELL_GAP: f64 = 1.0;
+/// A `TJ` adjustment wider than this (in font sizes) separates two cells.
+const TJ_CELL_GAP: f64 = 0.8;
+/// Rows further apart than this many line heights end an unruled table.
+const MAX_ROW_GAP: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableColumn {
+    Serial,
+    Description,
+    HsnSac,
+    Quantity,
+    Unit,
+    Rate,
+    Amount,
+    Other,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableLineItem {
+    pub page: u32,
+    pub description: Option<String>,
+    pub hsn_sac: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub rate: Option<f64>,
+    pub amount: Option<f64>,
+}
+
+impl TableLineItem {
+    /// Same keys as the model's `items` entries, so the rest of the pipeline reads it as is.
+    pub fn to_json(&self) -> Value {
+        let mut item = Map::new();
+        let mut put = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                item.insert(key.to_string(), value);
+            }
+        };
+        put("description", self.description.clone().map(Value::String));
+        put("HSN/SAC", self.hsn_sac.clone().map(Value::String));
+        put("quantity", self.quantity.map(Value::from));
+        put("unit", self.unit.clone().map(Value::String));
+        put("rate", self.rate.map(Value::from));
+        put("amount", self.amount.map(Value::from));
+        Value::Object(item)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedTable {
+    pub page: u32,
+    /// Columns were taken from ruling lines rather than header positions.
+    pub ruled: bool,
+    pub headers: Vec<String>,
+    pub columns: Vec<TableColumn>,
+    pub rows: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableExtraction {
+    pub file_id: String,
+    pub tables: Vec<DetectedTable>,
+    pub items: Vec<TableLineItem>,
+}
+
+/// Reads line items straight from the layout of a PDF's text layer. Item tables are found
+/// by their header row; columns come from the table's vertical rules when it has them and
+/// from the header labels' positions when it does not.
+pub struct TableExtractor;
+
+impl TableExtractor {
+    pub fn extract_file(conn: &Connection, file_id: &str) -> Result<TableExtraction, String> {
+        let stored_path: String = conn
+            .query_row(
+                "SELECT stored_path FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let path = Path::new(&stored_path);
+        let is_pdf = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        if !is_pdf {
+            return Err("Tables can only be read from PDFs with a text layer.".to_string());
+        }
+
+        let document =
+            Document::load(path).map_err(|error| format!("The PDF could not be read: {}", error))?;
+        let (tables, items) = Self::extract(&document)?;
+        Ok(TableExtraction {
+            file_id: file_id.to_string(),
+            tables,
+            items,
+        })
+    }
+
+    pub fn extract(document: &Document) -> Result<(Vec<DetectedTable>, Vec<TableLineItem>), String> {
+        let mut tables = Vec::new();
+        let mut items = Vec::new();
+        let mut any_text = false;
+        for (number, page_id) in document.get_pages() {
+            let layout = page_layout(document, page_id)?;
+            any_text |= !layout.runs.is_empty();
+            if let Some((table, mut page_items)) = find_table(number, &layout) {
+                tables.push(table);
+                items.append(&mut page_items);
+            }
+        }
+        if !any_text {
+            return Err("This PDF has no readable text layer, so its tables cannot be read.".to_string());
+        }
+        Ok((tables, items))
+    }
+
+    /// Text of the page's text layer inside `area`, line by line, top first. A run belongs to
+    /// the area when its middle does. `None` when the page has no text layer at all, as with
+    /// scanned pages.
+    pub fn text_in_area(
+        document: &Document,
+        page: u32,
+        area: &BoundingBox,
+    ) -> Result<Option<String>, String> {
+        let page_id = *document
+            .get_pages()
+            .get(&page)
+            .ok_or_else(|| format!("The document has no page {}.", page))?;
+        let layout = page_layout(document, page_id)?;
+        if layout.runs.is_empty() {
+            return Ok(None);
+        }
+
+        // Areas are measured from the top left; PDF space starts at the bottom left.
+        let [left, bottom, right, top] = media_box(document, page_id);
+        let (width, height) = (right - left, top - bottom);
+        let (x_min, x_max) = (left + area.x_min * width, left + area.x_max * width);
+        let (y_min, y_max) = (top - area.y_max * height, top - area.y_min * height);
+        let inside: Vec<TextRun> = layout
+            .runs
+            .into_iter()
+            .filter(|run| {
+                let (x, y) = (run.x + run.width / 2.0, run.y + run.size / 3.0);
+                (x_min..=x_max).contains(&x) && (y_min..=y_max).contains(&y)
+            })
+            .collect();
+
+        let lines: Vec<String> = rows(&inside)
+            .into_iter()
+            .map(|row| {
+                row.cells
+                    .into_iter()
+                    .map(|cell| cell.text)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        Ok(Some(lines.join("\n")))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TextRun {
+    x: f64,
+    y: f64,
+    width: f64,
+    size: f64,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    x: f64,
+    end: f64,
+    text: String,
+}
+
+struct Row {
+    y: f64,
+    size: f64,
+    cells: Vec<Cell>,
+}
+
+#[derive(Default)]
+struct PageLayout {
+    runs: Vec<TextRun>,
+    /// Vertical rules as `(x, bottom, top)`.
+    verticals: Vec<(f64, f64, f64)>,
+}
+
+type Matrix = [f64; 6];
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(m: &Matrix, n: &Matrix) -> Matrix {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+fn transform(m: &Matrix, x: f64, y: f64) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+fn translate(tx: f64, ty: f64) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+struct FontInfo {
+    encoding: Option<String>,
+    to_unicode: Option<CMap>,
+}
+
+impl FontInfo {
+    fn decode(&self, bytes: &[u8]) -> String {
+        if let Some(cmap) = &self.to_unicode {
+            return cmap.decode(bytes);
+        }
+        match self.encoding.as_deref() {
+            // Glyph ids without a ToUnicode map cannot be turned into text.
+            Some("Identity-H") | Some("Identity-V") => String::new(),
+            encoding => Document::decode_text(encoding, bytes),
+        }
+    }
+}
+
+/// The parts of a ToUnicode CMap needed to map character codes to text.
+struct CMap {
+    code_bytes: usize,
+    map: HashMap<u32, String>,
+}
+
+impl CMap {
+    fn parse(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        let mut map = HashMap::new();
+        let mut code_bytes = 1;
+
+        for section in sections(&text, "beginbfchar", "endbfchar") {
+            let tokens = hex_tokens(section);
+            for pair in tokens.chunks(2) {
+                if let [source, target] = pair {
+                    code_bytes = code_bytes.max(source.len());
+                    map.insert(code(source), utf16(target));
+                }
+            }
+        }
+        for section in sections(&text, "beginbfrange", "endbfrange") {
+            for line in section.lines() {
+                let tokens = hex_tokens(line);
+                let (Some(low), Some(high)) = (tokens.first(), tokens.get(1)) else {
+                    continue;
+                };
+                code_bytes = code_bytes.max(low.len());
+                let (low_code, high_code) = (code(low), code(high));
+                if line.contains('[') {
+                    for (offset, target) in tokens[2..].iter().enumerate() {
+                        map.insert(low_code + offset as u32, utf16(target));
+                    }
+                } else if let Some(target) = tokens.get(2) {
+                    let start = utf16(target);
+                    let Some(first) = start.chars().last() else {
+                        continue;
+                    };
+                    let prefix: String = start.chars().take(start.chars().count() - 1).collect();
+                    for (offset, source) in (low_code..=high_code.min(low_code + 0xFFFF)).enumerate() {
+                        if let Some(character) = char::from_u32(first as u32 + offset as u32) {
+                            map.insert(source, format!("{}{}", prefix, character));
+                        }
+                    }
+                }
+            }
+        }
+        Self { code_bytes, map }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .chunks(self.code_bytes)
+            .filter_map(|chunk| self.map.get(&code(chunk)))
+            .cloned()
+            .collect()
+    }
+}
+
+fn sections<'a>(text: &'a str, begin: &str, end: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(begin) {
+        let after = &rest[start + begin.len()..];
+        let Some(stop) = after.find(end) else {
+            break;
+        };
+        found.push(&after[..stop]);
+        rest = &after[stop + end.len()..];
+    }
+    found
+}
+
+fn hex_tokens(text: &str) -> Vec<Vec<u8>> {
+    text.split('<')
+        .skip(1)
+        .filter_map(|part| part.split('>').next())
+        .map(|hex| {
+            let digits: Vec<u8> = hex
+                .chars()
+                .filter_map(|digit| digit.to_digit(16))
+                .map(|digit| digit as u8)
+                .collect();
+            digits
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |byte, digit| byte * 16 + digit))
+                .collect()
+        })
+        .collect()
+}
+
+fn code(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |code, byte| (code << 8) | *byte as u32)
+}
+
+fn utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| ((pair[0] as u16) << 8) | pair.get(1).copied().unwrap_or(0) as u16)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn page_fonts(document: &Document, page_id: ObjectId) -> HashMap<Vec<u8>, FontInfo> {
+    document
+        .get_page_fonts(page_id)
+        .into_iter()
+        .map(|(name, font)| {
+            let to_unicode = font
+                .get(b"ToUnicode")
+                .ok()
+                .map(|object| resolve(document, object))
+                .and_then(|object| object.as_stream().ok())
+                .and_then(|stream| {
+                    stream
+                        .decompressed_content()
+                        .ok()
+                        .or_else(|| Some(stream.content.clone()))
+                })
+                .map(|data| CMap::parse(&data))
+                .filter(|cmap| !cmap.map.is_empty());
+            let encoding = font_encoding(document, font);
+            (name, FontInfo { encoding, to_unicode })
+        })
+        .collect()
+}
+
+fn font_encoding(document: &Document, font: &Dictionary) -> Option<String> {
+    let encoding = font
+        .get(b"Encoding")
+        .ok()
+        .map(|object| resolve(document, object))?;
+    match encoding {
+        Object::Name(name) => Some(String::from_utf8_lossy(name).to_string()),
+        Object::Dictionary(dictionary) => dictionary
+            .get(b"BaseEncoding")
+            .and_then(Object::as_name_str)
+            .ok()
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// The page's `MediaBox` as `[left, bottom, right, top]`, inherited from its parents when
+/// the page has none; US Letter when no node sets one.
+fn media_box(document: &Document, page_id: ObjectId) -> [f64; 4] {
+    let mut node = document.get_dictionary(page_id).ok();
+    while let Some(dictionary) = node {
+        if let Ok(Object::Array(bounds)) = dictionary
+            .get(b"MediaBox")
+            .map(|bounds| resolve(document, bounds))
+        {
+            if let [left, bottom, right, top] = bounds.as_slice() {
+                let [left, bottom, right, top] = [number(left), number(bottom), number(right), number(top)];
+                if right > left && top > bottom {
+                    return [left, bottom, right, top];
+                }
+            }
+        }
+        node = dictionary
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .and_then(|parent| document.get_dictionary(parent))
+            .ok();
+    }
+    [0.0, 0.0, 612.0, 792.0]
+}
+
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => document.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+fn number(object: &Object) -> f64 {
+    object.as_float().map(f64::from).unwrap_or(0.0)
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    font: Option<Vec<u8>>,
+    size: f64,
+    leading: f64,
+    scale: f64,
+}
+
+/// Walks the page's content stream, placing each piece of text and collecting the vertical
+/// lines and rectangle edges a ruled table is drawn with.
+fn page_layout(document: &Document, page_id: ObjectId) -> Result<PageLayout, String> {
+    let data = document
+        .get_page_content(page_id)
+        .map_err(|error| error.to_string())?;
+    let Ok(content) = Content::decode(&data) else {
+        return Ok(PageLayout::default());
+    };
+    let fonts = page_fonts(document, page_id);
+
+    let mut layout = PageLayout::default();
+    let mut state = GraphicsState {
+        ctm: IDENTITY,
+        font: None,
+        size: 12.0,
+        leading: 0.0,
+        scale: 1.0,
+    };
+    let mut stack: Vec<GraphicsState> = Vec::new();
+    let mut text_matrix = IDENTITY;
+    let mut line_matrix = IDENTITY;
+    let mut path_start: Option<(f64, f64)> = None;
+
+    for operation in &content.operations {
+        let operands = &operation.operands;
+        let operand = |index: usize| operands.get(index).map(number).unwrap_or(0.0);
+        match operation.operator.as_str() {
+            "q" => stack.push(state.clone()),
+            "Q" => {
+                if let Some(previous) = stack.pop() {
+                    state = previous;
+                }
+            }
+            "cm" if operands.len() == 6 => {
+                let matrix = [
+                    operand(0),
+                    operand(1),
+                    operand(2),
+                    operand(3),
+                    operand(4),
+                    operand(5),
+                ];
+                state.ctm = multiply(&matrix, &state.ctm);
+            }
+            "BT" => {
+                text_matrix = IDENTITY;
+                line_matrix = IDENTITY;
+            }
+            "Tf" => {
+                state.font = operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .map(<[u8]>::to_vec);
+                state.size = operand(1);
+            }
+            "TL" => state.leading = operand(0),
+            "Tz" => state.scale = operand(0) / 100.0,
+            "Td" | "TD" => {
+                if operation.operator == "TD" {
+                    state.leading = -operand(1);
+                }
+                line_matrix = multiply(&translate(operand(0), operand(1)), &line_matrix);
+                text_matrix = line_matrix;
+            }
+            "Tm" if operands.len() == 6 => {
+                line_matrix = [
+                    operand(0),
+                    operand(1),
+                    operand(2),
+                    operand(3),
+                    operand(4),
+                    operand(5),
+                ];
+                text_matrix = line_matrix;
+            }
+            "T*" | "'" | "\"" => {
+                line_matrix = multiply(&translate(0.0, -state.leading), &line_matrix);
+                text_matrix = line_matrix;
+                if let Some(text) = operands.last() {
+                    show(
+                        &mut layout,
+                        &state,
+                        &fonts,
+                        &mut text_matrix,
+                        std::slice::from_ref(text),
+                    );
+                }
+            }
+            "Tj" | "TJ" => {
+                let elements = match operands.first() {
+                    Some(Object::Array(elements)) => elements.as_slice(),
+                    _ => operands.as_slice(),
+                };
+                show(&mut layout, &state, &fonts, &mut text_matrix, elements);
+            }
+            "m" => path_start = Some(transform(&state.ctm, operand(0), operand(1))),
+            "l" => {
+                let end = transform(&state.ctm, operand(0), operand(1));
+                if let Some(start) = path_start {
+                    add_rule(&mut layout, start, end);
+                }
+                path_start = Some(end);
+            }
+            "re" => {
+                let (x, y, width, height) = (operand(0), operand(1), operand(2), operand(3));
+                let corners = [
+                    transform(&state.ctm, x, y),
+                    transform(&state.ctm, x + width, y),
+                    transform(&state.ctm, x + width, y + height),
+                    transform(&state.ctm, x, y + height),
+                ];
+                // Cell borders, and the thin filled rectangles many producers draw rules with.
+                add_rule(&mut layout, corners[0], corners[3]);
+                add_rule(&mut layout, corners[1], corners[2]);
+            }
+            _ => {}
+        }
+    }
+    Ok(layout)
+}
+
+fn add_rule(layout: &mut PageLayout, start: (f64, f64), end: (f64, f64)) {
+    let vertical = (start.0 - end.0).abs() < 1.0 && (start.1 - end.1).abs() > 4.0;
+    if vertical {
+        layout
+            .verticals
+            .push((start.0, start.1.min(end.1), start.1.max(end.1)));
+    }
+}
+
+/// Places shown text. A wide `TJ` adjustment starts a new run, so that cells the producer
+/// wrote in one operation still land in separate columns.
+fn show(
+    layout: &mut PageLayout,
+    state: &GraphicsState,
+    fonts: &HashMap<Vec<u8>, FontInfo>,
+    text_matrix: &mut Matrix,
+    elements: &[Object],
+) {
+    let font = state.font.as_ref().and_then(|name| fonts.get(name));
+    let mut current: Option<TextRun> = None;
+
+    for element in elements {
+        match element {
+            Object::String(bytes, _) => {
+                let text = match font {
+                    Some(font) => font.decode(bytes),
+                    None => Document::decode_text(None, bytes),
+                };
+                let advance = text.chars().count() as f64 * GLYPH_WIDTH * state.size * state.scale;
+                let device = multiply(text_matrix, &state.ctm);
+                let (x, y) = transform(&device, 0.0, 0.0);
+                let size = state.size * (device[2] * device[2] + device[3] * device[3]).sqrt();
+                let width = advance * (device[0] * device[0] + device[1] * device[1]).sqrt();
+                match current.as_mut() {
+                    Some(run) => {
+                        run.text.push_str(&text);
+                        run.width = x + width - run.x;
+                    }
+                    None => {
+                        current = Some(TextRun {
+                            x,
+                            y,
+                            width,
+                            size,
+                            text,
+                        })
+                    }
+                }
+                *text_matrix = multiply(&translate(advance, 0.0), text_matrix);
+            }
+            Object::Integer(_) | Object::Real(_) => {
+                let adjustment = -number(element) / 1000.0;
+                *text_matrix = multiply(
+                    &translate(adjustment * state.size * state.scale, 0.0),
+                    text_matrix,
+                );
+                if adjustment > TJ_CELL_GAP {
+                    layout
+                        .runs
+                        .extend(current.take().filter(|run| !run.text.trim().is_empty()));
+                } else if adjustment > 0.2 {
+                    if let Some(run) = current.as_mut() {
+                        run.text.push(' ');
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    layout
+        .runs
+        .extend(current.filter(|run| !run.text.trim().is_empty()));
+}
+
+/// Groups runs into lines, top of the page first, and each line into cells.
+fn rows(runs: &[TextRun]) -> Vec<Row> {
+    let mut runs: Vec<&TextRun> = runs.iter().collect();
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut lines: Vec<Vec<&TextRun>> = Vec::new();
+    for run in runs {
+        match lines.last_mut() {
+            Some(line) if (line[0].y - run.y).abs() <= line[0].size.max(run.size) * 0.4 => line.push(run),
+            _ => lines.push(vec![run]),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let size = line.iter().map(|run| run.size).fold(0.0, f64::max);
+            let mut cells: Vec<Cell> = Vec::new();
+            for run in &line {
+                match cells.last_mut() {
+                    Some(cell) if run.x - cell.end < run.size * CELL_GAP => {
+                        cell.text.push(' ');
+                        cell.text.push_str(run.text.trim());
+                        cell.end = cell.end.max(run.x + run.width);
+                    }
+                    _ => cells.push(Cell {
+                        x: run.x,
+                        end: run.x + run.width,
+                        text: run.text.trim().to_string(),
+                    }),
+                }
+            }
+            Row {
+                y: line[0].y,
+                size,
+                cells,
+            }
+        })
+        .collect()
+}
+
+fn header_column(text: &str) -> TableColumn {
+    let label = text.trim().to_lowercase();
+    let label = label.trim_matches(|character: char| !character.is_alphanumeric() && character != '#');
+    HEADER_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| {
+            label == *keyword
+                || label.starts_with(&format!("{} ", keyword))
+                || label.starts_with(&format!("{}.", keyword))
+                || (keyword.len() > 3 && label.contains(keyword))
+        })
+        .map(|(_, column)| *column)
+        .unwrap_or(TableColumn::Other)
+}
+
+fn is_header(columns: &[TableColumn]) -> bool {
+    columns.contains(&TableColumn::Description)
+        && columns.iter().any(|column| {
+            matches!(
+                column,
+                TableColumn::Amount | TableColumn::Rate | TableColumn::Quantity
+            )
+        })
+}
+
+/// Column spans `(start, end)` for the header, and the bottom of the table when it is ruled.
+/// Vertical rules crossing the header are used when they give every label a column of its
+/// own.
+fn column_spans(header: &Row, verticals: &[(f64, f64, f64)]) -> (Option<f64>, Vec<(f64, f64)>) {
+    let crossing: Vec<&(f64, f64, f64)> = verticals
+        .iter()
+        .filter(|(_, bottom, top)| *bottom - 2.0 <= header.y && header.y <= *top + 2.0)
+        .collect();
+    let mut rules: Vec<f64> = crossing.iter().map(|(x, _, _)| *x).collect();
+    rules.sort_by(f64::total_cmp);
+    rules.dedup_by(|a, b| (*a - *b).abs() < 2.0);
+
+    let spans: Vec<(f64, f64)> = rules.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    let one_label_each = spans.iter().all(|span| {
+        header
+            .cells
+            .iter()
+            .filter(|cell| span_index(std::slice::from_ref(span), cell, false).is_some())
+            .count()
+            <= 1
+    });
+    if spans.len() >= 2 && one_label_each {
+        let bottom = crossing
+            .iter()
+            .map(|(_, bottom, _)| *bottom)
+            .fold(f64::MAX, f64::min);
+        return (Some(bottom), spans);
+    }
+    // Unruled: each label is its own column, and values go to the nearest label.
+    let spans = header.cells.iter().map(|cell| (cell.x, cell.end)).collect();
+    (None, spans)
+}
+
+/// The span the cell overlaps most. With `nearest`, a cell overlapping none goes to the
+/// closest span.
+fn span_index(spans: &[(f64, f64)], cell: &Cell, nearest: bool) -> Option<usize> {
+    let overlap = |(start, end): &(f64, f64)| (cell.end.min(*end) - cell.x.max(*start)).max(0.0);
+    let overlapping = spans
+        .iter()
+        .enumerate()
+        .map(|(index, span)| (index, overlap(span)))
+        .filter(|(_, overlap)| *overlap > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index);
+    if overlapping.is_some() || !nearest {
+        return overlapping;
+    }
+    let distance = |(start, end): &(f64, f64)| (start - cell.end).max(cell.x - end);
+    spans
+        .iter()
+        .enumerate()
+        .min_by(|a, b| distance(a.1).total_cmp(&distance(b.1)))
+        .map(|(index, _)| index)
+}
+
+fn find_table(page: u32, layout: &PageLayout) -> Option<(DetectedTable, Vec<TableLineItem>)> {
+    let rows = rows(&layout.runs);
+    let header_index = rows.iter().position(|row| {
+        let columns: Vec<TableColumn> = row.cells.iter().map(|cell| header_column(&cell.text)).collect();
+        is_header(&columns)
+    })?;
+    let header = &rows[header_index];
+    let (ruled_bottom, spans) = column_spans(header, &layout.verticals);
+    let columns: Vec<TableColumn> = spans
+        .iter()
+        .map(|span| {
+            let labels: Vec<&str> = header
+                .cells
+                .iter()
+                .filter(|cell| span_index(std::slice::from_ref(span), cell, false).is_some())
+                .map(|cell| cell.text.as_str())
+                .collect();
+            header_column(&labels.join(" "))
+        })
+        .collect();
+
+    let mut items: Vec<TableLineItem> = Vec::new();
+    let mut previous_y = header.y;
+    let mut row_count = 0;
+    for row in &rows[header_index + 1..] {
+        let line_height = row.size.max(1.0) * 1.2;
+        let past_table = match ruled_bottom {
+            Some(bottom) => row.y < bottom - 2.0,
+            None => previous_y - row.y > MAX_ROW_GAP * line_height,
+        };
+        let first = row
+            .cells
+            .first()
+            .map(|cell| cell.text.to_lowercase())
+            .unwrap_or_default();
+        let closing = STOP_LABELS.iter().any(|label| {
+            first
+                .strip_prefix(label)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', ':', '(']))
+        });
+        if past_table || closing {
+            break;
+        }
+        previous_y = row.y;
+
+        let mut values: HashMap<TableColumn, String> = HashMap::new();
+        for cell in &row.cells {
+            let Some(column) = span_index(&spans, cell, ruled_bottom.is_none()).map(|index| columns[index])
+            else {
+                continue;
+            };
+            let value = values.entry(column).or_default();
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(&cell.text);
+        }
+        let text = |column: TableColumn| {
+            values
+                .get(&column)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let amount = text(TableColumn::Amount).and_then(|value| parse_amount(&value));
+        let rate = text(TableColumn::Rate).and_then(|value| parse_amount(&value));
+        let quantity_text = text(TableColumn::Quantity);
+        let quantity = quantity_text.as_deref().and_then(parse_amount);
+        let description = text(TableColumn::Description);
+
+        if amount.is_none() && rate.is_none() && quantity.is_none() {
+            // Wrapped description lines belong to the item above.
+            if let (Some(extra), Some(item)) = (description, items.last_mut()) {
+                let joined = match item.description.take() {
+                    Some(existing) => format!("{} {}", existing, extra),
+                    None => extra,
+                };
+                item.description = Some(joined);
+            }
+            continue;
+        }
+
+        // "10 Nos" in a quantity column without a unit column of its own.
+        let unit = text(TableColumn::Unit).or_else(|| {
+            quantity_text.as_deref().and_then(|value| {
+                let unit = value.trim_start_matches(|character: char| {
+                    character.is_ascii_digit() || matches!(character, '.' | ',' | ' ')
+                });
+                (!unit.is_empty() && unit.chars().all(char::is_alphabetic)).then(|| unit.to_string())
+            })
+        });
+        row_count += 1;
+        items.push(TableLineItem {
+            page,
+            description,
+            hsn_sac: text(TableColumn::HsnSac),
+            quantity,
+            unit,
+            rate,
+            amount,
+        });
+    }
+
+    if items.is_empty() {
+        return None;
+    }
+    Some((
+        DetectedTable {
+            page,
+            ruled: ruled_bottom.is_some(),
+            headers: header.cells.iter().map(|cell| cell.text.clone()).collect(),
+            columns,
+            rows: row_count,
+        },
+        items,
+    ))
+}
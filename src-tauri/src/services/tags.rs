@@ -0,0 +1,131 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+const MAX_TAG_CHARS: usize = 64;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// Files carrying the tag.
+    pub file_count: i64,
+}
+
+/// Free-form labels on files, such as a project or cost center, matched without regard to
+/// case. A prefix like `project:` or `cc:` keeps related tags together in the list. Tags
+/// no file carries any more are dropped.
+pub struct Tags;
+
+impl Tags {
+    /// Tags `file_ids` with every one of `names`, creating tags that do not exist yet.
+    /// Returns how many tags were newly put on a file.
+    pub fn add(conn: &Connection, file_ids: &[String], names: &[String]) -> Result<usize, String> {
+        let names = normalized(names)?;
+        let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+        let mut added = 0;
+        for name in &names {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![name])
+                .map_err(|error| error.to_string())?;
+            let tag_id: i64 = tx
+                .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| {
+                    row.get(0)
+                })
+                .map_err(|error| error.to_string())?;
+            for file_id in file_ids {
+                added += tx
+                    .execute(
+                        "INSERT OR IGNORE INTO file_tags (file_id, tag_id)
+                         SELECT id, ?2 FROM files WHERE id = ?1",
+                        params![file_id, tag_id],
+                    )
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        tx.commit().map_err(|error| error.to_string())?;
+        Ok(added)
+    }
+
+    /// Takes `names` off `file_ids`. Returns how many tags were taken off a file.
+    pub fn remove(conn: &Connection, file_ids: &[String], names: &[String]) -> Result<usize, String> {
+        let names = normalized(names)?;
+        let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+        let mut removed = 0;
+        for name in &names {
+            let Some(tag_id) = tx
+                .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .optional()
+                .map_err(|error| error.to_string())?
+            else {
+                continue;
+            };
+            for file_id in file_ids {
+                removed += tx
+                    .execute(
+                        "DELETE FROM file_tags WHERE file_id = ?1 AND tag_id = ?2",
+                        params![file_id, tag_id],
+                    )
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        tx.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM file_tags)",
+            [],
+        )
+        .map_err(|error| error.to_string())?;
+        tx.commit().map_err(|error| error.to_string())?;
+        Ok(removed)
+    }
+
+    /// Every tag in use, by name.
+    pub fn list(conn: &Connection) -> Result<Vec<Tag>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT tags.id, tags.name, COUNT(file_tags.file_id)
+                 FROM tags
+                 LEFT JOIN file_tags ON file_tags.tag_id = tags.id
+                 GROUP BY tags.id
+                 HAVING COUNT(file_tags.file_id) > 0
+                 ORDER BY tags.name COLLATE NOCASE ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    file_count: row.get(2)?,
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(tags)
+    }
+}
+
+/// Trims each name and collapses inner whitespace; blank names are skipped.
+fn normalized(names: &[String]) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            continue;
+        }
+        if name.chars().count() > MAX_TAG_CHARS {
+            return Err(format!("Keep tags to {} characters or fewer.", MAX_TAG_CHARS));
+        }
+        if !cleaned
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+        {
+            cleaned.push(name);
+        }
+    }
+    if cleaned.is_empty() {
+        return Err("Enter at least one tag.".to_string());
+    }
+    Ok(cleaned)
+}
@@ -0,0 +1,489 @@
+use crate::services::i18n::I18n;
+use crate::services::parsed_invoice::{ParsedInvoice, BUYER_GSTIN, PLACE_OF_SUPPLY, SELLER_GSTIN};
+use serde::Serialize;
+
+const GSTIN_CHARSET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Check letters of Spanish DNI/NIE numbers, indexed by the number modulo 23.
+const DNI_LETTERS: &str = "TRWAGMYFPDXBNJZSQVHLCKE";
+
+const GST_STATE_CODES: &[(&str, &str)] = &[
+    ("01", "Jammu and Kashmir"),
+    ("02", "Himachal Pradesh"),
+    ("03", "Punjab"),
+    ("04", "Chandigarh"),
+    ("05", "Uttarakhand"),
+    ("06", "Haryana"),
+    ("07", "Delhi"),
+    ("08", "Rajasthan"),
+    ("09", "Uttar Pradesh"),
+    ("10", "Bihar"),
+    ("11", "Sikkim"),
+    ("12", "Arunachal Pradesh"),
+    ("13", "Nagaland"),
+    ("14", "Manipur"),
+    ("15", "Mizoram"),
+    ("16", "Tripura"),
+    ("17", "Meghalaya"),
+    ("18", "Assam"),
+    ("19", "West Bengal"),
+    ("20", "Jharkhand"),
+    ("21", "Odisha"),
+    ("22", "Chhattisgarh"),
+    ("23", "Madhya Pradesh"),
+    ("24", "Gujarat"),
+    ("25", "Daman and Diu"),
+    ("26", "Dadra and Nagar Haveli and Daman and Diu"),
+    ("27", "Maharashtra"),
+    ("28", "Andhra Pradesh (Old)"),
+    ("29", "Karnataka"),
+    ("30", "Goa"),
+    ("31", "Lakshadweep"),
+    ("32", "Kerala"),
+    ("33", "Tamil Nadu"),
+    ("34", "Puducherry"),
+    ("35", "Andaman and Nicobar Islands"),
+    ("36", "Telangana"),
+    ("37", "Andhra Pradesh"),
+    ("38", "Ladakh"),
+    ("97", "Other Territory"),
+    ("99", "Centre Jurisdiction"),
+];
+
+/// VAT number bodies (without the country prefix). `d` is a digit, `a` a letter,
+/// `x` either; any other character must match literally.
+const VAT_FORMATS: &[(&str, &[&str])] = &[
+    ("AT", &["Udddddddd"]),
+    ("BE", &["dddddddddd"]),
+    ("BG", &["ddddddddd", "dddddddddd"]),
+    ("CY", &["dddddddda"]),
+    ("CZ", &["dddddddd", "ddddddddd", "dddddddddd"]),
+    ("DE", &["ddddddddd"]),
+    ("DK", &["dddddddd"]),
+    ("EE", &["ddddddddd"]),
+    ("EL", &["ddddddddd"]),
+    ("ES", &["xdddddddx"]),
+    ("FI", &["dddddddd"]),
+    ("FR", &["xxddddddddd"]),
+    ("HR", &["ddddddddddd"]),
+    ("HU", &["dddddddd"]),
+    ("IE", &["ddddddda", "dddddddaa", "dxddddda"]),
+    ("IT", &["ddddddddddd"]),
+    ("LT", &["ddddddddd", "dddddddddddd"]),
+    ("LU", &["dddddddd"]),
+    ("LV", &["ddddddddddd"]),
+    ("MT", &["dddddddd"]),
+    ("NL", &["dddddddddBdd"]),
+    ("PL", &["dddddddddd"]),
+    ("PT", &["ddddddddd"]),
+    (
+        "RO",
+        &["dd", "ddd", "dddd", "ddddd", "dddddd", "ddddddd", "dddddddd", "ddddddddd", "dddddddddd"],
+    ),
+    ("SE", &["dddddddddd01"]),
+    ("SI", &["dddddddd"]),
+    ("SK", &["dddddddddd"]),
+    ("GB", &["ddddddddd", "dddddddddddd", "GDddd", "HAddd"]),
+    ("XI", &["ddddddddd", "dddddddddddd", "GDddd", "HAddd"]),
+];
+
+/// Two-digit EIN prefixes the IRS has assigned to its campuses and online applications.
+const EIN_PREFIXES: &[(u8, u8)] = &[
+    (1, 6),
+    (10, 16),
+    (20, 27),
+    (30, 48),
+    (50, 68),
+    (71, 77),
+    (80, 88),
+    (90, 95),
+    (98, 99),
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum TaxIdKind {
+    Gstin,
+    EuVat,
+    Ein,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxIdCheck {
+    pub field: String,
+    pub value: String,
+    pub normalized: String,
+    pub kind: TaxIdKind,
+    pub valid: bool,
+    /// Whether a check digit confirmed the number. False when only the format could be
+    /// checked, as for EINs and VAT numbers of countries without a known check digit.
+    pub check_digit_verified: bool,
+    pub message: Option<String>,
+}
+
+pub struct TaxIdValidator;
+
+impl TaxIdValidator {
+    pub fn normalize(value: &str) -> String {
+        value
+            .chars()
+            .filter(|ch| !ch.is_whitespace() && !"-./".contains(*ch))
+            .flat_map(char::to_uppercase)
+            .collect()
+    }
+
+    pub fn detect_kind(normalized: &str) -> TaxIdKind {
+        let prefix = normalized.get(0..2).unwrap_or("");
+        if normalized.len() == 15 && prefix.chars().all(|ch| ch.is_ascii_digit()) {
+            TaxIdKind::Gstin
+        } else if VAT_FORMATS.iter().any(|(country, _)| *country == prefix) {
+            TaxIdKind::EuVat
+        } else if normalized.len() == 9 && normalized.chars().all(|ch| ch.is_ascii_digit()) {
+            TaxIdKind::Ein
+        } else {
+            TaxIdKind::Unknown
+        }
+    }
+
+    /// Validates a single identifier, returning an error message when it is malformed.
+    pub fn validate(value: &str) -> (TaxIdKind, Result<(), String>) {
+        let normalized = Self::normalize(value);
+        let kind = Self::detect_kind(&normalized);
+        let result = match kind {
+            TaxIdKind::Gstin => validate_gstin(&normalized),
+            TaxIdKind::EuVat => validate_vat(&normalized),
+            TaxIdKind::Ein => validate_ein(&normalized),
+            TaxIdKind::Unknown => Err(I18n::text("tax_id.unrecognized").to_string()),
+        };
+        (kind, result)
+    }
+
+    pub fn check_field(field: &str, value: &str) -> TaxIdCheck {
+        let (kind, result) = Self::validate(value);
+        let normalized = Self::normalize(value);
+        // Only a number that passed validation is known to match its format.
+        let check_digit_verified = result.is_ok()
+            && match kind {
+                TaxIdKind::Gstin => true,
+                TaxIdKind::EuVat => {
+                    let (country, body) = normalized.split_at(2);
+                    vat_check_digit(country, body).is_some()
+                }
+                TaxIdKind::Ein | TaxIdKind::Unknown => false,
+            };
+        TaxIdCheck {
+            field: field.to_string(),
+            value: value.to_string(),
+            normalized,
+            kind,
+            valid: result.is_ok(),
+            check_digit_verified,
+            message: result.err(),
+        }
+    }
+
+    /// Checks the seller/buyer identifiers of a parsed invoice, including
+    /// cross-field mismatches such as a buyer GSTIN from another state than the
+    /// place of supply.
+    pub fn check_invoice(invoice: &ParsedInvoice) -> Vec<TaxIdCheck> {
+        let mut checks = Vec::new();
+        let seller = invoice.text(SELLER_GSTIN);
+        let buyer = invoice.text(BUYER_GSTIN);
+
+        if let Some(value) = &seller {
+            checks.push(Self::check_field(SELLER_GSTIN, value));
+        }
+
+        if let Some(value) = &buyer {
+            let mut check = Self::check_field(BUYER_GSTIN, value);
+            if check.valid && check.kind == TaxIdKind::Gstin {
+                if let Some(place) = invoice.text(PLACE_OF_SUPPLY) {
+                    if let Some(message) = place_of_supply_mismatch(&check.normalized, &place) {
+                        check.valid = false;
+                        check.message = Some(message);
+                    }
+                }
+            }
+            checks.push(check);
+        }
+
+        if let (Some(seller), Some(buyer)) = (&seller, &buyer) {
+            if Self::normalize(seller) == Self::normalize(buyer) {
+                if let Some(check) = checks.last_mut() {
+                    check.valid = false;
+                    check.message = Some(I18n::text("tax_id.same_as_seller").to_string());
+                }
+            }
+        }
+
+        checks
+    }
+
+    pub fn gst_state_name(code: &str) -> Option<&'static str> {
+        GST_STATE_CODES
+            .iter()
+            .find(|(state_code, _)| *state_code == code)
+            .map(|(_, name)| *name)
+    }
+
+    /// Resolves a place-of-supply string ("27", "27-Maharashtra", "Maharashtra") to a GST state code.
+    pub fn gst_state_code(place: &str) -> Option<&'static str> {
+        let trimmed = place.trim();
+        let digits: String = trimmed.chars().take_while(|ch| ch.is_ascii_digit()).collect();
+        if digits.len() == 2 {
+            if let Some((code, _)) = GST_STATE_CODES.iter().find(|(code, _)| *code == digits) {
+                return Some(code);
+            }
+        }
+
+        let name = trimmed
+            .trim_start_matches(|ch: char| ch.is_ascii_digit() || ch == '-' || ch.is_whitespace())
+            .to_lowercase();
+        GST_STATE_CODES
+            .iter()
+            .find(|(_, state)| state.to_lowercase() == name)
+            .map(|(code, _)| *code)
+    }
+}
+
+fn gstin_checksum(body: &str) -> Option<char> {
+    let mut sum = 0;
+    for (index, ch) in body.chars().enumerate() {
+        let code = GSTIN_CHARSET.find(ch)? as u32;
+        let factor = if index % 2 == 0 { 1 } else { 2 };
+        let product = code * factor;
+        sum += product / 36 + product % 36;
+    }
+    let check = (36 - sum % 36) % 36;
+    GSTIN_CHARSET.chars().nth(check as usize)
+}
+
+fn validate_gstin(gstin: &str) -> Result<(), String> {
+    let chars: Vec<char> = gstin.chars().collect();
+    if chars.len() != 15 {
+        return Err(I18n::text("tax_id.gstin_length").to_string());
+    }
+
+    if TaxIdValidator::gst_state_name(&gstin[0..2]).is_none() {
+        return Err(I18n::format(
+            "tax_id.unknown_state",
+            &[("code", gstin[0..2].to_string())],
+        ));
+    }
+
+    let pan_ok = chars[2..7].iter().all(|ch| ch.is_ascii_uppercase())
+        && chars[7..11].iter().all(|ch| ch.is_ascii_digit())
+        && chars[11].is_ascii_uppercase();
+    if !pan_ok {
+        return Err(I18n::text("tax_id.invalid_pan").to_string());
+    }
+
+    if !(chars[12].is_ascii_uppercase() || ('1'..='9').contains(&chars[12])) {
+        return Err(I18n::text("tax_id.invalid_entity_code").to_string());
+    }
+
+    if chars[13] != 'Z' {
+        return Err(I18n::text("tax_id.missing_z").to_string());
+    }
+
+    match gstin_checksum(&gstin[0..14]) {
+        Some(expected) if expected == chars[14] => Ok(()),
+        Some(expected) => Err(I18n::format(
+            "tax_id.checksum_mismatch",
+            &[("expected", expected.to_string()), ("found", chars[14].to_string())],
+        )),
+        None => Err(I18n::text("tax_id.unsupported_characters").to_string()),
+    }
+}
+
+fn matches_format(body: &str, format: &str) -> bool {
+    body.chars().count() == format.chars().count()
+        && body.chars().zip(format.chars()).all(|(ch, pattern)| match pattern {
+            'd' => ch.is_ascii_digit(),
+            'a' => ch.is_ascii_uppercase(),
+            'x' => ch.is_ascii_alphanumeric(),
+            literal => ch == literal,
+        })
+}
+
+fn validate_vat(vat: &str) -> Result<(), String> {
+    let (country, body) = vat.split_at(2);
+    let formats = VAT_FORMATS
+        .iter()
+        .find(|(code, _)| *code == country)
+        .map(|(_, formats)| *formats)
+        .ok_or_else(|| {
+            I18n::format(
+                "tax_id.unsupported_vat_country",
+                &[("country", country.to_string())],
+            )
+        })?;
+
+    if !formats.iter().any(|format| matches_format(body, format)) {
+        return Err(I18n::format("tax_id.vat_format", &[("country", country.to_string())]));
+    }
+    match vat_check_digit(country, body) {
+        Some(false) => Err(I18n::format(
+            "tax_id.vat_check_digit",
+            &[("country", country.to_string())],
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Verifies the check digit of a VAT number body already matching its country's format.
+/// `None` for countries whose scheme is not checked here, and for French numbers with the
+/// newer alphanumeric key, which has no published algorithm.
+fn vat_check_digit(country: &str, body: &str) -> Option<bool> {
+    let digits: Vec<u32> = body.chars().filter_map(|ch| ch.to_digit(10)).collect();
+    match country {
+        "DE" => Some(mod_11_10(&digits[..8]) == digits[8]),
+        "FR" => {
+            let (key, siren) = body.split_at(2);
+            let key: u64 = key.parse().ok()?;
+            let siren: u64 = siren.parse().ok()?;
+            Some(key == (12 + 3 * (siren % 97)) % 97)
+        }
+        "IT" => Some(luhn_valid(&digits)),
+        // Numbers issued before 2020 carry an 11-proof; newer ones are ISO 7064 MOD 97-10
+        // over the whole number including the country code.
+        "NL" => {
+            let weighted: u32 = digits[..8]
+                .iter()
+                .zip((2..=9).rev())
+                .map(|(digit, weight)| digit * weight)
+                .sum();
+            Some(weighted % 11 == digits[8] || mod_97(&format!("NL{}", body)) == 1)
+        }
+        "ES" => Some(spanish_check(body)),
+        "BE" => {
+            let number: u64 = body[..8].parse().ok()?;
+            let check: u64 = body[8..].parse().ok()?;
+            Some(97 - number % 97 == check)
+        }
+        "PL" => {
+            let weighted: u32 = digits[..9]
+                .iter()
+                .zip([6, 5, 7, 2, 3, 4, 5, 6, 7])
+                .map(|(digit, weight)| digit * weight)
+                .sum();
+            Some(weighted % 11 == digits[9])
+        }
+        _ => None,
+    }
+}
+
+/// ISO 7064 MOD 11,10 check digit, as used by German VAT numbers.
+fn mod_11_10(digits: &[u32]) -> u32 {
+    let mut product = 10;
+    for digit in digits {
+        let sum = match (digit + product) % 10 {
+            0 => 10,
+            sum => sum,
+        };
+        product = (2 * sum) % 11;
+    }
+    (11 - product) % 10
+}
+
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| match (index % 2, digit * 2) {
+            (1, doubled) if doubled > 9 => doubled - 9,
+            (1, doubled) => doubled,
+            _ => *digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Remainder of the number read with letters as 10 to 35, as in IBANs.
+fn mod_97(value: &str) -> u32 {
+    value
+        .chars()
+        .filter_map(|ch| ch.to_digit(36))
+        .fold(0, |remainder, digit| {
+            let shift = if digit > 9 { 100 } else { 10 };
+            (remainder * shift + digit) % 97
+        })
+}
+
+/// NIF/NIE numbers of people end in a letter derived from the number. Company (CIF)
+/// numbers end in a control digit, or the matching letter of `JABCDEFGHI`, computed from
+/// the seven middle digits.
+fn spanish_check(body: &str) -> bool {
+    let chars: Vec<char> = body.chars().collect();
+    let (first, last) = (chars[0], chars[8]);
+    let middle: String = chars[1..8].iter().collect();
+
+    let personal_prefix = match first {
+        'X' => Some('0'),
+        'Y' => Some('1'),
+        'Z' => Some('2'),
+        digit if digit.is_ascii_digit() => Some(digit),
+        _ => None,
+    };
+    if let Some(prefix) = personal_prefix {
+        let number: u32 = match format!("{}{}", prefix, middle).parse() {
+            Ok(number) => number,
+            Err(_) => return false,
+        };
+        return DNI_LETTERS.chars().nth((number % 23) as usize) == Some(last);
+    }
+
+    let sum: u32 = middle
+        .chars()
+        .filter_map(|ch| ch.to_digit(10))
+        .enumerate()
+        .map(|(index, digit)| match index % 2 {
+            0 => (digit * 2) / 10 + (digit * 2) % 10,
+            _ => digit,
+        })
+        .sum();
+    let control = (10 - sum % 10) % 10;
+    let as_letter = "JABCDEFGHI".chars().nth(control as usize) == Some(last);
+    let as_digit = last.to_digit(10) == Some(control);
+    match first {
+        'K' | 'L' | 'M' | 'N' | 'P' | 'Q' | 'R' | 'S' | 'W' => as_letter,
+        'A' | 'B' | 'E' | 'H' => as_digit,
+        _ => as_letter || as_digit,
+    }
+}
+
+/// EINs carry no check digit, so only the campus prefix can be verified.
+fn validate_ein(ein: &str) -> Result<(), String> {
+    let prefix: u8 = ein[0..2].parse().unwrap_or(0);
+    if EIN_PREFIXES
+        .iter()
+        .any(|(first, last)| (*first..=*last).contains(&prefix))
+    {
+        Ok(())
+    } else {
+        Err(I18n::format("tax_id.ein_prefix", &[("prefix", ein[0..2].to_string())]))
+    }
+}
+
+fn place_of_supply_mismatch(gstin: &str, place: &str) -> Option<String> {
+    let place_code = TaxIdValidator::gst_state_code(place)?;
+    let gstin_code = &gstin[0..2];
+    if place_code == gstin_code {
+        return None;
+    }
+
+    Some(I18n::format(
+        "tax_id.place_of_supply_mismatch",
+        &[
+            (
+                "registered",
+                TaxIdValidator::gst_state_name(gstin_code).unwrap_or(gstin_code).to_string(),
+            ),
+            (
+                "place",
+                TaxIdValidator::gst_state_name(place_code).unwrap_or(place_code).to_string(),
+            ),
+        ],
+    ))
+}
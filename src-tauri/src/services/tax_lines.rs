@@ -0,0 +1,257 @@
+use crate::services::{
+    parsed_invoice::{ParsedInvoice, BUYER_GSTIN, PLACE_OF_SUPPLY, SELLER_GSTIN, SUBTOTAL, TAX_TOTAL},
+    tax_id_validator::TaxIdValidator,
+};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Where a file's tax lines were read from, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxLineSource {
+    /// The GST summary table printed on the invoice.
+    Printed,
+    /// The tax amounts of the line items, grouped by rate.
+    Items,
+    /// The subtotal and tax total, when nothing finer was extracted.
+    Totals,
+}
+
+impl TaxLineSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxLineSource::Printed => "printed",
+            TaxLineSource::Items => "items",
+            TaxLineSource::Totals => "totals",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "printed" => TaxLineSource::Printed,
+            "items" => TaxLineSource::Items,
+            _ => TaxLineSource::Totals,
+        }
+    }
+}
+
+/// Taxable value and GST components of an invoice at one rate.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxLine {
+    /// Total GST rate in percent, e.g. 18 for 9% CGST and 9% SGST.
+    pub rate: f64,
+    pub taxable_value: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub cess: f64,
+    pub source: TaxLineSource,
+}
+
+impl TaxLine {
+    fn new(rate: f64, source: TaxLineSource) -> Self {
+        Self {
+            rate,
+            taxable_value: 0.0,
+            cgst: 0.0,
+            sgst: 0.0,
+            igst: 0.0,
+            cess: 0.0,
+            source,
+        }
+    }
+
+    fn add(&mut self, other: &TaxLine) {
+        self.taxable_value += other.taxable_value;
+        self.cgst += other.cgst;
+        self.sgst += other.sgst;
+        self.igst += other.igst;
+        self.cess += other.cess;
+    }
+}
+
+/// Per-rate CGST, SGST, IGST and cess of each parsed file in `tax_lines`, so GST returns
+/// read the split instead of working it out from `parsed_details` again. Rewritten
+/// whenever the parsed details change.
+pub struct TaxLineStore;
+
+impl TaxLineStore {
+    /// Replaces the tax lines of a file with the ones `parsed_details` gives.
+    pub fn replace_for_file(
+        conn: &Connection,
+        file_id: &str,
+        parsed_details: Option<&str>,
+    ) -> Result<usize, String> {
+        conn.execute("DELETE FROM tax_lines WHERE file_id = ?1", params![file_id])
+            .map_err(|error| error.to_string())?;
+
+        let lines = parsed_details
+            .and_then(ParsedInvoice::from_json)
+            .map(|invoice| Self::compute(&invoice))
+            .unwrap_or_default();
+
+        let mut stmt = conn
+            .prepare(
+                "INSERT INTO tax_lines (file_id, rate, taxable_value, cgst, sgst, igst, cess, source)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )
+            .map_err(|error| error.to_string())?;
+        for line in &lines {
+            stmt.execute(params![
+                file_id,
+                line.rate,
+                line.taxable_value,
+                line.cgst,
+                line.sgst,
+                line.igst,
+                line.cess,
+                line.source.as_str(),
+            ])
+            .map_err(|error| error.to_string())?;
+        }
+
+        Ok(lines.len())
+    }
+
+    /// The stored tax lines of a file, lowest rate first.
+    pub fn for_file(conn: &Connection, file_id: &str) -> Result<Vec<TaxLine>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT rate, taxable_value, cgst, sgst, igst, cess, source
+                 FROM tax_lines WHERE file_id = ?1 ORDER BY rate ASC",
+            )
+            .map_err(|error| error.to_string())?;
+        let lines = stmt
+            .query_map(params![file_id], |row| {
+                Ok(TaxLine {
+                    rate: row.get(0)?,
+                    taxable_value: row.get(1)?,
+                    cgst: row.get(2)?,
+                    sgst: row.get(3)?,
+                    igst: row.get(4)?,
+                    cess: row.get(5)?,
+                    source: TaxLineSource::parse(&row.get::<_, String>(6)?),
+                })
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(lines)
+    }
+
+    /// Tax lines of an invoice: its printed GST summary when there is one, else its line
+    /// items grouped by rate, else a single line from its totals. Amounts are taken as
+    /// extracted; only a tax total without a split is divided, into IGST for inter-state
+    /// supplies and evenly into CGST and SGST otherwise.
+    pub fn compute(invoice: &ParsedInvoice) -> Vec<TaxLine> {
+        let printed: Vec<TaxLine> = invoice
+            .tax_breakup()
+            .iter()
+            .map(|row| {
+                let taxable = row.taxable_value.unwrap_or(0.0);
+                let tax = row.cgst.unwrap_or(0.0) + row.sgst.unwrap_or(0.0) + row.igst.unwrap_or(0.0);
+                TaxLine {
+                    taxable_value: taxable,
+                    cgst: row.cgst.unwrap_or(0.0),
+                    sgst: row.sgst.unwrap_or(0.0),
+                    igst: row.igst.unwrap_or(0.0),
+                    cess: row.cess.unwrap_or(0.0),
+                    ..TaxLine::new(
+                        row.rate.unwrap_or_else(|| implied_rate(taxable, tax)),
+                        TaxLineSource::Printed,
+                    )
+                }
+            })
+            .collect();
+        if !printed.is_empty() {
+            return by_rate(printed);
+        }
+
+        let items = invoice.line_items();
+        if !items.is_empty() {
+            return by_rate(
+                items
+                    .iter()
+                    .map(|item| {
+                        let rate = match item.igst_rate {
+                            Some(rate) => rate,
+                            None => item.cgst_rate.unwrap_or(0.0) + item.sgst_rate.unwrap_or(0.0),
+                        };
+                        TaxLine {
+                            taxable_value: item.amount.unwrap_or(0.0),
+                            cgst: item.cgst.unwrap_or(0.0),
+                            sgst: item.sgst.unwrap_or(0.0),
+                            igst: item.igst.unwrap_or(0.0),
+                            ..TaxLine::new(rate, TaxLineSource::Items)
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        let taxable = invoice.number(SUBTOTAL).unwrap_or(0.0);
+        let tax = invoice.number(TAX_TOTAL).unwrap_or(0.0);
+        let mut line = TaxLine {
+            taxable_value: taxable,
+            ..TaxLine::new(implied_rate(taxable, tax), TaxLineSource::Totals)
+        };
+        if is_inter_state(invoice, place_of_supply(invoice).as_deref()) {
+            line.igst = tax;
+        } else {
+            line.cgst = tax / 2.0;
+            line.sgst = tax / 2.0;
+        }
+        vec![line]
+    }
+}
+
+/// GST state code the supply is taxed in: the printed place of supply, or else the state of
+/// the buyer's GSTIN.
+pub fn place_of_supply(invoice: &ParsedInvoice) -> Option<String> {
+    invoice
+        .text(PLACE_OF_SUPPLY)
+        .and_then(|place| TaxIdValidator::gst_state_code(&place))
+        .or_else(|| {
+            invoice
+                .text(BUYER_GSTIN)
+                .map(|value| TaxIdValidator::normalize(&value))
+                .and_then(|value| value.get(..2).and_then(TaxIdValidator::gst_state_code))
+        })
+        .map(str::to_string)
+}
+
+/// Whether the seller's state differs from the place of supply, which makes the supply
+/// subject to IGST. False when either is unknown.
+pub fn is_inter_state(invoice: &ParsedInvoice, place_of_supply: Option<&str>) -> bool {
+    let seller_gstin = invoice
+        .text(SELLER_GSTIN)
+        .map(|value| TaxIdValidator::normalize(&value));
+    match (
+        seller_gstin.as_deref().and_then(|value| value.get(..2)),
+        place_of_supply,
+    ) {
+        (Some(supplier), Some(place)) => supplier != place,
+        _ => false,
+    }
+}
+
+fn by_rate(lines: Vec<TaxLine>) -> Vec<TaxLine> {
+    let mut grouped: BTreeMap<i64, TaxLine> = BTreeMap::new();
+    for line in lines {
+        grouped
+            .entry((line.rate * 100.0).round() as i64)
+            .and_modify(|existing| existing.add(&line))
+            .or_insert(line);
+    }
+    grouped.into_values().collect()
+}
+
+fn implied_rate(taxable: f64, tax: f64) -> f64 {
+    if taxable > 0.0 {
+        (tax / taxable * 10000.0).round() / 100.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,155 @@
+use crate::db::get_connection;
+use crate::services::settings::Settings;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+const ENABLED_KEY: &str = "telemetry.enabled";
+const INSTALL_ID_KEY: &str = "telemetry.install_id";
+const PERIOD_START_KEY: &str = "telemetry.period_start";
+const LAST_SENT_KEY: &str = "telemetry.last_sent_at";
+
+/// Collection endpoint baked in at build time. Builds without it never send anything,
+/// but the preview still shows what would be reported.
+const ENDPOINT: Option<&str> = option_env!("INVOX_TELEMETRY_URL");
+const SEND_INTERVAL_HOURS: i64 = 24;
+
+pub const FILES_IMPORTED: &str = "files_imported";
+pub const EXPORTS_GENERATED: &str = "exports_generated";
+pub const CRASHES: &str = "crashes";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPayload {
+    pub install_id: String,
+    pub app_version: &'static str,
+    pub os: &'static str,
+    pub period_start: Option<String>,
+    pub period_end: String,
+    pub counters: BTreeMap<String, i64>,
+}
+
+/// Opt-in usage counters. Only event counts are stored: no file names, parties,
+/// amounts or paths ever leave the machine, and nothing is recorded while disabled.
+pub struct Telemetry;
+
+impl Telemetry {
+    pub fn enabled(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, ENABLED_KEY)?.as_deref() == Some("1"))
+    }
+
+    /// Opting out also drops any counters that were not sent yet.
+    pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<(), String> {
+        if enabled {
+            if Settings::get(conn, INSTALL_ID_KEY)?.is_none() {
+                Settings::set(conn, INSTALL_ID_KEY, &Uuid::new_v4().to_string())?;
+            }
+            if Settings::get(conn, PERIOD_START_KEY)?.is_none() {
+                Settings::set(conn, PERIOD_START_KEY, &Utc::now().to_rfc3339())?;
+            }
+        } else {
+            Self::reset(conn)?;
+            Settings::remove(conn, INSTALL_ID_KEY)?;
+            Settings::remove(conn, PERIOD_START_KEY)?;
+        }
+        Settings::set(conn, ENABLED_KEY, if enabled { "1" } else { "0" })
+    }
+
+    pub fn increment(conn: &Connection, counter: &str) -> Result<(), String> {
+        if !Self::enabled(conn)? {
+            return Ok(());
+        }
+        conn.execute(
+            "INSERT INTO telemetry_counters (name, value) VALUES (?1, 1)
+             ON CONFLICT(name) DO UPDATE SET value = value + 1",
+            params![counter],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    /// Records use of a feature as `feature.<name>`. Best effort: a counter that cannot be
+    /// written never fails the feature itself.
+    pub fn record_feature(conn: &Connection, name: &str) {
+        let _ = Self::increment(conn, &format!("feature.{}", name));
+    }
+
+    /// Counts a generated export both overall and per export kind. Best effort, like
+    /// `record_feature`.
+    pub fn record_export(conn: &Connection, kind: &str) {
+        let _ = Self::increment(conn, EXPORTS_GENERATED);
+        Self::record_feature(conn, kind);
+    }
+
+    /// Best-effort counter bump for callers without a connection at hand.
+    pub fn track(counter: &str) {
+        if let Ok(conn) = get_connection() {
+            let _ = Self::increment(&conn, counter);
+        }
+    }
+
+    /// Exactly the payload the next upload would contain.
+    pub fn preview(conn: &Connection) -> Result<TelemetryPayload, String> {
+        let mut stmt = conn
+            .prepare("SELECT name, value FROM telemetry_counters ORDER BY name")
+            .map_err(|error| error.to_string())?;
+        let counters = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|error| error.to_string())?
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        Ok(TelemetryPayload {
+            install_id: Settings::get(conn, INSTALL_ID_KEY)?.unwrap_or_default(),
+            app_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            period_start: Settings::get(conn, PERIOD_START_KEY)?,
+            period_end: Utc::now().to_rfc3339(),
+            counters,
+        })
+    }
+
+    /// Uploads the batched counters once a day and starts a new period on success.
+    pub fn send_if_due() -> Result<(), String> {
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let Some(endpoint) = ENDPOINT else {
+            return Ok(());
+        };
+        if !Self::enabled(&conn)? {
+            return Ok(());
+        }
+
+        let last_sent =
+            Settings::get(&conn, LAST_SENT_KEY)?.and_then(|value| DateTime::parse_from_rfc3339(&value).ok());
+        if let Some(last_sent) = last_sent {
+            if Utc::now().signed_duration_since(last_sent) < Duration::hours(SEND_INTERVAL_HOURS) {
+                return Ok(());
+            }
+        }
+
+        let payload = Self::preview(&conn)?;
+        if payload.counters.is_empty() {
+            return Ok(());
+        }
+        let body = serde_json::to_string(&payload).map_err(|error| error.to_string())?;
+        ureq::AgentBuilder::new()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .post(endpoint)
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+            .map_err(|error| error.to_string())?;
+
+        Self::reset(&conn)?;
+        Settings::set(&conn, PERIOD_START_KEY, &payload.period_end)?;
+        Settings::set(&conn, LAST_SENT_KEY, &payload.period_end)
+    }
+
+    fn reset(conn: &Connection) -> Result<(), String> {
+        conn.execute("DELETE FROM telemetry_counters", [])
+            .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
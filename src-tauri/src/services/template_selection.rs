@@ -0,0 +1,321 @@
+use crate::services::extraction_templates::{ExtractionTemplate, ExtractionTemplates};
+use crate::services::party_registry::SELLERS;
+use lopdf::{Dictionary, Document, Object};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Shorter names match too much running text to identify a vendor on their own.
+const MIN_NAME_LENGTH: usize = 4;
+
+/// A stored PDF as templates see it: its text layer and a hash of each image on the first
+/// page, where letterhead logos sit.
+pub struct TemplateDocument {
+    pub text: String,
+    pub logo_hashes: Vec<String>,
+}
+
+impl TemplateDocument {
+    /// Scanned documents and images have no text layer, so templates cannot read them.
+    pub fn load(conn: &Connection, file_id: &str) -> Result<Self, String> {
+        let stored_path: String = conn
+            .query_row(
+                "SELECT stored_path FROM files WHERE id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?
+            .ok_or_else(|| format!("File not found: {}", file_id))?;
+        let path = Path::new(&stored_path);
+        let is_pdf = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        if !is_pdf {
+            return Err("Extraction templates only read PDFs with a text layer.".to_string());
+        }
+
+        let document =
+            Document::load(path).map_err(|error| format!("The PDF could not be read: {}", error))?;
+        let pages = document.get_pages();
+        let numbers: Vec<u32> = pages.keys().copied().collect();
+        let text = document.extract_text(&numbers).unwrap_or_default();
+        if text.trim().is_empty() {
+            return Err("This PDF has no text layer, so templates cannot read it.".to_string());
+        }
+        let logo_hashes = pages
+            .values()
+            .next()
+            .map(|page_id| first_page_images(&document, *page_id))
+            .unwrap_or_default();
+        Ok(Self { text, logo_hashes })
+    }
+
+    /// The smallest first-page image: a logo rather than a background or a full-page scan.
+    fn logo_hash(&self) -> Option<&String> {
+        self.logo_hashes.first()
+    }
+}
+
+/// How a vendor was recognised in a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VendorSignal {
+    Gstin,
+    Logo,
+    Name,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateCandidate {
+    pub template_id: i64,
+    pub name: String,
+    pub seller_id: Option<i64>,
+    pub pattern_matched: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateMatch {
+    pub seller_id: Option<i64>,
+    pub seller_name: Option<String>,
+    pub detected_by: Option<VendorSignal>,
+    pub template_id: Option<i64>,
+    pub template_name: Option<String>,
+    /// Why this template, or none, was picked; shown as is.
+    pub reason: String,
+    /// Every enabled template and whether its match pattern is in the document.
+    pub candidates: Vec<TemplateCandidate>,
+    #[serde(skip)]
+    pub template: Option<ExtractionTemplate>,
+}
+
+/// Picks the template for a document. The vendor is recognised by a GSTIN, logo or name
+/// known to master data, and its templates are preferred; otherwise any template whose
+/// match pattern is found is used, and without one the file takes the generic model path.
+pub struct TemplateSelector;
+
+impl TemplateSelector {
+    pub fn select(conn: &Connection, document: &TemplateDocument) -> Result<TemplateMatch, String> {
+        let templates: Vec<ExtractionTemplate> = ExtractionTemplates::list(conn)?
+            .into_iter()
+            .filter(|template| template.enabled)
+            .collect();
+        let candidates: Vec<TemplateCandidate> = templates
+            .iter()
+            .map(|template| TemplateCandidate {
+                template_id: template.id,
+                name: template.name.clone(),
+                seller_id: template.seller_id,
+                pattern_matched: ExtractionTemplates::matches(template, &document.text),
+            })
+            .collect();
+        let matched = |template: &&ExtractionTemplate| {
+            candidates
+                .iter()
+                .any(|candidate| candidate.template_id == template.id && candidate.pattern_matched)
+        };
+
+        let vendor = detect_vendor(conn, document, &templates)?;
+        let mut notes = Vec::new();
+        if let Some((seller_id, seller_name, signal)) = &vendor {
+            let recognised = format!("Recognised {} by its {}", seller_name, signal_label(*signal));
+            let own: Vec<&ExtractionTemplate> = templates
+                .iter()
+                .filter(|template| template.seller_id == Some(*seller_id))
+                .collect();
+            if let Some(template) = own.iter().copied().find(matched) {
+                return Ok(chosen(
+                    vendor.clone(),
+                    template,
+                    format!("{}.", recognised),
+                    candidates,
+                ));
+            }
+            notes.push(if own.is_empty() {
+                format!("{}, but it has no template.", recognised)
+            } else {
+                format!(
+                    "{}, but the match pattern of its templates is not in this document.",
+                    recognised
+                )
+            });
+        }
+
+        // Vendor templates first, so a generic catch-all does not shadow them.
+        let mut fallbacks: Vec<&ExtractionTemplate> = templates.iter().filter(matched).collect();
+        fallbacks.sort_by_key(|template| template.seller_id.is_none());
+        if let Some(template) = fallbacks.first() {
+            notes.push("The template's match pattern is in this document.".to_string());
+            return Ok(chosen(vendor, template, notes.join(" "), candidates));
+        }
+
+        notes.push(if templates.is_empty() {
+            "No templates are enabled, so a model extracts this file.".to_string()
+        } else {
+            "No template matches, so a model extracts this file.".to_string()
+        });
+        let (seller_id, seller_name, detected_by) = split_vendor(vendor);
+        Ok(TemplateMatch {
+            seller_id,
+            seller_name,
+            detected_by,
+            template_id: None,
+            template_name: None,
+            reason: notes.join(" "),
+            candidates,
+            template: None,
+        })
+    }
+
+    /// Keeps the document's logo on a template it was extracted with, so later documents
+    /// with the same letterhead find it even when their text differs.
+    pub fn remember_logo(
+        conn: &Connection,
+        template_id: i64,
+        document: &TemplateDocument,
+    ) -> Result<(), String> {
+        let Some(logo_hash) = document.logo_hash() else {
+            return Ok(());
+        };
+        conn.execute(
+            "UPDATE extraction_templates SET logo_hash = ?1 WHERE id = ?2",
+            params![logo_hash, template_id],
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
+
+fn chosen(
+    vendor: Option<(i64, String, VendorSignal)>,
+    template: &ExtractionTemplate,
+    reason: String,
+    candidates: Vec<TemplateCandidate>,
+) -> TemplateMatch {
+    let (seller_id, seller_name, detected_by) = split_vendor(vendor);
+    TemplateMatch {
+        seller_id: seller_id.or(template.seller_id),
+        seller_name: seller_name.or_else(|| template.seller_name.clone()),
+        detected_by,
+        template_id: Some(template.id),
+        template_name: Some(template.name.clone()),
+        reason: format!("{} Using \"{}\".", reason, template.name),
+        candidates,
+        template: Some(template.clone()),
+    }
+}
+
+fn split_vendor(
+    vendor: Option<(i64, String, VendorSignal)>,
+) -> (Option<i64>, Option<String>, Option<VendorSignal>) {
+    match vendor {
+        Some((id, name, signal)) => (Some(id), Some(name), Some(signal)),
+        None => (None, None, None),
+    }
+}
+
+fn signal_label(signal: VendorSignal) -> &'static str {
+    match signal {
+        VendorSignal::Gstin => "GSTIN",
+        VendorSignal::Logo => "logo",
+        VendorSignal::Name => "name",
+    }
+}
+
+/// GSTIN first, being unambiguous, then a logo seen on one of the vendor's templates,
+/// then the vendor's name or one of its aliases appearing in the text.
+fn detect_vendor(
+    conn: &Connection,
+    document: &TemplateDocument,
+    templates: &[ExtractionTemplate],
+) -> Result<Option<(i64, String, VendorSignal)>, String> {
+    let text = document.text.to_uppercase();
+    for gstin in gstin_pattern().find_iter(&text) {
+        let seller: Option<(i64, String)> = conn
+            .query_row(
+                "SELECT id, name FROM sellers WHERE UPPER(tax_id) = ?1",
+                params![gstin.as_str()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| error.to_string())?;
+        if let Some((id, name)) = seller {
+            return Ok(Some((id, name, VendorSignal::Gstin)));
+        }
+    }
+
+    let logos: HashSet<&String> = document.logo_hashes.iter().collect();
+    let by_logo = templates.iter().find(|template| {
+        template.seller_id.is_some()
+            && template
+                .logo_hash
+                .as_ref()
+                .is_some_and(|hash| logos.contains(hash))
+    });
+    if let Some(template) = by_logo {
+        if let (Some(id), Some(name)) = (template.seller_id, template.seller_name.clone()) {
+            return Ok(Some((id, name, VendorSignal::Logo)));
+        }
+    }
+
+    let text = document.text.to_lowercase();
+    let found = SELLERS.list(conn)?.into_iter().find(|seller| {
+        std::iter::once(&seller.name)
+            .chain(seller.aliases.iter())
+            .map(|name| name.trim().to_lowercase())
+            .any(|name| name.len() >= MIN_NAME_LENGTH && text.contains(&name))
+    });
+    Ok(found.map(|seller| (seller.id, seller.name, VendorSignal::Name)))
+}
+
+fn gstin_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\b\d{2}[A-Z]{5}\d{4}[A-Z][1-9A-Z]Z[0-9A-Z]\b").expect("valid GSTIN pattern")
+    })
+}
+
+/// blake3 of each image on the page, smallest first.
+fn first_page_images(document: &Document, page_id: lopdf::ObjectId) -> Vec<String> {
+    let (inline, inherited) = document.get_page_resources(page_id);
+    let mut resources: Vec<&Dictionary> = inline.into_iter().collect();
+    resources.extend(
+        inherited
+            .into_iter()
+            .filter_map(|id| document.get_dictionary(id).ok()),
+    );
+
+    let mut images: Vec<&[u8]> = resources
+        .into_iter()
+        .filter_map(|resources| resources.get(b"XObject").ok())
+        .filter_map(|xobjects| resolve(document, xobjects).as_dict().ok())
+        .flat_map(|xobjects| xobjects.iter())
+        .filter_map(|(_, object)| resolve(document, object).as_stream().ok())
+        .filter(|stream| {
+            stream
+                .dict
+                .get(b"Subtype")
+                .and_then(Object::as_name_str)
+                .is_ok_and(|subtype| subtype == "Image")
+        })
+        .map(|stream| stream.content.as_slice())
+        .collect();
+    images.sort_by_key(|content| content.len());
+    images
+        .into_iter()
+        .map(|content| blake3::hash(content).to_hex().to_string())
+        .collect()
+}
+
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => document.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
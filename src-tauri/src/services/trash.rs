@@ -0,0 +1,572 @@
+use crate::db::trash_dir;
+use crate::services::audit_log::AuditLog;
+use crate::services::roles::Roles;
+use crate::services::secure_delete::SecureDelete;
+use crate::services::settings::Settings;
+use crate::services::workspace_lock::WorkspaceLock;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RETENTION_DAYS_KEY: &str = "trash.retention_days";
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// Key a BLOB column is stored under in a snapshot, base64 encoded.
+const BLOB_KEY: &str = "$blob";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedFile {
+    pub file_id: String,
+    pub file_name: String,
+    pub size_bytes: i64,
+    pub deleted_at: String,
+    /// Role that deleted the file, from the audit log.
+    pub deleted_by: Option<String>,
+    pub reason: Option<String>,
+    /// When the retention setting will purge it; `None` when auto-purge is off.
+    pub purge_after: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteOutcome {
+    /// Moved to the trash, from where it can be restored.
+    Trashed,
+    /// Deleted for good, as secure delete is on.
+    Shredded,
+    NotFound,
+    /// Kept, as its document could not be moved; see `error`.
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDeleteResult {
+    pub file_id: String,
+    /// `None` when the file was not found.
+    pub file_name: Option<String>,
+    pub outcome: DeleteOutcome,
+    /// Why the file was kept, or what went wrong overwriting a shredded document.
+    pub error: Option<String>,
+}
+
+/// A file whose document has been moved into the trash folder ahead of deleting its row.
+struct StagedFile {
+    /// Position of the file's entry in the results.
+    index: usize,
+    stored_path: String,
+    /// `None` when the document was already missing from disk.
+    trash_path: Option<PathBuf>,
+}
+
+/// Recently deleted files. Deleting moves the document into the trash folder and its row
+/// into `deleted_files`, together with a snapshot of the file's row and of every row
+/// other tables keep for it, so restoring brings back its parsed details, history, tags
+/// and sheet entries as they were. Purging removes the document and snapshot for good.
+pub struct Trash;
+
+impl Trash {
+    /// Days a deleted file is kept before it is purged automatically; 0 keeps it until purged.
+    pub fn retention_days(conn: &Connection) -> Result<i64, String> {
+        Ok(Settings::get(conn, RETENTION_DAYS_KEY)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_DAYS))
+    }
+
+    pub fn set_retention_days(conn: &Connection, days: i64) -> Result<(), String> {
+        if days < 0 {
+            return Err("Retention must be zero or more days.".to_string());
+        }
+        Settings::set(conn, RETENTION_DAYS_KEY, &days.to_string())
+    }
+
+    /// Deletes `file_ids`, to the trash or, when `shred` is set, for good. Every document
+    /// is first moved into the trash folder; a file whose document cannot be moved is
+    /// reported as failed and kept. The rows of the rest are removed in one transaction, and
+    /// if that fails each document is moved back and nothing is deleted. Shredded documents
+    /// are only overwritten once the transaction has committed.
+    pub fn delete(
+        conn: &Connection,
+        file_ids: &[String],
+        shred: bool,
+        reason: Option<&str>,
+    ) -> Result<Vec<FileDeleteResult>, String> {
+        let mut seen = HashSet::new();
+        let mut results = Vec::with_capacity(file_ids.len());
+        let mut staged = Vec::new();
+        for file_id in file_ids.iter().filter(|file_id| seen.insert(file_id.as_str())) {
+            let row: Option<(String, String)> = conn
+                .query_row(
+                    "SELECT file_name, stored_path FROM files WHERE id = ?1",
+                    params![file_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let Some((file_name, stored_path)) = row else {
+                results.push(FileDeleteResult {
+                    file_id: file_id.clone(),
+                    file_name: None,
+                    outcome: DeleteOutcome::NotFound,
+                    error: None,
+                });
+                continue;
+            };
+
+            let (outcome, error) = match stage(&stored_path) {
+                Ok(trash_path) => {
+                    staged.push(StagedFile {
+                        index: results.len(),
+                        stored_path,
+                        trash_path,
+                    });
+                    let outcome = if shred {
+                        DeleteOutcome::Shredded
+                    } else {
+                        DeleteOutcome::Trashed
+                    };
+                    (outcome, None)
+                }
+                Err(error) => (DeleteOutcome::Failed, Some(error)),
+            };
+            results.push(FileDeleteResult {
+                file_id: file_id.clone(),
+                file_name: Some(file_name),
+                outcome,
+                error,
+            });
+        }
+
+        if let Err(error) = remove_rows(conn, &results, &staged, shred, reason) {
+            for file in &staged {
+                if let Some(trash_path) = &file.trash_path {
+                    let _ = fs::rename(trash_path, &file.stored_path);
+                }
+            }
+            return Err(error);
+        }
+
+        if shred {
+            for file in &staged {
+                let Some(trash_path) = &file.trash_path else {
+                    continue;
+                };
+                if let Err(error) = SecureDelete::remove_file(trash_path, true) {
+                    results[file.index].error = Some(format!(
+                        "The document could not be overwritten and is still in the trash folder: {}",
+                        error
+                    ));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Most recently deleted first.
+    pub fn list(conn: &Connection, limit: i64) -> Result<Vec<DeletedFile>, String> {
+        let retention = Self::retention_days(conn)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT file_id, file_name, size_bytes, deleted_at FROM deleted_files
+                 ORDER BY deleted_at DESC LIMIT ?1",
+            )
+            .map_err(|error| error.to_string())?;
+        let rows = stmt
+            .query_map(params![limit.max(1)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+
+        let mut files = Vec::with_capacity(rows.len());
+        for (file_id, file_name, size_bytes, deleted_at) in rows {
+            let details: Option<Value> = conn
+                .query_row(
+                    "SELECT details FROM audit_log WHERE action = 'file.deleted' AND subject = ?1
+                     ORDER BY id DESC LIMIT 1",
+                    params![file_id],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?
+                .flatten()
+                .and_then(|raw| serde_json::from_str(&raw).ok());
+            let detail = |key: &str| {
+                details
+                    .as_ref()
+                    .and_then(|details| details.get(key))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            };
+            let purge_after = DateTime::parse_from_rfc3339(&deleted_at)
+                .ok()
+                .filter(|_| retention > 0)
+                .map(|deleted| (deleted + Duration::days(retention)).to_rfc3339());
+
+            files.push(DeletedFile {
+                deleted_by: detail("role"),
+                reason: detail("reason"),
+                file_id,
+                file_name,
+                size_bytes,
+                deleted_at,
+                purge_after,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Puts trashed files back where they were. Returns how many were restored; ids that are
+    /// not in the trash are skipped.
+    pub fn restore(conn: &Connection, file_ids: &[String]) -> Result<usize, String> {
+        let mut restored = 0;
+        for file_id in file_ids {
+            let trashed: Option<(String, Option<String>, Option<String>)> = conn
+                .query_row(
+                    "SELECT file_name, trash_path, snapshot FROM deleted_files WHERE file_id = ?1",
+                    params![file_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let Some((file_name, trash_path, snapshot)) = trashed else {
+                continue;
+            };
+            let Some(Value::Object(tables)) = snapshot.and_then(|raw| serde_json::from_str(&raw).ok()) else {
+                return Err(format!(
+                    "{} was deleted before files could be restored; it can only be purged.",
+                    file_name
+                ));
+            };
+            let stored_path = tables
+                .get("files")
+                .and_then(|rows| rows.get(0))
+                .and_then(|row| row.get("stored_path"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("The snapshot of {} is incomplete.", file_name))?;
+
+            let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+            // The file's own row first, so the triggers on `files` see it before anything
+            // that refers to it.
+            let mut ordered: Vec<(&String, &Value)> = tables.iter().collect();
+            ordered.sort_by_key(|(table, _)| table.as_str() != "files");
+            for (table, rows) in ordered {
+                let key_column = if table == "files" { "id" } else { "file_id" };
+                restore_rows(&tx, table, key_column, file_id, rows)?;
+            }
+            tx.execute("DELETE FROM deleted_files WHERE file_id = ?1", params![file_id])
+                .map_err(|error| error.to_string())?;
+            AuditLog::record(
+                &tx,
+                "file.restored",
+                Some(file_id),
+                Some(serde_json::json!({ "fileName": file_name })),
+            )?;
+
+            let moved = match trash_path.as_deref().map(Path::new).filter(|path| path.exists()) {
+                Some(trashed) => {
+                    let target = Path::new(&stored_path);
+                    if target.exists() {
+                        return Err(format!("Another file already exists at {}.", stored_path));
+                    }
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+                    }
+                    fs::rename(trashed, target).map_err(|error| error.to_string())?;
+                    Some((trashed, target))
+                }
+                None => None,
+            };
+            if let Err(error) = tx.commit() {
+                if let Some((trashed, target)) = moved {
+                    let _ = fs::rename(target, trashed);
+                }
+                return Err(error.to_string());
+            }
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// Removes trashed files for good, shredding them when secure delete is on.
+    pub fn purge(conn: &Connection, file_ids: &[String], source: &str) -> Result<usize, String> {
+        let shred = SecureDelete::enabled(conn)?;
+        let mut purged = 0;
+        for file_id in file_ids {
+            let trashed: Option<(String, Option<String>)> = conn
+                .query_row(
+                    "SELECT file_name, trash_path FROM deleted_files WHERE file_id = ?1",
+                    params![file_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|error| error.to_string())?;
+            let Some((file_name, trash_path)) = trashed else {
+                continue;
+            };
+
+            if let Some(path) = trash_path.as_deref().map(Path::new).filter(|path| path.exists()) {
+                SecureDelete::remove_file(path, shred)?;
+            }
+            conn.execute("DELETE FROM deleted_files WHERE file_id = ?1", params![file_id])
+                .map_err(|error| error.to_string())?;
+            AuditLog::record(
+                conn,
+                "file.purged",
+                Some(file_id),
+                Some(serde_json::json!({ "fileName": file_name, "shredded": shred, "source": source })),
+            )?;
+            purged += 1;
+        }
+        Ok(purged)
+    }
+
+    /// Purges files kept longer than the retention setting.
+    pub fn purge_expired(conn: &Connection) -> Result<usize, String> {
+        let retention = Self::retention_days(conn)?;
+        if retention == 0 || WorkspaceLock::is_locked(conn)? {
+            return Ok(0);
+        }
+        let expired = deleted_before(conn, Utc::now() - Duration::days(retention))?;
+        Self::purge(conn, &expired, "retention")
+    }
+
+    /// Purges files deleted more than `days` ago; 0 empties the trash.
+    pub fn purge_older_than(conn: &Connection, days: i64) -> Result<usize, String> {
+        if days < 0 {
+            return Err("Age must be zero or more days.".to_string());
+        }
+        let expired = deleted_before(conn, Utc::now() - Duration::days(days))?;
+        Self::purge(conn, &expired, "manual")
+    }
+}
+
+/// Moves a document into the trash folder, returning where it went; `None` when it is not
+/// on disk.
+fn stage(stored_path: &str) -> Result<Option<PathBuf>, String> {
+    let source = Path::new(stored_path);
+    match source.file_name() {
+        Some(name) if source.exists() => {
+            let target = trash_dir().map_err(|error| error.to_string())?.join(name);
+            fs::rename(source, &target)
+                .map_err(|error| format!("The document could not be moved to the trash: {}", error))?;
+            Ok(Some(target))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Deletes the rows of the staged files in one transaction. Trashed files keep theirs in
+/// `deleted_files`, with a snapshot to restore from.
+fn remove_rows(
+    conn: &Connection,
+    results: &[FileDeleteResult],
+    staged: &[StagedFile],
+    shred: bool,
+    reason: Option<&str>,
+) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|error| error.to_string())?;
+    let role = Roles::current(&tx)?;
+    let deleted_at = Utc::now().to_rfc3339();
+    for file in staged {
+        let result = &results[file.index];
+        if !shred {
+            let snapshot = snapshot(&tx, &result.file_id)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO deleted_files
+                   (file_id, file_name, trash_path, size_bytes, content_hash, hash_algo, status, parsed_details,
+                    deleted_at, snapshot)
+                 SELECT id, file_name, ?2, size_bytes, content_hash, hash_algo, status, parsed_details, ?3, ?4
+                 FROM files WHERE id = ?1",
+                params![
+                    result.file_id,
+                    file.trash_path
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().into_owned()),
+                    deleted_at,
+                    snapshot.to_string()
+                ],
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        tx.execute("DELETE FROM files WHERE id = ?1", params![result.file_id])
+            .map_err(|error| error.to_string())?;
+        AuditLog::record(
+            &tx,
+            "file.deleted",
+            Some(&result.file_id),
+            Some(serde_json::json!({
+                "fileName": result.file_name,
+                "shredded": shred,
+                "trashed": !shred,
+                "role": role.as_str(),
+                "reason": reason,
+            })),
+        )?;
+    }
+    tx.commit().map_err(|error| error.to_string())
+}
+
+fn deleted_before(conn: &Connection, cutoff: DateTime<Utc>) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT file_id FROM deleted_files WHERE deleted_at <= ?1")
+        .map_err(|error| error.to_string())?;
+    let file_ids = stmt
+        .query_map(params![cutoff.to_rfc3339()], |row| row.get::<_, String>(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    Ok(file_ids)
+}
+
+/// The file's row and the rows of every table with a `file_id` column, keyed by table.
+fn snapshot(conn: &Connection, file_id: &str) -> Result<Value, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.name FROM sqlite_master m JOIN pragma_table_info(m.name) p
+             WHERE m.type = 'table' AND p.name = 'file_id' AND m.name <> 'deleted_files'
+               AND m.sql NOT LIKE 'CREATE VIRTUAL TABLE%'",
+        )
+        .map_err(|error| error.to_string())?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    let mut snapshot = Map::new();
+    snapshot.insert("files".to_string(), table_rows(conn, "files", "id", file_id)?);
+    for table in tables {
+        let rows = table_rows(conn, &table, "file_id", file_id)?;
+        if rows.as_array().is_some_and(|rows| !rows.is_empty()) {
+            snapshot.insert(table, rows);
+        }
+    }
+    Ok(Value::Object(snapshot))
+}
+
+fn table_rows(conn: &Connection, table: &str, key_column: &str, file_id: &str) -> Result<Value, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT * FROM \"{}\" WHERE \"{}\" = ?1",
+            table, key_column
+        ))
+        .map_err(|error| error.to_string())?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let mut rows = stmt.query(params![file_id]).map_err(|error| error.to_string())?;
+    let mut values = Vec::new();
+    while let Some(row) = rows.next().map_err(|error| error.to_string())? {
+        let mut object = Map::new();
+        for (index, column) in columns.iter().enumerate() {
+            let value = match row.get_ref(index).map_err(|error| error.to_string())? {
+                ValueRef::Null => Value::Null,
+                ValueRef::Integer(value) => Value::from(value),
+                ValueRef::Real(value) => Value::from(value),
+                ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+                ValueRef::Blob(bytes) => serde_json::json!({ BLOB_KEY: STANDARD.encode(bytes) }),
+            };
+            object.insert(column.clone(), value);
+        }
+        values.push(Value::Object(object));
+    }
+    Ok(Value::Array(values))
+}
+
+/// Writes snapshot rows back into `table`. Tables that still hold rows for the file were
+/// not cleared by the delete and are left as they are, as are columns the table no longer
+/// has.
+fn restore_rows(
+    conn: &Connection,
+    table: &str,
+    key_column: &str,
+    file_id: &str,
+    rows: &Value,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")
+        .map_err(|error| error.to_string())?;
+    let columns = stmt
+        .query_map(params![table], |row| row.get::<_, String>(0))
+        .map_err(|error| error.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    if columns.is_empty() {
+        return Ok(());
+    }
+    let present = conn
+        .query_row(
+            &format!(
+                "SELECT 1 FROM \"{}\" WHERE \"{}\" = ?1 LIMIT 1",
+                table, key_column
+            ),
+            params![file_id],
+            |_row| Ok(()),
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .is_some();
+    if present {
+        if table == "files" {
+            return Err(format!("File {} is already in the library.", file_id));
+        }
+        return Ok(());
+    }
+
+    for row in rows.as_array().into_iter().flatten() {
+        let Some(row) = row.as_object() else {
+            continue;
+        };
+        let (names, values): (Vec<String>, Vec<SqlValue>) = row
+            .iter()
+            .filter(|(column, _)| columns.contains(column))
+            .map(|(column, value)| (format!("\"{}\"", column), sql_value(value)))
+            .unzip();
+        if names.is_empty() {
+            continue;
+        }
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO \"{}\" ({}) VALUES ({})",
+                table,
+                names.join(", "),
+                vec!["?"; names.len()].join(", ")
+            ),
+            params_from_iter(values),
+        )
+        .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(flag) => SqlValue::Integer(*flag as i64),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => SqlValue::Integer(integer),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => SqlValue::Text(text.clone()),
+        Value::Object(object) => match object.get(BLOB_KEY).and_then(Value::as_str) {
+            Some(encoded) => STANDARD
+                .decode(encoded)
+                .map(SqlValue::Blob)
+                .unwrap_or(SqlValue::Null),
+            None => SqlValue::Text(value.to_string()),
+        },
+        Value::Array(_) => SqlValue::Text(value.to_string()),
+    }
+}
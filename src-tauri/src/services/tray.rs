@@ -0,0 +1,159 @@
+use crate::db::get_connection;
+use crate::services::{
+    audit_log::AuditLog,
+    i18n::I18n,
+    launch_import::{focus_main_window, LaunchImport},
+    processing_pause::ProcessingPause,
+    settings::Settings,
+};
+use rusqlite::Connection;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Manager, Window, WindowEvent};
+use tauri_plugin_dialog::DialogExt;
+
+const BACKGROUND_MODE_KEY: &str = "app.background_mode";
+
+const MENU_OPEN: &str = "tray.open";
+const MENU_IMPORT: &str = "tray.import_files";
+const MENU_PAUSE: &str = "tray.toggle_pause";
+const MENU_QUIT: &str = "tray.quit";
+
+/// Extensions offered by "Import files…"; the same documents the library accepts.
+const IMPORT_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg", "webp", "tif", "tiff", "bmp", "heic"];
+
+/// Tray menu items whose label follows backend state.
+struct TrayMenu {
+    pause: MenuItem,
+}
+
+/// Tray icon and background mode. With background mode on, closing the main window only
+/// hides it, so the scheduler and other background loops keep running until "Quit".
+pub struct Tray;
+
+impl Tray {
+    pub fn background_mode(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, BACKGROUND_MODE_KEY)?.as_deref() == Some("1"))
+    }
+
+    pub fn set_background_mode(conn: &Connection, enabled: bool) -> Result<(), String> {
+        Settings::set(conn, BACKGROUND_MODE_KEY, if enabled { "1" } else { "0" })
+    }
+
+    pub fn install(app: &App) -> tauri::Result<()> {
+        let paused = get_connection()
+            .ok()
+            .and_then(|conn| ProcessingPause::is_paused(&conn).ok())
+            .unwrap_or(false);
+
+        let open = MenuItem::with_id(app, MENU_OPEN, I18n::text("tray.open"), true, None::<&str>)?;
+        let import = MenuItem::with_id(
+            app,
+            MENU_IMPORT,
+            I18n::text("tray.import_files"),
+            true,
+            None::<&str>,
+        )?;
+        let pause = MenuItem::with_id(app, MENU_PAUSE, pause_label(paused), true, None::<&str>)?;
+        let quit = MenuItem::with_id(app, MENU_QUIT, I18n::text("tray.quit"), true, None::<&str>)?;
+        let menu = Menu::with_items(
+            app,
+            &[
+                &open,
+                &PredefinedMenuItem::separator(app)?,
+                &import,
+                &pause,
+                &PredefinedMenuItem::separator(app)?,
+                &quit,
+            ],
+        )?;
+        app.manage(TrayMenu { pause });
+
+        let mut tray = TrayIconBuilder::with_id("main")
+            .tooltip("Invox AI")
+            .menu(&menu)
+            .show_menu_on_left_click(false)
+            .on_menu_event(on_menu_event)
+            .on_tray_icon_event(|tray, event| {
+                if let TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                } = event
+                {
+                    focus_main_window(tray.app_handle());
+                }
+            });
+        if let Some(icon) = app.default_window_icon() {
+            tray = tray.icon(icon.clone());
+        }
+        tray.build(app)?;
+        Ok(())
+    }
+
+    /// Window hook: keeps the app alive in the tray instead of exiting on close.
+    pub fn on_window_event(window: &Window, event: &WindowEvent) {
+        let WindowEvent::CloseRequested { api, .. } = event else {
+            return;
+        };
+        let background = get_connection()
+            .ok()
+            .and_then(|conn| Self::background_mode(&conn).ok())
+            .unwrap_or(false);
+        if background && window.label() == "main" {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+
+    /// Keeps the tray label in step when processing is paused from the UI.
+    pub fn sync_pause_label(app: &AppHandle, paused: bool) {
+        if let Some(menu) = app.try_state::<TrayMenu>() {
+            let _ = menu.pause.set_text(pause_label(paused));
+        }
+    }
+}
+
+fn pause_label(paused: bool) -> &'static str {
+    if paused {
+        I18n::text("tray.resume_processing")
+    } else {
+        I18n::text("tray.pause_processing")
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_OPEN => focus_main_window(app),
+        MENU_IMPORT => {
+            let handle = app.clone();
+            app.dialog()
+                .file()
+                .add_filter("Invoices", IMPORT_EXTENSIONS)
+                .pick_files(move |picked| {
+                    let paths: Vec<_> = picked
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|path| path.into_path().ok())
+                        .collect();
+                    LaunchImport::from_paths(&handle, paths);
+                });
+        }
+        MENU_PAUSE => {
+            let Ok(conn) = get_connection() else {
+                return;
+            };
+            let paused = !ProcessingPause::is_paused(&conn).unwrap_or(false);
+            if ProcessingPause::set_paused(app, &conn, paused).is_ok() {
+                let _ = AuditLog::record(
+                    &conn,
+                    "processing.paused_changed",
+                    None,
+                    Some(serde_json::json!({ "paused": paused, "source": "tray" })),
+                );
+                Tray::sync_pause_label(app, paused);
+            }
+        }
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
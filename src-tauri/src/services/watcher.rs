@@ -0,0 +1,365 @@
+use crate::commands::file_operations::import_file;
+use crate::db::get_connection;
+use crate::services::folder_pipeline::collect_documents;
+use crate::services::gemini::mime_for;
+use crate::services::import_batches::{ImportBatches, ImportSource};
+use crate::services::notifier::{JobEvent, Notifier};
+use crate::services::workspace_lock::WorkspaceLock;
+use chrono::{DateTime, Utc};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with a [`WatchedFileImported`] for every file picked up from a watched folder.
+pub const WATCHED_FILE_IMPORTED_EVENT: &str = "watcher://file-imported";
+
+/// How long a file must stay the same size before it is imported, so scanners that write
+/// a PDF page by page are not read half-way.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolder {
+    pub id: i64,
+    pub path: String,
+    pub recursive: bool,
+    pub enabled: bool,
+    pub created_at: String,
+    /// Files changed before this were already picked up.
+    pub last_scanned_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFileImported {
+    pub folder_id: Option<i64>,
+    pub path: String,
+    pub file_id: String,
+    /// The document was already in the library.
+    pub duplicate: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolderScan {
+    pub folders: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub failed: usize,
+}
+
+/// The active file system watcher; replaced whenever the folder list changes.
+static WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+/// Paths reported by the watcher, handed to the import thread.
+static CHANGES: OnceLock<Mutex<Sender<PathBuf>>> = OnceLock::new();
+
+/// Hot folders: documents dropped into a watched folder are imported as soon as they are
+/// fully written. The `watched_folder_scan` schedule catches files that arrived while the
+/// app was closed.
+pub struct FolderWatcher;
+
+impl FolderWatcher {
+    /// Starts the import thread and watches the enabled folders. Called once at startup.
+    pub fn start<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        if CHANGES.get().is_none() {
+            let (sender, receiver) = mpsc::channel();
+            let app = app.clone();
+            thread::Builder::new()
+                .name("invox-folder-watcher".to_string())
+                .spawn(move || import_loop(app, receiver))
+                .map_err(|error| error.to_string())?;
+            let _ = CHANGES.set(Mutex::new(sender));
+        }
+        Self::reload()
+    }
+
+    /// Re-reads the folder list. Folders that no longer exist are skipped until they reappear.
+    pub fn reload() -> Result<(), String> {
+        let Some(changes) = CHANGES.get() else {
+            return Ok(());
+        };
+        let sender = changes.lock().map_err(|error| error.to_string())?.clone();
+        let conn = get_connection().map_err(|error| error.to_string())?;
+        let folders: Vec<WatchedFolder> = Self::list(&conn)?
+            .into_iter()
+            .filter(|folder| folder.enabled)
+            .collect();
+
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    let _ = sender.send(path);
+                }
+            }
+        })
+        .map_err(|error| error.to_string())?;
+        for folder in &folders {
+            let path = Path::new(&folder.path);
+            if !path.is_dir() {
+                continue;
+            }
+            let mode = if folder.recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            watcher.watch(path, mode).map_err(|error| error.to_string())?;
+        }
+
+        *WATCHER.lock().map_err(|error| error.to_string())? = Some(watcher);
+        Ok(())
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<WatchedFolder>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, path, recursive, enabled, created_at, last_scanned_at
+                 FROM watched_folders ORDER BY path",
+            )
+            .map_err(|error| error.to_string())?;
+        let folders = stmt
+            .query_map([], map_folder)
+            .map_err(|error| error.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(folders)
+    }
+
+    pub fn get(conn: &Connection, folder_id: i64) -> Result<WatchedFolder, String> {
+        conn.query_row(
+            "SELECT id, path, recursive, enabled, created_at, last_scanned_at
+             FROM watched_folders WHERE id = ?1",
+            params![folder_id],
+            map_folder,
+        )
+        .optional()
+        .map_err(|error| error.to_string())?
+        .ok_or_else(|| format!("Watched folder not found: {}", folder_id))
+    }
+
+    /// Watches `path` from now on; documents already in it are left alone.
+    pub fn add(conn: &Connection, path: &str, recursive: bool) -> Result<WatchedFolder, String> {
+        let folder = fs::canonicalize(path.trim()).map_err(|error| format!("{}: {}", path, error))?;
+        if !folder.is_dir() {
+            return Err(format!("{} is not a folder.", folder.display()));
+        }
+        let folder = folder.to_string_lossy().into_owned();
+        conn.execute(
+            "INSERT INTO watched_folders (path, recursive, last_scanned_at) VALUES (?1, ?2, ?3)",
+            params![folder, recursive, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| match error {
+            rusqlite::Error::SqliteFailure(failure, _)
+                if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                format!("{} is already watched.", folder)
+            }
+            other => other.to_string(),
+        })?;
+        Self::get(conn, conn.last_insert_rowid())
+    }
+
+    pub fn remove(conn: &Connection, folder_id: i64) -> Result<WatchedFolder, String> {
+        let folder = Self::get(conn, folder_id)?;
+        conn.execute("DELETE FROM watched_folders WHERE id = ?1", params![folder_id])
+            .map_err(|error| error.to_string())?;
+        Ok(folder)
+    }
+
+    pub fn set_enabled(conn: &Connection, folder_id: i64, enabled: bool) -> Result<WatchedFolder, String> {
+        Self::get(conn, folder_id)?;
+        conn.execute(
+            "UPDATE watched_folders SET enabled = ?2 WHERE id = ?1",
+            params![folder_id, enabled],
+        )
+        .map_err(|error| error.to_string())?;
+        Self::get(conn, folder_id)
+    }
+
+    /// Imports documents changed since each folder's last scan, for files that arrived while
+    /// the app was not running. Failures are reported through notifications, not the result.
+    pub fn scan<R: Runtime>(app: &AppHandle<R>, conn: &Connection) -> Result<WatchedFolderScan, String> {
+        let mut summary = WatchedFolderScan::default();
+        for folder in Self::list(conn)?.into_iter().filter(|folder| folder.enabled) {
+            let path = Path::new(&folder.path);
+            if !path.is_dir() {
+                continue;
+            }
+            let started_at = Utc::now();
+            let since = folder
+                .last_scanned_at
+                .as_deref()
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(SystemTime::from);
+            let (documents, _) = collect_documents(path, folder.recursive)?;
+            let mut batch_id = None;
+            for document in documents {
+                let modified = fs::metadata(&document)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                if matches!((since, modified), (Some(since), Some(modified)) if modified < since) {
+                    continue;
+                }
+                let batch_id = match batch_id {
+                    Some(batch_id) => batch_id,
+                    None => *batch_id.insert(ImportBatches::begin(
+                        conn,
+                        ImportSource::WatchedFolder,
+                        Some(&folder.path),
+                    )?),
+                };
+                match import(app, Some(folder.id), &document, batch_id) {
+                    Ok(true) => summary.duplicates += 1,
+                    Ok(false) => summary.imported += 1,
+                    Err(_) => summary.failed += 1,
+                }
+            }
+            if let Some(batch_id) = batch_id {
+                ImportBatches::finish(conn, batch_id, false)?;
+            }
+            conn.execute(
+                "UPDATE watched_folders SET last_scanned_at = ?2 WHERE id = ?1",
+                params![folder.id, started_at.to_rfc3339()],
+            )
+            .map_err(|error| error.to_string())?;
+            summary.folders += 1;
+        }
+        Ok(summary)
+    }
+}
+
+fn map_folder(row: &Row) -> rusqlite::Result<WatchedFolder> {
+    Ok(WatchedFolder {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        recursive: row.get(2)?,
+        enabled: row.get(3)?,
+        created_at: row.get(4)?,
+        last_scanned_at: row.get(5)?,
+    })
+}
+
+/// Waits for reported files to settle, then imports them. Files that change size are
+/// checked again; files that disappear (temporary scanner output) are dropped.
+fn import_loop<R: Runtime>(app: AppHandle<R>, changes: Receiver<PathBuf>) {
+    let mut pending: HashMap<PathBuf, (Instant, Option<u64>)> = HashMap::new();
+    loop {
+        match changes.recv_timeout(POLL_INTERVAL) {
+            Ok(path) => {
+                if mime_for(&path).is_some() {
+                    pending.insert(path, (Instant::now(), None));
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, (seen, _))| seen.elapsed() >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut ready = Vec::new();
+        for path in settled {
+            let Some(size) = fs::metadata(&path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+            else {
+                pending.remove(&path);
+                continue;
+            };
+            if pending.get(&path).map(|(_, last)| *last) != Some(Some(size)) {
+                pending.insert(path, (Instant::now(), Some(size)));
+                continue;
+            }
+            pending.remove(&path);
+            ready.push(path);
+        }
+        if ready.is_empty() {
+            continue;
+        }
+
+        let Ok(conn) = get_connection() else {
+            continue;
+        };
+        // Left for the next scan once the workspace is unlocked.
+        if WorkspaceLock::is_locked(&conn).unwrap_or(true) {
+            continue;
+        }
+        // Files that settle together are recorded as one import batch.
+        let Ok(batch_id) = ImportBatches::begin(&conn, ImportSource::WatchedFolder, None) else {
+            continue;
+        };
+        for path in ready {
+            let folder_id = folder_for(&conn, &path);
+            let _ = import(&app, folder_id, &path, batch_id);
+        }
+        let _ = ImportBatches::finish(&conn, batch_id, false);
+    }
+}
+
+/// Imports one document and tells the frontend. Returns whether it was a duplicate.
+fn import<R: Runtime>(
+    app: &AppHandle<R>,
+    folder_id: Option<i64>,
+    path: &Path,
+    batch_id: i64,
+) -> Result<bool, String> {
+    let display = path.to_string_lossy().into_owned();
+    let outcome = import_file(display.clone(), Some(batch_id), None).and_then(|outcome| {
+        outcome
+            .split_once(':')
+            .map(|(kind, id)| (kind == "DUPLICATE", id.to_string()))
+            .ok_or_else(|| "Unexpected import result.".to_string())
+    });
+    match outcome {
+        Ok((duplicate, file_id)) => {
+            let _ = app.emit(
+                WATCHED_FILE_IMPORTED_EVENT,
+                WatchedFileImported {
+                    folder_id,
+                    path: display,
+                    file_id,
+                    duplicate,
+                },
+            );
+            Ok(duplicate)
+        }
+        Err(error) => {
+            Notifier::notify(
+                app,
+                JobEvent::ImportFailed {
+                    file_name: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or(display),
+                    message: error.clone(),
+                },
+            );
+            Err(error)
+        }
+    }
+}
+
+/// The watched folder `path` was found in, preferring the deepest match.
+fn folder_for(conn: &Connection, path: &Path) -> Option<i64> {
+    FolderWatcher::list(conn)
+        .ok()?
+        .into_iter()
+        .filter(|folder| path.starts_with(&folder.path))
+        .max_by_key(|folder| folder.path.len())
+        .map(|folder| folder.id)
+}
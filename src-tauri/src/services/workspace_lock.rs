@@ -0,0 +1,73 @@
+use crate::services::{i18n::I18n, roles::Roles, settings::Settings};
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::Serialize;
+
+const LOCKED_KEY: &str = "workspace.locked";
+const LOCKED_AT_KEY: &str = "workspace.locked_at";
+const LOCKED_BY_KEY: &str = "workspace.locked_by";
+const REASON_KEY: &str = "workspace.lock_reason";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceLockStatus {
+    pub locked: bool,
+    pub locked_at: Option<String>,
+    /// Role that locked the workspace.
+    pub locked_by: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Read-only mode for the whole workspace. The flag lives in the database, so every
+/// machine sharing the data directory sees it; mutating commands check it with
+/// [`WorkspaceLock::require_unlocked`] while reads and exports keep working.
+pub struct WorkspaceLock;
+
+impl WorkspaceLock {
+    pub fn is_locked(conn: &Connection) -> Result<bool, String> {
+        Ok(Settings::get(conn, LOCKED_KEY)?.as_deref() == Some("1"))
+    }
+
+    pub fn status(conn: &Connection) -> Result<WorkspaceLockStatus, String> {
+        if !Self::is_locked(conn)? {
+            return Ok(WorkspaceLockStatus {
+                locked: false,
+                locked_at: None,
+                locked_by: None,
+                reason: None,
+            });
+        }
+        Ok(WorkspaceLockStatus {
+            locked: true,
+            locked_at: Settings::get(conn, LOCKED_AT_KEY)?,
+            locked_by: Settings::get(conn, LOCKED_BY_KEY)?,
+            reason: Settings::get(conn, REASON_KEY)?.filter(|reason| !reason.is_empty()),
+        })
+    }
+
+    pub fn lock(conn: &Connection, reason: Option<&str>) -> Result<(), String> {
+        Settings::set(conn, LOCKED_AT_KEY, &Utc::now().to_rfc3339())?;
+        Settings::set(conn, LOCKED_BY_KEY, Roles::current(conn)?.as_str())?;
+        Settings::set(conn, REASON_KEY, reason.map(str::trim).unwrap_or_default())?;
+        Settings::set(conn, LOCKED_KEY, "1")
+    }
+
+    pub fn unlock(conn: &Connection) -> Result<(), String> {
+        for key in [LOCKED_KEY, LOCKED_AT_KEY, LOCKED_BY_KEY, REASON_KEY] {
+            Settings::remove(conn, key)?;
+        }
+        Ok(())
+    }
+
+    /// Fails with a "workspace locked" error, including the lock reason when one was given.
+    pub fn require_unlocked(conn: &Connection) -> Result<(), String> {
+        let status = Self::status(conn)?;
+        if !status.locked {
+            return Ok(());
+        }
+        Err(match status.reason {
+            Some(reason) => I18n::format("workspace.locked_with_reason", &[("reason", reason)]),
+            None => I18n::text("workspace.locked").to_string(),
+        })
+    }
+}